@@ -0,0 +1,400 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Measures the core read and write paths in both endiannesses
+//! with `criterion`, so that changes can be compared statistically.
+//!
+//! Alongside them, `huffman_trees` compares the decoding throughput
+//! of jump table and compact Huffman trees,
+//! whose memory use is printed beforehand,
+//! and `frames` measures writing a FLAC-style mix of fields
+//! along with the benefit of buffering whole bytes within the writer.
+//!
+//! Run with `cargo bench --bench bitstream`,
+//! optionally followed by `-- <filter>` to run only some benchmarks,
+//! such as `-- write/` or `-- little-endian`.
+//!
+//! To compare a change against a baseline,
+//! save one before making the change with
+//! `cargo bench --bench bitstream -- --save-baseline before`
+//! and then measure against it with
+//! `cargo bench --bench bitstream -- --baseline before`.
+
+extern crate bitstream_io;
+use bitstream_io::huffman::{
+    compile_compact_read_tree, compile_read_tree, compile_write_tree, ReadHuffmanTree,
+};
+use bitstream_io::{
+    BigEndian, BitRead, BitReader, BitWrite, BitWriter, BufBitWriter, Endianness, HuffmanRead,
+    HuffmanWrite, LittleEndian,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+use std::io::{self, BufWriter, Cursor, Write};
+use std::mem;
+
+const VALUES: usize = 10_000;
+const FRAMES: usize = 100;
+const SAMPLES: usize = 256;
+const WIDTHS: [u32; 10] = [1, 3, 8, 12, 16, 24, 32, 47, 63, 64];
+
+/// Returns pseudo-random values which fit in the given number of bits
+fn values(bits: u32) -> Vec<u64> {
+    let mut state = 1u64;
+    (0..VALUES)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            if bits == 64 {
+                state
+            } else {
+                state >> (64 - bits)
+            }
+        })
+        .collect()
+}
+
+/// Returns geometrically distributed values, as for unary codes
+fn unary_values() -> Vec<u32> {
+    values(64)
+        .into_iter()
+        .map(|v| v.trailing_zeros().min(20))
+        .collect()
+}
+
+/// Builds codes for 64 symbols in groups of 4,
+/// where each group's codes are a bit longer than the last
+fn huffman_spec() -> Vec<(u32, Vec<u8>)> {
+    let mut spec = Vec::new();
+    for group in 0..16u8 {
+        for symbol in 0..4u8 {
+            let mut code = vec![1; usize::from(group)];
+            if group < 15 {
+                code.push(0);
+            }
+            code.push(symbol >> 1);
+            code.push(symbol & 1);
+            spec.push((u32::from(group * 4 + symbol), code));
+        }
+    }
+    spec
+}
+
+fn huffman_symbols() -> Vec<u32> {
+    values(6)
+        .into_iter()
+        .map(|v| (v as u32).pow(2) / 64)
+        .collect()
+}
+
+fn write_values<E: Endianness>(bits: u32, values: &[u64]) -> Vec<u8> {
+    let mut writer = BitWriter::<_, E>::new(Vec::with_capacity(values.len() * 8));
+    for value in values {
+        writer.write(bits, *value).unwrap();
+    }
+    writer.byte_align().unwrap();
+    writer.into_writer()
+}
+
+fn bench_write<E: Endianness>(c: &mut Criterion, endianness: &str) {
+    let mut group = c.benchmark_group("write");
+    for &bits in WIDTHS.iter() {
+        let values = values(bits);
+        group.throughput(Throughput::Elements(VALUES as u64));
+        group.bench_with_input(BenchmarkId::new(endianness, bits), &values, |b, values| {
+            b.iter(|| write_values::<E>(bits, black_box(values)))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("write_unary");
+    let values = unary_values();
+    group.throughput(Throughput::Elements(VALUES as u64));
+    group.bench_function(endianness, |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::<_, E>::new(Vec::new());
+            for value in black_box(&values) {
+                writer.write_unary1(*value).unwrap();
+            }
+            writer.into_writer()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("write_bytes");
+    let data = vec![0xA5; 4096];
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    for &offset in [0, 1, 4].iter() {
+        group.bench_with_input(BenchmarkId::new(endianness, offset), &data, |b, data| {
+            b.iter(|| {
+                let mut writer = BitWriter::<_, E>::new(Vec::with_capacity(data.len() + 1));
+                writer.write(offset, 0u8).unwrap();
+                writer.write_bytes(black_box(data)).unwrap();
+                writer.into_writer()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_read<E: Endianness>(c: &mut Criterion, endianness: &str) {
+    let mut group = c.benchmark_group("read");
+    for &bits in WIDTHS.iter() {
+        let data = write_values::<E>(bits, &values(bits));
+        group.throughput(Throughput::Elements(VALUES as u64));
+        group.bench_with_input(BenchmarkId::new(endianness, bits), &data, |b, data| {
+            b.iter(|| {
+                let mut reader = BitReader::<_, E>::new(Cursor::new(black_box(data)));
+                for _ in 0..VALUES {
+                    black_box(reader.read::<u64>(bits).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("read_unary");
+    let mut writer = BitWriter::<_, E>::new(Vec::new());
+    for value in unary_values() {
+        writer.write_unary1(value).unwrap();
+    }
+    writer.byte_align().unwrap();
+    let data = writer.into_writer();
+    group.throughput(Throughput::Elements(VALUES as u64));
+    group.bench_function(endianness, |b| {
+        b.iter(|| {
+            let mut reader = BitReader::<_, E>::new(Cursor::new(black_box(&data)));
+            for _ in 0..VALUES {
+                black_box(reader.read_unary1().unwrap());
+            }
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("read_bytes");
+    let data = vec![0xA5; 4097];
+    let mut buf = vec![0; 4096];
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    for &offset in [0, 1, 4].iter() {
+        group.bench_function(BenchmarkId::new(endianness, offset), |b| {
+            b.iter(|| {
+                let mut reader = BitReader::<_, E>::new(Cursor::new(black_box(&data)));
+                reader.skip(offset).unwrap();
+                reader.read_bytes(&mut buf).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_huffman<E: Endianness>(c: &mut Criterion, endianness: &str) {
+    let write_tree = compile_write_tree::<E, u32>(huffman_spec()).unwrap();
+    let read_tree = compile_read_tree::<E, u32>(huffman_spec()).unwrap();
+    let symbols = huffman_symbols();
+
+    let mut group = c.benchmark_group("huffman_encode");
+    group.throughput(Throughput::Elements(VALUES as u64));
+    group.bench_function(endianness, |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::<_, E>::new(Vec::new());
+            for symbol in black_box(&symbols) {
+                writer.write_huffman(&write_tree, *symbol).unwrap();
+            }
+            writer.into_writer()
+        })
+    });
+    group.finish();
+
+    let mut writer = BitWriter::<_, E>::new(Vec::new());
+    for symbol in symbols.iter() {
+        writer.write_huffman(&write_tree, *symbol).unwrap();
+    }
+    writer.byte_align().unwrap();
+    let data = writer.into_writer();
+
+    let mut group = c.benchmark_group("huffman_decode");
+    group.throughput(Throughput::Elements(VALUES as u64));
+    group.bench_function(endianness, |b| {
+        b.iter(|| {
+            let mut reader = BitReader::<_, E>::new(Cursor::new(black_box(&data)));
+            for _ in 0..VALUES {
+                black_box(reader.read_huffman(&read_tree).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Returns the bytes used by a jump table tree's entries
+fn jump_table_size<E: Endianness, T: Clone>(tree: &[ReadHuffmanTree<E, T>]) -> usize {
+    tree.iter()
+        .map(|entry| match entry {
+            ReadHuffmanTree::Continue(tree) => jump_table_size(tree),
+            _ => 0,
+        })
+        .sum::<usize>()
+        + mem::size_of_val(tree)
+}
+
+fn bench_huffman_trees(c: &mut Criterion) {
+    let write_tree = compile_write_tree::<BigEndian, u32>(huffman_spec()).unwrap();
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    for symbol in huffman_symbols() {
+        writer.write_huffman(&write_tree, symbol).unwrap();
+    }
+    writer.byte_align().unwrap();
+    let data = writer.into_writer();
+
+    let mut group = c.benchmark_group("huffman_trees");
+    group.throughput(Throughput::Elements(VALUES as u64));
+
+    let tree = compile_read_tree::<BigEndian, u32>(huffman_spec()).unwrap();
+    println!("jump table tree: {} bytes", jump_table_size(&tree));
+    group.bench_function("jump table", |b| {
+        b.iter(|| {
+            let mut reader = BitReader::endian(Cursor::new(black_box(&data)), BigEndian);
+            for _ in 0..VALUES {
+                black_box(reader.read_huffman(&tree).unwrap());
+            }
+        })
+    });
+
+    for &lookup_bits in [1, 4, 8, 12].iter() {
+        let tree =
+            compile_compact_read_tree::<BigEndian, u32>(huffman_spec(), lookup_bits).unwrap();
+        println!(
+            "compact tree, {:>2} bits: {} bytes",
+            lookup_bits,
+            tree.memory_size()
+        );
+        group.bench_with_input(
+            BenchmarkId::new("compact", lookup_bits),
+            &data,
+            |b, data| {
+                b.iter(|| {
+                    let mut reader = BitReader::endian(Cursor::new(black_box(data)), BigEndian);
+                    for _ in 0..VALUES {
+                        black_box(reader.read_compact_huffman(&tree).unwrap());
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Writes frames with a fixed header, one Rice-coded subframe
+/// and one verbatim subframe
+fn write_frames<W: BitWrite>(writer: &mut W, residuals: &[i32], samples: &[i16]) {
+    for frame in 0..FRAMES {
+        writer.write(14, 0b11111111111110u16).unwrap();
+        writer.write(1, 0u8).unwrap();
+        writer.write(1, 0u8).unwrap();
+        writer.write(4, 0b1100u8).unwrap();
+        writer.write(4, 0b1001u8).unwrap();
+        writer.write(4, 0b0001u8).unwrap();
+        writer.write(3, 0b100u8).unwrap();
+        writer.write(1, 0u8).unwrap();
+        writer.write(8, (frame & 0xFF) as u8).unwrap();
+        writer.write(8, 0xA5u8).unwrap();
+
+        // Rice-coded residuals with a parameter of 4
+        writer.write(4, 4u8).unwrap();
+        for residual in residuals {
+            let folded = ((residual << 1) ^ (residual >> 31)) as u32;
+            writer.write_unary1(folded >> 4).unwrap();
+            writer.write(4, folded & 0b1111).unwrap();
+        }
+
+        // verbatim samples of various widths
+        for (i, sample) in samples.iter().enumerate() {
+            match i % 3 {
+                0 => writer.write_signed(16, *sample).unwrap(),
+                1 => writer.write_signed(24, i32::from(*sample) << 4).unwrap(),
+                _ => writer.write(20, u32::from(*sample as u16) << 4).unwrap(),
+            }
+        }
+        writer.byte_align().unwrap();
+        writer.write(16, 0xBEEFu16).unwrap();
+    }
+}
+
+/// A stream which only counts the bytes written to it
+#[derive(Debug)]
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    #[inline(never)]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn bench_frames(c: &mut Criterion) {
+    let residuals: Vec<i32> = values(6).into_iter().map(|v| v as i32 - 32).collect();
+    let samples: Vec<i16> = values(16).into_iter().map(|v| v as i16).collect();
+    let (residuals, samples) = (&residuals[0..SAMPLES], &samples[0..SAMPLES]);
+
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    write_frames(&mut writer, residuals, samples);
+    let bytes = writer.into_writer().len();
+
+    let mut group = c.benchmark_group("frames");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.bench_function("big-endian", |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+            write_frames(&mut writer, black_box(residuals), black_box(samples));
+            writer.into_writer()
+        })
+    });
+    group.bench_function("little-endian", |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+            write_frames(&mut writer, black_box(residuals), black_box(samples));
+            writer.into_writer()
+        })
+    });
+
+    // buffering in front of a stream which isn't a Vec
+    group.bench_function("BufWriter", |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::endian(BufWriter::new(ByteCounter(0)), BigEndian);
+            write_frames(&mut writer, black_box(residuals), black_box(samples));
+            writer.into_writer().into_inner().unwrap().0
+        })
+    });
+    group.bench_function("BufBitWriter", |b| {
+        b.iter(|| {
+            let mut writer = BufBitWriter::endian(ByteCounter(0), BigEndian);
+            write_frames(&mut writer, black_box(residuals), black_box(samples));
+            writer.into_writer().unwrap().0
+        })
+    });
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    bench_write::<BigEndian>(c, "big-endian");
+    bench_write::<LittleEndian>(c, "little-endian");
+    bench_read::<BigEndian>(c, "big-endian");
+    bench_read::<LittleEndian>(c, "little-endian");
+    bench_huffman::<BigEndian>(c, "big-endian");
+    bench_huffman::<LittleEndian>(c, "little-endian");
+    bench_huffman_trees(c);
+    bench_frames(c);
+}
+
+criterion_group!(bitstream, benches);
+criterion_main!(bitstream);
@@ -0,0 +1,53 @@
+/*
+ * C declarations for the bitstream-io `ffi` feature.
+ *
+ * Licensed under the Apache License, Version 2.0 or the MIT license,
+ * at your option.
+ */
+
+#ifndef BITSTREAM_IO_H
+#define BITSTREAM_IO_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BITSTREAM_BIG_ENDIAN 0
+#define BITSTREAM_LITTLE_ENDIAN 1
+
+#define BITSTREAM_OK 0
+#define BITSTREAM_ERR_NULL -1
+#define BITSTREAM_ERR_INVALID_INPUT -2
+#define BITSTREAM_ERR_EOF -3
+#define BITSTREAM_ERR_OTHER -4
+
+typedef struct BitstreamWriter BitstreamWriter;
+typedef struct BitstreamReader BitstreamReader;
+
+BitstreamWriter *bitstream_writer_new(int endian);
+void bitstream_writer_free(BitstreamWriter *writer);
+int bitstream_writer_write(BitstreamWriter *writer, uint32_t bits, uint64_t value);
+int bitstream_writer_write_signed(BitstreamWriter *writer, uint32_t bits, int64_t value);
+int bitstream_writer_write_bytes(BitstreamWriter *writer, const uint8_t *data, size_t len);
+int bitstream_writer_byte_align(BitstreamWriter *writer);
+int bitstream_writer_byte_aligned(const BitstreamWriter *writer);
+const uint8_t *bitstream_writer_data(const BitstreamWriter *writer, size_t *len);
+int bitstream_writer_clear(BitstreamWriter *writer);
+
+BitstreamReader *bitstream_reader_new(int endian, const uint8_t *data, size_t len);
+void bitstream_reader_free(BitstreamReader *reader);
+int bitstream_reader_read(BitstreamReader *reader, uint32_t bits, uint64_t *value);
+int bitstream_reader_read_signed(BitstreamReader *reader, uint32_t bits, int64_t *value);
+int bitstream_reader_read_bytes(BitstreamReader *reader, uint8_t *data, size_t len);
+int bitstream_reader_skip(BitstreamReader *reader, uint64_t bits);
+int bitstream_reader_byte_align(BitstreamReader *reader);
+int bitstream_reader_byte_aligned(const BitstreamReader *reader);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BITSTREAM_IO_H */
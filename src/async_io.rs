@@ -0,0 +1,227 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Byte readers and writers over `tokio`'s asynchronous streams.
+//!
+//! `AsyncByteReader` and `AsyncByteWriter` offer the same
+//! whole-value reads and writes in a given endianness as
+//! `ByteReader` and `ByteWriter`, but over `AsyncRead` and `AsyncWrite`
+//! streams such as sockets, so fixed-width protocol headers
+//! may be parsed as they arrive without blocking a task.
+//!
+//! Each value is read or written as a single buffered
+//! `read_exact` or `write_all`, so a value cancelled partway through
+//! may have been partially consumed from or sent to the stream.
+//!
+//! ## Example
+//!
+//! Reading and writing a DNS message header.
+//!
+//! ```
+//! use bitstream_io::BigEndian;
+//! use bitstream_io::async_io::{AsyncByteReader, AsyncByteWriter};
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let mut writer = AsyncByteWriter::endian(Vec::new(), BigEndian);
+//! writer.write(0xABCDu16).await.unwrap(); // ID
+//! writer.write(0x0100u16).await.unwrap(); // flags
+//! writer.write(1u16).await.unwrap();      // question count
+//! writer.write_bytes(&[0; 6]).await.unwrap();
+//! assert_eq!(writer.position(), 12);
+//! let data = writer.into_writer();
+//!
+//! let mut reader = AsyncByteReader::endian(data.as_slice(), BigEndian);
+//! assert_eq!(reader.read::<u16>().await.unwrap(), 0xABCD);
+//! assert_eq!(reader.read::<u16>().await.unwrap(), 0x0100);
+//! assert_eq!(reader.read::<u16>().await.unwrap(), 1);
+//! # });
+//! ```
+
+#![warn(missing_docs)]
+
+use super::{Endianness, Numeric};
+use std::io;
+use std::marker::PhantomData;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// For reading aligned bytes from an asynchronous stream of bytes
+/// in a given endianness.
+///
+/// This only reads aligned values and maintains no internal state
+/// beyond a count of the bytes read.
+#[derive(Clone, Debug)]
+pub struct AsyncByteReader<R: AsyncRead + Unpin, E: Endianness> {
+    reader: R,
+    position: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<R: AsyncRead + Unpin, E: Endianness> AsyncByteReader<R, E> {
+    /// Wraps an AsyncByteReader around something that implements `AsyncRead`
+    pub fn new(reader: R) -> AsyncByteReader<R, E> {
+        AsyncByteReader {
+            reader,
+            position: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps an AsyncByteReader around something that implements `AsyncRead`
+    /// with the given endianness.
+    pub fn endian(reader: R, _endian: E) -> AsyncByteReader<R, E> {
+        AsyncByteReader::new(reader)
+    }
+
+    /// Reads whole numeric value from stream
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use bitstream_io::LittleEndian;
+    /// use bitstream_io::async_io::AsyncByteReader;
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let data = [0b00000000, 0b11111111];
+    /// let mut reader = AsyncByteReader::endian(&data[..], LittleEndian);
+    /// assert_eq!(reader.read::<u16>().await.unwrap(), 0b1111111100000000);
+    /// assert!(reader.read::<u8>().await.is_err());
+    /// # });
+    /// ```
+    pub async fn read<N: Numeric>(&mut self) -> io::Result<N> {
+        let mut buffer = N::buffer();
+        self.reader.read_exact(buffer.as_mut()).await?;
+        self.position += buffer.as_ref().len() as u64;
+        E::read_numeric(buffer.as_ref())
+    }
+
+    /// Completely fills the given buffer with whole bytes.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub async fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.reader.read_exact(buf).await?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Returns the number of bytes read so far.
+    ///
+    /// Bytes read directly from the internal reader aren't counted.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Provides mutable reference to internal reader.
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of `AsyncByteReader`.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+/// For writing aligned bytes to an asynchronous stream of bytes
+/// in a given endianness.
+///
+/// This only writes aligned values and maintains no internal state
+/// beyond a count of the bytes written.
+#[derive(Clone, Debug)]
+pub struct AsyncByteWriter<W: AsyncWrite + Unpin, E: Endianness> {
+    writer: W,
+    position: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<W: AsyncWrite + Unpin, E: Endianness> AsyncByteWriter<W, E> {
+    /// Wraps an AsyncByteWriter around something that implements `AsyncWrite`
+    pub fn new(writer: W) -> AsyncByteWriter<W, E> {
+        AsyncByteWriter {
+            writer,
+            position: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps an AsyncByteWriter around something that implements `AsyncWrite`
+    /// with the given endianness.
+    pub fn endian(writer: W, _endian: E) -> AsyncByteWriter<W, E> {
+        AsyncByteWriter::new(writer)
+    }
+
+    /// Writes whole numeric value to stream
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use bitstream_io::LittleEndian;
+    /// use bitstream_io::async_io::AsyncByteWriter;
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut writer = AsyncByteWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write(0b0000000011111111u16).await.unwrap();
+    /// writer.write(-2i8).await.unwrap();
+    /// assert_eq!(writer.into_writer(), [0b11111111, 0b00000000, 0xFE]);
+    /// # });
+    /// ```
+    pub async fn write<N: Numeric>(&mut self, value: N) -> io::Result<()> {
+        let mut buffer = N::buffer();
+        E::write_numeric(buffer.as_mut(), value)?;
+        self.write_bytes(buffer.as_ref()).await
+    }
+
+    /// Writes the entirety of a byte buffer to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub async fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer.write_all(buf).await?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Flushes the internal writer,
+    /// so that buffered bytes reach their destination.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush().await
+    }
+
+    /// Returns the number of bytes written so far.
+    ///
+    /// Bytes written directly to the internal writer aren't counted.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Provides mutable reference to internal writer.
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of `AsyncByteWriter`.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
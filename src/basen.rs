@@ -0,0 +1,413 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adapters between byte streams and base64, base32 or hex text.
+//!
+//! Some protocols embed bit-packed fields inside textual containers,
+//! such as the base64 parameter sets in SDP or binary blobs in JSON.
+//! `EncodingWriter` turns each byte written to it into text
+//! in the chosen `Encoding`, and `DecodingReader` turns text
+//! back into bytes, so a `BitWriter` or `BitReader`
+//! wrapped around them works directly with the text.
+//!
+//! Text may be written to an `io::Write`, or to a `fmt::Write`
+//! such as a `String` by way of `FmtWriter`.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::basen::{DecodingReader, Encoding, EncodingWriter, FmtWriter};
+//!
+//! let mut text = String::new();
+//! let mut writer = BitWriter::endian(
+//!     EncodingWriter::new(FmtWriter::new(&mut text), Encoding::Base64),
+//!     BigEndian,
+//! );
+//! writer.write(3, 0b101u8).unwrap();
+//! writer.write(13, 0x1234u16).unwrap();
+//! writer.write(8, 0xFFu8).unwrap();
+//! writer.write(4, 0b1001u8).unwrap();
+//! writer.byte_align().unwrap();
+//! // emit the final partial symbol and any padding
+//! writer.into_writer().finish().unwrap();
+//! assert_eq!(text, "sjT/kA==");
+//!
+//! let mut reader = BitReader::endian(
+//!     DecodingReader::new(text.as_bytes(), Encoding::Base64),
+//!     BigEndian,
+//! );
+//! assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+//! assert_eq!(reader.read::<u16>(13).unwrap(), 0x1234);
+//! assert_eq!(reader.read::<u8>(8).unwrap(), 0xFF);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1001);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::io;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// A text encoding of binary data, as defined by RFC 4648
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Standard base64, padded with `=` to a multiple of 4 characters
+    Base64,
+    /// URL and filename safe base64, without padding
+    Base64Url,
+    /// Base32, padded with `=` to a multiple of 8 characters
+    Base32,
+    /// Lowercase hexadecimal, without padding
+    Hex,
+}
+
+impl Encoding {
+    /// Returns the number of bits encoded by each character
+    #[inline]
+    pub fn symbol_bits(self) -> u32 {
+        match self {
+            Encoding::Base64 | Encoding::Base64Url => 6,
+            Encoding::Base32 => 5,
+            Encoding::Hex => 4,
+        }
+    }
+
+    /// Returns the number of characters the text is padded to
+    /// a multiple of, if it is padded
+    #[inline]
+    pub fn padding_block(self) -> Option<u64> {
+        match self {
+            Encoding::Base64 => Some(4),
+            Encoding::Base32 => Some(8),
+            Encoding::Base64Url | Encoding::Hex => None,
+        }
+    }
+
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            Encoding::Base64 => BASE64,
+            Encoding::Base64Url => BASE64_URL,
+            Encoding::Base32 => BASE32,
+            Encoding::Hex => HEX,
+        }
+    }
+
+    /// Returns the symbol of the given character, if any.
+    /// Base32 and hex characters may be of either case.
+    fn decode(self, c: u8) -> Option<u8> {
+        let c = match self {
+            Encoding::Base32 => c.to_ascii_uppercase(),
+            Encoding::Hex => c.to_ascii_lowercase(),
+            Encoding::Base64 | Encoding::Base64Url => c,
+        };
+        self.alphabet()
+            .iter()
+            .position(|a| *a == c)
+            .map(|symbol| symbol as u8)
+    }
+}
+
+/// An `io::Write` which writes each byte written to it
+/// as text in a given encoding.
+///
+/// Characters are written as soon as enough bits
+/// are available for them.
+/// Because a byte may end partway through a character,
+/// `finish` must be called once all bytes are written
+/// to write the final character and any padding.
+#[derive(Clone, Debug)]
+pub struct EncodingWriter<W: io::Write> {
+    writer: W,
+    encoding: Encoding,
+    bits: u32,
+    count: u32,
+    written: u64,
+}
+
+impl<W: io::Write> EncodingWriter<W> {
+    /// Wraps an EncodingWriter around something that implements `io::Write`
+    #[inline]
+    pub fn new(writer: W, encoding: Encoding) -> Self {
+        EncodingWriter {
+            writer,
+            encoding,
+            bits: 0,
+            count: 0,
+            written: 0,
+        }
+    }
+
+    /// Returns the encoding being written
+    #[inline]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the number of characters written so far
+    #[inline]
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Provides mutable reference to internal writer
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Writes the final partial character, padded with 0 bits,
+    /// along with any padding characters the encoding requires,
+    /// and returns the internal writer
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::basen::{Encoding, EncodingWriter};
+    /// let mut writer = EncodingWriter::new(Vec::new(), Encoding::Base32);
+    /// writer.write_all(b"f").unwrap();
+    /// assert_eq!(writer.written(), 1);
+    /// assert_eq!(writer.finish().unwrap(), b"MY======");
+    ///
+    /// let mut writer = EncodingWriter::new(Vec::new(), Encoding::Hex);
+    /// writer.write_all(&[0x01, 0xAB]).unwrap();
+    /// assert_eq!(writer.finish().unwrap(), b"01ab");
+    /// ```
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut text = Vec::new();
+        let symbol_bits = self.encoding.symbol_bits();
+        if self.count > 0 {
+            let symbol = (self.bits << (symbol_bits - self.count)) as usize;
+            text.push(self.encoding.alphabet()[symbol]);
+            self.written += 1;
+            self.count = 0;
+        }
+        if let Some(block) = self.encoding.padding_block() {
+            let padding = (block - self.written % block) % block;
+            text.resize(text.len() + padding as usize, b'=');
+            self.written += padding;
+        }
+        self.writer.write_all(&text)?;
+        Ok(self.writer)
+    }
+
+    /// Unwraps internal writer and disposes of EncodingWriter.
+    /// Any bits of a partial character are discarded.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: io::Write> io::Write for EncodingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let symbol_bits = self.encoding.symbol_bits();
+        let alphabet = self.encoding.alphabet();
+        let mut text = Vec::with_capacity(buf.len() * 2);
+        // the partial character is only updated once the text is written,
+        // so a failed write may be retried with the same bytes
+        let mut bits = self.bits;
+        let mut count = self.count;
+        for byte in buf.iter() {
+            bits = (bits << 8) | u32::from(*byte);
+            count += 8;
+            while count >= symbol_bits {
+                count -= symbol_bits;
+                text.push(alphabet[(bits >> count) as usize]);
+                bits &= (1 << count) - 1;
+            }
+        }
+        self.writer.write_all(&text)?;
+        self.bits = bits;
+        self.count = count;
+        self.written += text.len() as u64;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// An `io::Read` which reads text in a given encoding
+/// and returns the bytes it encodes.
+///
+/// ASCII whitespace between characters is skipped.
+/// Text ends at the end of the underlying stream
+/// or at the first padding character,
+/// after which nothing more is read from the underlying stream.
+/// Any bits left over after the final whole byte are discarded.
+///
+/// Text is read from the underlying stream a byte at a time,
+/// so unbuffered streams should be wrapped in a `BufReader`.
+#[derive(Clone, Debug)]
+pub struct DecodingReader<R: io::Read> {
+    reader: R,
+    encoding: Encoding,
+    bits: u32,
+    count: u32,
+    finished: bool,
+    invalid: bool,
+}
+
+impl<R: io::Read> DecodingReader<R> {
+    /// Wraps a DecodingReader around something that implements `io::Read`
+    #[inline]
+    pub fn new(reader: R, encoding: Encoding) -> Self {
+        DecodingReader {
+            reader,
+            encoding,
+            bits: 0,
+            count: 0,
+            finished: false,
+            invalid: false,
+        }
+    }
+
+    /// Returns the encoding being read
+    #[inline]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Provides mutable reference to internal reader
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of DecodingReader.
+    /// Any bits decoded but not yet returned are discarded.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Returns the next character's symbol,
+    /// or `None` at the end of the text
+    ///
+    /// Once an invalid character is found,
+    /// every later call returns an error.
+    fn next_symbol(&mut self) -> io::Result<Option<u8>> {
+        let mut c = [0];
+        while !self.invalid {
+            if self.reader.read(&mut c)? == 0 {
+                return Ok(None);
+            }
+            match c[0] {
+                b'=' => return Ok(None),
+                c if c.is_ascii_whitespace() => continue,
+                c => match self.encoding.decode(c) {
+                    Some(symbol) => return Ok(Some(symbol)),
+                    None => self.invalid = true,
+                },
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid character for encoding",
+        ))
+    }
+}
+
+impl<R: io::Read> io::Read for DecodingReader<R> {
+    /// # Example
+    /// ```
+    /// use std::io::Read;
+    /// use bitstream_io::basen::{DecodingReader, Encoding};
+    /// let mut decoded = Vec::new();
+    /// DecodingReader::new(&b"aGVs\nbG8="[..], Encoding::Base64)
+    ///     .read_to_end(&mut decoded)
+    ///     .unwrap();
+    /// assert_eq!(decoded, b"hello");
+    ///
+    /// let mut reader = DecodingReader::new(&b"0g"[..], Encoding::Hex);
+    /// assert!(reader.read_to_end(&mut decoded).is_err());
+    /// ```
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let symbol_bits = self.encoding.symbol_bits();
+        let mut read = 0;
+        while read < buf.len() {
+            if self.count >= 8 {
+                self.count -= 8;
+                buf[read] = (self.bits >> self.count) as u8;
+                self.bits &= (1 << self.count) - 1;
+                read += 1;
+            } else if self.finished {
+                break;
+            } else {
+                match self.next_symbol() {
+                    Ok(Some(symbol)) => {
+                        self.bits = (self.bits << symbol_bits) | u32::from(symbol);
+                        self.count += symbol_bits;
+                    }
+                    Ok(None) => self.finished = true,
+                    Err(err) if read == 0 => return Err(err),
+                    // the error is reported by the next call
+                    Err(_) => break,
+                }
+            }
+        }
+        Ok(read)
+    }
+}
+
+/// An `io::Write` which writes UTF-8 text to a `fmt::Write`,
+/// such as a `String`
+///
+/// Each buffer written must be valid UTF-8 on its own,
+/// which is always the case for text from an `EncodingWriter`.
+#[derive(Clone, Debug)]
+pub struct FmtWriter<W: fmt::Write> {
+    writer: W,
+}
+
+impl<W: fmt::Write> FmtWriter<W> {
+    /// Wraps a FmtWriter around something that implements `fmt::Write`
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        FmtWriter { writer }
+    }
+
+    /// Provides mutable reference to internal writer
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of FmtWriter
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: fmt::Write> io::Write for FmtWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = std::str::from_utf8(buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 text"))?;
+        self.writer
+            .write_str(text)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "formatter error"))?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversion of whole buffers between bit orders.
+//!
+//! A stream written most significant bit first, as by a
+//! `BigEndian` writer, holds the same sequence of bits
+//! as a stream written least significant bit first, as by
+//! a `LittleEndian` writer, once the bits of each byte are reversed.
+//! Remuxing between MSB-first and LSB-first formats
+//! then only requires reversing each byte of large buffers,
+//! which these functions do a machine word at a time.
+//!
+//! With the `simd` feature, 16 bytes are reversed at a time
+//! using SSSE3 on x86-64 processors which support it, detected at runtime,
+//! and NEON on AArch64.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite, LittleEndian};
+//! use bitstream_io::bitorder::convert_bit_order;
+//!
+//! let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+//! writer.write_bit(true).unwrap();
+//! writer.write_bit(false).unwrap();
+//! writer.write_bit(true).unwrap();
+//! writer.write_bit(true).unwrap();
+//! writer.byte_align().unwrap();
+//! let msb_first = writer.into_writer();
+//! assert_eq!(msb_first, [0b1011_0000]);
+//!
+//! let lsb_first = convert_bit_order(&msb_first);
+//! assert_eq!(lsb_first, [0b0000_1101]);
+//! let mut reader = BitReader::endian(lsb_first.as_slice(), LittleEndian);
+//! assert_eq!(reader.read_bit().unwrap(), true);
+//! assert_eq!(reader.read_bit().unwrap(), false);
+//! assert_eq!(reader.read_bit().unwrap(), true);
+//! assert_eq!(reader.read_bit().unwrap(), true);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::convert::TryInto;
+
+/// Returns a copy of the given bytes with the bits of each byte reversed,
+/// converting them between MSB-first and LSB-first bit order
+///
+/// # Example
+/// ```
+/// use bitstream_io::bitorder::convert_bit_order;
+/// assert_eq!(
+///     convert_bit_order(&[0b1000_0000, 0b1100_1010, 0b0000_1111]),
+///     [0b0000_0001, 0b0101_0011, 0b1111_0000],
+/// );
+/// ```
+pub fn convert_bit_order(data: &[u8]) -> Vec<u8> {
+    let mut converted = data.to_vec();
+    convert_bit_order_in_place(&mut converted);
+    converted
+}
+
+/// Reverses the bits of each of the given bytes in place,
+/// converting them between MSB-first and LSB-first bit order
+///
+/// # Example
+/// ```
+/// use bitstream_io::bitorder::convert_bit_order_in_place;
+/// let mut data = [0b1000_0000, 0b1100_1010, 0b0000_1111];
+/// convert_bit_order_in_place(&mut data);
+/// assert_eq!(data, [0b0000_0001, 0b0101_0011, 0b1111_0000]);
+/// ```
+pub fn convert_bit_order_in_place(data: &mut [u8]) {
+    #[cfg(feature = "simd")]
+    let data = simd::reverse_bytes(data);
+
+    let mut words = data.chunks_exact_mut(8);
+    for word in words.by_ref() {
+        let reversed = reverse_word_bytes(u64::from_ne_bytes(word.as_ref().try_into().unwrap()));
+        word.copy_from_slice(&reversed.to_ne_bytes());
+    }
+    for byte in words.into_remainder() {
+        *byte = byte.reverse_bits();
+    }
+}
+
+/// Reverses the bits within each byte of a word,
+/// leaving the bytes themselves in place
+#[inline]
+fn reverse_word_bytes(word: u64) -> u64 {
+    let word = ((word >> 1) & 0x5555_5555_5555_5555) | ((word & 0x5555_5555_5555_5555) << 1);
+    let word = ((word >> 2) & 0x3333_3333_3333_3333) | ((word & 0x3333_3333_3333_3333) << 2);
+    ((word >> 4) & 0x0F0F_0F0F_0F0F_0F0F) | ((word & 0x0F0F_0F0F_0F0F_0F0F) << 4)
+}
+
+#[cfg(feature = "simd")]
+#[allow(unsafe_code)]
+mod simd {
+    /// Reverses the bits of each byte in whole 16-byte blocks,
+    /// if the processor supports it,
+    /// and returns the bytes which remain to be reversed
+    #[cfg(target_arch = "x86_64")]
+    pub fn reverse_bytes(data: &mut [u8]) -> &mut [u8] {
+        if is_x86_feature_detected!("ssse3") {
+            // SAFETY: the processor supports SSSE3
+            unsafe { reverse_bytes_ssse3(data) }
+        } else {
+            data
+        }
+    }
+
+    /// Each nibble's reversal, in the high nibble of a byte
+    #[cfg(target_arch = "x86_64")]
+    const NIBBLES_HIGH: [u8; 16] = [
+        0x00, 0x80, 0x40, 0xC0, 0x20, 0xA0, 0x60, 0xE0, 0x10, 0x90, 0x50, 0xD0, 0x30, 0xB0, 0x70,
+        0xF0,
+    ];
+
+    /// Each nibble's reversal, in the low nibble of a byte
+    #[cfg(target_arch = "x86_64")]
+    const NIBBLES_LOW: [u8; 16] = [
+        0x0, 0x8, 0x4, 0xC, 0x2, 0xA, 0x6, 0xE, 0x1, 0x9, 0x5, 0xD, 0x3, 0xB, 0x7, 0xF,
+    ];
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "ssse3")]
+    unsafe fn reverse_bytes_ssse3(data: &mut [u8]) -> &mut [u8] {
+        use std::arch::x86_64::*;
+
+        let high = _mm_loadu_si128(NIBBLES_HIGH.as_ptr() as *const __m128i);
+        let low = _mm_loadu_si128(NIBBLES_LOW.as_ptr() as *const __m128i);
+        let mask = _mm_set1_epi8(0x0F);
+
+        let mut blocks = data.chunks_exact_mut(16);
+        for block in blocks.by_ref() {
+            // SAFETY: the block is 16 bytes, and unaligned loads and stores are used
+            let bytes = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+            let low_nibbles = _mm_and_si128(bytes, mask);
+            let high_nibbles = _mm_and_si128(_mm_srli_epi16(bytes, 4), mask);
+            let reversed = _mm_or_si128(
+                _mm_shuffle_epi8(high, low_nibbles),
+                _mm_shuffle_epi8(low, high_nibbles),
+            );
+            _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, reversed);
+        }
+        blocks.into_remainder()
+    }
+
+    /// Reverses the bits of each byte in whole 16-byte blocks
+    /// and returns the bytes which remain to be reversed
+    #[cfg(target_arch = "aarch64")]
+    pub fn reverse_bytes(data: &mut [u8]) -> &mut [u8] {
+        use std::arch::aarch64::*;
+
+        let mut blocks = data.chunks_exact_mut(16);
+        for block in blocks.by_ref() {
+            // SAFETY: NEON is always available on AArch64,
+            // and the block is 16 bytes
+            unsafe {
+                let bytes = vld1q_u8(block.as_ptr());
+                vst1q_u8(block.as_mut_ptr(), vrbitq_u8(bytes));
+            }
+        }
+        blocks.into_remainder()
+    }
+
+    /// Returns all of the bytes to be reversed without SIMD
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    #[inline]
+    pub fn reverse_bytes(data: &mut [u8]) -> &mut [u8] {
+        data
+    }
+}
@@ -0,0 +1,501 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Traits and implementations for holding bits in memory.
+//!
+//! ## Example
+//!
+//! Extracting a sub-stream of bits which begins in the middle
+//! of a byte and handing it off to an independent parser.
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitRead};
+//! use bitstream_io::buffer::BitBuffer;
+//!
+//! let data = [0b1011_0111, 0b0101_0101];
+//! let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+//! assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+//! let mut payload: BitBuffer<BigEndian> = reader.read_to_bitbuffer(10).unwrap();
+//! assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+//!
+//! assert_eq!(payload.read::<u8>(5).unwrap(), 0b10111);
+//! assert_eq!(payload.read::<u8>(5).unwrap(), 0b01010);
+//! assert!(payload.read_bit().is_err());
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+use super::{BitQueue, BitRead, BitWrite, Endianness, Numeric, PhantomData, SignedNumeric};
+
+/// An owned, in-memory sequence of bits
+/// which may be of any length, not just whole bytes.
+///
+/// Bits are appended to the end of the buffer with `BitWrite`,
+/// packed into bytes in the given endianness,
+/// and read back from the start of the buffer with `BitRead`.
+/// Because the buffer tracks its length in bits,
+/// no padding or final byte bookkeeping is needed
+/// when writing values which aren't a whole number of bytes.
+///
+/// Both traits have `byte_aligned` and `byte_align` methods,
+/// which apply to the read position for `BitRead`
+/// and to the end of the buffer for `BitWrite`.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitRead, BitWrite};
+/// use bitstream_io::buffer::BitBuffer;
+/// let mut buffer: BitBuffer<BigEndian> = BitBuffer::new();
+/// buffer.write(3, 0b101).unwrap();
+/// buffer.write_signed(7, -5).unwrap();
+/// buffer.write_bit(true).unwrap();
+/// assert_eq!(buffer.len_bits(), 11);
+/// assert_eq!(buffer.read::<u8>(3).unwrap(), 0b101);
+/// assert_eq!(buffer.read_signed::<i8>(7).unwrap(), -5);
+/// assert!(buffer.read_bit().unwrap());
+/// assert!(buffer.read_bit().is_err());
+/// ```
+pub struct BitBuffer<E: Endianness> {
+    bytes: Vec<u8>,
+    len: u64,
+    position: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<E: Endianness> BitBuffer<E> {
+    /// Returns a new, empty buffer
+    #[inline]
+    pub fn new() -> BitBuffer<E> {
+        BitBuffer {
+            bytes: Vec::new(),
+            len: 0,
+            position: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns a new, empty buffer with space for the given number of bytes
+    #[inline]
+    pub fn with_capacity(bytes: usize) -> BitBuffer<E> {
+        BitBuffer {
+            bytes: Vec::with_capacity(bytes),
+            len: 0,
+            position: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Builds a buffer from whole bytes and the number of bits
+    /// used in the final byte, where 0 indicates the whole byte is used.
+    /// Any unused bits of the final byte are discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trailing_bits` is 8 or more,
+    /// or is nonzero while `bytes` is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitRead};
+    /// use bitstream_io::buffer::BitBuffer;
+    /// let mut buffer: BitBuffer<BigEndian> = BitBuffer::from_parts(vec![0xAB, 0b1011_1111], 3);
+    /// assert_eq!(buffer.len_bits(), 11);
+    /// assert_eq!(buffer.read::<u16>(11).unwrap(), 0b10101011_101);
+    /// assert_eq!(buffer.into_parts(), (vec![0xAB, 0b1010_0000], 3));
+    /// ```
+    pub fn from_parts(mut bytes: Vec<u8>, trailing_bits: u32) -> BitBuffer<E> {
+        assert!(trailing_bits < 8);
+        let mut len = bytes.len() as u64 * 8;
+        if trailing_bits > 0 {
+            let last = bytes.last_mut().expect("trailing bits without bytes");
+            let mut queue = BitQueue::<E, u8>::from_value(*last, 8);
+            *last = pad(BitQueue::<E, u8>::from_value(
+                queue.pop(trailing_bits),
+                trailing_bits,
+            ));
+            len -= u64::from(8 - trailing_bits);
+        }
+        BitBuffer {
+            bytes,
+            len,
+            position: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Consumes the buffer and returns its bytes along with
+    /// the number of bits used in the final byte,
+    /// where 0 indicates the whole byte is used.
+    /// Unused bits in the final byte are 0.
+    /// All bits are returned, whether they have been read or not.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitWrite};
+    /// use bitstream_io::buffer::BitBuffer;
+    /// let mut buffer: BitBuffer<LittleEndian> = BitBuffer::new();
+    /// buffer.write(4, 0b1101).unwrap();
+    /// buffer.write(8, 0xFF).unwrap();
+    /// assert_eq!(buffer.into_parts(), (vec![0b1111_1101, 0b0000_1111], 4));
+    /// ```
+    #[inline]
+    pub fn into_parts(self) -> (Vec<u8>, u32) {
+        (self.bytes, bit_offset(self.len))
+    }
+
+    /// Returns the total number of bits in the buffer,
+    /// whether they have been read or not.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitRead, BitWrite};
+    /// use bitstream_io::buffer::BitBuffer;
+    /// let mut buffer: BitBuffer<BigEndian> = BitBuffer::new();
+    /// assert_eq!(buffer.len_bits(), 0);
+    /// buffer.write(13, 0x1234).unwrap();
+    /// assert_eq!(buffer.len_bits(), 13);
+    /// buffer.skip(5).unwrap();
+    /// assert_eq!(buffer.len_bits(), 13);
+    /// ```
+    #[inline]
+    pub fn len_bits(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the buffer contains no bits
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all bits from the buffer and resets its read position,
+    /// keeping its allocated space for reuse
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitRead, BitWrite};
+    /// use bitstream_io::buffer::BitBuffer;
+    /// let mut buffer: BitBuffer<BigEndian> = BitBuffer::new();
+    /// buffer.write(12, 0xABC).unwrap();
+    /// buffer.skip(4).unwrap();
+    /// buffer.clear();
+    /// assert!(buffer.is_empty());
+    /// buffer.write(4, 0b1001).unwrap();
+    /// assert_eq!(buffer.read::<u8>(4).unwrap(), 0b1001);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.len = 0;
+        self.position = 0;
+    }
+
+    /// Returns a new buffer containing a copy of the given range of bits,
+    /// counted from the start of this buffer
+    /// and ready to be read from its beginning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is decreasing or extends past the end of the buffer.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitRead, BitWrite};
+    /// use bitstream_io::buffer::BitBuffer;
+    /// let mut buffer: BitBuffer<BigEndian> = BitBuffer::new();
+    /// buffer.write(16, 0b1011_0111_0101_0101u16).unwrap();
+    /// let mut slice = buffer.slice(3..13);
+    /// assert_eq!(slice.len_bits(), 10);
+    /// assert_eq!(slice.read::<u16>(10).unwrap(), 0b10111_01010);
+    /// ```
+    pub fn slice(&self, range: Range<u64>) -> BitBuffer<E> {
+        assert!(range.start <= range.end, "bit range starts after its end");
+        assert!(range.end <= self.len, "bit range out of bounds");
+        let mut slice = BitBuffer::new();
+        let mut position = range.start;
+        if bit_offset(position) == 0 {
+            let start = (position / 8) as usize;
+            let end = (range.end / 8) as usize;
+            slice.push_bytes(&self.bytes[start..end]);
+            position = range.end - u64::from(bit_offset(range.end));
+        }
+        while position < range.end {
+            let remaining = std::cmp::min(range.end - position, 8) as u32;
+            let (bits, value) = self.bits_at(position, remaining);
+            slice.push_bits(bits, value);
+            position += u64::from(bits);
+        }
+        slice
+    }
+
+    /// Appends the given number of bits from an 8-bit value
+    pub(crate) fn push_bits(&mut self, bits: u32, value: u8) {
+        debug_assert!(bits <= 8);
+        if bits == 0 {
+            return;
+        }
+        let offset = bit_offset(self.len);
+        if offset == 0 {
+            self.bytes
+                .push(pad(BitQueue::<E, u8>::from_value(value, bits)));
+        } else {
+            let last = self.bytes.last_mut().unwrap();
+            let mut partial = BitQueue::<E, u8>::from_value(*last, 8);
+            let mut acc = BitQueue::<E, u8>::from_value(partial.pop(offset), offset);
+            let mut incoming = BitQueue::<E, u8>::from_value(value, bits);
+            let head = std::cmp::min(bits, 8 - offset);
+            acc.push(head, incoming.pop(head));
+            *last = pad(acc);
+            if !incoming.is_empty() {
+                let tail = incoming.len();
+                self.bytes
+                    .push(pad(BitQueue::<E, u8>::from_value(incoming.pop_all(), tail)));
+            }
+        }
+        self.len += u64::from(bits);
+    }
+
+    /// Appends whole bytes
+    pub(crate) fn push_bytes(&mut self, bytes: &[u8]) {
+        if bit_offset(self.len) == 0 {
+            self.bytes.extend_from_slice(bytes);
+            self.len += bytes.len() as u64 * 8;
+        } else {
+            for b in bytes {
+                self.push_bits(8, *b);
+            }
+        }
+    }
+
+    /// Returns every byte in the buffer,
+    /// with any unused bits of the final byte set to 0
+    #[cfg(feature = "serde")]
+    #[inline]
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Writes every bit in the buffer to the given writer,
+    /// whether it has been read or not
+    pub(crate) fn playback<W: BitWrite>(&self, writer: &mut W) -> io::Result<()> {
+        let whole = (self.len / 8) as usize;
+        writer.write_bytes(&self.bytes[..whole])?;
+        match bit_offset(self.len) {
+            0 => Ok(()),
+            trailing => {
+                let (bits, value) = self.bits_at(self.len - u64::from(trailing), trailing);
+                writer.write(bits, value)
+            }
+        }
+    }
+
+    fn reserve(&self, bits: u64) -> io::Result<()> {
+        if bits <= self.len - self.position {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "end of bit buffer",
+            ))
+        }
+    }
+
+    /// Returns up to 8 bits from the given position,
+    /// not crossing a byte boundary
+    fn bits_at(&self, position: u64, bits: u32) -> (u32, u8) {
+        let offset = bit_offset(position);
+        let bits = std::cmp::min(bits, 8 - offset);
+        let mut queue = BitQueue::<E, u8>::from_value(self.bytes[(position / 8) as usize], 8);
+        if offset > 0 {
+            queue.drop(offset);
+        }
+        (bits, queue.pop(bits))
+    }
+
+    /// Removes up to 8 bits from the current position,
+    /// not crossing a byte boundary
+    #[inline]
+    fn pop_bits(&mut self, bits: u32) -> (u32, u8) {
+        let (bits, value) = self.bits_at(self.position, bits);
+        self.position += u64::from(bits);
+        (bits, value)
+    }
+}
+
+/// Returns the number of bits into its byte the given position is
+#[inline]
+fn bit_offset(position: u64) -> u32 {
+    (position % 8) as u32
+}
+
+/// Pads a partial byte with 0 bits and returns it
+#[inline]
+fn pad<E: Endianness>(mut queue: BitQueue<E, u8>) -> u8 {
+    let bits = queue.remaining_len();
+    queue.push(bits, 0);
+    queue.value()
+}
+
+impl<E: Endianness> Default for BitBuffer<E> {
+    #[inline]
+    fn default() -> Self {
+        BitBuffer::new()
+    }
+}
+
+impl<E: Endianness> Clone for BitBuffer<E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        BitBuffer {
+            bytes: self.bytes.clone(),
+            len: self.len,
+            position: self.position,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: Endianness> fmt::Debug for BitBuffer<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitBuffer")
+            .field("bytes", &self.bytes)
+            .field("len", &self.len)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<E: Endianness> BitRead for BitBuffer<E> {
+    fn read_bit(&mut self) -> io::Result<bool> {
+        self.reserve(1)?;
+        Ok(self.pop_bits(1).1 == 1)
+    }
+
+    fn read<U>(&mut self, mut bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ));
+        }
+        self.reserve(u64::from(bits))?;
+        let mut acc = BitQueue::<E, U>::new();
+        while bits > 0 {
+            let (popped, value) = self.pop_bits(bits);
+            acc.push(popped, U::from_u8(value));
+            bits -= popped;
+        }
+        Ok(acc.value())
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        if bits > S::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ));
+        }
+        self.reserve(u64::from(bits))?;
+        E::read_signed(self, bits)
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        self.reserve(bits)?;
+        self.position += bits;
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.reserve(buf.len() as u64 * 8)?;
+        if BitRead::byte_aligned(self) {
+            let start = (self.position / 8) as usize;
+            buf.copy_from_slice(&self.bytes[start..start + buf.len()]);
+            self.position += buf.len() as u64 * 8;
+        } else {
+            for b in buf.iter_mut() {
+                *b = self.read(8)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        bit_offset(self.position) == 0
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        let to_next = (8 - self.position % 8) % 8;
+        self.position = std::cmp::min(self.position + to_next, self.len);
+    }
+}
+
+impl<E: Endianness> BitWrite for BitBuffer<E> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.push_bits(1, if bit { 1 } else { 0 });
+        Ok(())
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            let mut acc = BitQueue::<E, U>::from_value(value, bits);
+            while !acc.is_empty() {
+                let bits = std::cmp::min(acc.len(), 8 - bit_offset(self.len));
+                self.push_bits(bits, acc.pop(bits).to_u8());
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.push_bytes(buf);
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        bit_offset(self.len) == 0
+    }
+}
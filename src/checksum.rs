@@ -0,0 +1,608 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Traits and implementations for calculating checksums
+//! over the bytes read from or written to a stream.
+//!
+//! `ChecksumReader` and `ChecksumWriter` wrap a stream and update
+//! a `Checksum` with every byte that passes through them.
+//! Because bitstream readers and writers only read or write
+//! whole bytes, wrapping one around a checksum stream
+//! calculates the checksum of exactly the bytes consumed or produced,
+//! which is available whenever the bitstream is byte-aligned.
+//!
+//! `HashedBitWriter` instead wraps a bitstream writer
+//! and updates a `Checksum` with every bit written to it,
+//! so that a checksum is available even when it isn't byte-aligned.
+//!
+//! ## Example
+//!
+//! Calculating the CRC-8 of a FLAC frame header as it is written.
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitWriter, BitWrite};
+//! use bitstream_io::checksum::{ChecksumWriter, Crc8};
+//!
+//! let mut writer = BitWriter::endian(ChecksumWriter::new(Vec::new(), Crc8::new()), BigEndian);
+//! writer.write(14, 0b11111111111110).unwrap();  // sync code
+//! writer.write(1, 0).unwrap();                  // reserved
+//! writer.write(1, 0).unwrap();                  // blocking strategy
+//! writer.write(4, 0b1100).unwrap();             // block size
+//! writer.write(4, 0b1001).unwrap();             // sample rate
+//! writer.write(4, 0b0001).unwrap();             // channel assignment
+//! writer.write(3, 0b100).unwrap();              // bits per sample
+//! writer.write(1, 0).unwrap();                  // padding
+//! writer.write(8, 0).unwrap();                  // frame number
+//! let crc8 = writer.writer().unwrap().checksum();
+//! writer.write(8, crc8).unwrap();
+//! assert_eq!(writer.into_writer().into_writer(), [0xFF, 0xF8, 0xC9, 0x18, 0x00, 0xC2]);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::write::BitWrite;
+use super::{BitQueue, Endianness, Numeric, SignedNumeric};
+use std::io;
+
+/// A trait for anything that can calculate a checksum
+/// from a sequence of bytes.
+pub trait Checksum {
+    /// The type of the calculated checksum
+    type Output;
+
+    /// Updates the checksum with the given bytes
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of all bytes since the last reset
+    fn checksum(&self) -> Self::Output;
+
+    /// Resets the checksum to its initial state
+    fn reset(&mut self);
+}
+
+const fn crc8_table(polynomial: u8) -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ polynomial
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const fn crc16_table(polynomial: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ polynomial
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const fn crc32_table(polynomial: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ polynomial
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const fn crc32_reflected_table(polynomial: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ polynomial
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const CRC8_TABLE: [u8; 256] = crc8_table(0x07);
+const CRC16_TABLE: [u16; 256] = crc16_table(0x8005);
+const CRC32_TABLE: [u32; 256] = crc32_reflected_table(0xEDB8_8320);
+const CRC32_MPEG2_TABLE: [u32; 256] = crc32_table(0x04C1_1DB7);
+
+/// CRC-8 with polynomial 0x07 and an initial value of 0,
+/// as used by FLAC frame headers.
+///
+/// # Example
+/// ```
+/// use bitstream_io::checksum::{Checksum, Crc8};
+/// let mut crc = Crc8::new();
+/// crc.update(b"123456789");
+/// assert_eq!(crc.checksum(), 0xF4);
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Crc8 {
+    crc: u8,
+}
+
+impl Crc8 {
+    /// Creates a new checksum
+    #[inline]
+    pub fn new() -> Crc8 {
+        Crc8::default()
+    }
+}
+
+impl Checksum for Crc8 {
+    type Output = u8;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.crc = CRC8_TABLE[(self.crc ^ *b) as usize];
+        }
+    }
+
+    #[inline]
+    fn checksum(&self) -> u8 {
+        self.crc
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.crc = 0;
+    }
+}
+
+/// CRC-16 with polynomial 0x8005 and an initial value of 0,
+/// as used by FLAC frames.
+///
+/// # Example
+/// ```
+/// use bitstream_io::checksum::{Checksum, Crc16};
+/// let mut crc = Crc16::new();
+/// crc.update(b"123456789");
+/// assert_eq!(crc.checksum(), 0xFEE8);
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Crc16 {
+    crc: u16,
+}
+
+impl Crc16 {
+    /// Creates a new checksum
+    #[inline]
+    pub fn new() -> Crc16 {
+        Crc16::default()
+    }
+}
+
+impl Checksum for Crc16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.crc = (self.crc << 8) ^ CRC16_TABLE[((self.crc >> 8) as u8 ^ *b) as usize];
+        }
+    }
+
+    #[inline]
+    fn checksum(&self) -> u16 {
+        self.crc
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.crc = 0;
+    }
+}
+
+/// The reflected CRC-32 with polynomial 0x04C11DB7
+/// as used by Ethernet, gzip and PNG.
+///
+/// # Example
+/// ```
+/// use bitstream_io::checksum::{Checksum, Crc32};
+/// let mut crc = Crc32::new();
+/// crc.update(b"123456789");
+/// assert_eq!(crc.checksum(), 0xCBF43926);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Creates a new checksum
+    #[inline]
+    pub fn new() -> Crc32 {
+        Crc32 { crc: 0xFFFF_FFFF }
+    }
+}
+
+impl Default for Crc32 {
+    #[inline]
+    fn default() -> Self {
+        Crc32::new()
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.crc = (self.crc >> 8) ^ CRC32_TABLE[(self.crc as u8 ^ *b) as usize];
+        }
+    }
+
+    #[inline]
+    fn checksum(&self) -> u32 {
+        self.crc ^ 0xFFFF_FFFF
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.crc = 0xFFFF_FFFF;
+    }
+}
+
+/// The non-reflected CRC-32 with polynomial 0x04C11DB7
+/// as used by MPEG-2 transport streams.
+///
+/// # Example
+/// ```
+/// use bitstream_io::checksum::{Checksum, Crc32Mpeg2};
+/// let mut crc = Crc32Mpeg2::new();
+/// crc.update(b"123456789");
+/// assert_eq!(crc.checksum(), 0x0376E6E7);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Crc32Mpeg2 {
+    crc: u32,
+}
+
+impl Crc32Mpeg2 {
+    /// Creates a new checksum
+    #[inline]
+    pub fn new() -> Crc32Mpeg2 {
+        Crc32Mpeg2 { crc: 0xFFFF_FFFF }
+    }
+}
+
+impl Default for Crc32Mpeg2 {
+    #[inline]
+    fn default() -> Self {
+        Crc32Mpeg2::new()
+    }
+}
+
+impl Checksum for Crc32Mpeg2 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.crc = (self.crc << 8) ^ CRC32_MPEG2_TABLE[((self.crc >> 24) as u8 ^ *b) as usize];
+        }
+    }
+
+    #[inline]
+    fn checksum(&self) -> u32 {
+        self.crc
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.crc = 0xFFFF_FFFF;
+    }
+}
+
+/// For calculating a checksum of all the bytes read from a stream.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitReader, BitRead};
+/// use bitstream_io::checksum::{ChecksumReader, Crc8};
+/// let data: &[u8] = &[0xFF, 0xF8, 0xC9, 0x18, 0x00, 0xC2];
+/// let mut reader = BitReader::endian(ChecksumReader::new(data, Crc8::new()), BigEndian);
+/// assert_eq!(reader.read::<u16>(14).unwrap(), 0b11111111111110);
+/// reader.skip(26).unwrap();
+/// let crc8 = reader.reader().unwrap().checksum();
+/// assert_eq!(reader.read::<u8>(8).unwrap(), crc8);
+/// assert_eq!(reader.reader().unwrap().checksum(), 0);
+/// ```
+pub struct ChecksumReader<R: io::Read, C: Checksum> {
+    reader: R,
+    checksum: C,
+}
+
+impl<R: io::Read, C: Checksum> ChecksumReader<R, C> {
+    /// Wraps a ChecksumReader around something that implements `Read`
+    /// using the given checksum.
+    pub fn new(reader: R, checksum: C) -> ChecksumReader<R, C> {
+        ChecksumReader { reader, checksum }
+    }
+
+    /// Returns the checksum of all bytes read since the last reset
+    #[inline]
+    pub fn checksum(&self) -> C::Output {
+        self.checksum.checksum()
+    }
+
+    /// Resets the checksum to its initial state
+    #[inline]
+    pub fn reset(&mut self) {
+        self.checksum.reset()
+    }
+
+    /// Unwraps internal reader and disposes of ChecksumReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Unwraps internal reader and checksum
+    /// and disposes of ChecksumReader.
+    #[inline]
+    pub fn into_parts(self) -> (R, C) {
+        (self.reader, self.checksum)
+    }
+}
+
+impl<R: io::Read, C: Checksum> io::Read for ChecksumReader<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes = self.reader.read(buf)?;
+        self.checksum.update(&buf[0..bytes]);
+        Ok(bytes)
+    }
+}
+
+/// For calculating a checksum of all the bytes written to a stream.
+pub struct ChecksumWriter<W: io::Write, C: Checksum> {
+    writer: W,
+    checksum: C,
+}
+
+impl<W: io::Write, C: Checksum> ChecksumWriter<W, C> {
+    /// Wraps a ChecksumWriter around something that implements `Write`
+    /// using the given checksum.
+    pub fn new(writer: W, checksum: C) -> ChecksumWriter<W, C> {
+        ChecksumWriter { writer, checksum }
+    }
+
+    /// Returns the checksum of all bytes written since the last reset
+    #[inline]
+    pub fn checksum(&self) -> C::Output {
+        self.checksum.checksum()
+    }
+
+    /// Resets the checksum to its initial state
+    #[inline]
+    pub fn reset(&mut self) {
+        self.checksum.reset()
+    }
+
+    /// Unwraps internal writer and disposes of ChecksumWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Unwraps internal writer and checksum
+    /// and disposes of ChecksumWriter.
+    #[inline]
+    pub fn into_parts(self) -> (W, C) {
+        (self.writer, self.checksum)
+    }
+}
+
+impl<W: io::Write, C: Checksum> io::Write for ChecksumWriter<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bytes = self.writer.write(buf)?;
+        self.checksum.update(&buf[0..bytes]);
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// For calculating a checksum of all the bits written to a bitstream,
+/// including any partial byte not yet written to the underlying stream.
+///
+/// Bits are packed into bytes for the checksum the same way
+/// a `BitWriter` of the same endianness would pack them,
+/// so while it's byte-aligned the checksum is that
+/// of the same bytes written to the stream.
+/// Otherwise, it's the checksum of those bytes followed by
+/// the final partial byte, padded with 0 bits as if by `byte_align`,
+/// followed by a byte containing the number of bits in that partial byte.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+/// use bitstream_io::checksum::{Checksum, Crc16, HashedBitWriter};
+/// let mut writer = HashedBitWriter::endian(
+///     BitWriter::endian(Vec::new(), BigEndian),
+///     BigEndian,
+///     Crc16::new(),
+/// );
+/// writer.write(12, 0xABCu16).unwrap();
+/// // 0xAB followed by 0xC padded to a byte, then the 4 bits in it
+/// let mut crc = Crc16::new();
+/// crc.update(&[0xAB, 0xC0, 4]);
+/// assert_eq!(writer.hash(), crc.checksum());
+/// writer.write(4, 0xDu8).unwrap();
+/// let mut crc = Crc16::new();
+/// crc.update(&[0xAB, 0xCD]);
+/// assert_eq!(writer.hash(), crc.checksum());
+/// assert_eq!(writer.into_writer().into_writer(), [0xAB, 0xCD]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct HashedBitWriter<W: BitWrite, E: Endianness, C: Checksum> {
+    writer: W,
+    checksum: C,
+    partial: BitQueue<E, u8>,
+}
+
+impl<W: BitWrite, E: Endianness, C: Checksum> HashedBitWriter<W, E, C> {
+    /// Wraps a HashedBitWriter around something that implements `BitWrite`
+    /// using the given checksum.
+    #[inline]
+    pub fn new(writer: W, checksum: C) -> Self {
+        HashedBitWriter {
+            writer,
+            checksum,
+            partial: BitQueue::new(),
+        }
+    }
+
+    /// Wraps a HashedBitWriter around something that implements `BitWrite`
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(writer: W, _endian: E, checksum: C) -> Self {
+        HashedBitWriter::new(writer, checksum)
+    }
+
+    /// Returns the checksum of all bits written since the last reset
+    pub fn hash(&self) -> C::Output
+    where
+        C: Clone,
+    {
+        if self.partial.is_empty() {
+            self.checksum.checksum()
+        } else {
+            let bits = self.partial.len();
+            let mut partial = self.partial.clone();
+            partial.push(8 - bits, 0);
+            let mut checksum = self.checksum.clone();
+            checksum.update(&[partial.pop_all(), bits as u8]);
+            checksum.checksum()
+        }
+    }
+
+    /// Resets the checksum to its initial state,
+    /// after which bits are packed into bytes from that point on
+    #[inline]
+    pub fn reset(&mut self) {
+        self.checksum.reset();
+        self.partial.clear();
+    }
+
+    /// Provides mutable reference to internal writer
+    ///
+    /// Bits written directly to it aren't included in the checksum.
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of HashedBitWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Unwraps internal writer and checksum
+    /// and disposes of HashedBitWriter.
+    ///
+    /// Any bits in a partial byte are not included in the checksum.
+    #[inline]
+    pub fn into_parts(self) -> (W, C) {
+        (self.writer, self.checksum)
+    }
+
+    // Adds bits to the checksum in stream order
+    fn update<U: Numeric>(&mut self, bits: u32, value: U) {
+        let mut queue = BitQueue::<E, U>::from_value(value, bits);
+        while !queue.is_empty() {
+            self.partial.push(1, u8::from(!queue.pop(1).is_zero()));
+            if self.partial.is_full() {
+                self.checksum.update(&[self.partial.pop_all()]);
+            }
+        }
+    }
+}
+
+impl<W: BitWrite, E: Endianness, C: Checksum> BitWrite for HashedBitWriter<W, E, C> {
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.writer.write_bit(bit)?;
+        self.update(1, u8::from(bit));
+        Ok(())
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        self.writer.write(bits, value)?;
+        self.update(bits, value);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer.write_bytes(buf)?;
+        if self.partial.is_empty() {
+            self.checksum.update(buf);
+        } else {
+            for byte in buf {
+                self.update(8, *byte);
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+}
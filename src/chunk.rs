@@ -0,0 +1,249 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A writer for length-prefixed chunks, such as RIFF chunks
+//! or ISO base media file format boxes.
+//!
+//! Container formats commonly wrap each piece of their data
+//! in a small header giving its ID and length,
+//! but the length usually isn't known until the data is written.
+//! `ChunkWriter` writes a placeholder length when a chunk begins
+//! and seeks back to fill it in when the chunk ends.
+//! Chunks may be nested, in which case each enclosing chunk's length
+//! includes the whole of the chunks inside it.
+//!
+//! ## Example
+//!
+//! Writing a RIFF file with two nested chunks.
+//!
+//! ```
+//! use std::io::Cursor;
+//! use bitstream_io::{ByteWrite, ByteWriter, LittleEndian};
+//! use bitstream_io::chunk::{ChunkLayout, ChunkWriter};
+//!
+//! let writer = ByteWriter::endian(Cursor::new(Vec::new()), LittleEndian);
+//! let mut writer = ChunkWriter::new(writer, ChunkLayout::Riff);
+//! writer.begin_chunk(b"RIFF").unwrap();
+//! writer.write_bytes(b"WAVE").unwrap();
+//! writer.begin_chunk(b"data").unwrap();
+//! writer.write(0x1234i16).unwrap();
+//! writer.end_chunk().unwrap();
+//! writer.end_chunk().unwrap();
+//! assert_eq!(
+//!     writer.into_writer().into_writer().into_inner(),
+//!     b"RIFF\x0E\x00\x00\x00WAVEdata\x02\x00\x00\x00\x34\x12"
+//! );
+//! ```
+
+#![warn(missing_docs)]
+
+use super::write::{ByteWrite, ByteWriter};
+use super::{Endianness, Numeric};
+use std::io;
+
+/// How a chunk's ID and length are laid out in its header
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChunkLayout {
+    /// The 4 byte ID followed by a 32-bit length
+    /// which doesn't include the header itself,
+    /// as used by RIFF and IFF files.
+    ///
+    /// Chunks with an odd length are followed by a 0 pad byte
+    /// which isn't included in their length,
+    /// but is included in the length of any enclosing chunk.
+    Riff,
+    /// A 32-bit length which includes the header itself
+    /// followed by the 4 byte ID,
+    /// as used by ISO base media files such as MP4 and QuickTime.
+    Box,
+}
+
+impl ChunkLayout {
+    /// The size of a chunk's header in bytes
+    const HEADER_SIZE: u64 = 8;
+
+    /// Where the length field sits relative to the start of the chunk
+    #[inline]
+    fn length_offset(self) -> u64 {
+        match self {
+            ChunkLayout::Riff => 4,
+            ChunkLayout::Box => 0,
+        }
+    }
+}
+
+/// A writer of nested, length-prefixed chunks
+/// which fills in each chunk's length once it has been written.
+///
+/// The chunk lengths are written in the `ByteWriter`'s endianness,
+/// so RIFF files should use a little-endian writer
+/// and ISO base media files a big-endian one.
+///
+/// # Example
+///
+/// Writing an MP4-style box containing another box.
+///
+/// ```
+/// use std::io::Cursor;
+/// use bitstream_io::{BigEndian, ByteWrite, ByteWriter};
+/// use bitstream_io::chunk::{ChunkLayout, ChunkWriter};
+///
+/// let writer = ByteWriter::endian(Cursor::new(Vec::new()), BigEndian);
+/// let mut writer = ChunkWriter::new(writer, ChunkLayout::Box);
+/// writer.begin_chunk(b"moov").unwrap();
+/// writer.begin_chunk(b"mvhd").unwrap();
+/// assert_eq!(writer.depth(), 2);
+/// writer.write(1u16).unwrap();
+/// let writer = writer.finish().unwrap();
+/// assert_eq!(
+///     writer.into_writer().into_inner(),
+///     b"\x00\x00\x00\x12moov\x00\x00\x00\x0Amvhd\x00\x01"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct ChunkWriter<W: io::Write + io::Seek, E: Endianness> {
+    writer: ByteWriter<W, E>,
+    layout: ChunkLayout,
+    open: Vec<u64>,
+}
+
+impl<W: io::Write + io::Seek, E: Endianness> ChunkWriter<W, E> {
+    /// Wraps a ChunkWriter around a `ByteWriter`
+    /// which writes chunks with the given layout.
+    ///
+    /// Chunk lengths are measured with the `ByteWriter`'s position,
+    /// so the writer may begin anywhere in the stream.
+    #[inline]
+    pub fn new(writer: ByteWriter<W, E>, layout: ChunkLayout) -> Self {
+        ChunkWriter {
+            writer,
+            layout,
+            open: Vec::new(),
+        }
+    }
+
+    /// Writes the header of a new chunk with the given ID
+    /// and a placeholder length, to be filled in by `end_chunk`.
+    /// If a chunk is already open, the new chunk is nested inside it.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn begin_chunk(&mut self, id: &[u8; 4]) -> io::Result<()> {
+        let start = self.writer.position();
+        match self.layout {
+            ChunkLayout::Riff => {
+                self.writer.write_bytes(id)?;
+                self.writer.write(0u32)?;
+            }
+            ChunkLayout::Box => {
+                self.writer.write(0u32)?;
+                self.writer.write_bytes(id)?;
+            }
+        }
+        self.open.push(start);
+        Ok(())
+    }
+
+    /// Finishes the innermost open chunk by writing its length
+    /// to its header, leaving the stream positioned after the chunk.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput` if no chunk is open,
+    /// or if the chunk is too large for its length field.
+    /// The chunk remains open if its length can't be written.
+    pub fn end_chunk(&mut self) -> io::Result<()> {
+        let start = match self.open.last() {
+            Some(start) => *start,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "no open chunk to end",
+                ))
+            }
+        };
+        let end = self.writer.position();
+        let length = match self.layout {
+            ChunkLayout::Riff => end - start - ChunkLayout::HEADER_SIZE,
+            ChunkLayout::Box => end - start,
+        };
+        if length > u64::from(u32::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "chunk too large for length field",
+            ));
+        }
+        // seeks are relative, since the ByteWriter's position
+        // needn't be an offset from the start of the stream
+        let back = (end - start - self.layout.length_offset()) as i64;
+        self.writer.seek(io::SeekFrom::Current(-back))?;
+        self.writer.write(length as u32)?;
+        self.writer.seek(io::SeekFrom::Current(back - 4))?;
+        if self.layout == ChunkLayout::Riff && length % 2 == 1 {
+            self.writer.write(0u8)?;
+        }
+        self.open.pop();
+        Ok(())
+    }
+
+    /// Returns the number of chunks currently open
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Returns the layout of the chunks being written
+    #[inline]
+    pub fn layout(&self) -> ChunkLayout {
+        self.layout
+    }
+
+    /// Provides mutable reference to internal writer
+    ///
+    /// Bytes written to it with `ByteWriter`'s own methods
+    /// are counted toward any open chunks,
+    /// but seeking it while a chunk is open will
+    /// corrupt that chunk's length.
+    #[inline]
+    pub fn writer(&mut self) -> &mut ByteWriter<W, E> {
+        &mut self.writer
+    }
+
+    /// Ends any chunks still open and returns the internal writer
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from `end_chunk`.
+    pub fn finish(mut self) -> io::Result<ByteWriter<W, E>> {
+        while !self.open.is_empty() {
+            self.end_chunk()?;
+        }
+        Ok(self.writer)
+    }
+
+    /// Unwraps internal writer and disposes of `ChunkWriter`.
+    /// The lengths of any chunks still open are left unwritten.
+    #[inline]
+    pub fn into_writer(self) -> ByteWriter<W, E> {
+        self.writer
+    }
+}
+
+impl<W: io::Write + io::Seek, E: Endianness> ByteWrite for ChunkWriter<W, E> {
+    #[inline]
+    fn write<N: Numeric>(&mut self, value: N) -> io::Result<()> {
+        self.writer.write(value)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer.write_bytes(buf)
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Small byte-level codecs over byte streams.
+//!
+//! These are the simple compression schemes which image formats
+//! tend to embed in their headers and strips,
+//! offered as extension traits for any `ByteWrite` or `ByteRead`
+//! so they may be mixed freely with the stream's other fields.
+//!
+//! PackBits is the run-length encoding used by TIFF, MacPaint and ILBM.
+//! Each run begins with a signed header byte `n`:
+//! from 0 to 127, the next `n + 1` bytes are copied literally;
+//! from -1 to -127, the next byte is repeated `1 - n` times;
+//! and -128 is skipped.
+//!
+//! ## Example
+//!
+//! The sample from the TIFF specification.
+//!
+//! ```
+//! use std::io::Cursor;
+//! use bitstream_io::{BigEndian, ByteReader, ByteWriter};
+//! use bitstream_io::codecs::{PackBitsRead, PackBitsWrite};
+//!
+//! let data = [
+//!     0xAA, 0xAA, 0xAA, 0x80, 0x00, 0x2A, 0xAA, 0xAA,
+//!     0xAA, 0xAA, 0x80, 0x00, 0x2A, 0x22, 0xAA, 0xAA,
+//!     0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+//! ];
+//! let mut writer = ByteWriter::endian(Vec::new(), BigEndian);
+//! writer.write_packbits(&data).unwrap();
+//! let packed = writer.into_writer();
+//! assert_eq!(
+//!     packed,
+//!     [0xFE, 0xAA, 0x02, 0x80, 0x00, 0x2A, 0xFD, 0xAA,
+//!      0x03, 0x80, 0x00, 0x2A, 0x22, 0xF7, 0xAA],
+//! );
+//!
+//! let mut reader = ByteReader::endian(Cursor::new(&packed), BigEndian);
+//! assert_eq!(reader.read_packbits(data.len()).unwrap(), data);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::ByteRead;
+use super::write::ByteWrite;
+use std::io;
+
+/// The longest run a single PackBits header can describe
+const PACKBITS_MAX_RUN: usize = 128;
+
+/// Writes PackBits-compressed data to any `ByteWrite`
+pub trait PackBitsWrite: ByteWrite {
+    /// Compresses the given bytes with PackBits and writes them.
+    ///
+    /// Repeated bytes are written as runs once there are
+    /// three or more of them, or two at the start of a run,
+    /// and all other bytes are gathered into literal runs.
+    /// No -128 headers are ever written.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, ByteWriter};
+    /// use bitstream_io::codecs::PackBitsWrite;
+    /// let mut writer = ByteWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_packbits(b"aabcccc").unwrap();
+    /// assert_eq!(writer.into_writer(), [0xFF, b'a', 0x00, b'b', 0xFD, b'c']);
+    /// ```
+    fn write_packbits(&mut self, data: &[u8]) -> io::Result<()> {
+        let mut literal = 0;
+        let mut i = 0;
+        while i < data.len() {
+            let run = data[i..]
+                .iter()
+                .take(PACKBITS_MAX_RUN)
+                .take_while(|b| **b == data[i])
+                .count();
+            if run >= 3 || (run == 2 && literal == 0) {
+                write_packbits_literal(self, &data[i - literal..i])?;
+                literal = 0;
+                self.write((1 - run as i16) as i8)?;
+                self.write(data[i])?;
+                i += run;
+            } else {
+                literal += 1;
+                i += 1;
+                if literal == PACKBITS_MAX_RUN {
+                    write_packbits_literal(self, &data[i - literal..i])?;
+                    literal = 0;
+                }
+            }
+        }
+        write_packbits_literal(self, &data[i - literal..i])
+    }
+}
+
+impl<W: ByteWrite + ?Sized> PackBitsWrite for W {}
+
+/// Writes a literal run of at most 128 bytes, if any
+fn write_packbits_literal<W: ByteWrite + ?Sized>(writer: &mut W, literal: &[u8]) -> io::Result<()> {
+    if literal.is_empty() {
+        Ok(())
+    } else {
+        writer.write((literal.len() - 1) as u8)?;
+        writer.write_bytes(literal)
+    }
+}
+
+/// Reads PackBits-compressed data from any `ByteRead`
+pub trait PackBitsRead: ByteRead {
+    /// Reads and decompresses PackBits runs
+    /// until exactly the given number of bytes have been produced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidData` if a run
+    /// would produce more bytes than the given length.
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Cursor;
+    /// use bitstream_io::{BigEndian, ByteReader};
+    /// use bitstream_io::codecs::PackBitsRead;
+    /// let data = [0xFF, b'a', 0x80, 0x00, b'b', 0xFD, b'c'];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_packbits(7).unwrap(), b"aabcccc");
+    ///
+    /// // the final run overruns the expected length
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), BigEndian);
+    /// assert!(reader.read_packbits(5).is_err());
+    /// ```
+    fn read_packbits(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            let header: i8 = self.read()?;
+            let run = match header {
+                -128 => continue,
+                n if n < 0 => (1 - i16::from(n)) as usize,
+                n => n as usize + 1,
+            };
+            if run > len - data.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "PackBits run exceeds expected length",
+                ));
+            }
+            if header < 0 {
+                let byte: u8 = self.read()?;
+                data.resize(data.len() + run, byte);
+            } else {
+                let start = data.len();
+                data.resize(start + run, 0);
+                self.read_bytes(&mut data[start..])?;
+            }
+        }
+        Ok(data)
+    }
+}
+
+impl<R: ByteRead + ?Sized> PackBitsRead for R {}
@@ -0,0 +1,492 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A convolutional encoder and hard-decision Viterbi decoder
+//! for forward error correction.
+//!
+//! A convolutional code of rate 1/n sends n coded bits
+//! for every data bit, each being the parity of a different
+//! selection of the most recent data bits.
+//! `ConvolutionalWriter` encodes the data bits written to it,
+//! and `ViterbiReader` finds the most likely data bits
+//! for the coded bits read back, correcting any errors along the way.
+//!
+//! Data is coded in frames, each of which is ended
+//! by a tail of 0 bits returning the encoder to its starting state.
+//! The reader must be told how many data bits each frame holds.
+//!
+//! ## Example
+//!
+//! Sending two frames with the rate 1/2, constraint length 7 code
+//! used by NASA's Voyager missions and many others since.
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::convolutional::{ConvolutionalCode, ConvolutionalWriter, ViterbiReader};
+//!
+//! let code = ConvolutionalCode::new(7, &[0o171, 0o133]);
+//! let mut writer = ConvolutionalWriter::endian(
+//!     BitWriter::endian(Vec::new(), BigEndian),
+//!     BigEndian,
+//!     code.clone(),
+//! );
+//! writer.write(16, 0xCAFEu16).unwrap();
+//! writer.terminate().unwrap();
+//! writer.write(16, 0xBEEFu16).unwrap();
+//! let mut writer = writer.finish().unwrap();
+//! writer.byte_align().unwrap();
+//! let mut data = writer.into_writer();
+//! // each frame is 16 data bits plus 6 tail bits, coded at 2 bits each
+//! assert_eq!(data.len(), 11);
+//!
+//! // flip a few bits here and there
+//! data[1] ^= 0b0001_0000;
+//! data[4] ^= 0b1000_0001;
+//! data[9] ^= 0b0000_0100;
+//!
+//! let mut reader = ViterbiReader::endian(
+//!     BitReader::endian(&data[..], BigEndian),
+//!     BigEndian,
+//!     code,
+//!     16,
+//! );
+//! assert_eq!(reader.read::<u16>(16).unwrap(), 0xCAFE);
+//! assert_eq!(reader.frame_errors(), 3);
+//! assert_eq!(reader.read::<u16>(16).unwrap(), 0xBEEF);
+//! assert_eq!(reader.frame_errors(), 1);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{BitQueue, Endianness, Numeric, SignedNumeric};
+use std::io;
+use std::marker::PhantomData;
+
+/// A rate 1/n convolutional code
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConvolutionalCode {
+    constraint_length: u32,
+    polynomials: Box<[u32]>,
+}
+
+impl ConvolutionalCode {
+    /// Builds a code from its constraint length -
+    /// the number of data bits each coded bit depends on -
+    /// and one generator polynomial per coded bit.
+    ///
+    /// Polynomials are given in the customary form,
+    /// where the most-significant of the `constraint_length` bits
+    /// selects the newest data bit and the least-significant bit
+    /// selects the oldest.
+    /// The coded bits for each data bit are sent
+    /// in the order of their polynomials.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the constraint length isn't between 2 and 16,
+    /// if no polynomials or more than 32 are given,
+    /// or if any polynomial is 0 or larger than the constraint length.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitstream_io::convolutional::{ConvolutionalCode, ConvolutionalWriter};
+    /// // the textbook rate 1/2 code with generators 111 and 101
+    /// let code = ConvolutionalCode::new(3, &[0b111, 0b101]);
+    /// let mut writer = ConvolutionalWriter::endian(
+    ///     BitWriter::endian(Vec::new(), BigEndian),
+    ///     BigEndian,
+    ///     code,
+    /// );
+    /// writer.write(4, 0b1011u8).unwrap();
+    /// let mut writer = writer.finish().unwrap();
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b11_10_00_01, 0b01_11_0000]);
+    /// ```
+    pub fn new(constraint_length: u32, polynomials: &[u32]) -> Self {
+        assert!(
+            (2..=16).contains(&constraint_length),
+            "constraint length must be between 2 and 16"
+        );
+        assert!(
+            (1..=32).contains(&polynomials.len()),
+            "between 1 and 32 polynomials are required"
+        );
+        for &polynomial in polynomials {
+            assert!(
+                polynomial != 0 && polynomial >> constraint_length == 0,
+                "polynomial doesn't fit constraint length"
+            );
+        }
+        ConvolutionalCode {
+            constraint_length,
+            polynomials: polynomials.into(),
+        }
+    }
+
+    /// Returns the number of data bits each coded bit depends on
+    #[inline]
+    pub fn constraint_length(&self) -> u32 {
+        self.constraint_length
+    }
+
+    /// Returns the generator polynomials, one per coded bit
+    #[inline]
+    pub fn polynomials(&self) -> &[u32] {
+        &self.polynomials
+    }
+
+    /// Returns the number of coded bits sent for each data bit
+    #[inline]
+    pub fn outputs(&self) -> u32 {
+        self.polynomials.len() as u32
+    }
+
+    // the coded bits for the given register contents,
+    // with the first polynomial's bit in the least-significant place
+    fn output(&self, register: u32) -> u32 {
+        self.polynomials
+            .iter()
+            .enumerate()
+            .fold(0, |output, (i, polynomial)| {
+                output | ((register & polynomial).count_ones() & 1) << i
+            })
+    }
+}
+
+/// A bitstream wrapper which writes the convolutional coding
+/// of the data bits written to it.
+///
+/// Alignment is counted from the number of data bits written to the wrapper,
+/// so `byte_align` pads the data being coded.
+#[derive(Clone, Debug)]
+pub struct ConvolutionalWriter<W: BitWrite, E: Endianness> {
+    writer: W,
+    code: ConvolutionalCode,
+    state: u32,
+    written: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<W: BitWrite, E: Endianness> ConvolutionalWriter<W, E> {
+    /// Wraps a ConvolutionalWriter around something that implements `BitWrite`
+    #[inline]
+    pub fn new(writer: W, code: ConvolutionalCode) -> Self {
+        ConvolutionalWriter {
+            writer,
+            code,
+            state: 0,
+            written: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a ConvolutionalWriter around something that implements `BitWrite`
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(writer: W, _endian: E, code: ConvolutionalCode) -> Self {
+        ConvolutionalWriter::new(writer, code)
+    }
+
+    /// Returns the code being written
+    #[inline]
+    pub fn code(&self) -> &ConvolutionalCode {
+        &self.code
+    }
+
+    /// Ends the current frame by coding enough 0 bits
+    /// to return the encoder to its starting state.
+    ///
+    /// The tail bits aren't counted toward the writer's alignment.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn terminate(&mut self) -> io::Result<()> {
+        for _ in 1..self.code.constraint_length {
+            self.encode(false)?;
+        }
+        Ok(())
+    }
+
+    /// Provides mutable reference to internal writer
+    ///
+    /// Bits written to it directly are not coded.
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Ends the current frame and returns the internal writer
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.terminate()?;
+        Ok(self.writer)
+    }
+
+    /// Unwraps internal writer and disposes of ConvolutionalWriter.
+    /// The current frame is left unterminated.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn encode(&mut self, bit: bool) -> io::Result<()> {
+        let register = u32::from(bit) << (self.code.constraint_length - 1) | self.state;
+        let output = self.code.output(register);
+        for i in 0..self.code.outputs() {
+            self.writer.write_bit(output & (1 << i) != 0)?;
+        }
+        self.state = register >> 1;
+        Ok(())
+    }
+}
+
+impl<W: BitWrite, E: Endianness> BitWrite for ConvolutionalWriter<W, E> {
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.encode(bit)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            let mut queue = BitQueue::<E, U>::from_value(value, bits);
+            while !queue.is_empty() {
+                self.write_bit(!queue.pop(1).is_zero())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.written & 7 == 0
+    }
+}
+
+/// A bitstream wrapper which decodes convolutionally coded frames
+/// of a fixed number of data bits.
+///
+/// Each frame is read from the wrapped reader and decoded in full
+/// before any of its bits are returned.
+/// Decoding compares every possible state of the encoder
+/// for each data bit, so its cost doubles
+/// with each step of the constraint length.
+/// Alignment is counted from the number of data bits read from the wrapper,
+/// so `byte_align` discards bits of the decoded data.
+#[derive(Clone, Debug)]
+pub struct ViterbiReader<R: BitRead, E: Endianness> {
+    reader: R,
+    code: ConvolutionalCode,
+    frame_bits: u32,
+    frame: Vec<bool>,
+    position: usize,
+    errors: u32,
+    read: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<R: BitRead, E: Endianness> ViterbiReader<R, E> {
+    /// Wraps a ViterbiReader around something that implements `BitRead`
+    /// which decodes frames of `frame_bits` data bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame_bits` is 0.
+    #[inline]
+    pub fn new(reader: R, code: ConvolutionalCode, frame_bits: u32) -> Self {
+        assert!(frame_bits > 0, "frame must contain data bits");
+        ViterbiReader {
+            reader,
+            code,
+            frame_bits,
+            frame: Vec::new(),
+            position: 0,
+            errors: 0,
+            read: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a ViterbiReader around something that implements `BitRead`
+    /// with the given endianness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame_bits` is 0.
+    #[inline]
+    pub fn endian(reader: R, _endian: E, code: ConvolutionalCode, frame_bits: u32) -> Self {
+        ViterbiReader::new(reader, code, frame_bits)
+    }
+
+    /// Returns the code being read
+    #[inline]
+    pub fn code(&self) -> &ConvolutionalCode {
+        &self.code
+    }
+
+    /// Returns the number of coded bits of the most recently decoded frame
+    /// which differed from the coding of its decoded data
+    #[inline]
+    pub fn frame_errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// Provides mutable reference to internal reader
+    ///
+    /// Bits read from it directly are not decoded,
+    /// and are read after the current frame.
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of ViterbiReader.
+    /// Any unread bits of the current frame are discarded.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    fn next_frame(&mut self) -> io::Result<()> {
+        let high = self.code.constraint_length - 1;
+        let states = 1 << high;
+        let steps = self.frame_bits as usize + high as usize;
+
+        // the encoder starts in state 0,
+        // and any state it can't yet be in is unreachable
+        let mut metrics = vec![u32::MAX; states];
+        metrics[0] = 0;
+        let mut next = vec![u32::MAX; states];
+        let mut decisions = Vec::with_capacity(steps * states);
+
+        for _ in 0..steps {
+            let mut received = 0;
+            for i in 0..self.code.outputs() {
+                if self.reader.read_bit()? {
+                    received |= 1 << i;
+                }
+            }
+            for (state, metric) in next.iter_mut().enumerate() {
+                // each state is reached from one of two others,
+                // which differ only in their oldest data bit
+                let bit = (state >> (high - 1)) as u32;
+                let mut best = (u32::MAX, false);
+                for oldest in 0..2 {
+                    let previous = (state << 1) & (states - 1) | oldest;
+                    if metrics[previous] != u32::MAX {
+                        let register = bit << high | previous as u32;
+                        let distance = (self.code.output(register) ^ received).count_ones();
+                        if metrics[previous] + distance < best.0 {
+                            best = (metrics[previous] + distance, oldest == 1);
+                        }
+                    }
+                }
+                *metric = best.0;
+                decisions.push(best.1);
+            }
+            std::mem::swap(&mut metrics, &mut next);
+        }
+
+        // the tail returns the encoder to state 0,
+        // so trace the surviving path back from there
+        let mut frame = vec![false; steps];
+        let mut state = 0;
+        for (step, bit) in frame.iter_mut().enumerate().rev() {
+            *bit = state >> (high - 1) != 0;
+            state = (state << 1) & (states - 1) | usize::from(decisions[step * states + state]);
+        }
+        frame.truncate(self.frame_bits as usize);
+
+        self.frame = frame;
+        self.position = 0;
+        self.errors = metrics[0];
+        Ok(())
+    }
+}
+
+impl<R: BitRead, E: Endianness> BitRead for ViterbiReader<R, E> {
+    fn read_bit(&mut self) -> io::Result<bool> {
+        if self.position == self.frame.len() {
+            self.next_frame()?;
+        }
+        let bit = self.frame[self.position];
+        self.position += 1;
+        self.read += 1;
+        Ok(bit)
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits <= U::bits_size() {
+            let mut queue = BitQueue::<E, U>::new();
+            for _ in 0..bits {
+                queue.push(1, U::from_u8(self.read_bit()?.into()));
+            }
+            Ok(queue.value())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        }
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        for _ in 0..bits {
+            self.read_bit()?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.read & 7 == 0
+    }
+
+    fn byte_align(&mut self) {
+        while !self.byte_aligned() && self.read_bit().is_ok() {}
+    }
+}
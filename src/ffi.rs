@@ -0,0 +1,441 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A C interface to bitstream readers and writers.
+//!
+//! This lets existing C codecs move their bit I/O onto this crate
+//! one call site at a time.
+//! Readers and writers are passed to C as opaque handles,
+//! which are created by `bitstream_reader_new` and `bitstream_writer_new`
+//! and must be released by `bitstream_reader_free` and `bitstream_writer_free`.
+//! Functions which may fail return `BITSTREAM_OK` on success
+//! or one of the negative `BITSTREAM_ERR_` codes on failure.
+//! The matching C declarations are in `include/bitstream_io.h`.
+//!
+//! Writers collect their output in memory,
+//! which C code retrieves with `bitstream_writer_data`
+//! and may discard with `bitstream_writer_clear` once it has been used.
+//! Readers take a copy of the data they read from.
+//!
+//! To link these functions from C, build the crate as a `cdylib`
+//! or `staticlib` with the `ffi` feature enabled, such as with
+//! `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::ffi::*;
+//!
+//! unsafe {
+//!     let writer = bitstream_writer_new(BITSTREAM_BIG_ENDIAN);
+//!     assert_eq!(bitstream_writer_write(writer, 3, 0b101), BITSTREAM_OK);
+//!     assert_eq!(bitstream_writer_write_signed(writer, 5, -3), BITSTREAM_OK);
+//!     assert_eq!(bitstream_writer_write(writer, 3, 8), BITSTREAM_ERR_INVALID_INPUT);
+//!     assert_eq!(bitstream_writer_byte_align(writer), BITSTREAM_OK);
+//!     let mut len = 0;
+//!     let data = bitstream_writer_data(writer, &mut len);
+//!     assert_eq!(std::slice::from_raw_parts(data, len), [0b101_11101]);
+//!
+//!     let reader = bitstream_reader_new(BITSTREAM_BIG_ENDIAN, data, len);
+//!     bitstream_writer_free(writer);
+//!     let mut value = 0;
+//!     assert_eq!(bitstream_reader_read(reader, 3, &mut value), BITSTREAM_OK);
+//!     assert_eq!(value, 0b101);
+//!     let mut signed = 0;
+//!     assert_eq!(bitstream_reader_read_signed(reader, 5, &mut signed), BITSTREAM_OK);
+//!     assert_eq!(signed, -3);
+//!     assert_eq!(bitstream_reader_read(reader, 1, &mut value), BITSTREAM_ERR_EOF);
+//!     bitstream_reader_free(reader);
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{BitWriter, DynBitReader, DynBitWriter, DynEndianness, Endianness};
+use std::io;
+use std::mem;
+use std::os::raw::c_int;
+use std::ptr;
+use std::slice;
+
+/// Reads or writes the most significant bits first
+pub const BITSTREAM_BIG_ENDIAN: c_int = 0;
+/// Reads or writes the least significant bits first
+pub const BITSTREAM_LITTLE_ENDIAN: c_int = 1;
+
+/// The call succeeded
+pub const BITSTREAM_OK: c_int = 0;
+/// A handle or output pointer was null
+pub const BITSTREAM_ERR_NULL: c_int = -1;
+/// A number of bits or a value was out of range
+pub const BITSTREAM_ERR_INVALID_INPUT: c_int = -2;
+/// A read went past the end of the data
+pub const BITSTREAM_ERR_EOF: c_int = -3;
+/// Any other error
+pub const BITSTREAM_ERR_OTHER: c_int = -4;
+
+/// An opaque handle to a bitstream writer
+#[derive(Debug)]
+pub struct BitstreamWriter {
+    writer: DynBitWriter<Vec<u8>>,
+}
+
+/// An opaque handle to a bitstream reader
+#[derive(Debug)]
+pub struct BitstreamReader {
+    reader: DynBitReader<io::Cursor<Vec<u8>>>,
+}
+
+fn endianness(endianness: c_int) -> Option<DynEndianness> {
+    match endianness {
+        BITSTREAM_BIG_ENDIAN => Some(DynEndianness::Big),
+        BITSTREAM_LITTLE_ENDIAN => Some(DynEndianness::Little),
+        _ => None,
+    }
+}
+
+fn status(result: io::Result<()>) -> c_int {
+    match result {
+        Ok(()) => BITSTREAM_OK,
+        Err(err) => match err.kind() {
+            io::ErrorKind::InvalidInput => BITSTREAM_ERR_INVALID_INPUT,
+            io::ErrorKind::UnexpectedEof => BITSTREAM_ERR_EOF,
+            _ => BITSTREAM_ERR_OTHER,
+        },
+    }
+}
+
+/// Returns true if the value fits in a signed field of the given size
+fn signed_in_range(bits: u32, value: i64) -> bool {
+    match bits {
+        1..=63 => (-(1 << (bits - 1))..(1 << (bits - 1))).contains(&value),
+        64 => true,
+        _ => false,
+    }
+}
+
+/// Creates a new writer of the given endianness
+/// which collects its output in memory.
+///
+/// Returns null if the endianness is unknown.
+#[no_mangle]
+pub extern "C" fn bitstream_writer_new(endian: c_int) -> *mut BitstreamWriter {
+    match endianness(endian) {
+        Some(endian) => Box::into_raw(Box::new(BitstreamWriter {
+            writer: DynBitWriter::new(Vec::new(), endian),
+        })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a writer and all of its output.
+///
+/// # Safety
+///
+/// The writer must be null or have come from `bitstream_writer_new`,
+/// and must not be used again afterward.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_free(writer: *mut BitstreamWriter) {
+    if !writer.is_null() {
+        drop(Box::from_raw(writer));
+    }
+}
+
+/// Writes an unsigned value of up to 64 bits.
+///
+/// # Safety
+///
+/// The writer must be null or a live handle from `bitstream_writer_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_write(
+    writer: *mut BitstreamWriter,
+    bits: u32,
+    value: u64,
+) -> c_int {
+    match writer.as_mut() {
+        Some(writer) => status(writer.writer.write(bits, value)),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Writes a two's complement signed value of 1 to 64 bits.
+///
+/// # Safety
+///
+/// The writer must be null or a live handle from `bitstream_writer_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_write_signed(
+    writer: *mut BitstreamWriter,
+    bits: u32,
+    value: i64,
+) -> c_int {
+    match writer.as_mut() {
+        Some(_) if !signed_in_range(bits, value) => BITSTREAM_ERR_INVALID_INPUT,
+        Some(writer) => status(writer.writer.write_signed(bits, value)),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Writes the given number of whole bytes.
+///
+/// # Safety
+///
+/// The writer must be null or a live handle from `bitstream_writer_new`,
+/// and the data must point to at least `len` readable bytes
+/// unless `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_write_bytes(
+    writer: *mut BitstreamWriter,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    match writer.as_mut() {
+        Some(writer) if len == 0 => status(writer.writer.write_bytes(&[])),
+        Some(_) if data.is_null() => BITSTREAM_ERR_NULL,
+        Some(writer) => status(writer.writer.write_bytes(slice::from_raw_parts(data, len))),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Pads the writer with 0 bits until it is byte-aligned.
+///
+/// # Safety
+///
+/// The writer must be null or a live handle from `bitstream_writer_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_byte_align(writer: *mut BitstreamWriter) -> c_int {
+    match writer.as_mut() {
+        Some(writer) => status(writer.writer.byte_align()),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Returns 1 if the writer is byte-aligned, 0 if it isn't,
+/// or `BITSTREAM_ERR_NULL`.
+///
+/// # Safety
+///
+/// The writer must be null or a live handle from `bitstream_writer_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_byte_aligned(writer: *const BitstreamWriter) -> c_int {
+    match writer.as_ref() {
+        Some(writer) => writer.writer.byte_aligned().into(),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Returns the whole bytes written so far
+/// and stores their number in `len`.
+/// Any partial byte is not included until it is completed.
+///
+/// The bytes remain valid until the writer is next used.
+/// Returns null if either argument is null.
+///
+/// # Safety
+///
+/// The writer must be null or a live handle from `bitstream_writer_new`,
+/// and `len` must be null or point to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_data(
+    writer: *const BitstreamWriter,
+    len: *mut usize,
+) -> *const u8 {
+    match (writer.as_ref(), len.as_mut()) {
+        (Some(writer), Some(len)) => {
+            let data: &[u8] = match &writer.writer {
+                DynBitWriter::Big(writer) => writer.written_so_far().0,
+                DynBitWriter::Little(writer) => writer.written_so_far().0,
+            };
+            *len = data.len();
+            data.as_ptr()
+        }
+        _ => ptr::null(),
+    }
+}
+
+/// Discards the whole bytes written so far,
+/// such as after they have been retrieved with `bitstream_writer_data`.
+/// Any partial byte is kept.
+///
+/// # Safety
+///
+/// The writer must be null or a live handle from `bitstream_writer_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_writer_clear(writer: *mut BitstreamWriter) -> c_int {
+    match writer.as_mut() {
+        Some(writer) => {
+            match &mut writer.writer {
+                DynBitWriter::Big(writer) => clear_written(writer),
+                DynBitWriter::Little(writer) => clear_written(writer),
+            }
+            BITSTREAM_OK
+        }
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+fn clear_written<E: Endianness>(writer: &mut BitWriter<Vec<u8>, E>) {
+    let (bits, value) = (writer.pending_bits(), writer.pending_value());
+    let mut data = mem::replace(writer, BitWriter::new(Vec::new())).into_writer();
+    data.clear();
+    // the pending bits came from a writer, so they're always valid
+    if let Ok(resumed) = BitWriter::from_pending(data, bits, value) {
+        *writer = resumed;
+    }
+}
+
+/// Creates a new reader of the given endianness
+/// over a copy of the given bytes.
+///
+/// Returns null if the endianness is unknown
+/// or if the data is null and `len` isn't 0.
+///
+/// # Safety
+///
+/// The data must point to at least `len` readable bytes
+/// unless `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_new(
+    endian: c_int,
+    data: *const u8,
+    len: usize,
+) -> *mut BitstreamReader {
+    let data = if len == 0 {
+        Vec::new()
+    } else if data.is_null() {
+        return ptr::null_mut();
+    } else {
+        slice::from_raw_parts(data, len).to_vec()
+    };
+    match endianness(endian) {
+        Some(endian) => Box::into_raw(Box::new(BitstreamReader {
+            reader: DynBitReader::new(io::Cursor::new(data), endian),
+        })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a reader and its copy of the data.
+///
+/// # Safety
+///
+/// The reader must be null or have come from `bitstream_reader_new`,
+/// and must not be used again afterward.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_free(reader: *mut BitstreamReader) {
+    if !reader.is_null() {
+        drop(Box::from_raw(reader));
+    }
+}
+
+/// Reads an unsigned value of up to 64 bits into `value`.
+///
+/// # Safety
+///
+/// The reader must be null or a live handle from `bitstream_reader_new`,
+/// and `value` must be null or point to a writable `uint64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_read(
+    reader: *mut BitstreamReader,
+    bits: u32,
+    value: *mut u64,
+) -> c_int {
+    match (reader.as_mut(), value.as_mut()) {
+        (Some(reader), Some(value)) => status(reader.reader.read(bits).map(|v| *value = v)),
+        _ => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Reads a two's complement signed value of 1 to 64 bits into `value`.
+///
+/// # Safety
+///
+/// The reader must be null or a live handle from `bitstream_reader_new`,
+/// and `value` must be null or point to a writable `int64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_read_signed(
+    reader: *mut BitstreamReader,
+    bits: u32,
+    value: *mut i64,
+) -> c_int {
+    match (reader.as_mut(), value.as_mut()) {
+        (Some(_), Some(_)) if !(1..=64).contains(&bits) => BITSTREAM_ERR_INVALID_INPUT,
+        (Some(reader), Some(value)) => status(reader.reader.read_signed(bits).map(|v| *value = v)),
+        _ => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Reads the given number of whole bytes into `data`.
+///
+/// # Safety
+///
+/// The reader must be null or a live handle from `bitstream_reader_new`,
+/// and the data must point to at least `len` writable bytes
+/// unless `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_read_bytes(
+    reader: *mut BitstreamReader,
+    data: *mut u8,
+    len: usize,
+) -> c_int {
+    match reader.as_mut() {
+        Some(reader) if len == 0 => status(reader.reader.read_bytes(&mut [])),
+        Some(_) if data.is_null() => BITSTREAM_ERR_NULL,
+        Some(reader) => status(
+            reader
+                .reader
+                .read_bytes(slice::from_raw_parts_mut(data, len)),
+        ),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Skips the given number of bits.
+///
+/// # Safety
+///
+/// The reader must be null or a live handle from `bitstream_reader_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_skip(reader: *mut BitstreamReader, bits: u64) -> c_int {
+    match reader.as_mut() {
+        Some(reader) => status(reader.reader.skip(bits)),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Discards bits until the reader is byte-aligned.
+///
+/// # Safety
+///
+/// The reader must be null or a live handle from `bitstream_reader_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_byte_align(reader: *mut BitstreamReader) -> c_int {
+    match reader.as_mut() {
+        Some(reader) => {
+            reader.reader.byte_align();
+            BITSTREAM_OK
+        }
+        None => BITSTREAM_ERR_NULL,
+    }
+}
+
+/// Returns 1 if the reader is byte-aligned, 0 if it isn't,
+/// or `BITSTREAM_ERR_NULL`.
+///
+/// # Safety
+///
+/// The reader must be null or a live handle from `bitstream_reader_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bitstream_reader_byte_aligned(reader: *const BitstreamReader) -> c_int {
+    match reader.as_ref() {
+        Some(reader) => reader.reader.byte_aligned().into(),
+        None => BITSTREAM_ERR_NULL,
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Traits and implementations for floating point formats
+//! which have no native Rust type.
+//!
+//! Each format implements `FloatNumeric` so that it may be
+//! read and written with the `read_float` and `write_float` methods,
+//! and converts to and from `f64` for use in calculations.
+//!
+//! ## Example
+//!
+//! Reading an AIFF file's sample rate,
+//! which is stored as an 80-bit extended precision float.
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitRead};
+//! use bitstream_io::float::Extended;
+//!
+//! let data = [0x40, 0x0E, 0xAC, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+//! let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+//! let sample_rate: Extended = reader.read_float().unwrap();
+//! assert_eq!(sample_rate.to_f64(), 44100.0);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::FloatNumeric;
+
+const F64_EXPONENT_BIAS: i32 = 1023;
+const F64_FRACTION_BITS: u32 = 52;
+const F64_FRACTION_MASK: u64 = (1 << F64_FRACTION_BITS) - 1;
+
+/// A 16-bit IEEE 754 half precision float,
+/// with 1 sign bit, 5 exponent bits and 10 fraction bits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Half {
+    bits: u16,
+}
+
+impl Half {
+    const EXPONENT_BIAS: i32 = 15;
+    const FRACTION_BITS: u32 = 10;
+    const INFINITY: u16 = 0x7C00;
+
+    /// Builds a half from its bit pattern
+    #[inline]
+    pub fn from_bits(bits: u16) -> Half {
+        Half { bits }
+    }
+
+    /// Returns the half's bit pattern
+    #[inline]
+    pub fn to_bits(self) -> u16 {
+        self.bits
+    }
+
+    /// Converts a double to the nearest half,
+    /// rounding ties to even.
+    /// Values too large for a half become infinite.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::float::Half;
+    /// assert_eq!(Half::from_f64(1.0).to_bits(), 0x3C00);
+    /// assert_eq!(Half::from_f64(-2.0).to_bits(), 0xC000);
+    /// assert_eq!(Half::from_f64(65504.0).to_bits(), 0x7BFF);
+    /// assert_eq!(Half::from_f64(1.0e6).to_bits(), 0x7C00);
+    /// assert_eq!(Half::from_f64(1.0 + 1.0 / 2048.0).to_bits(), 0x3C00);
+    /// ```
+    pub fn from_f64(value: f64) -> Half {
+        let bits = value.to_bits();
+        let sign = ((bits >> 48) & 0x8000) as u16;
+        let exponent = ((bits >> F64_FRACTION_BITS) & 0x7FF) as i32;
+        let fraction = bits & F64_FRACTION_MASK;
+        let shift = F64_FRACTION_BITS - Self::FRACTION_BITS;
+
+        let magnitude = if exponent == 0x7FF {
+            if fraction == 0 {
+                Self::INFINITY
+            } else {
+                // quiet NaN, keeping as much of the payload as fits
+                Self::INFINITY | 0x200 | (fraction >> shift) as u16
+            }
+        } else {
+            let exponent = exponent - F64_EXPONENT_BIAS + Self::EXPONENT_BIAS;
+            if exponent > 0 {
+                // a carry out of the fraction increments the exponent,
+                // which becomes infinity if it overflows
+                let rounded =
+                    ((exponent as u64) << Self::FRACTION_BITS) + round_shift(fraction, shift);
+                if rounded >= u64::from(Self::INFINITY) {
+                    Self::INFINITY
+                } else {
+                    rounded as u16
+                }
+            } else if exponent == -(F64_EXPONENT_BIAS - Self::EXPONENT_BIAS) && fraction == 0 {
+                0
+            } else {
+                // subnormal half, which may round up to the smallest normal
+                let significand = fraction | (1 << F64_FRACTION_BITS);
+                round_shift(significand, shift + (1 - exponent) as u32) as u16
+            }
+        };
+        Half::from_bits(sign | magnitude)
+    }
+
+    /// Converts the half to a double, which is always exact.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::float::Half;
+    /// assert_eq!(Half::from_bits(0x3C00).to_f64(), 1.0);
+    /// assert_eq!(Half::from_bits(0xC000).to_f64(), -2.0);
+    /// assert_eq!(Half::from_bits(0x0001).to_f64(), 2.0f64.powi(-24));
+    /// assert!(Half::from_bits(0x7E00).to_f64().is_nan());
+    /// ```
+    pub fn to_f64(self) -> f64 {
+        let sign = u64::from(self.bits & 0x8000) << 48;
+        let exponent = i32::from((self.bits >> Self::FRACTION_BITS) & 0x1F);
+        let fraction = u64::from(self.bits & 0x3FF);
+        let shift = F64_FRACTION_BITS - Self::FRACTION_BITS;
+
+        if exponent == 0x1F {
+            f64::from_bits(sign | (0x7FF << F64_FRACTION_BITS) | (fraction << shift))
+        } else if exponent == 0 {
+            let magnitude = fraction as f64 * 2.0f64.powi(-24);
+            if sign == 0 {
+                magnitude
+            } else {
+                -magnitude
+            }
+        } else {
+            let exponent = (exponent - Self::EXPONENT_BIAS + F64_EXPONENT_BIAS) as u64;
+            f64::from_bits(sign | (exponent << F64_FRACTION_BITS) | (fraction << shift))
+        }
+    }
+}
+
+impl FloatNumeric for Half {
+    type Bits = u16;
+
+    #[inline(always)]
+    fn bits_size() -> u32 {
+        16
+    }
+    #[inline(always)]
+    fn to_bits(self) -> u16 {
+        self.bits
+    }
+    #[inline(always)]
+    fn from_bits(bits: u16) -> Self {
+        Half { bits }
+    }
+}
+
+/// An 80-bit extended precision float, as used by
+/// x87 floating point units and AIFF sample rates,
+/// with 1 sign bit, 15 exponent bits and a 64-bit significand
+/// whose leading integer bit is explicit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Extended {
+    bits: u128,
+}
+
+impl Extended {
+    const EXPONENT_BIAS: i32 = 16383;
+    const SIGNIFICAND_BITS: u32 = 64;
+    const INTEGER_BIT: u64 = 1 << 63;
+
+    /// Builds an extended float from its bit pattern,
+    /// which occupies the lowest 80 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any bits above the lowest 80 are set.
+    #[inline]
+    pub fn from_bits(bits: u128) -> Extended {
+        assert!(bits >> 80 == 0, "excessive bits for extended float");
+        Extended { bits }
+    }
+
+    /// Returns the extended float's bit pattern
+    /// in the lowest 80 bits
+    #[inline]
+    pub fn to_bits(self) -> u128 {
+        self.bits
+    }
+
+    #[inline]
+    fn from_parts(sign: bool, exponent: u16, significand: u64) -> Extended {
+        Extended {
+            bits: (u128::from(sign) << 79)
+                | (u128::from(exponent) << Self::SIGNIFICAND_BITS)
+                | u128::from(significand),
+        }
+    }
+
+    /// Converts a double to an extended float, which is always exact.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::float::Extended;
+    /// assert_eq!(Extended::from_f64(44100.0).to_bits(), 0x400E_AC44_0000_0000_0000);
+    /// assert_eq!(Extended::from_f64(-1.0).to_bits(), 0xBFFF_8000_0000_0000_0000);
+    /// ```
+    pub fn from_f64(value: f64) -> Extended {
+        let bits = value.to_bits();
+        let sign = bits >> 63 == 1;
+        let exponent = ((bits >> F64_FRACTION_BITS) & 0x7FF) as i32;
+        let fraction = bits & F64_FRACTION_MASK;
+        let shift = Self::SIGNIFICAND_BITS - 1 - F64_FRACTION_BITS;
+
+        if exponent == 0x7FF {
+            Extended::from_parts(sign, 0x7FFF, Self::INTEGER_BIT | (fraction << shift))
+        } else if exponent == 0 {
+            if fraction == 0 {
+                Extended::from_parts(sign, 0, 0)
+            } else {
+                // normalize a subnormal double's significand
+                let zeros = fraction.leading_zeros();
+                let exponent = 1 - F64_EXPONENT_BIAS + Self::EXPONENT_BIAS
+                    - (zeros - (Self::SIGNIFICAND_BITS - 1 - F64_FRACTION_BITS)) as i32;
+                Extended::from_parts(sign, exponent as u16, fraction << zeros)
+            }
+        } else {
+            let exponent = exponent - F64_EXPONENT_BIAS + Self::EXPONENT_BIAS;
+            Extended::from_parts(
+                sign,
+                exponent as u16,
+                Self::INTEGER_BIT | (fraction << shift),
+            )
+        }
+    }
+
+    /// Converts the extended float to the nearest double,
+    /// rounding ties to even.
+    /// Values too large for a double become infinite
+    /// and values too small become zero.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::float::Extended;
+    /// assert_eq!(Extended::from_bits(0x400E_AC44_0000_0000_0000).to_f64(), 44100.0);
+    /// assert_eq!(Extended::from_bits(0x7FFE_8000_0000_0000_0000).to_f64(), f64::INFINITY);
+    /// assert_eq!(Extended::from_bits(0x0001_8000_0000_0000_0000).to_f64(), 0.0);
+    /// ```
+    pub fn to_f64(self) -> f64 {
+        let sign = ((self.bits >> 79) as u64) << 63;
+        let exponent = ((self.bits >> Self::SIGNIFICAND_BITS) & 0x7FFF) as i32;
+        let significand = self.bits as u64;
+        let shift = Self::SIGNIFICAND_BITS - 1 - F64_FRACTION_BITS;
+
+        if exponent == 0x7FFF {
+            let fraction = significand & !Self::INTEGER_BIT;
+            let payload = if fraction == 0 {
+                0
+            } else {
+                // quiet NaN, keeping as much of the payload as fits
+                (1 << (F64_FRACTION_BITS - 1)) | (fraction >> shift)
+            };
+            return f64::from_bits(sign | (0x7FF << F64_FRACTION_BITS) | payload);
+        } else if significand == 0 {
+            return f64::from_bits(sign);
+        }
+
+        // normalize, treating the denormal exponent of 0 as 1
+        let zeros = significand.leading_zeros();
+        let significand = significand << zeros;
+        let exponent =
+            std::cmp::max(exponent, 1) - zeros as i32 - Self::EXPONENT_BIAS + F64_EXPONENT_BIAS;
+
+        let magnitude = if exponent >= 0x7FF {
+            0x7FF << F64_FRACTION_BITS
+        } else if exponent >= 1 {
+            // the rounded significand includes the integer bit,
+            // which adds the remaining 1 to the exponent field
+            // and may carry into it
+            let rounded =
+                ((exponent as u64 - 1) << F64_FRACTION_BITS) + round_shift(significand, shift);
+            std::cmp::min(rounded, 0x7FF << F64_FRACTION_BITS)
+        } else {
+            // subnormal double, which may round up to the smallest normal
+            round_shift(significand, shift + (1 - exponent) as u32)
+        };
+        f64::from_bits(sign | magnitude)
+    }
+}
+
+impl FloatNumeric for Extended {
+    type Bits = u128;
+
+    #[inline(always)]
+    fn bits_size() -> u32 {
+        80
+    }
+    #[inline(always)]
+    fn to_bits(self) -> u128 {
+        self.bits
+    }
+    #[inline(always)]
+    fn from_bits(bits: u128) -> Self {
+        Extended::from_bits(bits)
+    }
+}
+
+/// Shifts value right by the given number of bits,
+/// rounding to nearest with ties to even.
+fn round_shift(value: u64, shift: u32) -> u64 {
+    if shift == 0 {
+        value
+    } else if shift > 64 {
+        0
+    } else {
+        let half = 1u128 << (shift - 1);
+        let remainder = u128::from(value) & ((half << 1) - 1);
+        let quotient = (u128::from(value) >> shift) as u64;
+        if remainder > half || (remainder == half && quotient & 1 == 1) {
+            quotient + 1
+        } else {
+            quotient
+        }
+    }
+}
@@ -11,10 +11,13 @@
 
 #![warn(missing_docs)]
 
+use super::read::BitRead;
+use super::write::BitWrite;
 use super::BitQueue;
 use super::Endianness;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::io;
 use std::marker::PhantomData;
 
 /// A compiled Huffman tree element for use with the `read_huffman` method.
@@ -36,6 +39,33 @@ pub enum ReadHuffmanTree<E: Endianness, T: Clone> {
     InvalidState,
 }
 
+impl<E: Endianness, T: Clone> Clone for ReadHuffmanTree<E, T> {
+    fn clone(&self) -> Self {
+        match self {
+            ReadHuffmanTree::Done(value, queue_value, queue_bits, _) => {
+                ReadHuffmanTree::Done(value.clone(), *queue_value, *queue_bits, PhantomData)
+            }
+            ReadHuffmanTree::Continue(tree) => ReadHuffmanTree::Continue(tree.clone()),
+            ReadHuffmanTree::InvalidState => ReadHuffmanTree::InvalidState,
+        }
+    }
+}
+
+impl<E: Endianness, T: Clone + fmt::Debug> fmt::Debug for ReadHuffmanTree<E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadHuffmanTree::Done(value, queue_value, queue_bits, _) => f
+                .debug_tuple("Done")
+                .field(value)
+                .field(queue_value)
+                .field(queue_bits)
+                .finish(),
+            ReadHuffmanTree::Continue(tree) => f.debug_tuple("Continue").field(tree).finish(),
+            ReadHuffmanTree::InvalidState => f.write_str("InvalidState"),
+        }
+    }
+}
+
 /// Given a vector of symbol/code pairs, compiles a Huffman tree
 /// for reading.
 ///
@@ -79,22 +109,74 @@ where
     E: Endianness,
     T: Clone,
 {
-    let tree = FinalHuffmanTree::new(values)?;
+    FinalHuffmanTree::new(values, None)
+        .map(|tree| compile_states(&tree))
+        .map_err(HuffmanTreeDiagnostic::into_error)
+}
+
+/// Given a vector of symbol/code pairs, compiles a Huffman tree
+/// for reading the same way as `compile_read_tree`,
+/// but also requires that no code is longer than `max_bits`.
+///
+/// # Errors
+///
+/// Returns a `HuffmanTreeDiagnostic` identifying the symbols
+/// and codes of the first problem found in the specification,
+/// or every code which has no symbol.
+///
+/// ## Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::huffman::{compile_read_tree_checked, HuffmanTreeDiagnostic};
+/// assert_eq!(
+///     compile_read_tree_checked::<BigEndian, char>(
+///         vec![('a', vec![0]),
+///              ('b', vec![1, 0]),
+///              ('c', vec![0, 1])], 8).unwrap_err(),
+///     HuffmanTreeDiagnostic::OrphanedLeaf {
+///         symbol: 'a',
+///         code: vec![0],
+///         longer: 'c',
+///         longer_code: vec![0, 1],
+///     });
+/// assert_eq!(
+///     compile_read_tree_checked::<BigEndian, char>(
+///         vec![('a', vec![0]),
+///              ('b', vec![1, 0]),
+///              ('c', vec![1, 1, 0, 1])], 3).unwrap_err(),
+///     HuffmanTreeDiagnostic::CodeTooLong { symbol: 'c', code: vec![1, 1, 0, 1] });
+/// ```
+pub fn compile_read_tree_checked<E, T>(
+    values: Vec<(T, Vec<u8>)>,
+    max_bits: u32,
+) -> Result<Box<[ReadHuffmanTree<E, T>]>, HuffmanTreeDiagnostic<T>>
+where
+    E: Endianness,
+    T: Clone,
+{
+    FinalHuffmanTree::new(values, Some(max_bits)).map(|tree| compile_states(&tree))
+}
 
+// Compiles a finalized tree into a jump table for every reader state
+fn compile_states<E, T>(tree: &FinalHuffmanTree<T>) -> Box<[ReadHuffmanTree<E, T>]>
+where
+    E: Endianness,
+    T: Clone,
+{
     let mut result = Vec::with_capacity(256);
     result.extend((0..256).map(|_| ReadHuffmanTree::InvalidState));
     let queue = BitQueue::from_value(0, 0);
     let i = queue.to_state();
-    result[i] = compile_queue(queue, &tree);
+    result[i] = compile_queue(queue, tree);
     for bits in 1..8 {
         for value in 0..(1 << bits) {
             let queue = BitQueue::from_value(value, bits);
             let i = queue.to_state();
-            result[i] = compile_queue(queue, &tree);
+            result[i] = compile_queue(queue, tree);
         }
     }
     assert_eq!(result.len(), 256);
-    Ok(result.into_boxed_slice())
+    result.into_boxed_slice()
 }
 
 fn compile_queue<E, T>(
@@ -127,6 +209,349 @@ where
     }
 }
 
+/// A compiled Huffman tree for use with the `read_compact_huffman` method.
+/// Returned by `compile_compact_read_tree`.
+///
+/// Where a `ReadHuffmanTree` holds a 256 entry jump table
+/// for every possible reader state, which grows very large
+/// for trees with many long codes, a compact tree holds
+/// a series of lookup tables indexed by at most a fixed number of bits.
+/// Each table is only as wide as the shortest code it leads to,
+/// so reading never consumes bits past the end of a code
+/// and any `BitRead` implementation may be used.
+///
+/// Wider lookup tables decode in fewer reads
+/// but usually take more memory.
+pub struct CompactReadHuffmanTree<E: Endianness, T> {
+    entries: Box<[CompactEntry<T>]>,
+    root_bits: u32,
+    phantom: PhantomData<E>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum CompactEntry<T> {
+    Leaf(T),
+    Unknown,
+    Table { offset: usize, bits: u32 },
+}
+
+/// Given a vector of symbol/code pairs, compiles a compact Huffman tree
+/// for reading whose lookup tables are indexed by
+/// at most `lookup_bits` bits.
+///
+/// Codes are given the same way as for `compile_read_tree`.
+///
+/// # Panics
+///
+/// Panics if `lookup_bits` is 0 or greater than 16.
+///
+/// ## Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{LittleEndian, BitReader, HuffmanRead};
+/// use bitstream_io::huffman::compile_compact_read_tree;
+/// let tree = compile_compact_read_tree(
+///     vec![('a', vec![0]),
+///          ('b', vec![1, 0]),
+///          ('c', vec![1, 1, 0]),
+///          ('d', vec![1, 1, 1])], 2).unwrap();
+/// let data = [0b11101101];
+/// let mut cursor = Cursor::new(&data);
+/// let mut reader = BitReader::endian(&mut cursor, LittleEndian);
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'b');
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'c');
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'd');
+/// ```
+pub fn compile_compact_read_tree<E, T>(
+    values: Vec<(T, Vec<u8>)>,
+    lookup_bits: u32,
+) -> Result<CompactReadHuffmanTree<E, T>, HuffmanTreeError>
+where
+    E: Endianness,
+    T: Clone,
+{
+    assert!(
+        lookup_bits > 0 && lookup_bits <= 16,
+        "lookup size must be between 1 and 16 bits"
+    );
+    FinalHuffmanTree::new(
+        values
+            .into_iter()
+            .map(|(symbol, code)| (Some(symbol), code))
+            .collect(),
+        None,
+    )
+    .map(|tree| compile_compact_tree(&tree, lookup_bits))
+    .map_err(HuffmanTreeDiagnostic::into_error)
+}
+
+/// Given a vector of symbol/code pairs, compiles a compact Huffman tree
+/// for reading in which not every possible code needs a symbol.
+///
+/// Codes are given the same way as for `compile_compact_read_tree`,
+/// but reading any code without a symbol returns an error
+/// which gives the number of bits consumed,
+/// so that the reader may resynchronize with the stream.
+///
+/// # Panics
+///
+/// Panics if `lookup_bits` is 0 or greater than 16.
+///
+/// ## Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BigEndian, BitReader, HuffmanRead};
+/// use bitstream_io::huffman::{compile_partial_compact_read_tree, HuffmanCodeError};
+/// // the code 1 1 1 has no symbol
+/// let tree = compile_partial_compact_read_tree(
+///     vec![('a', vec![0]),
+///          ('b', vec![1, 0]),
+///          ('c', vec![1, 1, 0])], 8).unwrap();
+/// let data = [0b10111_110];
+/// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'b');
+/// let err = reader.read_compact_huffman(&tree).unwrap_err();
+/// assert_eq!(HuffmanCodeError::from_io(&err),
+///            Some(&HuffmanCodeError::UnknownCode { consumed: 3 }));
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'c');
+/// ```
+pub fn compile_partial_compact_read_tree<E, T>(
+    values: Vec<(T, Vec<u8>)>,
+    lookup_bits: u32,
+) -> Result<CompactReadHuffmanTree<E, T>, HuffmanTreeError>
+where
+    E: Endianness,
+    T: Clone,
+{
+    assert!(
+        lookup_bits > 0 && lookup_bits <= 16,
+        "lookup size must be between 1 and 16 bits"
+    );
+    FinalHuffmanTree::new_partial(values)
+        .map(|tree| compile_compact_tree(&tree, lookup_bits))
+        .map_err(HuffmanTreeDiagnostic::into_error)
+}
+
+// Compiles a tree whose missing leaves are None
+// into a compact tree's lookup tables
+fn compile_compact_tree<E, T>(
+    tree: &FinalHuffmanTree<Option<T>>,
+    lookup_bits: u32,
+) -> CompactReadHuffmanTree<E, T>
+where
+    E: Endianness,
+    T: Clone,
+{
+    let mut entries = Vec::new();
+    let root_bits = compile_table::<E, T>(tree, lookup_bits, &mut entries).1;
+    CompactReadHuffmanTree {
+        entries: entries
+            .into_iter()
+            .map(|entry| entry.expect("unfilled table entry"))
+            .collect(),
+        root_bits,
+        phantom: PhantomData,
+    }
+}
+
+// Appends a lookup table for the given subtree
+// (and any tables it leads to) and returns its offset and size
+fn compile_table<E, T>(
+    tree: &FinalHuffmanTree<Option<T>>,
+    lookup_bits: u32,
+    entries: &mut Vec<Option<CompactEntry<T>>>,
+) -> (usize, u32)
+where
+    E: Endianness,
+    T: Clone,
+{
+    let bits = std::cmp::min(lookup_bits, tree.min_depth());
+    let offset = entries.len();
+    entries.extend((0..1 << bits).map(|_| None));
+    fill_table::<E, T>(tree, BitQueue::new(), bits, offset, lookup_bits, entries);
+    (offset, bits)
+}
+
+fn fill_table<E, T>(
+    tree: &FinalHuffmanTree<Option<T>>,
+    queue: BitQueue<E, u32>,
+    bits: u32,
+    offset: usize,
+    lookup_bits: u32,
+    entries: &mut Vec<Option<CompactEntry<T>>>,
+) where
+    E: Endianness,
+    T: Clone,
+{
+    match tree {
+        FinalHuffmanTree::Tree(ref bit0, ref bit1) if queue.len() < bits => {
+            for (bit, subtree) in [(0, bit0), (1, bit1)].iter() {
+                let mut queue = queue.clone();
+                queue.push(1, *bit);
+                fill_table(subtree, queue, bits, offset, lookup_bits, entries);
+            }
+        }
+        FinalHuffmanTree::Tree(_, _) => {
+            let (table, table_bits) = compile_table::<E, T>(tree, lookup_bits, entries);
+            entries[offset + queue.value() as usize] = Some(CompactEntry::Table {
+                offset: table,
+                bits: table_bits,
+            });
+        }
+        FinalHuffmanTree::Leaf(ref value) => {
+            entries[offset + queue.value() as usize] = Some(match value {
+                Some(value) => CompactEntry::Leaf(value.clone()),
+                None => CompactEntry::Unknown,
+            });
+        }
+    }
+}
+
+impl<E: Endianness, T: Clone> CompactReadHuffmanTree<E, T> {
+    /// Returns the approximate number of bytes
+    /// occupied by the tree's lookup tables
+    #[inline]
+    pub fn memory_size(&self) -> usize {
+        self.entries.len() * std::mem::size_of::<CompactEntry<T>>()
+    }
+
+    /// Reads the bits of a single code from the reader
+    /// and returns its symbol
+    ///
+    /// Gives up without reading any further
+    /// once the code is known to be longer than `max_bits`.
+    pub(crate) fn read<R: BitRead + ?Sized>(&self, reader: &mut R, max_bits: u32) -> io::Result<T> {
+        let mut offset = 0;
+        let mut bits = self.root_bits;
+        let mut consumed = 0;
+        loop {
+            // table widths never exceed the shortest code they lead to,
+            // so every code in this table is too long
+            if bits > max_bits - consumed {
+                return Err(HuffmanCodeError::CodeTooLong { consumed }.into());
+            }
+            let index: u32 = if bits > 0 { reader.read(bits)? } else { 0 };
+            consumed += bits;
+            match self.entries[offset + index as usize] {
+                CompactEntry::Leaf(ref value) => return Ok(value.clone()),
+                CompactEntry::Unknown => {
+                    return Err(HuffmanCodeError::UnknownCode { consumed }.into())
+                }
+                CompactEntry::Table {
+                    offset: table,
+                    bits: table_bits,
+                } => {
+                    offset = table;
+                    bits = table_bits;
+                }
+            }
+        }
+    }
+}
+
+impl<E: Endianness, T: Clone> Clone for CompactReadHuffmanTree<E, T> {
+    fn clone(&self) -> Self {
+        CompactReadHuffmanTree {
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| match entry {
+                    CompactEntry::Leaf(value) => CompactEntry::Leaf(value.clone()),
+                    CompactEntry::Unknown => CompactEntry::Unknown,
+                    CompactEntry::Table { offset, bits } => CompactEntry::Table {
+                        offset: *offset,
+                        bits: *bits,
+                    },
+                })
+                .collect(),
+            root_bits: self.root_bits,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: Endianness, T> fmt::Debug for CompactReadHuffmanTree<E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompactReadHuffmanTree")
+            .field("entries", &self.entries.len())
+            .field("root_bits", &self.root_bits)
+            .finish()
+    }
+}
+
+/// Compact trees are serialized as their lookup tables,
+/// which are endian-specific like the trees themselves
+/// and so should be deserialized in the same endianness.
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use bitstream_io::{BigEndian, BitReader, HuffmanRead};
+/// use bitstream_io::huffman::{compile_compact_read_tree, CompactReadHuffmanTree};
+/// let tree = compile_compact_read_tree::<BigEndian, char>(
+///     vec![('a', vec![0]),
+///          ('b', vec![1, 0]),
+///          ('c', vec![1, 1])], 1).unwrap();
+/// let cached = serde_json::to_vec(&tree).unwrap();
+/// let tree: CompactReadHuffmanTree<BigEndian, char> = serde_json::from_slice(&cached).unwrap();
+/// let mut reader = BitReader::endian(Cursor::new([0b10110_000]), BigEndian);
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'b');
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'c');
+/// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'a');
+/// ```
+#[cfg(feature = "serde")]
+impl<E: Endianness, T: serde::Serialize> serde::Serialize for CompactReadHuffmanTree<E, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("CompactReadHuffmanTree", 2)?;
+        state.serialize_field("root_bits", &self.root_bits)?;
+        state.serialize_field("entries", &self.entries)?;
+        state.end()
+    }
+}
+
+/// Deserializing fails if any lookup table
+/// lies outside of the tree's entries,
+/// or if a table could lead back to itself.
+#[cfg(feature = "serde")]
+impl<'de, E: Endianness, T: serde::Deserialize<'de>> serde::Deserialize<'de>
+    for CompactReadHuffmanTree<E, T>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "CompactReadHuffmanTree")]
+        struct Tables<T> {
+            root_bits: u32,
+            entries: Vec<CompactEntry<T>>,
+        }
+
+        let Tables { root_bits, entries } = Tables::deserialize(deserializer)?;
+        // tables are always appended after the entry leading to them,
+        // so each lookup moves strictly forward through the entries
+        let fits = |offset: usize, bits: u32| {
+            bits <= 16 && entries.len().saturating_sub(offset) >= 1 << bits
+        };
+        if !fits(0, root_bits) {
+            return Err(D::Error::custom("root table outside of tree"));
+        }
+        for (index, entry) in entries.iter().enumerate() {
+            if let CompactEntry::Table { offset, bits } = *entry {
+                if bits == 0 || offset <= index || !fits(offset, bits) {
+                    return Err(D::Error::custom("lookup table outside of tree"));
+                }
+            }
+        }
+        Ok(CompactReadHuffmanTree {
+            entries: entries.into_boxed_slice(),
+            root_bits,
+            phantom: PhantomData,
+        })
+    }
+}
+
 // A complete Huffman tree with no empty nodes
 enum FinalHuffmanTree<T: Clone> {
     Leaf(T),
@@ -134,14 +559,35 @@ enum FinalHuffmanTree<T: Clone> {
 }
 
 impl<T: Clone> FinalHuffmanTree<T> {
-    fn new(values: Vec<(T, Vec<u8>)>) -> Result<FinalHuffmanTree<T>, HuffmanTreeError> {
-        let mut tree = WipHuffmanTree::new_empty();
+    fn new(
+        values: Vec<(T, Vec<u8>)>,
+        max_bits: Option<u32>,
+    ) -> Result<FinalHuffmanTree<T>, HuffmanTreeDiagnostic<T>> {
+        WipHuffmanTree::from_values(values, max_bits)?.into_read_tree()
+    }
+}
 
-        for (symbol, code) in values {
-            tree.add(code.as_slice(), symbol)?;
+impl<T: Clone> FinalHuffmanTree<Option<T>> {
+    // Builds a tree in which codes without a symbol are None leaves
+    fn new_partial(
+        values: Vec<(T, Vec<u8>)>,
+    ) -> Result<FinalHuffmanTree<Option<T>>, HuffmanTreeDiagnostic<T>> {
+        match WipHuffmanTree::from_values(values, None)? {
+            WipHuffmanTree::Empty => Err(HuffmanTreeDiagnostic::MissingLeaf {
+                codes: vec![Vec::new()],
+            }),
+            tree => Ok(tree.into_partial_tree()),
         }
+    }
 
-        tree.into_read_tree()
+    // The length of the shortest code in the tree
+    fn min_depth(&self) -> u32 {
+        match self {
+            FinalHuffmanTree::Leaf(_) => 0,
+            FinalHuffmanTree::Tree(bit0, bit1) => {
+                1 + std::cmp::min(bit0.min_depth(), bit1.min_depth())
+            }
+        }
     }
 }
 
@@ -168,49 +614,161 @@ impl<T: Clone> WipHuffmanTree<T> {
         WipHuffmanTree::Tree(Box::new(Self::new_empty()), Box::new(Self::new_empty()))
     }
 
-    fn into_read_tree(self) -> Result<FinalHuffmanTree<T>, HuffmanTreeError> {
+    fn from_values(
+        values: Vec<(T, Vec<u8>)>,
+        max_bits: Option<u32>,
+    ) -> Result<WipHuffmanTree<T>, HuffmanTreeDiagnostic<T>> {
+        let mut tree = WipHuffmanTree::new_empty();
+
+        for (symbol, code) in values {
+            check_code(&symbol, &code, max_bits)?;
+            tree.add(code.as_slice(), 0, symbol)?;
+        }
+
+        Ok(tree)
+    }
+
+    fn into_read_tree(self) -> Result<FinalHuffmanTree<T>, HuffmanTreeDiagnostic<T>> {
+        let mut codes = Vec::new();
+        match self.finish(&mut Vec::new(), &mut codes) {
+            Some(tree) => Ok(tree),
+            None => Err(HuffmanTreeDiagnostic::MissingLeaf { codes }),
+        }
+    }
+
+    // Converts to a finalized tree if there are no empty nodes,
+    // gathering the codes of any empty nodes along the way
+    fn finish(self, code: &mut Vec<u8>, missing: &mut Vec<Vec<u8>>) -> Option<FinalHuffmanTree<T>> {
         match self {
-            WipHuffmanTree::Empty => Err(HuffmanTreeError::MissingLeaf),
-            WipHuffmanTree::Leaf(v) => Ok(FinalHuffmanTree::Leaf(v)),
+            WipHuffmanTree::Empty => {
+                missing.push(code.clone());
+                None
+            }
+            WipHuffmanTree::Leaf(v) => Some(FinalHuffmanTree::Leaf(v)),
             WipHuffmanTree::Tree(zero, one) => {
-                let zero = zero.into_read_tree()?;
-                let one = one.into_read_tree()?;
-                Ok(FinalHuffmanTree::Tree(Box::new(zero), Box::new(one)))
+                code.push(0);
+                let zero = zero.finish(code, missing);
+                code.pop();
+                code.push(1);
+                let one = one.finish(code, missing);
+                code.pop();
+                Some(FinalHuffmanTree::Tree(Box::new(zero?), Box::new(one?)))
             }
         }
     }
 
-    fn add(&mut self, code: &[u8], symbol: T) -> Result<(), HuffmanTreeError> {
+    fn into_partial_tree(self) -> FinalHuffmanTree<Option<T>> {
+        match self {
+            WipHuffmanTree::Empty => FinalHuffmanTree::Leaf(None),
+            WipHuffmanTree::Leaf(v) => FinalHuffmanTree::Leaf(Some(v)),
+            WipHuffmanTree::Tree(zero, one) => FinalHuffmanTree::Tree(
+                Box::new(zero.into_partial_tree()),
+                Box::new(one.into_partial_tree()),
+            ),
+        }
+    }
+
+    // Appends the bits leading to some leaf within the tree to code
+    // and returns that leaf's symbol
+    fn any_leaf(&self, code: &mut Vec<u8>) -> Option<&T> {
+        match self {
+            WipHuffmanTree::Empty => None,
+            WipHuffmanTree::Leaf(symbol) => Some(symbol),
+            WipHuffmanTree::Tree(zero, one) => {
+                code.push(0);
+                if let Some(symbol) = zero.any_leaf(code) {
+                    return Some(symbol);
+                }
+                code.pop();
+                code.push(1);
+                let symbol = one.any_leaf(code);
+                if symbol.is_none() {
+                    code.pop();
+                }
+                symbol
+            }
+        }
+    }
+
+    // Adds the remainder of code after its first depth bits,
+    // whose bits have already been checked
+    fn add(
+        &mut self,
+        code: &[u8],
+        depth: usize,
+        symbol: T,
+    ) -> Result<(), HuffmanTreeDiagnostic<T>> {
         match self {
             WipHuffmanTree::Empty => {
-                if code.is_empty() {
+                if depth == code.len() {
                     *self = WipHuffmanTree::new_leaf(symbol);
                     Ok(())
                 } else {
                     *self = WipHuffmanTree::new_tree();
-                    self.add(code, symbol)
+                    self.add(code, depth, symbol)
                 }
             }
-            WipHuffmanTree::Leaf(_) => Err(if code.is_empty() {
-                HuffmanTreeError::DuplicateLeaf
+            WipHuffmanTree::Leaf(other) => Err(if depth == code.len() {
+                HuffmanTreeDiagnostic::DuplicateLeaf {
+                    symbol,
+                    code: code.to_vec(),
+                    first: other.clone(),
+                }
             } else {
-                HuffmanTreeError::OrphanedLeaf
+                HuffmanTreeDiagnostic::OrphanedLeaf {
+                    symbol: other.clone(),
+                    code: code[0..depth].to_vec(),
+                    longer: symbol,
+                    longer_code: code.to_vec(),
+                }
             }),
+            WipHuffmanTree::Tree(..) if depth == code.len() => {
+                // every code added below a node ends in a leaf
+                let mut longer_code = code.to_vec();
+                let longer = self
+                    .any_leaf(&mut longer_code)
+                    .expect("tree node without leaves")
+                    .clone();
+                Err(HuffmanTreeDiagnostic::OrphanedLeaf {
+                    symbol,
+                    code: code.to_vec(),
+                    longer,
+                    longer_code,
+                })
+            }
             WipHuffmanTree::Tree(ref mut zero, ref mut one) => {
-                if code.is_empty() {
-                    Err(HuffmanTreeError::DuplicateLeaf)
+                if code[depth] == 0 {
+                    zero.add(code, depth + 1, symbol)
                 } else {
-                    match code[0] {
-                        0 => zero.add(&code[1..], symbol),
-                        1 => one.add(&code[1..], symbol),
-                        _ => Err(HuffmanTreeError::InvalidBit),
-                    }
+                    one.add(code, depth + 1, symbol)
                 }
             }
         }
     }
 }
 
+// Checks that every bit of a symbol's code is 0 or 1
+// and that the code is no longer than max_bits, if given
+fn check_code<T: Clone>(
+    symbol: &T,
+    code: &[u8],
+    max_bits: Option<u32>,
+) -> Result<(), HuffmanTreeDiagnostic<T>> {
+    if code.iter().any(|bit| *bit > 1) {
+        Err(HuffmanTreeDiagnostic::InvalidBit {
+            symbol: symbol.clone(),
+            code: code.to_vec(),
+        })
+    } else if max_bits.map(|max| code.len() > max as usize) == Some(true) {
+        Err(HuffmanTreeDiagnostic::CodeTooLong {
+            symbol: symbol.clone(),
+            code: code.to_vec(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
 /// An error type during Huffman tree compilation.
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum HuffmanTreeError {
@@ -222,6 +780,8 @@ pub enum HuffmanTreeError {
     DuplicateLeaf,
     /// A Huffman code is the prefix of some longer code
     OrphanedLeaf,
+    /// One of the digits in a Huffman code is too large for its radix
+    InvalidDigit,
 }
 
 impl fmt::Display for HuffmanTreeError {
@@ -231,12 +791,227 @@ impl fmt::Display for HuffmanTreeError {
             HuffmanTreeError::MissingLeaf => write!(f, "missing leaf node in specification"),
             HuffmanTreeError::DuplicateLeaf => write!(f, "duplicate leaf node in specification"),
             HuffmanTreeError::OrphanedLeaf => write!(f, "orphaned leaf node in specification"),
+            HuffmanTreeError::InvalidDigit => write!(f, "invalid digit in code"),
         }
     }
 }
 
 impl std::error::Error for HuffmanTreeError {}
 
+/// A detailed error during Huffman tree compilation
+/// which identifies the symbols and codes at fault.
+///
+/// Returned by `compile_read_tree_checked`, `compile_write_tree_checked`
+/// and `WriteHuffmanTree::verify_complete`.
+/// Codes are given as bits in the order they occur in the stream.
+///
+/// ## Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::huffman::{compile_read_tree_checked, HuffmanTreeDiagnostic};
+/// assert_eq!(
+///     compile_read_tree_checked::<BigEndian, char>(
+///         vec![('a', vec![0, 0]),
+///              ('b', vec![1, 0])], 8).unwrap_err(),
+///     HuffmanTreeDiagnostic::MissingLeaf { codes: vec![vec![0, 1], vec![1, 1]] });
+/// assert_eq!(
+///     compile_read_tree_checked::<BigEndian, char>(
+///         vec![('a', vec![0]),
+///              ('b', vec![1]),
+///              ('c', vec![1])], 8).unwrap_err(),
+///     HuffmanTreeDiagnostic::DuplicateLeaf { symbol: 'c', code: vec![1], first: 'b' });
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum HuffmanTreeDiagnostic<T> {
+    /// One of the bits in a symbol's code is not 0 or 1
+    InvalidBit {
+        /// The symbol whose code is invalid
+        symbol: T,
+        /// The code itself
+        code: Vec<u8>,
+    },
+    /// Some Huffman codes in the specification have no defined symbol
+    MissingLeaf {
+        /// Every code, in order, which is neither assigned a symbol
+        /// nor the prefix of a code which is
+        codes: Vec<Vec<u8>>,
+    },
+    /// The same Huffman code specifies multiple symbols
+    DuplicateLeaf {
+        /// The symbol given the code a second time
+        symbol: T,
+        /// The code itself
+        code: Vec<u8>,
+        /// The symbol the code was first given to
+        first: T,
+    },
+    /// A Huffman code is the prefix of some longer code
+    OrphanedLeaf {
+        /// The symbol of the shorter code
+        symbol: T,
+        /// The shorter code
+        code: Vec<u8>,
+        /// The symbol of a longer code which it is the prefix of
+        longer: T,
+        /// The longer code
+        longer_code: Vec<u8>,
+    },
+    /// The same symbol is given multiple codes for writing
+    DuplicateSymbol {
+        /// The symbol itself
+        symbol: T,
+        /// The symbol's second code
+        code: Vec<u8>,
+        /// The symbol's first code
+        first_code: Vec<u8>,
+    },
+    /// A symbol's code is longer than the maximum allowed
+    CodeTooLong {
+        /// The symbol whose code is too long
+        symbol: T,
+        /// The code itself
+        code: Vec<u8>,
+    },
+}
+
+impl<T> HuffmanTreeDiagnostic<T> {
+    // Reduces the diagnostic to the error of compilation
+    // without checks, which never limits code lengths
+    // or rejects repeated symbols
+    fn into_error(self) -> HuffmanTreeError {
+        match self {
+            HuffmanTreeDiagnostic::InvalidBit { .. } => HuffmanTreeError::InvalidBit,
+            HuffmanTreeDiagnostic::MissingLeaf { .. } => HuffmanTreeError::MissingLeaf,
+            HuffmanTreeDiagnostic::DuplicateLeaf { .. } => HuffmanTreeError::DuplicateLeaf,
+            HuffmanTreeDiagnostic::OrphanedLeaf { .. } => HuffmanTreeError::OrphanedLeaf,
+            HuffmanTreeDiagnostic::DuplicateSymbol { .. }
+            | HuffmanTreeDiagnostic::CodeTooLong { .. } => {
+                unreachable!("only checked compilation gives this diagnostic")
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for HuffmanTreeDiagnostic<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HuffmanTreeDiagnostic::InvalidBit { symbol, code } => {
+                write!(f, "invalid bit in code {:?} for symbol {:?}", code, symbol)
+            }
+            HuffmanTreeDiagnostic::MissingLeaf { codes } => write!(
+                f,
+                "missing leaf nodes in specification for codes {:?}",
+                codes
+            ),
+            HuffmanTreeDiagnostic::DuplicateLeaf {
+                symbol,
+                code,
+                first,
+            } => write!(
+                f,
+                "duplicate leaf node in specification for code {:?} \
+                 with symbols {:?} and {:?}",
+                code, first, symbol
+            ),
+            HuffmanTreeDiagnostic::OrphanedLeaf {
+                symbol,
+                code,
+                longer,
+                longer_code,
+            } => write!(
+                f,
+                "orphaned leaf node in specification for code {:?} with symbol {:?}, \
+                 which prefixes code {:?} with symbol {:?}",
+                code, symbol, longer_code, longer
+            ),
+            HuffmanTreeDiagnostic::DuplicateSymbol {
+                symbol,
+                code,
+                first_code,
+            } => write!(
+                f,
+                "duplicate symbol {:?} in specification with codes {:?} and {:?}",
+                symbol, first_code, code
+            ),
+            HuffmanTreeDiagnostic::CodeTooLong { symbol, code } => {
+                write!(f, "code {:?} for symbol {:?} is too long", code, symbol)
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for HuffmanTreeDiagnostic<T> {}
+
+/// An error reading a Huffman code from a stream.
+///
+/// These are returned as the inner error
+/// of an `io::Error` of kind `InvalidData`,
+/// from which `from_io` retrieves them.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum HuffmanCodeError {
+    /// The bits read so far aren't the start of any code in the tree
+    UnknownCode {
+        /// The number of bits consumed from the stream
+        consumed: u32,
+    },
+    /// Every code the bits read so far could be the start of
+    /// is longer than the maximum allowed
+    CodeTooLong {
+        /// The number of bits consumed from the stream
+        consumed: u32,
+    },
+}
+
+impl HuffmanCodeError {
+    /// Returns the number of bits consumed from the stream
+    /// before the error was encountered
+    #[inline]
+    pub fn consumed(&self) -> u32 {
+        match *self {
+            HuffmanCodeError::UnknownCode { consumed }
+            | HuffmanCodeError::CodeTooLong { consumed } => consumed,
+        }
+    }
+
+    /// Returns the `HuffmanCodeError` wrapped by an `io::Error`, if any
+    ///
+    /// ## Example
+    /// ```
+    /// use std::io;
+    /// use bitstream_io::huffman::HuffmanCodeError;
+    /// let err: io::Error = HuffmanCodeError::UnknownCode { consumed: 5 }.into();
+    /// assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    /// assert_eq!(HuffmanCodeError::from_io(&err).map(|e| e.consumed()), Some(5));
+    /// assert!(HuffmanCodeError::from_io(&io::ErrorKind::UnexpectedEof.into()).is_none());
+    /// ```
+    pub fn from_io(err: &io::Error) -> Option<&HuffmanCodeError> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<HuffmanCodeError>())
+    }
+}
+
+impl fmt::Display for HuffmanCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HuffmanCodeError::UnknownCode { consumed } => {
+                write!(f, "unknown Huffman code after {} bits", consumed)
+            }
+            HuffmanCodeError::CodeTooLong { consumed } => {
+                write!(f, "Huffman code too long after {} bits", consumed)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HuffmanCodeError {}
+
+impl From<HuffmanCodeError> for io::Error {
+    #[inline]
+    fn from(err: HuffmanCodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
 /// Given a vector of symbol/code pairs, compiles a Huffman tree
 /// for writing.
 ///
@@ -286,21 +1061,88 @@ where
     let mut map = BTreeMap::new();
 
     for (symbol, code) in values {
-        let mut encoded = Vec::new();
-        for bits in code.chunks(32) {
-            let mut acc = BitQueue::<E, u32>::new();
-            for bit in bits {
-                match *bit {
-                    0 => acc.push(1, 0),
-                    1 => acc.push(1, 1),
-                    _ => return Err(HuffmanTreeError::InvalidBit),
-                }
+        if code.iter().any(|bit| *bit > 1) {
+            return Err(HuffmanTreeError::InvalidBit);
+        }
+        map.entry(symbol).or_insert_with(|| encode_bits::<E>(&code));
+    }
+
+    Ok(WriteHuffmanTree {
+        map,
+        phantom: PhantomData,
+    })
+}
+
+/// Given a vector of symbol/code pairs, compiles a Huffman tree
+/// for writing the same way as `compile_write_tree`,
+/// but also requires that each symbol occurs only once,
+/// that no code is longer than `max_bits`
+/// and that no code is the same as or the prefix of another,
+/// so that everything written with the tree can be read back.
+/// As with `compile_write_tree`, not all possible codes
+/// need to be assigned a symbol.
+///
+/// # Errors
+///
+/// Returns a `HuffmanTreeDiagnostic` identifying the symbols
+/// and codes of the first problem found in the specification.
+///
+/// ## Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::huffman::{compile_write_tree_checked, HuffmanTreeDiagnostic};
+/// assert!(compile_write_tree_checked::<BigEndian, char>(
+///     vec![('a', vec![0]),
+///          ('b', vec![1, 0])], 8).is_ok());
+/// assert_eq!(
+///     compile_write_tree_checked::<BigEndian, char>(
+///         vec![('a', vec![0]),
+///              ('b', vec![1, 0]),
+///              ('a', vec![1, 1])], 8).unwrap_err(),
+///     HuffmanTreeDiagnostic::DuplicateSymbol {
+///         symbol: 'a',
+///         code: vec![1, 1],
+///         first_code: vec![0],
+///     });
+/// assert_eq!(
+///     compile_write_tree_checked::<BigEndian, char>(
+///         vec![('a', vec![1, 0]),
+///              ('b', vec![1])], 8).unwrap_err(),
+///     HuffmanTreeDiagnostic::OrphanedLeaf {
+///         symbol: 'b',
+///         code: vec![1],
+///         longer: 'a',
+///         longer_code: vec![1, 0],
+///     });
+/// ```
+pub fn compile_write_tree_checked<E, T>(
+    values: Vec<(T, Vec<u8>)>,
+    max_bits: u32,
+) -> Result<WriteHuffmanTree<E, T>, HuffmanTreeDiagnostic<T>>
+where
+    E: Endianness,
+    T: Ord + Clone,
+{
+    use std::collections::btree_map::Entry;
+
+    let mut map: BTreeMap<T, Box<[(u32, u32)]>> = BTreeMap::new();
+    let mut tree = WipHuffmanTree::new_empty();
+
+    for (symbol, code) in values {
+        check_code(&symbol, &code, Some(max_bits))?;
+        match map.entry(symbol.clone()) {
+            Entry::Occupied(entry) => {
+                return Err(HuffmanTreeDiagnostic::DuplicateSymbol {
+                    symbol,
+                    code,
+                    first_code: WriteHuffmanTree::<E, T>::code_bits(entry.get()),
+                })
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(encode_bits::<E>(&code));
             }
-            let len = acc.len();
-            encoded.push((len, acc.value()))
         }
-        map.entry(symbol)
-            .or_insert_with(|| encoded.into_boxed_slice());
+        tree.add(&code, 0, symbol)?;
     }
 
     Ok(WriteHuffmanTree {
@@ -309,6 +1151,222 @@ where
     })
 }
 
+// Packs a code whose bits have already been checked
+// into chunks of at most 32 bits for writing
+fn encode_bits<E: Endianness>(code: &[u8]) -> Box<[(u32, u32)]> {
+    code.chunks(32)
+        .map(|bits| {
+            let mut acc = BitQueue::<E, u32>::new();
+            for bit in bits {
+                acc.push(1, u32::from(*bit));
+            }
+            (acc.len(), acc.value())
+        })
+        .collect()
+}
+
+/// Given a vector of symbol/code pairs, returns the same pairs
+/// with the bits of each code in reverse order.
+///
+/// Codes given to `compile_read_tree` and `compile_write_tree`
+/// are always in the order their bits occur in the stream.
+/// Some formats document their codes in one bit order
+/// but transmit them in the other, such as codes stored
+/// as integers whose least-significant bit is sent first.
+/// Reversing such a specification before compiling it
+/// allows those codes to be used directly.
+/// Note that the reversed codes must still form a valid
+/// prefix code in order to compile a read tree.
+///
+/// ## Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{LittleEndian, BitReader, BitWriter, HuffmanRead, HuffmanWrite};
+/// use bitstream_io::huffman::{compile_read_tree, compile_write_tree, reverse_codes};
+/// // codes as documented, with their last-transmitted bit first
+/// let spec = vec![('a', vec![0]),
+///                 ('b', vec![0, 1]),
+///                 ('c', vec![0, 1, 1]),
+///                 ('d', vec![1, 1, 1])];
+/// assert!(compile_read_tree::<LittleEndian, char>(spec.clone()).is_err());
+/// assert_eq!(reverse_codes(spec.clone()),
+///            vec![('a', vec![0]),
+///                 ('b', vec![1, 0]),
+///                 ('c', vec![1, 1, 0]),
+///                 ('d', vec![1, 1, 1])]);
+/// let write_tree = compile_write_tree(reverse_codes(spec.clone())).unwrap();
+/// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+/// writer.write_huffman(&write_tree, 'b').unwrap();
+/// writer.write_huffman(&write_tree, 'c').unwrap();
+/// writer.write_huffman(&write_tree, 'd').unwrap();
+/// let data = writer.into_writer();
+/// assert_eq!(data, [0b11101101]);
+/// let read_tree = compile_read_tree(reverse_codes(spec)).unwrap();
+/// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+/// assert_eq!(reader.read_huffman(&read_tree).unwrap(), 'b');
+/// assert_eq!(reader.read_huffman(&read_tree).unwrap(), 'c');
+/// assert_eq!(reader.read_huffman(&read_tree).unwrap(), 'd');
+/// ```
+pub fn reverse_codes<T>(values: Vec<(T, Vec<u8>)>) -> Vec<(T, Vec<u8>)> {
+    values
+        .into_iter()
+        .map(|(symbol, mut code)| {
+            code.reverse();
+            (symbol, code)
+        })
+        .collect()
+}
+
+/// Given a vector of symbol/code pairs whose codes are digits
+/// in radix `1 << digit_bits`, compiles a Huffman tree for reading.
+///
+/// Each digit of a code occupies `digit_bits` bits in the stream,
+/// laid out the same way `read(digit_bits)` would read it
+/// for the tree's endianness.
+/// As with `compile_read_tree`, all possible codes must be
+/// assigned some symbol.
+///
+/// ## Examples
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BigEndian, BitReader, HuffmanRead};
+/// use bitstream_io::huffman::compile_read_tree_radix;
+/// let tree = compile_read_tree_radix(
+///     2,
+///     vec![('a', vec![0]),
+///          ('b', vec![1]),
+///          ('c', vec![2]),
+///          ('d', vec![3, 0]),
+///          ('e', vec![3, 1]),
+///          ('f', vec![3, 2]),
+///          ('g', vec![3, 3])]).unwrap();
+/// let data = [0b01_11_10_10];
+/// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+/// assert_eq!(reader.read_huffman(&tree).unwrap(), 'b');
+/// assert_eq!(reader.read_huffman(&tree).unwrap(), 'f');
+/// assert_eq!(reader.read_huffman(&tree).unwrap(), 'c');
+/// ```
+///
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{LittleEndian, BitReader, HuffmanRead};
+/// use bitstream_io::huffman::compile_read_tree_radix;
+/// let tree = compile_read_tree_radix(
+///     2,
+///     vec![('a', vec![0]),
+///          ('b', vec![1]),
+///          ('c', vec![2]),
+///          ('d', vec![3, 0]),
+///          ('e', vec![3, 1]),
+///          ('f', vec![3, 2]),
+///          ('g', vec![3, 3])]).unwrap();
+/// let data = [0b10_10_11_01];
+/// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+/// assert_eq!(reader.read_huffman(&tree).unwrap(), 'b');
+/// assert_eq!(reader.read_huffman(&tree).unwrap(), 'f');
+/// assert_eq!(reader.read_huffman(&tree).unwrap(), 'c');
+/// ```
+pub fn compile_read_tree_radix<E, T>(
+    digit_bits: u32,
+    values: Vec<(T, Vec<u8>)>,
+) -> Result<Box<[ReadHuffmanTree<E, T>]>, HuffmanTreeError>
+where
+    E: Endianness,
+    T: Clone,
+{
+    compile_read_tree(expand_digits::<E, T>(digit_bits, values)?)
+}
+
+/// Given a vector of symbol/code pairs whose codes are digits
+/// in radix `1 << digit_bits`, compiles a Huffman tree for writing.
+///
+/// Each digit of a code is written as a chunk of `digit_bits` bits,
+/// the same way `write(digit_bits, digit)` would write it
+/// for the tree's endianness.
+///
+/// ## Examples
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, HuffmanWrite};
+/// use bitstream_io::huffman::compile_write_tree_radix;
+/// let tree = compile_write_tree_radix(
+///     2,
+///     vec![('a', vec![0]),
+///          ('b', vec![1]),
+///          ('c', vec![2]),
+///          ('d', vec![3, 0]),
+///          ('e', vec![3, 1]),
+///          ('f', vec![3, 2]),
+///          ('g', vec![3, 3])]).unwrap();
+/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+/// writer.write_huffman(&tree, 'b').unwrap();
+/// writer.write_huffman(&tree, 'f').unwrap();
+/// writer.write_huffman(&tree, 'c').unwrap();
+/// assert_eq!(writer.into_writer(), [0b01_11_10_10]);
+/// ```
+///
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{LittleEndian, BitWriter, HuffmanWrite};
+/// use bitstream_io::huffman::compile_write_tree_radix;
+/// let tree = compile_write_tree_radix(
+///     2,
+///     vec![('a', vec![0]),
+///          ('b', vec![1]),
+///          ('c', vec![2]),
+///          ('d', vec![3, 0]),
+///          ('e', vec![3, 1]),
+///          ('f', vec![3, 2]),
+///          ('g', vec![3, 3])]).unwrap();
+/// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+/// writer.write_huffman(&tree, 'b').unwrap();
+/// writer.write_huffman(&tree, 'f').unwrap();
+/// writer.write_huffman(&tree, 'c').unwrap();
+/// assert_eq!(writer.into_writer(), [0b10_10_11_01]);
+/// ```
+pub fn compile_write_tree_radix<E, T>(
+    digit_bits: u32,
+    values: Vec<(T, Vec<u8>)>,
+) -> Result<WriteHuffmanTree<E, T>, HuffmanTreeError>
+where
+    E: Endianness,
+    T: Ord + Clone,
+{
+    compile_write_tree(expand_digits::<E, T>(digit_bits, values)?)
+}
+
+/// Converts codes of `digit_bits`-sized digits
+/// into codes of individual bits in stream order
+fn expand_digits<E, T>(
+    digit_bits: u32,
+    values: Vec<(T, Vec<u8>)>,
+) -> Result<Vec<(T, Vec<u8>)>, HuffmanTreeError>
+where
+    E: Endianness,
+{
+    assert!(
+        (1..=8).contains(&digit_bits),
+        "digit bits must be between 1 and 8"
+    );
+
+    values
+        .into_iter()
+        .map(|(symbol, code)| {
+            let mut bits = Vec::with_capacity(code.len() * digit_bits as usize);
+            for &digit in code.iter() {
+                if digit_bits < 8 && (digit >> digit_bits) != 0 {
+                    return Err(HuffmanTreeError::InvalidDigit);
+                }
+                let mut queue = BitQueue::<E, u8>::from_value(digit, digit_bits);
+                while !queue.is_empty() {
+                    bits.push(queue.pop(1));
+                }
+            }
+            Ok((symbol, bits))
+        })
+        .collect()
+}
+
 /// A compiled Huffman tree for use with the `write_huffman` method.
 /// Returned by `compiled_write_tree`.
 pub struct WriteHuffmanTree<E: Endianness, T: Ord> {
@@ -316,6 +1374,61 @@ pub struct WriteHuffmanTree<E: Endianness, T: Ord> {
     phantom: PhantomData<E>,
 }
 
+impl<E: Endianness, T: Ord + Clone> Clone for WriteHuffmanTree<E, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        WriteHuffmanTree {
+            map: self.map.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: Endianness, T: Ord + fmt::Debug> fmt::Debug for WriteHuffmanTree<E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WriteHuffmanTree")
+            .field("map", &self.map)
+            .finish()
+    }
+}
+
+/// Write trees are serialized as a list of symbol/code pairs
+/// in symbol order, in the same form given to `compile_write_tree`,
+/// and are compiled again when deserialized.
+///
+/// # Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::huffman::{compile_write_tree, WriteHuffmanTree};
+/// let tree = compile_write_tree::<BigEndian, char>(
+///     vec![('a', vec![0]),
+///          ('b', vec![1, 0]),
+///          ('c', vec![1, 1])]).unwrap();
+/// let json = serde_json::to_string(&tree).unwrap();
+/// assert_eq!(json, r#"[["a",[0]],["b",[1,0]],["c",[1,1]]]"#);
+/// let tree: WriteHuffmanTree<BigEndian, char> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(tree.code(&'b'), Some((2, 0b10)));
+/// ```
+#[cfg(feature = "serde")]
+impl<E: Endianness, T: Ord + Clone + serde::Serialize> serde::Serialize for WriteHuffmanTree<E, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(|(symbol, bits, _)| (symbol, bits)))
+    }
+}
+
+/// Deserializing fails if the symbol/code pairs
+/// can't be compiled into a tree.
+#[cfg(feature = "serde")]
+impl<'de, E, T> serde::Deserialize<'de> for WriteHuffmanTree<E, T>
+where
+    E: Endianness,
+    T: Ord + Clone + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        compile_write_tree(Vec::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
 impl<E: Endianness, T: Ord + Clone> WriteHuffmanTree<E, T> {
     /// Returns true if symbol is in tree.
     #[inline]
@@ -330,4 +1443,555 @@ impl<E: Endianness, T: Ord + Clone> WriteHuffmanTree<E, T> {
     pub fn get(&self, symbol: &T) -> impl Iterator<Item = &(u32, u32)> {
         self.map[symbol].iter()
     }
+
+    /// Given symbol, returns the total length of its code in bits,
+    /// or `None` if the symbol is not found.
+    ///
+    /// ## Example
+    /// ```
+    /// use bitstream_io::BigEndian;
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree::<BigEndian, char>(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let total: u32 = "abacad".chars().map(|c| tree.code_len(&c).unwrap()).sum();
+    /// assert_eq!(total, 11);
+    /// assert_eq!(tree.code_len(&'e'), None);
+    /// ```
+    #[inline]
+    pub fn code_len(&self, symbol: &T) -> Option<u32> {
+        self.map
+            .get(symbol)
+            .map(|code| code.iter().map(|(bits, _)| bits).sum())
+    }
+
+    /// Checks that the tree's codes would also compile into a read tree,
+    /// which requires every possible code to be assigned some symbol.
+    ///
+    /// # Errors
+    ///
+    /// Returns `HuffmanTreeDiagnostic::MissingLeaf` listing
+    /// every code without a symbol,
+    /// or a diagnostic for the first pair of symbols whose codes conflict,
+    /// taking symbols in order.
+    ///
+    /// ## Example
+    /// ```
+    /// use bitstream_io::BigEndian;
+    /// use bitstream_io::huffman::{compile_write_tree, HuffmanTreeDiagnostic};
+    /// let tree = compile_write_tree::<BigEndian, char>(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0, 1]),
+    ///          ('c', vec![1, 1])]).unwrap();
+    /// assert_eq!(tree.verify_complete(),
+    ///            Err(HuffmanTreeDiagnostic::MissingLeaf { codes: vec![vec![1, 0, 0]] }));
+    /// let tree = compile_write_tree::<BigEndian, char>(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1])]).unwrap();
+    /// assert!(tree.verify_complete().is_ok());
+    /// ```
+    pub fn verify_complete(&self) -> Result<(), HuffmanTreeDiagnostic<T>> {
+        let mut tree = WipHuffmanTree::new_empty();
+        for (symbol, chunks) in self.map.iter() {
+            tree.add(&Self::code_bits(chunks), 0, symbol.clone())?;
+        }
+        tree.into_read_tree().map(|_| ())
+    }
+
+    /// Given symbol, returns the length of its code in bits
+    /// and the code itself as an integer whose most-significant bit
+    /// is the first one written to the stream,
+    /// or `None` if the symbol is not found
+    /// or its code is longer than 64 bits.
+    ///
+    /// ## Example
+    /// ```
+    /// use bitstream_io::LittleEndian;
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree::<LittleEndian, char>(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// assert_eq!(tree.code(&'a'), Some((1, 0b0)));
+    /// assert_eq!(tree.code(&'c'), Some((3, 0b110)));
+    /// assert_eq!(tree.code(&'e'), None);
+    /// ```
+    pub fn code(&self, symbol: &T) -> Option<(u32, u64)> {
+        let bits = Self::code_bits(self.map.get(symbol)?);
+        if bits.len() <= 64 {
+            Some((
+                bits.len() as u32,
+                bits.iter()
+                    .fold(0, |code, bit| (code << 1) | u64::from(*bit)),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over every symbol in the tree, in order,
+    /// along with the bits of its code in the order they're written
+    /// to the stream and the code's length in bits.
+    ///
+    /// The bits are given the same way as to `compile_write_tree`,
+    /// which allows a tree to be described in a stream's header.
+    ///
+    /// ## Example
+    /// ```
+    /// use bitstream_io::BigEndian;
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let spec = vec![('d', vec![1, 1, 1]),
+    ///                 ('c', vec![1, 1, 0]),
+    ///                 ('b', vec![1, 0]),
+    ///                 ('a', vec![0])];
+    /// let tree = compile_write_tree::<BigEndian, char>(spec).unwrap();
+    /// // the expected length of a code, given each symbol's probability
+    /// let probability = |symbol: char| match symbol {
+    ///     'a' => 0.5,
+    ///     'b' => 0.25,
+    ///     _ => 0.125,
+    /// };
+    /// let expected: f64 = tree.iter()
+    ///     .map(|(symbol, _, len)| probability(*symbol) * f64::from(len))
+    ///     .sum();
+    /// assert_eq!(expected, 1.75);
+    /// let mut table = Vec::new();
+    /// for (symbol, bits, _) in tree.iter() {
+    ///     let bits: String = bits.iter().map(|bit| if *bit == 0 { '0' } else { '1' }).collect();
+    ///     table.push(format!("{} {}", symbol, bits));
+    /// }
+    /// assert_eq!(table, ["a 0", "b 10", "c 110", "d 111"]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&T, Vec<u8>, u32)> {
+        self.map.iter().map(|(symbol, chunks)| {
+            let bits = Self::code_bits(chunks);
+            let len = bits.len() as u32;
+            (symbol, bits, len)
+        })
+    }
+
+    // Unpacks a code's chunks into its bits in stream order
+    fn code_bits(chunks: &[(u32, u32)]) -> Vec<u8> {
+        let mut code = Vec::new();
+        for (bits, value) in chunks.iter() {
+            let mut queue = BitQueue::<E, u32>::from_value(*value, *bits);
+            while !queue.is_empty() {
+                code.push(queue.pop(1) as u8);
+            }
+        }
+        code
+    }
+}
+
+/// A Huffman tree which adapts to the symbols coded with it,
+/// for use with the `write_adaptive_huffman`
+/// and `read_adaptive_huffman` methods.
+///
+/// This implements the FGK algorithm.
+/// The tree begins empty and each symbol's code is updated
+/// after every symbol is coded, so that frequent symbols
+/// get progressively shorter codes without any table
+/// being transmitted.
+/// A symbol's first occurrence is written as an escape code
+/// followed by the symbol itself as an unsigned value
+/// of the tree's symbol size.
+///
+/// Encoders and decoders must start from identical trees
+/// and code exactly the same sequence of symbols.
+///
+/// # Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+/// use bitstream_io::huffman::AdaptiveHuffmanTree;
+///
+/// let mut tree = AdaptiveHuffmanTree::new(8);
+/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+/// for b in b"abracadabra" {
+///     writer.write_adaptive_huffman(&mut tree, u32::from(*b)).unwrap();
+/// }
+/// writer.byte_align().unwrap();
+/// let data = writer.into_writer();
+/// assert!(data.len() < 11);
+///
+/// let mut tree = AdaptiveHuffmanTree::new(8);
+/// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+/// for b in b"abracadabra" {
+///     assert_eq!(reader.read_adaptive_huffman(&mut tree).unwrap(), u32::from(*b));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct AdaptiveHuffmanTree {
+    symbol_bits: u32,
+    nodes: Vec<AdaptiveNode>,
+    // node indexes ordered from the root downward,
+    // such that weights never increase
+    order: Vec<usize>,
+    leaves: BTreeMap<u32, usize>,
+    nyt: usize,
+}
+
+#[derive(Clone, Debug)]
+struct AdaptiveNode {
+    weight: u64,
+    rank: usize,
+    parent: Option<usize>,
+    children: Option<[usize; 2]>,
+    symbol: Option<u32>,
+}
+
+impl AdaptiveHuffmanTree {
+    /// Creates an empty tree for symbols of the given number of bits
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol_bits` is 0 or greater than 32.
+    pub fn new(symbol_bits: u32) -> AdaptiveHuffmanTree {
+        assert!(
+            symbol_bits > 0 && symbol_bits <= 32,
+            "symbol size must be between 1 and 32 bits"
+        );
+        AdaptiveHuffmanTree {
+            symbol_bits,
+            nodes: vec![AdaptiveNode {
+                weight: 0,
+                rank: 0,
+                parent: None,
+                children: None,
+                symbol: None,
+            }],
+            order: vec![0],
+            leaves: BTreeMap::new(),
+            nyt: 0,
+        }
+    }
+
+    /// Returns the size of the tree's symbols, in bits
+    #[inline]
+    pub fn symbol_bits(&self) -> u32 {
+        self.symbol_bits
+    }
+
+    /// Returns the number of times the given symbol has been coded
+    #[inline]
+    pub fn frequency(&self, symbol: u32) -> u64 {
+        self.leaves
+            .get(&symbol)
+            .map(|&node| self.nodes[node].weight)
+            .unwrap_or(0)
+    }
+
+    /// Writes the symbol's current code to the writer
+    /// and updates the tree
+    pub(crate) fn write<W: BitWrite + ?Sized>(
+        &mut self,
+        writer: &mut W,
+        symbol: u32,
+    ) -> io::Result<()> {
+        if (self.symbol_bits < 32) && (symbol >= (1 << self.symbol_bits)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ));
+        }
+        let leaf = self.leaves.get(&symbol).copied();
+        let mut code = Vec::new();
+        let mut node = leaf.unwrap_or(self.nyt);
+        while let Some(parent) = self.nodes[node].parent {
+            code.push(matches!(self.nodes[parent].children, Some([_, right]) if right == node));
+            node = parent;
+        }
+        for bit in code.iter().rev() {
+            writer.write_bit(*bit)?;
+        }
+        if leaf.is_none() {
+            writer.write(self.symbol_bits, symbol)?;
+        }
+        self.update(symbol);
+        Ok(())
+    }
+
+    /// Reads a symbol using the current codes from the reader
+    /// and updates the tree
+    pub(crate) fn read<R: BitRead + ?Sized>(&mut self, reader: &mut R) -> io::Result<u32> {
+        let mut node = 0;
+        while let Some(children) = self.nodes[node].children {
+            node = children[if reader.read_bit()? { 1 } else { 0 }];
+        }
+        let symbol = match self.nodes[node].symbol {
+            Some(symbol) => symbol,
+            None => reader.read(self.symbol_bits)?,
+        };
+        self.update(symbol);
+        Ok(symbol)
+    }
+
+    fn update(&mut self, symbol: u32) {
+        let mut node = match self.leaves.get(&symbol) {
+            Some(&leaf) => leaf,
+            None => {
+                // the current NYT node gains a new NYT node
+                // and the new symbol's leaf as its children
+                let parent = self.nyt;
+                let rank = self.nodes[parent].rank;
+                let leaf = self.push_node(parent, rank + 1, Some(symbol));
+                let nyt = self.push_node(parent, rank + 2, None);
+                self.nodes[parent].children = Some([nyt, leaf]);
+                self.nyt = nyt;
+                self.leaves.insert(symbol, leaf);
+                leaf
+            }
+        };
+        loop {
+            let weight = self.nodes[node].weight;
+            let mut leader = self.nodes[node].rank;
+            while leader > 0 && self.nodes[self.order[leader - 1]].weight == weight {
+                leader -= 1;
+            }
+            let leader = self.order[leader];
+            if leader != node && Some(leader) != self.nodes[node].parent {
+                self.swap(node, leader);
+            }
+            self.nodes[node].weight += 1;
+            match self.nodes[node].parent {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+    }
+
+    fn push_node(&mut self, parent: usize, rank: usize, symbol: Option<u32>) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(AdaptiveNode {
+            weight: 0,
+            rank,
+            parent: Some(parent),
+            children: None,
+            symbol,
+        });
+        self.order.push(index);
+        index
+    }
+
+    /// Exchanges the positions of two nodes in the tree,
+    /// neither of which may be the root or an ancestor of the other
+    fn swap(&mut self, a: usize, b: usize) {
+        let parent_a = self.nodes[a].parent.expect("swapped root");
+        let parent_b = self.nodes[b].parent.expect("swapped root");
+        for &(parent, from, to) in [(parent_a, a, b), (parent_b, b, a)].iter() {
+            if let Some(children) = self.nodes[parent].children.as_mut() {
+                if parent_a == parent_b {
+                    children.swap(0, 1);
+                    break;
+                }
+                for child in children.iter_mut() {
+                    if *child == from {
+                        *child = to;
+                    }
+                }
+            }
+        }
+        self.nodes[a].parent = Some(parent_b);
+        self.nodes[b].parent = Some(parent_a);
+        let (rank_a, rank_b) = (self.nodes[a].rank, self.nodes[b].rank);
+        self.order.swap(rank_a, rank_b);
+        self.nodes[a].rank = rank_b;
+        self.nodes[b].rank = rank_a;
+    }
+}
+
+/// For tallying how often each symbol occurs
+/// in order to build Huffman codes suited to them.
+///
+/// Symbols are counted with `write_symbol`, or collected
+/// from an iterator such as the symbols of a `SymbolRecorder`,
+/// without needing a tree beforehand.
+/// `huffman_codes` then builds optimal canonical codes
+/// in the form taken by `compile_write_tree` and `compile_read_tree`,
+/// so a two-pass encoder may gather statistics, build its table
+/// and play back its recorded symbols using that table.
+///
+/// # Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::huffman::{compile_write_tree, FrequencyCounter};
+/// let mut counter = FrequencyCounter::new();
+/// for (symbol, count) in [('a', 45), ('b', 13), ('c', 12), ('d', 16), ('e', 9), ('f', 5)] {
+///     for _ in 0..count {
+///         counter.write_symbol(symbol);
+///     }
+/// }
+/// assert_eq!(counter.frequency(&'a'), 45);
+/// assert_eq!(counter.total(), 100);
+///
+/// let codes = counter.huffman_codes();
+/// assert_eq!(
+///     codes,
+///     [('a', vec![0]),
+///      ('b', vec![1, 0, 0]),
+///      ('c', vec![1, 0, 1]),
+///      ('d', vec![1, 1, 0]),
+///      ('e', vec![1, 1, 1, 0]),
+///      ('f', vec![1, 1, 1, 1])],
+/// );
+/// let tree = compile_write_tree::<BigEndian, char>(codes).unwrap();
+/// let bits: u64 = counter
+///     .iter()
+///     .map(|(symbol, count)| u64::from(tree.code_len(symbol).unwrap()) * count)
+///     .sum();
+/// assert_eq!(bits, 224);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrequencyCounter<T: Ord> {
+    counts: BTreeMap<T, u64>,
+}
+
+impl<T: Ord> Default for FrequencyCounter<T> {
+    #[inline]
+    fn default() -> Self {
+        FrequencyCounter::new()
+    }
+}
+
+impl<T: Ord> FrequencyCounter<T> {
+    /// Creates new counter with no symbols counted
+    #[inline]
+    pub fn new() -> Self {
+        FrequencyCounter {
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Counts one occurrence of the given symbol
+    #[inline]
+    pub fn write_symbol(&mut self, symbol: T) {
+        let count = self.counts.entry(symbol).or_insert(0);
+        *count = count.saturating_add(1);
+    }
+
+    /// Returns the number of times the given symbol has been counted
+    #[inline]
+    pub fn frequency(&self, symbol: &T) -> u64 {
+        self.counts.get(symbol).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of symbols counted, including repeats
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.counts
+            .values()
+            .fold(0, |total, count| total.saturating_add(*count))
+    }
+
+    /// Returns the number of distinct symbols counted
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns true if no symbols have been counted
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Returns an iterator over each distinct symbol
+    /// and the number of times it has been counted, in symbol order
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&T, u64)> {
+        self.counts.iter().map(|(symbol, count)| (symbol, *count))
+    }
+
+    /// Discards all counts
+    #[inline]
+    pub fn clear(&mut self) {
+        self.counts.clear()
+    }
+}
+
+impl<T: Ord + Clone> FrequencyCounter<T> {
+    /// Builds optimal Huffman codes for the symbols counted,
+    /// returned in the form taken by `compile_write_tree`
+    /// and `compile_read_tree`.
+    ///
+    /// The codes are canonical: they are assigned in order of length
+    /// and then of symbol, with each code numerically following
+    /// the one before, so they may be rebuilt from their lengths alone.
+    /// A single distinct symbol is given an empty code,
+    /// and no codes are returned if nothing has been counted.
+    pub fn huffman_codes(&self) -> Vec<(T, Vec<u8>)> {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        // leaves occupy the first nodes, in symbol order,
+        // and each node points to the node which merges it
+        let mut parents: Vec<Option<usize>> = vec![None; self.counts.len()];
+        let mut heap: BinaryHeap<Reverse<(u64, usize)>> = self
+            .counts
+            .values()
+            .enumerate()
+            .map(|(node, count)| Reverse((*count, node)))
+            .collect();
+        while let (Some(Reverse((weight0, node0))), Some(Reverse((weight1, node1)))) =
+            (heap.pop(), heap.pop())
+        {
+            let merged = parents.len();
+            parents.push(None);
+            parents[node0] = Some(merged);
+            parents[node1] = Some(merged);
+            heap.push(Reverse((weight0.saturating_add(weight1), merged)));
+        }
+
+        let mut lengths: Vec<(usize, &T)> = self
+            .counts
+            .keys()
+            .enumerate()
+            .map(|(node, symbol)| {
+                let mut length = 0;
+                let mut node = node;
+                while let Some(parent) = parents[node] {
+                    length += 1;
+                    node = parent;
+                }
+                (length, symbol)
+            })
+            .collect();
+        lengths.sort_by_key(|(length, _)| *length);
+
+        let mut codes: Vec<(T, Vec<u8>)> = Vec::with_capacity(lengths.len());
+        for (length, symbol) in lengths {
+            // each code is one more than the last, extended with 0 bits
+            let mut code = codes
+                .last()
+                .map(|(_, last)| last.clone())
+                .unwrap_or_default();
+            if let Some(zero) = code.iter().rposition(|bit| *bit == 0) {
+                code.truncate(zero + 1);
+                code[zero] = 1;
+            }
+            code.resize(length, 0);
+            codes.push((symbol.clone(), code));
+        }
+        codes
+    }
+}
+
+impl<T: Ord> Extend<T> for FrequencyCounter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, symbols: I) {
+        symbols
+            .into_iter()
+            .for_each(|symbol| self.write_symbol(symbol))
+    }
+}
+
+impl<T: Ord> std::iter::FromIterator<T> for FrequencyCounter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(symbols: I) -> Self {
+        let mut counter = FrequencyCounter::new();
+        counter.extend(symbols);
+        counter
+    }
 }
@@ -0,0 +1,380 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Wrappers which reorder the bits of a stream in fixed-size blocks.
+//!
+//! Formats protected by forward error correction commonly
+//! interleave their coded bits, so that a burst of errors
+//! on the channel is spread across many codewords
+//! instead of overwhelming one of them.
+//! `InterleavingWriter` gathers each block of bits written to it
+//! and passes them along in the order given by an `Interleaver`,
+//! while `DeinterleavingReader` reads each block from the stream
+//! it wraps and returns its bits in their original order.
+//!
+//! ## Example
+//!
+//! Interleaving with a 3 row by 4 column block,
+//! where bits are written across rows and sent down columns.
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::interleave::{DeinterleavingReader, Interleaver, InterleavingWriter};
+//!
+//! let interleaver = Interleaver::rows_columns(3, 4);
+//! let mut writer = InterleavingWriter::endian(
+//!     BitWriter::endian(Vec::new(), BigEndian),
+//!     BigEndian,
+//!     interleaver.clone(),
+//! );
+//! writer.write(4, 0b1111u8).unwrap();
+//! writer.write(4, 0b0000u8).unwrap();
+//! writer.write(4, 0b1010u8).unwrap();
+//! writer.write(4, 0b1111u8).unwrap();
+//! // the final block is padded with 0 bits
+//! let writer = writer.finish().unwrap();
+//! assert_eq!(writer.into_writer(), [0b1011_0010, 0b1100_1001, 0b0010_0100]);
+//!
+//! let data = [0b1011_0010, 0b1100_1001, 0b0010_0100];
+//! let mut reader = DeinterleavingReader::endian(
+//!     BitReader::endian(&data[..], BigEndian),
+//!     BigEndian,
+//!     interleaver,
+//! );
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0b1111_0000_1010);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{BitQueue, Endianness, Numeric, SignedNumeric};
+use std::io;
+use std::marker::PhantomData;
+
+/// The order in which the bits of each block are sent
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interleaver {
+    permutation: Box<[usize]>,
+}
+
+impl Interleaver {
+    /// Builds an interleaver from a permutation of a block's bits,
+    /// in which the bit sent at position `i` of the block
+    /// is the bit written at position `permutation[i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the permutation is empty
+    /// or doesn't contain each position of the block exactly once.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitstream_io::interleave::{Interleaver, InterleavingWriter};
+    /// // reverse each group of 4 bits
+    /// let interleaver = Interleaver::new(vec![3, 2, 1, 0]);
+    /// let mut writer = InterleavingWriter::endian(
+    ///     BitWriter::endian(Vec::new(), BigEndian),
+    ///     BigEndian,
+    ///     interleaver,
+    /// );
+    /// writer.write(8, 0b1000_1100u8).unwrap();
+    /// assert_eq!(writer.finish().unwrap().into_writer(), [0b0001_0011]);
+    /// ```
+    pub fn new(permutation: Vec<usize>) -> Self {
+        assert!(!permutation.is_empty(), "permutation must not be empty");
+        let mut seen = vec![false; permutation.len()];
+        for &position in permutation.iter() {
+            match seen.get_mut(position) {
+                Some(seen @ false) => *seen = true,
+                _ => panic!("invalid permutation"),
+            }
+        }
+        Interleaver {
+            permutation: permutation.into_boxed_slice(),
+        }
+    }
+
+    /// Builds a block interleaver whose bits are written
+    /// across `rows` rows of `columns` bits each,
+    /// and sent down each column in turn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` or `columns` is 0.
+    pub fn rows_columns(rows: usize, columns: usize) -> Self {
+        assert!(rows > 0 && columns > 0, "rows and columns must be nonzero");
+        Interleaver {
+            permutation: (0..rows * columns)
+                .map(|i| (i % rows) * columns + i / rows)
+                .collect(),
+        }
+    }
+
+    /// Returns the number of bits in each block
+    #[inline]
+    pub fn block_size(&self) -> usize {
+        self.permutation.len()
+    }
+
+    /// Returns the position written of each bit sent
+    #[inline]
+    pub fn permutation(&self) -> &[usize] {
+        &self.permutation
+    }
+}
+
+/// A bitstream wrapper which reorders each block of bits written to it.
+///
+/// Bits are held until their block is complete.
+/// Alignment is counted from the number of bits written to the wrapper,
+/// so `byte_align` pads the data being interleaved.
+#[derive(Clone, Debug)]
+pub struct InterleavingWriter<W: BitWrite, E: Endianness> {
+    writer: W,
+    interleaver: Interleaver,
+    block: Vec<bool>,
+    written: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<W: BitWrite, E: Endianness> InterleavingWriter<W, E> {
+    /// Wraps an InterleavingWriter around something that implements `BitWrite`
+    #[inline]
+    pub fn new(writer: W, interleaver: Interleaver) -> Self {
+        InterleavingWriter {
+            writer,
+            block: Vec::with_capacity(interleaver.block_size()),
+            interleaver,
+            written: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps an InterleavingWriter around something that implements `BitWrite`
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(writer: W, _endian: E, interleaver: Interleaver) -> Self {
+        InterleavingWriter::new(writer, interleaver)
+    }
+
+    /// Returns the number of bits waiting for their block to complete
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.block.len()
+    }
+
+    /// Provides mutable reference to internal writer
+    ///
+    /// Bits written to it directly are not interleaved,
+    /// and are written ahead of any bits still pending.
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Pads any incomplete block with 0 bits, writes it,
+    /// and returns the internal writer
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn finish(mut self) -> io::Result<W> {
+        while !self.block.is_empty() {
+            self.write_bit(false)?;
+        }
+        Ok(self.writer)
+    }
+
+    /// Unwraps internal writer and disposes of InterleavingWriter.
+    /// Any bits of an incomplete block are discarded.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: BitWrite, E: Endianness> BitWrite for InterleavingWriter<W, E> {
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.block.push(bit);
+        self.written += 1;
+        if self.block.len() == self.interleaver.block_size() {
+            for &position in self.interleaver.permutation.iter() {
+                self.writer.write_bit(self.block[position])?;
+            }
+            self.block.clear();
+        }
+        Ok(())
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            let mut queue = BitQueue::<E, U>::from_value(value, bits);
+            while !queue.is_empty() {
+                self.write_bit(!queue.pop(1).is_zero())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.written & 7 == 0
+    }
+}
+
+/// A bitstream wrapper which restores the original order
+/// of each block of bits read from it.
+///
+/// Each block is read from the wrapped reader in full
+/// before any of its bits are returned,
+/// so a stream which ends partway through a block
+/// returns an error instead of that block's bits.
+/// Alignment is counted from the number of bits read from the wrapper,
+/// so `byte_align` discards bits of the deinterleaved data.
+#[derive(Clone, Debug)]
+pub struct DeinterleavingReader<R: BitRead, E: Endianness> {
+    reader: R,
+    interleaver: Interleaver,
+    block: Vec<bool>,
+    position: usize,
+    read: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<R: BitRead, E: Endianness> DeinterleavingReader<R, E> {
+    /// Wraps a DeinterleavingReader around something that implements `BitRead`
+    #[inline]
+    pub fn new(reader: R, interleaver: Interleaver) -> Self {
+        DeinterleavingReader {
+            reader,
+            block: Vec::new(),
+            position: 0,
+            interleaver,
+            read: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a DeinterleavingReader around something that implements `BitRead`
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(reader: R, _endian: E, interleaver: Interleaver) -> Self {
+        DeinterleavingReader::new(reader, interleaver)
+    }
+
+    /// Returns the number of bits of the current block
+    /// which have yet to be read
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.block.len() - self.position
+    }
+
+    /// Provides mutable reference to internal reader
+    ///
+    /// Bits read from it directly are not deinterleaved,
+    /// and are read after any bits still pending.
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of DeinterleavingReader.
+    /// Any unread bits of the current block are discarded.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    fn next_block(&mut self) -> io::Result<()> {
+        let mut block = vec![false; self.interleaver.block_size()];
+        for &position in self.interleaver.permutation.iter() {
+            block[position] = self.reader.read_bit()?;
+        }
+        self.block = block;
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl<R: BitRead, E: Endianness> BitRead for DeinterleavingReader<R, E> {
+    fn read_bit(&mut self) -> io::Result<bool> {
+        if self.position == self.block.len() {
+            self.next_block()?;
+        }
+        let bit = self.block[self.position];
+        self.position += 1;
+        self.read += 1;
+        Ok(bit)
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits <= U::bits_size() {
+            let mut queue = BitQueue::<E, U>::new();
+            for _ in 0..bits {
+                queue.push(1, U::from_u8(self.read_bit()?.into()));
+            }
+            Ok(queue.value())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        }
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        for _ in 0..bits {
+            self.read_bit()?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.read & 7 == 0
+    }
+
+    fn byte_align(&mut self) {
+        while !self.byte_aligned() && self.read_bit().is_ok() {}
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Wrappers which transparently invert every bit
+//! written to or read from a stream.
+//!
+//! Some legacy formats, such as certain fax encodings,
+//! store the complement of their bit content.
+//! `InvertedWriter` flips every bit before passing it along
+//! to the stream it wraps, and `InvertedReader` flips
+//! every bit read back from the stream it wraps,
+//! so the rest of a codec may work with the bits as they're specified.
+//!
+//! Whole values are flipped with a single exclusive-or,
+//! so wrapping a stream costs little more than the stream itself.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::invert::{InvertedReader, InvertedWriter};
+//!
+//! let mut writer = InvertedWriter::new(BitWriter::endian(Vec::new(), BigEndian));
+//! writer.write(4, 0b0011u8).unwrap();
+//! writer.write_bit(true).unwrap();
+//! writer.write_signed(3, -2i8).unwrap();
+//! let data = writer.into_writer().into_writer();
+//! assert_eq!(data, [0b1100_0_001]);
+//!
+//! let mut reader = InvertedReader::new(BitReader::endian(data.as_slice(), BigEndian));
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0b0011);
+//! assert!(reader.read_bit().unwrap());
+//! assert_eq!(reader.read_signed::<i8>(3).unwrap(), -2);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{ones_mask, Numeric, SignedNumeric};
+use std::io;
+
+/// A bitstream wrapper which inverts every bit written to it.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+/// use bitstream_io::invert::InvertedWriter;
+/// let mut writer = InvertedWriter::new(BitWriter::endian(Vec::new(), LittleEndian));
+/// writer.write(3, 0b001u8).unwrap();
+/// writer.write_unary0(2).unwrap();
+/// writer.write(2, 0u8).unwrap();
+/// writer.write_bytes(&[0x0F]).unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b11_100_110, 0xF0]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct InvertedWriter<W: BitWrite> {
+    writer: W,
+}
+
+impl<W: BitWrite> InvertedWriter<W> {
+    /// Wraps an InvertedWriter around something that implements `BitWrite`
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        InvertedWriter { writer }
+    }
+
+    /// Provides mutable reference to internal writer
+    ///
+    /// Bits written to it directly are not inverted.
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of InvertedWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: BitWrite> BitWrite for InvertedWriter<W> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.writer.write_bit(!bit)
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() || (bits < U::bits_size() && value >= (U::one() << bits)) {
+            // let the wrapped writer report the error
+            self.writer.write(bits, value)
+        } else {
+            self.writer.write(bits, value ^ ones_mask(bits))
+        }
+    }
+
+    /// Because inverting every bit of a twos-complement value
+    /// gives another value in the same range,
+    /// this writes the complement of the value.
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        if bits == 0 || bits >= S::bits_size() {
+            // every value fits, or the wrapped writer reports the error
+            return self.writer.write_signed(bits, !value);
+        }
+        let limit = S::one() << (bits - 1);
+        if value >= limit || value < S::default() - limit {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            self.writer.write_signed(bits, !value)
+        }
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        self.writer.write_unary1(value)
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        self.writer.write_unary0(value)
+    }
+
+    #[inline]
+    fn write_run(&mut self, bit: bool, count: u64) -> io::Result<()> {
+        self.writer.write_run(!bit, count)
+    }
+
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        let mut inverted = [0; 256];
+        for chunk in buf.chunks(inverted.len()) {
+            for (i, b) in inverted.iter_mut().zip(chunk) {
+                *i = !b;
+            }
+            self.writer.write_bytes(&inverted[..chunk.len()])?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+}
+
+/// A bitstream wrapper which inverts every bit read from it.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{LittleEndian, BitReader, BitRead};
+/// use bitstream_io::invert::InvertedReader;
+/// let data = [0b11_100_110, 0xF0];
+/// let mut reader = InvertedReader::new(BitReader::endian(&data[..], LittleEndian));
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b001);
+/// assert_eq!(reader.read_unary0().unwrap(), 2);
+/// reader.byte_align();
+/// let mut buf = [0; 1];
+/// reader.read_bytes(&mut buf).unwrap();
+/// assert_eq!(buf, [0x0F]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct InvertedReader<R: BitRead> {
+    reader: R,
+}
+
+impl<R: BitRead> InvertedReader<R> {
+    /// Wraps an InvertedReader around something that implements `BitRead`
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        InvertedReader { reader }
+    }
+
+    /// Provides mutable reference to internal reader
+    ///
+    /// Bits read from it directly are not inverted.
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of InvertedReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BitRead> BitRead for InvertedReader<R> {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        self.reader.read_bit().map(|bit| !bit)
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        self.reader
+            .read::<U>(bits)
+            .map(|value| value ^ ones_mask(bits))
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        self.reader.read_signed::<S>(bits).map(|value| !value)
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        self.reader.skip(bits)
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.reader.read_bytes(buf)?;
+        for b in buf.iter_mut() {
+            *b = !*b;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn read_unary0(&mut self) -> io::Result<u32> {
+        self.reader.read_unary1()
+    }
+
+    #[inline]
+    fn read_unary1(&mut self) -> io::Result<u32> {
+        self.reader.read_unary0()
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        self.reader.byte_align()
+    }
+}
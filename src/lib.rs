@@ -27,6 +27,52 @@
 //! Readers and writers are also designed to work with integer
 //! types of any possible size.
 //! Many of Rust's built-in integer types are supported by default.
+//!
+//! # Optional Features
+//!
+//! The `bitvec` feature adds methods for writing and reading
+//! the `BitSlice` and `BitVec` collections of the `bitvec` crate.
+//!
+//! The `testing` feature adds the `testing` module,
+//! with helpers for checking that fields round-trip
+//! through a writer and reader.
+//! The `proptest` feature enables `testing` and adds
+//! `proptest` strategies for generating fields to that module.
+//!
+//! The `observer` feature adds the `observer` module
+//! and lets a `BitObserver` be attached to a `BitWriter` or `BitReader`
+//! to be told about every run of bits written or read.
+//!
+//! The `wasm` feature adds the `wasm` module,
+//! with streams over JavaScript `Uint8Array` buffers
+//! for use on the `wasm32-unknown-unknown` target.
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize`
+//! for `BitRecorder`, `WriteHuffmanTree` and `CompactReadHuffmanTree`
+//! so that recordings and compiled Huffman trees
+//! may be stored or sent between processes.
+//!
+//! The `tokio` feature adds the `async_io` module,
+//! with byte readers and writers over `tokio`'s
+//! `AsyncRead` and `AsyncWrite` streams.
+//!
+//! The `codecs` feature adds the `codecs` module,
+//! with small byte-level codecs such as PackBits
+//! for any `ByteWrite` or `ByteRead`.
+//!
+//! The `ffi` feature adds the `ffi` module,
+//! with `extern "C"` functions for creating and using
+//! readers and writers from C through opaque handles.
+//!
+//! The `python` feature adds the `python` module,
+//! with `pyo3` bindings exposing bit readers and writers
+//! over `bytes` to Python.
+//!
+//! The `simd` feature lets `bitorder` convert buffers
+//! between bit orders with SSSE3 or NEON instructions.
+//!
+//! The `ffi` and `python` modules and the `simd` feature
+//! are the only parts of the crate which use `unsafe` code.
 
 //! # Migrating From Pre 1.0.0
 //!
@@ -39,20 +85,68 @@
 //! stream byte source or endianness.
 
 #![warn(missing_docs)]
-#![forbid(unsafe_code)]
-
-use std::fmt::Debug;
+#![cfg_attr(
+    not(any(feature = "ffi", feature = "python", feature = "simd")),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(
+    any(feature = "ffi", feature = "python", feature = "simd"),
+    deny(unsafe_code)
+)]
+
+use std::fmt::{self, Debug};
 use std::io;
 use std::marker::PhantomData;
 use std::mem;
 use std::ops::{BitOrAssign, BitXor, Not, Rem, RemAssign, Shl, ShlAssign, Shr, ShrAssign, Sub};
 
+#[cfg(feature = "tokio")]
+pub mod async_io;
+pub mod basen;
+pub mod bitorder;
+pub mod buffer;
+pub mod checksum;
+pub mod chunk;
+#[cfg(feature = "codecs")]
+pub mod codecs;
+pub mod convolutional;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
+pub mod float;
 pub mod huffman;
+pub mod interleave;
+pub mod invert;
+#[cfg(feature = "observer")]
+pub mod observer;
+pub mod pack;
+pub mod parity;
+pub mod planes;
+#[cfg(feature = "python")]
+#[allow(unsafe_code)]
+pub mod python;
+pub mod rangecoder;
 pub mod read;
+pub mod schema;
+pub mod scramble;
+pub mod split;
+pub mod stuffing;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod word;
 pub mod write;
-pub use read::{BitRead, BitReader, ByteRead, ByteReader, HuffmanRead};
+pub use read::{
+    BitIter, BitRead, BitReadCore, BitReader, BitSliceReader, Bookmark, ByteRead, ByteReader,
+    CountingBitReader, DynBitReader, FromByteStream, HuffmanRead, IterReader, LengthLimitError,
+    LimitedBitReader, LookaheadReader, RemainingBytes, Utf8Policy,
+};
 pub use write::{
-    BitCounter, BitRecorder, BitWrite, BitWriter, ByteWrite, ByteWriter, HuffmanWrite,
+    BitCounter, BitRecorder, BitSliceWriter, BitWrite, BitWriteCore, BitWriter, BufBitWriter,
+    ByteWrite, ByteWriter, CallbackWriter, CounterOverflowError, DebugBitWriter, DynBitWriter,
+    HuffmanWrite, LimitedBitWriter, NibbleWriter, SymbolRecorder, TeeBitWriter, ToBitStream,
+    ToByteStream,
 };
 
 /// This trait extends many common integer types (both unsigned and signed)
@@ -118,6 +212,8 @@ pub trait Numeric:
     fn from_le_bytes(bytes: Self::Bytes) -> Self;
 
     /// Convert to a generic unsigned write value for stream recording purposes
+    #[deprecated(note = "BitRecorder no longer stores written values individually")]
+    #[allow(deprecated)]
     fn unsigned_value(self) -> write::UnsignedValue;
 }
 
@@ -179,6 +275,7 @@ macro_rules! define_numeric {
                 <$t>::from_le_bytes(bytes)
             }
             #[inline(always)]
+            #[allow(deprecated)]
             fn unsigned_value(self) -> write::UnsignedValue {
                 self.into()
             }
@@ -201,6 +298,8 @@ pub trait SignedNumeric: Numeric {
     fn as_unsigned(self, bits: u32) -> Self;
 
     /// Converts to a generic signed value for stream recording purposes.
+    #[deprecated(note = "BitRecorder no longer stores written values individually")]
+    #[allow(deprecated)]
     fn signed_value(self) -> write::SignedValue;
 }
 
@@ -220,6 +319,7 @@ macro_rules! define_signed_numeric {
                 self - (-1 << (bits - 1))
             }
             #[inline(always)]
+            #[allow(deprecated)]
             fn signed_value(self) -> write::SignedValue {
                 self.into()
             }
@@ -244,6 +344,285 @@ define_signed_numeric!(i32);
 define_signed_numeric!(i64);
 define_signed_numeric!(i128);
 
+/// This trait extends floating point types
+/// so that they can be read from or written to streams
+/// by way of their bit patterns.
+pub trait FloatNumeric: Copy {
+    /// The unsigned integer type which holds the float's bit pattern
+    type Bits: Numeric;
+
+    /// Size of the float's bit pattern in bits,
+    /// which may be smaller than the size of `Bits`
+    fn bits_size() -> u32;
+
+    /// Returns the float's bit pattern
+    fn to_bits(self) -> Self::Bits;
+
+    /// Builds a float from its bit pattern
+    fn from_bits(bits: Self::Bits) -> Self;
+}
+
+macro_rules! define_float_numeric {
+    ($t:ty, $b:ty) => {
+        impl FloatNumeric for $t {
+            type Bits = $b;
+
+            #[inline(always)]
+            fn bits_size() -> u32 {
+                mem::size_of::<$t>() as u32 * 8
+            }
+            #[inline(always)]
+            fn to_bits(self) -> $b {
+                <$t>::to_bits(self)
+            }
+            #[inline(always)]
+            fn from_bits(bits: $b) -> Self {
+                <$t>::from_bits(bits)
+            }
+        }
+    };
+}
+
+define_float_numeric!(f32, u32);
+define_float_numeric!(f64, u64);
+
+/// How a signed value is represented in a stream
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SignedRepr {
+    /// Negative values are stored as twos-complement
+    TwosComplement,
+    /// A sign bit is followed by the value's magnitude
+    SignMagnitude,
+    /// Negative values are stored with all the bits
+    /// of their magnitude inverted
+    OnesComplement,
+}
+
+impl SignedRepr {
+    /// Converts a value in this representation
+    /// to the twos-complement value with the same bit pattern
+    fn encode<S>(self, bits: u32, value: S) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        if bits == 0 || bits > S::bits_size() || self == SignedRepr::TwosComplement {
+            // let the twos-complement writer report any errors
+            return Ok(value);
+        }
+        // neither representation can hold -2 ^ (bits - 1)
+        let limit = S::one() << (bits - 1);
+        let in_range = if bits == S::bits_size() {
+            value != limit
+        } else {
+            (value < limit) && (value > S::default() - limit)
+        };
+        if !in_range {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else if !value.is_negative() {
+            Ok(value)
+        } else if self == SignedRepr::SignMagnitude {
+            Ok((S::default() - value).as_negative(bits))
+        } else {
+            Ok(value - S::one())
+        }
+    }
+
+    /// Converts a twos-complement value to the value
+    /// with the same bit pattern in this representation.
+    /// Negative zero is returned as zero.
+    fn decode<S>(self, bits: u32, value: S) -> S
+    where
+        S: SignedNumeric,
+    {
+        if !value.is_negative() {
+            value
+        } else {
+            match self {
+                SignedRepr::TwosComplement => value,
+                SignedRepr::SignMagnitude => S::default() - value.as_unsigned(bits),
+                SignedRepr::OnesComplement => value - (S::default() - S::one()),
+            }
+        }
+    }
+}
+
+/// Where the sign bit of a twos-complement value
+/// is placed relative to its remaining bits in a stream
+///
+/// Big-endian streams normally place the sign bit first
+/// and little-endian streams normally place it last,
+/// since it's the value's most significant bit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SignPlacement {
+    /// The sign bit is placed before the value's remaining bits
+    First,
+    /// The sign bit is placed after the value's remaining bits
+    Last,
+}
+
+/// The order in which the decimal digits
+/// of a binary-coded decimal value are stored
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BcdOrder {
+    /// The most significant digit is stored first
+    MostSignificantFirst,
+    /// The least significant digit is stored first
+    LeastSignificantFirst,
+}
+
+/// The largest number of decimal digits any `u64` can hold
+pub(crate) const BCD_MAX_DIGITS: u32 = 19;
+
+/// How a value is rounded to the nearest representable value
+/// when it falls between two of them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round to the nearest value, with ties rounded away from zero
+    Nearest,
+    /// Round to the nearest value, with ties rounded to even
+    NearestEven,
+    /// Round toward negative infinity
+    Floor,
+    /// Round toward positive infinity
+    Ceiling,
+    /// Round toward zero, discarding any fractional part
+    TowardZero,
+}
+
+impl Rounding {
+    /// Rounds the value to an integer in this mode
+    fn round(self, value: f64) -> f64 {
+        match self {
+            Rounding::Nearest => value.round(),
+            Rounding::NearestEven => {
+                let floor = value.floor();
+                let difference = value - floor;
+                if difference < 0.5 || (difference == 0.5 && floor % 2.0 == 0.0) {
+                    floor
+                } else {
+                    floor + 1.0
+                }
+            }
+            Rounding::Floor => value.floor(),
+            Rounding::Ceiling => value.ceil(),
+            Rounding::TowardZero => value.trunc(),
+        }
+    }
+
+    /// Scales the value by the given number of fractional bits
+    /// and rounds it to an integer in this mode
+    fn to_fixed_point(self, frac_bits: u32, value: f64) -> io::Result<f64> {
+        if value.is_finite() {
+            Ok(self.round(value * 2.0f64.powi(frac_bits as i32)))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "non-finite value for fixed point written",
+            ))
+        }
+    }
+}
+
+/// The bit pattern a stream's reserved fields must be filled with
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReservedPolicy {
+    /// Reserved bits are all 0
+    Zeros,
+    /// Reserved bits are all 1
+    Ones,
+}
+
+impl ReservedPolicy {
+    /// Returns the value of a reserved field of the given size,
+    /// which should be no larger than the type
+    fn pattern<U: Numeric>(self, bits: u32) -> U {
+        match self {
+            ReservedPolicy::Zeros => U::default(),
+            ReservedPolicy::Ones => ones_mask(bits),
+        }
+    }
+}
+
+/// Displays the leading bits of a byte slice in binary,
+/// with an underscore between each byte's nibbles
+/// and a space between bytes.
+///
+/// Each byte's bits are shown from most significant to least,
+/// which is the order a big-endian reader consumes them.
+/// If more bits are requested than the slice holds,
+/// only the bits it holds are shown.
+///
+/// # Example
+/// ```
+/// use bitstream_io::Bits;
+/// let bytes = [0b1010_0101, 0b0101_1111];
+/// assert_eq!(format!("{}", Bits::new(&bytes, 12)), "1010_0101 0101");
+/// assert_eq!(format!("{}", Bits::new(&bytes, 3)), "101");
+/// assert_eq!(format!("{}", Bits::new(&bytes, 99)), "1010_0101 0101_1111");
+/// assert_eq!(format!("{:?}", Bits::new(&bytes, 0)), "");
+/// ```
+#[derive(Copy, Clone)]
+pub struct Bits<'a> {
+    bytes: &'a [u8],
+    bits: u64,
+}
+
+impl<'a> Bits<'a> {
+    /// Displays the first `bits` bits of the given bytes
+    #[inline]
+    pub fn new(bytes: &'a [u8], bits: u64) -> Self {
+        Bits {
+            bytes,
+            bits: bits.min(bytes.len() as u64 * 8),
+        }
+    }
+
+    /// Displays every bit of the given bytes
+    #[inline]
+    pub fn all(bytes: &'a [u8]) -> Self {
+        Bits::new(bytes, bytes.len() as u64 * 8)
+    }
+
+    /// Returns the number of bits displayed
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns true if no bits are displayed
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+}
+
+impl<'a> fmt::Display for Bits<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use fmt::Write;
+
+        for i in 0..self.bits {
+            if i > 0 && i % 8 == 0 {
+                f.write_char(' ')?;
+            } else if i > 0 && i % 4 == 0 {
+                f.write_char('_')?;
+            }
+            let bit = (self.bytes[(i / 8) as usize] >> (7 - i % 8)) & 1;
+            f.write_char(if bit == 0 { '0' } else { '1' })?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for Bits<'a> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
 /// A stream's endianness, or byte order, for determining
 /// how bits should be read.
 ///
@@ -308,7 +687,7 @@ pub trait Endianness: Sized {
 }
 
 /// Big-endian, or most significant bits first
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct BigEndian;
 
 /// Big-endian, or most significant bits first
@@ -444,7 +823,7 @@ impl Endianness for BigEndian {
 }
 
 /// Little-endian, or least significant bits first
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct LittleEndian;
 
 /// Little-endian, or least significant bits first
@@ -572,15 +951,199 @@ impl Endianness for LittleEndian {
     }
 }
 
+/// An endianness chosen at runtime,
+/// such as from a format's byte-order mark.
+///
+/// Because `Endianness` is implemented by types rather than values,
+/// this is used with `DynBitReader` and `DynBitWriter`,
+/// which dispatch to a reader or writer of the chosen endianness.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DynEndianness {
+    /// Big-endian, or most significant bits first
+    Big,
+    /// Little-endian, or least significant bits first
+    Little,
+}
+
+impl From<BigEndian> for DynEndianness {
+    #[inline]
+    fn from(_endian: BigEndian) -> Self {
+        DynEndianness::Big
+    }
+}
+
+impl From<LittleEndian> for DynEndianness {
+    #[inline]
+    fn from(_endian: LittleEndian) -> Self {
+        DynEndianness::Little
+    }
+}
+
+/// An error from a reader or writer with a fixed budget of bits,
+/// such as `LimitedBitReader`, `LimitedBitWriter` or `BitSliceWriter`,
+/// when an operation would exceed the bits which remain in it.
+///
+/// This is returned as the inner error
+/// of an `io::Error` of kind `Other`,
+/// from which `from_io` retrieves it,
+/// so that an exhausted budget may be told apart
+/// from truncated input or a full output stream.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct BitLimitError {
+    requested: u64,
+    remaining: u64,
+}
+
+impl BitLimitError {
+    #[inline]
+    pub(crate) fn new(requested: u64, remaining: u64) -> Self {
+        BitLimitError {
+            requested,
+            remaining,
+        }
+    }
+
+    /// Returns the number of bits the operation needed
+    #[inline]
+    pub fn requested(&self) -> u64 {
+        self.requested
+    }
+
+    /// Returns the number of bits which remained in the budget
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Returns the `BitLimitError` wrapped by an `io::Error`, if any
+    pub fn from_io(err: &io::Error) -> Option<&BitLimitError> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<BitLimitError>())
+    }
+}
+
+impl fmt::Display for BitLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} bits requested but only {} remain within limit",
+            self.requested, self.remaining
+        )
+    }
+}
+
+impl std::error::Error for BitLimitError {}
+
+impl From<BitLimitError> for io::Error {
+    #[inline]
+    fn from(err: BitLimitError) -> Self {
+        io::Error::new(io::ErrorKind::Other, err)
+    }
+}
+
+/// Returns a value's bit pattern widened to a `u128`
+/// without sign extension
+#[inline]
+pub(crate) fn numeric_to_u128<N: Numeric>(value: N) -> u128 {
+    value
+        .to_be_bytes()
+        .as_ref()
+        .iter()
+        .fold(0, |acc, b| (acc << 8) | u128::from(*b))
+}
+
+/// Returns a value whose lowest `bits` bits are all 1,
+/// or all of whose bits are 1 if `bits` is the type's size or larger
+#[inline]
+pub(crate) fn ones_mask<N: Numeric>(bits: u32) -> N {
+    if bits >= N::bits_size() {
+        !N::default()
+    } else {
+        (N::one() << bits) - N::one()
+    }
+}
+
+/// Reverses the order of a value's lowest `bits` bits,
+/// where the value must fit in that many bits
+#[inline]
+pub(crate) fn reverse_bits<N: Numeric>(bits: u32, value: N) -> N {
+    if bits == 0 {
+        value
+    } else {
+        // move the field to the top, then reverse the whole value
+        let mut bytes = (value << (N::bits_size() - bits)).to_le_bytes();
+        for b in bytes.as_mut().iter_mut() {
+            *b = b.reverse_bits();
+        }
+        N::from_be_bytes(bytes)
+    }
+}
+
+/// Converts a signed value no wider than 64 bits to an `i64`
+#[inline]
+pub(crate) fn signed_to_i64<S: SignedNumeric>(value: S) -> i64 {
+    let init = if value.is_negative() { -1 } else { 0 };
+    value
+        .to_be_bytes()
+        .as_ref()
+        .iter()
+        .fold(init, |acc, b| (acc << 8) | i64::from(*b))
+}
+
+/// Converts a `u64` which fits the given type to that type
+#[inline]
+pub(crate) fn numeric_from_u64<N: Numeric>(value: u64) -> N {
+    numeric_from_be_bytes(value.to_be_bytes(), 0)
+}
+
+/// Converts an `i64` which fits the given type to that type
+#[inline]
+pub(crate) fn signed_from_i64<S: SignedNumeric>(value: i64) -> S {
+    numeric_from_be_bytes(value.to_be_bytes(), if value < 0 { 0xFF } else { 0 })
+}
+
+#[inline]
+fn numeric_from_be_bytes<N: Numeric>(source: [u8; 8], fill: u8) -> N {
+    let mut bytes = N::buffer();
+    let target = bytes.as_mut();
+    let len = target.len();
+    let copied = len.min(source.len());
+    for b in target[0..len - copied].iter_mut() {
+        *b = fill;
+    }
+    target[len - copied..].copy_from_slice(&source[source.len() - copied..]);
+    N::from_be_bytes(bytes)
+}
+
 /// A queue for efficiently pushing bits onto a value
 /// and popping them off a value.
-#[derive(Clone, Default)]
+#[derive(Default)]
 pub struct BitQueue<E: Endianness, N: Numeric> {
     phantom: PhantomData<E>,
     value: N,
     bits: u32,
 }
 
+impl<E: Endianness, N: Numeric> Clone for BitQueue<E, N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        BitQueue {
+            phantom: PhantomData,
+            value: self.value,
+            bits: self.bits,
+        }
+    }
+}
+
+impl<E: Endianness, N: Numeric> fmt::Debug for BitQueue<E, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitQueue")
+            .field("value", &self.value)
+            .field("bits", &self.bits)
+            .finish()
+    }
+}
+
 impl<E: Endianness, N: Numeric> BitQueue<E, N> {
     /// Returns a new empty queue
     #[inline]
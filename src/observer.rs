@@ -0,0 +1,305 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Hooks for observing the bits written by a `BitWriter`
+//! or read by a `BitReader`.
+//!
+//! A `BitObserver` attached to a writer or reader
+//! with its `set_observer` method is told about every run of bits
+//! as it passes through, along with the offset at which it starts.
+//! This makes it easy to build bit allocation statistics,
+//! such as how many bits each kind of syntax element takes,
+//! without wrapping every call site.
+//! A single write or read may be reported as several runs,
+//! but every bit is reported exactly once.
+//! `SectionedBitCounter` is a ready-made observer
+//! which totals bits by named section.
+//!
+//! Since observers are shared as `Arc<Mutex<_>>`,
+//! the caller keeps its own handle to them
+//! and may update or inspect them between writes.
+//!
+//! This module is only available with the `observer` feature.
+//! Without it, readers and writers carry no observer
+//! and have no added cost.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use std::io::Write;
+//! use std::sync::{Arc, Mutex};
+//! use bitstream_io::{BigEndian, BitWriter, BitWrite};
+//! use bitstream_io::observer::BitObserver;
+//!
+//! #[derive(Default)]
+//! struct Allocation {
+//!     element: &'static str,
+//!     bits: BTreeMap<&'static str, u64>,
+//! }
+//!
+//! impl BitObserver for Allocation {
+//!     fn on_write(&mut self, bits: u32, _offset: u64) {
+//!         *self.bits.entry(self.element).or_default() += u64::from(bits);
+//!     }
+//! }
+//!
+//! let allocation = Arc::new(Mutex::new(Allocation::default()));
+//! let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+//! writer.set_observer(allocation.clone());
+//!
+//! allocation.lock().unwrap().element = "header";
+//! writer.write(12, 0xABCu16).unwrap();
+//! writer.write_bit(true).unwrap();
+//! allocation.lock().unwrap().element = "residual";
+//! writer.write_unary0(5).unwrap();
+//! writer.write_signed(7, -20i8).unwrap();
+//! allocation.lock().unwrap().element = "header";
+//! writer.byte_align().unwrap();
+//!
+//! assert_eq!(allocation.lock().unwrap().bits["header"], 19);
+//! assert_eq!(allocation.lock().unwrap().bits["residual"], 13);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Receives notice of the bits passing through
+/// a `BitWriter` or `BitReader`
+///
+/// Offsets count the bits successfully written or read
+/// through that writer or reader since it was created,
+/// so seeking a reader doesn't change them
+/// and bits examined by a reader's `peek` methods aren't reported.
+pub trait BitObserver {
+    /// Called when the given number of bits
+    /// are written, starting at the given offset
+    #[inline]
+    fn on_write(&mut self, bits: u32, offset: u64) {
+        let _ = (bits, offset);
+    }
+
+    /// Called when the given number of bits
+    /// are read or skipped, starting at the given offset
+    #[inline]
+    fn on_read(&mut self, bits: u32, offset: u64) {
+        let _ = (bits, offset);
+    }
+}
+
+/// An optional observer along with the current offset
+#[derive(Clone, Default)]
+pub(crate) struct Observed {
+    observer: Option<Arc<Mutex<dyn BitObserver + Send>>>,
+    offset: u64,
+}
+
+impl Observed {
+    #[inline]
+    pub(crate) fn set(&mut self, observer: Arc<Mutex<dyn BitObserver + Send>>) {
+        self.observer = Some(observer);
+    }
+
+    #[inline]
+    pub(crate) fn take(&mut self) -> Option<Arc<Mutex<dyn BitObserver + Send>>> {
+        self.observer.take()
+    }
+
+    /// Reports the given number of bits as written
+    #[inline]
+    pub(crate) fn wrote(&mut self, bits: u64) {
+        self.advance(bits, |observer, bits, offset| {
+            observer.on_write(bits, offset)
+        })
+    }
+
+    /// Reports the given number of bits as read
+    #[inline]
+    pub(crate) fn read(&mut self, bits: u64) {
+        self.advance(bits, |observer, bits, offset| {
+            observer.on_read(bits, offset)
+        })
+    }
+
+    /// Detaches the observer and offset until `resume` is called,
+    /// so that peeked bits aren't reported
+    #[inline]
+    pub(crate) fn suspend(&mut self) -> Observed {
+        std::mem::take(self)
+    }
+
+    #[inline]
+    pub(crate) fn resume(&mut self, suspended: Observed) {
+        *self = suspended;
+    }
+
+    fn advance<F>(&mut self, mut bits: u64, mut report: F)
+    where
+        F: FnMut(&mut dyn BitObserver, u32, u64),
+    {
+        if let Some(observer) = self.observer.as_ref() {
+            let mut observer = observer.lock().unwrap_or_else(PoisonError::into_inner);
+            // runs wider than a u32 are reported in pieces
+            while bits > 0 {
+                let run = bits.min(u32::MAX.into()) as u32;
+                report(&mut *observer, run, self.offset);
+                self.offset += u64::from(run);
+                bits -= u64::from(run);
+            }
+        } else {
+            self.offset += bits;
+        }
+    }
+}
+
+/// An observer which totals bits by named section,
+/// for profiling where an encoder's bit budget goes.
+///
+/// Bits written or read while a section is open
+/// are counted toward the most recently started one,
+/// so nested sections exclude the bits of those inside them.
+/// Bits outside any section are counted separately.
+/// Starting a section with the same name more than once
+/// adds to its earlier total.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use std::sync::{Arc, Mutex};
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+/// use bitstream_io::observer::SectionedBitCounter;
+///
+/// let sections = Arc::new(Mutex::new(SectionedBitCounter::new()));
+/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+/// writer.set_observer(sections.clone());
+///
+/// writer.write(4, 0b1010u8).unwrap();
+/// sections.lock().unwrap().start_section("lpc_coeffs");
+/// writer.write(12, 0x123u16).unwrap();
+/// sections.lock().unwrap().start_section("shift");
+/// writer.write(5, 7u8).unwrap();
+/// sections.lock().unwrap().end_section();
+/// writer.write(12, 0x456u16).unwrap();
+/// sections.lock().unwrap().end_section();
+/// sections.lock().unwrap().start_section("shift");
+/// writer.write(5, 9u8).unwrap();
+/// sections.lock().unwrap().end_section();
+/// writer.byte_align().unwrap();
+///
+/// let sections = sections.lock().unwrap();
+/// assert_eq!(sections.section_bits("lpc_coeffs"), 24);
+/// assert_eq!(sections.section_bits("shift"), 10);
+/// assert_eq!(sections.unsectioned_bits(), 6);
+/// assert_eq!(sections.total_bits(), 40);
+/// assert_eq!(sections.to_string(), "lpc_coeffs: 24 bits\nshift: 10 bits\n(none): 6 bits\n");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SectionedBitCounter {
+    sections: Vec<(String, u64)>,
+    open: Vec<usize>,
+    unsectioned: u64,
+}
+
+impl SectionedBitCounter {
+    /// Creates a counter with no sections
+    #[inline]
+    pub fn new() -> Self {
+        SectionedBitCounter::default()
+    }
+
+    /// Starts counting bits toward the named section
+    /// until the matching `end_section`
+    pub fn start_section<S: AsRef<str>>(&mut self, name: S) {
+        let name = name.as_ref();
+        let index = match self.sections.iter().position(|(n, _)| n == name) {
+            Some(index) => index,
+            None => {
+                self.sections.push((name.to_owned(), 0));
+                self.sections.len() - 1
+            }
+        };
+        self.open.push(index);
+    }
+
+    /// Ends the most recently started section,
+    /// returning to the one it was started in, if any
+    ///
+    /// # Panics
+    ///
+    /// Panics if no section is open.
+    pub fn end_section(&mut self) {
+        self.open.pop().expect("no open section to end");
+    }
+
+    /// Returns the name of the section bits are currently counted toward
+    #[inline]
+    pub fn current_section(&self) -> Option<&str> {
+        self.open.last().map(|i| self.sections[*i].0.as_str())
+    }
+
+    /// Returns the total bits counted toward the named section
+    pub fn section_bits(&self, name: &str) -> u64 {
+        self.sections
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, bits)| *bits)
+            .unwrap_or(0)
+    }
+
+    /// Returns each section's name and bits, in the order first started
+    #[inline]
+    pub fn sections(&self) -> &[(String, u64)] {
+        &self.sections
+    }
+
+    /// Returns the bits counted outside of any section
+    #[inline]
+    pub fn unsectioned_bits(&self) -> u64 {
+        self.unsectioned
+    }
+
+    /// Returns the bits counted in total
+    pub fn total_bits(&self) -> u64 {
+        self.sections.iter().map(|(_, bits)| *bits).sum::<u64>() + self.unsectioned
+    }
+
+    fn add(&mut self, bits: u32) {
+        match self.open.last() {
+            Some(index) => self.sections[*index].1 += u64::from(bits),
+            None => self.unsectioned += u64::from(bits),
+        }
+    }
+}
+
+impl BitObserver for SectionedBitCounter {
+    #[inline]
+    fn on_write(&mut self, bits: u32, _offset: u64) {
+        self.add(bits)
+    }
+
+    #[inline]
+    fn on_read(&mut self, bits: u32, _offset: u64) {
+        self.add(bits)
+    }
+}
+
+/// Lists each section's bits in the order first started,
+/// followed by any bits outside of sections
+impl fmt::Display for SectionedBitCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, bits) in self.sections.iter() {
+            writeln!(f, "{}: {} bits", name, bits)?;
+        }
+        if self.unsectioned > 0 {
+            writeln!(f, "(none): {} bits", self.unsectioned)?;
+        }
+        Ok(())
+    }
+}
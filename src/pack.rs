@@ -0,0 +1,184 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Packing of small fixed layouts at compile time.
+//!
+//! `BitPacker` builds a fixed-size array of bytes from a sequence
+//! of fields using only `const fn` methods,
+//! so that constant headers and static tables can be laid out
+//! the same way a `BitWriter` would write them
+//! without any writer at runtime.
+//! Mistakes such as a value too large for its field
+//! or fields which overflow the array are reported
+//! as errors during compilation.
+//!
+//! ## Example
+//!
+//! Building a constant FLAC frame header.
+//!
+//! ```
+//! use bitstream_io::pack::BitPacker;
+//!
+//! const HEADER: [u8; 5] = BitPacker::big_endian()
+//!     .field(14, 0b11111111111110)  // sync code
+//!     .field(1, 0)                  // reserved
+//!     .field(1, 0)                  // blocking strategy
+//!     .field(4, 0b1100)             // block size
+//!     .field(4, 0b1001)             // sample rate
+//!     .field(4, 0b0001)             // channel assignment
+//!     .field(3, 0b100)              // bits per sample
+//!     .field(1, 0)                  // padding
+//!     .field(8, 0)                  // frame number
+//!     .into_bytes();
+//! assert_eq!(HEADER, [0xFF, 0xF8, 0xC9, 0x18, 0x00]);
+//! ```
+
+#![warn(missing_docs)]
+
+/// Packs fields into an array of `N` bytes in a const context.
+///
+/// Any bits of the array not filled by fields are 0,
+/// as if the final partial byte were padded by `byte_align`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BitPacker<const N: usize> {
+    bytes: [u8; N],
+    bits: usize,
+    big_endian: bool,
+}
+
+impl<const N: usize> BitPacker<N> {
+    /// Creates an empty packer which places fields
+    /// most significant bit first, like `BigEndian`
+    pub const fn big_endian() -> Self {
+        BitPacker {
+            bytes: [0; N],
+            bits: 0,
+            big_endian: true,
+        }
+    }
+
+    /// Creates an empty packer which places fields
+    /// least significant bit first, like `LittleEndian`
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::pack::BitPacker;
+    /// const PACKED: [u8; 2] = BitPacker::little_endian()
+    ///     .field(2, 0b01)
+    ///     .field(3, 0b101)
+    ///     .field(5, 0b10111)
+    ///     .into_bytes();
+    /// assert_eq!(PACKED, [0b111_101_01, 0b10]);
+    /// ```
+    pub const fn little_endian() -> Self {
+        BitPacker {
+            bytes: [0; N],
+            bits: 0,
+            big_endian: false,
+        }
+    }
+
+    /// Returns the number of bits packed so far
+    pub const fn len(&self) -> usize {
+        self.bits
+    }
+
+    /// Returns true if no bits have been packed
+    pub const fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Appends an unsigned value using the given number of bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bits is larger than 64,
+    /// if the value is too large to fit the given number of bits,
+    /// or if the field doesn't fit in the remaining space.
+    /// In a const context, these are compile-time errors.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::pack::BitPacker;
+    /// const PACKED: [u8; 2] = BitPacker::big_endian()
+    ///     .field(2, 0b01)
+    ///     .field(3, 0b101)
+    ///     .field(5, 0b10111)
+    ///     .into_bytes();
+    /// assert_eq!(PACKED, [0b01_101_101, 0b11_000000]);
+    /// ```
+    pub const fn field(mut self, bits: u32, value: u64) -> Self {
+        assert!(bits <= 64, "excessive bits for field");
+        assert!(
+            bits == 64 || value >> bits == 0,
+            "excessive value for bits packed"
+        );
+        assert!(
+            self.bits + bits as usize <= N * 8,
+            "fields exceed packed size"
+        );
+
+        let mut i = 0;
+        while i < bits {
+            let bit = if self.big_endian {
+                (value >> (bits - 1 - i)) & 1
+            } else {
+                (value >> i) & 1
+            };
+            if bit == 1 {
+                let shift = if self.big_endian {
+                    7 - (self.bits % 8)
+                } else {
+                    self.bits % 8
+                };
+                self.bytes[self.bits / 8] |= 1 << shift;
+            }
+            self.bits += 1;
+            i += 1;
+        }
+        self
+    }
+
+    /// Appends a twos-complement signed value
+    /// using the given number of bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bits is 0 or larger than 64,
+    /// if the value is out of range for the given number of bits,
+    /// or if the field doesn't fit in the remaining space.
+    /// In a const context, these are compile-time errors.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::pack::BitPacker;
+    /// const PACKED: [u8; 1] = BitPacker::big_endian()
+    ///     .signed_field(4, -5)
+    ///     .signed_field(4, 7)
+    ///     .into_bytes();
+    /// assert_eq!(PACKED, [0b10110111]);
+    /// ```
+    pub const fn signed_field(self, bits: u32, value: i64) -> Self {
+        assert!(bits >= 1 && bits <= 64, "excessive bits for field");
+        if bits == 64 {
+            self.field(64, value as u64)
+        } else {
+            let limit = 1i64 << (bits - 1);
+            assert!(
+                value >= -limit && value < limit,
+                "excessive value for bits packed"
+            );
+            self.field(bits, (value as u64) & ((1u64 << bits) - 1))
+        }
+    }
+
+    /// Returns the packed array of bytes
+    pub const fn into_bytes(self) -> [u8; N] {
+        self.bytes
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Wrappers which transparently insert and check parity bits.
+//!
+//! Many older telecom and storage formats,
+//! such as 9-track tape images and UART-style serial payloads,
+//! follow every fixed-size group of data bits with a parity bit.
+//! `ParityWriter` wraps a bitstream and emits a parity bit
+//! after every group of data bits written to it,
+//! while `ParityReader` wraps a bitstream and
+//! checks and strips those parity bits from what's read.
+//!
+//! ## Example
+//!
+//! Writing 7-bit ASCII with even parity.
+//!
+//! ```
+//! use std::io::Read;
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::parity::{Parity, ParityReader, ParityWriter};
+//!
+//! let mut writer = ParityWriter::endian(
+//!     BitWriter::endian(Vec::new(), BigEndian),
+//!     BigEndian,
+//!     Parity::Even,
+//!     7,
+//! );
+//! writer.write(7, b'A').unwrap();
+//! writer.write(7, b'C').unwrap();
+//! let data = writer.into_writer().into_writer();
+//! assert_eq!(data, [0b1000001_0, 0b1000011_1]);
+//!
+//! let mut reader = ParityReader::endian(
+//!     BitReader::endian(data.as_slice(), BigEndian),
+//!     BigEndian,
+//!     Parity::Even,
+//!     7,
+//! );
+//! assert_eq!(reader.read::<u8>(7).unwrap(), b'A');
+//! assert_eq!(reader.read::<u8>(7).unwrap(), b'C');
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{BitQueue, Endianness, Numeric, SignedNumeric};
+use std::io;
+use std::marker::PhantomData;
+
+/// The kind of parity bit following each group of data bits
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Parity {
+    /// The parity bit makes the total number of 1 bits even
+    Even,
+    /// The parity bit makes the total number of 1 bits odd
+    Odd,
+}
+
+/// Tracks the data bits of the group in progress
+#[derive(Copy, Clone, Debug)]
+struct ParityState {
+    parity: Parity,
+    data_bits: u32,
+    count: u32,
+    odd_ones: bool,
+}
+
+impl ParityState {
+    fn new(parity: Parity, data_bits: u32) -> Self {
+        assert!(data_bits > 0, "parity groups must have data bits");
+        ParityState {
+            parity,
+            data_bits,
+            count: 0,
+            odd_ones: false,
+        }
+    }
+
+    /// Adds a data bit to the group and returns its parity bit
+    /// if the group is now complete
+    #[inline]
+    fn push(&mut self, bit: bool) -> Option<bool> {
+        self.odd_ones ^= bit;
+        self.count += 1;
+        if self.count == self.data_bits {
+            let parity_bit = match self.parity {
+                Parity::Even => self.odd_ones,
+                Parity::Odd => !self.odd_ones,
+            };
+            self.count = 0;
+            self.odd_ones = false;
+            Some(parity_bit)
+        } else {
+            None
+        }
+    }
+}
+
+/// A bitstream wrapper which writes a parity bit
+/// after every group of data bits written to it.
+///
+/// A final group with fewer data bits than the group size
+/// gets no parity bit, so callers should write whole groups.
+/// Because parity bits change how many bits are sent to the
+/// wrapped writer, its alignment is what's reported
+/// by `byte_aligned`.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+/// use bitstream_io::parity::{Parity, ParityWriter};
+/// let mut writer = ParityWriter::endian(
+///     BitWriter::endian(Vec::new(), BigEndian),
+///     BigEndian,
+///     Parity::Odd,
+///     3,
+/// );
+/// writer.write(6, 0b101_111).unwrap();
+/// writer.write(3, 0b000).unwrap();
+/// writer.write(3, 0b001).unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b1011_1110, 0b0001_0010]);
+/// ```
+///
+/// ```
+/// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+/// use bitstream_io::parity::{Parity, ParityWriter};
+/// let mut writer = ParityWriter::endian(
+///     BitWriter::endian(Vec::new(), LittleEndian),
+///     LittleEndian,
+///     Parity::Odd,
+///     3,
+/// );
+/// writer.write(6, 0b111_101).unwrap();
+/// writer.write(3, 0b000).unwrap();
+/// writer.write(3, 0b100).unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b0111_1101, 0b0100_1000]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParityWriter<W: BitWrite, E: Endianness> {
+    writer: W,
+    state: ParityState,
+    phantom: PhantomData<E>,
+}
+
+impl<W: BitWrite, E: Endianness> ParityWriter<W, E> {
+    /// Wraps a ParityWriter around something that implements `BitWrite`
+    /// which writes a parity bit of the given kind
+    /// after every `data_bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data_bits` is 0.
+    #[inline]
+    pub fn new(writer: W, parity: Parity, data_bits: u32) -> Self {
+        ParityWriter {
+            writer,
+            state: ParityState::new(parity, data_bits),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a ParityWriter around something that implements `BitWrite`
+    /// with the given endianness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data_bits` is 0.
+    #[inline]
+    pub fn endian(writer: W, _endian: E, parity: Parity, data_bits: u32) -> Self {
+        ParityWriter::new(writer, parity, data_bits)
+    }
+
+    /// Returns the number of data bits written in the current group
+    #[inline]
+    pub fn group_position(&self) -> u32 {
+        self.state.count
+    }
+
+    /// Provides mutable reference to internal writer
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of ParityWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: BitWrite, E: Endianness> BitWrite for ParityWriter<W, E> {
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.writer.write_bit(bit)?;
+        match self.state.push(bit) {
+            Some(parity_bit) => self.writer.write_bit(parity_bit),
+            None => Ok(()),
+        }
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            let mut queue = BitQueue::<E, U>::from_value(value, bits);
+            while !queue.is_empty() {
+                self.write_bit(!queue.pop(1).is_zero())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+}
+
+/// A bitstream wrapper which checks and removes the parity bit
+/// following every group of data bits read from it.
+///
+/// Because parity bits change how many bits are taken from the
+/// wrapped reader, its alignment is what's reported
+/// by `byte_aligned` and discarded by `byte_align`.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitReader, BitRead};
+/// use bitstream_io::parity::{Parity, ParityReader};
+/// let data = [0b1011_1110, 0b0001_0010];
+/// let mut reader = ParityReader::endian(
+///     BitReader::endian(&data[..], BigEndian),
+///     BigEndian,
+///     Parity::Odd,
+///     3,
+/// );
+/// assert_eq!(reader.read::<u8>(6).unwrap(), 0b101_111);
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b000);
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b001);
+///
+/// // a flipped data bit is an error
+/// let data = [0b1011_1110, 0b1001_0010];
+/// let mut reader = ParityReader::endian(
+///     BitReader::endian(&data[..], BigEndian),
+///     BigEndian,
+///     Parity::Odd,
+///     3,
+/// );
+/// assert_eq!(reader.read::<u8>(6).unwrap(), 0b101_111);
+/// assert!(reader.read::<u8>(3).is_err());
+/// ```
+///
+/// ```
+/// use bitstream_io::{LittleEndian, BitReader, BitRead};
+/// use bitstream_io::parity::{Parity, ParityReader};
+/// let data = [0b0111_1101, 0b0100_1000];
+/// let mut reader = ParityReader::endian(
+///     BitReader::endian(&data[..], LittleEndian),
+///     LittleEndian,
+///     Parity::Odd,
+///     3,
+/// );
+/// assert_eq!(reader.read::<u8>(6).unwrap(), 0b111_101);
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b000);
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b100);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParityReader<R: BitRead, E: Endianness> {
+    reader: R,
+    state: ParityState,
+    phantom: PhantomData<E>,
+}
+
+impl<R: BitRead, E: Endianness> ParityReader<R, E> {
+    /// Wraps a ParityReader around something that implements `BitRead`
+    /// which expects a parity bit of the given kind
+    /// after every `data_bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data_bits` is 0.
+    #[inline]
+    pub fn new(reader: R, parity: Parity, data_bits: u32) -> Self {
+        ParityReader {
+            reader,
+            state: ParityState::new(parity, data_bits),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a ParityReader around something that implements `BitRead`
+    /// with the given endianness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data_bits` is 0.
+    #[inline]
+    pub fn endian(reader: R, _endian: E, parity: Parity, data_bits: u32) -> Self {
+        ParityReader::new(reader, parity, data_bits)
+    }
+
+    /// Returns the number of data bits read in the current group
+    #[inline]
+    pub fn group_position(&self) -> u32 {
+        self.state.count
+    }
+
+    /// Provides mutable reference to internal reader
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of ParityReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BitRead, E: Endianness> BitRead for ParityReader<R, E> {
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidData` if the bit
+    /// completing a group is followed by a parity bit
+    /// which doesn't match the group's data bits.
+    fn read_bit(&mut self) -> io::Result<bool> {
+        let bit = self.reader.read_bit()?;
+        match self.state.push(bit) {
+            Some(parity_bit) if self.reader.read_bit()? != parity_bit => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "parity bit mismatch",
+            )),
+            _ => Ok(bit),
+        }
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits <= U::bits_size() {
+            let mut queue = BitQueue::<E, U>::new();
+            for _ in 0..bits {
+                queue.push(1, U::from_u8(self.read_bit()?.into()));
+            }
+            Ok(queue.value())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        }
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        for _ in 0..bits {
+            self.read_bit()?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        self.reader.byte_align()
+    }
+}
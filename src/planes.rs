@@ -0,0 +1,285 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversion between packed pixels and separate bit planes.
+//!
+//! Planar image formats, such as Amiga ILBM images
+//! and many e-ink framebuffers, store each bit of a pixel's value
+//! in a separate plane, rather than storing the value's bits together.
+//! A `BitPlanes` layout describes the image's size, its number of planes,
+//! and how those planes are arranged in the stream,
+//! after which `write_planes` splits packed pixel values into planes
+//! and `read_planes` gathers them back into packed values.
+//!
+//! Plane 0 holds each pixel's least significant bit.
+//!
+//! ## Example
+//!
+//! Writing and reading a small 4 color ILBM body,
+//! whose rows are interleaved plane by plane
+//! and padded to 16-bit words.
+//!
+//! ```
+//! use std::io::Cursor;
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::planes::BitPlanes;
+//!
+//! const BODY: BitPlanes = BitPlanes::new(3, 2, 2)
+//!     .row_interleaved()
+//!     .row_alignment(16);
+//! assert_eq!(BODY.total_bits(), 64);
+//!
+//! let pixels: [u8; 6] = [1, 2, 3,
+//!                        0, 3, 1];
+//! let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+//! BODY.write_planes(&mut writer, &pixels).unwrap();
+//! let data = writer.into_writer();
+//! assert_eq!(data, [0b101_00000, 0, 0b011_00000, 0,
+//!                   0b011_00000, 0, 0b010_00000, 0]);
+//!
+//! let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+//! assert_eq!(BODY.read_planes::<_, u8>(&mut reader).unwrap(), pixels);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::Numeric;
+use std::io;
+
+/// The layout of an image's bit planes in a stream
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitPlanes {
+    width: usize,
+    height: usize,
+    planes: u32,
+    interleaved: bool,
+    alignment: u32,
+}
+
+impl BitPlanes {
+    /// Builds a layout for an image of the given width and height in pixels
+    /// with the given number of planes.
+    ///
+    /// By default, each plane is stored in full before the next,
+    /// with no padding between rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of planes is 0.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// use bitstream_io::planes::BitPlanes;
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// BitPlanes::new(4, 1, 2).write_planes(&mut writer, &[0u8, 1, 2, 3]).unwrap();
+    /// //                           plane 0  plane 1
+    /// assert_eq!(writer.into_writer(), [0b0101_0011]);
+    /// ```
+    pub const fn new(width: usize, height: usize, planes: u32) -> Self {
+        assert!(planes > 0, "planes must be nonzero");
+        BitPlanes {
+            width,
+            height,
+            planes,
+            interleaved: false,
+            alignment: 1,
+        }
+    }
+
+    /// Stores a row of each plane in turn, instead of each whole plane.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter};
+    /// use bitstream_io::planes::BitPlanes;
+    /// let pixels = [0u8, 1, 2, 3];
+    ///
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// BitPlanes::new(2, 2, 2).write_planes(&mut writer, &pixels).unwrap();
+    /// //                                 plane 0 plane 1
+    /// assert_eq!(writer.into_writer(), [0b01_01__00_11]);
+    ///
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// BitPlanes::new(2, 2, 2)
+    ///     .row_interleaved()
+    ///     .write_planes(&mut writer, &pixels)
+    ///     .unwrap();
+    /// //                                 row 0   row 1
+    /// assert_eq!(writer.into_writer(), [0b01_00__01_11]);
+    /// ```
+    pub const fn row_interleaved(mut self) -> Self {
+        self.interleaved = true;
+        self
+    }
+
+    /// Pads each row of each plane with 0 bits
+    /// to a multiple of the given number of bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bits is 0.
+    pub const fn row_alignment(mut self, bits: u32) -> Self {
+        assert!(bits > 0, "row alignment must be nonzero");
+        self.alignment = bits;
+        self
+    }
+
+    /// Returns the image's width in pixels
+    #[inline]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the image's height in pixels
+    #[inline]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the image's number of planes
+    #[inline]
+    pub const fn planes(&self) -> u32 {
+        self.planes
+    }
+
+    /// Returns the number of bits in each row of a plane,
+    /// including any padding
+    #[inline]
+    pub const fn row_bits(&self) -> u64 {
+        let width = self.width as u64;
+        let alignment = self.alignment as u64;
+        match width % alignment {
+            0 => width,
+            partial => width + (alignment - partial),
+        }
+    }
+
+    /// Returns the number of bits in the whole image,
+    /// including any padding
+    #[inline]
+    pub const fn total_bits(&self) -> u64 {
+        self.row_bits() * self.height as u64 * self.planes as u64
+    }
+
+    /// Splits the given packed pixels, in row order, into planes
+    /// and writes them to the given stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of pixels doesn't match the image,
+    /// if the number of planes exceeds the size of the pixel type,
+    /// or if any pixel's value is too large for the number of planes.
+    /// Nothing is written in that case.
+    /// Otherwise, passes along any I/O error from the underlying stream.
+    pub fn write_planes<W, U>(&self, writer: &mut W, pixels: &[U]) -> io::Result<()>
+    where
+        W: BitWrite + ?Sized,
+        U: Numeric,
+    {
+        if pixels.len() != self.width * self.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "pixel count doesn't match image size",
+            ));
+        }
+        if self.planes > U::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive planes for pixel type",
+            ));
+        }
+        if self.planes < U::bits_size() && pixels.iter().any(|p| *p >= (U::one() << self.planes)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive pixel value for planes",
+            ));
+        }
+
+        let padding = self.row_bits() - self.width as u64;
+        self.for_each_row(|plane, row| {
+            for pixel in pixels[row * self.width..(row + 1) * self.width].iter() {
+                writer.write_bit(!((*pixel >> plane) % (U::one() << 1)).is_zero())?;
+            }
+            for _ in 0..padding {
+                writer.write_bit(false)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Reads planes from the given stream
+    /// and gathers them into packed pixels, in row order.
+    ///
+    /// Padding bits are skipped without being checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of planes exceeds
+    /// the size of the pixel type.
+    /// Otherwise, passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitReader};
+    /// use bitstream_io::planes::BitPlanes;
+    /// let data = [0b1100_1010];
+    /// let mut reader = BitReader::endian(&data[..], LittleEndian);
+    /// assert_eq!(
+    ///     BitPlanes::new(4, 1, 2).read_planes::<_, u8>(&mut reader).unwrap(),
+    ///     [0, 1, 2, 3],
+    /// );
+    /// ```
+    pub fn read_planes<R, U>(&self, reader: &mut R) -> io::Result<Vec<U>>
+    where
+        R: BitRead + ?Sized,
+        U: Numeric,
+    {
+        if self.planes > U::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive planes for pixel type",
+            ));
+        }
+
+        let mut pixels = vec![U::default(); self.width * self.height];
+        let padding = self.row_bits() - self.width as u64;
+        self.for_each_row(|plane, row| {
+            for pixel in pixels[row * self.width..(row + 1) * self.width].iter_mut() {
+                if reader.read_bit()? {
+                    *pixel |= U::one() << plane;
+                }
+            }
+            reader.skip(padding)
+        })?;
+        Ok(pixels)
+    }
+
+    /// Calls the given function with each plane and row in stream order
+    fn for_each_row<F>(&self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(u32, usize) -> io::Result<()>,
+    {
+        if self.interleaved {
+            for row in 0..self.height {
+                for plane in 0..self.planes {
+                    f(plane, row)?;
+                }
+            }
+        } else {
+            for plane in 0..self.planes {
+                for row in 0..self.height {
+                    f(plane, row)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Python bindings for bitstream readers and writers.
+//!
+//! These expose `BitReader` and `BitWriter` classes over `bytes`
+//! to Python by way of `pyo3`, with the same semantics
+//! as `DynBitReader` and `DynBitWriter`,
+//! so a format may be prototyped in Python
+//! and parsed identically by a production Rust parser.
+//!
+//! The `bitstream_io` function initializes the Python module.
+//! To build an importable extension module,
+//! compile the crate as a `cdylib` with the `python` feature
+//! and `pyo3`'s `extension-module` feature enabled,
+//! such as with `maturin`.
+//!
+//! Errors are raised as Python exceptions:
+//! `ValueError` for out-of-range bit counts or values,
+//! `EOFError` for reads past the end of the data,
+//! and `OSError` otherwise.
+//!
+//! ## Example
+//!
+//! ```python
+//! from bitstream_io import BitReader, BitWriter
+//!
+//! writer = BitWriter("big")
+//! writer.write(3, 0b101)
+//! writer.write_signed(5, -3)
+//! writer.byte_align()
+//! assert writer.getvalue() == bytes([0b101_11101])
+//!
+//! reader = BitReader(writer.getvalue(), "big")
+//! assert reader.read(3) == 0b101
+//! assert reader.read_signed(5) == -3
+//! ```
+
+#![warn(missing_docs)]
+// pyo3's generated method wrappers convert each error into itself
+#![allow(clippy::useless_conversion)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{DynBitReader, DynBitWriter, DynEndianness};
+use pyo3::exceptions::{PyEOFError, PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::io;
+
+fn endianness(endianness: &str) -> PyResult<DynEndianness> {
+    match endianness {
+        "big" => Ok(DynEndianness::Big),
+        "little" => Ok(DynEndianness::Little),
+        _ => Err(PyValueError::new_err(
+            "endianness must be \"big\" or \"little\"",
+        )),
+    }
+}
+
+fn py_error(err: io::Error) -> PyErr {
+    match err.kind() {
+        io::ErrorKind::InvalidInput => PyValueError::new_err(err.to_string()),
+        io::ErrorKind::UnexpectedEof => PyEOFError::new_err(err.to_string()),
+        _ => PyOSError::new_err(err.to_string()),
+    }
+}
+
+/// Writes bits to an in-memory buffer of bytes
+/// in "big" or "little" endianness.
+#[pyclass(name = "BitWriter", module = "bitstream_io")]
+#[derive(Debug)]
+pub struct PyBitWriter {
+    writer: DynBitWriter<Vec<u8>>,
+}
+
+#[pymethods]
+impl PyBitWriter {
+    #[new]
+    #[pyo3(signature = (endianness = "big"))]
+    fn new(endianness: &str) -> PyResult<Self> {
+        Ok(PyBitWriter {
+            writer: DynBitWriter::new(Vec::new(), self::endianness(endianness)?),
+        })
+    }
+
+    /// Writes a single bit.
+    fn write_bit(&mut self, bit: bool) -> PyResult<()> {
+        self.writer.write_bit(bit).map_err(py_error)
+    }
+
+    /// Writes an unsigned value of up to 64 bits.
+    fn write(&mut self, bits: u32, value: u64) -> PyResult<()> {
+        self.writer.write(bits, value).map_err(py_error)
+    }
+
+    /// Writes a two's complement signed value of 1 to 64 bits.
+    fn write_signed(&mut self, bits: u32, value: i64) -> PyResult<()> {
+        let fits = match bits {
+            1..=63 => (-(1 << (bits - 1))..(1 << (bits - 1))).contains(&value),
+            64 => true,
+            _ => false,
+        };
+        if fits {
+            self.writer.write_signed(bits, value).map_err(py_error)
+        } else {
+            Err(PyValueError::new_err("excessive value for bits written"))
+        }
+    }
+
+    /// Writes whole bytes.
+    fn write_bytes(&mut self, data: &[u8]) -> PyResult<()> {
+        self.writer.write_bytes(data).map_err(py_error)
+    }
+
+    /// Pads the writer with 0 bits until it is byte-aligned.
+    fn byte_align(&mut self) -> PyResult<()> {
+        self.writer.byte_align().map_err(py_error)
+    }
+
+    /// Returns True if the writer is byte-aligned.
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+
+    /// Returns the whole bytes written so far.
+    /// Any partial byte is not included until it is completed.
+    fn getvalue<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        let data: &[u8] = match &self.writer {
+            DynBitWriter::Big(writer) => writer.written_so_far().0,
+            DynBitWriter::Little(writer) => writer.written_so_far().0,
+        };
+        PyBytes::new_bound(py, data)
+    }
+}
+
+/// Reads bits from a copy of some bytes
+/// in "big" or "little" endianness.
+#[pyclass(name = "BitReader", module = "bitstream_io")]
+#[derive(Debug)]
+pub struct PyBitReader {
+    reader: DynBitReader<io::Cursor<Vec<u8>>>,
+}
+
+#[pymethods]
+impl PyBitReader {
+    #[new]
+    #[pyo3(signature = (data, endianness = "big"))]
+    fn new(data: &[u8], endianness: &str) -> PyResult<Self> {
+        Ok(PyBitReader {
+            reader: DynBitReader::new(
+                io::Cursor::new(data.to_vec()),
+                self::endianness(endianness)?,
+            ),
+        })
+    }
+
+    /// Reads a single bit.
+    fn read_bit(&mut self) -> PyResult<bool> {
+        self.reader.read_bit().map_err(py_error)
+    }
+
+    /// Reads an unsigned value of up to 64 bits.
+    fn read(&mut self, bits: u32) -> PyResult<u64> {
+        self.reader.read(bits).map_err(py_error)
+    }
+
+    /// Reads a two's complement signed value of 1 to 64 bits.
+    fn read_signed(&mut self, bits: u32) -> PyResult<i64> {
+        if (1..=64).contains(&bits) {
+            self.reader.read_signed(bits).map_err(py_error)
+        } else {
+            Err(PyValueError::new_err("excessive bits for type read"))
+        }
+    }
+
+    /// Reads the given number of whole bytes.
+    fn read_bytes<'py>(&mut self, py: Python<'py>, len: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let mut data = vec![0; len];
+        self.reader.read_bytes(&mut data).map_err(py_error)?;
+        Ok(PyBytes::new_bound(py, &data))
+    }
+
+    /// Skips the given number of bits.
+    fn skip(&mut self, bits: u64) -> PyResult<()> {
+        self.reader.skip(bits).map_err(py_error)
+    }
+
+    /// Discards bits until the reader is byte-aligned.
+    fn byte_align(&mut self) {
+        self.reader.byte_align()
+    }
+
+    /// Returns True if the reader is byte-aligned.
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    /// Returns the number of bits read from the start of the data.
+    fn position(&mut self) -> PyResult<u64> {
+        match &mut self.reader {
+            DynBitReader::Big(reader) => reader.position_in_bits(),
+            DynBitReader::Little(reader) => reader.position_in_bits(),
+        }
+        .map_err(py_error)
+    }
+}
+
+/// Initializes the `bitstream_io` Python module
+/// with its `BitReader` and `BitWriter` classes.
+#[pymodule]
+pub fn bitstream_io(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBitReader>()?;
+    module.add_class::<PyBitWriter>()?;
+    Ok(())
+}
@@ -0,0 +1,312 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Traits and implementations for binary range coding
+//! to or from a stream.
+//!
+//! The range coder encodes a sequence of bits,
+//! each according to the probability stored in an adaptive `Context`
+//! which is updated as bits are encoded or decoded.
+//! Bits that are likely according to their context
+//! therefore take up less than a single bit of output.
+//! Bits may also be coded directly, without any context,
+//! at a fixed cost of one bit each.
+//!
+//! The encoder writes whole 8-bit values to any `BitWrite`
+//! and the decoder reads them from any `BitRead`,
+//! so range coded data may begin at any position in a stream.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitWriter};
+//! use bitstream_io::rangecoder::{Context, RangeDecoder, RangeEncoder};
+//!
+//! let bits = [false, false, true, false, false, false, true, false];
+//!
+//! let mut encoder = RangeEncoder::new(BitWriter::endian(Vec::new(), BigEndian));
+//! let mut context = Context::new();
+//! for bit in bits.iter() {
+//!     encoder.encode(&mut context, *bit).unwrap();
+//! }
+//! encoder.encode_direct(12, 0xABC).unwrap();
+//! let data = encoder.finish().unwrap().into_writer();
+//!
+//! let mut decoder = RangeDecoder::new(BitReader::endian(Cursor::new(&data), BigEndian)).unwrap();
+//! let mut context = Context::new();
+//! for bit in bits.iter() {
+//!     assert_eq!(decoder.decode(&mut context).unwrap(), *bit);
+//! }
+//! assert_eq!(decoder.decode_direct(12).unwrap(), 0xABC);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::io;
+
+use super::read::BitRead;
+use super::write::BitWrite;
+
+const TOP_VALUE: u32 = 1 << 24;
+const PROBABILITY_BITS: u32 = 11;
+const PROBABILITY_MAX: u16 = 1 << PROBABILITY_BITS;
+const MOVE_BITS: u32 = 5;
+
+/// An adaptive probability for coding a single bit.
+///
+/// Each context tracks the likelihood of a 0 bit
+/// for the bits coded with it, so a distinct context
+/// should be used for each distinct kind of bit in a stream.
+/// Encoders and decoders must use contexts in exactly the same order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Context {
+    probability: u16,
+}
+
+impl Context {
+    /// Creates a new context with an equal likelihood of 0 and 1 bits
+    #[inline]
+    pub fn new() -> Context {
+        Context {
+            probability: PROBABILITY_MAX / 2,
+        }
+    }
+
+    /// Returns the context's current probability of a 0 bit
+    /// as a fraction of 2048.
+    #[inline]
+    pub fn probability(&self) -> u16 {
+        self.probability
+    }
+
+    #[inline]
+    fn bound(&self, range: u32) -> u32 {
+        (range >> PROBABILITY_BITS) * u32::from(self.probability)
+    }
+
+    #[inline]
+    fn update(&mut self, bit: bool) {
+        if bit {
+            self.probability -= self.probability >> MOVE_BITS;
+        } else {
+            self.probability += (PROBABILITY_MAX - self.probability) >> MOVE_BITS;
+        }
+    }
+}
+
+impl Default for Context {
+    #[inline]
+    fn default() -> Self {
+        Context::new()
+    }
+}
+
+/// For range coding bits to an underlying bit writer.
+///
+/// Because the encoder holds pending output internally,
+/// it must be finished with the `finish` method
+/// in order to write all of its output.
+pub struct RangeEncoder<W: BitWrite> {
+    writer: W,
+    low: u64,
+    range: u32,
+    cache: u8,
+    cache_size: u64,
+}
+
+impl<W: BitWrite> RangeEncoder<W> {
+    /// Wraps a RangeEncoder around something that implements `BitWrite`
+    pub fn new(writer: W) -> RangeEncoder<W> {
+        RangeEncoder {
+            writer,
+            low: 0,
+            range: 0xFFFF_FFFF,
+            cache: 0,
+            cache_size: 1,
+        }
+    }
+
+    /// Encodes a single bit using the given context
+    /// and updates that context.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn encode(&mut self, context: &mut Context, bit: bool) -> io::Result<()> {
+        let bound = context.bound(self.range);
+        if bit {
+            self.low += u64::from(bound);
+            self.range -= bound;
+        } else {
+            self.range = bound;
+        }
+        context.update(bit);
+        self.normalize()
+    }
+
+    /// Encodes the given number of bits from an unsigned value,
+    /// most-significant bit first, with a fixed probability
+    /// of one half for each.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if more than 32 bits are requested
+    /// or if the value is too large to fit the given number of bits.
+    pub fn encode_direct(&mut self, bits: u32, value: u32) -> io::Result<()> {
+        if bits > 32 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < 32) && (value >= (1 << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            for i in (0..bits).rev() {
+                self.range >>= 1;
+                if (value >> i) & 1 == 1 {
+                    self.low += u64::from(self.range);
+                }
+                self.normalize()?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Writes any pending output to the underlying writer
+    /// and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn finish(mut self) -> io::Result<W> {
+        for _ in 0..5 {
+            self.shift_low()?;
+        }
+        Ok(self.writer)
+    }
+
+    #[inline]
+    fn normalize(&mut self) -> io::Result<()> {
+        while self.range < TOP_VALUE {
+            self.range <<= 8;
+            self.shift_low()?;
+        }
+        Ok(())
+    }
+
+    fn shift_low(&mut self) -> io::Result<()> {
+        if (self.low as u32) < 0xFF00_0000 || (self.low >> 32) != 0 {
+            let carry = (self.low >> 32) as u8;
+            let mut byte = self.cache;
+            while self.cache_size > 0 {
+                self.writer.write(8, byte.wrapping_add(carry))?;
+                byte = 0xFF;
+                self.cache_size -= 1;
+            }
+            self.cache = (self.low >> 24) as u8;
+        }
+        self.cache_size += 1;
+        self.low = (self.low & 0x00FF_FFFF) << 8;
+        Ok(())
+    }
+}
+
+/// For decoding range coded bits from an underlying bit reader.
+pub struct RangeDecoder<R: BitRead> {
+    reader: R,
+    code: u32,
+    range: u32,
+}
+
+impl<R: BitRead> RangeDecoder<R> {
+    /// Wraps a RangeDecoder around something that implements `BitRead`,
+    /// reading the start of the range coded data from it.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn new(mut reader: R) -> io::Result<RangeDecoder<R>> {
+        let mut code = 0;
+        for _ in 0..5 {
+            code = (code << 8) | reader.read::<u32>(8)?;
+        }
+        Ok(RangeDecoder {
+            reader,
+            code,
+            range: 0xFFFF_FFFF,
+        })
+    }
+
+    /// Unwraps internal reader and disposes of RangeDecoder.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Decodes a single bit using the given context
+    /// and updates that context.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    pub fn decode(&mut self, context: &mut Context) -> io::Result<bool> {
+        let bound = context.bound(self.range);
+        let bit = if self.code < bound {
+            self.range = bound;
+            false
+        } else {
+            self.code -= bound;
+            self.range -= bound;
+            true
+        };
+        context.update(bit);
+        self.normalize().map(|()| bit)
+    }
+
+    /// Decodes an unsigned value of the given number of bits,
+    /// most-significant bit first, with a fixed probability
+    /// of one half for each.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if more than 32 bits are requested.
+    pub fn decode_direct(&mut self, bits: u32) -> io::Result<u32> {
+        if bits > 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ));
+        }
+        let mut value = 0;
+        for _ in 0..bits {
+            self.range >>= 1;
+            value <<= 1;
+            if self.code >= self.range {
+                self.code -= self.range;
+                value |= 1;
+            }
+            self.normalize()?;
+        }
+        Ok(value)
+    }
+
+    #[inline]
+    fn normalize(&mut self) -> io::Result<()> {
+        while self.range < TOP_VALUE {
+            self.range <<= 8;
+            self.code = (self.code << 8) | self.reader.read::<u32>(8)?;
+        }
+        Ok(())
+    }
+}
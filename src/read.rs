@@ -74,9 +74,170 @@
 
 #![warn(missing_docs)]
 
+#[cfg(feature = "observer")]
+use super::observer::{BitObserver, Observed};
+#[cfg(feature = "bitvec")]
+use bitvec::{order::Msb0, vec::BitVec};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
 use std::io;
+use std::ops::Range;
+#[cfg(feature = "observer")]
+use std::sync::{Arc, Mutex};
 
-use super::{huffman::ReadHuffmanTree, BitQueue, Endianness, Numeric, PhantomData, SignedNumeric};
+use super::write::BitWrite;
+use super::{
+    buffer::BitBuffer, huffman::AdaptiveHuffmanTree, huffman::CompactReadHuffmanTree,
+    huffman::ReadHuffmanTree, numeric_from_u64, numeric_to_u128, reverse_bits, schema::Record,
+    schema::Schema, signed_from_i64, split::SplitField, BcdOrder, BigEndian, BitLimitError,
+    BitQueue, DynEndianness, Endianness, FloatNumeric, LittleEndian, Numeric, PhantomData,
+    SignPlacement, SignedNumeric, SignedRepr, BCD_MAX_DIGITS,
+};
+
+/// How strings read from a stream which are not valid UTF-8 are handled
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Invalid UTF-8 is an error
+    Strict,
+    /// Invalid UTF-8 sequences are replaced with U+FFFD
+    Lossy,
+}
+
+impl Utf8Policy {
+    fn decode(self, bytes: Vec<u8>) -> io::Result<String> {
+        match self {
+            Utf8Policy::Strict => String::from_utf8(bytes)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 in string")),
+            Utf8Policy::Lossy => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+}
+
+/// An error from `BitRead::read_expect`
+/// when the stream doesn't contain the expected value.
+///
+/// This is returned as the inner error
+/// of an `io::Error` of kind `InvalidData`,
+/// from which `from_io` retrieves it.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct UnexpectedValueError {
+    bits: u32,
+    expected: u128,
+    found: u128,
+}
+
+impl UnexpectedValueError {
+    fn new<U: Numeric>(bits: u32, expected: U, found: U) -> Self {
+        // values are kept as their unsigned bit patterns
+        UnexpectedValueError {
+            bits,
+            expected: numeric_to_u128(expected),
+            found: numeric_to_u128(found),
+        }
+    }
+
+    /// Returns the number of bits read
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the value which was expected
+    #[inline]
+    pub fn expected(&self) -> u128 {
+        self.expected
+    }
+
+    /// Returns the value which was read instead
+    #[inline]
+    pub fn found(&self) -> u128 {
+        self.found
+    }
+
+    /// Returns the `UnexpectedValueError` wrapped by an `io::Error`, if any
+    pub fn from_io(err: &io::Error) -> Option<&UnexpectedValueError> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<UnexpectedValueError>())
+    }
+}
+
+impl fmt::Display for UnexpectedValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected {:#x} in {} bits but found {:#x}",
+            self.expected, self.bits, self.found
+        )
+    }
+}
+
+impl std::error::Error for UnexpectedValueError {}
+
+impl From<UnexpectedValueError> for io::Error {
+    #[inline]
+    fn from(err: UnexpectedValueError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// An error from `BitRead::read_bytes_checked` or `BitRead::read_vec`
+/// when a length read from the stream exceeds the caller's maximum.
+///
+/// This is returned as the inner error
+/// of an `io::Error` of kind `InvalidData`,
+/// from which `from_io` retrieves it.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct LengthLimitError {
+    len: u64,
+    max: u64,
+}
+
+impl LengthLimitError {
+    /// Returns the length which was requested
+    #[inline]
+    pub fn requested(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns the maximum length which was allowed
+    #[inline]
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Returns the `LengthLimitError` wrapped by an `io::Error`, if any
+    pub fn from_io(err: &io::Error) -> Option<&LengthLimitError> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<LengthLimitError>())
+    }
+}
+
+impl fmt::Display for LengthLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "length {} exceeds maximum of {}", self.len, self.max)
+    }
+}
+
+impl std::error::Error for LengthLimitError {}
+
+impl From<LengthLimitError> for io::Error {
+    #[inline]
+    fn from(err: LengthLimitError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Checks an untrusted length against a maximum
+/// before anything is allocated for it
+fn check_length(len: u64, max: u64) -> io::Result<usize> {
+    if len > max {
+        Err(LengthLimitError { len, max }.into())
+    } else {
+        usize::try_from(len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "excessive length"))
+    }
+}
 
 /// A trait for anything that can read a variable number of
 /// potentially un-aligned values from an input stream
@@ -113,550 +274,4091 @@ pub trait BitRead {
     where
         S: SignedNumeric;
 
-    /// Skips the given number of bits in the stream.
-    /// Since this method does not need an accumulator,
-    /// it may be slightly faster than reading to an empty variable.
-    /// In addition, since there is no accumulator,
-    /// there is no upper limit on the number of bits
-    /// which may be skipped.
-    /// These bits are still read from the stream, however,
-    /// and are never skipped via a `seek` method.
+    /// Reads an unsigned value from the stream with
+    /// the given number of bits and checks that it matches
+    /// a required constant, such as a sync word,
+    /// a marker bit or a reserved field which must be 0.
+    /// The bits are consumed whether the value matches or not.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
-    fn skip(&mut self, bits: u32) -> io::Result<()>;
-
-    /// Completely fills the given buffer with whole bytes.
-    /// If the stream is already byte-aligned, it will map
-    /// to a faster `read_exact` call.  Otherwise it will read
-    /// bytes individually in 8-bit increments.
-    ///
-    /// # Errors
+    /// Returns an error of kind `InvalidData` wrapping an
+    /// `UnexpectedValueError` if some other value is read.
+    /// Returns an error of kind `InvalidInput` without reading anything
+    /// if the expected value doesn't fit in the given number of bits.
     ///
-    /// Passes along any I/O error from the underlying stream.
-    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
-        for b in buf.iter_mut() {
-            *b = self.read(8)?;
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor, ErrorKind};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// use bitstream_io::read::UnexpectedValueError;
+    /// let data = [0xFF, 0xF1, 0b0_1_000000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// reader.read_expect(12, 0xFFFu16).unwrap();
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 1);
+    /// reader.read_expect(1, 0u8).unwrap();
+    /// let err = reader.read_expect(1, 0u8).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidData);
+    /// assert_eq!(err.to_string(), "expected 0x0 in 1 bits but found 0x1");
+    /// let err = UnexpectedValueError::from_io(&err).unwrap();
+    /// assert_eq!((err.bits(), err.expected(), err.found()), (1, 0, 1));
+    /// ```
+    fn read_expect<U>(&mut self, bits: u32, expected: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits < U::bits_size() && expected >= (U::one() << bits) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits read",
+            ));
+        }
+        let found = self.read(bits)?;
+        if found == expected {
+            Ok(())
+        } else {
+            Err(UnexpectedValueError::new(bits, expected, found).into())
         }
-        Ok(())
     }
 
-    /// Counts the number of 1 bits in the stream until the next
-    /// 0 bit and returns the amount read.
-    /// Because this field is variably-sized and may be large,
-    /// its output is always a `u32` type.
+    /// Reads a signed value from the stream with the given number of bits
+    /// in the given signed representation.
+    /// Negative zero is returned as zero.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
-    fn read_unary0(&mut self) -> io::Result<u32> {
-        let mut unary = 0;
-        while self.read_bit()? {
-            unary += 1;
-        }
-        Ok(unary)
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, SignedRepr};
+    /// let data = [0b1011_1100];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_signed_with::<i8>(SignedRepr::SignMagnitude, 4).unwrap(), -3);
+    /// assert_eq!(reader.read_signed_with::<i8>(SignedRepr::OnesComplement, 4).unwrap(), -3);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead, SignedRepr};
+    /// let data = [0b1111_1000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_signed_with::<i8>(SignedRepr::SignMagnitude, 4).unwrap(), 0);
+    /// assert_eq!(reader.read_signed_with::<i8>(SignedRepr::OnesComplement, 4).unwrap(), 0);
+    /// ```
+    #[inline]
+    fn read_signed_with<S>(&mut self, repr: SignedRepr, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        self.read_signed(bits).map(|value| repr.decode(bits, value))
     }
 
-    /// Counts the number of 0 bits in the stream until the next
-    /// 1 bit and returns the amount read.
-    /// Because this field is variably-sized and may be large,
-    /// its output is always a `u32` type.
+    /// Reads a twos-complement signed value from the stream
+    /// with the given number of bits,
+    /// whose sign bit is placed before or after its remaining bits
+    /// regardless of the stream's endianness.
+    /// The remaining bits are read as an unsigned value
+    /// in the stream's endianness.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
-    fn read_unary1(&mut self) -> io::Result<u32> {
-        let mut unary = 0;
-        while !(self.read_bit()?) {
-            unary += 1;
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits, or if `bits` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, SignPlacement};
+    /// let data = [0b1101_1_101];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_signed_placed::<i8>(SignPlacement::Last, 5).unwrap(), -3);
+    /// assert_eq!(reader.read_signed_placed::<i8>(SignPlacement::First, 3).unwrap(), -3);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead, SignPlacement};
+    /// let data = [0b101_1101_1];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_signed_placed::<i8>(SignPlacement::First, 5).unwrap(), -3);
+    /// assert_eq!(reader.read_signed_placed::<i8>(SignPlacement::Last, 3).unwrap(), -3);
+    /// ```
+    fn read_signed_placed<S>(&mut self, placement: SignPlacement, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        if bits == 0 || bits > S::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ));
         }
-        Ok(unary)
+        let (negative, rest) = match placement {
+            SignPlacement::First => {
+                let negative = self.read_bit()?;
+                (negative, read_sign_rest::<_, S>(self, bits)?)
+            }
+            SignPlacement::Last => {
+                let rest = read_sign_rest::<_, S>(self, bits)?;
+                (self.read_bit()?, rest)
+            }
+        };
+        Ok(if negative {
+            rest.as_negative(bits)
+        } else {
+            rest
+        })
     }
 
-    /// Returns true if the stream is aligned at a whole byte.
-    fn byte_aligned(&self) -> bool;
-
-    /// Throws away all unread bit values until the next whole byte.
-    /// Does nothing if the stream is already aligned.
-    fn byte_align(&mut self);
-}
-
-/// A trait for anything that can read Huffman codes
-/// of a given endianness from an input stream
-pub trait HuffmanRead<E: Endianness> {
-    /// Given a compiled Huffman tree, reads bits from the stream
-    /// until the next symbol is encountered.
+    /// Reads interleaved samples, as is common for PCM audio,
+    /// into a buffer per channel,
+    /// with each sample read as a signed value of the given number of bits.
+    /// The first sample of every channel is read in channel order,
+    /// followed by the second sample of every channel, and so on,
+    /// until every buffer is filled.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
-    fn read_huffman<T>(&mut self, tree: &[ReadHuffmanTree<E, T>]) -> io::Result<T>
-    where
-        T: Clone;
-}
-
-/// For reading non-aligned bits from a stream of bytes in a given endianness.
-///
-/// This will read exactly as many whole bytes needed to return
-/// the requested number of bits.  It may cache up to a single partial byte
-/// but no more.
-#[derive(Clone)]
-pub struct BitReader<R: io::Read, E: Endianness> {
-    reader: R,
-    bitqueue: BitQueue<E, u8>,
-}
-
-impl<R: io::Read, E: Endianness> BitReader<R, E> {
-    /// Wraps a BitReader around something that implements `Read`
-    pub fn new(reader: R) -> BitReader<R, E> {
-        BitReader {
-            reader,
-            bitqueue: BitQueue::new(),
+    /// Returns an error of kind `InvalidInput` if the buffers
+    /// don't all have the same length,
+    /// or if `bits` isn't between 1 and 32.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0x12, 0x34, 0x56, 0xFF, 0xF8, 0x00];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let (mut left, mut right) = ([0; 2], [0; 2]);
+    /// reader.read_samples_interleaved(12, &mut [&mut left, &mut right]).unwrap();
+    /// assert_eq!(left, [0x123, -1]);
+    /// assert_eq!(right, [0x456, -0x800]);
+    /// ```
+    fn read_samples_interleaved(
+        &mut self,
+        bits: u32,
+        channels: &mut [&mut [i32]],
+    ) -> io::Result<()> {
+        if bits == 0 || bits > 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ));
         }
-    }
-
-    /// Wraps a BitReader around something that implements `Read`
-    /// with the given endianness.
-    pub fn endian(reader: R, _endian: E) -> BitReader<R, E> {
-        BitReader {
-            reader,
-            bitqueue: BitQueue::new(),
+        let frames = channels.first().map(|channel| channel.len()).unwrap_or(0);
+        if channels.iter().any(|channel| channel.len() != frames) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "channels have different numbers of samples",
+            ));
+        }
+        for frame in 0..frames {
+            for channel in channels.iter_mut() {
+                channel[frame] = self.read_signed(bits)?;
+            }
         }
+        Ok(())
     }
 
-    /// Unwraps internal reader and disposes of BitReader.
+    /// Reads a zigzag-encoded unsigned value of the given number of bits
+    /// from the stream, where 0, 1, 2, 3, 4 and so on
+    /// are returned as 0, -1, 1, -2, 2.
     ///
-    /// # Warning
+    /// # Errors
     ///
-    /// Any unread partial bits are discarded.
-    #[inline]
-    pub fn into_reader(self) -> R {
-        self.reader
-    }
-
-    /// If stream is byte-aligned, provides mutable reference
-    /// to internal reader.  Otherwise returns `None`
-    #[inline]
-    pub fn reader(&mut self) -> Option<&mut R> {
-        if self.byte_aligned() {
-            Some(&mut self.reader)
-        } else {
-            None
-        }
-    }
-
-    /// Converts `BitReader` to `ByteReader` in the same endianness.
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if more than 64 bits are requested.
     ///
-    /// # Warning
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b0001_0100];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_zigzag(4).unwrap(), -1);
+    /// assert_eq!(reader.read_zigzag(4).unwrap(), 2);
+    /// ```
     ///
-    /// Any unread partial bits are discarded.
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b0100_0001];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_zigzag(4).unwrap(), -1);
+    /// assert_eq!(reader.read_zigzag(4).unwrap(), 2);
+    /// ```
     #[inline]
-    pub fn into_bytereader(self) -> ByteReader<R, E> {
-        ByteReader::new(self.into_reader())
+    fn read_zigzag(&mut self, bits: u32) -> io::Result<i64> {
+        self.read::<u64>(bits)
+            .map(|value| ((value >> 1) as i64) ^ -((value & 1) as i64))
     }
 
-    /// If stream is byte-aligned, provides temporary `ByteReader`
-    /// in the same endianness.  Otherwise returns `None`
+    /// Reads a reflected binary Gray code of the given number of bits
+    /// from the stream and returns the unsigned value it encodes.
     ///
-    /// # Warning
+    /// # Errors
     ///
-    /// Any reader bits left over when `ByteReader` is dropped are lost.
-    #[inline]
-    pub fn bytereader(&mut self) -> Option<ByteReader<&mut R, E>> {
-        self.reader().map(ByteReader::new)
-    }
-
-    /// Consumes reader and returns any un-read partial byte
-    /// as a `(bits, value)` tuple.
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
     ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b1010_0101, 0b0101_1010];
+    /// let data = [0b111_01010];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.read::<u16>(9).unwrap(), 0b1010_0101_0);
-    /// let (bits, value) = reader.into_unread();
-    /// assert_eq!(bits, 7);
-    /// assert_eq!(value, 0b101_1010);
+    /// assert_eq!(reader.read_gray::<u8>(3).unwrap(), 5);
+    /// assert_eq!(reader.read_gray::<u8>(5).unwrap(), 12);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b1010_0101, 0b0101_1010];
-    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.read::<u16>(8).unwrap(), 0b1010_0101);
-    /// let (bits, value) = reader.into_unread();
-    /// assert_eq!(bits, 0);
-    /// assert_eq!(value, 0);
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b01010_111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_gray::<u8>(3).unwrap(), 5);
+    /// assert_eq!(reader.read_gray::<u8>(5).unwrap(), 12);
     /// ```
-    #[inline]
-    pub fn into_unread(self) -> (u32, u8) {
-        (self.bitqueue.len(), self.bitqueue.value())
+    fn read_gray<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        let mut value: U = self.read(bits)?;
+        let mut shift = 1;
+        while shift < bits {
+            value = value ^ (value >> shift);
+            shift <<= 1;
+        }
+        Ok(value)
     }
-}
 
-impl<R: io::Read, E: Endianness> BitRead for BitReader<R, E> {
-    /// # Examples
+    /// Reads an unsigned value from the stream with the given number
+    /// of bits, with the order of the field's bits reversed.
+    /// That is, a big-endian stream supplies the value's
+    /// least significant bit first and a little-endian stream
+    /// supplies its most significant bit first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
     ///
+    /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// let data = [0b011_11101];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), false);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), false);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_reversed::<u8>(3).unwrap(), 0b110);
+    /// assert_eq!(reader.read_reversed::<u8>(5).unwrap(), 0b10111);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// let data = [0b11101_011];
     /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), false);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
-    /// assert_eq!(reader.read_bit().unwrap(), false);
-    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_reversed::<u8>(3).unwrap(), 0b110);
+    /// assert_eq!(reader.read_reversed::<u8>(5).unwrap(), 0b10111);
     /// ```
-    #[inline(always)]
-    fn read_bit(&mut self) -> io::Result<bool> {
-        if self.bitqueue.is_empty() {
-            self.bitqueue.set(read_byte(&mut self.reader)?, 8);
-        }
-        Ok(self.bitqueue.pop(1) == 1)
+    #[inline]
+    fn read_reversed<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        self.read(bits).map(|value| reverse_bits(bits, value))
     }
 
+    /// Reads a binary-coded decimal value of the given number of
+    /// 4-bit digits, most significant digit first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidData` if any digit is
+    /// larger than 9, and an error of kind `InvalidInput`
+    /// if more than 19 digits are requested.
+    ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// let data = [0x12, 0x34, 0x5A];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.read::<u8>(1).unwrap(), 0b1);
-    /// assert_eq!(reader.read::<u8>(2).unwrap(), 0b01);
-    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10111);
+    /// assert_eq!(reader.read_bcd(4).unwrap(), 1234);
+    /// assert!(reader.read_bcd(2).is_err());
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// let data = [0x21, 0x43, 0xA5];
     /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
-    /// assert_eq!(reader.read::<u8>(1).unwrap(), 0b1);
-    /// assert_eq!(reader.read::<u8>(2).unwrap(), 0b11);
-    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// assert_eq!(reader.read_bcd(4).unwrap(), 1234);
+    /// assert!(reader.read_bcd(2).is_err());
     /// ```
+    #[inline]
+    fn read_bcd(&mut self, digits: u32) -> io::Result<u64> {
+        self.read_bcd_with(BcdOrder::MostSignificantFirst, digits)
+    }
+
+    /// Reads a binary-coded decimal value of the given number of
+    /// 4-bit digits, stored in the given order.
+    ///
+    /// # Errors
     ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidData` if any digit is
+    /// larger than 9, and an error of kind `InvalidInput`
+    /// if more than 19 digits are requested.
+    ///
+    /// # Example
     /// ```
     /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0;10];
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, BcdOrder};
+    /// let data = [0x12, 0x34];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert!(reader.read::<u8>(9).is_err());    // can't read  9 bits to u8
-    /// assert!(reader.read::<u16>(17).is_err());  // can't read 17 bits to u16
-    /// assert!(reader.read::<u32>(33).is_err());  // can't read 33 bits to u32
-    /// assert!(reader.read::<u64>(65).is_err());  // can't read 65 bits to u64
+    /// assert_eq!(reader.read_bcd_with(BcdOrder::LeastSignificantFirst, 4).unwrap(), 4321);
     /// ```
-    fn read<U>(&mut self, mut bits: u32) -> io::Result<U>
-    where
-        U: Numeric,
-    {
-        if bits <= U::bits_size() {
-            let bitqueue_len = self.bitqueue.len();
-            if bits <= bitqueue_len {
-                Ok(U::from_u8(self.bitqueue.pop(bits)))
-            } else {
-                let mut acc =
-                    BitQueue::from_value(U::from_u8(self.bitqueue.pop_all()), bitqueue_len);
-                bits -= bitqueue_len;
-
-                read_aligned(&mut self.reader, bits / 8, &mut acc)?;
-                read_unaligned(&mut self.reader, bits % 8, &mut acc, &mut self.bitqueue)?;
-                Ok(acc.value())
-            }
-        } else {
-            Err(io::Error::new(
+    fn read_bcd_with(&mut self, order: BcdOrder, digits: u32) -> io::Result<u64> {
+        if digits > BCD_MAX_DIGITS {
+            return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
-                "excessive bits for type read",
-            ))
+                "excessive digits for BCD value",
+            ));
+        }
+        let mut value = 0;
+        let mut scale = 1;
+        for _ in 0..digits {
+            let digit = self.read::<u64>(4)?;
+            if digit > 9 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid BCD digit",
+                ));
+            }
+            match order {
+                BcdOrder::MostSignificantFirst => value = value * 10 + digit,
+                BcdOrder::LeastSignificantFirst => {
+                    value += digit * scale;
+                    scale *= 10;
+                }
+            }
         }
+        Ok(value)
     }
 
+    /// Skips the given number of bits in the stream.
+    /// Since this method does not need an accumulator,
+    /// it may be slightly faster than reading to an empty variable.
+    /// In addition, since there is no accumulator,
+    /// there is no upper limit on the number of bits
+    /// which may be skipped.
+    /// These bits are still read from the stream, however,
+    /// and are never skipped via a `seek` method.
+    /// Use `BitReader::seek_bits` to skip over large regions
+    /// of seekable streams without reading them.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn skip(&mut self, bits: u64) -> io::Result<()>;
+
+    /// Reads a value split across several segments of the stream,
+    /// checking that all the field's marker bits have their expected values.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidData` if any marker bits
+    /// don't match.
+    ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// use bitstream_io::split::{Segment, SplitField};
+    /// const FIELD: SplitField = SplitField::new(&[
+    ///     Segment::Value(3),
+    ///     Segment::Marker { bits: 1, value: 1 },
+    ///     Segment::Value(4),
+    /// ]);
+    /// let data = [0b101_1_0110, 0b101_0_0110];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
+    /// assert_eq!(reader.read_split(&FIELD).unwrap(), 0b101_0110);
+    /// assert!(reader.read_split(&FIELD).is_err());
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// use bitstream_io::split::{Segment, SplitField};
+    /// const FIELD: SplitField = SplitField::new(&[
+    ///     Segment::Value(3),
+    ///     Segment::Marker { bits: 1, value: 1 },
+    ///     Segment::Value(4),
+    /// ]);
+    /// let data = [0b0110_1_101, 0b0110_0_101];
     /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
-    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
+    /// assert_eq!(reader.read_split(&FIELD).unwrap(), 0b101_0110);
+    /// assert!(reader.read_split(&FIELD).is_err());
     /// ```
+    #[inline]
+    fn read_split(&mut self, field: &SplitField) -> io::Result<u64> {
+        field.read(self)
+    }
+
+    /// Reads a record of all the fields in the given schema.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream,
+    /// along with any error from reading an individual field.
     ///
+    /// # Example
     /// ```
     /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0;10];
-    /// let mut r = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert!(r.read_signed::<i8>(9).is_err());   // can't read 9 bits to i8
-    /// assert!(r.read_signed::<i16>(17).is_err()); // can't read 17 bits to i16
-    /// assert!(r.read_signed::<i32>(33).is_err()); // can't read 33 bits to i32
-    /// assert!(r.read_signed::<i64>(65).is_err()); // can't read 65 bits to i64
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// use bitstream_io::schema::{FieldKind, FieldValue, Schema};
+    /// let mut schema = Schema::new();
+    /// schema.push("count", FieldKind::Unary1);
+    /// schema.push("value", FieldKind::Unsigned(5));
+    /// let data = [0b10110_100];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// let record = reader.read_record(&schema).unwrap();
+    /// assert_eq!(record.values(), [FieldValue::Unsigned(2), FieldValue::Unsigned(0b10110)]);
     /// ```
     #[inline]
-    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
-    where
-        S: SignedNumeric,
-    {
-        E::read_signed(self, bits)
+    fn read_record(&mut self, schema: &Schema) -> io::Result<Record> {
+        schema.read(self)
     }
 
+    /// Reads a symbol from the stream using an adaptive Huffman tree
+    /// and updates the tree with that symbol.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// The tree is left unchanged if the symbol cannot be read.
+    ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// use bitstream_io::huffman::AdaptiveHuffmanTree;
+    /// let data = [0x61, 0b1_0_011000, 0b10_000000];
+    /// let mut tree = AdaptiveHuffmanTree::new(8);
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert!(reader.skip(3).is_ok());
-    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10111);
+    /// assert_eq!(reader.read_adaptive_huffman(&mut tree).unwrap(), 0x61);
+    /// assert_eq!(reader.read_adaptive_huffman(&mut tree).unwrap(), 0x61);
+    /// assert_eq!(reader.read_adaptive_huffman(&mut tree).unwrap(), 0x62);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader, BitRead};
-    /// let data = [0b10110111];
+    /// use bitstream_io::huffman::AdaptiveHuffmanTree;
+    /// let data = [0b0111_1010];
+    /// let mut tree = AdaptiveHuffmanTree::new(4);
     /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
-    /// assert!(reader.skip(3).is_ok());
-    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// for _ in 0..4 {
+    ///     assert_eq!(reader.read_adaptive_huffman(&mut tree).unwrap(), 0xA);
+    /// }
+    /// assert!(reader.read_adaptive_huffman(&mut tree).is_err());
+    /// assert_eq!(tree.frequency(0xA), 4);
     /// ```
-    fn skip(&mut self, mut bits: u32) -> io::Result<()> {
-        use std::cmp::min;
-
-        let to_drop = min(self.bitqueue.len(), bits);
-        if to_drop != 0 {
-            self.bitqueue.drop(to_drop);
-            bits -= to_drop;
-        }
-
-        skip_aligned(&mut self.reader, bits / 8)?;
-        skip_unaligned(&mut self.reader, bits % 8, &mut self.bitqueue)
+    #[inline]
+    fn read_adaptive_huffman(&mut self, tree: &mut AdaptiveHuffmanTree) -> io::Result<u32> {
+        tree.read(self)
     }
 
-    /// # Example
-    /// ```
-    /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = b"foobar";
-    /// let mut reader = BitReader::endian(Cursor::new(data), BigEndian);
-    /// assert!(reader.skip(24).is_ok());
-    /// let mut buf = [0;3];
-    /// assert!(reader.read_bytes(&mut buf).is_ok());
-    /// assert_eq!(&buf, b"bar");
-    /// ```
+    /// Completely fills the given buffer with whole bytes.
+    /// If the stream is already byte-aligned, it will map
+    /// to a faster `read_exact` call.  Otherwise it will read
+    /// bytes individually in 8-bit increments.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
     fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
-        if self.byte_aligned() {
-            self.reader.read_exact(buf)
-        } else {
-            for b in buf.iter_mut() {
-                *b = self.read(8)?;
-            }
-            Ok(())
+        for b in buf.iter_mut() {
+            *b = self.read(8)?;
         }
+        Ok(())
     }
 
+    /// Reads a fixed number of whole bytes, at any bit position,
+    /// and returns them as an array.
+    /// This suits fields too wide for any integer type,
+    /// such as 160-bit hashes or 256-bit identifiers.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b01110111, 0b11111110];
+    /// let data = [0b1000_0000, 0b1000_0001, 0b1000_0010, 0b0000_0000];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.read_unary0().unwrap(), 0);
-    /// assert_eq!(reader.read_unary0().unwrap(), 3);
-    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// assert_eq!(reader.read::<u8>(1).unwrap(), 1);
+    /// assert_eq!(reader.read_to_bytes::<3>().unwrap(), [0x01, 0x03, 0x04]);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader, BitRead};
-    /// let data = [0b11101110, 0b01111111];
+    /// let data = [0b0000_0011, 0b0000_0010, 0b0000_0100, 0b0000_0000];
     /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
-    /// assert_eq!(reader.read_unary0().unwrap(), 0);
-    /// assert_eq!(reader.read_unary0().unwrap(), 3);
-    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// assert_eq!(reader.read::<u8>(1).unwrap(), 1);
+    /// assert_eq!(reader.read_to_bytes::<3>().unwrap(), [0x01, 0x01, 0x02]);
     /// ```
-    fn read_unary0(&mut self) -> io::Result<u32> {
-        if self.bitqueue.is_empty() {
-            read_aligned_unary(&mut self.reader, 0b1111_1111, &mut self.bitqueue)
-                .map(|u| u + self.bitqueue.pop_1())
-        } else if self.bitqueue.all_1() {
-            let base = self.bitqueue.len();
-            self.bitqueue.clear();
-            read_aligned_unary(&mut self.reader, 0b1111_1111, &mut self.bitqueue)
-                .map(|u| base + u + self.bitqueue.pop_1())
-        } else {
-            Ok(self.bitqueue.pop_1())
-        }
+    fn read_to_bytes<const SIZE: usize>(&mut self) -> io::Result<[u8; SIZE]> {
+        let mut buf = [0; SIZE];
+        self.read_bytes(&mut buf)?;
+        Ok(buf)
     }
 
+    /// Reads the given number of bits, at any bit position,
+    /// into a vector of bytes.
+    /// Whole bytes are read as by `read_bytes`.
+    /// If `bits` is not a multiple of 8,
+    /// the final byte holds the value of the remaining bits
+    /// as read by `read`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
     /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0b10001000, 0b00000001];
+    /// let data = [0b1000_0000, 0b1000_0001, 0b1111_0000];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.read_unary1().unwrap(), 0);
-    /// assert_eq!(reader.read_unary1().unwrap(), 3);
-    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// assert_eq!(reader.read::<u8>(1).unwrap(), 1);
+    /// assert_eq!(reader.read_bits_to_vec(19).unwrap(), vec![0x01, 0x03, 0b111]);
     /// ```
     ///
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{LittleEndian, BitReader, BitRead};
-    /// let data = [0b00010001, 0b10000000];
+    /// let data = [0b0000_0011, 0b0000_0010, 0b0000_1110];
     /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
-    /// assert_eq!(reader.read_unary1().unwrap(), 0);
-    /// assert_eq!(reader.read_unary1().unwrap(), 3);
-    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// assert_eq!(reader.read::<u8>(1).unwrap(), 1);
+    /// assert_eq!(reader.read_bits_to_vec(19).unwrap(), vec![0x01, 0x01, 0b111]);
     /// ```
-    fn read_unary1(&mut self) -> io::Result<u32> {
-        if self.bitqueue.is_empty() {
-            read_aligned_unary(&mut self.reader, 0b0000_0000, &mut self.bitqueue)
-                .map(|u| u + self.bitqueue.pop_0())
-        } else if self.bitqueue.all_0() {
-            let base = self.bitqueue.len();
-            self.bitqueue.clear();
-            read_aligned_unary(&mut self.reader, 0b0000_0000, &mut self.bitqueue)
-                .map(|u| base + u + self.bitqueue.pop_0())
-        } else {
-            Ok(self.bitqueue.pop_0())
+    fn read_bits_to_vec(&mut self, bits: u64) -> io::Result<Vec<u8>> {
+        let whole = usize::try_from(bits / 8).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "excessive bits for vector")
+        })?;
+        // grow the vector as bytes arrive
+        // so that a corrupt length doesn't allocate everything up front
+        let mut vec = Vec::new();
+        while vec.len() < whole {
+            let start = vec.len();
+            vec.resize(start + (whole - start).min(1 << 16), 0);
+            self.read_bytes(&mut vec[start..])?;
+        }
+        let partial = (bits % 8) as u32;
+        if partial > 0 {
+            vec.push(self.read(partial)?);
         }
+        Ok(vec)
     }
 
+    /// Reads the given number of whole bytes into a vector,
+    /// provided the number doesn't exceed the given maximum.
+    ///
+    /// This is for lengths read from untrusted data,
+    /// which are checked before anything is allocated for them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LengthLimitError` as an error of kind `InvalidData`
+    /// if `len` exceeds `max`, in which case nothing is read.
+    /// Passes along any I/O error from the underlying stream.
+    ///
     /// # Example
     /// ```
     /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0];
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, LengthLimitError};
+    /// let data = [0x03, b'a', b'b', b'c', 0xFF];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.byte_aligned(), true);
-    /// assert!(reader.skip(1).is_ok());
-    /// assert_eq!(reader.byte_aligned(), false);
-    /// assert!(reader.skip(7).is_ok());
-    /// assert_eq!(reader.byte_aligned(), true);
+    /// let len = reader.read::<u64>(8).unwrap();
+    /// assert_eq!(reader.read_bytes_checked(len, 16).unwrap(), b"abc");
+    /// let len = reader.read::<u64>(8).unwrap();
+    /// let err = reader.read_bytes_checked(len, 16).unwrap_err();
+    /// assert_eq!(LengthLimitError::from_io(&err).unwrap().requested(), 255);
     /// ```
-    #[inline]
-    fn byte_aligned(&self) -> bool {
-        self.bitqueue.is_empty()
+    fn read_bytes_checked(&mut self, len: u64, max: u64) -> io::Result<Vec<u8>> {
+        let len = check_length(len, max)?;
+        // grow the vector as bytes arrive
+        // so that a generous maximum doesn't allocate everything up front
+        let mut vec = Vec::new();
+        while vec.len() < len {
+            let start = vec.len();
+            vec.resize(start + (len - start).min(1 << 16), 0);
+            self.read_bytes(&mut vec[start..])?;
+        }
+        Ok(vec)
     }
 
-    /// # Example
+    /// Reads the given number of values with the given closure
+    /// into a vector, provided the number doesn't exceed the given maximum.
+    ///
+    /// This is for counts read from untrusted data,
+    /// which are checked before anything is allocated for them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `LengthLimitError` as an error of kind `InvalidData`
+    /// if `len` exceeds `max`, in which case nothing is read.
+    /// Passes along any error from the closure.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, LengthLimitError};
+    /// let data = [0x31, 0x23, 0xF0];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let count = reader.read::<u64>(4).unwrap();
+    /// let values = reader.read_vec(count, 4, |r| r.read::<u8>(4)).unwrap();
+    /// assert_eq!(values, [1, 2, 3]);
+    /// let count = reader.read::<u64>(4).unwrap();
+    /// let err = reader.read_vec(count, 4, |r| r.read::<u8>(4)).unwrap_err();
+    /// assert_eq!(LengthLimitError::from_io(&err).unwrap().max(), 4);
+    /// ```
+    fn read_vec<T, F>(&mut self, len: u64, max: u64, mut read: F) -> io::Result<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> io::Result<T>,
+    {
+        let len = check_length(len, max)?;
+        let mut vec = Vec::with_capacity(len);
+        for _ in 0..len {
+            vec.push(read(self)?);
+        }
+        Ok(vec)
+    }
+
+    /// Counts the number of 1 bits in the stream until the next
+    /// 0 bit and returns the amount read.
+    /// Because this field is variably-sized and may be large,
+    /// its output is always a `u32` type.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn read_unary0(&mut self) -> io::Result<u32> {
+        let mut unary = 0;
+        while self.read_bit()? {
+            unary += 1;
+        }
+        Ok(unary)
+    }
+
+    /// Counts the number of 0 bits in the stream until the next
+    /// 1 bit and returns the amount read.
+    /// Because this field is variably-sized and may be large,
+    /// its output is always a `u32` type.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn read_unary1(&mut self) -> io::Result<u32> {
+        let mut unary = 0;
+        while !(self.read_bit()?) {
+            unary += 1;
+        }
+        Ok(unary)
+    }
+
+    /// Counts the number of 1 bits in the stream until the next
+    /// 0 bit, like `read_unary0`, but reads at most `max` bits.
+    /// If `max` 1 bits are read, no 0 bit is expected to follow
+    /// and `max` is returned, which formats commonly use
+    /// as an escape code.
+    /// This bounds how much of an untrusted stream
+    /// a single unary field may consume.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
     /// ```
     /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0x00, 0xFF];
+    /// let data = [0b01101111, 0b11111111, 0b11111111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_unary0_capped(4).unwrap(), 0);
+    /// assert_eq!(reader.read_unary0_capped(4).unwrap(), 2);
+    /// assert_eq!(reader.read_unary0_capped(4).unwrap(), 4);
+    /// assert_eq!(reader.read_unary0_capped(12).unwrap(), 12);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b11110110, 0b11111111, 0b11111111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_unary0_capped(4).unwrap(), 0);
+    /// assert_eq!(reader.read_unary0_capped(4).unwrap(), 2);
+    /// assert_eq!(reader.read_unary0_capped(4).unwrap(), 4);
+    /// assert_eq!(reader.read_unary0_capped(12).unwrap(), 12);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+    /// ```
+    fn read_unary0_capped(&mut self, max: u32) -> io::Result<u32> {
+        let mut unary = 0;
+        while unary < max && self.read_bit()? {
+            unary += 1;
+        }
+        Ok(unary)
+    }
+
+    /// Counts the number of 0 bits in the stream until the next
+    /// 1 bit, like `read_unary1`, but reads at most `max` bits.
+    /// If `max` 0 bits are read, no 1 bit is expected to follow
+    /// and `max` is returned, which formats commonly use
+    /// as an escape code.
+    /// This bounds how much of an untrusted stream
+    /// a single unary field may consume.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10010000, 0b00000000, 0b00000000];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_unary1_capped(4).unwrap(), 0);
+    /// assert_eq!(reader.read_unary1_capped(4).unwrap(), 2);
+    /// assert_eq!(reader.read_unary1_capped(4).unwrap(), 4);
+    /// assert_eq!(reader.read_unary1_capped(12).unwrap(), 12);
     /// assert_eq!(reader.read::<u8>(4).unwrap(), 0);
-    /// reader.byte_align();
-    /// assert_eq!(reader.read::<u8>(8).unwrap(), 0xFF);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b00001001, 0b00000000, 0b00000000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_unary1_capped(4).unwrap(), 0);
+    /// assert_eq!(reader.read_unary1_capped(4).unwrap(), 2);
+    /// assert_eq!(reader.read_unary1_capped(4).unwrap(), 4);
+    /// assert_eq!(reader.read_unary1_capped(12).unwrap(), 12);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0);
+    /// ```
+    fn read_unary1_capped(&mut self, max: u32) -> io::Result<u32> {
+        let mut unary = 0;
+        while unary < max && !(self.read_bit()?) {
+            unary += 1;
+        }
+        Ok(unary)
+    }
+
+    /// Reads an unsigned value from the stream encoded as
+    /// an order-`k` exponential-Golomb code.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the order is larger than 64.
+    /// Returns an error of kind `InvalidData` if the code's
+    /// value is too large to fit a `u64`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10100110, 0b01001000, 0b10010000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 0);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 1);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 2);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 3);
+    /// assert_eq!(reader.read_exp_golomb(2).unwrap(), 0);
+    /// assert_eq!(reader.read_exp_golomb(2).unwrap(), 5);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b01100101, 0b00010010, 0b00000011];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 0);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 1);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 2);
+    /// assert_eq!(reader.read_exp_golomb(0).unwrap(), 3);
+    /// assert_eq!(reader.read_exp_golomb(2).unwrap(), 0);
+    /// assert_eq!(reader.read_exp_golomb(2).unwrap(), 5);
+    /// ```
+    fn read_exp_golomb(&mut self, k: u32) -> io::Result<u64> {
+        if k > 64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive order for exp-Golomb code",
+            ));
+        }
+        let bits = self.read_unary1()? + k;
+        if bits > 64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "excessive value for exp-Golomb code",
+            ));
+        }
+        let value = (1u128 << bits) | u128::from(self.read::<u64>(bits)?);
+        u64::try_from(value - (1 << k)).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "excessive value for exp-Golomb code",
+            )
+        })
+    }
+
+    /// Returns true if the stream is aligned at a whole byte.
+    fn byte_aligned(&self) -> bool;
+
+    /// Throws away all unread bit values until the next whole byte.
+    /// Does nothing if the stream is already aligned.
+    fn byte_align(&mut self);
+
+    /// Reads a floating point value from the stream
+    /// by way of its bit pattern, in the stream's endianness.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b1100_0100, 0b0000_0100, 0, 0, 0];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1100);
+    /// assert_eq!(reader.read_float::<f32>().unwrap(), 3.0);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// use bitstream_io::float::Half;
+    /// let data = [0x00, 0x3C];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_float::<Half>().unwrap().to_f64(), 1.0);
     /// ```
     #[inline]
-    fn byte_align(&mut self) {
-        self.bitqueue.clear()
+    fn read_float<F>(&mut self) -> io::Result<F>
+    where
+        F: FloatNumeric,
+    {
+        self.read(F::bits_size()).map(F::from_bits)
     }
-}
 
-impl<R, E> BitReader<R, E>
-where
-    E: Endianness,
-    R: io::Read + io::Seek,
-{
-    /// # Example
+    /// Reads an unsigned fixed point value from the stream
+    /// with the given number of integer and fractional bits.
+    /// The result is exact for values of up to 53 bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if more than 64 bits are requested.
+    ///
+    /// # Examples
     /// ```
-    /// use std::io::{Read, Cursor, SeekFrom};
+    /// use std::io::{Read, Cursor};
     /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0x00, 0xFF];
+    /// let data = [0x00, 0x01, 0x80, 0x00, 0b0100_0000];
     /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.position_in_bits().unwrap(), 0);
+    /// assert_eq!(reader.read_fixed_point(16, 16).unwrap(), 1.5);
+    /// assert_eq!(reader.read_fixed_point(2, 6).unwrap(), 1.0);
+    /// ```
     ///
-    /// let pos = reader.seek_bits(SeekFrom::Start(5)).unwrap();
-    /// assert!(pos == 5 && 5 == reader.position_in_bits().unwrap());
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0x24];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_fixed_point(4, 4).unwrap(), 2.25);
+    /// ```
+    fn read_fixed_point(&mut self, int_bits: u32, frac_bits: u32) -> io::Result<f64> {
+        let bits = int_bits.checked_add(frac_bits).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "excessive bits for type read")
+        })?;
+        let value = self.read::<u64>(bits)?;
+        Ok(value as f64 / 2.0f64.powi(frac_bits as i32))
+    }
+
+    /// Reads a twos-complement signed fixed point value from the stream
+    /// with the given number of integer bits, including the sign bit,
+    /// and fractional bits.
+    /// The result is exact for values of up to 53 bits.
     ///
-    /// let pos = reader.seek_bits(SeekFrom::Current(-2)).unwrap();
-    /// assert!(pos == 3 && 3 == reader.position_in_bits().unwrap());    ///
+    /// # Errors
     ///
-    /// let pos = reader.seek_bits(SeekFrom::End(5)).unwrap();
-    /// assert!(pos == 11 && 11 == reader.position_in_bits().unwrap());
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if more than 64 bits are requested.
+    ///
+    /// # Examples
     /// ```
-    pub fn seek_bits(&mut self, from: io::SeekFrom) -> io::Result<u64> {
-        match from {
-            io::SeekFrom::Start(from_start_pos) => {
-                let (bytes, bits) = (from_start_pos / 8, (from_start_pos % 8) as u32);
-                self.byte_align();
-                self.reader.seek(io::SeekFrom::Start(bytes))?;
-                self.skip(bits)?;
-                Ok(from_start_pos)
-            }
-            io::SeekFrom::End(from_end_pos) => {
-                let reader_end = self.reader.seek(io::SeekFrom::End(0))?;
-                let new_pos = (reader_end * 8) as i64 - from_end_pos;
-                assert!(new_pos >= 0, "The final position should be greater than 0");
-                self.seek_bits(io::SeekFrom::Start(new_pos as u64))
-            }
-            io::SeekFrom::Current(offset) => {
-                let new_pos = self.position_in_bits()? as i64 + offset;
-                assert!(new_pos >= 0, "The final position should be greater than 0");
-                self.seek_bits(io::SeekFrom::Start(new_pos as u64))
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0xC0, 0x00, 0x00, 0x00, 0xFF];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_signed_fixed_point(2, 30).unwrap(), -1.0);
+    /// assert_eq!(reader.read_signed_fixed_point(2, 6).unwrap(), -0.015625);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0xDC];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_signed_fixed_point(4, 4).unwrap(), -2.25);
+    /// ```
+    fn read_signed_fixed_point(&mut self, int_bits: u32, frac_bits: u32) -> io::Result<f64> {
+        let bits = int_bits.checked_add(frac_bits).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "excessive bits for type read")
+        })?;
+        let value = self.read_signed::<i64>(bits)?;
+        Ok(value as f64 / 2.0f64.powi(frac_bits as i32))
+    }
+
+    /// Reads a null-terminated string of at most `max_len` bytes,
+    /// not including the terminator, which is consumed but not returned.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if no terminator is found within `max_len` bytes,
+    /// or if the string is not valid UTF-8 under a strict policy.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, Utf8Policy};
+    /// let data = b"foo\0bar\0";
+    /// let mut reader = BitReader::endian(Cursor::new(data), BigEndian);
+    /// assert_eq!(reader.read_cstr(16, Utf8Policy::Strict).unwrap(), "foo");
+    /// assert!(reader.read_cstr(2, Utf8Policy::Strict).is_err());
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead, Utf8Policy};
+    /// let data = b"f\xFFo\0";
+    /// let mut reader = BitReader::endian(Cursor::new(data), LittleEndian);
+    /// assert_eq!(reader.read_cstr(16, Utf8Policy::Lossy).unwrap(), "f\u{FFFD}o");
+    /// ```
+    fn read_cstr(&mut self, max_len: usize, policy: Utf8Policy) -> io::Result<String> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.read::<u8>(8)? {
+                0 => break policy.decode(bytes),
+                _ if bytes.len() == max_len => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "string exceeds maximum length",
+                    ))
+                }
+                byte => bytes.push(byte),
             }
         }
     }
 
-    /// # Example
-    /// ```
-    /// use std::fs::read;
-    /// use std::io::{Read, Cursor, SeekFrom};
-    /// use bitstream_io::{BigEndian, BitReader, BitRead};
-    /// let data = [0x00, 0xFF];
-    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
-    /// assert_eq!(reader.position_in_bits().unwrap(), 0);
-    ///
-    /// let _: i32 = reader.read_signed(5).unwrap();
-    /// assert_eq!(reader.position_in_bits().unwrap(), 5);
-    ///
-    /// reader.read_bit().unwrap();
-    /// assert_eq!(reader.position_in_bits().unwrap(), 6);
-    /// ```
+    /// Reads a string prefixed by its length in bytes,
+    /// which is read as an unsigned value of `len_bits` bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the length is longer than `max_len`,
+    /// in which case the string itself is not read,
+    /// or if the string is not valid UTF-8 under a strict policy.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, Utf8Policy};
+    /// let data = [0b0011_0110, 0b0110_0110, 0b1111_0110, 0b1111_0000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_prefixed_str(4, 16, Utf8Policy::Strict).unwrap(), "foo");
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead, Utf8Policy};
+    /// let data = [0x03, 0x00, b'f', b'o', b'o'];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert!(reader.read_prefixed_str(16, 2, Utf8Policy::Strict).is_err());
+    /// ```
+    fn read_prefixed_str(
+        &mut self,
+        len_bits: u32,
+        max_len: usize,
+        policy: Utf8Policy,
+    ) -> io::Result<String> {
+        let len = self.read::<u64>(len_bits)?;
+        if len > max_len as u64 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "string exceeds maximum length",
+            ))
+        } else {
+            let mut bytes = vec![0; len as usize];
+            self.read_bytes(&mut bytes)?;
+            policy.decode(bytes)
+        }
+    }
+
+    /// Reads the given number of bits from the stream
+    /// into a new in-memory buffer which implements `BitRead` itself.
+    /// The bits are copied exactly as they occur in the stream,
+    /// regardless of whether the stream is byte-aligned,
+    /// so the buffer should use the same endianness as the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// use bitstream_io::buffer::BitBuffer;
+    /// let data = [0b1011_0111, 0b0101_0101];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b111);
+    /// let mut payload: BitBuffer<LittleEndian> = reader.read_to_bitbuffer(10).unwrap();
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b010);
+    /// assert_eq!(payload.read::<u16>(10).unwrap(), 0b1_0101_10110);
+    /// ```
+    fn read_to_bitbuffer<E>(&mut self, bits: u64) -> io::Result<BitBuffer<E>>
+    where
+        E: Endianness,
+    {
+        let mut buffer = BitBuffer::new();
+        let mut chunk = [0; 4096];
+        let mut bytes = bits / 8;
+        while bytes > 0 {
+            let to_read = std::cmp::min(bytes, chunk.len() as u64) as usize;
+            self.read_bytes(&mut chunk[0..to_read])?;
+            buffer.push_bytes(&chunk[0..to_read]);
+            bytes -= to_read as u64;
+        }
+        let remainder = (bits % 8) as u32;
+        buffer.push_bits(remainder, self.read(remainder)?);
+        Ok(buffer)
+    }
+
+    /// Copies the given number of bits from the stream to the given writer
+    /// exactly as they occur in the stream,
+    /// so the writer should use the same endianness as the stream.
+    /// Whole bytes are copied in bulk when both streams are byte-aligned
+    /// and 64-bit words are copied at a time otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from either stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+    /// let data = [0b1011_0111, 0b0101_0101];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+    /// writer.write(2, 0b11).unwrap();
+    /// reader.copy_to(&mut writer, 10).unwrap();
+    /// writer.write(4, 0).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b1110_1110, 0b1010_0000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead, BitWriter, BitWrite};
+    /// let data = [0b1011_0111, 0b0101_0101, 0b1111_0000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// reader.copy_to(&mut writer, 16).unwrap();
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 0b1111_0000);
+    /// assert_eq!(writer.into_writer(), [0b1011_0111, 0b0101_0101]);
+    /// ```
+    fn copy_to<W>(&mut self, writer: &mut W, mut bits: u64) -> io::Result<()>
+    where
+        W: BitWrite + ?Sized,
+    {
+        if self.byte_aligned() && writer.byte_aligned() {
+            let mut chunk = [0; 4096];
+            while bits >= 8 {
+                let to_copy = std::cmp::min(bits / 8, chunk.len() as u64) as usize;
+                self.read_bytes(&mut chunk[0..to_copy])?;
+                writer.write_bytes(&chunk[0..to_copy])?;
+                bits -= to_copy as u64 * 8;
+            }
+        }
+        while bits >= 64 {
+            writer.write(64, self.read::<u64>(64)?)?;
+            bits -= 64;
+        }
+        if bits > 0 {
+            writer.write(bits as u32, self.read::<u64>(bits as u32)?)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a reader which reads at most the given number of bits
+    /// from this reader before returning errors.
+    ///
+    /// Reads past the limit return a `BitLimitError`
+    /// as an error of kind `Other`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, BitLimitError};
+    /// let data = [0b10110111, 0b01010101];
+    /// let reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let mut limited = reader.take(10);
+    /// assert_eq!(limited.read::<u8>(3).unwrap(), 0b101);
+    /// let err = limited.read::<u8>(8).unwrap_err();
+    /// assert_eq!(BitLimitError::from_io(&err).unwrap().remaining(), 7);
+    /// assert_eq!(limited.read::<u8>(7).unwrap(), 0b1011101);
+    /// assert!(limited.read_bit().is_err());
+    /// let mut reader = limited.into_reader();
+    /// assert_eq!(reader.read::<u8>(6).unwrap(), 0b010101);
+    /// ```
+    #[inline]
+    fn take(self, bits: u64) -> LimitedBitReader<Self>
+    where
+        Self: Sized,
+    {
+        LimitedBitReader::new(self, bits)
+    }
+
+    /// Returns an iterator which reads the given number of bits
+    /// from this reader, one at a time.
+    ///
+    /// The iterator yields an error and then stops
+    /// if the underlying stream fails before all the bits are read.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110001, 0b11110000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let bitmap = reader.read_bits_iter(8).collect::<Result<Vec<bool>, _>>().unwrap();
+    /// assert_eq!(bitmap, [true, false, true, true, false, false, false, true]);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b10001101, 0b00001111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// let bitmap = reader.read_bits_iter(8).collect::<Result<Vec<bool>, _>>().unwrap();
+    /// assert_eq!(bitmap, [true, false, true, true, false, false, false, true]);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110001];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let mut bits = reader.read_bits_iter(10);
+    /// assert_eq!(bits.by_ref().take(8).filter(|b| *b.as_ref().unwrap()).count(), 4);
+    /// assert!(bits.next().unwrap().is_err());
+    /// assert!(bits.next().is_none());
+    /// ```
+    #[inline]
+    fn read_bits_iter(&mut self, bits: u64) -> BitIter<'_, Self> {
+        BitIter {
+            reader: self,
+            remaining: bits,
+        }
+    }
+
+    /// Reads the given number of bits from the stream
+    /// into a `BitVec`, in the order they occur in the stream.
+    ///
+    /// Requires the `bitvec` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// use bitvec::prelude::*;
+    /// let data = [0b10110001, 0b11110000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_bitvec(12).unwrap(), bits![u8, Msb0; 1, 0, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// use bitvec::prelude::*;
+    /// let data = [0b10001101, 0b00001111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_bitvec(12).unwrap(), bits![u8, Msb0; 1, 0, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1]);
+    /// ```
+    #[cfg(feature = "bitvec")]
+    fn read_bitvec(&mut self, bits: u64) -> io::Result<BitVec<u8, Msb0>> {
+        // the capacity of a bogus length is capped until data arrives
+        let mut vec = BitVec::with_capacity(bits.min(1 << 16) as usize);
+        for bit in self.read_bits_iter(bits) {
+            vec.push(bit?);
+        }
+        Ok(vec)
+    }
+}
+
+/// An iterator over a fixed number of bits read from a `BitRead`.
+///
+/// This is created by the `read_bits_iter` method of `BitRead`.
+#[derive(Debug)]
+pub struct BitIter<'r, R: ?Sized> {
+    reader: &'r mut R,
+    remaining: u64,
+}
+
+impl<'r, R: BitRead + ?Sized> BitIter<'r, R> {
+    /// Returns the number of bits still to be read
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl<'r, R: BitRead + ?Sized> Iterator for BitIter<'r, R> {
+    type Item = io::Result<bool>;
+
+    fn next(&mut self) -> Option<io::Result<bool>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let result = self.reader.read_bit();
+        if result.is_ok() {
+            self.remaining -= 1;
+        } else {
+            self.remaining = 0;
+        }
+        Some(result)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // an I/O error may end the iteration early
+        (0, usize::try_from(self.remaining).ok())
+    }
+}
+
+/// An object-safe subset of `BitRead` which works
+/// with values of at most 64 bits, so that readers may be used
+/// as `dyn BitReadCore` trait objects,
+/// such as to hand a codec plugin whichever reader is in use.
+///
+/// This is implemented for everything which implements `BitRead`.
+/// In turn, `dyn BitReadCore` implements `BitRead` itself
+/// so that all of its methods remain available,
+/// except that values wider than 64 bits may not be read.
+///
+/// # Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BigEndian, BitReader, BitRead, BitReadCore, BitSliceReader};
+/// fn decode(reader: &mut dyn BitReadCore) -> (u8, i8, u32) {
+///     let kind = reader.read(4).unwrap();
+///     let delta = reader.read_signed(4).unwrap();
+///     let length = reader.read_unary0().unwrap();
+///     (kind, delta, length)
+/// }
+/// let data = [0b1011_1111, 0b1100_0000];
+/// let mut readers: Vec<Box<dyn BitReadCore>> = vec![
+///     Box::new(BitReader::endian(Cursor::new(data), BigEndian)),
+///     Box::new(BitSliceReader::endian(&data, BigEndian)),
+/// ];
+/// for reader in readers.iter_mut() {
+///     assert_eq!(decode(reader.as_mut()), (0b1011, -1, 2));
+/// }
+/// ```
+pub trait BitReadCore {
+    /// Reads an unsigned value of up to 64 bits from the stream,
+    /// as by `BitRead::read`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64.
+    fn read_u64(&mut self, bits: u32) -> io::Result<u64>;
+
+    /// Reads a twos-complement signed value of up to 64 bits
+    /// from the stream, as by `BitRead::read_signed`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64.
+    fn read_i64(&mut self, bits: u32) -> io::Result<i64>;
+
+    /// Skips the given number of bits in the stream,
+    /// as by `BitRead::skip`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn core_skip(&mut self, bits: u64) -> io::Result<()>;
+
+    /// Completely fills the given buffer with whole bytes,
+    /// as by `BitRead::read_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn core_read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()>;
+
+    /// Returns true if the stream is aligned at a whole byte.
+    fn core_byte_aligned(&self) -> bool;
+
+    /// Throws away all unread bit values until the next whole byte.
+    fn core_byte_align(&mut self);
+}
+
+impl<R: BitRead> BitReadCore for R {
+    #[inline]
+    fn read_u64(&mut self, bits: u32) -> io::Result<u64> {
+        self.read(bits)
+    }
+
+    #[inline]
+    fn read_i64(&mut self, bits: u32) -> io::Result<i64> {
+        self.read_signed(bits)
+    }
+
+    #[inline]
+    fn core_skip(&mut self, bits: u64) -> io::Result<()> {
+        self.skip(bits)
+    }
+
+    #[inline]
+    fn core_read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.read_bytes(buf)
+    }
+
+    #[inline]
+    fn core_byte_aligned(&self) -> bool {
+        self.byte_aligned()
+    }
+
+    #[inline]
+    fn core_byte_align(&mut self) {
+        self.byte_align()
+    }
+}
+
+impl<'a> BitRead for dyn BitReadCore + 'a {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        self.read_u64(1).map(|bit| bit == 1)
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits <= U::bits_size() && bits <= 64 {
+            self.read_u64(bits).map(numeric_from_u64)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        }
+    }
+
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        if bits <= S::bits_size() && bits <= 64 {
+            self.read_i64(bits).map(signed_from_i64)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        }
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        self.core_skip(bits)
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.core_read_bytes(buf)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.core_byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        self.core_byte_align()
+    }
+}
+
+impl<R: BitRead + ?Sized> BitRead for &mut R {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        (**self).read_bit()
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        (**self).read(bits)
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        (**self).read_signed(bits)
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        (**self).skip(bits)
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read_bytes(buf)
+    }
+
+    #[inline]
+    fn read_unary0(&mut self) -> io::Result<u32> {
+        (**self).read_unary0()
+    }
+
+    #[inline]
+    fn read_unary1(&mut self) -> io::Result<u32> {
+        (**self).read_unary1()
+    }
+
+    #[inline]
+    fn read_unary0_capped(&mut self, max: u32) -> io::Result<u32> {
+        (**self).read_unary0_capped(max)
+    }
+
+    #[inline]
+    fn read_unary1_capped(&mut self, max: u32) -> io::Result<u32> {
+        (**self).read_unary1_capped(max)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        (**self).byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        (**self).byte_align()
+    }
+}
+
+/// A trait for anything that can read Huffman codes
+/// of a given endianness from an input stream
+pub trait HuffmanRead<E: Endianness> {
+    /// Given a compiled Huffman tree, reads bits from the stream
+    /// until the next symbol is encountered.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn read_huffman<T>(&mut self, tree: &[ReadHuffmanTree<E, T>]) -> io::Result<T>
+    where
+        T: Clone;
+
+    /// Given a compiled Huffman tree, reads enough symbols
+    /// from the stream to fill the given buffer.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// The contents of the buffer are unspecified on error.
+    fn read_huffman_into<T>(
+        &mut self,
+        tree: &[ReadHuffmanTree<E, T>],
+        symbols: &mut [T],
+    ) -> io::Result<()>
+    where
+        T: Clone,
+    {
+        for symbol in symbols.iter_mut() {
+            *symbol = self.read_huffman(tree)?;
+        }
+        Ok(())
+    }
+
+    /// Given a compiled compact Huffman tree, reads bits from the stream
+    /// until the next symbol is encountered.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, HuffmanRead};
+    /// use bitstream_io::huffman::compile_compact_read_tree;
+    /// let tree = compile_compact_read_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])], 8).unwrap();
+    /// let data = [0b10110111];
+    /// let mut cursor = Cursor::new(&data);
+    /// let mut reader = BitReader::endian(&mut cursor, BigEndian);
+    /// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'b');
+    /// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'c');
+    /// assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 'd');
+    /// ```
+    #[inline]
+    fn read_compact_huffman<T>(&mut self, tree: &CompactReadHuffmanTree<E, T>) -> io::Result<T>
+    where
+        Self: BitRead,
+        T: Clone,
+    {
+        tree.read(self, u32::MAX)
+    }
+
+    /// Given a compiled compact Huffman tree, reads bits from the stream
+    /// until the next symbol is encountered,
+    /// or until the code is known to be longer than `max_bits`.
+    ///
+    /// No more than `max_bits` bits are consumed,
+    /// which allows a reader to resynchronize with a corrupted stream
+    /// without running far past the damage.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidData` wrapping a
+    /// `HuffmanCodeError::CodeTooLong` if the code is too long,
+    /// or a `HuffmanCodeError::UnknownCode` if the code has no symbol.
+    /// Either one gives the number of bits consumed.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead, HuffmanRead};
+    /// use bitstream_io::huffman::{compile_compact_read_tree, HuffmanCodeError};
+    /// let tree = compile_compact_read_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])], 1).unwrap();
+    /// let data = [0b10_11_1_0_11, 0b0000_0000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_huffman_or(&tree, 2).unwrap(), 'b');
+    /// let err = reader.read_huffman_or(&tree, 2).unwrap_err();
+    /// assert_eq!(HuffmanCodeError::from_io(&err),
+    ///            Some(&HuffmanCodeError::CodeTooLong { consumed: 2 }));
+    /// assert!(reader.read_bit().unwrap());
+    /// assert_eq!(reader.read_huffman_or(&tree, 2).unwrap(), 'a');
+    /// assert_eq!(reader.read_huffman_or(&tree, 3).unwrap(), 'c');
+    /// ```
+    #[inline]
+    fn read_huffman_or<T>(
+        &mut self,
+        tree: &CompactReadHuffmanTree<E, T>,
+        max_bits: u32,
+    ) -> io::Result<T>
+    where
+        Self: BitRead,
+        T: Clone,
+    {
+        tree.read(self, max_bits)
+    }
+}
+
+/// For reading non-aligned bits from a stream of bytes in a given endianness.
+///
+/// This will read exactly as many whole bytes needed to return
+/// the requested number of bits.  It may cache up to a single partial byte
+/// but no more, apart from any whole bytes examined by `peek`
+/// or `peek_huffman` which haven't been read yet.
+#[derive(Clone)]
+pub struct BitReader<R: io::Read, E: Endianness> {
+    reader: PeekedReader<R>,
+    bitqueue: BitQueue<E, u8>,
+    #[cfg(feature = "observer")]
+    observed: Observed,
+}
+
+impl<R: io::Read + fmt::Debug, E: Endianness> fmt::Debug for BitReader<R, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitReader")
+            .field("reader", &self.reader.inner)
+            .field("bitqueue", &self.bitqueue)
+            .finish()
+    }
+}
+
+/// A reader which returns any bytes already examined by a peek
+/// before reading more from the wrapped stream
+#[derive(Clone)]
+struct PeekedReader<R> {
+    inner: R,
+    // bytes taken from the stream by a peek but not yet read
+    peeked: VecDeque<u8>,
+    // bytes read while a peek is in progress
+    recorded: Option<Vec<u8>>,
+}
+
+impl<R> PeekedReader<R> {
+    #[inline]
+    fn new(inner: R) -> Self {
+        PeekedReader {
+            inner,
+            peeked: VecDeque::new(),
+            recorded: None,
+        }
+    }
+
+    /// Begins recording every byte read
+    #[inline]
+    fn start_peek(&mut self) {
+        self.recorded = Some(Vec::new());
+    }
+
+    /// Stops recording and places the recorded bytes
+    /// back in front of any remaining peeked bytes
+    fn finish_peek(&mut self) {
+        if let Some(recorded) = self.recorded.take() {
+            for byte in recorded.into_iter().rev() {
+                self.peeked.push_front(byte);
+            }
+        }
+    }
+
+    /// Places bytes which were just read back in front
+    /// of any remaining peeked bytes, to be read again
+    fn unread(&mut self, bytes: &[u8]) {
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.truncate(recorded.len() - bytes.len());
+        }
+        for byte in bytes.iter().rev() {
+            self.peeked.push_front(*byte);
+        }
+    }
+}
+
+impl<R: io::Read> io::Read for PeekedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = if self.peeked.is_empty() {
+            self.inner.read(buf)?
+        } else {
+            let len = buf.len().min(self.peeked.len());
+            for (dst, src) in buf.iter_mut().zip(self.peeked.drain(0..len)) {
+                *dst = src;
+            }
+            len
+        };
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.extend_from_slice(&buf[0..read]);
+        }
+        Ok(read)
+    }
+}
+
+impl<R: io::Read, E: Endianness> BitReader<R, E> {
+    /// Wraps a BitReader around something that implements `Read`
+    pub fn new(reader: R) -> BitReader<R, E> {
+        BitReader {
+            reader: PeekedReader::new(reader),
+            bitqueue: BitQueue::new(),
+            #[cfg(feature = "observer")]
+            observed: Observed::default(),
+        }
+    }
+
+    /// Wraps a BitReader around something that implements `Read`
+    /// with the given endianness.
+    pub fn endian(reader: R, _endian: E) -> BitReader<R, E> {
+        BitReader {
+            reader: PeekedReader::new(reader),
+            bitqueue: BitQueue::new(),
+            #[cfg(feature = "observer")]
+            observed: Observed::default(),
+        }
+    }
+
+    /// Unwraps internal reader and disposes of BitReader.
+    ///
+    /// # Warning
+    ///
+    /// Any unread partial bits are discarded,
+    /// as are any bytes examined by `peek` but not yet read.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader.inner
+    }
+
+    /// If stream is byte-aligned, provides mutable reference
+    /// to internal reader.  Otherwise returns `None`
+    ///
+    /// Also returns `None` if bytes examined by `peek`
+    /// have yet to be read, since the internal reader is past them.
+    #[inline]
+    pub fn reader(&mut self) -> Option<&mut R> {
+        if self.byte_aligned() && self.reader.peeked.is_empty() {
+            Some(&mut self.reader.inner)
+        } else {
+            None
+        }
+    }
+
+    /// Converts `BitReader` to `ByteReader` in the same endianness.
+    ///
+    /// # Warning
+    ///
+    /// Any unread partial bits are discarded,
+    /// as are any bytes examined by `peek` but not yet read.
+    #[inline]
+    pub fn into_bytereader(self) -> ByteReader<R, E> {
+        ByteReader::new(self.into_reader())
+    }
+
+    /// If stream is byte-aligned, provides temporary `ByteReader`
+    /// in the same endianness.  Otherwise returns `None`
+    ///
+    /// # Warning
+    ///
+    /// Any reader bits left over when `ByteReader` is dropped are lost.
+    #[inline]
+    pub fn bytereader(&mut self) -> Option<ByteReader<&mut R, E>> {
+        self.reader().map(ByteReader::new)
+    }
+
+    /// Attaches an observer which is told about every run of bits
+    /// read or skipped from now on, replacing any previous observer.
+    ///
+    /// Clones of this reader share the same observer.
+    /// Bits examined by `peek` and `peek_huffman`
+    /// and bytes read through the reader returned by `bytereader`
+    /// aren't reported.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use std::sync::{Arc, Mutex};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// use bitstream_io::observer::BitObserver;
+    ///
+    /// #[derive(Default)]
+    /// struct Runs(Vec<(u32, u64)>);
+    ///
+    /// impl BitObserver for Runs {
+    ///     fn on_read(&mut self, bits: u32, offset: u64) {
+    ///         self.0.push((bits, offset));
+    ///     }
+    /// }
+    ///
+    /// let data = [0b101_00111, 0xAB, 0xCD, 0xEF];
+    /// let runs = Arc::new(Mutex::new(Runs::default()));
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+    /// reader.set_observer(runs.clone());
+    /// assert_eq!(reader.peek::<u8>(5).unwrap(), 0b00111);
+    /// assert_eq!(reader.read_unary1().unwrap(), 2);
+    /// reader.skip(10).unwrap();
+    /// assert!(reader.take_observer().is_some());
+    /// assert_eq!(reader.read::<u16>(16).unwrap(), 0xCDEF);
+    /// assert_eq!(runs.lock().unwrap().0, [(3, 3), (10, 6)]);
+    /// ```
+    #[cfg(feature = "observer")]
+    #[inline]
+    pub fn set_observer(&mut self, observer: Arc<Mutex<dyn BitObserver + Send>>) {
+        self.observed.set(observer)
+    }
+
+    /// Detaches and returns the reader's observer, if any
+    #[cfg(feature = "observer")]
+    #[inline]
+    pub fn take_observer(&mut self) -> Option<Arc<Mutex<dyn BitObserver + Send>>> {
+        self.observed.take()
+    }
+
+    /// Consumes reader and returns any un-read partial byte
+    /// as a `(bits, value)` tuple.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b1010_0101, 0b0101_1010];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u16>(9).unwrap(), 0b1010_0101_0);
+    /// let (bits, value) = reader.into_unread();
+    /// assert_eq!(bits, 7);
+    /// assert_eq!(value, 0b101_1010);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b1010_0101, 0b0101_1010];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u16>(8).unwrap(), 0b1010_0101);
+    /// let (bits, value) = reader.into_unread();
+    /// assert_eq!(bits, 0);
+    /// assert_eq!(value, 0);
+    /// ```
+    #[inline]
+    pub fn into_unread(self) -> (u32, u8) {
+        (self.bitqueue.len(), self.bitqueue.value())
+    }
+
+    /// Reads bits matching the given bit up to the next bit
+    /// which differs from it, and returns the length of the run.
+    /// Unlike the unary methods, the differing bit is left unread
+    /// so that it begins the next run.
+    /// A run which continues to the end of the stream ends there.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b11100000, 0b00000000, 0b00111111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_run(true).unwrap(), 3);
+    /// assert_eq!(reader.read_run(true).unwrap(), 0);
+    /// assert_eq!(reader.read_run(false).unwrap(), 15);
+    /// assert_eq!(reader.read_run(true).unwrap(), 6);
+    /// assert!(reader.read_bit().is_err());
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b00000111, 0b00000000, 0b11111100];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_run(true).unwrap(), 3);
+    /// assert_eq!(reader.read_run(false).unwrap(), 15);
+    /// assert_eq!(reader.read_run(true).unwrap(), 6);
+    /// assert!(reader.read_bit().is_err());
+    /// ```
+    pub fn read_run(&mut self, bit: bool) -> io::Result<u64> {
+        let mut run = 0;
+        loop {
+            let queued = self.bitqueue.len();
+            let matching = if bit {
+                E::next_ones(&self.bitqueue)
+            } else {
+                E::next_zeros(&self.bitqueue)
+            }
+            .min(queued);
+            run += u64::from(matching);
+            if matching < queued {
+                if matching > 0 {
+                    self.bitqueue.drop(matching);
+                }
+                #[cfg(feature = "observer")]
+                self.observed.read(run);
+                return Ok(run);
+            }
+
+            self.bitqueue.clear();
+            let mut byte = [0];
+            match io::Read::read(&mut self.reader, &mut byte) {
+                Ok(0) => {
+                    #[cfg(feature = "observer")]
+                    self.observed.read(run);
+                    return Ok(run);
+                }
+                Ok(_) => self.bitqueue.set(byte[0], 8),
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Reads a single bit, or returns `None` if the stream
+    /// ends cleanly on a byte boundary before it.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let mut bits = Vec::new();
+    /// while let Some(bit) = reader.read_bit_opt().unwrap() {
+    ///     bits.push(bit);
+    /// }
+    /// assert_eq!(bits, [true, false, true, true, false, true, true, true]);
+    /// ```
+    pub fn read_bit_opt(&mut self) -> io::Result<Option<bool>> {
+        if self.at_clean_eof()? {
+            Ok(None)
+        } else {
+            self.read_bit().map(Some)
+        }
+    }
+
+    /// Reads an unsigned value of the given number of bits,
+    /// or returns `None` if the stream ends cleanly
+    /// on a byte boundary before it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `UnexpectedEof` if the stream
+    /// ends partway through the value,
+    /// and an error if more bits are requested than the type holds.
+    /// Passes along any other I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0x12, 0x34, 0x56];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let mut values = Vec::new();
+    /// while let Some(value) = reader.read_opt::<u8>(4).unwrap() {
+    ///     values.push(value);
+    /// }
+    /// assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor, ErrorKind};
+    /// use bitstream_io::{BigEndian, BitReader};
+    /// let data = [0x12, 0x34, 0x56];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_opt::<u16>(16).unwrap(), Some(0x1234));
+    /// assert_eq!(
+    ///     reader.read_opt::<u16>(16).unwrap_err().kind(),
+    ///     ErrorKind::UnexpectedEof,
+    /// );
+    /// ```
+    pub fn read_opt<U>(&mut self, bits: u32) -> io::Result<Option<U>>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        } else if bits > 0 && self.at_clean_eof()? {
+            Ok(None)
+        } else {
+            self.read(bits).map(Some)
+        }
+    }
+
+    /// Reads an unsigned value from the stream with
+    /// the given number of bits without consuming them.
+    /// The bits remain to be read afterward,
+    /// whether the read succeeds or not.
+    /// Any whole bytes this takes from the underlying stream
+    /// are buffered by the reader, so the stream needn't be seekable.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Also returns an error if the output type is too small
+    /// to hold the requested number of bits.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110111, 0b01010101];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+    /// assert_eq!(reader.peek::<u16>(9).unwrap(), 0b10111_0101);
+    /// assert_eq!(reader.peek::<u8>(2).unwrap(), 0b10);
+    /// assert_eq!(reader.read::<u16>(13).unwrap(), 0b10111_01010101);
+    /// assert!(reader.peek::<u8>(1).is_err());
+    /// ```
+    pub fn peek<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        self.peek_with(|r| r.read(bits))
+    }
+
+    /// Given a compiled Huffman tree, reads the next symbol
+    /// from the stream without consuming it.
+    /// The symbol's bits remain to be read afterward,
+    /// whether the read succeeds or not.
+    /// Like `peek`, this needs no `Seek` from the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, HuffmanRead};
+    /// use bitstream_io::huffman::compile_read_tree;
+    /// let tree = compile_read_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.peek_huffman(&tree).unwrap(), 'b');
+    /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'b');
+    /// assert_eq!(reader.peek_huffman(&tree).unwrap(), 'c');
+    /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'c');
+    /// ```
+    pub fn peek_huffman<T>(&mut self, tree: &[ReadHuffmanTree<E, T>]) -> io::Result<T>
+    where
+        T: Clone,
+    {
+        self.peek_with(|r| r.read_huffman(tree))
+    }
+
+    /// Scans forward from the current position for the given pattern
+    /// of bits and leaves the reader positioned at its start,
+    /// returning the number of bits skipped over to reach it.
+    /// The pattern is given as it would be returned by `read`
+    /// for the same number of bits, and may begin at any bit position.
+    ///
+    /// The scan shifts each bit into a window of the pattern's size
+    /// and compares the window to the pattern, one bit at a time.
+    /// The stream is read a buffer at a time,
+    /// and any bytes read past the start of the pattern
+    /// are kept to be read again, so this needs no `Seek`
+    /// from the underlying stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `UnexpectedEof` if the stream
+    /// ends without the pattern being found,
+    /// in which case the reader is left at its end.
+    /// Returns an error if the pattern is not between 1 and 64 bits
+    /// or is too large to fit the given number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// // a 14 bit sync code of 0b11111111111110 begins 5 bits in
+    /// let data = [0b10100_111, 0b11111111, 0b110_10101];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.seek_to_pattern(14, 0b11111111111110).unwrap(), 5);
+    /// assert_eq!(reader.read::<u16>(14).unwrap(), 0b11111111111110);
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10101);
+    /// assert!(reader.seek_to_pattern(2, 0b11).is_err());
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0x00, 0b1100_0000, 0b0000_0011];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0);
+    /// assert_eq!(reader.seek_to_pattern(4, 0b1111).unwrap(), 10);
+    /// assert_eq!(reader.position_in_bits().unwrap(), 14);
+    /// ```
+    ///
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// // a byte slice reader, which can't seek
+    /// let data: &[u8] = &[0x12, 0x34, 0xFF, 0xF8, 0x56];
+    /// let mut reader = BitReader::endian(data, BigEndian);
+    /// assert_eq!(reader.seek_to_pattern(13, 0b1111111111111).unwrap(), 16);
+    /// assert!(reader.byte_aligned());
+    /// assert_eq!(reader.read::<u16>(16).unwrap(), 0xFFF8);
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 0x56);
+    /// ```
+    pub fn seek_to_pattern(&mut self, bits: u32, pattern: u64) -> io::Result<u64> {
+        if bits == 0 || bits > 64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ));
+        } else if bits < 64 && pattern >= (1 << bits) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for pattern",
+            ));
+        }
+        let mask = u64::MAX >> (64 - bits);
+
+        // the pattern and window hold bits in the order they're read,
+        // with the earliest bit most significant
+        let mut queue = BitQueue::<E, u64>::from_value(pattern, bits);
+        let target = (0..bits).fold(0, |acc, _| (acc << 1) | queue.pop(1));
+        let mut window = 0u64;
+        let mut scanned = 0u64;
+        let found =
+            |window: u64, scanned: u64| scanned >= u64::from(bits) && window & mask == target;
+
+        // queued bits are scanned from a copy
+        // so that they remain if the pattern starts among them
+        let queued = u64::from(self.bitqueue.len());
+        let mut bitqueue = self.bitqueue.clone();
+        while !bitqueue.is_empty() {
+            window = (window << 1) | u64::from(bitqueue.pop(1));
+            scanned += 1;
+            if found(window, scanned) {
+                let skipped = scanned - u64::from(bits);
+                self.bitqueue.drop(skipped as u32);
+                #[cfg(feature = "observer")]
+                self.observed.read(skipped);
+                return Ok(skipped);
+            }
+        }
+
+        // the rest is read a buffer at a time,
+        // keeping the last 8 bytes of the previous read
+        // since a pattern of up to 64 bits may start in them
+        const KEPT: usize = 8;
+        let mut buf = [0; KEPT + 4096];
+        let mut kept = 0;
+        // the number of stream bytes read before buf[0]
+        let mut base = 0u64;
+        loop {
+            let read = match io::Read::read(&mut self.reader, &mut buf[kept..]) {
+                Ok(0) => {
+                    self.bitqueue.clear();
+                    #[cfg(feature = "observer")]
+                    self.observed.read(scanned);
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "pattern not found",
+                    ));
+                }
+                Ok(read) => read,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            let end = kept + read;
+            for index in kept..end {
+                let mut byte = BitQueue::<E, u8>::from_value(buf[index], 8);
+                for _ in 0..8 {
+                    window = (window << 1) | u64::from(byte.pop(1));
+                    scanned += 1;
+                    if found(window, scanned) {
+                        let skipped = scanned - u64::from(bits);
+                        if skipped < queued {
+                            // every byte read is still to come
+                            self.bitqueue.drop(skipped as u32);
+                            self.reader.unread(&buf[0..end]);
+                        } else {
+                            let start = skipped - queued;
+                            let index = (start / 8 - base) as usize;
+                            let offset = (start % 8) as u32;
+                            if offset == 0 {
+                                self.bitqueue.clear();
+                                self.reader.unread(&buf[index..end]);
+                            } else {
+                                self.bitqueue = BitQueue::from_value(buf[index], 8);
+                                self.bitqueue.drop(offset);
+                                self.reader.unread(&buf[index + 1..end]);
+                            }
+                        }
+                        #[cfg(feature = "observer")]
+                        self.observed.read(skipped);
+                        return Ok(skipped);
+                    }
+                }
+            }
+            if end > KEPT {
+                buf.copy_within(end - KEPT..end, 0);
+                base += (end - KEPT) as u64;
+                kept = KEPT;
+            } else {
+                kept = end;
+            }
+        }
+    }
+
+    /// Returns true if no bits are queued and the stream has ended,
+    /// otherwise queues the stream's next byte if necessary
+    fn at_clean_eof(&mut self) -> io::Result<bool> {
+        if !self.bitqueue.is_empty() {
+            return Ok(false);
+        }
+        let mut byte = [0];
+        loop {
+            match io::Read::read(&mut self.reader, &mut byte) {
+                Ok(0) => return Ok(true),
+                Ok(_) => {
+                    self.bitqueue.set(byte[0], 8);
+                    return Ok(false);
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn peek_with<T, F>(&mut self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Self) -> io::Result<T>,
+    {
+        let bitqueue = self.bitqueue.clone();
+        self.reader.start_peek();
+        #[cfg(feature = "observer")]
+        let observed = self.observed.suspend();
+        let result = f(self);
+        #[cfg(feature = "observer")]
+        self.observed.resume(observed);
+        self.reader.finish_peek();
+        self.bitqueue = bitqueue;
+        result
+    }
+}
+
+impl<'a, E: Endianness> BitReader<&'a [u8], E> {
+    /// Returns an independent reader over the next given number of bytes
+    /// of a byte slice and advances this reader past them.
+    ///
+    /// This is for handing portions of a stream, such as frames,
+    /// to separate threads without copying the underlying data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader is not byte-aligned,
+    /// if bytes examined by `peek` have yet to be read,
+    /// or if fewer than the given number of bytes remain.
+    /// The reader is unchanged on error.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0x12, 0x34, 0x56];
+    /// let mut reader = BitReader::endian(&data[..], LittleEndian);
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 0x12);
+    /// let mut frame = reader.split_bytes(1).unwrap();
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 0x56);
+    /// assert_eq!(frame.read::<u8>(4).unwrap(), 0x4);
+    /// assert!(frame.split_bytes(0).is_err());
+    /// assert_eq!(frame.read::<u8>(4).unwrap(), 0x3);
+    /// assert!(frame.split_bytes(1).is_err());
+    /// ```
+    pub fn split_bytes(&mut self, bytes: usize) -> io::Result<BitReader<&'a [u8], E>> {
+        if !self.bitqueue.is_empty() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "split reader not byte-aligned",
+            ))
+        } else if !self.reader.peeked.is_empty() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "split reader has peeked bytes pending",
+            ))
+        } else if bytes > self.reader.inner.len() {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "split past end of data",
+            ))
+        } else {
+            let (split, rest) = self.reader.inner.split_at(bytes);
+            self.reader.inner = rest;
+            #[cfg(feature = "observer")]
+            self.observed.read(bytes as u64 * 8);
+            Ok(BitReader::new(split))
+        }
+    }
+
+    /// Returns the next given number of bytes of a byte slice
+    /// and advances this reader past them, without copying them.
+    ///
+    /// The bytes borrow from the slice rather than the reader,
+    /// so they may outlive it.
+    /// Memory-mapped files, such as those of the `memmap2` crate,
+    /// dereference to a byte slice and may be read this way
+    /// without copying their payloads out of the mapping.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader is not byte-aligned,
+    /// if bytes examined by `peek` have yet to be read,
+    /// or if fewer than the given number of bytes remain.
+    /// The reader is unchanged on error.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0x03, b'a', b'b', b'c', 0xFF];
+    /// let payload = {
+    ///     let mut reader = BitReader::endian(&data[..], BigEndian);
+    ///     let len = reader.read::<u8>(8).unwrap();
+    ///     let payload = reader.read_bytes_borrowed(len.into()).unwrap();
+    ///     assert_eq!(reader.read::<u8>(4).unwrap(), 0xF);
+    ///     assert!(reader.read_bytes_borrowed(0).is_err());
+    ///     payload
+    /// };
+    /// assert_eq!(payload, b"abc");
+    /// ```
+    pub fn read_bytes_borrowed(&mut self, bytes: usize) -> io::Result<&'a [u8]> {
+        if !self.bitqueue.is_empty() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "borrowed read not byte-aligned",
+            ))
+        } else if !self.reader.peeked.is_empty() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "borrowed read has peeked bytes pending",
+            ))
+        } else if bytes > self.reader.inner.len() {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read past end of data",
+            ))
+        } else {
+            let (read, rest) = self.reader.inner.split_at(bytes);
+            self.reader.inner = rest;
+            #[cfg(feature = "observer")]
+            self.observed.read(bytes as u64 * 8);
+            Ok(read)
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>, E: Endianness> BitReader<IterReader<I>, E> {
+    /// Wraps a BitReader around anything which produces bytes,
+    /// such as an iterator, without an `io::Read` implementation
+    /// of its own.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// // bytes produced lazily, one at a time
+    /// let bytes = (0..4).map(|i| 0x11 * i);
+    /// let mut reader = BitReader::<_, BigEndian>::from_bytes_iter(bytes);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0x0);
+    /// assert_eq!(reader.read::<u16>(16).unwrap(), 0x0112);
+    /// assert_eq!(reader.read::<u16>(12).unwrap(), 0x233);
+    /// assert!(reader.read::<u8>(1).is_err());
+    /// ```
+    #[inline]
+    pub fn from_bytes_iter<T>(bytes: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = u8>,
+    {
+        BitReader::new(IterReader::new(bytes))
+    }
+}
+
+impl<R: io::Read + RemainingBytes, E: Endianness> BitReader<R, E> {
+    /// Returns the exact number of bits which remain to be read,
+    /// including any queued partial byte,
+    /// or `None` if the source doesn't know how many bytes it has left.
+    ///
+    /// Checking a count read from untrusted data against this
+    /// before allocating room for it guards against headers
+    /// which claim more data than there is.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Cursor, Read};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0xFF, 0x00, 0x01, 0x02];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.remaining_bits(), Some(32));
+    /// let count = reader.read::<u8>(8).unwrap();
+    /// assert_eq!(reader.remaining_bits(), Some(24));
+    /// // 255 16-bit values can't fit in 24 bits
+    /// assert!(u64::from(count) * 16 > reader.remaining_bits().unwrap());
+    /// reader.read::<u8>(3).unwrap();
+    /// assert_eq!(reader.remaining_bits(), Some(21));
+    ///
+    /// let mut reader = BitReader::endian((&data[..]).take(2), BigEndian);
+    /// assert_eq!(reader.remaining_bits(), Some(16));
+    /// ```
+    pub fn remaining_bits(&self) -> Option<u64> {
+        self.reader
+            .inner
+            .remaining_bytes()?
+            .checked_add(self.reader.peeked.len() as u64)?
+            .checked_mul(8)?
+            .checked_add(u64::from(self.bitqueue.len()))
+    }
+}
+
+impl<R: io::Read, E: Endianness> BitRead for BitReader<R, E> {
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), false);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), false);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), false);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), false);
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// ```
+    #[inline(always)]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        if self.bitqueue.is_empty() {
+            self.bitqueue.set(read_byte(&mut self.reader)?, 8);
+        }
+        #[cfg(feature = "observer")]
+        self.observed.read(1);
+        Ok(self.bitqueue.pop(1) == 1)
+    }
+
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u8>(1).unwrap(), 0b1);
+    /// assert_eq!(reader.read::<u8>(2).unwrap(), 0b01);
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10111);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read::<u8>(1).unwrap(), 0b1);
+    /// assert_eq!(reader.read::<u8>(2).unwrap(), 0b11);
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0;10];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert!(reader.read::<u8>(9).is_err());    // can't read  9 bits to u8
+    /// assert!(reader.read::<u16>(17).is_err());  // can't read 17 bits to u16
+    /// assert!(reader.read::<u32>(33).is_err());  // can't read 33 bits to u32
+    /// assert!(reader.read::<u64>(65).is_err());  // can't read 65 bits to u64
+    /// ```
+    fn read<U>(&mut self, mut bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits <= U::bits_size() {
+            let bitqueue_len = self.bitqueue.len();
+            if bits <= bitqueue_len {
+                #[cfg(feature = "observer")]
+                self.observed.read(bits.into());
+                Ok(U::from_u8(self.bitqueue.pop(bits)))
+            } else {
+                let mut acc =
+                    BitQueue::from_value(U::from_u8(self.bitqueue.pop_all()), bitqueue_len);
+                bits -= bitqueue_len;
+
+                read_aligned(&mut self.reader, bits / 8, &mut acc)?;
+                read_unaligned(&mut self.reader, bits % 8, &mut acc, &mut self.bitqueue)?;
+                #[cfg(feature = "observer")]
+                self.observed.read(u64::from(bits + bitqueue_len));
+                Ok(acc.value())
+            }
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        }
+    }
+
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), 7);
+    /// assert_eq!(reader.read_signed::<i8>(4).unwrap(), -5);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0;10];
+    /// let mut r = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert!(r.read_signed::<i8>(9).is_err());   // can't read 9 bits to i8
+    /// assert!(r.read_signed::<i16>(17).is_err()); // can't read 17 bits to i16
+    /// assert!(r.read_signed::<i32>(33).is_err()); // can't read 33 bits to i32
+    /// assert!(r.read_signed::<i64>(65).is_err()); // can't read 65 bits to i64
+    /// ```
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert!(reader.skip(3).is_ok());
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10111);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert!(reader.skip(3).is_ok());
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = vec![0; 1 << 20];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert!(reader.skip(3).is_ok());
+    /// assert!(reader.skip(((1 << 20) - 1) * 8).is_ok());
+    /// assert!(reader.skip(5).is_ok());
+    /// assert!(reader.skip(1).is_err());
+    /// ```
+    fn skip(&mut self, mut bits: u64) -> io::Result<()> {
+        use std::cmp::min;
+
+        #[cfg(feature = "observer")]
+        let skipped = bits;
+        let to_drop = min(self.bitqueue.len() as u64, bits);
+        if to_drop != 0 {
+            self.bitqueue.drop(to_drop as u32);
+            bits -= to_drop;
+        }
+
+        skip_aligned(&mut self.reader, bits / 8)?;
+        skip_unaligned(&mut self.reader, (bits % 8) as u32, &mut self.bitqueue)?;
+        #[cfg(feature = "observer")]
+        self.observed.read(skipped);
+        Ok(())
+    }
+
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = b"foobar";
+    /// let mut reader = BitReader::endian(Cursor::new(data), BigEndian);
+    /// assert!(reader.skip(24).is_ok());
+    /// let mut buf = [0;3];
+    /// assert!(reader.read_bytes(&mut buf).is_ok());
+    /// assert_eq!(&buf, b"bar");
+    /// ```
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        if self.byte_aligned() {
+            io::Read::read_exact(&mut self.reader, buf)?;
+            #[cfg(feature = "observer")]
+            self.observed.read(buf.len() as u64 * 8);
+            Ok(())
+        } else {
+            for b in buf.iter_mut() {
+                *b = self.read(8)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b01110111, 0b11111110];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_unary0().unwrap(), 0);
+    /// assert_eq!(reader.read_unary0().unwrap(), 3);
+    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b11101110, 0b01111111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_unary0().unwrap(), 0);
+    /// assert_eq!(reader.read_unary0().unwrap(), 3);
+    /// assert_eq!(reader.read_unary0().unwrap(), 10);
+    /// ```
+    fn read_unary0(&mut self) -> io::Result<u32> {
+        let unary = if self.bitqueue.is_empty() {
+            read_aligned_unary(&mut self.reader, 0b1111_1111, &mut self.bitqueue)?
+                + self.bitqueue.pop_1()
+        } else if self.bitqueue.all_1() {
+            let base = self.bitqueue.len();
+            self.bitqueue.clear();
+            base + read_aligned_unary(&mut self.reader, 0b1111_1111, &mut self.bitqueue)?
+                + self.bitqueue.pop_1()
+        } else {
+            self.bitqueue.pop_1()
+        };
+        #[cfg(feature = "observer")]
+        self.observed.read(u64::from(unary) + 1);
+        Ok(unary)
+    }
+
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10001000, 0b00000001];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_unary1().unwrap(), 0);
+    /// assert_eq!(reader.read_unary1().unwrap(), 3);
+    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b00010001, 0b10000000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_unary1().unwrap(), 0);
+    /// assert_eq!(reader.read_unary1().unwrap(), 3);
+    /// assert_eq!(reader.read_unary1().unwrap(), 10);
+    /// ```
+    fn read_unary1(&mut self) -> io::Result<u32> {
+        let unary = if self.bitqueue.is_empty() {
+            read_aligned_unary(&mut self.reader, 0b0000_0000, &mut self.bitqueue)?
+                + self.bitqueue.pop_0()
+        } else if self.bitqueue.all_0() {
+            let base = self.bitqueue.len();
+            self.bitqueue.clear();
+            base + read_aligned_unary(&mut self.reader, 0b0000_0000, &mut self.bitqueue)?
+                + self.bitqueue.pop_0()
+        } else {
+            self.bitqueue.pop_0()
+        };
+        #[cfg(feature = "observer")]
+        self.observed.read(u64::from(unary) + 1);
+        Ok(unary)
+    }
+
+    fn read_unary0_capped(&mut self, max: u32) -> io::Result<u32> {
+        let mut unary = 0;
+        while unary < max {
+            if self.bitqueue.is_empty() {
+                self.bitqueue.set(read_byte(&mut self.reader)?, 8);
+            }
+            let ones = E::next_ones(&self.bitqueue)
+                .min(self.bitqueue.len())
+                .min(max - unary);
+            if ones > 0 {
+                self.bitqueue.drop(ones);
+                unary += ones;
+            }
+            if unary < max && !self.bitqueue.is_empty() {
+                // the stop bit is next in the queue
+                self.bitqueue.drop(1);
+                break;
+            }
+        }
+        // the stop bit is only read if the maximum isn't reached
+        #[cfg(feature = "observer")]
+        self.observed
+            .read(u64::from(unary) + u64::from(unary < max));
+        Ok(unary)
+    }
+
+    fn read_unary1_capped(&mut self, max: u32) -> io::Result<u32> {
+        let mut unary = 0;
+        while unary < max {
+            if self.bitqueue.is_empty() {
+                self.bitqueue.set(read_byte(&mut self.reader)?, 8);
+            }
+            let zeros = E::next_zeros(&self.bitqueue)
+                .min(self.bitqueue.len())
+                .min(max - unary);
+            if zeros > 0 {
+                self.bitqueue.drop(zeros);
+                unary += zeros;
+            }
+            if unary < max && !self.bitqueue.is_empty() {
+                // the stop bit is next in the queue
+                self.bitqueue.drop(1);
+                break;
+            }
+        }
+        // the stop bit is only read if the maximum isn't reached
+        #[cfg(feature = "observer")]
+        self.observed
+            .read(u64::from(unary) + u64::from(unary < max));
+        Ok(unary)
+    }
+
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.byte_aligned(), true);
+    /// assert!(reader.skip(1).is_ok());
+    /// assert_eq!(reader.byte_aligned(), false);
+    /// assert!(reader.skip(7).is_ok());
+    /// assert_eq!(reader.byte_aligned(), true);
+    /// ```
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.bitqueue.is_empty()
+    }
+
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0x00, 0xFF];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0);
+    /// reader.byte_align();
+    /// assert_eq!(reader.read::<u8>(8).unwrap(), 0xFF);
+    /// ```
+    #[inline]
+    fn byte_align(&mut self) {
+        #[cfg(feature = "observer")]
+        self.observed.read(self.bitqueue.len().into());
+        self.bitqueue.clear()
+    }
+}
+
+/// A saved position within a seekable `BitReader`,
+/// returned by `BitReader::bookmark` and
+/// passed to `BitReader::restore` to return to it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    position: u64,
+    bits: u32,
+    value: u8,
+}
+
+impl<R, E> BitReader<R, E>
+where
+    E: Endianness,
+    R: io::Read + io::Seek,
+{
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor, SeekFrom};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0x00, 0xFF];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.position_in_bits().unwrap(), 0);
+    ///
+    /// let pos = reader.seek_bits(SeekFrom::Start(5)).unwrap();
+    /// assert!(pos == 5 && 5 == reader.position_in_bits().unwrap());
+    ///
+    /// let pos = reader.seek_bits(SeekFrom::Current(-2)).unwrap();
+    /// assert!(pos == 3 && 3 == reader.position_in_bits().unwrap());    ///
+    ///
+    /// let pos = reader.seek_bits(SeekFrom::End(5)).unwrap();
+    /// assert!(pos == 11 && 11 == reader.position_in_bits().unwrap());
+    /// ```
+    pub fn seek_bits(&mut self, from: io::SeekFrom) -> io::Result<u64> {
+        match from {
+            io::SeekFrom::Start(from_start_pos) => {
+                let (bytes, bits) = (from_start_pos / 8, from_start_pos % 8);
+                self.byte_align();
+                self.reader.inner.seek(io::SeekFrom::Start(bytes))?;
+                self.reader.peeked.clear();
+                self.skip(bits)?;
+                Ok(from_start_pos)
+            }
+            io::SeekFrom::End(from_end_pos) => {
+                let reader_end = self.reader.inner.seek(io::SeekFrom::End(0))?;
+                self.reader.peeked.clear();
+                let new_pos = (reader_end * 8) as i64 - from_end_pos;
+                assert!(new_pos >= 0, "The final position should be greater than 0");
+                self.seek_bits(io::SeekFrom::Start(new_pos as u64))
+            }
+            io::SeekFrom::Current(offset) => {
+                let new_pos = self.position_in_bits()? as i64 + offset;
+                assert!(new_pos >= 0, "The final position should be greater than 0");
+                self.seek_bits(io::SeekFrom::Start(new_pos as u64))
+            }
+        }
+    }
+
+    /// # Example
+    /// ```
+    /// use std::fs::read;
+    /// use std::io::{Read, Cursor, SeekFrom};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0x00, 0xFF];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.position_in_bits().unwrap(), 0);
+    ///
+    /// let _: i32 = reader.read_signed(5).unwrap();
+    /// assert_eq!(reader.position_in_bits().unwrap(), 5);
+    ///
+    /// reader.read_bit().unwrap();
+    /// assert_eq!(reader.position_in_bits().unwrap(), 6);
+    /// ```
+    #[inline]
+    pub fn position_in_bits(&mut self) -> io::Result<u64> {
+        let bytes = self.stream_position()?;
+        Ok(bytes * 8 - (self.bitqueue.len() as u64))
+    }
+
+    /// Returns an independent reader over the given range of bytes
+    /// of the underlying stream, which must have been cloned
+    /// from a stream whose clones may be positioned independently,
+    /// such as a `Cursor` over a byte slice.
+    /// The range is counted from the start of the stream,
+    /// and this reader's own position is unaffected.
+    ///
+    /// This is for handing disjoint portions of a stream,
+    /// such as frames whose offsets are known, to separate threads
+    /// without copying the underlying data.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from seeking the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0x12, 0x34, 0x56, 0x78];
+    /// let mut reader = BitReader::endian(Cursor::new(&data[..]), BigEndian);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0x1);
+    /// let mut first = reader.fork(1..3).unwrap();
+    /// let mut second = reader.fork(3..4).unwrap();
+    /// assert_eq!(second.read::<u8>(8).unwrap(), 0x78);
+    /// assert!(second.read_bit().is_err());
+    /// assert_eq!(first.read::<u16>(16).unwrap(), 0x3456);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0x2);
+    /// ```
+    pub fn fork(&self, bytes: Range<u64>) -> io::Result<BitReader<io::Take<R>, E>>
+    where
+        R: Clone,
+    {
+        let mut reader = self.reader.inner.clone();
+        reader.seek(io::SeekFrom::Start(bytes.start))?;
+        Ok(BitReader::new(
+            reader.take(bytes.end.saturating_sub(bytes.start)),
+        ))
+    }
+
+    /// Saves the reader's current position, including any
+    /// partially read byte, so that it may be returned to later
+    /// with `restore`.
+    /// This allows a parser to try several interpretations
+    /// of the stream and backtrack from any which fail.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, BitRead};
+    /// let data = [0b10110111, 0b01010101];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+    /// let bookmark = reader.bookmark().unwrap();
+    /// assert_eq!(reader.read::<u16>(13).unwrap(), 0b10111_01010101);
+    /// assert!(reader.read_bit().is_err());
+    /// reader.restore(&bookmark).unwrap();
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10111);
+    /// assert_eq!(reader.position_in_bits().unwrap(), 8);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead};
+    /// let data = [0b10110111, 0b01010101];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b111);
+    /// let bookmark = reader.bookmark().unwrap();
+    /// assert_eq!(reader.read::<u16>(13).unwrap(), 0b01010101_10110);
+    /// reader.restore(&bookmark).unwrap();
+    /// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+    /// ```
+    pub fn bookmark(&mut self) -> io::Result<Bookmark> {
+        Ok(Bookmark {
+            position: self.stream_position()?,
+            bits: self.bitqueue.len(),
+            value: self.bitqueue.clone().value(),
+        })
+    }
+
+    /// Returns the reader to a position saved by `bookmark`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    #[inline]
+    pub fn restore(&mut self, bookmark: &Bookmark) -> io::Result<()> {
+        self.reader
+            .inner
+            .seek(io::SeekFrom::Start(bookmark.position))?;
+        self.reader.peeked.clear();
+        self.bitqueue = BitQueue::from_value(bookmark.value, bookmark.bits);
+        Ok(())
+    }
+
+    /// Returns the byte position of the next unread byte,
+    /// which precedes any peeked bytes the stream is past
+    #[inline]
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.reader.inner.stream_position()? - self.reader.peeked.len() as u64)
+    }
+}
+
+impl<R: io::Read, E: Endianness> HuffmanRead<E> for BitReader<R, E> {
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, BitReader, HuffmanRead};
+    /// use bitstream_io::huffman::compile_read_tree;
+    /// let tree = compile_read_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let data = [0b10110111];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'b');
+    /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'c');
+    /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'd');
+    /// ```
+    fn read_huffman<T>(&mut self, tree: &[ReadHuffmanTree<E, T>]) -> io::Result<T>
+    where
+        T: Clone,
+    {
+        let mut result: &ReadHuffmanTree<E, T> = &tree[self.bitqueue.to_state()];
+        #[cfg(feature = "observer")]
+        let mut consumed = self.bitqueue.len();
+        loop {
+            match result {
+                ReadHuffmanTree::Done(ref value, ref queue_val, ref queue_bits, _) => {
+                    self.bitqueue.set(*queue_val, *queue_bits);
+                    #[cfg(feature = "observer")]
+                    self.observed.read(u64::from(consumed - *queue_bits));
+                    return Ok(value.clone());
+                }
+                ReadHuffmanTree::Continue(ref tree) => {
+                    result = &tree[read_byte(&mut self.reader)? as usize];
+                    #[cfg(feature = "observer")]
+                    {
+                        consumed += 8;
+                    }
+                }
+                ReadHuffmanTree::InvalidState => {
+                    panic!("invalid state");
+                }
+            }
+        }
+    }
+
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, BitReader, HuffmanRead};
+    /// use bitstream_io::huffman::compile_read_tree;
+    /// let tree = compile_read_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let data = [0b11101101, 0b00000000];
+    /// let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    /// let mut symbols = ['?'; 4];
+    /// reader.read_huffman_into(&tree, &mut symbols).unwrap();
+    /// assert_eq!(symbols, ['b', 'c', 'd', 'a']);
+    /// ```
+    fn read_huffman_into<T>(
+        &mut self,
+        tree: &[ReadHuffmanTree<E, T>],
+        symbols: &mut [T],
+    ) -> io::Result<()>
+    where
+        T: Clone,
+    {
+        // the reader's state only needs updating once all symbols are read
+        // or when the stream has no more bytes
+        let mut state = self.bitqueue.to_state();
+        let mut queue = (0, 0);
+        #[cfg(feature = "observer")]
+        let mut consumed = u64::from(self.bitqueue.len());
+        for symbol in symbols.iter_mut() {
+            let mut result: &ReadHuffmanTree<E, T> = &tree[state];
+            loop {
+                match result {
+                    ReadHuffmanTree::Done(ref value, ref queue_val, ref queue_bits, _) => {
+                        *symbol = value.clone();
+                        queue = (*queue_val, *queue_bits);
+                        state = (1 << queue.1) | (queue.0 as usize);
+                        break;
+                    }
+                    ReadHuffmanTree::Continue(ref tree) => match read_byte(&mut self.reader) {
+                        Ok(byte) => {
+                            result = &tree[byte as usize];
+                            #[cfg(feature = "observer")]
+                            {
+                                consumed += 8;
+                            }
+                        }
+                        Err(err) => {
+                            self.bitqueue.set(queue.0, queue.1);
+                            return Err(err);
+                        }
+                    },
+                    ReadHuffmanTree::InvalidState => {
+                        panic!("invalid state");
+                    }
+                }
+            }
+        }
+        self.bitqueue.set(queue.0, queue.1);
+        #[cfg(feature = "observer")]
+        self.observed.read(consumed - u64::from(queue.1));
+        Ok(())
+    }
+}
+
+impl<R, E> HuffmanRead<E> for &mut R
+where
+    R: HuffmanRead<E> + ?Sized,
+    E: Endianness,
+{
+    #[inline]
+    fn read_huffman<T>(&mut self, tree: &[ReadHuffmanTree<E, T>]) -> io::Result<T>
+    where
+        T: Clone,
+    {
+        (**self).read_huffman(tree)
+    }
+
+    #[inline]
+    fn read_huffman_into<T>(
+        &mut self,
+        tree: &[ReadHuffmanTree<E, T>],
+        symbols: &mut [T],
+    ) -> io::Result<()>
+    where
+        T: Clone,
+    {
+        (**self).read_huffman_into(tree, symbols)
+    }
+}
+
+/// For limiting the number of bits which may be read from a reader.
+///
+/// Any read which would exceed the limit returns a `BitLimitError`
+/// as an error of kind `Other` and is not passed to the underlying reader,
+/// so that an exhausted budget isn't mistaken for truncated input.
+/// This is typically created by the `take` method of `BitRead`.
+#[derive(Clone, Debug)]
+pub struct LimitedBitReader<R: BitRead> {
+    reader: R,
+    remaining: u64,
+}
+
+impl<R: BitRead> LimitedBitReader<R> {
+    /// Wraps a LimitedBitReader around something that implements `BitRead`
+    /// which allows at most the given number of bits to be read.
+    #[inline]
+    pub fn new(reader: R, limit: u64) -> Self {
+        LimitedBitReader {
+            reader,
+            remaining: limit,
+        }
+    }
+
+    /// Returns number of bits which may still be read
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Provides mutable reference to internal reader
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of LimitedBitReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    #[inline]
+    fn reserve(&self, bits: u64) -> io::Result<u64> {
+        if bits <= self.remaining {
+            Ok(bits)
+        } else {
+            Err(BitLimitError::new(bits, self.remaining).into())
+        }
+    }
+}
+
+impl<R: BitRead> BitRead for LimitedBitReader<R> {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        let bits = self.reserve(1)?;
+        let bit = self.reader.read_bit()?;
+        self.remaining -= bits;
+        Ok(bit)
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        let bits = self.reserve(bits.into())?;
+        let value = self.reader.read(bits as u32)?;
+        self.remaining -= bits;
+        Ok(value)
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        let bits = self.reserve(bits.into())?;
+        let value = self.reader.read_signed(bits as u32)?;
+        self.remaining -= bits;
+        Ok(value)
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        let bits = self.reserve(bits)?;
+        self.reader.skip(bits)?;
+        self.remaining -= bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let bits = self.reserve(buf.len() as u64 * 8)?;
+        self.reader.read_bytes(buf)?;
+        self.remaining -= bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    /// Bits thrown away count against the limit,
+    /// but the underlying reader is always left byte-aligned.
+    fn byte_align(&mut self) {
+        while !self.reader.byte_aligned() && self.read_bit().is_ok() {}
+        self.reader.byte_align()
+    }
+}
+
+/// For counting the number of bits read from a reader.
+///
+/// This is the reading counterpart to `BitCounter`,
+/// so that parsers may check a structure consumed exactly
+/// its declared size, or find where its payload begins,
+/// without adding up the widths of its fields by hand.
+///
+/// Only successful reads are counted,
+/// since a failed read may consume an unknown number of bits.
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use bitstream_io::{BigEndian, BitReader, BitRead, CountingBitReader};
+/// let data = [0b10110111, 0b01010101, 0b11110000];
+/// let mut reader = CountingBitReader::new(BitReader::endian(Cursor::new(&data), BigEndian));
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+/// assert_eq!(reader.read_unary0().unwrap(), 1);
+/// assert_eq!(reader.bits_read(), 5);
+/// reader.byte_align();
+/// assert_eq!(reader.bits_read(), 8);
+/// reader.skip(4).unwrap();
+/// assert_eq!(reader.read::<u8>(8).unwrap(), 0b0101_1111);
+/// assert_eq!(reader.bits_read(), 20);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CountingBitReader<R: BitRead> {
+    reader: R,
+    bits: u64,
+}
+
+impl<R: BitRead> CountingBitReader<R> {
+    /// Wraps a CountingBitReader around something that implements `BitRead`
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        CountingBitReader { reader, bits: 0 }
+    }
+
+    /// Returns number of bits read
+    #[inline]
+    pub fn bits_read(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns number of bytes read, rounding up any partial byte
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.bits / 8 + u64::from(self.bits & 7 != 0)
+    }
+
+    /// Resets the number of bits read to 0
+    #[inline]
+    pub fn reset(&mut self) {
+        self.bits = 0;
+    }
+
+    /// Provides mutable reference to internal reader
+    ///
+    /// Bits read from it directly are not counted.
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of CountingBitReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BitRead> BitRead for CountingBitReader<R> {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        let bit = self.reader.read_bit()?;
+        self.bits += 1;
+        Ok(bit)
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        let value = self.reader.read(bits)?;
+        self.bits += u64::from(bits);
+        Ok(value)
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        let value = self.reader.read_signed(bits)?;
+        self.bits += u64::from(bits);
+        Ok(value)
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        self.reader.skip(bits)?;
+        self.bits += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.reader.read_bytes(buf)?;
+        self.bits += buf.len() as u64 * 8;
+        Ok(())
+    }
+
+    #[inline]
+    fn read_unary0(&mut self) -> io::Result<u32> {
+        let unary = self.reader.read_unary0()?;
+        self.bits += u64::from(unary) + 1;
+        Ok(unary)
+    }
+
+    #[inline]
+    fn read_unary1(&mut self) -> io::Result<u32> {
+        let unary = self.reader.read_unary1()?;
+        self.bits += u64::from(unary) + 1;
+        Ok(unary)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    /// Bits thrown away are counted as read.
+    fn byte_align(&mut self) {
+        while !self.reader.byte_aligned() && self.read_bit().is_ok() {}
+        self.reader.byte_align()
+    }
+}
+
+/// For inspecting a window of upcoming bits before reading them.
+///
+/// Table-driven decoders, such as those for Huffman-coded
+/// JPEG or DEFLATE data, commonly look at the next several bits
+/// to find a code, then consume only as many bits as the code is long.
+/// `fill_lookahead` buffers up to `MAX_LOOKAHEAD` bits from the wrapped reader,
+/// which `lookahead` may then examine any number of times
+/// and `consume` discards once decoded.
+/// Unlike `peek`, this works with any `BitRead` rather than only `BitReader`.
+///
+/// All of `BitRead`'s methods read buffered bits first,
+/// so lookahead may be freely mixed with ordinary reads.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitReader, BitRead, LookaheadReader};
+/// // codes are 0 -> 'a', 10 -> 'b', 110 -> 'c', 111 -> 'd',
+/// // looked up by their first 3 bits
+/// const TABLE: [(char, u32); 8] = [
+///     ('a', 1), ('a', 1), ('a', 1), ('a', 1),
+///     ('b', 2), ('b', 2), ('c', 3), ('d', 3),
+/// ];
+/// let data = [0b10_0_110_11, 0b1_0_000000];
+/// let mut reader = LookaheadReader::endian(BitReader::endian(&data[..], BigEndian), BigEndian);
+/// let mut decoded = String::new();
+/// for _ in 0..5 {
+///     reader.fill_lookahead(3).unwrap();
+///     let (symbol, len) = TABLE[reader.lookahead(3) as usize];
+///     reader.consume(len).unwrap();
+///     decoded.push(symbol);
+/// }
+/// assert_eq!(decoded, "bacda");
+/// assert_eq!(reader.read::<u8>(6).unwrap(), 0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LookaheadReader<R: BitRead, E: Endianness> {
+    reader: R,
+    queue: BitQueue<E, u64>,
+}
+
+impl<R: BitRead, E: Endianness> LookaheadReader<R, E> {
+    /// The largest number of bits `fill_lookahead` will buffer
+    pub const MAX_LOOKAHEAD: u32 = 56;
+
+    /// Wraps a LookaheadReader around something that implements `BitRead`
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        LookaheadReader {
+            reader,
+            queue: BitQueue::new(),
+        }
+    }
+
+    /// Wraps a LookaheadReader around something that implements `BitRead`
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(reader: R, _endian: E) -> Self {
+        LookaheadReader::new(reader)
+    }
+
+    /// Buffers at least the given number of bits from the wrapped reader,
+    /// if the stream has that many left,
+    /// and returns the number of bits now buffered.
+    ///
+    /// Whole bytes are read where possible,
+    /// so more bits than asked for may be buffered.
+    /// Fewer bits are buffered only if the stream ends first,
+    /// which isn't treated as an error.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream
+    /// other than reaching its end.
+    /// Returns an error of kind `InvalidInput`
+    /// if more than `MAX_LOOKAHEAD` bits are requested.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitReader, BitRead, LookaheadReader};
+    /// let data = [0xAB, 0xCD];
+    /// let mut reader = LookaheadReader::endian(BitReader::endian(&data[..], LittleEndian), LittleEndian);
+    /// assert_eq!(reader.fill_lookahead(4).unwrap(), 8);
+    /// assert_eq!(reader.fill_lookahead(12).unwrap(), 16);
+    /// assert_eq!(reader.fill_lookahead(20).unwrap(), 16);
+    /// assert!(reader.fill_lookahead(57).is_err());
+    /// ```
+    pub fn fill_lookahead(&mut self, bits: u32) -> io::Result<u32> {
+        if bits > Self::MAX_LOOKAHEAD {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for lookahead",
+            ));
+        }
+        // once any bits are buffered, the wrapped reader is kept byte-aligned
+        // so that whole bytes may be read from then on
+        loop {
+            if self.reader.byte_aligned() {
+                if self.queue.len() >= bits {
+                    break;
+                }
+                match self.reader.read::<u8>(8) {
+                    Ok(byte) => self.queue.push(8, byte.into()),
+                    Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(err) => return Err(err),
+                }
+            } else {
+                if bits == 0 && self.queue.is_empty() {
+                    break;
+                }
+                match self.reader.read_bit() {
+                    Ok(bit) => self.queue.push(1, bit.into()),
+                    Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        Ok(self.queue.len())
+    }
+
+    /// Returns the number of bits currently buffered
+    #[inline]
+    pub fn lookahead_len(&self) -> u32 {
+        self.queue.len()
+    }
+
+    /// Returns the next given number of bits without consuming them,
+    /// as `read` would return them.
+    ///
+    /// Any bits past those buffered by `fill_lookahead`
+    /// are returned as 0, as if the stream were padded with 0 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `MAX_LOOKAHEAD` bits are requested.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitReader, LookaheadReader};
+    /// let data = [0b1011_0111];
+    /// let mut reader = LookaheadReader::endian(BitReader::endian(&data[..], BigEndian), BigEndian);
+    /// reader.fill_lookahead(8).unwrap();
+    /// assert_eq!(reader.lookahead(3), 0b101);
+    /// assert_eq!(reader.lookahead(3), 0b101);
+    /// assert_eq!(reader.lookahead(12), 0b1011_0111_0000);
+    /// ```
+    pub fn lookahead(&self, bits: u32) -> u64 {
+        assert!(bits <= Self::MAX_LOOKAHEAD, "excessive bits for lookahead");
+        let mut queue = self.queue.clone();
+        if bits <= queue.len() {
+            queue.pop(bits)
+        } else {
+            queue.push(bits - queue.len(), 0);
+            queue.value()
+        }
+    }
+
+    /// Discards the given number of buffered bits
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `UnexpectedEof`
+    /// if fewer bits than that are buffered,
+    /// such as when a code was matched against
+    /// the padding past the end of the stream.
+    /// No bits are consumed in that case.
+    pub fn consume(&mut self, bits: u32) -> io::Result<()> {
+        if bits <= self.queue.len() {
+            self.queue.drop(bits);
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "consumed past end of lookahead",
+            ))
+        }
+    }
+
+    /// Provides mutable reference to internal reader
+    ///
+    /// Bits read from it directly skip past any bits still buffered.
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of LookaheadReader.
+    /// Any bits still buffered are discarded.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BitRead, E: Endianness> BitRead for LookaheadReader<R, E> {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        if self.queue.is_empty() {
+            self.reader.read_bit()
+        } else {
+            Ok(self.queue.pop(1) != 0)
+        }
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        } else if bits <= self.queue.len() {
+            Ok(numeric_from_u64(self.queue.pop(bits)))
+        } else if self.queue.is_empty() {
+            self.reader.read(bits)
+        } else {
+            let buffered = self.queue.len();
+            let rest = self.reader.read::<U>(bits - buffered)?;
+            let mut value = BitQueue::<E, U>::new();
+            value.push(buffered, numeric_from_u64(self.queue.pop_all()));
+            value.push(bits - buffered, rest);
+            Ok(value.value())
+        }
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        let buffered = self.queue.len();
+        if bits <= u64::from(buffered) {
+            self.queue.drop(bits as u32);
+            Ok(())
+        } else {
+            self.reader.skip(bits - u64::from(buffered))?;
+            self.queue.clear();
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        if self.queue.is_empty() {
+            self.reader.byte_aligned()
+        } else {
+            self.queue.len() & 7 == 0
+        }
+    }
+
+    fn byte_align(&mut self) {
+        if self.queue.is_empty() {
+            self.reader.byte_align()
+        } else {
+            self.queue.drop(self.queue.len() & 7)
+        }
+    }
+}
+
+/// For reading bit values from an underlying stream
+/// in an endianness chosen at runtime.
+///
+/// This dispatches each read to a `BitReader` of the chosen
+/// endianness, so parsing code which only needs `BitRead`
+/// doesn't need to be generic over or duplicated per endianness.
+///
+/// # Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BitRead, DynBitReader, DynEndianness};
+/// let data = [0xFE, 0xFF, 0x01, 0x02, 0xFF, 0xFE, 0x01, 0x02];
+/// let mut cursor = Cursor::new(&data);
+/// let mut values = Vec::new();
+/// for _ in 0..2 {
+///     // a byte-order mark picks the endianness of what follows
+///     let mut bom = [0; 2];
+///     cursor.read_exact(&mut bom).unwrap();
+///     let endianness = match bom {
+///         [0xFE, 0xFF] => DynEndianness::Big,
+///         _ => DynEndianness::Little,
+///     };
+///     let mut reader = DynBitReader::new(&mut cursor, endianness);
+///     values.push(reader.read::<u16>(16).unwrap());
+/// }
+/// assert_eq!(values, [0x0102, 0x0201]);
+/// ```
+#[derive(Debug)]
+pub enum DynBitReader<R: io::Read> {
+    /// A big-endian reader
+    Big(BitReader<R, BigEndian>),
+    /// A little-endian reader
+    Little(BitReader<R, LittleEndian>),
+}
+
+impl<R: io::Read> DynBitReader<R> {
+    /// Wraps a DynBitReader around something that implements `Read`
+    /// with the given endianness.
+    #[inline]
+    pub fn new(reader: R, endianness: DynEndianness) -> Self {
+        match endianness {
+            DynEndianness::Big => DynBitReader::Big(BitReader::new(reader)),
+            DynEndianness::Little => DynBitReader::Little(BitReader::new(reader)),
+        }
+    }
+
+    /// Returns the endianness values are read in
+    #[inline]
+    pub fn endianness(&self) -> DynEndianness {
+        match self {
+            DynBitReader::Big(_) => DynEndianness::Big,
+            DynBitReader::Little(_) => DynEndianness::Little,
+        }
+    }
+
+    /// Unwraps internal reader and disposes of DynBitReader.
+    ///
+    /// # Warning
+    ///
+    /// Any unread partial bits are discarded.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        match self {
+            DynBitReader::Big(r) => r.into_reader(),
+            DynBitReader::Little(r) => r.into_reader(),
+        }
+    }
+}
+
+impl<R: io::Read> From<BitReader<R, BigEndian>> for DynBitReader<R> {
+    #[inline]
+    fn from(reader: BitReader<R, BigEndian>) -> Self {
+        DynBitReader::Big(reader)
+    }
+}
+
+impl<R: io::Read> From<BitReader<R, LittleEndian>> for DynBitReader<R> {
+    #[inline]
+    fn from(reader: BitReader<R, LittleEndian>) -> Self {
+        DynBitReader::Little(reader)
+    }
+}
+
+impl<R: io::Read> BitRead for DynBitReader<R> {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        match self {
+            DynBitReader::Big(r) => r.read_bit(),
+            DynBitReader::Little(r) => r.read_bit(),
+        }
+    }
+
+    #[inline]
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        match self {
+            DynBitReader::Big(r) => r.read(bits),
+            DynBitReader::Little(r) => r.read(bits),
+        }
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        match self {
+            DynBitReader::Big(r) => r.read_signed(bits),
+            DynBitReader::Little(r) => r.read_signed(bits),
+        }
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        match self {
+            DynBitReader::Big(r) => r.skip(bits),
+            DynBitReader::Little(r) => r.skip(bits),
+        }
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        match self {
+            DynBitReader::Big(r) => r.read_bytes(buf),
+            DynBitReader::Little(r) => r.read_bytes(buf),
+        }
+    }
+
+    #[inline]
+    fn read_unary0(&mut self) -> io::Result<u32> {
+        match self {
+            DynBitReader::Big(r) => r.read_unary0(),
+            DynBitReader::Little(r) => r.read_unary0(),
+        }
+    }
+
+    #[inline]
+    fn read_unary1(&mut self) -> io::Result<u32> {
+        match self {
+            DynBitReader::Big(r) => r.read_unary1(),
+            DynBitReader::Little(r) => r.read_unary1(),
+        }
+    }
+
+    #[inline]
+    fn read_unary0_capped(&mut self, max: u32) -> io::Result<u32> {
+        match self {
+            DynBitReader::Big(r) => r.read_unary0_capped(max),
+            DynBitReader::Little(r) => r.read_unary0_capped(max),
+        }
+    }
+
+    #[inline]
+    fn read_unary1_capped(&mut self, max: u32) -> io::Result<u32> {
+        match self {
+            DynBitReader::Big(r) => r.read_unary1_capped(max),
+            DynBitReader::Little(r) => r.read_unary1_capped(max),
+        }
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        match self {
+            DynBitReader::Big(r) => r.byte_aligned(),
+            DynBitReader::Little(r) => r.byte_aligned(),
+        }
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        match self {
+            DynBitReader::Big(r) => r.byte_align(),
+            DynBitReader::Little(r) => r.byte_align(),
+        }
+    }
+}
+
+/// For reading bit values from a slice of bytes
+/// without going through the `io::Read` trait.
+///
+/// Because the whole input is always available,
+/// values are taken directly from the slice,
+/// reads which would pass the end of the slice fail
+/// without consuming anything,
+/// and the number of bits remaining is always known.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitSliceReader, BitRead};
+/// let data = [0b10110111, 0b01010101, 0xFF, 0x00];
+/// let mut reader = BitSliceReader::endian(&data, BigEndian);
+/// assert_eq!(reader.remaining_bits(), 32);
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+/// assert_eq!(reader.read::<u16>(13).unwrap(), 0b10111_01010101);
+/// assert_eq!(reader.remaining_bits(), 16);
+/// assert!(reader.read::<u32>(17).is_err());
+/// assert_eq!(reader.read::<u16>(16).unwrap(), 0xFF00);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct BitSliceReader<'a, E: Endianness> {
+    data: &'a [u8],
+    position: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<'a, E: Endianness> BitSliceReader<'a, E> {
+    /// Wraps a BitSliceReader around a slice of bytes
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Self {
+        BitSliceReader {
+            data,
+            position: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a BitSliceReader around a slice of bytes
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(data: &'a [u8], _endian: E) -> Self {
+        BitSliceReader::new(data)
+    }
+
+    /// Returns the number of bits read from the start of the slice
+    #[inline]
+    pub fn position_in_bits(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the number of bits which remain to be read
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitSliceReader, BitRead};
+    /// let data = [0b10110111, 0b01010101];
+    /// let mut reader = BitSliceReader::endian(&data, LittleEndian);
+    /// assert_eq!(reader.read::<u8>(3).unwrap(), 0b111);
+    /// assert_eq!(reader.remaining_bits(), 13);
+    /// assert_eq!(reader.read::<u16>(13).unwrap(), 0b01010101_10110);
+    /// assert_eq!(reader.remaining_bits(), 0);
+    /// ```
+    #[inline]
+    pub fn remaining_bits(&self) -> u64 {
+        self.data.len() as u64 * 8 - self.position
+    }
+
+    /// Returns the unread portion of the slice,
+    /// starting with any partially read byte.
+    #[inline]
+    pub fn remaining_slice(&self) -> &'a [u8] {
+        &self.data[(self.position / 8) as usize..]
+    }
+
+    /// Returns the next given number of bytes of the slice
+    /// and advances this reader past them, without copying them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader is not byte-aligned,
+    /// if bytes examined by `peek` have yet to be read,
+    /// or if fewer than the given number of bytes remain.
+    /// The reader is unchanged on error.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitSliceReader, BitRead};
+    /// let data = [0x02, 0x12, 0x34, 0x0F];
+    /// let mut reader = BitSliceReader::endian(&data, LittleEndian);
+    /// let len = reader.read::<u8>(8).unwrap();
+    /// assert_eq!(reader.read_bytes_borrowed(len.into()).unwrap(), [0x12, 0x34]);
+    /// assert_eq!(reader.read::<u8>(4).unwrap(), 0xF);
+    /// assert!(reader.read_bytes_borrowed(0).is_err());
+    /// ```
+    pub fn read_bytes_borrowed(&mut self, bytes: usize) -> io::Result<&'a [u8]> {
+        if self.offset() != 0 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "borrowed read not byte-aligned",
+            ))
+        } else {
+            self.reserve(bytes as u64 * 8)?;
+            let start = (self.position / 8) as usize;
+            self.position += bytes as u64 * 8;
+            Ok(&self.data[start..start + bytes])
+        }
+    }
+
+    #[inline]
+    fn reserve(&self, bits: u64) -> io::Result<()> {
+        if bits <= self.remaining_bits() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read past end of data",
+            ))
+        }
+    }
+
+    /// The number of bits already read from the current byte
+    #[inline]
+    fn offset(&self) -> u32 {
+        (self.position & 7) as u32
+    }
+
+    /// The index of the next byte which hasn't been read from
+    #[inline]
+    fn next_byte(&self) -> usize {
+        ((self.position + 7) >> 3) as usize
+    }
+
+    /// The unread bits of the current partially read byte, if any
+    #[inline]
+    fn partial(&self) -> BitQueue<E, u8> {
+        match self.offset() {
+            0 => BitQueue::new(),
+            offset => {
+                let mut queue = BitQueue::from_value(self.data[(self.position / 8) as usize], 8);
+                queue.drop(offset);
+                queue
+            }
+        }
+    }
+}
+
+impl<'a, E: Endianness> BitRead for BitSliceReader<'a, E> {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        self.reserve(1)?;
+        let mut queue = BitQueue::<E, u8>::from_value(self.data[(self.position / 8) as usize], 8);
+        if self.offset() > 0 {
+            queue.drop(self.offset());
+        }
+        self.position += 1;
+        Ok(queue.pop(1) == 1)
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ));
+        }
+        self.reserve(bits.into())?;
+
+        let start = (self.position / 8) as usize;
+        if self.offset() == 0 && bits == U::bits_size() {
+            // whole aligned values are read directly from the slice
+            self.position += u64::from(bits);
+            return E::read_numeric(&self.data[start..start + (bits / 8) as usize]);
+        }
+
+        let mut partial = self.partial();
+        let mut acc = BitQueue::<E, U>::new();
+        let mut bits = bits;
+        let mut next = start + usize::from(!partial.is_empty());
+        let from_partial = bits.min(partial.len());
+        if from_partial > 0 {
+            acc.push(from_partial, U::from_u8(partial.pop(from_partial)));
+            bits -= from_partial;
+        }
+        while bits >= 8 {
+            acc.push(8, U::from_u8(self.data[next]));
+            next += 1;
+            bits -= 8;
+        }
+        if bits > 0 {
+            let mut last = BitQueue::<E, u8>::from_value(self.data[next], 8);
+            acc.push(bits, U::from_u8(last.pop(bits)));
+        }
+        self.position += u64::from(acc.len());
+        Ok(acc.value())
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    #[inline]
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        self.reserve(bits)?;
+        self.position += bits;
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.reserve(buf.len() as u64 * 8)?;
+        if self.byte_aligned() {
+            let start = (self.position / 8) as usize;
+            buf.copy_from_slice(&self.data[start..start + buf.len()]);
+            self.position += buf.len() as u64 * 8;
+            Ok(())
+        } else {
+            buf.iter_mut()
+                .try_for_each(|b| self.read(8).map(|v| *b = v))
+        }
+    }
+
     #[inline]
-    pub fn position_in_bits(&mut self) -> io::Result<u64> {
-        let bytes = self.reader.stream_position()?;
-        Ok(bytes * 8 - (self.bitqueue.len() as u64))
+    fn byte_aligned(&self) -> bool {
+        self.offset() == 0
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        self.position = self.next_byte() as u64 * 8;
     }
 }
 
-impl<R: io::Read, E: Endianness> HuffmanRead<E> for BitReader<R, E> {
+impl<'a, E: Endianness> HuffmanRead<E> for BitSliceReader<'a, E> {
     /// # Example
     /// ```
-    /// use std::io::{Read, Cursor};
-    /// use bitstream_io::{BigEndian, BitReader, HuffmanRead};
+    /// use bitstream_io::{BigEndian, BitSliceReader, HuffmanRead};
     /// use bitstream_io::huffman::compile_read_tree;
     /// let tree = compile_read_tree(
     ///     vec![('a', vec![0]),
@@ -664,25 +4366,37 @@ impl<R: io::Read, E: Endianness> HuffmanRead<E> for BitReader<R, E> {
     ///          ('c', vec![1, 1, 0]),
     ///          ('d', vec![1, 1, 1])]).unwrap();
     /// let data = [0b10110111];
-    /// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    /// let mut reader = BitSliceReader::endian(&data, BigEndian);
     /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'b');
     /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'c');
     /// assert_eq!(reader.read_huffman(&tree).unwrap(), 'd');
+    /// assert!(reader.read_huffman(&tree).is_err());
     /// ```
     fn read_huffman<T>(&mut self, tree: &[ReadHuffmanTree<E, T>]) -> io::Result<T>
     where
         T: Clone,
     {
-        let mut result: &ReadHuffmanTree<E, T> = &tree[self.bitqueue.to_state()];
+        // the position is only updated once a whole symbol is read
+        let mut next = self.next_byte();
+        let mut result: &ReadHuffmanTree<E, T> = &tree[self.partial().to_state()];
         loop {
             match result {
-                ReadHuffmanTree::Done(ref value, ref queue_val, ref queue_bits, _) => {
-                    self.bitqueue.set(*queue_val, *queue_bits);
+                ReadHuffmanTree::Done(ref value, _, ref queue_bits, _) => {
+                    self.position = next as u64 * 8 - u64::from(*queue_bits);
                     return Ok(value.clone());
                 }
-                ReadHuffmanTree::Continue(ref tree) => {
-                    result = &tree[read_byte(&mut self.reader)? as usize];
-                }
+                ReadHuffmanTree::Continue(ref tree) => match self.data.get(next) {
+                    Some(byte) => {
+                        result = &tree[*byte as usize];
+                        next += 1;
+                    }
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "read past end of data",
+                        ))
+                    }
+                },
                 ReadHuffmanTree::InvalidState => {
                     panic!("invalid state");
                 }
@@ -691,6 +4405,131 @@ impl<R: io::Read, E: Endianness> HuffmanRead<E> for BitReader<R, E> {
     }
 }
 
+/// An `io::Read` over an iterator of bytes,
+/// for streams which are produced lazily
+/// by decompressors, generators or channel receivers.
+///
+/// The stream ends when the iterator does.
+/// Each read takes as many bytes as fit in the buffer,
+/// so an iterator which blocks while waiting for bytes
+/// may block a read which could have returned fewer.
+#[derive(Clone, Debug)]
+pub struct IterReader<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = u8>> IterReader<I> {
+    /// Wraps an IterReader around anything which produces bytes
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Read;
+    /// use bitstream_io::IterReader;
+    /// let mut reader = IterReader::new(vec![1, 2, 3]);
+    /// let mut buf = [0; 2];
+    /// assert_eq!(reader.read(&mut buf).unwrap(), 2);
+    /// assert_eq!(buf, [1, 2]);
+    /// assert_eq!(reader.read(&mut buf).unwrap(), 1);
+    /// assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    /// ```
+    #[inline]
+    pub fn new<T>(bytes: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = u8>,
+    {
+        IterReader {
+            iter: bytes.into_iter(),
+        }
+    }
+
+    /// Unwraps the iterator and disposes of IterReader
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I: Iterator<Item = u8>> io::Read for IterReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        for (slot, byte) in buf.iter_mut().zip(&mut self.iter) {
+            *slot = byte;
+            read += 1;
+        }
+        Ok(read)
+    }
+}
+
+/// For byte sources which know exactly how many bytes remain in them.
+///
+/// `BitReader::remaining_bits` uses this to tell parsers
+/// how much data is left, so that a length or count
+/// read from a header may be checked before anything is allocated for it.
+pub trait RemainingBytes {
+    /// Returns the number of bytes which remain to be read,
+    /// or `None` if that number isn't known exactly
+    fn remaining_bytes(&self) -> Option<u64>;
+}
+
+impl RemainingBytes for &[u8] {
+    #[inline]
+    fn remaining_bytes(&self) -> Option<u64> {
+        u64::try_from(self.len()).ok()
+    }
+}
+
+impl<T: AsRef<[u8]>> RemainingBytes for io::Cursor<T> {
+    #[inline]
+    fn remaining_bytes(&self) -> Option<u64> {
+        u64::try_from(self.get_ref().as_ref().len())
+            .ok()
+            .map(|len| len.saturating_sub(self.position()))
+    }
+}
+
+impl<R: RemainingBytes> RemainingBytes for io::Take<R> {
+    #[inline]
+    fn remaining_bytes(&self) -> Option<u64> {
+        self.get_ref()
+            .remaining_bytes()
+            .map(|remaining| remaining.min(self.limit()))
+    }
+}
+
+impl<I: ExactSizeIterator<Item = u8>> RemainingBytes for IterReader<I> {
+    #[inline]
+    fn remaining_bytes(&self) -> Option<u64> {
+        u64::try_from(self.iter.len()).ok()
+    }
+}
+
+impl<R: RemainingBytes + ?Sized> RemainingBytes for &mut R {
+    #[inline]
+    fn remaining_bytes(&self) -> Option<u64> {
+        (**self).remaining_bytes()
+    }
+}
+
+/// Reads the bits of a signed value other than its sign bit
+fn read_sign_rest<R, S>(reader: &mut R, bits: u32) -> io::Result<S>
+where
+    R: BitRead + ?Sized,
+    S: SignedNumeric,
+{
+    if bits < S::bits_size() {
+        reader.read(bits - 1)
+    } else {
+        // the remaining bits of a full-width value
+        // are read as a signed value one bit narrower
+        let narrowed = reader.read_signed::<S>(bits - 1)?;
+        Ok(if narrowed.is_negative() {
+            narrowed.as_unsigned(bits)
+        } else {
+            narrowed
+        })
+    }
+}
+
 #[inline]
 fn read_byte<R>(mut reader: R) -> io::Result<u8>
 where
@@ -718,21 +4557,20 @@ where
     Ok(())
 }
 
-fn skip_aligned<R>(mut reader: R, mut bytes: u32) -> io::Result<()>
+fn skip_aligned<R>(mut reader: R, bytes: u64) -> io::Result<()>
 where
     R: io::Read,
 {
-    use std::cmp::min;
-
-    /*skip up to 8 bytes at a time
+    /*skip bytes in large chunks by copying them to a sink
     (unlike with read_aligned, "bytes" may be larger than any native type)*/
-    let mut buf = [0; 8];
-    while bytes > 0 {
-        let to_read = min(8, bytes);
-        reader.read_exact(&mut buf[0..to_read as usize])?;
-        bytes -= to_read;
+    if bytes > 0 && io::copy(&mut io::Read::take(&mut reader, bytes), &mut io::sink())? < bytes {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "failed to skip requested bytes",
+        ))
+    } else {
+        Ok(())
     }
-    Ok(())
 }
 
 #[inline]
@@ -792,6 +4630,28 @@ where
 }
 
 /// A trait for anything that can read aligned values from an input stream
+///
+/// Parsers written against this trait accept
+/// a `ByteReader` of either endianness, a mutable reference to one,
+/// or the temporary `ByteReader` of a byte-aligned `BitReader`.
+///
+/// # Example
+/// ```
+/// use std::io::{Read, Cursor};
+/// use bitstream_io::{BigEndian, BitReader, BitRead, ByteRead, ByteReader, LittleEndian};
+/// fn read_header<R: ByteRead>(mut r: R) -> std::io::Result<(u16, i8)> {
+///     Ok((r.read()?, r.read()?))
+/// }
+/// let data = [0x34, 0x12, 0xFE];
+/// let mut reader = ByteReader::endian(Cursor::new(&data), LittleEndian);
+/// assert_eq!(read_header(&mut reader).unwrap(), (0x1234, -2));
+/// let data = [0b10100101, 0x12, 0x34, 0xFE];
+/// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+/// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1010);
+/// assert!(reader.bytereader().is_none());
+/// assert_eq!(reader.read::<u8>(4).unwrap(), 0b0101);
+/// assert_eq!(read_header(reader.bytereader().unwrap()).unwrap(), (0x1234, -2));
+/// ```
 pub trait ByteRead {
     /// Reads whole numeric value from stream
     ///
@@ -828,14 +4688,108 @@ pub trait ByteRead {
         }
         Ok(())
     }
+
+    /// Reads a whole byte and returns its high and low nibbles,
+    /// in that order.
+    /// The high nibble is always first, regardless of endianness.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, ByteReader, ByteRead};
+    /// let data = [0x12, 0x59];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_nibbles().unwrap(), (1, 2));
+    /// assert_eq!(reader.read_nibbles().unwrap(), (5, 9));
+    /// assert!(reader.read_nibbles().is_err());
+    /// ```
+    fn read_nibbles(&mut self) -> io::Result<(u8, u8)> {
+        let byte: u8 = self.read()?;
+        Ok((byte >> 4, byte & 0x0F))
+    }
+
+    /// Parses and returns a complex type
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's `from_reader` method.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, ByteReader, ByteRead, FromByteStream};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct ChunkHeader {
+    ///     id: [u8; 4],
+    ///     size: u32,
+    /// }
+    ///
+    /// impl FromByteStream for ChunkHeader {
+    ///     type Error = std::io::Error;
+    ///
+    ///     fn from_reader<R: ByteRead + ?Sized>(r: &mut R) -> std::io::Result<Self> {
+    ///         let mut id = [0; 4];
+    ///         r.read_bytes(&mut id)?;
+    ///         Ok(ChunkHeader { id, size: r.read()? })
+    ///     }
+    /// }
+    ///
+    /// let data = [b'R', b'I', b'F', b'F', 0x24, 0x08, 0x00, 0x00];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.parse::<ChunkHeader>().unwrap(),
+    ///            ChunkHeader { id: *b"RIFF", size: 0x824 });
+    /// ```
+    #[inline]
+    fn parse<F: FromByteStream>(&mut self) -> Result<F, F::Error> {
+        F::from_reader(self)
+    }
+}
+
+/// Implemented by complex types that can be parsed from a `ByteRead`
+/// with the `parse` method.
+pub trait FromByteStream {
+    /// Error generated during parsing, such as `io::Error`
+    type Error;
+
+    /// Parses the type from the given byte stream
+    fn from_reader<R: ByteRead + ?Sized>(r: &mut R) -> Result<Self, Self::Error>
+    where
+        Self: Sized;
 }
 
 /// For reading aligned bytes from a stream of bytes in a given endianness.
 ///
-/// This only reads aligned values and maintains no internal state.
+/// This only reads aligned values and maintains no internal state
+/// beyond a count of the bytes read.
 pub struct ByteReader<R: io::Read, E: Endianness> {
     phantom: PhantomData<E>,
     reader: R,
+    position: u64,
+}
+
+impl<R: io::Read + Clone, E: Endianness> Clone for ByteReader<R, E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ByteReader {
+            phantom: PhantomData,
+            reader: self.reader.clone(),
+            position: self.position,
+        }
+    }
+}
+
+impl<R: io::Read + fmt::Debug, E: Endianness> fmt::Debug for ByteReader<R, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ByteReader")
+            .field("reader", &self.reader)
+            .field("position", &self.position)
+            .finish()
+    }
 }
 
 impl<R: io::Read, E: Endianness> ByteReader<R, E> {
@@ -844,6 +4798,7 @@ impl<R: io::Read, E: Endianness> ByteReader<R, E> {
         ByteReader {
             phantom: PhantomData,
             reader,
+            position: 0,
         }
     }
 
@@ -853,6 +4808,7 @@ impl<R: io::Read, E: Endianness> ByteReader<R, E> {
         ByteReader {
             phantom: PhantomData,
             reader,
+            position: 0,
         }
     }
 
@@ -868,6 +4824,43 @@ impl<R: io::Read, E: Endianness> ByteReader<R, E> {
         &mut self.reader
     }
 
+    /// Returns the number of bytes read so far.
+    ///
+    /// This counts from where the reader was created,
+    /// even after seeking, so it is only the offset
+    /// from the start of the stream if the reader was created there.
+    /// Bytes read directly from the internal reader,
+    /// or through the temporary reader from `bitreader`, aren't counted.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, ByteReader, ByteRead};
+    /// let data = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.position(), 0);
+    /// assert_eq!(reader.read::<u16>().unwrap(), 0x0001);
+    /// assert_eq!(reader.position(), 2);
+    /// let mut buf = [0; 3];
+    /// reader.read_bytes(&mut buf).unwrap();
+    /// assert_eq!(reader.position(), 5);
+    /// assert!(reader.read::<u32>().is_err());
+    /// ```
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Provides temporary `BitReader` over the internal reader
+    /// which adds the bytes it reads to the position
+    #[inline]
+    fn counted_bitreader(&mut self) -> BitReader<CountingReader<'_, R>, E> {
+        BitReader::new(CountingReader {
+            reader: &mut self.reader,
+            count: &mut self.position,
+        })
+    }
+
     /// Converts `ByteReader` to `BitReader` in the same endianness.
     #[inline]
     pub fn into_bitreader(self) -> BitReader<R, E> {
@@ -883,16 +4876,239 @@ impl<R: io::Read, E: Endianness> ByteReader<R, E> {
     pub fn bitreader(&mut self) -> BitReader<&mut R, E> {
         BitReader::new(self.reader())
     }
+
+    /// Reads a floating point value from the stream
+    /// by way of its bit pattern, in the stream's endianness.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the float's size is not a whole number of bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, ByteReader};
+    /// let data = [0x3F, 0xC0, 0x00, 0x00];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), BigEndian);
+    /// assert_eq!(reader.read_float::<f32>().unwrap(), 1.5);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, ByteReader};
+    /// use bitstream_io::float::Extended;
+    /// let data = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0xAC, 0x0E, 0x40];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_float::<Extended>().unwrap().to_f64(), 44100.0);
+    /// ```
+    pub fn read_float<F>(&mut self) -> io::Result<F>
+    where
+        F: FloatNumeric,
+    {
+        if F::bits_size() % 8 == 0 {
+            self.counted_bitreader().read_float()
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "float size not a whole number of bytes",
+            ))
+        }
+    }
+
+    /// Reads a null-terminated string of at most `max_len` bytes,
+    /// not including the terminator, which is consumed but not returned.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if no terminator is found within `max_len` bytes,
+    /// or if the string is not valid UTF-8 under a strict policy.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{BigEndian, ByteReader, Utf8Policy};
+    /// let data = b"foo\0";
+    /// let mut reader = ByteReader::endian(Cursor::new(data), BigEndian);
+    /// assert_eq!(reader.read_cstr(16, Utf8Policy::Strict).unwrap(), "foo");
+    /// ```
+    #[inline]
+    pub fn read_cstr(&mut self, max_len: usize, policy: Utf8Policy) -> io::Result<String> {
+        self.counted_bitreader().read_cstr(max_len, policy)
+    }
+
+    /// Reads a string prefixed by its length in bytes,
+    /// which is read as an unsigned value of `len_bytes` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the length is longer than `max_len`,
+    /// in which case the string itself is not read,
+    /// or if the string is not valid UTF-8 under a strict policy.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor};
+    /// use bitstream_io::{LittleEndian, ByteReader, Utf8Policy};
+    /// let data = [0x03, 0x00, b'f', b'o', b'o'];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.read_prefixed_str(2, 16, Utf8Policy::Strict).unwrap(), "foo");
+    /// ```
+    pub fn read_prefixed_str(
+        &mut self,
+        len_bytes: u32,
+        max_len: usize,
+        policy: Utf8Policy,
+    ) -> io::Result<String> {
+        self.counted_bitreader()
+            .read_prefixed_str(len_bytes.saturating_mul(8), max_len, policy)
+    }
+}
+
+impl<R: io::Read + io::Seek, E: Endianness> ByteReader<R, E> {
+    /// Seeks the internal reader to the given position
+    /// and returns the new offset from the start of the stream,
+    /// moving the reader's `position` along with it.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput`,
+    /// without moving the reader, when seeking to before
+    /// where the reader was created.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Read, Cursor, SeekFrom};
+    /// use bitstream_io::{LittleEndian, ByteReader, ByteRead};
+    /// let data = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05];
+    /// let mut reader = ByteReader::endian(Cursor::new(&data), LittleEndian);
+    /// assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 4);
+    /// assert_eq!(reader.read::<u16>().unwrap(), 0x0504);
+    /// assert_eq!(reader.position(), 6);
+    /// assert_eq!(reader.seek(SeekFrom::Current(-5)).unwrap(), 1);
+    /// assert_eq!(reader.read::<u8>().unwrap(), 0x01);
+    /// assert_eq!(reader.position(), 2);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Read, Cursor, SeekFrom};
+    /// use bitstream_io::{BigEndian, ByteReader, ByteRead};
+    /// let mut cursor = Cursor::new(b"head\x00\x01");
+    /// cursor.set_position(4);
+    /// let mut reader = ByteReader::endian(cursor, BigEndian);
+    /// assert_eq!(reader.read::<u16>().unwrap(), 1);
+    /// assert_eq!(reader.position(), 2);
+    /// assert_eq!(reader.seek(SeekFrom::Current(-1)).unwrap(), 5);
+    /// assert_eq!(reader.position(), 1);
+    /// assert!(reader.seek(SeekFrom::Start(3)).is_err());
+    /// assert_eq!(reader.read::<u8>().unwrap(), 1);
+    /// assert_eq!(reader.position(), 2);
+    /// ```
+    pub fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let current = self.reader.stream_position()?;
+        let start = current.saturating_sub(self.position);
+        let offset = self.reader.seek(pos)?;
+        match offset.checked_sub(start) {
+            Some(position) => {
+                self.position = position;
+                Ok(offset)
+            }
+            None => {
+                self.reader.seek(io::SeekFrom::Start(current))?;
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "seek to before where reader was created",
+                ))
+            }
+        }
+    }
+}
+
+impl<'a, E: Endianness> ByteReader<&'a [u8], E> {
+    /// Returns the next given number of bytes of a byte slice
+    /// and advances this reader past them, without copying them.
+    ///
+    /// The bytes borrow from the slice rather than the reader,
+    /// so parsers may keep views of payloads such as tag values
+    /// or embedded pictures without allocating a buffer for each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than the given number of bytes remain.
+    /// The reader is unchanged on error.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, ByteReader, ByteRead};
+    /// let data = [0x04, 0x00, b'T', b'I', b'T', b'2', 0x01];
+    /// let title = {
+    ///     let mut reader = ByteReader::endian(&data[..], LittleEndian);
+    ///     let len = reader.read::<u16>().unwrap();
+    ///     let title = reader.read_bytes_borrowed(len.into()).unwrap();
+    ///     assert_eq!(reader.position(), 6);
+    ///     assert!(reader.read_bytes_borrowed(2).is_err());
+    ///     assert_eq!(reader.read::<u8>().unwrap(), 0x01);
+    ///     title
+    /// };
+    /// assert_eq!(title, b"TIT2");
+    /// ```
+    pub fn read_bytes_borrowed(&mut self, bytes: usize) -> io::Result<&'a [u8]> {
+        if bytes > self.reader.len() {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read past end of data",
+            ))
+        } else {
+            let (read, rest) = self.reader.split_at(bytes);
+            self.reader = rest;
+            self.position += bytes as u64;
+            Ok(read)
+        }
+    }
+}
+
+/// A reader which counts the bytes read through it
+struct CountingReader<'a, R> {
+    reader: &'a mut R,
+    count: &'a mut u64,
+}
+
+impl<'a, R: io::Read> io::Read for CountingReader<'a, R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        *self.count += read as u64;
+        Ok(read)
+    }
 }
 
 impl<R: io::Read, E: Endianness> ByteRead for ByteReader<R, E> {
     #[inline]
     fn read<N: Numeric>(&mut self) -> Result<N, io::Error> {
-        E::read_numeric(&mut self.reader)
+        let value = E::read_numeric(&mut self.reader)?;
+        self.position += u64::from(N::bits_size() / 8);
+        Ok(value)
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.reader.read_exact(buf)?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+}
+
+impl<R: ByteRead + ?Sized> ByteRead for &mut R {
+    #[inline]
+    fn read<N: Numeric>(&mut self) -> Result<N, io::Error> {
+        (**self).read()
     }
 
     #[inline]
     fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
-        self.reader.read_exact(buf)
+        (**self).read_bytes(buf)
     }
 }
@@ -0,0 +1,281 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Runtime descriptions of records made up of named fields.
+//!
+//! A `Schema` lists a record's fields in stream order,
+//! each with a name and a `FieldKind`.
+//! Because a schema is built at runtime,
+//! it can come from a configuration file
+//! rather than a compiled struct,
+//! which suits tools that handle many similar headers.
+//! `BitRead::read_record` reads a whole `Record`
+//! of `FieldValue`s and `BitWrite::write_record` writes one.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::schema::{FieldKind, FieldValue, Schema};
+//!
+//! let schema: Schema = vec![
+//!     ("version", FieldKind::Unsigned(3)),
+//!     ("flag", FieldKind::Bit),
+//!     ("offset", FieldKind::Signed(4)),
+//!     ("tag", FieldKind::Bytes(2)),
+//! ]
+//! .into_iter()
+//! .collect();
+//!
+//! let data = [0b101_1_1110, b'o', b'k'];
+//! let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+//! let record = reader.read_record(&schema).unwrap();
+//! assert_eq!(record.get("version"), Some(&FieldValue::Unsigned(5)));
+//! assert_eq!(record.get("flag"), Some(&FieldValue::Bit(true)));
+//! assert_eq!(record.get("offset"), Some(&FieldValue::Signed(-2)));
+//! assert_eq!(record.get("tag"), Some(&FieldValue::Bytes(b"ok".to_vec())));
+//!
+//! let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+//! writer.write_record(&schema, record.values()).unwrap();
+//! assert_eq!(writer.into_writer(), data);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use std::convert::TryFrom;
+use std::io;
+use std::iter::FromIterator;
+
+/// The kind of a single field in a schema
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FieldKind {
+    /// A single bit, as a `FieldValue::Bit`
+    Bit,
+    /// An unsigned value of up to 64 bits, as a `FieldValue::Unsigned`
+    Unsigned(u32),
+    /// A twos-complement signed value of up to 64 bits,
+    /// as a `FieldValue::Signed`
+    Signed(u32),
+    /// The given number of whole bytes, as a `FieldValue::Bytes`
+    Bytes(usize),
+    /// A unary value terminated by a 0 bit, as a `FieldValue::Unsigned`
+    Unary0,
+    /// A unary value terminated by a 1 bit, as a `FieldValue::Unsigned`
+    Unary1,
+    /// A binary-coded decimal value of the given number of digits,
+    /// most significant digit first, as a `FieldValue::Unsigned`
+    Bcd(u32),
+}
+
+impl FieldKind {
+    /// Returns true if the value is of this kind
+    pub fn matches(&self, value: &FieldValue) -> bool {
+        match (self, value) {
+            (FieldKind::Bit, FieldValue::Bit(_))
+            | (FieldKind::Unsigned(_), FieldValue::Unsigned(_))
+            | (FieldKind::Signed(_), FieldValue::Signed(_))
+            | (FieldKind::Unary0, FieldValue::Unsigned(_))
+            | (FieldKind::Unary1, FieldValue::Unsigned(_))
+            | (FieldKind::Bcd(_), FieldValue::Unsigned(_)) => true,
+            (FieldKind::Bytes(len), FieldValue::Bytes(bytes)) => bytes.len() == *len,
+            _ => false,
+        }
+    }
+}
+
+/// The value of a single field in a record
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FieldValue {
+    /// A single bit
+    Bit(bool),
+    /// An unsigned value
+    Unsigned(u64),
+    /// A signed value
+    Signed(i64),
+    /// A run of bytes
+    Bytes(Vec<u8>),
+}
+
+/// A list of named fields, in stream order
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Schema {
+    fields: Vec<(String, FieldKind)>,
+}
+
+impl Schema {
+    /// Creates an empty schema
+    #[inline]
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Appends a named field to the end of the schema
+    #[inline]
+    pub fn push<S: Into<String>>(&mut self, name: S, kind: FieldKind) {
+        self.fields.push((name.into(), kind))
+    }
+
+    /// Returns the schema's names and fields, in stream order
+    #[inline]
+    pub fn fields(&self) -> &[(String, FieldKind)] {
+        &self.fields
+    }
+
+    /// Returns the number of fields in the schema
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns true if the schema has no fields
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub(crate) fn read<R: BitRead + ?Sized>(&self, reader: &mut R) -> io::Result<Record> {
+        let mut values = Vec::with_capacity(self.fields.len());
+        for (_, kind) in self.fields.iter() {
+            values.push(match *kind {
+                FieldKind::Bit => FieldValue::Bit(reader.read_bit()?),
+                FieldKind::Unsigned(bits) => FieldValue::Unsigned(reader.read(bits)?),
+                FieldKind::Signed(bits) => FieldValue::Signed(reader.read_signed(bits)?),
+                FieldKind::Bytes(len) => {
+                    FieldValue::Bytes(reader.read_bits_to_vec((len as u64) * 8)?)
+                }
+                FieldKind::Unary0 => FieldValue::Unsigned(u64::from(reader.read_unary0()?)),
+                FieldKind::Unary1 => FieldValue::Unsigned(u64::from(reader.read_unary1()?)),
+                FieldKind::Bcd(digits) => FieldValue::Unsigned(reader.read_bcd(digits)?),
+            });
+        }
+        Ok(Record {
+            names: self.fields.iter().map(|(name, _)| name.clone()).collect(),
+            values,
+        })
+    }
+
+    pub(crate) fn write<W: BitWrite + ?Sized>(
+        &self,
+        writer: &mut W,
+        values: &[FieldValue],
+    ) -> io::Result<()> {
+        if values.len() != self.fields.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "record length doesn't match schema",
+            ));
+        }
+        // check every value up front so that a mismatched record
+        // isn't partially written
+        if !self
+            .fields
+            .iter()
+            .zip(values)
+            .all(|((_, kind), value)| kind.matches(value))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "field value doesn't match schema",
+            ));
+        }
+        for ((_, kind), value) in self.fields.iter().zip(values) {
+            match (*kind, value) {
+                (FieldKind::Bit, FieldValue::Bit(bit)) => writer.write_bit(*bit)?,
+                (FieldKind::Unsigned(bits), FieldValue::Unsigned(value)) => {
+                    writer.write(bits, *value)?
+                }
+                (FieldKind::Signed(bits), FieldValue::Signed(value)) => {
+                    writer.write_signed(bits, *value)?
+                }
+                (FieldKind::Bytes(_), FieldValue::Bytes(bytes)) => writer.write_bytes(bytes)?,
+                (FieldKind::Unary0, FieldValue::Unsigned(value)) => {
+                    writer.write_unary0(unary_value(*value)?)?
+                }
+                (FieldKind::Unary1, FieldValue::Unsigned(value)) => {
+                    writer.write_unary1(unary_value(*value)?)?
+                }
+                (FieldKind::Bcd(digits), FieldValue::Unsigned(value)) => {
+                    writer.write_bcd(digits, *value)?
+                }
+                _ => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, FieldKind)> for Schema {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (S, FieldKind)>>(iter: I) -> Self {
+        Schema {
+            fields: iter
+                .into_iter()
+                .map(|(name, kind)| (name.into(), kind))
+                .collect(),
+        }
+    }
+}
+
+impl<S: Into<String>> Extend<(S, FieldKind)> for Schema {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (S, FieldKind)>>(&mut self, iter: I) {
+        self.fields
+            .extend(iter.into_iter().map(|(name, kind)| (name.into(), kind)))
+    }
+}
+
+#[inline]
+fn unary_value(value: u64) -> io::Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "excessive value for unary field",
+        )
+    })
+}
+
+/// The values of a record read using a `Schema`, in stream order
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Record {
+    names: Vec<String>,
+    values: Vec<FieldValue>,
+}
+
+impl Record {
+    /// Returns the value of the first field with the given name,
+    /// if any
+    pub fn get(&self, name: &str) -> Option<&FieldValue> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|i| &self.values[i])
+    }
+
+    /// Returns the record's values, in stream order
+    #[inline]
+    pub fn values(&self) -> &[FieldValue] {
+        &self.values
+    }
+
+    /// Iterates over the record's names and values, in stream order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldValue)> {
+        self.names
+            .iter()
+            .map(|name| name.as_str())
+            .zip(self.values.iter())
+    }
+
+    /// Consumes the record and returns its values, in stream order
+    #[inline]
+    pub fn into_values(self) -> Vec<FieldValue> {
+        self.values
+    }
+}
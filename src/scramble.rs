@@ -0,0 +1,399 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Wrappers which scramble and descramble a bitstream
+//! with the pseudo-random sequence of a linear-feedback shift register.
+//!
+//! Broadcast and radio formats such as DVB and IEEE 802.11
+//! "whiten" their payloads by combining every bit with the output
+//! of an LFSR, so that long runs of identical bits don't reach the channel.
+//! `ScramblingWriter` exclusive-ors that sequence into every bit
+//! written to it, and `DescramblingReader` removes it again
+//! from every bit read, so long as both start from the same seed.
+//!
+//! ## Example
+//!
+//! Scrambling a frame with the IEEE 802.11 scrambler.
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::scramble::{DescramblingReader, ScramblingWriter};
+//!
+//! // x^7 + x^4 + 1, starting from all 1s
+//! let (polynomial, seed) = (1 << 7 | 1 << 4, 0b111_1111);
+//!
+//! let mut writer = ScramblingWriter::endian(
+//!     BitWriter::endian(Vec::new(), BigEndian),
+//!     BigEndian,
+//!     polynomial,
+//!     seed,
+//! );
+//! writer.write_bytes(&[0; 4]).unwrap();
+//! let data = writer.into_writer().into_writer();
+//! // scrambling 0 bits yields the sequence itself
+//! assert_eq!(data, [0b0000_1110, 0b1111_0010, 0b1100_1001, 0b0000_0010]);
+//!
+//! let mut reader = DescramblingReader::endian(
+//!     BitReader::endian(data.as_slice(), BigEndian),
+//!     BigEndian,
+//!     polynomial,
+//!     seed,
+//! );
+//! assert_eq!(reader.read::<u32>(32).unwrap(), 0);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{BitQueue, Endianness, Numeric, SignedNumeric};
+use std::io;
+use std::marker::PhantomData;
+
+/// A Fibonacci linear-feedback shift register
+/// generating a pseudo-random sequence of bits.
+///
+/// The polynomial is given as a mask in which bit `k` is set
+/// for each `x^k` term, and its highest term gives the register's size.
+/// The constant term is implied and bit 0 is ignored.
+/// Bit `k - 1` of the register holds stage `k`.
+///
+/// Each step, the stages named by the polynomial's terms
+/// are combined with exclusive-or to give the next bit of the sequence,
+/// which is shifted into stage 1 as the others move up a stage.
+///
+/// # Example
+/// ```
+/// use bitstream_io::scramble::Lfsr;
+/// // the DVB energy dispersal sequence, 1 + x^14 + x^15
+/// let mut lfsr = Lfsr::new(1 << 15 | 1 << 14, 0b000_0000_1010_1001);
+/// let bits: Vec<u8> = (0..16).map(|_| lfsr.next_bit() as u8).collect();
+/// assert_eq!(bits, [0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 0]);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Lfsr {
+    taps: u64,
+    mask: u64,
+    seed: u64,
+    state: u64,
+}
+
+impl Lfsr {
+    /// Builds a register from its polynomial and starting state
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial has no terms above `x^0`
+    /// or if the seed doesn't fit in the register.
+    pub fn new(polynomial: u64, seed: u64) -> Self {
+        let polynomial = polynomial & !1;
+        assert!(polynomial != 0, "polynomial must have a nonconstant term");
+        let mask = (1 << (63 - polynomial.leading_zeros())) - 1;
+        assert!(seed & !mask == 0, "seed too large for register");
+        Lfsr {
+            taps: polynomial >> 1,
+            mask,
+            seed,
+            state: seed,
+        }
+    }
+
+    /// Returns the number of stages in the register
+    #[inline]
+    pub fn len(&self) -> u32 {
+        self.mask.count_ones()
+    }
+
+    /// Always returns false, since a register has at least one stage
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the register's current state
+    #[inline]
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Returns the register to its starting state
+    #[inline]
+    pub fn reset(&mut self) {
+        self.state = self.seed;
+    }
+
+    /// Steps the register and returns the next bit of the sequence
+    #[inline]
+    pub fn next_bit(&mut self) -> bool {
+        let bit = (self.state & self.taps).count_ones() % 2 == 1;
+        self.state = ((self.state << 1) | u64::from(bit)) & self.mask;
+        bit
+    }
+
+    /// Returns the next `bits` bits of the sequence
+    /// packed into a value in the given endianness,
+    /// such that each bit of the sequence lines up with
+    /// the bit of a value read or written in that position
+    fn next_value<E: Endianness, U: Numeric>(&mut self, bits: u32) -> U {
+        let mut queue = BitQueue::<E, U>::new();
+        for _ in 0..bits {
+            queue.push(1, U::from_u8(self.next_bit().into()));
+        }
+        queue.value()
+    }
+}
+
+/// A bitstream wrapper which scrambles every bit written to it
+/// with the sequence of an LFSR.
+///
+/// Bits written as padding by `byte_align` are scrambled too,
+/// so the padding counts as part of the scrambled stream.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+/// use bitstream_io::scramble::ScramblingWriter;
+/// let mut writer = ScramblingWriter::endian(
+///     BitWriter::endian(Vec::new(), LittleEndian),
+///     LittleEndian,
+///     1 << 7 | 1 << 4,
+///     0b111_1111,
+/// );
+/// // the sequence begins 0000 1110,
+/// // which little-endian values take least-significant bit first
+/// writer.write(4, 0b1111u8).unwrap();
+/// writer.write(4, 0b0000u8).unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b0111_1111]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScramblingWriter<W: BitWrite, E: Endianness> {
+    writer: W,
+    lfsr: Lfsr,
+    phantom: PhantomData<E>,
+}
+
+impl<W: BitWrite, E: Endianness> ScramblingWriter<W, E> {
+    /// Wraps a ScramblingWriter around something that implements `BitWrite`
+    /// which scrambles bits with an LFSR of the given polynomial and seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial or seed is invalid, as for `Lfsr::new`.
+    #[inline]
+    pub fn new(writer: W, polynomial: u64, seed: u64) -> Self {
+        ScramblingWriter {
+            writer,
+            lfsr: Lfsr::new(polynomial, seed),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a ScramblingWriter around something that implements `BitWrite`
+    /// with the given endianness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial or seed is invalid, as for `Lfsr::new`.
+    #[inline]
+    pub fn endian(writer: W, _endian: E, polynomial: u64, seed: u64) -> Self {
+        ScramblingWriter::new(writer, polynomial, seed)
+    }
+
+    /// Returns the register generating the scrambling sequence
+    #[inline]
+    pub fn lfsr(&self) -> &Lfsr {
+        &self.lfsr
+    }
+
+    /// Returns the register to its seed,
+    /// as formats which scramble each frame separately require
+    #[inline]
+    pub fn reset(&mut self) {
+        self.lfsr.reset()
+    }
+
+    /// Provides mutable reference to internal writer
+    ///
+    /// Bits written to it directly are not scrambled.
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of ScramblingWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: BitWrite, E: Endianness> BitWrite for ScramblingWriter<W, E> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        let scrambled = bit ^ self.lfsr.next_bit();
+        self.writer.write_bit(scrambled)
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            let scrambled = value ^ self.lfsr.next_value::<E, U>(bits);
+            self.writer.write(bits, scrambled)
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+}
+
+/// A bitstream wrapper which descrambles every bit read from it
+/// with the sequence of an LFSR.
+///
+/// The register only advances for bits successfully read,
+/// and bits discarded by `byte_align` are read through it
+/// so that it stays in step with the stream.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{LittleEndian, BitReader, BitRead};
+/// use bitstream_io::scramble::DescramblingReader;
+/// let data = [0b0111_1111];
+/// let mut reader = DescramblingReader::endian(
+///     BitReader::endian(&data[..], LittleEndian),
+///     LittleEndian,
+///     1 << 7 | 1 << 4,
+///     0b111_1111,
+/// );
+/// assert_eq!(reader.read::<u8>(4).unwrap(), 0b1111);
+/// assert_eq!(reader.read::<u8>(4).unwrap(), 0b0000);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DescramblingReader<R: BitRead, E: Endianness> {
+    reader: R,
+    lfsr: Lfsr,
+    phantom: PhantomData<E>,
+}
+
+impl<R: BitRead, E: Endianness> DescramblingReader<R, E> {
+    /// Wraps a DescramblingReader around something that implements `BitRead`
+    /// which descrambles bits with an LFSR of the given polynomial and seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial or seed is invalid, as for `Lfsr::new`.
+    #[inline]
+    pub fn new(reader: R, polynomial: u64, seed: u64) -> Self {
+        DescramblingReader {
+            reader,
+            lfsr: Lfsr::new(polynomial, seed),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a DescramblingReader around something that implements `BitRead`
+    /// with the given endianness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial or seed is invalid, as for `Lfsr::new`.
+    #[inline]
+    pub fn endian(reader: R, _endian: E, polynomial: u64, seed: u64) -> Self {
+        DescramblingReader::new(reader, polynomial, seed)
+    }
+
+    /// Returns the register generating the descrambling sequence
+    #[inline]
+    pub fn lfsr(&self) -> &Lfsr {
+        &self.lfsr
+    }
+
+    /// Returns the register to its seed,
+    /// as formats which scramble each frame separately require
+    #[inline]
+    pub fn reset(&mut self) {
+        self.lfsr.reset()
+    }
+
+    /// Provides mutable reference to internal reader
+    ///
+    /// Bits read from it directly are not descrambled.
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of DescramblingReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BitRead, E: Endianness> BitRead for DescramblingReader<R, E> {
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<bool> {
+        let bit = self.reader.read_bit()?;
+        Ok(bit ^ self.lfsr.next_bit())
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        let value = self.reader.read::<U>(bits)?;
+        Ok(value ^ self.lfsr.next_value::<E, U>(bits))
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        self.reader.skip(bits)?;
+        for _ in 0..bits {
+            self.lfsr.next_bit();
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    fn byte_align(&mut self) {
+        while !self.reader.byte_aligned() && self.read_bit().is_ok() {}
+        self.reader.byte_align()
+    }
+}
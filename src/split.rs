@@ -0,0 +1,195 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Descriptors for values which are split across
+//! several fields in a stream, with fixed marker bits between them.
+//!
+//! Formats sometimes break a wide value into pieces
+//! so that no run of its bits can be mistaken for a start code,
+//! such as the 33-bit timestamps of MPEG program streams,
+//! which are stored as 3, 15 and 15 bit pieces
+//! each followed by a 1 marker bit.
+//! A `SplitField` declares that layout once,
+//! after which `BitWrite::write_split` and `BitRead::read_split`
+//! write and read the whole value, checking the markers when read.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::io::{Read, Cursor};
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::split::{Segment, SplitField};
+//!
+//! const PTS: SplitField = SplitField::new(&[
+//!     Segment::Marker { bits: 4, value: 0b0010 },
+//!     Segment::Value(3),
+//!     Segment::Marker { bits: 1, value: 1 },
+//!     Segment::Value(15),
+//!     Segment::Marker { bits: 1, value: 1 },
+//!     Segment::Value(15),
+//!     Segment::Marker { bits: 1, value: 1 },
+//! ]);
+//! assert_eq!(PTS.value_bits(), 33);
+//! assert_eq!(PTS.total_bits(), 40);
+//!
+//! let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+//! writer.write_split(&PTS, 0x1_2345_6789).unwrap();
+//! let data = writer.into_writer();
+//! assert_eq!(data, [0x29, 0x8D, 0x15, 0xCF, 0x13]);
+//!
+//! let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+//! assert_eq!(reader.read_split(&PTS).unwrap(), 0x1_2345_6789);
+//!
+//! // a cleared marker bit is an error
+//! let mut reader = BitReader::endian(Cursor::new([0x29, 0x8D, 0x15, 0xCF, 0x12]), BigEndian);
+//! assert!(reader.read_split(&PTS).is_err());
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use std::io;
+
+/// One piece of a split field
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Segment {
+    /// The given number of the value's bits,
+    /// taken from its most significant remaining bits
+    Value(u32),
+    /// Fixed marker bits which must have the given value
+    Marker {
+        /// the number of marker bits
+        bits: u32,
+        /// the marker's value
+        value: u64,
+    },
+}
+
+/// A value split across several segments in a stream
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SplitField<'s> {
+    segments: &'s [Segment],
+    value_bits: u32,
+    total_bits: u32,
+}
+
+impl<'s> SplitField<'s> {
+    /// Builds a split field from its segments, in stream order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value segments total more than 64 bits,
+    /// if any marker is wider than 64 bits,
+    /// or if any marker's value doesn't fit in its bits.
+    pub const fn new(segments: &'s [Segment]) -> Self {
+        let mut value_bits = 0;
+        let mut total_bits = 0;
+        let mut i = 0;
+        while i < segments.len() {
+            match segments[i] {
+                Segment::Value(bits) => {
+                    value_bits += bits;
+                    total_bits += bits;
+                }
+                Segment::Marker { bits, value } => {
+                    assert!(bits <= 64, "excessive bits for marker");
+                    assert!(
+                        bits == 64 || value < (1 << bits),
+                        "excessive value for marker bits"
+                    );
+                    total_bits += bits;
+                }
+            }
+            i += 1;
+        }
+        assert!(value_bits <= 64, "excessive bits for split value");
+        SplitField {
+            segments,
+            value_bits,
+            total_bits,
+        }
+    }
+
+    /// Returns the field's segments, in stream order
+    #[inline]
+    pub fn segments(&self) -> &'s [Segment] {
+        self.segments
+    }
+
+    /// Returns the number of bits in the field's value
+    #[inline]
+    pub fn value_bits(&self) -> u32 {
+        self.value_bits
+    }
+
+    /// Returns the number of bits the field occupies in the stream,
+    /// including markers
+    #[inline]
+    pub fn total_bits(&self) -> u32 {
+        self.total_bits
+    }
+
+    pub(crate) fn read<R: BitRead + ?Sized>(&self, reader: &mut R) -> io::Result<u64> {
+        let mut value = 0;
+        for segment in self.segments {
+            match *segment {
+                Segment::Value(0) => {}
+                Segment::Value(bits) => {
+                    let piece: u64 = reader.read(bits)?;
+                    value = if bits == 64 {
+                        piece
+                    } else {
+                        (value << bits) | piece
+                    };
+                }
+                Segment::Marker {
+                    bits,
+                    value: marker,
+                } => {
+                    if reader.read::<u64>(bits)? != marker {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "marker bits mismatch in split field",
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn write<W: BitWrite + ?Sized>(&self, writer: &mut W, value: u64) -> io::Result<()> {
+        if self.value_bits < 64 && value >= (1 << self.value_bits) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for split field",
+            ));
+        }
+        let mut remaining = self.value_bits;
+        for segment in self.segments {
+            match *segment {
+                Segment::Value(0) => {}
+                Segment::Value(bits) => {
+                    remaining -= bits;
+                    let piece = if bits == 64 {
+                        value
+                    } else {
+                        (value >> remaining) & ((1 << bits) - 1)
+                    };
+                    writer.write(bits, piece)?;
+                }
+                Segment::Marker {
+                    bits,
+                    value: marker,
+                } => writer.write(bits, marker)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,435 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Wrappers which transparently insert and remove
+//! stuffed bytes or bits so that payload data
+//! can never be mistaken for a stream's framing.
+//!
+//! `EmulationPreventionWriter` and `EmulationPreventionReader`
+//! wrap a byte stream and apply H.264/HEVC-style emulation prevention,
+//! where an `0x03` byte follows any two `0x00` bytes
+//! which would otherwise be followed by a byte of `0x03` or less.
+//! Because bitstream readers and writers only read or write whole bytes,
+//! wrapping one around these streams converts between raw byte
+//! sequence payloads and their escaped form.
+//!
+//! `BitStuffingWriter` and `BitStuffingReader` wrap a bitstream
+//! and apply HDLC-style bit stuffing, where a 0 bit follows
+//! every run of five 1 bits.
+//!
+//! ## Example
+//!
+//! Writing a payload which contains a start code prefix.
+//!
+//! ```
+//! use std::io::Read;
+//! use bitstream_io::{BigEndian, BitReader, BitRead, BitWriter, BitWrite};
+//! use bitstream_io::stuffing::{EmulationPreventionReader, EmulationPreventionWriter};
+//!
+//! let mut writer = BitWriter::endian(EmulationPreventionWriter::new(Vec::new()), BigEndian);
+//! writer.write(24, 0x000001).unwrap();
+//! writer.write(16, 0x0000).unwrap();
+//! let escaped = writer.into_writer().into_writer();
+//! assert_eq!(escaped, [0x00, 0x00, 0x03, 0x01, 0x00, 0x00]);
+//!
+//! let mut reader = BitReader::endian(EmulationPreventionReader::new(escaped.as_slice()), BigEndian);
+//! assert_eq!(reader.read::<u32>(24).unwrap(), 0x000001);
+//! assert_eq!(reader.read::<u16>(16).unwrap(), 0x0000);
+//! ```
+
+#![warn(missing_docs)]
+
+use super::read::BitRead;
+use super::write::BitWrite;
+use super::{BitQueue, Endianness, Numeric, SignedNumeric};
+use std::io;
+use std::marker::PhantomData;
+
+const EMULATION_PREVENTION_BYTE: u8 = 0x03;
+
+/// The number of 1 bits after which a 0 bit is stuffed
+const STUFFING_RUN: u32 = 5;
+
+/// A stream wrapper which inserts emulation prevention bytes
+/// into everything written to it.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::stuffing::EmulationPreventionWriter;
+/// let mut writer = EmulationPreventionWriter::new(Vec::new());
+/// writer.write_all(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x04]).unwrap();
+/// assert_eq!(writer.into_writer(), [0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0x04]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EmulationPreventionWriter<W: io::Write> {
+    writer: W,
+    zeros: u32,
+}
+
+impl<W: io::Write> EmulationPreventionWriter<W> {
+    /// Wraps an EmulationPreventionWriter around something that implements `Write`
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        EmulationPreventionWriter { writer, zeros: 0 }
+    }
+
+    /// Provides mutable reference to internal writer
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of EmulationPreventionWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: io::Write> io::Write for EmulationPreventionWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut escaped = Vec::with_capacity(buf.len() + buf.len() / 2);
+        let mut zeros = self.zeros;
+        for byte in buf.iter() {
+            if zeros >= 2 && *byte <= EMULATION_PREVENTION_BYTE {
+                escaped.push(EMULATION_PREVENTION_BYTE);
+                zeros = 0;
+            }
+            escaped.push(*byte);
+            zeros = if *byte == 0 { zeros + 1 } else { 0 };
+        }
+        self.writer.write_all(&escaped)?;
+        self.zeros = zeros;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// A stream wrapper which removes emulation prevention bytes
+/// from everything read from it.
+///
+/// # Example
+/// ```
+/// use std::io::Read;
+/// use bitstream_io::stuffing::EmulationPreventionReader;
+/// let data = [0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0x04];
+/// let mut reader = EmulationPreventionReader::new(&data[..]);
+/// let mut payload = Vec::new();
+/// reader.read_to_end(&mut payload).unwrap();
+/// assert_eq!(payload, [0x00, 0x00, 0x00, 0x00, 0x00, 0x04]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EmulationPreventionReader<R: io::Read> {
+    reader: R,
+    zeros: u32,
+}
+
+impl<R: io::Read> EmulationPreventionReader<R> {
+    /// Wraps an EmulationPreventionReader around something that implements `Read`
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        EmulationPreventionReader { reader, zeros: 0 }
+    }
+
+    /// Provides mutable reference to internal reader
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of EmulationPreventionReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: io::Read> io::Read for EmulationPreventionReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let read = self.reader.read(buf)?;
+            if read == 0 {
+                return Ok(0);
+            }
+
+            // escaped bytes are removed in place,
+            // so a buffer of nothing but escapes needs another read
+            let mut kept = 0;
+            for i in 0..read {
+                let byte = buf[i];
+                if self.zeros >= 2 && byte == EMULATION_PREVENTION_BYTE {
+                    self.zeros = 0;
+                } else {
+                    self.zeros = if byte == 0 { self.zeros + 1 } else { 0 };
+                    buf[kept] = byte;
+                    kept += 1;
+                }
+            }
+            if kept > 0 {
+                return Ok(kept);
+            }
+        }
+    }
+}
+
+/// A bitstream wrapper which inserts a 0 bit after
+/// every five consecutive 1 bits written to it.
+///
+/// Because stuffed bits change how many bits reach the
+/// wrapped writer, its alignment is what's reported
+/// by `byte_aligned` and padded by `byte_align`.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+/// use bitstream_io::stuffing::BitStuffingWriter;
+/// let mut writer = BitStuffingWriter::endian(BitWriter::endian(Vec::new(), BigEndian), BigEndian);
+/// writer.write(8, 0b01111110).unwrap();
+/// writer.write(7, 0b1111111).unwrap();
+/// writer.byte_align().unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b01111101, 0b01111101, 0b10000000]);
+/// ```
+///
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+/// use bitstream_io::stuffing::BitStuffingWriter;
+/// let mut writer = BitStuffingWriter::endian(BitWriter::endian(Vec::new(), LittleEndian), LittleEndian);
+/// writer.write(8, 0b01111110).unwrap();
+/// writer.write(7, 0b1111111).unwrap();
+/// writer.byte_align().unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b10111110, 0b10111110, 0b00000001]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitStuffingWriter<W: BitWrite, E: Endianness> {
+    writer: W,
+    ones: u32,
+    phantom: PhantomData<E>,
+}
+
+impl<W: BitWrite, E: Endianness> BitStuffingWriter<W, E> {
+    /// Wraps a BitStuffingWriter around something that implements `BitWrite`
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        BitStuffingWriter {
+            writer,
+            ones: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a BitStuffingWriter around something that implements `BitWrite`
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(writer: W, _endian: E) -> Self {
+        BitStuffingWriter::new(writer)
+    }
+
+    /// Provides mutable reference to internal writer
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of BitStuffingWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: BitWrite, E: Endianness> BitWrite for BitStuffingWriter<W, E> {
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.writer.write_bit(bit)?;
+        if !bit {
+            self.ones = 0;
+        } else if self.ones == STUFFING_RUN - 1 {
+            self.writer.write_bit(false)?;
+            self.ones = 0;
+        } else {
+            self.ones += 1;
+        }
+        Ok(())
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            let mut queue = BitQueue::<E, U>::from_value(value, bits);
+            while !queue.is_empty() {
+                self.write_bit(!queue.pop(1).is_zero())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+}
+
+/// A bitstream wrapper which removes the 0 bit following
+/// every five consecutive 1 bits read from it.
+///
+/// Because stuffed bits change how many bits are taken from the
+/// wrapped reader, its alignment is what's reported
+/// by `byte_aligned` and discarded by `byte_align`.
+///
+/// # Example
+/// ```
+/// use std::io::Read;
+/// use bitstream_io::{BigEndian, BitReader, BitRead};
+/// use bitstream_io::stuffing::BitStuffingReader;
+/// let data = [0b01111101, 0b01111101, 0b10000000];
+/// let mut reader = BitStuffingReader::endian(BitReader::endian(&data[..], BigEndian), BigEndian);
+/// assert_eq!(reader.read::<u8>(8).unwrap(), 0b01111110);
+/// assert_eq!(reader.read::<u8>(7).unwrap(), 0b1111111);
+/// ```
+///
+/// ```
+/// use std::io::Read;
+/// use bitstream_io::{LittleEndian, BitReader, BitRead};
+/// use bitstream_io::stuffing::BitStuffingReader;
+/// let data = [0b10111110, 0b10111110, 0b00000001];
+/// let mut reader = BitStuffingReader::endian(BitReader::endian(&data[..], LittleEndian), LittleEndian);
+/// assert_eq!(reader.read::<u8>(8).unwrap(), 0b01111110);
+/// assert_eq!(reader.read::<u8>(7).unwrap(), 0b1111111);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitStuffingReader<R: BitRead, E: Endianness> {
+    reader: R,
+    ones: u32,
+    phantom: PhantomData<E>,
+}
+
+impl<R: BitRead, E: Endianness> BitStuffingReader<R, E> {
+    /// Wraps a BitStuffingReader around something that implements `BitRead`
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        BitStuffingReader {
+            reader,
+            ones: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Wraps a BitStuffingReader around something that implements `BitRead`
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(reader: R, _endian: E) -> Self {
+        BitStuffingReader::new(reader)
+    }
+
+    /// Provides mutable reference to internal reader
+    #[inline]
+    pub fn reader(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps internal reader and disposes of BitStuffingReader.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BitRead, E: Endianness> BitRead for BitStuffingReader<R, E> {
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidData` if a run of
+    /// five 1 bits is followed by another 1 bit
+    /// rather than a stuffed 0 bit.
+    fn read_bit(&mut self) -> io::Result<bool> {
+        let bit = self.reader.read_bit()?;
+        if !bit {
+            self.ones = 0;
+        } else if self.ones == STUFFING_RUN - 1 {
+            if self.reader.read_bit()? {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "missing stuffed bit",
+                ));
+            }
+            self.ones = 0;
+        } else {
+            self.ones += 1;
+        }
+        Ok(bit)
+    }
+
+    fn read<U>(&mut self, bits: u32) -> io::Result<U>
+    where
+        U: Numeric,
+    {
+        if bits <= U::bits_size() {
+            let mut queue = BitQueue::<E, U>::new();
+            for _ in 0..bits {
+                queue.push(1, U::from_u8(self.read_bit()?.into()));
+            }
+            Ok(queue.value())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type read",
+            ))
+        }
+    }
+
+    #[inline]
+    fn read_signed<S>(&mut self, bits: u32) -> io::Result<S>
+    where
+        S: SignedNumeric,
+    {
+        E::read_signed(self, bits)
+    }
+
+    fn skip(&mut self, bits: u64) -> io::Result<()> {
+        for _ in 0..bits {
+            self.read_bit()?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.reader.byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) {
+        self.reader.byte_align()
+    }
+}
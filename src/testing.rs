@@ -0,0 +1,681 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for testing code built on bitstream readers and writers.
+//!
+//! The round-trip functions write a sequence of fields
+//! through a `BitWriter`, read them back through a `BitReader`
+//! and check that every field survives unchanged,
+//! which is the same invariant this crate's own tests rely on.
+//! They also check that a `BitCounter` and a `BitRecorder`
+//! agree with the writer.
+//!
+//! For sequences of mixed operations, an `OpLog`
+//! can be applied to any writer and then to any reader,
+//! and a `RecordingWriter` captures the log of
+//! the operations performed on another writer.
+//!
+//! This module requires the `testing` feature.
+//! The `proptest` feature adds strategies
+//! for generating `(bits, value)` pairs and operations
+//! which are valid for writing.
+//!
+//! ## Example
+//!
+//! ```
+//! use bitstream_io::{BigEndian, LittleEndian};
+//! use bitstream_io::testing::{assert_roundtrip, assert_signed_roundtrip};
+//!
+//! assert_roundtrip(BigEndian, &[(1, 1u8), (7, 0x7F), (3, 5)]);
+//! assert_roundtrip(LittleEndian, &[(64, u64::MAX), (13, 0x1FFF)]);
+//! assert_signed_roundtrip(BigEndian, &[(5, -16i32), (5, 15), (32, i32::MIN)]);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::io;
+
+use super::{
+    huffman::WriteHuffmanTree, numeric_to_u128, BitCounter, BitRead, BitReader, BitRecorder,
+    BitWrite, BitWriter, Endianness, HuffmanWrite, Numeric, SignedNumeric,
+};
+
+/// Writes all the given `(bits, value)` unsigned fields
+/// to a `BitWriter` and reads them back with a `BitReader`,
+/// returning the encoded bytes and the values read.
+///
+/// # Errors
+///
+/// Passes along any error from writing or reading a field,
+/// such as a value too large for its number of bits.
+///
+/// # Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::testing::roundtrip;
+/// let (bytes, values) = roundtrip(BigEndian, &[(4, 0xAu8), (4, 0x5)]).unwrap();
+/// assert_eq!(bytes, [0xA5]);
+/// assert_eq!(values, [0xA, 0x5]);
+/// assert!(roundtrip(BigEndian, &[(4, 0x10u8)]).is_err());
+/// ```
+pub fn roundtrip<E, U>(endianness: E, fields: &[(u32, U)]) -> io::Result<(Vec<u8>, Vec<U>)>
+where
+    E: Endianness,
+    U: Numeric,
+{
+    let mut writer = BitWriter::endian(Vec::new(), endianness);
+    for (bits, value) in fields {
+        writer.write(*bits, *value)?;
+    }
+    writer.byte_align()?;
+    let bytes = writer.into_writer();
+
+    let mut reader = BitReader::<_, E>::new(io::Cursor::new(&bytes));
+    let values = fields
+        .iter()
+        .map(|(bits, _)| reader.read(*bits))
+        .collect::<io::Result<Vec<U>>>()?;
+    Ok((bytes, values))
+}
+
+/// Writes all the given `(bits, value)` signed fields
+/// to a `BitWriter` and reads them back with a `BitReader`,
+/// returning the encoded bytes and the values read.
+///
+/// # Errors
+///
+/// Passes along any error from writing or reading a field,
+/// such as a value too large for its number of bits.
+///
+/// # Example
+/// ```
+/// use bitstream_io::LittleEndian;
+/// use bitstream_io::testing::signed_roundtrip;
+/// let (bytes, values) = signed_roundtrip(LittleEndian, &[(4, -1i8), (4, 7)]).unwrap();
+/// assert_eq!(bytes, [0x7F]);
+/// assert_eq!(values, [-1, 7]);
+/// assert!(signed_roundtrip(LittleEndian, &[(4, 8i8)]).is_err());
+/// ```
+pub fn signed_roundtrip<E, S>(endianness: E, fields: &[(u32, S)]) -> io::Result<(Vec<u8>, Vec<S>)>
+where
+    E: Endianness,
+    S: SignedNumeric,
+{
+    let mut writer = BitWriter::endian(Vec::new(), endianness);
+    for (bits, value) in fields {
+        writer.write_signed(*bits, *value)?;
+    }
+    writer.byte_align()?;
+    let bytes = writer.into_writer();
+
+    let mut reader = BitReader::<_, E>::new(io::Cursor::new(&bytes));
+    let values = fields
+        .iter()
+        .map(|(bits, _)| reader.read_signed(*bits))
+        .collect::<io::Result<Vec<S>>>()?;
+    Ok((bytes, values))
+}
+
+/// Asserts that all the given `(bits, value)` unsigned fields
+/// read back unchanged after being written,
+/// and that a `BitCounter` and `BitRecorder` agree
+/// with the `BitWriter` on what was written.
+///
+/// # Panics
+///
+/// Panics if any field fails to be written or read,
+/// or if any of the checks fails.
+pub fn assert_roundtrip<E, U>(endianness: E, fields: &[(u32, U)])
+where
+    E: Endianness,
+    U: Numeric,
+{
+    let (bytes, values) = roundtrip(endianness, fields).expect("fields failed to round-trip");
+    for (index, ((_, expected), value)) in fields.iter().zip(values.iter()).enumerate() {
+        assert!(
+            expected == value,
+            "field {} read back as {:?}, expected {:?}",
+            index,
+            value,
+            expected
+        );
+    }
+
+    let mut counter: BitCounter<u64, E> = BitCounter::new();
+    let mut recorder: BitRecorder<u64, E> = BitRecorder::new();
+    for (bits, value) in fields {
+        counter.write(*bits, *value).unwrap();
+        recorder.write(*bits, *value).unwrap();
+    }
+    check_written(
+        &bytes,
+        fields.iter().map(|(bits, _)| *bits),
+        counter,
+        recorder,
+    );
+}
+
+/// Asserts that all the given `(bits, value)` signed fields
+/// read back unchanged after being written,
+/// and that a `BitCounter` and `BitRecorder` agree
+/// with the `BitWriter` on what was written.
+///
+/// # Panics
+///
+/// Panics if any field fails to be written or read,
+/// or if any of the checks fails.
+pub fn assert_signed_roundtrip<E, S>(endianness: E, fields: &[(u32, S)])
+where
+    E: Endianness,
+    S: SignedNumeric,
+{
+    let (bytes, values) =
+        signed_roundtrip(endianness, fields).expect("fields failed to round-trip");
+    for (index, ((_, expected), value)) in fields.iter().zip(values.iter()).enumerate() {
+        assert!(
+            expected == value,
+            "field {} read back as {:?}, expected {:?}",
+            index,
+            value,
+            expected
+        );
+    }
+
+    let mut counter: BitCounter<u64, E> = BitCounter::new();
+    let mut recorder: BitRecorder<u64, E> = BitRecorder::new();
+    for (bits, value) in fields {
+        counter.write_signed(*bits, *value).unwrap();
+        recorder.write_signed(*bits, *value).unwrap();
+    }
+    check_written(
+        &bytes,
+        fields.iter().map(|(bits, _)| *bits),
+        counter,
+        recorder,
+    );
+}
+
+fn check_written<E, I>(
+    bytes: &[u8],
+    bits: I,
+    counter: BitCounter<u64, E>,
+    recorder: BitRecorder<u64, E>,
+) where
+    E: Endianness,
+    I: Iterator<Item = u32>,
+{
+    let total: u64 = bits.map(u64::from).sum();
+    assert_eq!(
+        counter.written(),
+        total,
+        "BitCounter disagrees on bits written"
+    );
+    assert_eq!(
+        recorder.written(),
+        total,
+        "BitRecorder disagrees on bits written"
+    );
+
+    let mut writer = BitWriter::<_, E>::new(Vec::new());
+    recorder.playback(&mut writer).unwrap();
+    writer.byte_align().unwrap();
+    assert_eq!(
+        writer.into_writer(),
+        bytes,
+        "BitRecorder playback disagrees with BitWriter"
+    );
+}
+
+/// A single operation on a bitstream,
+/// which can be applied to a writer or a reader.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Op {
+    /// A single bit
+    Bit(bool),
+    /// An unsigned value with the given number of bits
+    Unsigned {
+        /// the number of bits
+        bits: u32,
+        /// the value, which must fit in `bits`
+        value: u128,
+    },
+    /// A twos-complement signed value with the given number of bits
+    Signed {
+        /// the number of bits
+        bits: u32,
+        /// the value, which must fit in `bits`
+        value: i128,
+    },
+    /// A unary value terminated by a 0 bit
+    Unary0(u32),
+    /// A unary value terminated by a 1 bit
+    Unary1(u32),
+    /// A run of whole bytes
+    Bytes(Vec<u8>),
+    /// The given number of bits, written as 0 bits and skipped when read
+    Skip(u32),
+    /// Padding to the next whole byte
+    ByteAlign,
+}
+
+/// A sequence of operations on a bitstream.
+///
+/// Because it is an ordinary value, an `OpLog`
+/// can be built by a fuzzer or stored alongside a test,
+/// then applied to any writer and to any reader
+/// to check that they agree with one another
+/// or with some reference implementation.
+/// With the `serde` feature enabled, a log may also be serialized
+/// so that a failing case can be saved and replayed later.
+///
+/// Applying a log to a reader returns a new log of
+/// the same operations populated with the values read,
+/// so a successful round trip returns a log equal to the original.
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use bitstream_io::{BigEndian, BitReader, BitWriter};
+/// use bitstream_io::testing::{Op, OpLog};
+///
+/// let log: OpLog = vec![
+///     Op::Bit(true),
+///     Op::Unsigned { bits: 3, value: 5 },
+///     Op::Signed { bits: 4, value: -2 },
+///     Op::ByteAlign,
+///     Op::Bytes(vec![1, 2, 3]),
+///     Op::Unary1(4),
+///     Op::Skip(3),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+/// log.apply_to_writer(&mut writer).unwrap();
+/// let data = writer.into_writer();
+/// assert_eq!(data, [0b1101_1110, 1, 2, 3, 0b0000_1000]);
+///
+/// let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+/// assert_eq!(log.apply_to_reader(&mut reader).unwrap(), log);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpLog {
+    ops: Vec<Op>,
+}
+
+impl OpLog {
+    /// Creates an empty log
+    #[inline]
+    pub fn new() -> Self {
+        OpLog::default()
+    }
+
+    /// Appends an operation to the end of the log
+    #[inline]
+    pub fn push(&mut self, op: Op) {
+        self.ops.push(op)
+    }
+
+    /// Returns the operations in the log
+    #[inline]
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+
+    /// Consumes the log and returns its operations
+    #[inline]
+    pub fn into_ops(self) -> Vec<Op> {
+        self.ops
+    }
+
+    /// Performs every operation in the log on the given writer.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the writer,
+    /// such as a value too large for its number of bits.
+    pub fn apply_to_writer<W: BitWrite + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        for op in self.ops.iter() {
+            match op {
+                Op::Bit(bit) => writer.write_bit(*bit)?,
+                Op::Unsigned { bits, value } => writer.write(*bits, *value)?,
+                Op::Signed { bits, value } => writer.write_signed(*bits, *value)?,
+                Op::Unary0(value) => writer.write_unary0(*value)?,
+                Op::Unary1(value) => writer.write_unary1(*value)?,
+                Op::Bytes(bytes) => writer.write_bytes(bytes)?,
+                Op::Skip(bits) => writer.write_run(false, u64::from(*bits))?,
+                Op::ByteAlign => writer.byte_align()?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Performs every operation in the log on the given reader
+    /// and returns a log of the same operations
+    /// populated with the values actually read.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the reader.
+    pub fn apply_to_reader<R: BitRead + ?Sized>(&self, reader: &mut R) -> io::Result<OpLog> {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                Op::Bit(_) => reader.read_bit().map(Op::Bit),
+                Op::Unsigned { bits, .. } => reader
+                    .read(*bits)
+                    .map(|value| Op::Unsigned { bits: *bits, value }),
+                Op::Signed { bits, .. } => reader
+                    .read_signed(*bits)
+                    .map(|value| Op::Signed { bits: *bits, value }),
+                Op::Unary0(_) => reader.read_unary0().map(Op::Unary0),
+                Op::Unary1(_) => reader.read_unary1().map(Op::Unary1),
+                Op::Bytes(bytes) => {
+                    let mut buf = vec![0; bytes.len()];
+                    reader.read_bytes(&mut buf).map(|()| Op::Bytes(buf))
+                }
+                Op::Skip(bits) => reader.skip(u64::from(*bits)).map(|()| Op::Skip(*bits)),
+                Op::ByteAlign => {
+                    reader.byte_align();
+                    Ok(Op::ByteAlign)
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<Op>> for OpLog {
+    #[inline]
+    fn from(ops: Vec<Op>) -> Self {
+        OpLog { ops }
+    }
+}
+
+impl std::iter::FromIterator<Op> for OpLog {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Op>>(iter: I) -> Self {
+        OpLog {
+            ops: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Op> for OpLog {
+    #[inline]
+    fn extend<I: IntoIterator<Item = Op>>(&mut self, iter: I) {
+        self.ops.extend(iter)
+    }
+}
+
+/// For capturing the operations performed on another writer.
+///
+/// Unlike `BitRecorder`, which stands in for a writer
+/// and only keeps the bits written to it,
+/// this passes each write along to the wrapped writer
+/// and keeps an `OpLog` of the operations themselves.
+/// The log may be applied to another writer
+/// to produce identical output,
+/// or compared against the log of another run
+/// to find where two encoders diverge.
+///
+/// Writes which the wrapped writer rejects are not recorded.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+/// use bitstream_io::testing::{Op, RecordingWriter};
+/// let mut writer = RecordingWriter::new(BitWriter::endian(Vec::new(), BigEndian));
+/// writer.write(3, 0b101u8).unwrap();
+/// writer.write_signed(5, -3i8).unwrap();
+/// assert_eq!(
+///     writer.log().ops(),
+///     [
+///         Op::Unsigned { bits: 3, value: 0b101 },
+///         Op::Signed { bits: 5, value: -3 },
+///     ],
+/// );
+///
+/// let mut replayed = BitWriter::endian(Vec::new(), BigEndian);
+/// writer.log().apply_to_writer(&mut replayed).unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b101_11101]);
+/// assert_eq!(replayed.into_writer(), [0b101_11101]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecordingWriter<W: BitWrite> {
+    writer: W,
+    log: OpLog,
+}
+
+impl<W: BitWrite> RecordingWriter<W> {
+    /// Wraps a RecordingWriter around something that implements `BitWrite`
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        RecordingWriter {
+            writer,
+            log: OpLog::new(),
+        }
+    }
+
+    /// Returns the log of operations recorded so far
+    #[inline]
+    pub fn log(&self) -> &OpLog {
+        &self.log
+    }
+
+    /// Returns the log of operations recorded so far
+    /// and starts a new one
+    #[inline]
+    pub fn take_log(&mut self) -> OpLog {
+        std::mem::take(&mut self.log)
+    }
+
+    /// Provides mutable reference to internal writer
+    ///
+    /// Writes made directly to it are not recorded.
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of RecordingWriter
+    /// and its log
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Unwraps internal writer and the log of operations performed on it
+    #[inline]
+    pub fn into_parts(self) -> (W, OpLog) {
+        (self.writer, self.log)
+    }
+
+    #[inline]
+    fn record(&mut self, result: io::Result<()>, op: Op) -> io::Result<()> {
+        result.map(|()| self.log.push(op))
+    }
+}
+
+impl<W: BitWrite> BitWrite for RecordingWriter<W> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        let result = self.writer.write_bit(bit);
+        self.record(result, Op::Bit(bit))
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        let result = self.writer.write(bits, value);
+        let value = numeric_to_u128(value);
+        self.record(result, Op::Unsigned { bits, value })
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        let result = self.writer.write_signed(bits, value);
+        // sign-extend the value's bit pattern to 128 bits
+        let shift = 128 - S::bits_size();
+        let value = ((numeric_to_u128(value) << shift) as i128) >> shift;
+        self.record(result, Op::Signed { bits, value })
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        let result = self.writer.write_bytes(buf);
+        self.record(result, Op::Bytes(buf.to_vec()))
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        let result = self.writer.write_unary0(value);
+        self.record(result, Op::Unary0(value))
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        let result = self.writer.write_unary1(value);
+        self.record(result, Op::Unary1(value))
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) -> io::Result<()> {
+        let result = self.writer.byte_align();
+        self.record(result, Op::ByteAlign)
+    }
+}
+
+impl<W, E> HuffmanWrite<E> for RecordingWriter<W>
+where
+    W: BitWrite,
+    E: Endianness,
+{
+    #[inline]
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        tree.get(&symbol)
+            .try_for_each(|(bits, value)| self.write(*bits, *value))
+    }
+}
+
+/// Returns a strategy for `(bits, value)` pairs
+/// where `bits` is between 0 and 64 (inclusive)
+/// and `value` fits in that many bits.
+///
+/// Combine it with `proptest::collection::vec`
+/// to generate whole sequences of fields.
+///
+/// # Example
+/// ```
+/// use bitstream_io::BigEndian;
+/// use bitstream_io::testing::{assert_roundtrip, unsigned_field};
+/// use proptest::prelude::*;
+///
+/// proptest!(|(fields in proptest::collection::vec(unsigned_field(), 0..16))| {
+///     assert_roundtrip(BigEndian, &fields);
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub fn unsigned_field() -> impl proptest::strategy::Strategy<Value = (u32, u64)> {
+    use proptest::strategy::{Just, Strategy};
+
+    (0u32..=64).prop_flat_map(|bits| {
+        let max = if bits == 0 {
+            0
+        } else {
+            u64::MAX >> (64 - bits)
+        };
+        (Just(bits), 0..=max)
+    })
+}
+
+/// Returns a strategy for `(bits, value)` pairs
+/// where `bits` is between 1 and 64 (inclusive)
+/// and `value` fits in that many bits as a twos-complement value.
+///
+/// Combine it with `proptest::collection::vec`
+/// to generate whole sequences of fields.
+///
+/// # Example
+/// ```
+/// use bitstream_io::LittleEndian;
+/// use bitstream_io::testing::{assert_signed_roundtrip, signed_field};
+/// use proptest::prelude::*;
+///
+/// proptest!(|(fields in proptest::collection::vec(signed_field(), 0..16))| {
+///     assert_signed_roundtrip(LittleEndian, &fields);
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub fn signed_field() -> impl proptest::strategy::Strategy<Value = (u32, i64)> {
+    use proptest::strategy::{Just, Strategy};
+
+    (1u32..=64).prop_flat_map(|bits| {
+        let max = i64::MAX >> (64 - bits);
+        (Just(bits), (-max - 1)..=max)
+    })
+}
+
+/// Returns a strategy for single operations
+/// which are valid for writing.
+///
+/// Combine it with `proptest::collection::vec`
+/// to generate whole logs.
+///
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use bitstream_io::{LittleEndian, BitReader, BitWriter, BitWrite};
+/// use bitstream_io::testing::{op, OpLog};
+/// use proptest::prelude::*;
+///
+/// proptest!(|(ops in proptest::collection::vec(op(), 0..16))| {
+///     let log = OpLog::from(ops);
+///     let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+///     log.apply_to_writer(&mut writer).unwrap();
+///     writer.byte_align().unwrap();
+///     let data = writer.into_writer();
+///     let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+///     prop_assert_eq!(log.apply_to_reader(&mut reader).unwrap(), log);
+/// });
+/// ```
+#[cfg(feature = "proptest")]
+pub fn op() -> impl proptest::strategy::Strategy<Value = Op> {
+    use proptest::prelude::*;
+
+    prop_oneof![
+        any::<bool>().prop_map(Op::Bit),
+        unsigned_field().prop_map(|(bits, value)| Op::Unsigned {
+            bits,
+            value: value.into()
+        }),
+        signed_field().prop_map(|(bits, value)| Op::Signed {
+            bits,
+            value: value.into()
+        }),
+        (0u32..100).prop_map(Op::Unary0),
+        (0u32..100).prop_map(Op::Unary1),
+        proptest::collection::vec(any::<u8>(), 0..16).prop_map(Op::Bytes),
+        (0u32..100).prop_map(Op::Skip),
+        Just(Op::ByteAlign),
+    ]
+}
@@ -0,0 +1,182 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Streams over JavaScript typed arrays
+//! for use on the `wasm32-unknown-unknown` target.
+//!
+//! `Uint8ArrayReader` and `Uint8ArrayWriter` wrap a `js_sys::Uint8Array`
+//! and implement `Read`, `Write` and `Seek` over its contents,
+//! copying only as many bytes across the JavaScript boundary
+//! as each read or write needs.
+//! Bitstream readers and writers may then be wrapped around them
+//! as with any other stream.
+//!
+//! Because their methods call into JavaScript,
+//! they may only be used on a WebAssembly target with a JavaScript host.
+//!
+//! ## Example
+//!
+//! Reading the header of a PNG file passed in from JavaScript.
+//!
+//! ```no_run
+//! use bitstream_io::{BigEndian, ByteReader, ByteRead};
+//! use bitstream_io::wasm::Uint8ArrayReader;
+//! use js_sys::Uint8Array;
+//! use std::io::SeekFrom;
+//!
+//! fn png_size(file: Uint8Array) -> std::io::Result<(u32, u32)> {
+//!     let mut reader = ByteReader::endian(Uint8ArrayReader::new(file), BigEndian);
+//!     reader.seek(SeekFrom::Start(16))?;
+//!     Ok((reader.read()?, reader.read()?))
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+use js_sys::Uint8Array;
+use std::convert::TryInto;
+use std::io;
+
+/// A stream which reads the contents of a `Uint8Array`.
+///
+/// # Example
+/// ```no_run
+/// use bitstream_io::{LittleEndian, BitReader, BitRead};
+/// use bitstream_io::wasm::Uint8ArrayReader;
+/// use js_sys::Uint8Array;
+/// let array = Uint8Array::from(&[0b10110111u8][..]);
+/// let mut reader = BitReader::endian(Uint8ArrayReader::new(array), LittleEndian);
+/// assert_eq!(reader.read::<u8>(3).unwrap(), 0b111);
+/// assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Uint8ArrayReader {
+    array: Uint8Array,
+    position: u32,
+}
+
+impl Uint8ArrayReader {
+    /// Wraps a Uint8ArrayReader around a `Uint8Array`
+    #[inline]
+    pub fn new(array: Uint8Array) -> Self {
+        Uint8ArrayReader { array, position: 0 }
+    }
+
+    /// Returns the number of bytes read from the start of the array
+    #[inline]
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Unwraps internal array and disposes of Uint8ArrayReader.
+    #[inline]
+    pub fn into_array(self) -> Uint8Array {
+        self.array
+    }
+}
+
+impl io::Read for Uint8ArrayReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.array.length().saturating_sub(self.position);
+        let bytes = remaining.min(buf.len().try_into().unwrap_or(u32::MAX));
+        self.array
+            .subarray(self.position, self.position + bytes)
+            .copy_to(&mut buf[0..bytes as usize]);
+        self.position += bytes;
+        Ok(bytes as usize)
+    }
+}
+
+impl io::Seek for Uint8ArrayReader {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(self.position, self.array.length(), pos)?;
+        Ok(self.position.into())
+    }
+}
+
+/// A stream which writes to the contents of a `Uint8Array`,
+/// whose length is fixed when it's created.
+///
+/// # Example
+/// ```no_run
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+/// use bitstream_io::wasm::Uint8ArrayWriter;
+/// use js_sys::Uint8Array;
+/// let mut writer = BitWriter::endian(Uint8ArrayWriter::new(Uint8Array::new_with_length(2)), BigEndian);
+/// writer.write(12, 0xABCu16).unwrap();
+/// writer.write(4, 0xDu8).unwrap();
+/// assert!(writer.write(8, 0xEFu8).is_err());
+/// assert_eq!(writer.into_writer().into_array().to_vec(), [0xAB, 0xCD]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Uint8ArrayWriter {
+    array: Uint8Array,
+    position: u32,
+}
+
+impl Uint8ArrayWriter {
+    /// Wraps a Uint8ArrayWriter around a `Uint8Array`
+    #[inline]
+    pub fn new(array: Uint8Array) -> Self {
+        Uint8ArrayWriter { array, position: 0 }
+    }
+
+    /// Returns the number of bytes written from the start of the array
+    #[inline]
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Unwraps internal array and disposes of Uint8ArrayWriter.
+    #[inline]
+    pub fn into_array(self) -> Uint8Array {
+        self.array
+    }
+}
+
+impl io::Write for Uint8ArrayWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let remaining = self.array.length().saturating_sub(self.position);
+        let bytes = remaining.min(buf.len().try_into().unwrap_or(u32::MAX));
+        self.array
+            .subarray(self.position, self.position + bytes)
+            .copy_from(&buf[0..bytes as usize]);
+        self.position += bytes;
+        Ok(bytes as usize)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl io::Seek for Uint8ArrayWriter {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(self.position, self.array.length(), pos)?;
+        Ok(self.position.into())
+    }
+}
+
+// Typed arrays can't be resized,
+// so positions past their end are rejected
+fn seek_position(current: u32, len: u32, pos: io::SeekFrom) -> io::Result<u32> {
+    let target = match pos {
+        io::SeekFrom::Start(offset) => i128::from(offset),
+        io::SeekFrom::End(offset) => i128::from(len) + i128::from(offset),
+        io::SeekFrom::Current(offset) => i128::from(current) + i128::from(offset),
+    };
+    if (0..=i128::from(len)).contains(&target) {
+        Ok(target as u32)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "seek outside of array",
+        ))
+    }
+}
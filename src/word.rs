@@ -0,0 +1,242 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Adapters for streams made of multi-byte words.
+//!
+//! Some formats treat their stream as a sequence of 16 or 32-bit words
+//! and pack bits into each word as a whole, while storing
+//! the words themselves in the opposite byte order.
+//! For example, a stream of little-endian 16-bit words
+//! whose bits are packed most-significant first
+//! is not readable as a plain big-endian or little-endian bitstream.
+//!
+//! `WordReader` and `WordWriter` reverse the bytes of each
+//! word-sized chunk as it passes through,
+//! so that a `BitReader` or `BitWriter` wrapped around them
+//! sees the words in the same byte order as its bits.
+//! The word size is given by any `Numeric` type,
+//! such as `u16` or `u32`.
+//!
+//! ## Example
+//!
+//! Reading 16-bit little-endian words with bits packed
+//! most-significant first.
+//!
+//! ```
+//! use bitstream_io::{BigEndian, BitReader, BitRead};
+//! use bitstream_io::word::WordReader;
+//! let data: &[u8] = &[0x34, 0x12, 0x78, 0x56];
+//! let mut reader = BitReader::endian(WordReader::<_, u16>::new(data), BigEndian);
+//! assert_eq!(reader.read::<u8>(4).unwrap(), 0x1);
+//! assert_eq!(reader.read::<u16>(16).unwrap(), 0x2345);
+//! assert_eq!(reader.read::<u16>(12).unwrap(), 0x678);
+//! ```
+
+#![warn(missing_docs)]
+
+use std::io;
+
+use super::Numeric;
+
+/// For reading a stream of words whose byte order
+/// is the opposite of the stream's bit order.
+///
+/// Bytes are read from the underlying stream one whole word at a time
+/// and returned with each word's bytes reversed.
+pub struct WordReader<R: io::Read, N: Numeric> {
+    reader: R,
+    word: N::Bytes,
+    position: usize,
+}
+
+impl<R: io::Read, N: Numeric> WordReader<R, N> {
+    /// Wraps a WordReader around something that implements `Read`
+    pub fn new(reader: R) -> WordReader<R, N> {
+        let word = N::buffer();
+        let position = word.as_ref().len();
+        WordReader {
+            reader,
+            word,
+            position,
+        }
+    }
+
+    /// Unwraps internal reader and disposes of WordReader.
+    ///
+    /// # Warning
+    ///
+    /// Any unread bytes of a partially read word are discarded.
+    #[inline]
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Returns true if the reader is positioned at a word boundary.
+    #[inline]
+    pub fn word_aligned(&self) -> bool {
+        self.position == self.word.as_ref().len()
+    }
+
+    // reads the next whole word into our buffer,
+    // returning false if the stream is at a clean end
+    fn fill_word(&mut self) -> io::Result<bool> {
+        let word = self.word.as_mut();
+        let mut filled = 0;
+        while filled < word.len() {
+            match self.reader.read(&mut word[filled..]) {
+                Ok(0) => break,
+                Ok(bytes) => filled += bytes,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        if filled == 0 {
+            Ok(false)
+        } else if filled < word.len() {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "partial word at end of stream",
+            ))
+        } else {
+            word.reverse();
+            self.position = 0;
+            Ok(true)
+        }
+    }
+}
+
+impl<R: io::Read, N: Numeric> io::Read for WordReader<R, N> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || (self.word_aligned() && !self.fill_word()?) {
+            return Ok(0);
+        }
+        let word = &self.word.as_ref()[self.position..];
+        let to_copy = std::cmp::min(word.len(), buf.len());
+        buf[0..to_copy].copy_from_slice(&word[0..to_copy]);
+        self.position += to_copy;
+        Ok(to_copy)
+    }
+}
+
+/// For writing a stream of words whose byte order
+/// is the opposite of the stream's bit order.
+///
+/// Bytes are buffered until a whole word is available
+/// and then written to the underlying stream with
+/// that word's bytes reversed.
+/// Because only whole words are written, it is important
+/// that output is word-aligned before the writer's lifetime ends.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+/// use bitstream_io::word::WordWriter;
+/// let mut writer = BitWriter::endian(WordWriter::<_, u16>::new(Vec::new()), BigEndian);
+/// writer.write(4, 0x1).unwrap();
+/// writer.write(16, 0x2345).unwrap();
+/// writer.write(12, 0x678).unwrap();
+/// let words = writer.into_writer().into_writer();
+/// assert_eq!(words, [0x34, 0x12, 0x78, 0x56]);
+/// ```
+pub struct WordWriter<W: io::Write, N: Numeric> {
+    writer: W,
+    word: N::Bytes,
+    position: usize,
+}
+
+impl<W: io::Write, N: Numeric> WordWriter<W, N> {
+    /// Wraps a WordWriter around something that implements `Write`
+    pub fn new(writer: W) -> WordWriter<W, N> {
+        WordWriter {
+            writer,
+            word: N::buffer(),
+            position: 0,
+        }
+    }
+
+    /// Unwraps internal writer and disposes of WordWriter.
+    ///
+    /// # Warning
+    ///
+    /// Any unwritten bytes of a partial word are discarded.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Returns true if the writer is positioned at a word boundary.
+    #[inline]
+    pub fn word_aligned(&self) -> bool {
+        self.position == 0
+    }
+
+    /// Pads any partial word with 0 bytes and writes it.
+    /// Does nothing if the stream is already word-aligned.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::word::WordWriter;
+    /// let mut writer = WordWriter::<_, u32>::new(Vec::new());
+    /// writer.write_all(&[1, 2, 3, 4, 5]).unwrap();
+    /// writer.word_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [4, 3, 2, 1, 0, 0, 0, 5]);
+    /// ```
+    pub fn word_align(&mut self) -> io::Result<()> {
+        if self.word_aligned() {
+            Ok(())
+        } else {
+            for b in self.word.as_mut()[self.position..].iter_mut() {
+                *b = 0;
+            }
+            self.write_word()
+        }
+    }
+
+    /// Writes the buffered word with its bytes reversed,
+    /// leaving it buffered unless the write succeeds
+    fn write_word(&mut self) -> io::Result<()> {
+        let mut reversed = N::buffer();
+        for (r, b) in reversed
+            .as_mut()
+            .iter_mut()
+            .zip(self.word.as_ref().iter().rev())
+        {
+            *r = *b;
+        }
+        self.writer.write_all(reversed.as_ref())?;
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl<W: io::Write, N: Numeric> io::Write for WordWriter<W, N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let word = &mut self.word.as_mut()[self.position..];
+        let to_copy = std::cmp::min(word.len(), buf.len());
+        word[0..to_copy].copy_from_slice(&buf[0..to_copy]);
+        if self.position + to_copy == self.word.as_ref().len() {
+            // the copied bytes aren't accepted unless the word is written
+            self.write_word()?;
+        } else {
+            self.position += to_copy;
+        }
+        Ok(to_copy)
+    }
+
+    /// Flushes the underlying stream.
+    /// Any partial word is not flushed.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -71,11 +71,25 @@
 
 #![warn(missing_docs)]
 
-use std::convert::From;
+#[cfg(feature = "observer")]
+use super::observer::{BitObserver, Observed};
+#[cfg(feature = "bitvec")]
+use bitvec::{order::Msb0, slice::BitSlice};
+use std::cmp::Ordering;
+use std::convert::{From, TryFrom};
+use std::error;
+use std::fmt;
 use std::io;
-use std::ops::{AddAssign, Rem};
+#[cfg(feature = "observer")]
+use std::sync::{Arc, Mutex};
 
-use super::{huffman::WriteHuffmanTree, BitQueue, Endianness, Numeric, PhantomData, SignedNumeric};
+use super::{
+    buffer::BitBuffer, huffman::AdaptiveHuffmanTree, huffman::WriteHuffmanTree, numeric_to_u128,
+    reverse_bits, schema::FieldValue, schema::Schema, signed_to_i64, split::SplitField, BcdOrder,
+    BigEndian, BitLimitError, BitQueue, DynEndianness, Endianness, FloatNumeric, LittleEndian,
+    Numeric, PhantomData, ReservedPolicy, Rounding, SignPlacement, SignedNumeric, SignedRepr,
+    BCD_MAX_DIGITS,
+};
 
 /// For writing bit values to an underlying stream in a given endianness.
 ///
@@ -87,6 +101,29 @@ use super::{huffman::WriteHuffmanTree, BitQueue, Endianness, Numeric, PhantomDat
 pub struct BitWriter<W: io::Write, E: Endianness> {
     writer: W,
     bitqueue: BitQueue<E, u8>,
+    #[cfg(feature = "observer")]
+    observed: Observed,
+}
+
+impl<W: io::Write + Clone, E: Endianness> Clone for BitWriter<W, E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        BitWriter {
+            writer: self.writer.clone(),
+            bitqueue: self.bitqueue.clone(),
+            #[cfg(feature = "observer")]
+            observed: self.observed.clone(),
+        }
+    }
+}
+
+impl<W: io::Write + fmt::Debug, E: Endianness> fmt::Debug for BitWriter<W, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitWriter")
+            .field("writer", &self.writer)
+            .field("bitqueue", &self.bitqueue)
+            .finish()
+    }
 }
 
 impl<W: io::Write, E: Endianness> BitWriter<W, E> {
@@ -95,6 +132,8 @@ impl<W: io::Write, E: Endianness> BitWriter<W, E> {
         BitWriter {
             writer,
             bitqueue: BitQueue::new(),
+            #[cfg(feature = "observer")]
+            observed: Observed::default(),
         }
     }
 
@@ -104,6 +143,8 @@ impl<W: io::Write, E: Endianness> BitWriter<W, E> {
         BitWriter {
             writer,
             bitqueue: BitQueue::new(),
+            #[cfg(feature = "observer")]
+            observed: Observed::default(),
         }
     }
 
@@ -185,6 +226,81 @@ impl<W: io::Write, E: Endianness> BitWriter<W, E> {
         (self.bitqueue.len(), self.bitqueue.value())
     }
 
+    /// Returns the number of bits of the partial byte
+    /// not yet written to the stream.
+    ///
+    /// Together with `pending_value`, this is all the state
+    /// a writer holds beyond its stream, so a writer may be
+    /// persisted partway through a byte - such as when
+    /// the task driving it is cancelled - and later rebuilt
+    /// with `from_pending`.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(11, 0b1010_0101_011u16).unwrap();
+    /// assert_eq!(writer.pending_bits(), 3);
+    /// assert_eq!(writer.pending_value(), 0b011);
+    /// ```
+    #[inline]
+    pub fn pending_bits(&self) -> u32 {
+        self.bitqueue.len()
+    }
+
+    /// Returns the value of the partial byte
+    /// not yet written to the stream,
+    /// whose size is given by `pending_bits`.
+    #[inline]
+    pub fn pending_value(&self) -> u8 {
+        self.bitqueue.clone().value()
+    }
+
+    /// Wraps a BitWriter around something that implements `Write`
+    /// which resumes from a partial byte
+    /// previously taken from `pending_bits` and `pending_value`,
+    /// or from `into_unwritten`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if `bits` isn't less than 8
+    /// or `value` is too large for the given number of bits.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(11, 0b1010_0101_011u16).unwrap();
+    /// let (bits, value) = (writer.pending_bits(), writer.pending_value());
+    /// let data = writer.into_writer();
+    ///
+    /// // later, after the first writer has gone away
+    /// let mut writer = BitWriter::<_, BigEndian>::from_pending(data, bits, value).unwrap();
+    /// writer.write(5, 0b1_1110u8).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b1010_0101, 0b011_1_1110]);
+    /// assert!(BitWriter::<_, BigEndian>::from_pending(Vec::new(), 3, 8).is_err());
+    /// ```
+    pub fn from_pending(writer: W, bits: u32, value: u8) -> io::Result<BitWriter<W, E>> {
+        if bits >= 8 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "pending bits must be fewer than 8",
+            ))
+        } else if value >= (1 << bits) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for pending bits",
+            ))
+        } else {
+            Ok(BitWriter {
+                writer,
+                bitqueue: BitQueue::from_value(value, bits),
+                #[cfg(feature = "observer")]
+                observed: Observed::default(),
+            })
+        }
+    }
+
     /// Flushes output stream to disk, if necessary.
     /// Any partial bytes are not flushed.
     ///
@@ -195,6 +311,230 @@ impl<W: io::Write, E: Endianness> BitWriter<W, E> {
     pub fn flush(&mut self) -> io::Result<()> {
         self.writer.flush()
     }
+
+    /// Attaches an observer which is told about every run of bits
+    /// written from now on, replacing any previous observer.
+    ///
+    /// Clones of this writer share the same observer.
+    /// Bytes written through the writer returned by `bytewriter`
+    /// aren't reported.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use std::sync::{Arc, Mutex};
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitstream_io::observer::BitObserver;
+    ///
+    /// #[derive(Default)]
+    /// struct Runs(Vec<(u32, u64)>);
+    ///
+    /// impl BitObserver for Runs {
+    ///     fn on_write(&mut self, bits: u32, offset: u64) {
+    ///         self.0.push((bits, offset));
+    ///     }
+    /// }
+    ///
+    /// let runs = Arc::new(Mutex::new(Runs::default()));
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(3, 0b101u8).unwrap();
+    /// writer.set_observer(runs.clone());
+    /// writer.write(5, 0b10111u8).unwrap();
+    /// writer.write_bytes(b"ab").unwrap();
+    /// assert!(writer.take_observer().is_some());
+    /// writer.write(8, 0xFFu8).unwrap();
+    /// assert_eq!(runs.lock().unwrap().0, [(5, 3), (16, 8)]);
+    /// ```
+    #[cfg(feature = "observer")]
+    #[inline]
+    pub fn set_observer(&mut self, observer: Arc<Mutex<dyn BitObserver + Send>>) {
+        self.observed.set(observer)
+    }
+
+    /// Detaches and returns the writer's observer, if any
+    #[cfg(feature = "observer")]
+    #[inline]
+    pub fn take_observer(&mut self) -> Option<Arc<Mutex<dyn BitObserver + Send>>> {
+        self.observed.take()
+    }
+
+    /// Writes an unsigned value to the stream using the given
+    /// number of bits, like `BitWrite::write`,
+    /// but reports how many of the value's bits were committed
+    /// to the underlying stream if an error occurs.
+    ///
+    /// Whole bytes are passed to the underlying stream one at a time,
+    /// so the committed count is exact.
+    /// After an error, any bits still pending in a partial byte
+    /// are discarded and the writer is left byte-aligned,
+    /// so writing may resume at a whole byte boundary.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream,
+    /// along with the number of the value's leading bits
+    /// which were written before it occurred.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Write, ErrorKind};
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut data = [0; 2];
+    /// let mut writer = BitWriter::endian(&mut data[..], BigEndian);
+    /// writer.write(4, 0b1011).unwrap();
+    /// let err = writer.write_checked(24, 0xABCDEFu32).unwrap_err();
+    /// assert_eq!(err.committed(), 12);
+    /// assert_eq!(err.error().kind(), ErrorKind::WriteZero);
+    /// assert!(writer.byte_aligned());
+    /// assert_eq!(data, [0b1011_1010, 0xBC]);
+    /// ```
+    pub fn write_checked<U>(&mut self, bits: u32, value: U) -> Result<(), PartialWriteError>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(PartialWriteError::new(
+                0,
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "excessive bits for type written",
+                ),
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(PartialWriteError::new(
+                0,
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "excessive value for bits written",
+                ),
+            ))
+        } else if bits < self.bitqueue.remaining_len() {
+            self.bitqueue.push(bits, value.to_u8());
+            #[cfg(feature = "observer")]
+            self.observed.wrote(bits.into());
+            Ok(())
+        } else {
+            let mut acc = BitQueue::<E, U>::from_value(value, bits);
+            let mut committed = 0;
+            if !self.bitqueue.is_empty() {
+                let to_transfer = self.bitqueue.remaining_len();
+                self.bitqueue
+                    .push(to_transfer, acc.pop(to_transfer).to_u8());
+                let byte = self.bitqueue.pop(8);
+                self.write_checked_byte(committed, byte)?;
+                committed += to_transfer;
+            }
+            while acc.len() >= 8 {
+                self.write_checked_byte(committed, acc.pop(8).to_u8())?;
+                committed += 8;
+            }
+            self.bitqueue.push(acc.len(), acc.value().to_u8());
+            #[cfg(feature = "observer")]
+            self.observed.wrote(bits.into());
+            Ok(())
+        }
+    }
+
+    fn write_checked_byte(&mut self, committed: u32, byte: u8) -> Result<(), PartialWriteError> {
+        write_byte(&mut self.writer, byte).map_err(|error| {
+            self.bitqueue.clear();
+            PartialWriteError::new(committed, error)
+        })
+    }
+}
+
+impl<W: io::Write + AsRef<[u8]>, E: Endianness> BitWriter<W, E> {
+    /// Returns the bytes written to the underlying buffer so far,
+    /// along with any un-written partial byte as `bits` and `value`,
+    /// without consuming the writer.
+    /// This is useful for transmitting a stream's
+    /// completed bytes while it is still being written.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(15, 0b1010_0101_0101_101).unwrap();
+    /// assert_eq!(writer.written_so_far(), (&[0b1010_0101][..], 7, 0b0101_101));
+    /// writer.write(1, 1).unwrap();
+    /// assert_eq!(writer.written_so_far(), (&[0b1010_0101, 0b0101_1011][..], 0, 0));
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut data = Vec::new();
+    /// let mut writer = BitWriter::endian(&mut data, LittleEndian);
+    /// writer.write(15, 0b1010_1010_0101_101).unwrap();
+    /// assert_eq!(writer.written_so_far(), (&[0b0010_1101][..], 7, 0b1010_101));
+    /// ```
+    #[inline]
+    pub fn written_so_far(&self) -> (&[u8], u32, u8) {
+        (
+            self.writer.as_ref(),
+            self.bitqueue.len(),
+            self.bitqueue.clone().value(),
+        )
+    }
+}
+
+/// An error from `BitWriter::write_checked`
+/// which reports how many of a value's bits were committed
+/// to the underlying stream before the error occurred.
+#[derive(Debug)]
+pub struct PartialWriteError {
+    committed: u32,
+    error: io::Error,
+}
+
+impl PartialWriteError {
+    #[inline]
+    fn new(committed: u32, error: io::Error) -> Self {
+        PartialWriteError { committed, error }
+    }
+
+    /// Returns the number of the value's leading bits
+    /// which were written to the underlying stream
+    #[inline]
+    pub fn committed(&self) -> u32 {
+        self.committed
+    }
+
+    /// Returns the error which stopped the write
+    #[inline]
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Unwraps the error which stopped the write
+    #[inline]
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+}
+
+impl fmt::Display for PartialWriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} after {} bits written", self.error, self.committed)
+    }
+}
+
+impl error::Error for PartialWriteError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<PartialWriteError> for io::Error {
+    #[inline]
+    fn from(error: PartialWriteError) -> Self {
+        error.error
+    }
 }
 
 /// A trait for anything that can write a variable number of
@@ -236,239 +576,285 @@ pub trait BitWrite {
     where
         S: SignedNumeric;
 
-    /// Writes the entirety of a byte buffer to the stream.
+    /// Writes a signed value to the stream with the given number of bits
+    /// in the given signed representation.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits in that representation.
     ///
-    /// # Example
-    ///
+    /// # Examples
     /// ```
     /// use std::io::Write;
-    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, SignedRepr};
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write(8, 0x66).unwrap();
-    /// writer.write(8, 0x6F).unwrap();
-    /// writer.write(8, 0x6F).unwrap();
-    /// writer.write_bytes(b"bar").unwrap();
-    /// assert_eq!(writer.into_writer(), b"foobar");
+    /// writer.write_signed_with(SignedRepr::SignMagnitude, 4, -3).unwrap();
+    /// writer.write_signed_with(SignedRepr::OnesComplement, 4, -3).unwrap();
+    /// assert!(writer.write_signed_with(SignedRepr::SignMagnitude, 4, -8).is_err());
+    /// assert_eq!(writer.into_writer(), [0b1011_1100]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite, SignedRepr};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_signed_with(SignedRepr::SignMagnitude, 4, -3).unwrap();
+    /// writer.write_signed_with(SignedRepr::TwosComplement, 4, -3).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b1101_1011]);
     /// ```
     #[inline]
-    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
-        buf.iter().try_for_each(|b| self.write(8, *b))
+    fn write_signed_with<S>(&mut self, repr: SignedRepr, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        self.write_signed(bits, repr.encode(bits, value)?)
     }
 
-    /// Writes `value` number of 1 bits to the stream
-    /// and then writes a 0 bit.  This field is variably-sized.
+    /// Writes a twos-complement signed value to the stream
+    /// with the given number of bits,
+    /// with its sign bit placed before or after its remaining bits
+    /// regardless of the stream's endianness.
+    /// The remaining bits are written as an unsigned value
+    /// in the stream's endianness.
     ///
     /// # Errors
     ///
-    /// Passes along any I/O error from the underyling stream.
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits, or if `bits` is 0.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
     ///
     /// # Examples
     /// ```
     /// use std::io::Write;
-    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, SignPlacement};
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write_unary0(0).unwrap();
-    /// writer.write_unary0(3).unwrap();
-    /// writer.write_unary0(10).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b01110111, 0b11111110]);
+    /// writer.write_signed_placed(SignPlacement::Last, 5, -3i8).unwrap();
+    /// writer.write_signed_placed(SignPlacement::First, 3, -3i8).unwrap();
+    /// assert!(writer.write_signed_placed(SignPlacement::Last, 3, 4i8).is_err());
+    /// assert_eq!(writer.into_writer(), [0b1101_1_101]);
     /// ```
     ///
     /// ```
     /// use std::io::Write;
-    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite, SignPlacement};
     /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
-    /// writer.write_unary0(0).unwrap();
-    /// writer.write_unary0(3).unwrap();
-    /// writer.write_unary0(10).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b11101110, 0b01111111]);
+    /// writer.write_signed_placed(SignPlacement::First, 5, -3i8).unwrap();
+    /// writer.write_signed_placed(SignPlacement::Last, 3, -3i8).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b101_1101_1]);
     /// ```
-    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
-        match value {
-            0 => self.write_bit(false),
-            bits @ 1..=31 => self
-                .write(value, (1u32 << bits) - 1)
-                .and_then(|()| self.write_bit(false)),
-            32 => self
-                .write(value, 0xFFFF_FFFFu32)
-                .and_then(|()| self.write_bit(false)),
-            bits @ 33..=63 => self
-                .write(value, (1u64 << bits) - 1)
-                .and_then(|()| self.write_bit(false)),
-            64 => self
-                .write(value, 0xFFFF_FFFF_FFFF_FFFFu64)
-                .and_then(|()| self.write_bit(false)),
-            mut bits => {
-                while bits > 64 {
-                    self.write(64, 0xFFFF_FFFF_FFFF_FFFFu64)?;
-                    bits -= 64;
-                }
-                self.write_unary0(bits)
+    fn write_signed_placed<S>(
+        &mut self,
+        placement: SignPlacement,
+        bits: u32,
+        value: S,
+    ) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        if bits == 0 || bits > S::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ));
+        }
+        if bits < S::bits_size() {
+            let limit = S::one() << (bits - 1);
+            if value >= limit || value < S::default() - limit {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "excessive value for bits written",
+                ));
+            }
+        }
+        let (negative, rest) = if value.is_negative() {
+            (true, value.as_unsigned(bits))
+        } else {
+            (false, value)
+        };
+        match placement {
+            SignPlacement::First => {
+                self.write_bit(negative)?;
+                write_sign_rest(self, bits, rest)
+            }
+            SignPlacement::Last => {
+                write_sign_rest(self, bits, rest)?;
+                self.write_bit(negative)
             }
         }
     }
 
-    /// Writes `value` number of 0 bits to the stream
-    /// and then writes a 1 bit.  This field is variably-sized.
+    /// Writes the samples of several channels interleaved,
+    /// as is common for PCM audio,
+    /// with each sample written as a signed value of the given number of bits.
+    /// The first sample of every channel is written in channel order,
+    /// followed by the second sample of every channel, and so on.
     ///
     /// # Errors
     ///
-    /// Passes along any I/O error from the underyling stream.
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput` if the channels
+    /// don't all have the same number of samples,
+    /// if `bits` isn't between 1 and 32,
+    /// or if any sample is too large for the given number of bits.
+    /// These are all checked before anything is written.
     ///
     /// # Example
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let left = [0x123, -1];
+    /// let right = [0x456, -0x800];
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write_unary1(0).unwrap();
-    /// writer.write_unary1(3).unwrap();
-    /// writer.write_unary1(10).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10001000, 0b00000001]);
-    /// ```
-    ///
-    /// ```
-    /// use std::io::Write;
-    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
-    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
-    /// writer.write_unary1(0).unwrap();
-    /// writer.write_unary1(3).unwrap();
-    /// writer.write_unary1(10).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b00010001, 0b10000000]);
+    /// writer.write_samples_interleaved(12, &[&left, &right]).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x12, 0x34, 0x56, 0xFF, 0xF8, 0x00]);
     /// ```
-    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
-        match value {
-            0 => self.write_bit(true),
-            1..=32 => self.write(value, 0u32).and_then(|()| self.write_bit(true)),
-            33..=64 => self.write(value, 0u64).and_then(|()| self.write_bit(true)),
-            mut bits => {
-                while bits > 64 {
-                    self.write(64, 0u64)?;
-                    bits -= 64;
-                }
-                self.write_unary1(bits)
+    fn write_samples_interleaved(&mut self, bits: u32, channels: &[&[i32]]) -> io::Result<()> {
+        if bits == 0 || bits > 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ));
+        }
+        let frames = channels.first().map(|channel| channel.len()).unwrap_or(0);
+        if channels.iter().any(|channel| channel.len() != frames) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "channels have different numbers of samples",
+            ));
+        }
+        if bits < 32 {
+            let limit = 1 << (bits - 1);
+            if channels
+                .iter()
+                .flat_map(|channel| channel.iter())
+                .any(|sample| !(-limit..limit).contains(sample))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "excessive value for bits written",
+                ));
+            }
+        }
+        for frame in 0..frames {
+            for channel in channels {
+                self.write_signed(bits, channel[frame])?;
             }
         }
+        Ok(())
     }
 
-    /// Returns true if the stream is aligned at a whole byte.
-    fn byte_aligned(&self) -> bool;
-
-    /// Pads the stream with 0 bits until it is aligned at a whole byte.
-    /// Does nothing if the stream is already aligned.
+    /// Writes a signed value to the stream as a zigzag-encoded
+    /// unsigned value with the given number of bits,
+    /// where 0, -1, 1, -2, 2 and so on are written as 0, 1, 2, 3, 4.
     ///
     /// # Errors
     ///
-    /// Passes along any I/O error from the underyling stream.
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the encoded value is too large
+    /// to fit the given number of bits.
     ///
-    /// # Example
+    /// # Examples
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write(1, 0).unwrap();
-    /// writer.byte_align().unwrap();
-    /// writer.write(8, 0xFF).unwrap();
-    /// assert_eq!(writer.into_writer(), [0x00, 0xFF]);
+    /// writer.write_zigzag(4, -1).unwrap();
+    /// writer.write_zigzag(4, 2).unwrap();
+    /// assert!(writer.write_zigzag(4, -9).is_err());
+    /// assert_eq!(writer.into_writer(), [0b0001_0100]);
     /// ```
-    fn byte_align(&mut self) -> io::Result<()> {
-        while !self.byte_aligned() {
-            self.write_bit(false)?;
-        }
-        Ok(())
-    }
-}
-
-/// A trait for anything that can write Huffman codes
-/// of a given endianness to an output stream
-pub trait HuffmanWrite<E: Endianness> {
-    /// Writes Huffman code for the given symbol to the stream.
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_zigzag(4, -1).unwrap();
+    /// writer.write_zigzag(4, 2).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b0100_0001]);
+    /// ```
+    #[inline]
+    fn write_zigzag(&mut self, bits: u32, value: i64) -> io::Result<()> {
+        self.write(bits, ((value << 1) ^ (value >> 63)) as u64)
+    }
+
+    /// Writes an unsigned value to the stream as its reflected
+    /// binary Gray code with the given number of bits.
     ///
     /// # Errors
     ///
     /// Passes along any I/O error from the underlying stream.
-    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
-    where
-        T: Ord + Copy;
-}
-
-impl<W: io::Write, E: Endianness> BitWrite for BitWriter<W, E> {
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    ///
     /// # Examples
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(false).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(false).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// writer.write_gray(3, 5u8).unwrap();
+    /// writer.write_gray(5, 12u8).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b111_01010]);
     /// ```
     ///
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(false).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// writer.write_bit(false).unwrap();
-    /// writer.write_bit(true).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// writer.write_gray(3, 5u8).unwrap();
+    /// writer.write_gray(5, 12u8).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b01010_111]);
     /// ```
-    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
-        self.bitqueue.push(1, if bit { 1 } else { 0 });
-        if self.bitqueue.is_full() {
-            write_byte(&mut self.writer, self.bitqueue.pop(8))
-        } else {
-            Ok(())
-        }
+    #[inline]
+    fn write_gray<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        self.write(bits, value ^ (value >> 1))
     }
 
+    /// Writes an unsigned value to the stream with the given number
+    /// of bits, with the order of the field's bits reversed.
+    /// That is, a big-endian stream receives the value's
+    /// least significant bit first and a little-endian stream
+    /// receives its most significant bit first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the input type is too small
+    /// to hold the given number of bits.
+    /// Returns an error if the value is too large
+    /// to fit the given number of bits.
+    ///
     /// # Examples
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write(1, 0b1).unwrap();
-    /// writer.write(2, 0b01).unwrap();
-    /// writer.write(5, 0b10111).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// writer.write_reversed(3, 0b110u8).unwrap();
+    /// writer.write_reversed(5, 0b10111u8).unwrap();
+    /// assert!(writer.write_reversed(2, 0b100u8).is_err());
+    /// assert_eq!(writer.into_writer(), [0b011_11101]);
     /// ```
     ///
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
-    /// writer.write(1, 0b1).unwrap();
-    /// writer.write(2, 0b11).unwrap();
-    /// writer.write(5, 0b10110).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10110111]);
-    /// ```
-    ///
-    /// ```
-    /// use std::io::{Write, sink};
-    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
-    /// let mut w = BitWriter::endian(sink(), BigEndian);
-    /// assert!(w.write(9, 0u8).is_err());    // can't write  u8 in 9 bits
-    /// assert!(w.write(17, 0u16).is_err());  // can't write u16 in 17 bits
-    /// assert!(w.write(33, 0u32).is_err());  // can't write u32 in 33 bits
-    /// assert!(w.write(65, 0u64).is_err());  // can't write u64 in 65 bits
-    /// assert!(w.write(1, 2).is_err());      // can't write   2 in 1 bit
-    /// assert!(w.write(2, 4).is_err());      // can't write   4 in 2 bits
-    /// assert!(w.write(3, 8).is_err());      // can't write   8 in 3 bits
-    /// assert!(w.write(4, 16).is_err());     // can't write  16 in 4 bits
+    /// writer.write_reversed(3, 0b110u8).unwrap();
+    /// writer.write_reversed(5, 0b10111u8).unwrap();
+    /// assert!(writer.write_reversed(2, 0b100u8).is_err());
+    /// assert_eq!(writer.into_writer(), [0b11101_011]);
     /// ```
-    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    fn write_reversed<U>(&mut self, bits: u32, value: U) -> io::Result<()>
     where
         U: Numeric,
     {
@@ -482,393 +868,3626 @@ impl<W: io::Write, E: Endianness> BitWrite for BitWriter<W, E> {
                 io::ErrorKind::InvalidInput,
                 "excessive value for bits written",
             ))
-        } else if bits < self.bitqueue.remaining_len() {
-            self.bitqueue.push(bits, value.to_u8());
-            Ok(())
         } else {
-            let mut acc = BitQueue::from_value(value, bits);
-            write_unaligned(&mut self.writer, &mut acc, &mut self.bitqueue)?;
-            write_aligned(&mut self.writer, &mut acc)?;
-            self.bitqueue.push(acc.len(), acc.value().to_u8());
-            Ok(())
+            self.write(bits, reverse_bits(bits, value))
         }
     }
 
+    /// Writes a value as binary-coded decimal
+    /// with the given number of 4-bit digits,
+    /// most significant digit first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput` if the value
+    /// doesn't fit in the given number of digits,
+    /// or if more than 19 digits are requested.
+    ///
     /// # Examples
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write_signed(4, -5).unwrap();
-    /// writer.write_signed(4, 7).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// writer.write_bcd(4, 1234).unwrap();
+    /// writer.write_bcd(2, 5).unwrap();
+    /// assert!(writer.write_bcd(2, 100).is_err());
+    /// assert_eq!(writer.into_writer(), [0x12, 0x34, 0x05]);
     /// ```
     ///
     /// ```
     /// use std::io::Write;
     /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
-    /// writer.write_signed(4, 7).unwrap();
-    /// writer.write_signed(4, -5).unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// writer.write_bcd(4, 1234).unwrap();
+    /// writer.write_bcd(2, 5).unwrap();
+    /// assert!(writer.write_bcd(2, 100).is_err());
+    /// assert_eq!(writer.into_writer(), [0x21, 0x43, 0x50]);
     /// ```
     #[inline]
-    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
-    where
-        S: SignedNumeric,
-    {
-        E::write_signed(self, bits, value)
-    }
-
-    #[inline]
-    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
-        if self.byte_aligned() {
-            self.writer.write_all(buf)
-        } else {
-            buf.iter().try_for_each(|b| self.write(8, *b))
-        }
+    fn write_bcd(&mut self, digits: u32, value: u64) -> io::Result<()> {
+        self.write_bcd_with(BcdOrder::MostSignificantFirst, digits, value)
     }
 
+    /// Writes a value as binary-coded decimal
+    /// with the given number of 4-bit digits,
+    /// stored in the given order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput` if the value
+    /// doesn't fit in the given number of digits,
+    /// or if more than 19 digits are requested.
+    ///
     /// # Example
     /// ```
-    /// use std::io::{Write, sink};
-    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
-    /// let mut writer = BitWriter::endian(sink(), BigEndian);
-    /// assert_eq!(writer.byte_aligned(), true);
-    /// writer.write(1, 0).unwrap();
-    /// assert_eq!(writer.byte_aligned(), false);
-    /// writer.write(7, 0).unwrap();
-    /// assert_eq!(writer.byte_aligned(), true);
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, BcdOrder};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_bcd_with(BcdOrder::LeastSignificantFirst, 4, 1234).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x43, 0x21]);
     /// ```
-    #[inline(always)]
-    fn byte_aligned(&self) -> bool {
-        self.bitqueue.is_empty()
+    fn write_bcd_with(&mut self, order: BcdOrder, digits: u32, value: u64) -> io::Result<()> {
+        if digits > BCD_MAX_DIGITS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive digits for BCD value",
+            ));
+        } else if value >= 10u64.pow(digits) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for BCD digits",
+            ));
+        }
+        let mut decimal = [0; BCD_MAX_DIGITS as usize];
+        let decimal = &mut decimal[0..digits as usize];
+        let mut remaining = value;
+        for digit in decimal.iter_mut() {
+            *digit = (remaining % 10) as u8;
+            remaining /= 10;
+        }
+        match order {
+            BcdOrder::MostSignificantFirst => {
+                decimal.iter().rev().try_for_each(|d| self.write(4, *d))
+            }
+            BcdOrder::LeastSignificantFirst => decimal.iter().try_for_each(|d| self.write(4, *d)),
+        }
     }
-}
 
-impl<W: io::Write, E: Endianness> HuffmanWrite<E> for BitWriter<W, E> {
-    /// # Example
+    /// Writes a floating point value to the stream
+    /// by way of its bit pattern, in the stream's endianness.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
     /// ```
     /// use std::io::Write;
-    /// use bitstream_io::{BigEndian, BitWriter, HuffmanWrite};
-    /// use bitstream_io::huffman::compile_write_tree;
-    /// let tree = compile_write_tree(
-    ///     vec![('a', vec![0]),
-    ///          ('b', vec![1, 0]),
-    ///          ('c', vec![1, 1, 0]),
-    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
     /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-    /// writer.write_huffman(&tree, 'b').unwrap();
-    /// writer.write_huffman(&tree, 'c').unwrap();
-    /// writer.write_huffman(&tree, 'd').unwrap();
-    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// writer.write(4, 0b1100).unwrap();
+    /// writer.write_float(3.0f32).unwrap();
+    /// writer.write(4, 0).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b1100_0100, 0b0000_0100, 0, 0, 0]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// use bitstream_io::float::Half;
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_float(Half::from_f64(1.0)).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x00, 0x3C]);
     /// ```
     #[inline]
-    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    fn write_float<F>(&mut self, value: F) -> io::Result<()>
     where
-        T: Ord + Copy,
+        F: FloatNumeric,
     {
-        tree.get(&symbol)
-            .try_for_each(|(bits, value)| self.write(*bits, *value))
-    }
-}
-
-/// For counting the number of bits written but generating no output.
-///
-/// # Example
-/// ```
-/// use bitstream_io::{BigEndian, BitWrite, BitCounter};
-/// let mut writer: BitCounter<u32, BigEndian> = BitCounter::new();
-/// writer.write(1, 0b1).unwrap();
-/// writer.write(2, 0b01).unwrap();
-/// writer.write(5, 0b10111).unwrap();
-/// assert_eq!(writer.written(), 8);
-/// ```
-#[derive(Default)]
-pub struct BitCounter<N, E: Endianness> {
-    bits: N,
-    phantom: PhantomData<E>,
-}
-
-impl<N: Default + Copy, E: Endianness> BitCounter<N, E> {
-    /// Creates new counter
-    #[inline]
-    pub fn new() -> Self {
-        BitCounter {
-            bits: N::default(),
-            phantom: PhantomData,
-        }
-    }
-
-    /// Returns number of bits written
-    #[inline]
-    pub fn written(&self) -> N {
-        self.bits
-    }
-}
-
-impl<N, E> BitWrite for BitCounter<N, E>
-where
-    E: Endianness,
-    N: Copy + AddAssign + From<u32> + Rem<Output = N> + PartialEq,
-{
-    #[inline]
-    fn write_bit(&mut self, _bit: bool) -> io::Result<()> {
-        self.bits += 1.into();
-        Ok(())
+        self.write(F::bits_size(), value.to_bits())
     }
 
-    #[inline]
-    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
-    where
-        U: Numeric,
-    {
-        if bits > U::bits_size() {
+    /// Writes an unsigned fixed point value to the stream
+    /// with the given number of integer and fractional bits,
+    /// rounding it to the nearest representable value in the given mode.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if more than 64 bits are requested,
+    /// if the value is not finite,
+    /// or if the rounded value is too large to fit the given number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, Rounding};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_fixed_point(16, 16, 1.5, Rounding::Nearest).unwrap();
+    /// writer.write_fixed_point(2, 6, 0.01, Rounding::Floor).unwrap();
+    /// writer.write_fixed_point(2, 6, 0.01, Rounding::Ceiling).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x00, 0x01, 0x80, 0x00, 0x00, 0x01]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite, Rounding};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_fixed_point(4, 4, 2.25, Rounding::Nearest).unwrap();
+    /// writer.write_fixed_point(4, 4, 0.09375, Rounding::NearestEven).unwrap();
+    /// assert!(writer.write_fixed_point(4, 4, 16.0, Rounding::Nearest).is_err());
+    /// assert!(writer.write_fixed_point(4, 4, -1.0, Rounding::Nearest).is_err());
+    /// assert_eq!(writer.into_writer(), [0x24, 0x02]);
+    /// ```
+    fn write_fixed_point(
+        &mut self,
+        int_bits: u32,
+        frac_bits: u32,
+        value: f64,
+        rounding: Rounding,
+    ) -> io::Result<()> {
+        let bits = match int_bits.checked_add(frac_bits) {
+            Some(bits) if bits <= 64 => bits,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "excessive bits for type written",
+                ))
+            }
+        };
+        let scaled = rounding.to_fixed_point(frac_bits, value)?;
+        if scaled < 0.0 || scaled >= 2.0f64.powi(bits as i32) {
             Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
-                "excessive bits for type written",
+                "excessive value for bits written",
             ))
-        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+        } else {
+            self.write(bits, scaled as u64)
+        }
+    }
+
+    /// Writes a twos-complement signed fixed point value to the stream
+    /// with the given number of integer bits, including the sign bit,
+    /// and fractional bits,
+    /// rounding it to the nearest representable value in the given mode.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if more than 64 bits are requested,
+    /// if the value is not finite,
+    /// or if the rounded value is too large to fit the given number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, Rounding};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_signed_fixed_point(2, 30, -1.0, Rounding::Nearest).unwrap();
+    /// writer.write_signed_fixed_point(2, 6, -0.01, Rounding::TowardZero).unwrap();
+    /// writer.write_signed_fixed_point(2, 6, -0.01, Rounding::Floor).unwrap();
+    /// assert!(writer.write_signed_fixed_point(2, 6, 2.0, Rounding::Nearest).is_err());
+    /// assert_eq!(writer.into_writer(), [0xC0, 0x00, 0x00, 0x00, 0x00, 0xFF]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite, Rounding};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_signed_fixed_point(4, 4, -2.25, Rounding::Nearest).unwrap();
+    /// assert_eq!(writer.into_writer(), [0xDC]);
+    /// ```
+    fn write_signed_fixed_point(
+        &mut self,
+        int_bits: u32,
+        frac_bits: u32,
+        value: f64,
+        rounding: Rounding,
+    ) -> io::Result<()> {
+        let bits = match int_bits.checked_add(frac_bits) {
+            Some(bits) if bits <= 64 => bits,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "excessive bits for type written",
+                ))
+            }
+        };
+        let scaled = rounding.to_fixed_point(frac_bits, value)?;
+        let limit = 2.0f64.powi(bits as i32 - 1);
+        if bits == 0 || scaled < -limit || scaled >= limit {
             Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "excessive value for bits written",
             ))
         } else {
-            self.bits += bits.into();
-            Ok(())
+            self.write_signed(bits, scaled as i64)
         }
     }
 
+    /// Writes a string followed by a null terminator.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the string itself contains a null byte.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_cstr("foo").unwrap();
+    /// assert!(writer.write_cstr("b\0r").is_err());
+    /// assert_eq!(writer.into_writer(), b"foo\0");
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_cstr("").unwrap();
+    /// assert_eq!(writer.into_writer(), [0]);
+    /// ```
+    fn write_cstr(&mut self, s: &str) -> io::Result<()> {
+        if s.as_bytes().contains(&0) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "null byte in string written",
+            ))
+        } else {
+            self.write_bytes(s.as_bytes())
+                .and_then(|()| self.write(8, 0u8))
+        }
+    }
+
+    /// Writes a string prefixed by its length in bytes,
+    /// which is written as an unsigned value of `len_bits` bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the string's length
+    /// is too large to fit the given number of bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_prefixed_str(4, "foo").unwrap();
+    /// assert!(writer.write_prefixed_str(1, "bar").is_err());
+    /// writer.write(4, 0).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b0011_0110, 0b0110_0110, 0b1111_0110, 0b1111_0000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_prefixed_str(16, "foo").unwrap();
+    /// assert_eq!(writer.into_writer(), [0x03, 0x00, b'f', b'o', b'o']);
+    /// ```
+    fn write_prefixed_str(&mut self, len_bits: u32, s: &str) -> io::Result<()> {
+        self.write(len_bits, s.len() as u64)
+            .and_then(|()| self.write_bytes(s.as_bytes()))
+    }
+
+    /// Writes a value split across several segments of the stream,
+    /// along with all the field's marker bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput` if the value
+    /// is too large for the field's value bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitstream_io::split::{Segment, SplitField};
+    /// const FIELD: SplitField = SplitField::new(&[
+    ///     Segment::Value(3),
+    ///     Segment::Marker { bits: 1, value: 1 },
+    ///     Segment::Value(4),
+    /// ]);
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_split(&FIELD, 0b101_0110).unwrap();
+    /// assert!(writer.write_split(&FIELD, 0b1000_0000).is_err());
+    /// assert_eq!(writer.into_writer(), [0b101_1_0110]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// use bitstream_io::split::{Segment, SplitField};
+    /// const FIELD: SplitField = SplitField::new(&[
+    ///     Segment::Value(3),
+    ///     Segment::Marker { bits: 1, value: 1 },
+    ///     Segment::Value(4),
+    /// ]);
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_split(&FIELD, 0b101_0110).unwrap();
+    /// assert!(writer.write_split(&FIELD, 0b1000_0000).is_err());
+    /// assert_eq!(writer.into_writer(), [0b0110_1_101]);
+    /// ```
+    #[inline]
+    fn write_split(&mut self, field: &SplitField, value: u64) -> io::Result<()> {
+        field.write(self, value)
+    }
+
+    /// Writes a record of values for all the fields in the given schema,
+    /// in the schema's order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream,
+    /// along with any error from writing an individual field.
+    /// Returns an error of kind `InvalidInput` if the values
+    /// don't match the schema's fields.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// use bitstream_io::schema::{FieldKind, FieldValue, Schema};
+    /// let mut schema = Schema::new();
+    /// schema.push("count", FieldKind::Unary1);
+    /// schema.push("value", FieldKind::Unsigned(5));
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_record(&schema, &[FieldValue::Unsigned(2), FieldValue::Unsigned(0b10110)]).unwrap();
+    /// assert!(writer.write_record(&schema, &[FieldValue::Unsigned(2)]).is_err());
+    /// assert!(writer.write_record(&schema, &[FieldValue::Unsigned(2), FieldValue::Bit(true)]).is_err());
+    /// assert_eq!(writer.into_writer(), [0b10110_100]);
+    /// ```
+    #[inline]
+    fn write_record(&mut self, schema: &Schema, values: &[FieldValue]) -> io::Result<()> {
+        schema.write(self, values)
+    }
+
+    /// Writes a symbol to the stream using an adaptive Huffman tree
+    /// and updates the tree with that symbol.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the symbol is too large
+    /// for the tree's symbol size.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitstream_io::huffman::AdaptiveHuffmanTree;
+    /// let mut tree = AdaptiveHuffmanTree::new(8);
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_adaptive_huffman(&mut tree, 0x61).unwrap();
+    /// writer.write_adaptive_huffman(&mut tree, 0x61).unwrap();
+    /// writer.write_adaptive_huffman(&mut tree, 0x62).unwrap();
+    /// assert!(writer.write_adaptive_huffman(&mut tree, 0x100).is_err());
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0x61, 0b1_0_011000, 0b10_000000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// use bitstream_io::huffman::AdaptiveHuffmanTree;
+    /// let mut tree = AdaptiveHuffmanTree::new(4);
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_adaptive_huffman(&mut tree, 0xA).unwrap();
+    /// writer.write_adaptive_huffman(&mut tree, 0xA).unwrap();
+    /// writer.write_adaptive_huffman(&mut tree, 0xA).unwrap();
+    /// writer.write_adaptive_huffman(&mut tree, 0xA).unwrap();
+    /// assert_eq!(tree.frequency(0xA), 4);
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b0111_1010]);
+    /// ```
+    fn write_adaptive_huffman(
+        &mut self,
+        tree: &mut AdaptiveHuffmanTree,
+        symbol: u32,
+    ) -> io::Result<()> {
+        tree.write(self, symbol)
+    }
+
+    /// Writes the entirety of a byte buffer to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(8, 0x66).unwrap();
+    /// writer.write(8, 0x6F).unwrap();
+    /// writer.write(8, 0x6F).unwrap();
+    /// writer.write_bytes(b"bar").unwrap();
+    /// assert_eq!(writer.into_writer(), b"foobar");
+    /// ```
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        buf.iter().try_for_each(|b| self.write(8, *b))
+    }
+
+    /// Writes the given number of bits from a buffer of bytes,
+    /// at any bit position.
+    /// This is the counterpart of `BitRead::read_bits_to_vec`:
+    /// whole bytes are written as by `write_bytes` and,
+    /// if `bits` is not a multiple of 8,
+    /// the final byte holds the value of the remaining bits.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput` if the buffer's length
+    /// doesn't match the number of bits,
+    /// or if the final byte's value is too large for the remaining bits.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(1, 1).unwrap();
+    /// writer.write_bits_from_bytes(19, &[0x01, 0x03, 0b111]).unwrap();
+    /// assert!(writer.write_bits_from_bytes(19, &[0x01, 0x03]).is_err());
+    /// writer.write(4, 0).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b1000_0000, 0b1000_0001, 0b1111_0000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write(1, 1).unwrap();
+    /// writer.write_bits_from_bytes(19, &[0x01, 0x01, 0b111]).unwrap();
+    /// assert!(writer.write_bits_from_bytes(19, &[0x01, 0x01]).is_err());
+    /// writer.write(4, 0).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b0000_0011, 0b0000_0010, 0b0000_1110]);
+    /// ```
+    fn write_bits_from_bytes(&mut self, bits: u64, buf: &[u8]) -> io::Result<()> {
+        let whole = bits / 8;
+        let partial = (bits % 8) as u32;
+        if (buf.len() as u64) != whole + u64::from(partial > 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "buffer length doesn't match bits written",
+            ));
+        }
+        let (bytes, last) = buf.split_at(whole as usize);
+        self.write_bytes(bytes)?;
+        match last {
+            [last] => self.write(partial, *last),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes `value` number of 1 bits to the stream
+    /// and then writes a 0 bit.  This field is variably-sized.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_unary0(0).unwrap();
+    /// writer.write_unary0(3).unwrap();
+    /// writer.write_unary0(10).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b01110111, 0b11111110]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_unary0(0).unwrap();
+    /// writer.write_unary0(3).unwrap();
+    /// writer.write_unary0(10).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b11101110, 0b01111111]);
+    /// ```
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        match value {
+            0 => self.write_bit(false),
+            bits @ 1..=31 => self
+                .write(value, (1u32 << bits) - 1)
+                .and_then(|()| self.write_bit(false)),
+            32 => self
+                .write(value, 0xFFFF_FFFFu32)
+                .and_then(|()| self.write_bit(false)),
+            bits @ 33..=63 => self
+                .write(value, (1u64 << bits) - 1)
+                .and_then(|()| self.write_bit(false)),
+            64 => self
+                .write(value, 0xFFFF_FFFF_FFFF_FFFFu64)
+                .and_then(|()| self.write_bit(false)),
+            // longer values are written as a run
+            value => self
+                .write_run(true, u64::from(value))
+                .and_then(|()| self.write_bit(false)),
+        }
+    }
+
+    /// Writes `value` number of 0 bits to the stream
+    /// and then writes a 1 bit.  This field is variably-sized.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_unary1(0).unwrap();
+    /// writer.write_unary1(3).unwrap();
+    /// writer.write_unary1(10).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10001000, 0b00000001]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_unary1(0).unwrap();
+    /// writer.write_unary1(3).unwrap();
+    /// writer.write_unary1(10).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b00010001, 0b10000000]);
+    /// ```
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        match value {
+            0 => self.write_bit(true),
+            1..=32 => self.write(value, 0u32).and_then(|()| self.write_bit(true)),
+            33..=64 => self.write(value, 0u64).and_then(|()| self.write_bit(true)),
+            // longer values are written as a run
+            value => self
+                .write_run(false, u64::from(value))
+                .and_then(|()| self.write_bit(true)),
+        }
+    }
+
+    /// Writes `value` number of 1 bits to the stream
+    /// and then writes a 0 bit, like `write_unary0`,
+    /// unless `value` equals `max`, in which case
+    /// only the 1 bits are written.
+    /// This is the counterpart of `BitRead::read_unary0_capped`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput`
+    /// if `value` is larger than `max`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_unary0_capped(4, 0).unwrap();
+    /// writer.write_unary0_capped(4, 2).unwrap();
+    /// writer.write_unary0_capped(4, 4).unwrap();
+    /// assert!(writer.write_unary0_capped(4, 5).is_err());
+    /// assert_eq!(writer.into_writer(), [0b01101111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_unary0_capped(4, 0).unwrap();
+    /// writer.write_unary0_capped(4, 2).unwrap();
+    /// writer.write_unary0_capped(4, 4).unwrap();
+    /// assert!(writer.write_unary0_capped(4, 5).is_err());
+    /// assert_eq!(writer.into_writer(), [0b11110110]);
+    /// ```
+    fn write_unary0_capped(&mut self, max: u32, value: u32) -> io::Result<()> {
+        match value.cmp(&max) {
+            Ordering::Less => self.write_unary0(value),
+            Ordering::Equal => self.write_run(true, u64::from(max)),
+            Ordering::Greater => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for unary maximum",
+            )),
+        }
+    }
+
+    /// Writes `value` number of 0 bits to the stream
+    /// and then writes a 1 bit, like `write_unary1`,
+    /// unless `value` equals `max`, in which case
+    /// only the 0 bits are written.
+    /// This is the counterpart of `BitRead::read_unary1_capped`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput`
+    /// if `value` is larger than `max`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_unary1_capped(4, 0).unwrap();
+    /// writer.write_unary1_capped(4, 2).unwrap();
+    /// writer.write_unary1_capped(4, 4).unwrap();
+    /// assert!(writer.write_unary1_capped(4, 5).is_err());
+    /// assert_eq!(writer.into_writer(), [0b10010000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_unary1_capped(4, 0).unwrap();
+    /// writer.write_unary1_capped(4, 2).unwrap();
+    /// writer.write_unary1_capped(4, 4).unwrap();
+    /// assert!(writer.write_unary1_capped(4, 5).is_err());
+    /// assert_eq!(writer.into_writer(), [0b00001001]);
+    /// ```
+    fn write_unary1_capped(&mut self, max: u32, value: u32) -> io::Result<()> {
+        match value.cmp(&max) {
+            Ordering::Less => self.write_unary1(value),
+            Ordering::Equal => self.write_run(false, u64::from(max)),
+            Ordering::Greater => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for unary maximum",
+            )),
+        }
+    }
+
+    /// Writes `value` number of 1 bits to the stream
+    /// and then writes a 0 bit, like `write_unary0`,
+    /// but for values of any size.
+    /// Returns the number of bits written.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// assert_eq!(writer.write_unary0_u64(3).unwrap(), 4);
+    /// assert_eq!(writer.write_unary0_u64(10).unwrap(), 11);
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b11101111, 0b11111100]);
+    ///
+    /// let mut writer = BitWriter::endian(std::io::sink(), BigEndian);
+    /// assert_eq!(writer.write_unary0_u64(1 << 33).unwrap(), (1 << 33) + 1);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// assert_eq!(writer.write_unary0_u64(3).unwrap(), 4);
+    /// assert_eq!(writer.write_unary0_u64(10).unwrap(), 11);
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b11110111, 0b00111111]);
+    /// ```
+    fn write_unary0_u64(&mut self, value: u64) -> io::Result<u64> {
+        self.write_run(true, value)?;
+        self.write_bit(false)?;
+        Ok(value + 1)
+    }
+
+    /// Writes `value` number of 0 bits to the stream
+    /// and then writes a 1 bit, like `write_unary1`,
+    /// but for values of any size.
+    /// Returns the number of bits written.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// assert_eq!(writer.write_unary1_u64(3).unwrap(), 4);
+    /// assert_eq!(writer.write_unary1_u64(10).unwrap(), 11);
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b00010000, 0b00000010]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// assert_eq!(writer.write_unary1_u64(3).unwrap(), 4);
+    /// assert_eq!(writer.write_unary1_u64(10).unwrap(), 11);
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b00001000, 0b01000000]);
+    /// ```
+    fn write_unary1_u64(&mut self, value: u64) -> io::Result<u64> {
+        self.write_run(false, value)?;
+        self.write_bit(true)?;
+        Ok(value + 1)
+    }
+
+    /// Writes `count` copies of the given bit to the stream.
+    /// Unlike the unary methods, no terminating bit is written
+    /// and the run may be of any length.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_run(true, 3).unwrap();
+    /// writer.write_run(false, 15).unwrap();
+    /// writer.write_run(true, 6).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b11100000, 0b00000000, 0b00111111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_run(true, 3).unwrap();
+    /// writer.write_run(false, 15).unwrap();
+    /// writer.write_run(true, 6).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b00000111, 0b00000000, 0b11111100]);
+    /// ```
+    fn write_run(&mut self, bit: bool, mut count: u64) -> io::Result<()> {
+        let word = if bit { u64::MAX } else { 0 };
+        while count > 64 {
+            self.write(64, word)?;
+            count -= 64;
+        }
+        if count > 0 {
+            self.write(count as u32, word >> (64 - count))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes a reserved field of the given number of bits,
+    /// filled with the pattern required by the given policy.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, ReservedPolicy};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(2, 0b10u8).unwrap();
+    /// writer.write_reserved(3, ReservedPolicy::Ones).unwrap();
+    /// writer.write_reserved(3, ReservedPolicy::Zeros).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10_111_000]);
+    /// ```
+    #[inline]
+    fn write_reserved(&mut self, bits: u32, policy: ReservedPolicy) -> io::Result<()> {
+        self.write_run(policy == ReservedPolicy::Ones, bits.into())
+    }
+
+    /// Writes a value to a reserved field of the given number of bits,
+    /// but only if it's the pattern required by the given policy.
+    /// This is for code which fills in reserved fields from values
+    /// it's been handed, and which must not emit anything non-compliant.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput` without writing anything
+    /// if the value doesn't match the reserved pattern.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Write, ErrorKind};
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite, ReservedPolicy};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_reserved_checked(4, ReservedPolicy::Ones, 0b1111u8).unwrap();
+    /// let err = writer.write_reserved_checked(4, ReservedPolicy::Zeros, 0b0100u8).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    /// writer.write_reserved_checked(4, ReservedPolicy::Zeros, 0u8).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b0000_1111]);
+    /// ```
+    fn write_reserved_checked<U>(
+        &mut self,
+        bits: u32,
+        policy: ReservedPolicy,
+        value: U,
+    ) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if value == policy.pattern(bits) {
+            self.write(bits, value)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "value doesn't match reserved bits written",
+            ))
+        }
+    }
+
+    /// Writes each bit yielded by the given iterator to the stream,
+    /// in order.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let bitmap = [true, false, true, true, false, false, false, true];
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_bits_from(bitmap.iter().copied()).unwrap();
+    /// writer.write_bits_from("1111".chars().map(|c| c == '1')).unwrap();
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110001, 0b11110000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let bitmap = [true, false, true, true, false, false, false, true];
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_bits_from(bitmap.iter().copied()).unwrap();
+    /// writer.write_bits_from("1111".chars().map(|c| c == '1')).unwrap();
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10001101, 0b00001111]);
+    /// ```
+    fn write_bits_from<I>(&mut self, bits: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        bits.into_iter().try_for_each(|bit| self.write_bit(bit))
+    }
+
+    /// Writes all the bits of the given `BitSlice` to the stream,
+    /// in order.
+    ///
+    /// Requires the `bitvec` feature.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// use bitvec::prelude::*;
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_bitslice(bits![u8, Msb0; 1, 0, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1]).unwrap();
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110001, 0b11110000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// use bitvec::prelude::*;
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_bitslice(bits![u8, Msb0; 1, 0, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1]).unwrap();
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10001101, 0b00001111]);
+    /// ```
+    #[cfg(feature = "bitvec")]
+    fn write_bitslice(&mut self, bits: &BitSlice<u8, Msb0>) -> io::Result<()> {
+        self.write_bits_from(bits.iter().by_vals())
+    }
+
+    /// Writes an unsigned value to the stream as an order-`k`
+    /// exponential-Golomb code, where order 0 is the `ue(v)` code
+    /// used by H.264 and higher orders spend `k` more bits
+    /// on each code to shorten those of larger values.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the order is larger than 64.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_exp_golomb(0, 0).unwrap(); // 1
+    /// writer.write_exp_golomb(0, 1).unwrap(); // 010
+    /// writer.write_exp_golomb(0, 2).unwrap(); // 011
+    /// writer.write_exp_golomb(0, 3).unwrap(); // 00100
+    /// writer.write_exp_golomb(2, 0).unwrap(); // 100
+    /// writer.write_exp_golomb(2, 5).unwrap(); // 01001
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10100110, 0b01001000, 0b10010000]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_exp_golomb(0, 0).unwrap();
+    /// writer.write_exp_golomb(0, 1).unwrap();
+    /// writer.write_exp_golomb(0, 2).unwrap();
+    /// writer.write_exp_golomb(0, 3).unwrap();
+    /// writer.write_exp_golomb(2, 0).unwrap();
+    /// writer.write_exp_golomb(2, 5).unwrap();
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.into_writer(), [0b01100101, 0b00010010, 0b00000011]);
+    /// ```
+    fn write_exp_golomb(&mut self, k: u32, value: u64) -> io::Result<()> {
+        if k > 64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive order for exp-Golomb code",
+            ));
+        }
+        let value = u128::from(value) + (1 << k);
+        let bits = 127 - value.leading_zeros();
+        self.write_unary1(bits - k)?;
+        self.write(bits, (value ^ (1 << bits)) as u64)
+    }
+
+    /// Returns true if the stream is aligned at a whole byte.
+    fn byte_aligned(&self) -> bool;
+
+    /// Pads the stream with 0 bits until it is aligned at a whole byte.
+    /// Does nothing if the stream is already aligned.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(1, 0).unwrap();
+    /// writer.byte_align().unwrap();
+    /// writer.write(8, 0xFF).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x00, 0xFF]);
+    /// ```
+    fn byte_align(&mut self) -> io::Result<()> {
+        while !self.byte_aligned() {
+            self.write_bit(false)?;
+        }
+        Ok(())
+    }
+
+    /// Builds and writes a complex type
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's `to_writer` method.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, ToBitStream};
+    ///
+    /// struct FrameHeader {
+    ///     version: u8,
+    ///     keyframe: bool,
+    ///     size: u16,
+    /// }
+    ///
+    /// impl ToBitStream for FrameHeader {
+    ///     type Error = std::io::Error;
+    ///
+    ///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+    ///         w.write(3, self.version)?;
+    ///         w.write_bit(self.keyframe)?;
+    ///         w.write(12, self.size)
+    ///     }
+    /// }
+    ///
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.build(&FrameHeader { version: 5, keyframe: true, size: 0x123 }).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b101_1_0001, 0x23]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite, ToBitStream};
+    ///
+    /// struct FrameHeader {
+    ///     version: u8,
+    ///     keyframe: bool,
+    ///     size: u16,
+    /// }
+    ///
+    /// impl ToBitStream for FrameHeader {
+    ///     type Error = std::io::Error;
+    ///
+    ///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+    ///         w.write(3, self.version)?;
+    ///         w.write_bit(self.keyframe)?;
+    ///         w.write(12, self.size)
+    ///     }
+    /// }
+    ///
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.build(&FrameHeader { version: 5, keyframe: true, size: 0x123 }).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b0011_1_101, 0x12]);
+    /// ```
+    #[inline]
+    fn build<T: ToBitStream>(&mut self, build: &T) -> Result<(), T::Error> {
+        build.to_writer(self)
+    }
+}
+
+/// Implemented by complex types that can be written to a `BitWrite`
+/// with the `build` method.
+pub trait ToBitStream {
+    /// Error generated during building, such as `io::Error`
+    type Error;
+
+    /// Writes the type to the given bitstream
+    fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> Result<(), Self::Error>
+    where
+        Self: Sized;
+
+    /// Returns the number of bits the type occupies when written,
+    /// without generating any output.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's `to_writer` method.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BitWrite, ToBitStream};
+    ///
+    /// struct Marker(u8);
+    ///
+    /// impl ToBitStream for Marker {
+    ///     type Error = std::io::Error;
+    ///
+    ///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+    ///         w.write_unary1(u32::from(self.0))?;
+    ///         w.write(4, 0b1111u8)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Marker(0).bits_len().unwrap(), 5);
+    /// assert_eq!(Marker(6).bits_len().unwrap(), 11);
+    /// ```
+    fn bits_len(&self) -> Result<u64, Self::Error>
+    where
+        Self: Sized,
+    {
+        let mut counter = BitCounter::<u64, BigEndian>::new();
+        self.to_writer(&mut counter)?;
+        Ok(counter.written())
+    }
+
+    /// Returns the number of bytes the type occupies when written
+    /// starting from a byte-aligned position,
+    /// rounding up any final partial byte
+    /// to account for padding it to alignment.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's `to_writer` method.
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BitWrite, ToBitStream};
+    ///
+    /// struct Marker(u8);
+    ///
+    /// impl ToBitStream for Marker {
+    ///     type Error = std::io::Error;
+    ///
+    ///     fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+    ///         w.write_unary1(u32::from(self.0))?;
+    ///         w.write(4, 0b1111u8)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Marker(3).bytes_len().unwrap(), 1);
+    /// assert_eq!(Marker(4).bytes_len().unwrap(), 2);
+    /// ```
+    fn bytes_len(&self) -> Result<u64, Self::Error>
+    where
+        Self: Sized,
+    {
+        let mut counter = BitCounter::<u64, BigEndian>::new();
+        self.to_writer(&mut counter)?;
+        Ok(counter.written_bytes())
+    }
+}
+
+/// An object-safe subset of `BitWrite` which works
+/// with values of at most 64 bits, so that writers may be used
+/// as `dyn BitWriteCore` trait objects,
+/// such as to hand a codec plugin whichever writer is in use.
+///
+/// This is implemented for everything which implements `BitWrite`.
+/// In turn, `dyn BitWriteCore` implements `BitWrite` itself
+/// so that all of its methods remain available,
+/// except that values wider than 64 bits may not be written.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite, BitWriteCore, BitCounter};
+/// fn encode(writer: &mut dyn BitWriteCore) {
+///     writer.write(4, 0b1011u8).unwrap();
+///     writer.write_signed(4, -1i8).unwrap();
+///     writer.write_unary0(2).unwrap();
+///     writer.byte_align().unwrap();
+/// }
+/// let mut writers: Vec<Box<dyn BitWriteCore>> = vec![
+///     Box::new(BitWriter::endian(Vec::new(), BigEndian)),
+///     Box::new(BitCounter::<u32, BigEndian>::new()),
+/// ];
+/// for writer in writers.iter_mut() {
+///     encode(writer.as_mut());
+///     assert!(writer.core_byte_aligned());
+/// }
+/// ```
+pub trait BitWriteCore {
+    /// Writes an unsigned value of up to 64 bits to the stream,
+    /// as by `BitWrite::write`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64
+    /// or the value is too large to fit the given number of bits.
+    fn write_u64(&mut self, bits: u32, value: u64) -> io::Result<()>;
+
+    /// Writes a twos-complement signed value of up to 64 bits
+    /// to the stream, as by `BitWrite::write_signed`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the number of bits is larger than 64
+    /// or the value is too large to fit the given number of bits.
+    fn write_i64(&mut self, bits: u32, value: i64) -> io::Result<()>;
+
+    /// Writes the entirety of a byte buffer to the stream,
+    /// as by `BitWrite::write_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn core_write_bytes(&mut self, buf: &[u8]) -> io::Result<()>;
+
+    /// Returns true if the stream is aligned at a whole byte.
+    fn core_byte_aligned(&self) -> bool;
+
+    /// Pads the stream with 0 bits until it is aligned at a whole byte,
+    /// as by `BitWrite::byte_align`.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn core_byte_align(&mut self) -> io::Result<()>;
+}
+
+impl<W: BitWrite> BitWriteCore for W {
+    #[inline]
+    fn write_u64(&mut self, bits: u32, value: u64) -> io::Result<()> {
+        self.write(bits, value)
+    }
+
+    #[inline]
+    fn write_i64(&mut self, bits: u32, value: i64) -> io::Result<()> {
+        self.write_signed(bits, value)
+    }
+
+    #[inline]
+    fn core_write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.write_bytes(buf)
+    }
+
+    #[inline]
+    fn core_byte_aligned(&self) -> bool {
+        self.byte_aligned()
+    }
+
+    #[inline]
+    fn core_byte_align(&mut self) -> io::Result<()> {
+        self.byte_align()
+    }
+}
+
+impl<'a> BitWrite for dyn BitWriteCore + 'a {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.write_u64(1, u64::from(bit))
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() || bits > 64 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            self.write_u64(bits, numeric_to_u128(value) as u64)
+        }
+    }
+
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        if bits > S::bits_size() || bits > 64 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else {
+            self.write_i64(bits, signed_to_i64(value))
+        }
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.core_write_bytes(buf)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.core_byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) -> io::Result<()> {
+        self.core_byte_align()
+    }
+}
+
+impl<W: BitWrite + ?Sized> BitWrite for &mut W {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        (**self).write_bit(bit)
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        (**self).write(bits, value)
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        (**self).write_signed(bits, value)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        (**self).write_bytes(buf)
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        (**self).write_unary0(value)
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        (**self).write_unary1(value)
+    }
+
+    #[inline]
+    fn write_bits_from<I>(&mut self, bits: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        (**self).write_bits_from(bits)
+    }
+
+    #[inline]
+    fn write_run(&mut self, bit: bool, count: u64) -> io::Result<()> {
+        (**self).write_run(bit, count)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        (**self).byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) -> io::Result<()> {
+        (**self).byte_align()
+    }
+}
+
+/// A trait for anything that can write Huffman codes
+/// of a given endianness to an output stream
+pub trait HuffmanWrite<E: Endianness> {
+    /// Writes Huffman code for the given symbol to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy;
+
+    /// Writes Huffman codes for all the given symbols to the stream.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, HuffmanWrite};
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_huffman_all(&tree, &['b', 'c', 'd']).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, HuffmanWrite};
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_huffman_all(&tree, &['b', 'c', 'd']).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b11101101]);
+    /// ```
+    fn write_huffman_all<T>(
+        &mut self,
+        tree: &WriteHuffmanTree<E, T>,
+        symbols: &[T],
+    ) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        symbols
+            .iter()
+            .try_for_each(|symbol| self.write_huffman(tree, *symbol))
+    }
+
+    /// Writes Huffman code for the given symbol to the stream
+    /// and returns the number of bits written.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, HuffmanWrite};
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// assert_eq!(writer.write_huffman_len(&tree, 'b').unwrap(), 2);
+    /// assert_eq!(writer.write_huffman_len(&tree, 'c').unwrap(), 3);
+    /// assert_eq!(writer.write_huffman_len(&tree, 'd').unwrap(), 3);
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, HuffmanWrite};
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// assert_eq!(writer.write_huffman_len(&tree, 'b').unwrap(), 2);
+    /// assert_eq!(writer.write_huffman_len(&tree, 'c').unwrap(), 3);
+    /// assert_eq!(writer.write_huffman_len(&tree, 'd').unwrap(), 3);
+    /// assert_eq!(writer.into_writer(), [0b11101101]);
+    /// ```
+    fn write_huffman_len<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<u32>
+    where
+        T: Ord + Copy,
+    {
+        self.write_huffman(tree, symbol)?;
+        Ok(tree.code_len(&symbol).unwrap_or(0))
+    }
+}
+
+impl<W: io::Write, E: Endianness> BitWrite for BitWriter<W, E> {
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(false).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(false).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(false).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_bit(false).unwrap();
+    /// writer.write_bit(true).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.bitqueue.push(1, if bit { 1 } else { 0 });
+        #[cfg(feature = "observer")]
+        self.observed.wrote(1);
+        if self.bitqueue.is_full() {
+            write_byte(&mut self.writer, self.bitqueue.pop(8))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write(1, 0b1).unwrap();
+    /// writer.write(2, 0b01).unwrap();
+    /// writer.write(5, 0b10111).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write(1, 0b1).unwrap();
+    /// writer.write(2, 0b11).unwrap();
+    /// writer.write(5, 0b10110).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Write, sink};
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut w = BitWriter::endian(sink(), BigEndian);
+    /// assert!(w.write(9, 0u8).is_err());    // can't write  u8 in 9 bits
+    /// assert!(w.write(17, 0u16).is_err());  // can't write u16 in 17 bits
+    /// assert!(w.write(33, 0u32).is_err());  // can't write u32 in 33 bits
+    /// assert!(w.write(65, 0u64).is_err());  // can't write u64 in 65 bits
+    /// assert!(w.write(1, 2).is_err());      // can't write   2 in 1 bit
+    /// assert!(w.write(2, 4).is_err());      // can't write   4 in 2 bits
+    /// assert!(w.write(3, 8).is_err());      // can't write   8 in 3 bits
+    /// assert!(w.write(4, 16).is_err());     // can't write  16 in 4 bits
+    /// ```
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else if bits < self.bitqueue.remaining_len() {
+            self.bitqueue.push(bits, value.to_u8());
+            #[cfg(feature = "observer")]
+            self.observed.wrote(bits.into());
+            Ok(())
+        } else {
+            // the partial byte is completed and any whole bytes
+            // are gathered so they may be written all at once
+            let mut acc = BitQueue::<E, U>::from_value(value, bits);
+            let mut buf = [0; 17];
+            let mut len = 0;
+            if !self.bitqueue.is_empty() {
+                let transfer = self.bitqueue.remaining_len();
+                self.bitqueue.push(transfer, acc.pop(transfer).to_u8());
+                buf[0] = self.bitqueue.pop_all();
+                len = 1;
+            }
+            while acc.len() >= 8 {
+                buf[len] = acc.pop(8).to_u8();
+                len += 1;
+            }
+            self.bitqueue.push(acc.len(), acc.value().to_u8());
+            #[cfg(feature = "observer")]
+            self.observed.wrote(bits.into());
+            self.writer.write_all(&buf[0..len])
+        }
+    }
+
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_signed(4, -5).unwrap();
+    /// writer.write_signed(4, 7).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_signed(4, 7).unwrap();
+    /// writer.write_signed(4, -5).unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        if S::bits_size() <= 64 && bits > 0 && bits < S::bits_size() {
+            // an in-range value's twos-complement bits
+            // are written in a single pass
+            let value = signed_to_i64(value);
+            let limit = 1i64 << (bits - 1);
+            if (-limit..limit).contains(&value) {
+                return self.write(bits, (value as u64) & ((1 << bits) - 1));
+            }
+        }
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        if self.byte_aligned() {
+            self.writer.write_all(buf)?;
+            #[cfg(feature = "observer")]
+            self.observed.wrote(buf.len() as u64 * 8);
+            Ok(())
+        } else {
+            buf.iter().try_for_each(|b| self.write(8, *b))
+        }
+    }
+
+    fn write_run(&mut self, bit: bool, mut count: u64) -> io::Result<()> {
+        while count > 0 && !self.byte_aligned() {
+            self.write_bit(bit)?;
+            count -= 1;
+        }
+
+        // whole bytes of the run are written a buffer at a time
+        let chunk = [if bit { 0xFF } else { 0x00 }; 4096];
+        let mut bytes = count / 8;
+        while bytes > 0 {
+            let size = bytes.min(chunk.len() as u64);
+            self.writer.write_all(&chunk[0..size as usize])?;
+            #[cfg(feature = "observer")]
+            self.observed.wrote(size * 8);
+            bytes -= size;
+        }
+        match count % 8 {
+            0 => Ok(()),
+            bits => self.write(bits as u32, chunk[0] >> (8 - bits)),
+        }
+    }
+
+    fn write_bits_from<I>(&mut self, bits: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        // bits are gathered into words before being written
+        let mut acc = BitQueue::<E, u32>::new();
+        for bit in bits {
+            acc.push(1, bit as u32);
+            if acc.is_full() {
+                self.write(32, acc.pop_all())?;
+            }
+        }
+        let len = acc.len();
+        match len {
+            0 => Ok(()),
+            len => self.write(len, acc.value()),
+        }
+    }
+
+    /// # Example
+    /// ```
+    /// use std::io::{Write, sink};
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite};
+    /// let mut writer = BitWriter::endian(sink(), BigEndian);
+    /// assert_eq!(writer.byte_aligned(), true);
+    /// writer.write(1, 0).unwrap();
+    /// assert_eq!(writer.byte_aligned(), false);
+    /// writer.write(7, 0).unwrap();
+    /// assert_eq!(writer.byte_aligned(), true);
+    /// ```
+    #[inline(always)]
+    fn byte_aligned(&self) -> bool {
+        self.bitqueue.is_empty()
+    }
+}
+
+impl<W: io::Write, E: Endianness> HuffmanWrite<E> for BitWriter<W, E> {
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, HuffmanWrite};
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_huffman(&tree, 'b').unwrap();
+    /// writer.write_huffman(&tree, 'c').unwrap();
+    /// writer.write_huffman(&tree, 'd').unwrap();
+    /// assert_eq!(writer.into_writer(), [0b10110111]);
+    /// ```
+    #[inline]
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        tree.get(&symbol)
+            .try_for_each(|(bits, value)| self.write(*bits, *value))
+    }
+}
+
+impl<W, E> HuffmanWrite<E> for &mut W
+where
+    W: HuffmanWrite<E> + ?Sized,
+    E: Endianness,
+{
+    #[inline]
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        (**self).write_huffman(tree, symbol)
+    }
+}
+
+/// A running total of bits, as kept by a [`BitCounter`]
+pub trait Counter: Copy + Default {
+    /// Returns the total with the given number of bits added,
+    /// or `None` if the sum is too large for the type
+    fn checked_add_bits(self, bits: u64) -> Option<Self>;
+
+    /// Returns true if the total is a whole number of bytes
+    fn whole_bytes(self) -> bool;
+
+    /// Returns the total in bytes, rounding up any partial byte
+    fn bytes(self) -> Self;
+}
+
+macro_rules! define_counter {
+    ($t:ty) => {
+        impl Counter for $t {
+            #[inline]
+            fn checked_add_bits(self, bits: u64) -> Option<Self> {
+                <$t>::try_from(bits)
+                    .ok()
+                    .and_then(|bits| self.checked_add(bits))
+            }
+
+            #[inline]
+            fn whole_bytes(self) -> bool {
+                self & 7 == 0
+            }
+
+            #[inline]
+            fn bytes(self) -> Self {
+                if self.whole_bytes() {
+                    self >> 3
+                } else {
+                    (self >> 3) + 1
+                }
+            }
+        }
+    };
+}
+
+define_counter!(u16);
+define_counter!(u32);
+define_counter!(u64);
+define_counter!(u128);
+define_counter!(usize);
+define_counter!(i64);
+define_counter!(i128);
+
+/// Totals are kept exactly, so one which would pass
+/// the largest integer an `f64` holds without rounding is an overflow
+impl Counter for f64 {
+    #[inline]
+    fn checked_add_bits(self, bits: u64) -> Option<Self> {
+        const MAX_EXACT: u64 = 1 << f64::MANTISSA_DIGITS;
+
+        // totals only ever hold whole numbers no larger than MAX_EXACT
+        (self as u64)
+            .checked_add(bits)
+            .filter(|total| *total <= MAX_EXACT)
+            .map(|total| total as f64)
+    }
+
+    #[inline]
+    fn whole_bytes(self) -> bool {
+        self % 8.0 == 0.0
+    }
+
+    #[inline]
+    fn bytes(self) -> Self {
+        (self / 8.0).ceil()
+    }
+}
+
+/// An error from a `BitCounter` or `BitRecorder`
+/// when a write would overflow the type counting its bits.
+///
+/// This is returned as the inner error
+/// of an `io::Error` of kind `Other`,
+/// from which `from_io` retrieves it,
+/// so that an overflowing count may be told apart
+/// from an invalid write.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct CounterOverflowError {
+    requested: u64,
+}
+
+impl CounterOverflowError {
+    /// Returns the number of bits the write needed
+    #[inline]
+    pub fn requested(&self) -> u64 {
+        self.requested
+    }
+
+    /// Returns the `CounterOverflowError` wrapped by an `io::Error`, if any
+    pub fn from_io(err: &io::Error) -> Option<&CounterOverflowError> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<CounterOverflowError>())
+    }
+}
+
+impl fmt::Display for CounterOverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} more bits overflow counter", self.requested)
+    }
+}
+
+impl error::Error for CounterOverflowError {}
+
+impl From<CounterOverflowError> for io::Error {
+    #[inline]
+    fn from(err: CounterOverflowError) -> Self {
+        io::Error::new(io::ErrorKind::Other, err)
+    }
+}
+
+/// For counting the number of bits written but generating no output.
+///
+/// Writes which would overflow the counter's type
+/// return a `CounterOverflowError` and leave the count unchanged.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWrite, BitCounter};
+/// let mut writer: BitCounter<u32, BigEndian> = BitCounter::new();
+/// writer.write(1, 0b1).unwrap();
+/// writer.write(2, 0b01).unwrap();
+/// writer.write(5, 0b10111).unwrap();
+/// assert_eq!(writer.written(), 8);
+/// ```
+pub struct BitCounter<N, E: Endianness> {
+    bits: N,
+    phantom: PhantomData<E>,
+}
+
+impl<N: Counter, E: Endianness> Default for BitCounter<N, E> {
+    #[inline]
+    fn default() -> Self {
+        BitCounter::new()
+    }
+}
+
+impl<N: Counter, E: Endianness> Clone for BitCounter<N, E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        BitCounter {
+            bits: self.bits,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<N: fmt::Debug, E: Endianness> fmt::Debug for BitCounter<N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitCounter")
+            .field("bits", &self.bits)
+            .finish()
+    }
+}
+
+impl<N: Counter, E: Endianness> BitCounter<N, E> {
+    /// Creates new counter
+    #[inline]
+    pub fn new() -> Self {
+        BitCounter {
+            bits: N::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns number of bits written
+    #[inline]
+    pub fn written(&self) -> N {
+        self.bits
+    }
+
+    /// Returns number of bytes written, rounding up any partial byte
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitWrite, BitCounter};
+    /// let mut writer: BitCounter<u64, BigEndian> = BitCounter::new();
+    /// writer.write_bytes(b"foo").unwrap();
+    /// assert_eq!(writer.written_bytes(), 3);
+    /// writer.write_bit(true).unwrap();
+    /// assert_eq!(writer.written_bytes(), 4);
+    /// ```
+    #[inline]
+    pub fn written_bytes(&self) -> N {
+        self.bits.bytes()
+    }
+
+    fn add_bits(&mut self, bits: u64) -> io::Result<()> {
+        match self.bits.checked_add_bits(bits) {
+            Some(total) => {
+                self.bits = total;
+                Ok(())
+            }
+            None => Err(CounterOverflowError { requested: bits }.into()),
+        }
+    }
+}
+
+impl<N, E> BitWrite for BitCounter<N, E>
+where
+    E: Endianness,
+    N: Counter,
+{
+    #[inline]
+    fn write_bit(&mut self, _bit: bool) -> io::Result<()> {
+        self.add_bits(1)
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ))
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ))
+        } else {
+            self.add_bits(bits.into())
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        E::write_signed(self, bits, value)
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        self.add_bits(u64::from(value) + 1)
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        self.add_bits(u64::from(value) + 1)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.add_bits(buf.len() as u64 * 8)
+    }
+
+    #[inline]
+    fn write_run(&mut self, _bit: bool, count: u64) -> io::Result<()> {
+        self.add_bits(count)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.bits.whole_bytes()
+    }
+}
+
+impl<N, E> HuffmanWrite<E> for BitCounter<N, E>
+where
+    E: Endianness,
+    N: Counter,
+{
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        let bits = tree.get(&symbol).map(|&(bits, _)| u64::from(bits)).sum();
+        self.add_bits(bits)
+    }
+}
+
+/// A generic unsigned value for stream recording purposes
+#[deprecated(note = "BitRecorder no longer stores written values individually")]
+#[allow(dead_code)]
+pub struct UnsignedValue(InnerUnsignedValue);
+
+#[allow(dead_code)]
+enum InnerUnsignedValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+}
+
+macro_rules! define_unsigned_value {
+    ($t:ty, $n:ident) => {
+        #[allow(deprecated)]
+        impl From<$t> for UnsignedValue {
+            #[inline]
+            fn from(v: $t) -> Self {
+                UnsignedValue(InnerUnsignedValue::$n(v))
+            }
+        }
+    };
+}
+define_unsigned_value!(u8, U8);
+define_unsigned_value!(u16, U16);
+define_unsigned_value!(u32, U32);
+define_unsigned_value!(u64, U64);
+define_unsigned_value!(u128, U128);
+define_unsigned_value!(i8, I8);
+define_unsigned_value!(i16, I16);
+define_unsigned_value!(i32, I32);
+define_unsigned_value!(i64, I64);
+define_unsigned_value!(i128, I128);
+
+/// A generic signed value for stream recording purposes
+#[deprecated(note = "BitRecorder no longer stores written values individually")]
+#[allow(dead_code)]
+pub struct SignedValue(InnerSignedValue);
+
+#[allow(dead_code)]
+enum InnerSignedValue {
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+}
+
+macro_rules! define_signed_value {
+    ($t:ty, $n:ident) => {
+        #[allow(deprecated)]
+        impl From<$t> for SignedValue {
+            #[inline]
+            fn from(v: $t) -> Self {
+                SignedValue(InnerSignedValue::$n(v))
+            }
+        }
+    };
+}
+define_signed_value!(i8, I8);
+define_signed_value!(i16, I16);
+define_signed_value!(i32, I32);
+define_signed_value!(i64, I64);
+define_signed_value!(i128, I128);
+
+/// For recording writes in order to play them back on another writer.
+///
+/// Writes are stored as a packed sequence of bits in the
+/// recorder's endianness, so they should be played back
+/// to a writer of that same endianness.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite, BitRecorder};
+/// let mut recorder: BitRecorder<u32, BigEndian> = BitRecorder::new();
+/// recorder.write(1, 0b1).unwrap();
+/// recorder.write(2, 0b01).unwrap();
+/// recorder.write(5, 0b10111).unwrap();
+/// assert_eq!(recorder.written(), 8);
+/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+/// recorder.playback(&mut writer);
+/// assert_eq!(writer.into_writer(), [0b10110111]);
+/// ```
+pub struct BitRecorder<N, E: Endianness> {
+    counter: BitCounter<N, E>,
+    buffer: BitBuffer<E>,
+}
+
+impl<N: Counter, E: Endianness> Default for BitRecorder<N, E> {
+    #[inline]
+    fn default() -> Self {
+        BitRecorder::new()
+    }
+}
+
+impl<N: Counter, E: Endianness> Clone for BitRecorder<N, E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        BitRecorder {
+            counter: self.counter.clone(),
+            buffer: self.buffer.clone(),
+        }
+    }
+}
+
+impl<N: fmt::Debug, E: Endianness> fmt::Debug for BitRecorder<N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitRecorder")
+            .field("counter", &self.counter)
+            .field("buffer", &self.buffer)
+            .finish()
+    }
+}
+
+impl<N: Counter, E: Endianness> BitRecorder<N, E> {
+    /// Creates new recorder
+    #[inline]
+    pub fn new() -> Self {
+        BitRecorder {
+            counter: BitCounter::new(),
+            buffer: BitBuffer::new(),
+        }
+    }
+
+    /// Creates new recorder sized for the given number of writes
+    ///
+    /// Since writes are recorded as packed bits,
+    /// this reserves a byte for each write.
+    /// Use `with_byte_capacity` when the recorded size is known.
+    #[inline]
+    pub fn with_capacity(writes: usize) -> Self {
+        Self::with_byte_capacity(writes)
+    }
+
+    /// Creates new recorder with space for the given number of bytes
+    #[inline]
+    pub fn with_byte_capacity(bytes: usize) -> Self {
+        BitRecorder {
+            counter: BitCounter::new(),
+            buffer: BitBuffer::with_capacity(bytes),
+        }
+    }
+
+    /// Creates new recorder with the given endiannness
+    #[inline]
+    pub fn endian(_endian: E) -> Self {
+        BitRecorder {
+            counter: BitCounter::new(),
+            buffer: BitBuffer::new(),
+        }
+    }
+
+    /// Returns number of bits written
+    #[inline]
+    pub fn written(&self) -> N {
+        self.counter.written()
+    }
+
+    /// Returns number of bytes written, rounding up any partial byte
+    #[inline]
+    pub fn written_bytes(&self) -> N {
+        self.counter.written_bytes()
+    }
+
+    /// Plays recorded writes to the given writer
+    #[inline]
+    pub fn playback<W: BitWrite>(&self, writer: &mut W) -> io::Result<()> {
+        self.buffer.playback(writer)
+    }
+
+    /// Plays recorded writes to the given writer
+    /// and consumes the recorder
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWriter, BitWrite, BitRecorder};
+    /// let mut recorder: BitRecorder<u32, LittleEndian> = BitRecorder::new();
+    /// recorder.write(4, 0b1011).unwrap();
+    /// recorder.write_bytes(b"ab").unwrap();
+    /// recorder.write(4, 0b0101).unwrap();
+    /// let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    /// recorder.playback_once(&mut writer).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x1B, 0x26, 0x56]);
+    /// ```
+    #[inline]
+    pub fn playback_once<W: BitWrite>(self, writer: &mut W) -> io::Result<()> {
+        self.playback(writer)
+    }
+
+    /// Discards all recorded writes while keeping
+    /// the recorder's allocated space for reuse
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, BitWriter, BitWrite, BitRecorder};
+    /// let mut recorder: BitRecorder<u32, BigEndian> = BitRecorder::new();
+    /// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    /// for frame in 1..=3u8 {
+    ///     recorder.clear();
+    ///     recorder.write(4, frame).unwrap();
+    ///     assert_eq!(recorder.written(), 4);
+    ///     recorder.playback(&mut writer).unwrap();
+    /// }
+    /// writer.write(4, 0).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x12, 0x30]);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.counter = BitCounter::new();
+        self.buffer.clear();
+    }
+}
+
+/// Recordings are serialized as the number of bits recorded
+/// followed by those bits packed into bytes,
+/// with any unused bits of the final byte set to 0.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWrite, BitRecorder};
+/// let mut recorder: BitRecorder<u32, BigEndian> = BitRecorder::new();
+/// recorder.write(12, 0xABCu16).unwrap();
+/// let json = serde_json::to_string(&recorder).unwrap();
+/// assert_eq!(json, r#"{"bits":12,"bytes":[171,192]}"#);
+/// let recorder: BitRecorder<u32, BigEndian> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(recorder.written(), 12);
+/// ```
+#[cfg(feature = "serde")]
+impl<N, E: Endianness> serde::Serialize for BitRecorder<N, E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("BitRecorder", 2)?;
+        state.serialize_field("bits", &self.buffer.len_bits())?;
+        state.serialize_field("bytes", self.buffer.as_bytes())?;
+        state.end()
+    }
+}
+
+/// Deserializing fails if the number of bytes
+/// doesn't match the number of bits,
+/// or if there are too many bits for the recorder's counter.
+#[cfg(feature = "serde")]
+impl<'de, N: Counter, E: Endianness> serde::Deserialize<'de> for BitRecorder<N, E> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "BitRecorder")]
+        struct Recorded {
+            bits: u64,
+            bytes: Vec<u8>,
+        }
+
+        let Recorded { bits, bytes } = Recorded::deserialize(deserializer)?;
+        if bytes.len() as u64 != bits / 8 + u64::from(bits % 8 != 0) {
+            return Err(D::Error::custom("recorded bits don't match recorded bytes"));
+        }
+        let mut recorder = BitRecorder::with_byte_capacity(bytes.len());
+        BitBuffer::<E>::from_parts(bytes, (bits % 8) as u32)
+            .playback(&mut recorder)
+            .map_err(D::Error::custom)?;
+        Ok(recorder)
+    }
+}
+
+impl<N, E> BitWrite for BitRecorder<N, E>
+where
+    E: Endianness,
+    N: Counter,
+{
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.counter.write_bit(bit)?;
+        self.buffer.write_bit(bit)
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        self.counter.write(bits, value)?;
+        self.buffer.write(bits, value)
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        self.counter.write_signed(bits, value)?;
+        self.buffer.write_signed(bits, value)
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        self.counter.write_unary0(value)?;
+        self.buffer.write_unary0(value)
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        self.counter.write_unary1(value)?;
+        self.buffer.write_unary1(value)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.counter.write_bytes(buf)?;
+        self.buffer.write_bytes(buf)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.counter.byte_aligned()
+    }
+}
+
+impl<N, E> HuffmanWrite<E> for BitRecorder<N, E>
+where
+    E: Endianness,
+    N: Counter,
+{
+    #[inline]
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        tree.get(&symbol)
+            .try_for_each(|(bits, value)| self.write(*bits, *value))
+    }
+}
+
+/// For recording writes along with Huffman-coded symbols
+/// in order to play them back with a tree chosen afterward.
+///
+/// Where `BitRecorder` stores only the bits written,
+/// symbols recorded with `write_symbol` are kept as symbols
+/// and encoded with whichever tree is given to `playback`.
+/// This allows a two-pass encoder to gather statistics
+/// on the symbols it writes, build a table suited to them,
+/// and then emit the stream once that table is known.
+///
+/// Because the length of a symbol's code isn't known
+/// until playback, the recorder is only considered byte-aligned
+/// if no symbols have been written since it was last aligned
+/// with `byte_align`, whose padding is likewise deferred.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite, SymbolRecorder};
+/// use bitstream_io::huffman::compile_write_tree;
+/// let mut recorder: SymbolRecorder<BigEndian, char> = SymbolRecorder::new();
+/// recorder.write(4, 0b1010).unwrap();
+/// for c in "abbb".chars() {
+///     recorder.write_symbol(c);
+/// }
+/// recorder.byte_align().unwrap();
+///
+/// // 'b' turns out to be the most common symbol
+/// let first = compile_write_tree(vec![('a', vec![0]), ('b', vec![1])]).unwrap();
+/// let second = compile_write_tree(vec![('a', vec![1]), ('b', vec![0])]).unwrap();
+/// assert_eq!(recorder.written_with(&first), Some(8));
+///
+/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+/// recorder.playback(&mut writer, &first).unwrap();
+/// recorder.playback(&mut writer, &second).unwrap();
+/// assert_eq!(writer.into_writer(), [0b1010_0111, 0b1010_1000]);
+/// ```
+pub struct SymbolRecorder<E: Endianness, T> {
+    entries: Vec<Recorded<E, T>>,
+    // bits past the last byte boundary, if known
+    position: Option<u32>,
+}
+
+enum Recorded<E: Endianness, T> {
+    Bits(BitBuffer<E>),
+    Symbol(T),
+    ByteAlign,
+}
+
+impl<E: Endianness, T: Clone> Clone for Recorded<E, T> {
+    fn clone(&self) -> Self {
+        match self {
+            Recorded::Bits(buffer) => Recorded::Bits(buffer.clone()),
+            Recorded::Symbol(symbol) => Recorded::Symbol(symbol.clone()),
+            Recorded::ByteAlign => Recorded::ByteAlign,
+        }
+    }
+}
+
+impl<E: Endianness, T: fmt::Debug> fmt::Debug for Recorded<E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recorded::Bits(buffer) => f.debug_tuple("Bits").field(buffer).finish(),
+            Recorded::Symbol(symbol) => f.debug_tuple("Symbol").field(symbol).finish(),
+            Recorded::ByteAlign => f.write_str("ByteAlign"),
+        }
+    }
+}
+
+impl<E: Endianness, T> Default for SymbolRecorder<E, T> {
+    #[inline]
+    fn default() -> Self {
+        SymbolRecorder::new()
+    }
+}
+
+impl<E: Endianness, T: Clone> Clone for SymbolRecorder<E, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        SymbolRecorder {
+            entries: self.entries.clone(),
+            position: self.position,
+        }
+    }
+}
+
+impl<E: Endianness, T: fmt::Debug> fmt::Debug for SymbolRecorder<E, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SymbolRecorder")
+            .field("entries", &self.entries)
+            .finish()
+    }
+}
+
+impl<E: Endianness, T> SymbolRecorder<E, T> {
+    /// Creates new recorder
+    #[inline]
+    pub fn new() -> Self {
+        SymbolRecorder {
+            entries: Vec::new(),
+            position: Some(0),
+        }
+    }
+
+    /// Creates new recorder with the given endiannness
+    #[inline]
+    pub fn endian(_endian: E) -> Self {
+        SymbolRecorder::new()
+    }
+
+    /// Records a symbol to be encoded when played back
+    #[inline]
+    pub fn write_symbol(&mut self, symbol: T) {
+        self.entries.push(Recorded::Symbol(symbol));
+        self.position = None;
+    }
+
+    /// Returns an iterator over the symbols recorded, in order
+    pub fn symbols(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Recorded::Symbol(symbol) => Some(symbol),
+            _ => None,
+        })
+    }
+
+    /// Discards all recorded writes and symbols
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = Some(0);
+    }
+
+    fn record_bits<F>(&mut self, write: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut BitBuffer<E>) -> io::Result<()>,
+    {
+        if !matches!(self.entries.last(), Some(Recorded::Bits(_))) {
+            self.entries.push(Recorded::Bits(BitBuffer::new()));
+        }
+        match self.entries.last_mut() {
+            Some(Recorded::Bits(buffer)) => {
+                let before = buffer.len_bits();
+                write(buffer)?;
+                let written = buffer.len_bits() - before;
+                self.position = self
+                    .position
+                    .map(|position| ((u64::from(position) + written) % 8) as u32);
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<E: Endianness, T: Ord + Copy> SymbolRecorder<E, T> {
+    /// Returns the number of bits the recording would occupy
+    /// if played back with the given tree,
+    /// or `None` if any recorded symbol is missing from it
+    pub fn written_with(&self, tree: &WriteHuffmanTree<E, T>) -> Option<u64> {
+        self.entries
+            .iter()
+            .try_fold(0, |written, entry| match entry {
+                Recorded::Bits(buffer) => Some(written + buffer.len_bits()),
+                Recorded::Symbol(symbol) => Some(written + u64::from(tree.code_len(symbol)?)),
+                Recorded::ByteAlign => Some((written + 7) / 8 * 8),
+            })
+    }
+
+    /// Plays recorded writes to the given writer,
+    /// encoding recorded symbols with the given tree
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if any recorded symbol
+    /// is missing from the tree, in which case nothing is written.
+    /// Passes along any I/O error from the writer.
+    pub fn playback<W>(&self, writer: &mut W, tree: &WriteHuffmanTree<E, T>) -> io::Result<()>
+    where
+        W: BitWrite + HuffmanWrite<E>,
+    {
+        if !self.symbols().all(|symbol| tree.has_symbol(symbol)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "recorded symbol not in Huffman tree",
+            ));
+        }
+        self.entries.iter().try_for_each(|entry| match entry {
+            Recorded::Bits(buffer) => buffer.playback(writer),
+            Recorded::Symbol(symbol) => writer.write_huffman(tree, *symbol),
+            Recorded::ByteAlign => writer.byte_align(),
+        })
+    }
+}
+
+impl<E: Endianness, T> BitWrite for SymbolRecorder<E, T> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.record_bits(|buffer| buffer.write_bit(bit))
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        self.record_bits(|buffer| buffer.write(bits, value))
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        self.record_bits(|buffer| buffer.write_signed(bits, value))
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        self.record_bits(|buffer| buffer.write_unary0(value))
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        self.record_bits(|buffer| buffer.write_unary1(value))
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.record_bits(|buffer| buffer.write_bytes(buf))
+    }
+
+    #[inline]
+    fn write_run(&mut self, bit: bool, count: u64) -> io::Result<()> {
+        self.record_bits(|buffer| buffer.write_run(bit, count))
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.position == Some(0)
+    }
+
+    #[inline]
+    fn byte_align(&mut self) -> io::Result<()> {
+        if !self.byte_aligned() {
+            self.entries.push(Recorded::ByteAlign);
+            self.position = Some(0);
+        }
+        Ok(())
+    }
+}
+
+/// For forwarding all writes to two writers simultaneously.
+///
+/// Every write is performed on the first writer and then
+/// on the second, stopping at the first error.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite, BitCounter, TeeBitWriter};
+/// let mut writer = TeeBitWriter::new(
+///     BitWriter::endian(Vec::new(), BigEndian),
+///     BitCounter::<u32, BigEndian>::new(),
+/// );
+/// writer.write(1, 0b1).unwrap();
+/// writer.write(2, 0b01).unwrap();
+/// writer.write(5, 0b10111).unwrap();
+/// let (writer, counter) = writer.into_writers();
+/// assert_eq!(writer.into_writer(), [0b10110111]);
+/// assert_eq!(counter.written(), 8);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TeeBitWriter<A: BitWrite, B: BitWrite> {
+    first: A,
+    second: B,
+}
+
+impl<A: BitWrite, B: BitWrite> TeeBitWriter<A, B> {
+    /// Creates new writer forwarding to both of the given writers
+    #[inline]
+    pub fn new(first: A, second: B) -> Self {
+        TeeBitWriter { first, second }
+    }
+
+    /// Provides mutable reference to the first writer
+    #[inline]
+    pub fn first(&mut self) -> &mut A {
+        &mut self.first
+    }
+
+    /// Provides mutable reference to the second writer
+    #[inline]
+    pub fn second(&mut self) -> &mut B {
+        &mut self.second
+    }
+
+    /// Unwraps both internal writers and disposes of TeeBitWriter.
+    #[inline]
+    pub fn into_writers(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: BitWrite, B: BitWrite> BitWrite for TeeBitWriter<A, B> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.first.write_bit(bit)?;
+        self.second.write_bit(bit)
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        self.first.write(bits, value)?;
+        self.second.write(bits, value)
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        self.first.write_signed(bits, value)?;
+        self.second.write_signed(bits, value)
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.first.write_bytes(buf)?;
+        self.second.write_bytes(buf)
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        self.first.write_unary0(value)?;
+        self.second.write_unary0(value)
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        self.first.write_unary1(value)?;
+        self.second.write_unary1(value)
+    }
+
+    #[inline]
+    fn write_run(&mut self, bit: bool, count: u64) -> io::Result<()> {
+        self.first.write_run(bit, count)?;
+        self.second.write_run(bit, count)
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.first.byte_aligned()
+    }
+
+    #[inline]
+    fn byte_align(&mut self) -> io::Result<()> {
+        self.first.byte_align()?;
+        self.second.byte_align()
+    }
+}
+
+impl<A, B, E> HuffmanWrite<E> for TeeBitWriter<A, B>
+where
+    A: BitWrite + HuffmanWrite<E>,
+    B: BitWrite + HuffmanWrite<E>,
+    E: Endianness,
+{
+    #[inline]
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        self.first.write_huffman(tree, symbol)?;
+        self.second.write_huffman(tree, symbol)
+    }
+}
+
+/// For limiting the number of bits which may be written to a writer.
+///
+/// Any write which would exceed the limit returns a `BitLimitError`
+/// as an error of kind `Other` and is not passed to the underlying writer,
+/// so an encoder may tell an exceeded budget apart from a failing stream,
+/// discard its output and try again.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite, LimitedBitWriter, BitLimitError};
+/// let mut writer = LimitedBitWriter::new(BitWriter::endian(Vec::new(), BigEndian), 12);
+/// writer.write(8, 0xFF).unwrap();
+/// assert_eq!(writer.remaining(), 4);
+/// let err = writer.write(5, 0).unwrap_err();
+/// assert_eq!(BitLimitError::from_io(&err).unwrap().requested(), 5);
+/// writer.write(4, 0).unwrap();
+/// assert_eq!(writer.written(), 12);
+/// assert!(writer.write_bit(true).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct LimitedBitWriter<W: BitWrite> {
+    writer: W,
+    written: u64,
+    limit: u64,
+}
+
+impl<W: BitWrite> LimitedBitWriter<W> {
+    /// Wraps a LimitedBitWriter around something that implements `BitWrite`
+    /// which allows at most the given number of bits to be written.
+    #[inline]
+    pub fn new(writer: W, limit: u64) -> Self {
+        LimitedBitWriter {
+            writer,
+            written: 0,
+            limit,
+        }
+    }
+
+    /// Returns number of bits written
+    #[inline]
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Returns number of bits which may still be written
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.limit - self.written
+    }
+
+    /// Provides mutable reference to internal writer
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of LimitedBitWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    #[inline]
+    fn reserve(&self, bits: u64) -> io::Result<u64> {
+        if bits <= self.remaining() {
+            Ok(bits)
+        } else {
+            Err(BitLimitError::new(bits, self.remaining()).into())
+        }
+    }
+}
+
+impl<W: BitWrite> BitWrite for LimitedBitWriter<W> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        let bits = self.reserve(1)?;
+        self.writer.write_bit(bit)?;
+        self.written += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        let bits = self.reserve(bits.into())?;
+        self.writer.write(bits as u32, value)?;
+        self.written += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        let bits = self.reserve(bits.into())?;
+        self.writer.write_signed(bits as u32, value)?;
+        self.written += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        let bits = self.reserve(buf.len() as u64 * 8)?;
+        self.writer.write_bytes(buf)?;
+        self.written += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        let bits = self.reserve(u64::from(value) + 1)?;
+        self.writer.write_unary0(value)?;
+        self.written += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        let bits = self.reserve(u64::from(value) + 1)?;
+        self.writer.write_unary1(value)?;
+        self.written += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_run(&mut self, bit: bool, count: u64) -> io::Result<()> {
+        let bits = self.reserve(count)?;
+        self.writer.write_run(bit, bits)?;
+        self.written += bits;
+        Ok(())
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+}
+
+impl<W, E> HuffmanWrite<E> for LimitedBitWriter<W>
+where
+    W: BitWrite + HuffmanWrite<E>,
+    E: Endianness,
+{
+    #[inline]
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        let bits = self.reserve(tree.get(&symbol).map(|(bits, _)| u64::from(*bits)).sum())?;
+        self.writer.write_huffman(tree, symbol)?;
+        self.written += bits;
+        Ok(())
+    }
+}
+
+/// For logging each write to a writer, for debugging encoders.
+///
+/// Every write is forwarded to the underlying writer
+/// and then described on its own line in the log,
+/// along with the bit offset at which it started
+/// and whether it failed.
+/// Values are shown as binary digits of the bits written,
+/// while byte writes are shown in hex.
+/// Writes made by `BitWrite`'s provided methods
+/// are logged as the simpler writes they're built from.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite, DebugBitWriter};
+/// let mut writer = DebugBitWriter::new(BitWriter::endian(Vec::new(), BigEndian), String::new());
+/// writer.write(3, 0b101u8).unwrap();
+/// writer.write_signed(4, -2i8).unwrap();
+/// writer.byte_align().unwrap();
+/// writer.write_bytes(b"\x12\xAB").unwrap();
+/// assert!(writer.write(2, 4u8).is_err());
+/// let (writer, log) = writer.into_parts();
+/// assert_eq!(writer.into_writer(), [0b101_1110_0, 0x12, 0xAB]);
+/// assert_eq!(
+///     log,
+///     "0: write 3 bits 0b101 (5)\n\
+///      3: write_signed 4 bits 0b1110 (-2)\n\
+///      7: byte_align 1 bits\n\
+///      8: write_bytes 2 bytes [12 AB]\n\
+///      24: write 2 bits 0b00 (4) failed: excessive value for bits written\n"
+/// );
+/// ```
+///
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{LittleEndian, BitWriter, BitWrite, DebugBitWriter};
+/// let mut lines = Vec::new();
+/// let mut writer = DebugBitWriter::with_callback(
+///     BitWriter::endian(Vec::new(), LittleEndian),
+///     |line| lines.push(line.to_owned()),
+/// );
+/// writer.write_unary0(3).unwrap();
+/// writer.write(4, 0b1011u8).unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0b1011_0111]);
+/// assert_eq!(lines, ["0: write_unary0 3", "4: write 4 bits 0b1011 (11)"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DebugBitWriter<W: BitWrite, L: fmt::Write> {
+    writer: W,
+    log: L,
+    position: u64,
+}
+
+impl<W: BitWrite, L: fmt::Write> DebugBitWriter<W, L> {
+    /// Wraps a DebugBitWriter around something that implements `BitWrite`
+    /// which describes each write to the given log, one line per write.
+    #[inline]
+    pub fn new(writer: W, log: L) -> Self {
+        DebugBitWriter {
+            writer,
+            log,
+            position: 0,
+        }
+    }
+
+    /// Returns number of bits written through this writer
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Provides reference to the log
+    #[inline]
+    pub fn log(&self) -> &L {
+        &self.log
+    }
+
+    /// Provides mutable reference to internal writer
+    #[inline]
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps internal writer and disposes of DebugBitWriter.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Unwraps internal writer and log and disposes of DebugBitWriter.
+    #[inline]
+    pub fn into_parts(self) -> (W, L) {
+        (self.writer, self.log)
+    }
+
+    /// Logs a write of the given number of bits,
+    /// advancing the position if it succeeded
+    fn logged(
+        &mut self,
+        bits: u64,
+        result: io::Result<()>,
+        description: fmt::Arguments,
+    ) -> io::Result<()> {
+        let line = match &result {
+            Ok(()) => format!("{}: {}\n", self.position, description),
+            Err(err) => format!("{}: {} failed: {}\n", self.position, description, err),
+        };
+        if result.is_ok() {
+            self.position += bits;
+        }
+        self.log
+            .write_str(&line)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "unable to write to debug log"))?;
+        result
+    }
+}
+
+impl<W: BitWrite, F: FnMut(&str)> DebugBitWriter<W, DebugCallback<F>> {
+    /// Wraps a DebugBitWriter around something that implements `BitWrite`
+    /// which passes each write's description to the given callback,
+    /// without a trailing newline.
+    #[inline]
+    pub fn with_callback(writer: W, callback: F) -> Self {
+        DebugBitWriter::new(writer, DebugCallback(callback))
+    }
+}
+
+impl<W: BitWrite, L: fmt::Write> BitWrite for DebugBitWriter<W, L> {
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        let result = self.writer.write_bit(bit);
+        self.logged(1, result, format_args!("write_bit {}", u8::from(bit)))
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        let result = self.writer.write(bits, value);
+        self.logged(
+            bits.into(),
+            result,
+            format_args!(
+                "write {} bits 0b{} ({:?})",
+                bits,
+                binary_digits(bits, value),
+                value
+            ),
+        )
+    }
+
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        let result = self.writer.write_signed(bits, value);
+        self.logged(
+            bits.into(),
+            result,
+            format_args!(
+                "write_signed {} bits 0b{} ({:?})",
+                bits,
+                binary_digits(bits, value),
+                value
+            ),
+        )
+    }
+
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        let result = self.writer.write_bytes(buf);
+        let hex = buf
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.logged(
+            buf.len() as u64 * 8,
+            result,
+            format_args!("write_bytes {} bytes [{}]", buf.len(), hex),
+        )
+    }
+
+    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
+        let result = self.writer.write_unary0(value);
+        self.logged(
+            u64::from(value) + 1,
+            result,
+            format_args!("write_unary0 {}", value),
+        )
+    }
+
+    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
+        let result = self.writer.write_unary1(value);
+        self.logged(
+            u64::from(value) + 1,
+            result,
+            format_args!("write_unary1 {}", value),
+        )
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        self.writer.byte_aligned()
+    }
+
+    fn byte_align(&mut self) -> io::Result<()> {
+        let mut padding = 0;
+        let mut result = Ok(());
+        while !self.writer.byte_aligned() {
+            result = self.writer.write_bit(false);
+            if result.is_err() {
+                break;
+            }
+            padding += 1;
+        }
+        self.logged(padding, result, format_args!("byte_align {} bits", padding))
+    }
+}
+
+/// Adapts a callback into a log for `DebugBitWriter`,
+/// which calls it once per write with that write's description.
+#[derive(Clone, Debug)]
+pub struct DebugCallback<F>(F);
+
+impl<F: FnMut(&str)> fmt::Write for DebugCallback<F> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        (self.0)(s.trim_end_matches('\n'));
+        Ok(())
+    }
+}
+
+/// Returns the lowest `bits` bits of value as binary digits
+fn binary_digits<N: Numeric>(bits: u32, value: N) -> String {
+    let bytes = value.to_le_bytes();
+    let bytes = bytes.as_ref();
+    (0..bits.min(N::bits_size()))
+        .rev()
+        .map(|i| {
+            if bytes[(i / 8) as usize] & (1 << (i % 8)) != 0 {
+                '1'
+            } else {
+                '0'
+            }
+        })
+        .collect()
+}
+
+/// For writing bit values to an underlying stream
+/// in an endianness chosen at runtime.
+///
+/// This dispatches each write to a `BitWriter` of the chosen
+/// endianness, so encoding code which only needs `BitWrite`
+/// doesn't need to be generic over or duplicated per endianness.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BitWrite, DynBitWriter, DynEndianness};
+/// let mut data = Vec::new();
+/// for endianness in [DynEndianness::Big, DynEndianness::Little].iter() {
+///     let mut writer = DynBitWriter::new(&mut data, *endianness);
+///     writer.write(4, 0x1).unwrap();
+///     writer.write(12, 0x234).unwrap();
+/// }
+/// assert_eq!(data, [0x12, 0x34, 0x41, 0x23]);
+/// ```
+#[derive(Debug)]
+pub enum DynBitWriter<W: io::Write> {
+    /// A big-endian writer
+    Big(BitWriter<W, BigEndian>),
+    /// A little-endian writer
+    Little(BitWriter<W, LittleEndian>),
+}
+
+impl<W: io::Write> DynBitWriter<W> {
+    /// Wraps a DynBitWriter around something that implements `Write`
+    /// with the given endianness.
+    #[inline]
+    pub fn new(writer: W, endianness: DynEndianness) -> Self {
+        match endianness {
+            DynEndianness::Big => DynBitWriter::Big(BitWriter::new(writer)),
+            DynEndianness::Little => DynBitWriter::Little(BitWriter::new(writer)),
+        }
+    }
+
+    /// Returns the endianness values are written in
+    #[inline]
+    pub fn endianness(&self) -> DynEndianness {
+        match self {
+            DynBitWriter::Big(_) => DynEndianness::Big,
+            DynBitWriter::Little(_) => DynEndianness::Little,
+        }
+    }
+
+    /// Unwraps internal writer and disposes of DynBitWriter.
+    ///
+    /// # Warning
+    ///
+    /// Any unwritten partial bits are discarded.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        match self {
+            DynBitWriter::Big(w) => w.into_writer(),
+            DynBitWriter::Little(w) => w.into_writer(),
+        }
+    }
+}
+
+impl<W: io::Write> From<BitWriter<W, BigEndian>> for DynBitWriter<W> {
+    #[inline]
+    fn from(writer: BitWriter<W, BigEndian>) -> Self {
+        DynBitWriter::Big(writer)
+    }
+}
+
+impl<W: io::Write> From<BitWriter<W, LittleEndian>> for DynBitWriter<W> {
+    #[inline]
+    fn from(writer: BitWriter<W, LittleEndian>) -> Self {
+        DynBitWriter::Little(writer)
+    }
+}
+
+impl<W: io::Write> BitWrite for DynBitWriter<W> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        match self {
+            DynBitWriter::Big(w) => w.write_bit(bit),
+            DynBitWriter::Little(w) => w.write_bit(bit),
+        }
+    }
+
+    #[inline]
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        match self {
+            DynBitWriter::Big(w) => w.write(bits, value),
+            DynBitWriter::Little(w) => w.write(bits, value),
+        }
+    }
+
+    #[inline]
+    fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
+    where
+        S: SignedNumeric,
+    {
+        match self {
+            DynBitWriter::Big(w) => w.write_signed(bits, value),
+            DynBitWriter::Little(w) => w.write_signed(bits, value),
+        }
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            DynBitWriter::Big(w) => w.write_bytes(buf),
+            DynBitWriter::Little(w) => w.write_bytes(buf),
+        }
+    }
+
+    #[inline]
+    fn write_run(&mut self, bit: bool, count: u64) -> io::Result<()> {
+        match self {
+            DynBitWriter::Big(w) => w.write_run(bit, count),
+            DynBitWriter::Little(w) => w.write_run(bit, count),
+        }
+    }
+
+    #[inline]
+    fn byte_aligned(&self) -> bool {
+        match self {
+            DynBitWriter::Big(w) => w.byte_aligned(),
+            DynBitWriter::Little(w) => w.byte_aligned(),
+        }
+    }
+}
+
+/// For writing bit values into a fixed-size slice of bytes
+/// without any allocation.
+///
+/// Any write which would exceed the slice's capacity
+/// returns a `BitLimitError` as an error of kind `Other`
+/// and writes nothing.
+/// As with `BitWriter`, a partial byte is only
+/// stored in the slice once it is completed.
+///
+/// # Example
+/// ```
+/// use bitstream_io::{BigEndian, BitSliceWriter, BitWrite, BitLimitError};
+/// let mut buf = [0; 3];
+/// let mut writer = BitSliceWriter::endian(&mut buf, BigEndian);
+/// writer.write(4, 0b1011).unwrap();
+/// writer.write(16, 0xFFEE).unwrap();
+/// assert_eq!(writer.written_bits(), 20);
+/// assert_eq!(writer.written(), [0b1011_1111, 0b1111_1110]);
+/// let err = writer.write(5, 0).unwrap_err();
+/// assert_eq!(BitLimitError::from_io(&err).unwrap().remaining(), 4);
+/// writer.write(4, 0b0001).unwrap();
+/// assert_eq!(writer.remaining_bits(), 0);
+/// assert_eq!(buf, [0b1011_1111, 0b1111_1110, 0b1110_0001]);
+/// ```
+#[derive(Debug)]
+pub struct BitSliceWriter<'a, E: Endianness> {
+    buf: &'a mut [u8],
+    bytes: usize,
+    bitqueue: BitQueue<E, u8>,
+}
+
+impl<'a, E: Endianness> BitSliceWriter<'a, E> {
+    /// Wraps a BitSliceWriter around a slice of bytes
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        BitSliceWriter {
+            buf,
+            bytes: 0,
+            bitqueue: BitQueue::new(),
+        }
+    }
+
+    /// Wraps a BitSliceWriter around a slice of bytes
+    /// with the given endianness.
+    #[inline]
+    pub fn endian(buf: &'a mut [u8], _endian: E) -> Self {
+        BitSliceWriter::new(buf)
+    }
+
+    /// Returns the number of bits written, including
+    /// those of any partial byte
+    #[inline]
+    pub fn written_bits(&self) -> u64 {
+        self.bytes as u64 * 8 + u64::from(self.bitqueue.len())
+    }
+
+    /// Returns the number of bits which may still be written
+    #[inline]
+    pub fn remaining_bits(&self) -> u64 {
+        self.buf.len() as u64 * 8 - self.written_bits()
+    }
+
+    /// Returns the whole bytes written to the slice so far
+    ///
+    /// # Example
+    /// ```
+    /// use bitstream_io::{LittleEndian, BitSliceWriter, BitWrite};
+    /// let mut buf = [0; 4];
+    /// let mut writer = BitSliceWriter::endian(&mut buf, LittleEndian);
+    /// writer.write(12, 0xABC).unwrap();
+    /// assert_eq!(writer.written(), [0xBC]);
+    /// writer.byte_align().unwrap();
+    /// assert_eq!(writer.written(), [0xBC, 0x0A]);
+    /// ```
+    #[inline]
+    pub fn written(&self) -> &[u8] {
+        &self.buf[0..self.bytes]
+    }
+
+    #[inline]
+    fn reserve(&self, bits: u64) -> io::Result<()> {
+        if bits <= self.remaining_bits() {
+            Ok(())
+        } else {
+            Err(BitLimitError::new(bits, self.remaining_bits()).into())
+        }
+    }
+
+    #[inline]
+    fn push_byte(&mut self, byte: u8) {
+        self.buf[self.bytes] = byte;
+        self.bytes += 1;
+    }
+}
+
+impl<'a, E: Endianness> BitWrite for BitSliceWriter<'a, E> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.reserve(1)?;
+        self.bitqueue.push(1, u8::from(bit));
+        if self.bitqueue.is_full() {
+            let byte = self.bitqueue.pop(8);
+            self.push_byte(byte);
+        }
+        Ok(())
+    }
+
+    fn write<U>(&mut self, bits: u32, value: U) -> io::Result<()>
+    where
+        U: Numeric,
+    {
+        if bits > U::bits_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive bits for type written",
+            ));
+        } else if (bits < U::bits_size()) && (value >= (U::one() << bits)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for bits written",
+            ));
+        }
+        self.reserve(bits.into())?;
+
+        if bits < self.bitqueue.remaining_len() {
+            self.bitqueue.push(bits, value.to_u8());
+        } else {
+            let mut acc = BitQueue::<E, U>::from_value(value, bits);
+            if !self.bitqueue.is_empty() {
+                let fill = self.bitqueue.remaining_len();
+                self.bitqueue.push(fill, acc.pop(fill).to_u8());
+                let byte = self.bitqueue.pop(8);
+                self.push_byte(byte);
+            }
+            while acc.len() >= 8 {
+                let byte = acc.pop(8).to_u8();
+                self.push_byte(byte);
+            }
+            self.bitqueue.push(acc.len(), acc.value().to_u8());
+        }
+        Ok(())
+    }
+
     #[inline]
     fn write_signed<S>(&mut self, bits: u32, value: S) -> io::Result<()>
     where
         S: SignedNumeric,
     {
+        self.reserve(bits.into())?;
         E::write_signed(self, bits, value)
     }
 
-    #[inline]
-    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
-        self.bits += (value + 1).into();
-        Ok(())
-    }
-
-    #[inline]
-    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
-        self.bits += (value + 1).into();
-        Ok(())
-    }
-
-    #[inline]
     fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.bits += (buf.len() as u32 * 8).into();
-        Ok(())
+        self.reserve(buf.len() as u64 * 8)?;
+        if self.byte_aligned() {
+            self.buf[self.bytes..self.bytes + buf.len()].copy_from_slice(buf);
+            self.bytes += buf.len();
+            Ok(())
+        } else {
+            buf.iter().try_for_each(|b| self.write(8, *b))
+        }
     }
 
     #[inline]
     fn byte_aligned(&self) -> bool {
-        self.bits % 8.into() == 0.into()
+        self.bitqueue.is_empty()
     }
 }
 
-impl<N, E> HuffmanWrite<E> for BitCounter<N, E>
-where
-    E: Endianness,
-    N: AddAssign + From<u32>,
-{
+impl<'a, E: Endianness> HuffmanWrite<E> for BitSliceWriter<'a, E> {
+    /// # Example
+    /// ```
+    /// use bitstream_io::{BigEndian, BitSliceWriter, HuffmanWrite};
+    /// use bitstream_io::huffman::compile_write_tree;
+    /// let tree = compile_write_tree(
+    ///     vec![('a', vec![0]),
+    ///          ('b', vec![1, 0]),
+    ///          ('c', vec![1, 1, 0]),
+    ///          ('d', vec![1, 1, 1])]).unwrap();
+    /// let mut buf = [0; 1];
+    /// let mut writer = BitSliceWriter::endian(&mut buf, BigEndian);
+    /// writer.write_huffman(&tree, 'b').unwrap();
+    /// writer.write_huffman(&tree, 'c').unwrap();
+    /// writer.write_huffman(&tree, 'd').unwrap();
+    /// assert!(writer.write_huffman(&tree, 'a').is_err());
+    /// assert_eq!(buf, [0b10110111]);
+    /// ```
     fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
     where
         T: Ord + Copy,
     {
-        for &(bits, _) in tree.get(&symbol) {
-            let bits: N = bits.into();
-            self.bits += bits;
-        }
-        Ok(())
-    }
-}
-
-/// A generic unsigned value for stream recording purposes
-pub struct UnsignedValue(InnerUnsignedValue);
-
-enum InnerUnsignedValue {
-    U8(u8),
-    U16(u16),
-    U32(u32),
-    U64(u64),
-    U128(u128),
-    I8(i8),
-    I16(i16),
-    I32(i32),
-    I64(i64),
-    I128(i128),
-}
-
-macro_rules! define_unsigned_value {
-    ($t:ty, $n:ident) => {
-        impl From<$t> for UnsignedValue {
-            #[inline]
-            fn from(v: $t) -> Self {
-                UnsignedValue(InnerUnsignedValue::$n(v))
-            }
-        }
-    };
-}
-define_unsigned_value!(u8, U8);
-define_unsigned_value!(u16, U16);
-define_unsigned_value!(u32, U32);
-define_unsigned_value!(u64, U64);
-define_unsigned_value!(u128, U128);
-define_unsigned_value!(i8, I8);
-define_unsigned_value!(i16, I16);
-define_unsigned_value!(i32, I32);
-define_unsigned_value!(i64, I64);
-define_unsigned_value!(i128, I128);
-
-/// A generic signed value for stream recording purposes
-pub struct SignedValue(InnerSignedValue);
-
-enum InnerSignedValue {
-    I8(i8),
-    I16(i16),
-    I32(i32),
-    I64(i64),
-    I128(i128),
-}
-
-macro_rules! define_signed_value {
-    ($t:ty, $n:ident) => {
-        impl From<$t> for SignedValue {
-            #[inline]
-            fn from(v: $t) -> Self {
-                SignedValue(InnerSignedValue::$n(v))
-            }
-        }
-    };
-}
-define_signed_value!(i8, I8);
-define_signed_value!(i16, I16);
-define_signed_value!(i32, I32);
-define_signed_value!(i64, I64);
-define_signed_value!(i128, I128);
-
-enum WriteRecord {
-    Bit(bool),
-    Unsigned { bits: u32, value: UnsignedValue },
-    Signed { bits: u32, value: SignedValue },
-    Unary0(u32),
-    Unary1(u32),
-    Bytes(Box<[u8]>),
-}
-
-impl WriteRecord {
-    fn playback<W: BitWrite>(&self, writer: &mut W) -> io::Result<()> {
-        match self {
-            WriteRecord::Bit(v) => writer.write_bit(*v),
-            WriteRecord::Unsigned {
-                bits,
-                value: UnsignedValue(value),
-            } => match value {
-                InnerUnsignedValue::U8(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::U16(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::U32(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::U64(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::U128(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::I8(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::I16(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::I32(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::I64(v) => writer.write(*bits, *v),
-                InnerUnsignedValue::I128(v) => writer.write(*bits, *v),
-            },
-            WriteRecord::Signed {
-                bits,
-                value: SignedValue(value),
-            } => match value {
-                InnerSignedValue::I8(v) => writer.write_signed(*bits, *v),
-                InnerSignedValue::I16(v) => writer.write_signed(*bits, *v),
-                InnerSignedValue::I32(v) => writer.write_signed(*bits, *v),
-                InnerSignedValue::I64(v) => writer.write_signed(*bits, *v),
-                InnerSignedValue::I128(v) => writer.write_signed(*bits, *v),
-            },
-            WriteRecord::Unary0(v) => writer.write_unary0(*v),
-            WriteRecord::Unary1(v) => writer.write_unary1(*v),
-            WriteRecord::Bytes(bytes) => writer.write_bytes(bytes),
-        }
+        self.reserve(tree.get(&symbol).map(|(bits, _)| u64::from(*bits)).sum())?;
+        tree.get(&symbol)
+            .try_for_each(|(bits, value)| self.write(*bits, *value))
     }
 }
 
-/// For recording writes in order to play them back on another writer
+/// For writing bit values to an underlying stream in a given endianness,
+/// gathering completed bytes in an internal buffer
+/// which is written to the stream in large chunks.
+///
+/// A `BitWriter` passes each completed byte to its stream
+/// as soon as it's available.
+/// This writer instead appends bytes to its own buffer
+/// and only writes once the buffer reaches its capacity,
+/// so unbuffered streams such as files or sockets
+/// don't need to be wrapped in a `BufWriter`.
+///
+/// As with `BitWriter`, output should be byte-aligned
+/// and then flushed before the writer's lifetime ends.
+/// **Buffered bytes will be lost** if the writer is disposed of
+/// without calling `flush` or `into_writer`.
+///
 /// # Example
 /// ```
 /// use std::io::Write;
-/// use bitstream_io::{BigEndian, BitWriter, BitWrite, BitRecorder};
-/// let mut recorder: BitRecorder<u32, BigEndian> = BitRecorder::new();
-/// recorder.write(1, 0b1).unwrap();
-/// recorder.write(2, 0b01).unwrap();
-/// recorder.write(5, 0b10111).unwrap();
-/// assert_eq!(recorder.written(), 8);
-/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
-/// recorder.playback(&mut writer);
-/// assert_eq!(writer.into_writer(), [0b10110111]);
+/// use bitstream_io::{BigEndian, BitWrite, BufBitWriter};
+/// let mut data = Vec::new();
+/// let mut writer = BufBitWriter::endian(&mut data, BigEndian);
+/// writer.write(4, 0b1011).unwrap();
+/// writer.write(12, 0xFFE).unwrap();
+/// assert_eq!(writer.buffer(), [0b1011_1111, 0b1111_1110]);
+/// writer.flush().unwrap();
+/// assert!(writer.buffer().is_empty());
+/// assert_eq!(data, [0b1011_1111, 0b1111_1110]);
 /// ```
-#[derive(Default)]
-pub struct BitRecorder<N, E: Endianness> {
-    counter: BitCounter<N, E>,
-    records: Vec<WriteRecord>,
+pub struct BufBitWriter<W: io::Write, E: Endianness> {
+    writer: W,
+    buffer: BitWriter<Vec<u8>, E>,
+    capacity: usize,
 }
 
-impl<N: Default + Copy, E: Endianness> BitRecorder<N, E> {
-    /// Creates new recorder
+impl<W: io::Write + fmt::Debug, E: Endianness> fmt::Debug for BufBitWriter<W, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufBitWriter")
+            .field("writer", &self.writer)
+            .field("buffer", &self.buffer)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl<W: io::Write, E: Endianness> BufBitWriter<W, E> {
+    /// Wraps a BufBitWriter with a default capacity
+    /// around something that implements `Write`
     #[inline]
-    pub fn new() -> Self {
-        BitRecorder {
-            counter: BitCounter::new(),
-            records: Vec::new(),
-        }
+    pub fn new(writer: W) -> Self {
+        Self::with_capacity(8192, writer)
     }
 
-    /// Creates new recorder sized for the given number of writes
+    /// Wraps a BufBitWriter with a default capacity
+    /// around something that implements `Write`
+    /// with the given endianness.
     #[inline]
-    pub fn with_capacity(writes: usize) -> Self {
-        BitRecorder {
-            counter: BitCounter::new(),
-            records: Vec::with_capacity(writes),
-        }
+    pub fn endian(writer: W, _endian: E) -> Self {
+        Self::new(writer)
     }
 
-    /// Creates new recorder with the given endiannness
+    /// Wraps a BufBitWriter around something that implements `Write`
+    /// which buffers at least the given number of bytes
+    /// before writing them.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, BitWrite, BufBitWriter};
+    /// let mut data = Vec::new();
+    /// let mut writer: BufBitWriter<_, LittleEndian> = BufBitWriter::with_capacity(2, &mut data);
+    /// writer.write(12, 0xFFE).unwrap();
+    /// assert_eq!(writer.buffer(), [0xFE]);
+    /// writer.write(4, 0b1011).unwrap();
+    /// assert!(writer.buffer().is_empty());
+    /// writer.write(8, 0x12).unwrap();
+    /// assert_eq!(writer.into_writer().unwrap(), &[0xFE, 0b1011_1111, 0x12]);
+    /// ```
     #[inline]
-    pub fn endian(_endian: E) -> Self {
-        BitRecorder {
-            counter: BitCounter::new(),
-            records: Vec::new(),
+    pub fn with_capacity(capacity: usize, writer: W) -> Self {
+        BufBitWriter {
+            writer,
+            buffer: BitWriter::new(Vec::with_capacity(capacity)),
+            capacity,
         }
     }
 
-    /// Returns number of bits written
+    /// Returns the number of bytes buffered before they are written
     #[inline]
-    pub fn written(&self) -> N {
-        self.counter.written()
+    pub fn capacity(&self) -> usize {
+        self.capacity
     }
 
-    /// Plays recorded writes to the given writer
+    /// Returns the whole bytes which have been buffered
+    /// but not yet written to the underlying stream
     #[inline]
-    pub fn playback<W: BitWrite>(&self, writer: &mut W) -> io::Result<()> {
-        self.records
-            .iter()
-            .try_for_each(|record| record.playback(writer))
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer.writer
+    }
+
+    /// Writes any buffered bytes to the underlying stream
+    /// and flushes it.
+    /// Any partial bytes are not flushed.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any errors from the underlying stream.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_buffer()?;
+        self.writer.flush()
+    }
+
+    /// Writes any buffered bytes to the underlying stream,
+    /// then unwraps internal writer and disposes of BufBitWriter.
+    ///
+    /// # Warning
+    ///
+    /// Any unwritten partial bits are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any errors from the underlying stream.
+    pub fn into_writer(mut self) -> io::Result<W> {
+        self.write_buffer()?;
+        Ok(self.writer)
+    }
+
+    fn write_buffer(&mut self) -> io::Result<()> {
+        let buffer = &mut self.buffer.writer;
+        if !buffer.is_empty() {
+            self.writer.write_all(buffer)?;
+            buffer.clear();
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn check_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.writer.len() >= self.capacity {
+            self.write_buffer()
+        } else {
+            Ok(())
+        }
     }
 }
 
-impl<N, E> BitWrite for BitRecorder<N, E>
-where
-    E: Endianness,
-    N: Copy + From<u32> + AddAssign + Rem<Output = N> + Eq,
-{
+impl<W: io::Write, E: Endianness> BitWrite for BufBitWriter<W, E> {
     #[inline]
     fn write_bit(&mut self, bit: bool) -> io::Result<()> {
-        self.records.push(WriteRecord::Bit(bit));
-        self.counter.write_bit(bit)
+        self.buffer.write_bit(bit)?;
+        self.check_buffer()
     }
 
     #[inline]
@@ -876,12 +4495,8 @@ where
     where
         U: Numeric,
     {
-        self.counter.write(bits, value)?;
-        self.records.push(WriteRecord::Unsigned {
-            bits,
-            value: value.unsigned_value(),
-        });
-        Ok(())
+        self.buffer.write(bits, value)?;
+        self.check_buffer()
     }
 
     #[inline]
@@ -889,50 +4504,189 @@ where
     where
         S: SignedNumeric,
     {
-        self.counter.write_signed(bits, value)?;
-        self.records.push(WriteRecord::Signed {
-            bits,
-            value: value.signed_value(),
-        });
-        Ok(())
+        self.buffer.write_signed(bits, value)?;
+        self.check_buffer()
+    }
+
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        if self.buffer.byte_aligned() && buf.len() >= self.capacity {
+            // large aligned buffers bypass the internal buffer
+            self.write_buffer()?;
+            self.writer.write_all(buf)
+        } else {
+            self.buffer.write_bytes(buf)?;
+            self.check_buffer()
+        }
+    }
+
+    fn write_run(&mut self, bit: bool, mut count: u64) -> io::Result<()> {
+        // long runs are buffered a capacity at a time
+        let step = self.capacity.max(1) as u64 * 8;
+        while count > step {
+            self.buffer.write_run(bit, step)?;
+            self.check_buffer()?;
+            count -= step;
+        }
+        self.buffer.write_run(bit, count)?;
+        self.check_buffer()
     }
 
     #[inline]
-    fn write_unary0(&mut self, value: u32) -> io::Result<()> {
-        self.records.push(WriteRecord::Unary0(value));
-        self.counter.write_unary0(value)
+    fn byte_aligned(&self) -> bool {
+        self.buffer.byte_aligned()
     }
+}
 
+impl<W: io::Write, E: Endianness> HuffmanWrite<E> for BufBitWriter<W, E> {
     #[inline]
-    fn write_unary1(&mut self, value: u32) -> io::Result<()> {
-        self.records.push(WriteRecord::Unary1(value));
-        self.counter.write_unary1(value)
+    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
+    where
+        T: Ord + Copy,
+    {
+        self.buffer.write_huffman(tree, symbol)?;
+        self.check_buffer()
+    }
+}
+
+/// An `io::Write` which delivers its output to a callback
+/// in chunks, for sending output to a socket, ring buffer
+/// or foreign consumer without an `io::Write` implementation of its own.
+///
+/// Bytes are buffered until at least the writer's capacity
+/// has been written, or until it is flushed,
+/// and each chunk is then passed to the callback.
+/// Any error from the callback is passed along to the writer.
+///
+/// **Buffered bytes will be lost** if the writer is disposed of
+/// without calling `flush` or `into_callback`.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWrite, BitWriter, CallbackWriter};
+/// let mut chunks = Vec::new();
+/// let mut writer = BitWriter::endian(
+///     CallbackWriter::with_capacity(2, |chunk: &[u8]| {
+///         chunks.push(chunk.to_vec());
+///         Ok(())
+///     }),
+///     BigEndian,
+/// );
+/// writer.write(24, 0x123456u32).unwrap();
+/// writer.write(4, 0b1011u8).unwrap();
+/// writer.byte_align().unwrap();
+/// writer.flush().unwrap();
+/// drop(writer);
+/// assert_eq!(chunks, [vec![0x12, 0x34, 0x56], vec![0xB0]]);
+/// ```
+#[derive(Clone)]
+pub struct CallbackWriter<F> {
+    callback: F,
+    buffer: Vec<u8>,
+    capacity: usize,
+}
+
+impl<F> fmt::Debug for CallbackWriter<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CallbackWriter")
+            .field("buffer", &self.buffer)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl<F: FnMut(&[u8]) -> io::Result<()>> CallbackWriter<F> {
+    /// Wraps a CallbackWriter with a default capacity around a callback
+    #[inline]
+    pub fn new(callback: F) -> Self {
+        Self::with_capacity(8192, callback)
     }
 
+    /// Wraps a CallbackWriter around a callback
+    /// which is given chunks of at least the given number of bytes,
+    /// other than the chunk delivered by a flush.
+    ///
+    /// A capacity of 0 passes each write to the callback as it is made.
     #[inline]
-    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.records.push(WriteRecord::Bytes(buf.into()));
-        self.counter.write_bytes(buf)
+    pub fn with_capacity(capacity: usize, callback: F) -> Self {
+        CallbackWriter {
+            callback,
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the number of bytes buffered before they are delivered
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the bytes which have been buffered
+    /// but not yet delivered to the callback
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Delivers any buffered bytes to the callback,
+    /// then unwraps it and disposes of CallbackWriter.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the callback.
+    pub fn into_callback(mut self) -> io::Result<F> {
+        self.deliver()?;
+        Ok(self.callback)
+    }
+
+    fn deliver(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            (self.callback)(&self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&[u8]) -> io::Result<()>> io::Write for CallbackWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let buffered = self.buffer.len();
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.capacity.max(1) {
+            if let Err(err) = self.deliver() {
+                // a failed write leaves nothing written
+                self.buffer.truncate(buffered);
+                return Err(err);
+            }
+        }
+        Ok(buf.len())
     }
 
     #[inline]
-    fn byte_aligned(&self) -> bool {
-        self.counter.byte_aligned()
+    fn flush(&mut self) -> io::Result<()> {
+        self.deliver()
     }
 }
 
-impl<N, E> HuffmanWrite<E> for BitRecorder<N, E>
+/// Writes the bits of a signed value other than its sign bit
+fn write_sign_rest<W, S>(writer: &mut W, bits: u32, rest: S) -> io::Result<()>
 where
-    E: Endianness,
-    N: Copy + From<u32> + AddAssign + Rem<Output = N> + Eq,
+    W: BitWrite + ?Sized,
+    S: SignedNumeric,
 {
-    #[inline]
-    fn write_huffman<T>(&mut self, tree: &WriteHuffmanTree<E, T>, symbol: T) -> io::Result<()>
-    where
-        T: Ord + Copy,
-    {
-        tree.get(&symbol)
-            .try_for_each(|(bits, value)| self.write(*bits, *value))
+    if bits < S::bits_size() {
+        writer.write(bits - 1, rest)
+    } else {
+        // the remaining bits of a full-width value can't be
+        // written as an unsigned value of the same type,
+        // but have the same pattern as a signed value one bit narrower
+        let narrowed = if rest >= S::one() << (bits - 2) {
+            rest.as_negative(bits)
+        } else {
+            rest
+        };
+        writer.write_signed(bits - 1, narrowed)
     }
 }
 
@@ -945,55 +4699,34 @@ where
     writer.write_all(&buf)
 }
 
-fn write_unaligned<W, E, N>(
+/// For writing aligned bytes to a stream of bytes in a given endianness.
+///
+/// This only writes aligned values and maintains no internal state
+/// beyond a count of the bytes written.
+pub struct ByteWriter<W: io::Write, E: Endianness> {
+    phantom: PhantomData<E>,
     writer: W,
-    acc: &mut BitQueue<E, N>,
-    rem: &mut BitQueue<E, u8>,
-) -> io::Result<()>
-where
-    W: io::Write,
-    E: Endianness,
-    N: Numeric,
-{
-    if rem.is_empty() {
-        Ok(())
-    } else {
-        use std::cmp::min;
-        let bits_to_transfer = min(8 - rem.len(), acc.len());
-        rem.push(bits_to_transfer, acc.pop(bits_to_transfer).to_u8());
-        if rem.len() == 8 {
-            write_byte(writer, rem.pop(8))
-        } else {
-            Ok(())
-        }
-    }
+    position: u64,
 }
 
-fn write_aligned<W, E, N>(mut writer: W, acc: &mut BitQueue<E, N>) -> io::Result<()>
-where
-    W: io::Write,
-    E: Endianness,
-    N: Numeric,
-{
-    let to_write = (acc.len() / 8) as usize;
-    if to_write > 0 {
-        let mut buf = N::buffer();
-        let buf_ref: &mut [u8] = buf.as_mut();
-        for b in buf_ref[0..to_write].iter_mut() {
-            *b = acc.pop(8).to_u8();
-        }
-        writer.write_all(&buf_ref[0..to_write])
-    } else {
-        Ok(())
+impl<W: io::Write + Clone, E: Endianness> Clone for ByteWriter<W, E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ByteWriter {
+            phantom: PhantomData,
+            writer: self.writer.clone(),
+            position: self.position,
+        }
     }
 }
 
-/// For writing aligned bytes to a stream of bytes in a given endianness.
-///
-/// This only writes aligned values and maintains no internal state.
-pub struct ByteWriter<W: io::Write, E: Endianness> {
-    phantom: PhantomData<E>,
-    writer: W,
+impl<W: io::Write + fmt::Debug, E: Endianness> fmt::Debug for ByteWriter<W, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ByteWriter")
+            .field("writer", &self.writer)
+            .field("position", &self.position)
+            .finish()
+    }
 }
 
 impl<W: io::Write, E: Endianness> ByteWriter<W, E> {
@@ -1002,6 +4735,7 @@ impl<W: io::Write, E: Endianness> ByteWriter<W, E> {
         ByteWriter {
             phantom: PhantomData,
             writer,
+            position: 0,
         }
     }
 
@@ -1011,6 +4745,7 @@ impl<W: io::Write, E: Endianness> ByteWriter<W, E> {
         ByteWriter {
             phantom: PhantomData,
             writer,
+            position: 0,
         }
     }
 
@@ -1027,6 +4762,41 @@ impl<W: io::Write, E: Endianness> ByteWriter<W, E> {
         &mut self.writer
     }
 
+    /// Returns the number of bytes written so far.
+    ///
+    /// This counts from where the writer was created,
+    /// even after seeking, so it is only the offset
+    /// from the start of the stream if the writer was created there.
+    /// Bytes written directly to the internal writer,
+    /// or through the temporary writer from `bitwriter`, aren't counted.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, ByteWriter, ByteWrite};
+    /// let mut writer = ByteWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_bytes(b"RIFF").unwrap();
+    /// let size_offset = writer.position();
+    /// writer.write(0u32).unwrap();
+    /// writer.write_cstr("WAVE").unwrap();
+    /// assert_eq!(size_offset, 4);
+    /// assert_eq!(writer.position(), 13);
+    /// ```
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Provides temporary `BitWriter` over the internal writer
+    /// which adds the bytes it writes to the position
+    #[inline]
+    fn counted_bitwriter(&mut self) -> BitWriter<CountingWriter<'_, W>, E> {
+        BitWriter::new(CountingWriter {
+            writer: &mut self.writer,
+            count: &mut self.position,
+        })
+    }
+
     /// Converts `ByteWriter` to `BitWriter` in the same endianness.
     #[inline]
     pub fn into_bitwriter(self) -> BitWriter<W, E> {
@@ -1042,9 +4812,198 @@ impl<W: io::Write, E: Endianness> ByteWriter<W, E> {
     pub fn bitwriter(&mut self) -> BitWriter<&mut W, E> {
         BitWriter::new(self.writer())
     }
+
+    /// Writes a floating point value to the stream
+    /// by way of its bit pattern, in the stream's endianness.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the float's size is not a whole number of bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, ByteWriter};
+    /// let mut writer = ByteWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_float(1.5f32).unwrap();
+    /// writer.write_float(-2.0f64).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x3F, 0xC0, 0x00, 0x00,
+    ///                                   0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    /// ```
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, ByteWriter};
+    /// use bitstream_io::float::Extended;
+    /// let mut writer = ByteWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_float(Extended::from_f64(44100.0)).unwrap();
+    /// assert_eq!(writer.into_writer(), [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x44, 0xAC, 0x0E, 0x40]);
+    /// ```
+    pub fn write_float<F>(&mut self, value: F) -> io::Result<()>
+    where
+        F: FloatNumeric,
+    {
+        if F::bits_size() % 8 == 0 {
+            self.counted_bitwriter().write_float(value)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "float size not a whole number of bytes",
+            ))
+        }
+    }
+
+    /// Writes a string followed by a null terminator.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the string itself contains a null byte.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, ByteWriter};
+    /// let mut writer = ByteWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_cstr("foo").unwrap();
+    /// assert_eq!(writer.into_writer(), b"foo\0");
+    /// ```
+    #[inline]
+    pub fn write_cstr(&mut self, s: &str) -> io::Result<()> {
+        self.counted_bitwriter().write_cstr(s)
+    }
+
+    /// Writes a string prefixed by its length in bytes,
+    /// which is written as an unsigned value of `len_bytes` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error if the string's length
+    /// is too large to fit the given number of bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, ByteWriter};
+    /// let mut writer = ByteWriter::endian(Vec::new(), LittleEndian);
+    /// writer.write_prefixed_str(2, "foo").unwrap();
+    /// assert_eq!(writer.into_writer(), [0x03, 0x00, b'f', b'o', b'o']);
+    /// ```
+    pub fn write_prefixed_str(&mut self, len_bytes: u32, s: &str) -> io::Result<()> {
+        self.counted_bitwriter()
+            .write_prefixed_str(len_bytes.saturating_mul(8), s)
+    }
+}
+
+impl<W: io::Write + io::Seek, E: Endianness> ByteWriter<W, E> {
+    /// Seeks the internal writer to the given position
+    /// and returns the new offset from the start of the stream,
+    /// moving the writer's `position` along with it.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput`,
+    /// without moving the writer, when seeking to before
+    /// where the writer was created.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::{Write, Cursor, SeekFrom};
+    /// use bitstream_io::{BigEndian, ByteWriter, ByteWrite};
+    /// let mut writer = ByteWriter::endian(Cursor::new(Vec::new()), BigEndian);
+    /// writer.write_bytes(b"chnk").unwrap();
+    /// let size_offset = writer.position();
+    /// writer.write(0u16).unwrap();
+    /// writer.write_bytes(b"data").unwrap();
+    /// let end = writer.position();
+    /// writer.seek(SeekFrom::Start(size_offset)).unwrap();
+    /// writer.write((end - size_offset - 2) as u16).unwrap();
+    /// assert_eq!(writer.seek(SeekFrom::End(0)).unwrap(), end);
+    /// assert_eq!(writer.into_writer().into_inner(), b"chnk\x00\x04data");
+    /// ```
+    ///
+    /// ```
+    /// use std::io::{Write, Cursor, SeekFrom};
+    /// use bitstream_io::{BigEndian, ByteWriter, ByteWrite};
+    /// let mut cursor = Cursor::new(Vec::new());
+    /// cursor.write_all(b"head").unwrap();
+    /// let mut writer = ByteWriter::endian(cursor, BigEndian);
+    /// writer.write(0u16).unwrap();
+    /// assert_eq!(writer.position(), 2);
+    /// assert_eq!(writer.seek(SeekFrom::Current(-2)).unwrap(), 4);
+    /// assert_eq!(writer.position(), 0);
+    /// assert!(writer.seek(SeekFrom::Start(0)).is_err());
+    /// writer.write(0xFFFFu16).unwrap();
+    /// assert_eq!(writer.position(), 2);
+    /// assert_eq!(writer.into_writer().into_inner(), b"head\xFF\xFF");
+    /// ```
+    pub fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let current = self.writer.stream_position()?;
+        let start = current.saturating_sub(self.position);
+        let offset = self.writer.seek(pos)?;
+        match offset.checked_sub(start) {
+            Some(position) => {
+                self.position = position;
+                Ok(offset)
+            }
+            None => {
+                self.writer.seek(io::SeekFrom::Start(current))?;
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "seek to before where writer was created",
+                ))
+            }
+        }
+    }
+}
+
+/// A writer which counts the bytes written through it
+struct CountingWriter<'a, W> {
+    writer: &'a mut W,
+    count: &'a mut u64,
+}
+
+impl<'a, W: io::Write> io::Write for CountingWriter<'a, W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        *self.count += written as u64;
+        Ok(written)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
 }
 
 /// A trait for anything that can write aligned values to an output stream
+///
+/// Serializers written against this trait accept
+/// a `ByteWriter` of either endianness, a mutable reference to one,
+/// or the temporary `ByteWriter` of a byte-aligned `BitWriter`.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, BitWriter, BitWrite, ByteWrite, ByteWriter, LittleEndian};
+/// fn write_header<W: ByteWrite>(mut w: W) -> std::io::Result<()> {
+///     w.write(0x1234u16)?;
+///     w.write(-2i8)
+/// }
+/// let mut writer = ByteWriter::endian(Vec::new(), LittleEndian);
+/// write_header(&mut writer).unwrap();
+/// assert_eq!(writer.into_writer(), [0x34, 0x12, 0xFE]);
+/// let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+/// writer.write(4, 0b1010u8).unwrap();
+/// assert!(writer.bytewriter().is_none());
+/// writer.write(4, 0b0101u8).unwrap();
+/// write_header(writer.bytewriter().unwrap()).unwrap();
+/// assert_eq!(writer.into_writer(), [0b10100101, 0x12, 0x34, 0xFE]);
+/// ```
 pub trait ByteWrite {
     /// Writes whole numeric value to stream
     ///
@@ -1075,16 +5034,230 @@ pub trait ByteWrite {
     ///
     /// Passes along any I/O error from the underlying stream.
     fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()>;
+
+    /// Writes a whole byte from its high and low nibbles.
+    /// The high nibble is always first, regardless of endianness.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput`
+    /// if either nibble is larger than 15.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{BigEndian, ByteWriter, ByteWrite};
+    /// let mut writer = ByteWriter::endian(Vec::new(), BigEndian);
+    /// writer.write_nibbles(1, 2).unwrap();
+    /// writer.write_nibbles(5, 9).unwrap();
+    /// assert!(writer.write_nibbles(16, 0).is_err());
+    /// assert_eq!(writer.into_writer(), [0x12, 0x59]);
+    /// ```
+    fn write_nibbles(&mut self, high: u8, low: u8) -> io::Result<()> {
+        if high > 0x0F || low > 0x0F {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for nibble",
+            ))
+        } else {
+            self.write((high << 4) | low)
+        }
+    }
+
+    /// Builds and writes a complex type
+    ///
+    /// # Errors
+    ///
+    /// Passes along any error from the type's `to_writer` method.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use bitstream_io::{LittleEndian, ByteWriter, ByteWrite, ToByteStream};
+    ///
+    /// struct ChunkHeader {
+    ///     id: [u8; 4],
+    ///     size: u32,
+    /// }
+    ///
+    /// impl ToByteStream for ChunkHeader {
+    ///     type Error = std::io::Error;
+    ///
+    ///     fn to_writer<W: ByteWrite + ?Sized>(&self, w: &mut W) -> std::io::Result<()> {
+    ///         w.write_bytes(&self.id)?;
+    ///         w.write(self.size)
+    ///     }
+    /// }
+    ///
+    /// let mut writer = ByteWriter::endian(Vec::new(), LittleEndian);
+    /// writer.build(&ChunkHeader { id: *b"RIFF", size: 0x824 }).unwrap();
+    /// assert_eq!(writer.into_writer(), [b'R', b'I', b'F', b'F', 0x24, 0x08, 0x00, 0x00]);
+    /// ```
+    #[inline]
+    fn build<T: ToByteStream>(&mut self, build: &T) -> Result<(), T::Error> {
+        build.to_writer(self)
+    }
+}
+
+/// Implemented by complex types that can be written to a `ByteWrite`
+/// with the `build` method.
+pub trait ToByteStream {
+    /// Error generated during building, such as `io::Error`
+    type Error;
+
+    /// Writes the type to the given byte stream
+    fn to_writer<W: ByteWrite + ?Sized>(&self, w: &mut W) -> Result<(), Self::Error>
+    where
+        Self: Sized;
 }
 
 impl<W: io::Write, E: Endianness> ByteWrite for ByteWriter<W, E> {
     #[inline]
     fn write<N: Numeric>(&mut self, value: N) -> io::Result<()> {
-        E::write_numeric(&mut self.writer, value)
+        E::write_numeric(&mut self.writer, value)?;
+        self.position += u64::from(N::bits_size() / 8);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer.write_all(buf)?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+}
+
+/// For writing a stream of 4-bit nibbles to a byte writer.
+///
+/// Each pair of nibbles is written as one byte,
+/// with the first nibble of the pair in the byte's high bits,
+/// which suits naturally 4-bit aligned formats
+/// such as packed BCD digits
+/// without needing a full `BitWriter`.
+///
+/// # Example
+/// ```
+/// use std::io::Write;
+/// use bitstream_io::{BigEndian, ByteWriter, NibbleWriter};
+/// let mut writer = NibbleWriter::new(ByteWriter::endian(Vec::new(), BigEndian));
+/// writer.write_nibble(1).unwrap();
+/// writer.write_nibbles(2, 3).unwrap();
+/// writer.write_byte(0x45).unwrap();
+/// assert!(!writer.byte_aligned());
+/// writer.byte_align().unwrap();
+/// assert_eq!(writer.into_writer().into_writer(), [0x12, 0x34, 0x50]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct NibbleWriter<W: ByteWrite> {
+    writer: W,
+    pending: Option<u8>,
+}
+
+impl<W: ByteWrite> NibbleWriter<W> {
+    /// Wraps a NibbleWriter around something that implements `ByteWrite`
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        NibbleWriter {
+            writer,
+            pending: None,
+        }
+    }
+
+    /// Writes a single nibble to the stream.
+    /// Every second nibble completes a byte,
+    /// which is then written to the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput`
+    /// if the nibble is larger than 15.
+    pub fn write_nibble(&mut self, nibble: u8) -> io::Result<()> {
+        if nibble > 0x0F {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for nibble",
+            ))
+        } else {
+            match self.pending.take() {
+                Some(high) => self.writer.write_nibbles(high, nibble),
+                None => {
+                    self.pending = Some(nibble);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Writes two nibbles to the stream, the high one first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    /// Returns an error of kind `InvalidInput`
+    /// if either nibble is larger than 15.
+    pub fn write_nibbles(&mut self, high: u8, low: u8) -> io::Result<()> {
+        if self.pending.is_none() {
+            self.writer.write_nibbles(high, low)
+        } else if high > 0x0F || low > 0x0F {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "excessive value for nibble",
+            ))
+        } else {
+            self.write_nibble(high)?;
+            self.write_nibble(low)
+        }
+    }
+
+    /// Writes a whole byte to the stream as two nibbles,
+    /// the high one first.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    #[inline]
+    pub fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        self.write_nibbles(byte >> 4, byte & 0x0F)
+    }
+
+    /// Returns true if the stream is aligned at a whole byte.
+    #[inline]
+    pub fn byte_aligned(&self) -> bool {
+        self.pending.is_none()
+    }
+
+    /// Pads the stream with a 0 nibble, if necessary,
+    /// to align it at a whole byte.
+    ///
+    /// # Errors
+    ///
+    /// Passes along any I/O error from the underlying stream.
+    #[inline]
+    pub fn byte_align(&mut self) -> io::Result<()> {
+        match self.pending {
+            Some(_) => self.write_nibble(0),
+            None => Ok(()),
+        }
+    }
+
+    /// Unwraps internal writer and disposes of `NibbleWriter`.
+    /// Any unwritten partial nibble is discarded.
+    #[inline]
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: ByteWrite + ?Sized> ByteWrite for &mut W {
+    #[inline]
+    fn write<N: Numeric>(&mut self, value: N) -> io::Result<()> {
+        (**self).write(value)
     }
 
     #[inline]
     fn write_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.writer.write_all(buf)
+        (**self).write_bytes(buf)
     }
 }
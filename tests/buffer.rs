@@ -0,0 +1,116 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::buffer::BitBuffer;
+use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter, LittleEndian};
+use std::io::Cursor;
+
+macro_rules! define_buffer_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut buffer: BitBuffer<$endianness> = BitBuffer::new();
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            let mut total = 0;
+            for bits in 1..=32 {
+                let value = (0xDEAD_BEEFu64 % (1 << bits)) as u32;
+                buffer.write(bits, value).unwrap();
+                writer.write(bits, value).unwrap();
+                buffer.write_signed(bits + 1, -1i64).unwrap();
+                writer.write_signed(bits + 1, -1i64).unwrap();
+                total += bits + bits + 1;
+            }
+            buffer.write_bytes(b"foo").unwrap();
+            writer.write_bytes(b"foo").unwrap();
+            buffer.write_unary0(9).unwrap();
+            writer.write_unary0(9).unwrap();
+            buffer.write_unary1(3).unwrap();
+            writer.write_unary1(3).unwrap();
+            total += 24 + 10 + 4;
+            assert_eq!(buffer.len_bits(), u64::from(total));
+
+            // a slice of any range reads the same bits as the original stream
+            for start in (0..u64::from(total)).step_by(37) {
+                let end = std::cmp::min(start + 100, u64::from(total));
+                let mut slice = buffer.slice(start..end);
+                assert_eq!(slice.len_bits(), end - start);
+                let mut copy = buffer.clone();
+                copy.skip(start).unwrap();
+                for _ in start..end {
+                    assert_eq!(slice.read_bit().unwrap(), copy.read_bit().unwrap());
+                }
+                assert!(slice.read_bit().is_err());
+            }
+
+            // padding the buffer yields the same bytes as a BitWriter
+            let mut padded = buffer.clone();
+            BitWrite::byte_align(&mut padded).unwrap();
+            writer.byte_align().unwrap();
+            let output = writer.into_writer();
+            let (bytes, trailing_bits) = padded.into_parts();
+            assert_eq!(trailing_bits, 0);
+            assert_eq!(bytes, output);
+
+            // and the unpadded parts read back the same as a BitReader
+            let (bytes, trailing_bits) = buffer.into_parts();
+            assert_eq!(trailing_bits, total % 8);
+            let mut buffer: BitBuffer<$endianness> = BitBuffer::from_parts(bytes, trailing_bits);
+            let mut reader = BitReader::endian(Cursor::new(&output), $endianness);
+            for bits in 1..=32 {
+                assert_eq!(
+                    buffer.read::<u32>(bits).unwrap(),
+                    reader.read::<u32>(bits).unwrap()
+                );
+                assert_eq!(buffer.read_signed::<i64>(bits + 1).unwrap(), -1);
+                reader.skip(u64::from(bits + 1)).unwrap();
+            }
+            let mut buf = [0; 3];
+            buffer.read_bytes(&mut buf).unwrap();
+            assert_eq!(&buf, b"foo");
+            assert_eq!(buffer.read_unary0().unwrap(), 9);
+            assert_eq!(buffer.read_unary1().unwrap(), 3);
+            assert!(buffer.read_bit().is_err());
+        }
+    };
+}
+
+define_buffer_roundtrip!(test_buffer_roundtrip_be, BigEndian);
+define_buffer_roundtrip!(test_buffer_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_buffer_errors() {
+    let mut buffer: BitBuffer<BigEndian> = BitBuffer::new();
+    assert!(buffer.is_empty());
+    assert!(buffer.write(9, 0u8).is_err());
+    assert!(buffer.write(4, 16u8).is_err());
+    assert!(buffer.write_signed(9, 0i8).is_err());
+    assert!(buffer.is_empty());
+
+    buffer.write(12, 0xABCu16).unwrap();
+    assert!(buffer.read::<u8>(9).is_err());
+    assert!(buffer.read::<u16>(13).is_err());
+    let mut buf = [0; 2];
+    assert!(buffer.read_bytes(&mut buf).is_err());
+    assert!(buffer.skip(13).is_err());
+    assert_eq!(buffer.read::<u16>(12).unwrap(), 0xABC);
+
+    // aligning the read position stops at the end of the buffer
+    buffer.write(3, 0u8).unwrap();
+    buffer.read_bit().unwrap();
+    BitRead::byte_align(&mut buffer);
+    assert!(buffer.read_bit().is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_buffer_slice_out_of_bounds() {
+    let mut buffer: BitBuffer<LittleEndian> = BitBuffer::new();
+    buffer.write(10, 0u16).unwrap();
+    buffer.slice(4..11);
+}
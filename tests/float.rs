@@ -0,0 +1,218 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::float::{Extended, Half};
+use bitstream_io::{
+    BigEndian, BitRead, BitReader, BitWrite, BitWriter, ByteReader, ByteWriter, LittleEndian,
+};
+use std::io::Cursor;
+
+/// Yields a spread of f64 bit patterns covering every exponent
+fn f64_patterns() -> impl Iterator<Item = u64> {
+    let mut state = 0x1234_5678_9ABC_DEF0u64;
+    (0..20000).map(move |i| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        match i % 4 {
+            0 => state,
+            1 => state & 0x800F_FFFF_FFFF_FFFF,
+            2 => state | 0x7FF0_0000_0000_0000,
+            _ => (state & 0x800F_FFFF_FFFF_FFFF) | ((i as u64 % 0x800) << 52),
+        }
+    })
+}
+
+#[test]
+fn test_half_roundtrip() {
+    for bits in 0..=u16::MAX {
+        let half = Half::from_bits(bits);
+        let double = half.to_f64();
+        if double.is_nan() {
+            assert!(Half::from_f64(double).to_f64().is_nan());
+        } else {
+            assert_eq!(Half::from_f64(double), half);
+        }
+    }
+}
+
+#[test]
+fn test_half_rounding() {
+    // every value between two adjacent halves
+    // rounds to the nearer one, or the even one when tied
+    for bits in 0..0x7BFFu16 {
+        let low = Half::from_bits(bits).to_f64();
+        let high = Half::from_bits(bits + 1).to_f64();
+        let even = if bits % 2 == 0 { bits } else { bits + 1 };
+        let mid = (low + high) / 2.0;
+        assert_eq!(Half::from_f64(mid).to_bits(), even);
+        assert_eq!(Half::from_f64(-mid).to_bits(), even | 0x8000);
+        assert_eq!(Half::from_f64(low + (high - low) / 4.0).to_bits(), bits);
+        assert_eq!(
+            Half::from_f64(high - (high - low) / 4.0).to_bits(),
+            bits + 1
+        );
+    }
+
+    // values halfway past the largest half overflow to infinity
+    assert_eq!(Half::from_f64(65520.0).to_bits(), 0x7C00);
+    assert_eq!(Half::from_f64(65519.0).to_bits(), 0x7BFF);
+    assert_eq!(Half::from_f64(f64::INFINITY).to_bits(), 0x7C00);
+    assert_eq!(Half::from_f64(f64::NEG_INFINITY).to_bits(), 0xFC00);
+
+    // values below half the smallest subnormal become zero
+    assert_eq!(Half::from_f64(2.0f64.powi(-25)).to_bits(), 0);
+    assert_eq!(Half::from_f64(2.0f64.powi(-25) * 1.5).to_bits(), 1);
+    assert_eq!(Half::from_f64(-0.0).to_bits(), 0x8000);
+    assert_eq!(Half::from_f64(f64::MIN_POSITIVE / 2.0).to_bits(), 0);
+}
+
+#[test]
+fn test_half_matches_f32() {
+    // halves convert exactly to f32, so compare through it
+    for bits in 0..=u16::MAX {
+        let double = Half::from_bits(bits).to_f64();
+        if !double.is_nan() {
+            assert_eq!(f64::from(double as f32), double);
+        }
+    }
+}
+
+#[test]
+fn test_extended_roundtrip() {
+    for bits in f64_patterns() {
+        let double = f64::from_bits(bits);
+        let converted = Extended::from_f64(double).to_f64();
+        if double.is_nan() {
+            assert!(converted.is_nan());
+        } else {
+            assert_eq!(converted.to_bits(), bits);
+        }
+    }
+    assert_eq!(
+        Extended::from_f64(f64::MIN_POSITIVE / 4.0).to_f64(),
+        f64::MIN_POSITIVE / 4.0
+    );
+    assert_eq!(Extended::from_f64(f64::MAX).to_f64(), f64::MAX);
+}
+
+#[test]
+fn test_extended_rounding() {
+    let one = 0x3FFF_8000_0000_0000_0000u128;
+
+    // 11 extra significand bits round to nearest, ties to even
+    assert_eq!(Extended::from_bits(one | 0x3FF).to_f64(), 1.0);
+    assert_eq!(Extended::from_bits(one | 0x400).to_f64(), 1.0);
+    assert_eq!(
+        Extended::from_bits(one | 0x401).to_f64(),
+        1.0 + f64::EPSILON
+    );
+    assert_eq!(
+        Extended::from_bits(one | 0xC00).to_f64(),
+        1.0 + 2.0 * f64::EPSILON
+    );
+
+    // carrying out of the significand increments the exponent
+    assert_eq!(
+        Extended::from_bits(0x3FFF_FFFF_FFFF_FFFF_FFFF).to_f64(),
+        2.0
+    );
+    assert_eq!(
+        Extended::from_bits(0x43FE_FFFF_FFFF_FFFF_FFFF).to_f64(),
+        f64::INFINITY
+    );
+    assert_eq!(
+        Extended::from_bits(0xC3FE_FFFF_FFFF_FFFF_FC00).to_f64(),
+        f64::NEG_INFINITY
+    );
+    assert_eq!(
+        Extended::from_bits(0x43FE_FFFF_FFFF_FFFF_F7FF).to_f64(),
+        f64::MAX
+    );
+
+    // unnormalized and denormal significands are normalized
+    assert_eq!(
+        Extended::from_bits(0x4000_4000_0000_0000_0000).to_f64(),
+        1.0
+    );
+    assert_eq!(
+        Extended::from_bits(0x0000_0000_0000_0000_0001).to_f64(),
+        0.0
+    );
+    assert_eq!(
+        Extended::from_bits(0x3BCD_8000_0000_0000_0000).to_f64(),
+        f64::from_bits(1)
+    );
+    assert_eq!(
+        Extended::from_bits(0x3BCC_8000_0000_0000_0000).to_f64(),
+        0.0
+    );
+    assert_eq!(
+        Extended::from_bits(0x3BCC_8000_0000_0000_0001).to_f64(),
+        f64::from_bits(1)
+    );
+
+    assert!(Extended::from_bits(0x7FFF_C000_0000_0000_0000)
+        .to_f64()
+        .is_nan());
+    assert_eq!(
+        Extended::from_bits(0xFFFF_8000_0000_0000_0000).to_f64(),
+        f64::NEG_INFINITY
+    );
+}
+
+macro_rules! define_float_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            writer.write(3, 0b101u8).unwrap();
+            for bits in f64_patterns().take(100) {
+                let double = f64::from_bits(bits);
+                writer.write_float(double).unwrap();
+                writer.write_float(double as f32).unwrap();
+                writer.write_float(Half::from_f64(double)).unwrap();
+                writer.write_float(Extended::from_f64(double)).unwrap();
+            }
+            writer.write(5, 0u8).unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            for bits in f64_patterns().take(100) {
+                let double = f64::from_bits(bits);
+                assert_eq!(reader.read_float::<f64>().unwrap().to_bits(), bits);
+                assert_eq!(
+                    reader.read_float::<f32>().unwrap().to_bits(),
+                    (double as f32).to_bits()
+                );
+                assert_eq!(reader.read_float::<Half>().unwrap(), Half::from_f64(double));
+                assert_eq!(
+                    reader.read_float::<Extended>().unwrap(),
+                    Extended::from_f64(double)
+                );
+            }
+
+            let mut writer = ByteWriter::endian(Vec::new(), $endianness);
+            writer.write_float(1.0f64).unwrap();
+            writer.write_float(Extended::from_f64(-0.5)).unwrap();
+            writer.write_float(Half::from_f64(2.0)).unwrap();
+            let data = writer.into_writer();
+            assert_eq!(data.len(), 8 + 10 + 2);
+            let mut reader = ByteReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read_float::<f64>().unwrap(), 1.0);
+            assert_eq!(reader.read_float::<Extended>().unwrap().to_f64(), -0.5);
+            assert_eq!(reader.read_float::<Half>().unwrap().to_f64(), 2.0);
+            assert!(reader.read_float::<f32>().is_err());
+        }
+    };
+}
+
+define_float_roundtrip!(test_float_roundtrip_be, BigEndian);
+define_float_roundtrip!(test_float_roundtrip_le, LittleEndian);
@@ -155,3 +155,652 @@ fn test_lengthy_huffman_values() {
         }
     }
 }
+
+#[test]
+fn test_adaptive_huffman() {
+    use bitstream_io::huffman::AdaptiveHuffmanTree;
+    use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter, LittleEndian};
+    use std::io::Cursor;
+
+    // a skewed sequence of symbols, with some rare ones
+    let mut state = 12345u32;
+    let symbols: Vec<u32> = (0..5000)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            let r = (state >> 16) % 100;
+            if r < 50 {
+                3
+            } else if r < 75 {
+                200
+            } else if r < 95 {
+                r
+            } else {
+                state & 0xFFFF
+            }
+        })
+        .collect();
+
+    let mut tree_be = AdaptiveHuffmanTree::new(16);
+    let mut tree_le = AdaptiveHuffmanTree::new(16);
+    let mut writer_be = BitWriter::endian(Vec::new(), BigEndian);
+    let mut writer_le = BitWriter::endian(Vec::new(), LittleEndian);
+    for symbol in symbols.iter() {
+        writer_be
+            .write_adaptive_huffman(&mut tree_be, *symbol)
+            .unwrap();
+        writer_le
+            .write_adaptive_huffman(&mut tree_le, *symbol)
+            .unwrap();
+    }
+    assert_eq!(tree_be.frequency(3), tree_le.frequency(3));
+    writer_be.byte_align().unwrap();
+    writer_le.byte_align().unwrap();
+    let data_be = writer_be.into_writer();
+    let data_le = writer_le.into_writer();
+
+    // frequent symbols take far less than their raw size
+    assert!(data_be.len() < symbols.len());
+
+    let mut tree_be = AdaptiveHuffmanTree::new(16);
+    let mut tree_le = AdaptiveHuffmanTree::new(16);
+    let mut reader_be = BitReader::endian(Cursor::new(&data_be), BigEndian);
+    let mut reader_le = BitReader::endian(Cursor::new(&data_le), LittleEndian);
+    for symbol in symbols.iter() {
+        assert_eq!(
+            reader_be.read_adaptive_huffman(&mut tree_be).unwrap(),
+            *symbol
+        );
+        assert_eq!(
+            reader_le.read_adaptive_huffman(&mut tree_le).unwrap(),
+            *symbol
+        );
+    }
+
+    // full-width symbols are escaped in 32 bits
+    let mut tree = AdaptiveHuffmanTree::new(32);
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    for symbol in [u32::MAX, 0, u32::MAX].iter() {
+        writer.write_adaptive_huffman(&mut tree, *symbol).unwrap();
+    }
+    writer.byte_align().unwrap();
+    let data = writer.into_writer();
+    let mut tree = AdaptiveHuffmanTree::new(32);
+    let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    for symbol in [u32::MAX, 0, u32::MAX].iter() {
+        assert_eq!(reader.read_adaptive_huffman(&mut tree).unwrap(), *symbol);
+    }
+    assert_eq!(tree.frequency(u32::MAX), 2);
+}
+
+#[test]
+fn test_compact_huffman() {
+    use bitstream_io::huffman::{compile_compact_read_tree, compile_write_tree};
+    use bitstream_io::{
+        BigEndian, BitRead, BitReader, BitWrite, BitWriter, HuffmanRead, HuffmanWrite, LittleEndian,
+    };
+    use std::io::Cursor;
+
+    // a balanced 4-bit subtree under each of a long chain of prefixes
+    let mut spec = Vec::new();
+    for prefix in 0..20usize {
+        for suffix in 0..16u8 {
+            let mut code = vec![0; prefix];
+            code.push(1);
+            code.extend((0..4).map(|bit| (suffix >> bit) & 1));
+            spec.push(((prefix * 16) as u32 + u32::from(suffix), code));
+        }
+    }
+    spec.push((u32::MAX, vec![0; 20]));
+    let symbols: Vec<u32> = (0..20 * 16)
+        .chain(std::iter::once(u32::MAX))
+        .chain((0..20 * 16).rev())
+        .collect();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let write_tree = compile_write_tree::<$endianness, u32>(spec.clone()).unwrap();
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for symbol in symbols.iter() {
+                writer.write_huffman(&write_tree, *symbol).unwrap();
+            }
+            writer.write(7, 0b1010101u8).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            for lookup_bits in 1..=16 {
+                let tree = compile_compact_read_tree::<$endianness, u32>(spec.clone(), lookup_bits)
+                    .unwrap();
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                for symbol in symbols.iter() {
+                    assert_eq!(reader.read_compact_huffman(&tree).unwrap(), *symbol);
+                }
+                // no bits past the final code are consumed
+                assert_eq!(reader.read::<u8>(7).unwrap(), 0b1010101);
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+
+    // a tree of a single symbol reads no bits at all
+    let tree = compile_compact_read_tree::<BigEndian, u8>(vec![(7, vec![])], 8).unwrap();
+    let data: [u8; 0] = [];
+    let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    assert_eq!(reader.read_compact_huffman(&tree).unwrap(), 7);
+}
+
+#[test]
+fn test_huffman_batch() {
+    use bitstream_io::huffman::{compile_read_tree, compile_write_tree};
+    use bitstream_io::{
+        BigEndian, BitRead, BitReader, BitWrite, BitWriter, HuffmanRead, HuffmanWrite, LittleEndian,
+    };
+    use std::io::Cursor;
+
+    let spec = vec![
+        (0i16, vec![1, 1]),
+        (1, vec![1, 0]),
+        (-1, vec![0, 1]),
+        (2, vec![0, 0, 1]),
+        (-2, vec![0, 0, 0, 1, 1, 0]),
+        (3, vec![0, 0, 0, 1, 1, 1]),
+        (-3, vec![0, 0, 0, 1, 0]),
+        (4, vec![0, 0, 0, 0]),
+    ];
+    let symbols: Vec<i16> = (0..1000).map(|i| (i * 7 % 8) as i16 - 3).collect();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let write_tree = compile_write_tree::<$endianness, i16>(spec.clone()).unwrap();
+            let read_tree = compile_read_tree::<$endianness, i16>(spec.clone()).unwrap();
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            writer.write(3, 0b101u8).unwrap();
+            writer.write_huffman_all(&write_tree, &symbols).unwrap();
+            writer.write(5, 0b10011u8).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            let mut decoded = vec![0; symbols.len()];
+            reader
+                .read_huffman_into(&read_tree, &mut decoded[..10])
+                .unwrap();
+            assert_eq!(reader.read_huffman(&read_tree).unwrap(), symbols[10]);
+            reader
+                .read_huffman_into(&read_tree, &mut decoded[11..])
+                .unwrap();
+            decoded[10] = symbols[10];
+            assert_eq!(decoded, symbols);
+            assert_eq!(reader.read::<u8>(5).unwrap(), 0b10011);
+
+            // running out of data is an error
+            let mut reader = BitReader::endian(Cursor::new(&data[..10]), $endianness);
+            assert!(reader.read_huffman_into(&read_tree, &mut decoded).is_err());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_radix_huffman() {
+    use bitstream_io::huffman::{compile_read_tree_radix, compile_write_tree_radix};
+    use bitstream_io::{
+        BigEndian, BitRead, BitReader, BitWrite, BitWriter, HuffmanRead, HuffmanWrite, LittleEndian,
+    };
+    use std::io::Cursor;
+
+    // a complete code in radix 8 with one escape digit
+    let spec: Vec<(u8, Vec<u8>)> = (0..7)
+        .map(|d| (d, vec![d]))
+        .chain((0..8).map(|d| (7 + d, vec![7, d])))
+        .collect();
+    let symbols: Vec<u8> = (0..100).map(|i| (i * 11 % 15) as u8).collect();
+
+    assert_eq!(
+        compile_write_tree_radix::<BigEndian, u8>(2, vec![(0, vec![1, 4])]).err(),
+        Some(HuffmanTreeError::InvalidDigit)
+    );
+    assert!(compile_read_tree_radix::<BigEndian, u8>(1, vec![(0, vec![0]), (1, vec![1])]).is_ok());
+    assert!(compile_read_tree_radix::<BigEndian, u8>(2, vec![(0, vec![0]), (1, vec![1])]).is_err());
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let write_tree = compile_write_tree_radix::<$endianness, u8>(3, spec.clone()).unwrap();
+            let read_tree = compile_read_tree_radix::<$endianness, u8>(3, spec.clone()).unwrap();
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for symbol in &symbols {
+                writer.write_huffman(&write_tree, *symbol).unwrap();
+            }
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            // each digit is a 3-bit chunk as written by write(3, digit)
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for symbol in &symbols {
+                if *symbol < 7 {
+                    assert_eq!(reader.read::<u8>(3).unwrap(), *symbol);
+                } else {
+                    assert_eq!(reader.read::<u8>(3).unwrap(), 7);
+                    assert_eq!(reader.read::<u8>(3).unwrap(), *symbol - 7);
+                }
+            }
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for symbol in &symbols {
+                assert_eq!(reader.read_huffman(&read_tree).unwrap(), *symbol);
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_huffman_code_len() {
+    use bitstream_io::{BigEndian, BitCounter, BitWrite, BitWriter, HuffmanWrite};
+
+    let spec = vec![
+        (0i16, vec![1, 1]),
+        (1, vec![1, 0]),
+        (-1, vec![0, 1]),
+        (2, vec![0, 0, 1]),
+        (-2, vec![0, 0, 0, 1, 1, 0]),
+        (3, vec![0, 0, 0, 1, 1, 1]),
+        (-3, vec![0, 0, 0, 1, 0]),
+        (4, (0..40).map(|i| i % 2).collect()),
+    ];
+    let tree = compile_write_tree::<BigEndian, i16>(spec.clone()).unwrap();
+    for (symbol, code) in &spec {
+        assert_eq!(tree.code_len(symbol), Some(code.len() as u32));
+    }
+    assert_eq!(tree.code_len(&5), None);
+
+    let mut counter: BitCounter<u32, BigEndian> = BitCounter::new();
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    let mut estimated = 0;
+    let mut written = 0;
+    for symbol in (0..100).map(|i| (i * 7 % 8) as i16 - 3) {
+        estimated += tree.code_len(&symbol).unwrap();
+        counter.write_huffman(&tree, symbol).unwrap();
+        written += writer.write_huffman_len(&tree, symbol).unwrap();
+    }
+    writer.byte_align().unwrap();
+    assert_eq!(estimated, counter.written());
+    assert_eq!(written, estimated);
+    assert_eq!(
+        writer.into_writer().len() as u32,
+        (written >> 3) + u32::from(written & 7 != 0)
+    );
+}
+
+#[test]
+fn test_huffman_code_errors() {
+    use bitstream_io::huffman::{
+        compile_compact_read_tree, compile_partial_compact_read_tree, HuffmanCodeError,
+    };
+    use bitstream_io::{
+        BigEndian, BitRead, BitReader, BitWrite, BitWriter, HuffmanRead, LittleEndian,
+    };
+    use std::io::Cursor;
+
+    // codes starting 1 1 1 and 0 0 0 0 have no symbol
+    let spec: Vec<(u8, Vec<u8>)> = vec![
+        (0, vec![1, 0]),
+        (1, vec![1, 1, 0]),
+        (2, vec![0, 1]),
+        (3, vec![0, 0, 1, 0, 1]),
+        (4, vec![0, 0, 1, 0, 0, 1, 1, 0, 1, 0, 1]),
+        (5, vec![0, 0, 1, 0, 0, 1, 1, 0, 1, 0, 0]),
+        (6, vec![0, 0, 1, 0, 0, 0]),
+        (7, vec![0, 0, 1, 1]),
+        (8, vec![0, 0, 0, 1]),
+    ];
+    assert_eq!(
+        compile_compact_read_tree::<BigEndian, u8>(spec.clone(), 8).unwrap_err(),
+        HuffmanTreeError::MissingLeaf
+    );
+    assert_eq!(
+        compile_partial_compact_read_tree::<BigEndian, u8>(Vec::new(), 8).unwrap_err(),
+        HuffmanTreeError::MissingLeaf
+    );
+
+    // the 1 1 1 and 0 0 0 0 prefixes are followed by a bit
+    // which shouldn't be consumed, as is every code
+    let codes: Vec<Vec<u8>> = spec
+        .iter()
+        .map(|(_, code)| code.clone())
+        .chain(vec![vec![1, 1, 1], vec![0, 0, 0, 0]])
+        .collect();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for code in codes.iter() {
+                for bit in code.iter() {
+                    writer.write_bit(*bit == 1).unwrap();
+                }
+                writer.write_bit(true).unwrap();
+            }
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            for lookup_bits in 1..=16 {
+                let tree =
+                    compile_partial_compact_read_tree::<$endianness, u8>(spec.clone(), lookup_bits)
+                        .unwrap();
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                for (symbol, _) in spec.iter() {
+                    assert_eq!(reader.read_compact_huffman(&tree).unwrap(), *symbol);
+                    assert!(reader.read_bit().unwrap());
+                }
+                for consumed in [3, 4].iter() {
+                    let err = reader.read_compact_huffman(&tree).unwrap_err();
+                    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+                    assert_eq!(
+                        HuffmanCodeError::from_io(&err),
+                        Some(&HuffmanCodeError::UnknownCode {
+                            consumed: *consumed
+                        })
+                    );
+                    assert!(reader.read_bit().unwrap());
+                }
+
+                // codes longer than the limit consume no more than the limit
+                for (symbol, code) in spec.iter() {
+                    let len = code.len() as u32;
+                    for max_bits in 0..len {
+                        let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                        let mut skipped = 0;
+                        for (_, code) in spec.iter().take_while(|(s, _)| s != symbol) {
+                            skipped += code.len() as u64 + 1;
+                        }
+                        reader.skip(skipped).unwrap();
+                        let err = reader.read_huffman_or(&tree, max_bits).unwrap_err();
+                        let consumed = HuffmanCodeError::from_io(&err).unwrap().consumed();
+                        assert!(consumed <= max_bits);
+                        reader.skip(u64::from(len - consumed)).unwrap();
+                        assert!(reader.read_bit().unwrap());
+                        assert_eq!(
+                            reader.read_huffman_or(&tree, 11).ok(),
+                            spec.get(usize::from(*symbol) + 1).map(|(s, _)| *s)
+                        );
+                    }
+                    let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                    for (s, code) in spec.iter().take_while(|(s, _)| s != symbol) {
+                        assert_eq!(
+                            reader.read_huffman_or(&tree, code.len() as u32).unwrap(),
+                            *s
+                        );
+                        reader.skip(1).unwrap();
+                    }
+                    assert_eq!(reader.read_huffman_or(&tree, len).unwrap(), *symbol);
+                }
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_huffman_tree_diagnostics() {
+    use bitstream_io::huffman::{
+        compile_read_tree_checked, compile_write_tree_checked, compile_write_tree_radix,
+        HuffmanTreeDiagnostic,
+    };
+    use bitstream_io::{BigEndian, LittleEndian};
+
+    // a shorter code given after the longer code it prefixes
+    assert_eq!(
+        compile_read_tree_checked::<BigEndian, char>(
+            vec![
+                ('a', vec![1]),
+                ('b', vec![0, 1, 1]),
+                ('c', vec![0, 1, 0]),
+                ('d', vec![0, 0]),
+                ('e', vec![0, 1]),
+            ],
+            8
+        )
+        .unwrap_err(),
+        HuffmanTreeDiagnostic::OrphanedLeaf {
+            symbol: 'e',
+            code: vec![0, 1],
+            longer: 'c',
+            longer_code: vec![0, 1, 0],
+        }
+    );
+    assert_eq!(
+        compile_read_tree::<BigEndian, char>(vec![
+            ('a', vec![1]),
+            ('b', vec![0, 1, 1]),
+            ('c', vec![0, 1, 0]),
+            ('d', vec![0, 0]),
+            ('e', vec![0, 1]),
+        ])
+        .unwrap_err(),
+        HuffmanTreeError::OrphanedLeaf
+    );
+
+    assert_eq!(
+        compile_read_tree_checked::<BigEndian, u8>(vec![(0, vec![0]), (1, vec![1, 2])], 8)
+            .unwrap_err(),
+        HuffmanTreeDiagnostic::InvalidBit {
+            symbol: 1,
+            code: vec![1, 2],
+        }
+    );
+    assert_eq!(
+        compile_read_tree_checked::<BigEndian, u8>(vec![(0, vec![0]), (1, vec![1, 0])], 8)
+            .unwrap_err(),
+        HuffmanTreeDiagnostic::MissingLeaf {
+            codes: vec![vec![1, 1]]
+        }
+    );
+    assert_eq!(
+        compile_read_tree_checked::<BigEndian, u8>(Vec::new(), 8).unwrap_err(),
+        HuffmanTreeDiagnostic::MissingLeaf {
+            codes: vec![vec![]]
+        }
+    );
+
+    // codes are limited to the maximum length given
+    let long: Vec<(u32, Vec<u8>)> = (0..40)
+        .map(|i| (i, (0..i).map(|_| 0).chain(std::iter::once(1)).collect()))
+        .chain(std::iter::once((40, vec![0; 40])))
+        .collect();
+    assert!(compile_read_tree_checked::<LittleEndian, u32>(long.clone(), 40).is_ok());
+    assert_eq!(
+        compile_read_tree_checked::<LittleEndian, u32>(long.clone(), 39).unwrap_err(),
+        HuffmanTreeDiagnostic::CodeTooLong {
+            symbol: 39,
+            code: long[39].1.clone(),
+        }
+    );
+    assert!(compile_write_tree_checked::<LittleEndian, u32>(long.clone(), 40).is_ok());
+    assert_eq!(
+        compile_write_tree_checked::<LittleEndian, u32>(long, 39).unwrap_err(),
+        HuffmanTreeDiagnostic::CodeTooLong {
+            symbol: 39,
+            code: (0..39).map(|_| 0).chain(std::iter::once(1)).collect(),
+        }
+    );
+
+    // write trees need not be complete,
+    // but their symbols and codes must not repeat
+    assert!(compile_write_tree_checked::<BigEndian, char>(
+        vec![('a', vec![0]), ('b', vec![1, 0])],
+        8
+    )
+    .is_ok());
+    assert_eq!(
+        compile_write_tree_checked::<BigEndian, char>(
+            vec![('a', vec![0]), ('b', vec![1, 0]), ('a', vec![1, 1])],
+            8
+        )
+        .unwrap_err(),
+        HuffmanTreeDiagnostic::DuplicateSymbol {
+            symbol: 'a',
+            code: vec![1, 1],
+            first_code: vec![0],
+        }
+    );
+    assert!(compile_write_tree::<BigEndian, char>(vec![
+        ('a', vec![0]),
+        ('b', vec![1, 0]),
+        ('a', vec![1, 1])
+    ])
+    .is_ok());
+    assert_eq!(
+        compile_write_tree_checked::<BigEndian, char>(
+            vec![('a', vec![1, 0]), ('b', vec![1, 0])],
+            8
+        )
+        .unwrap_err(),
+        HuffmanTreeDiagnostic::DuplicateLeaf {
+            symbol: 'b',
+            code: vec![1, 0],
+            first: 'a',
+        }
+    );
+    assert_eq!(
+        compile_write_tree_checked::<BigEndian, char>(
+            vec![('a', vec![1]), ('b', vec![1, 0, 1])],
+            8
+        )
+        .unwrap_err(),
+        HuffmanTreeDiagnostic::OrphanedLeaf {
+            symbol: 'a',
+            code: vec![1],
+            longer: 'b',
+            longer_code: vec![1, 0, 1],
+        }
+    );
+
+    // write trees are verified with symbols in order
+    let spec = vec![
+        ('d', vec![0, 0, 0, 1]),
+        ('c', vec![0, 0, 1]),
+        ('b', vec![0, 1]),
+        ('a', vec![1]),
+    ];
+    for (endianness, tree) in [
+        (
+            "big",
+            compile_write_tree::<BigEndian, char>(spec.clone())
+                .unwrap()
+                .verify_complete(),
+        ),
+        (
+            "little",
+            compile_write_tree::<LittleEndian, char>(spec.clone())
+                .unwrap()
+                .verify_complete(),
+        ),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            tree,
+            &Err(HuffmanTreeDiagnostic::MissingLeaf {
+                codes: vec![vec![0, 0, 0, 0]]
+            }),
+            "{} endian",
+            endianness
+        );
+    }
+    let tree = compile_write_tree_radix::<BigEndian, char>(
+        2,
+        vec![('a', vec![1]), ('b', vec![0, 3]), ('c', vec![0])],
+    )
+    .unwrap();
+    assert_eq!(
+        tree.verify_complete(),
+        Err(HuffmanTreeDiagnostic::OrphanedLeaf {
+            symbol: 'c',
+            code: vec![0, 0],
+            longer: 'b',
+            longer_code: vec![0, 0, 1, 1],
+        })
+    );
+    assert_eq!(
+        compile_write_tree::<BigEndian, u8>(Vec::new())
+            .unwrap()
+            .verify_complete(),
+        Err(HuffmanTreeDiagnostic::MissingLeaf {
+            codes: vec![vec![]]
+        })
+    );
+
+    let err = compile_read_tree_checked::<BigEndian, u8>(vec![(0, vec![1, 0]), (1, vec![1, 0])], 8)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "duplicate leaf node in specification for code [1, 0] with symbols 0 and 1"
+    );
+}
+
+#[test]
+fn test_huffman_introspection() {
+    use bitstream_io::{BigEndian, LittleEndian};
+
+    // codes of every length from 1 to 70 bits
+    let spec: Vec<(u32, Vec<u8>)> = (0..70)
+        .map(|i| {
+            (
+                i,
+                (0..i)
+                    .map(|j| (j % 2) as u8)
+                    .chain(std::iter::once(1))
+                    .collect(),
+            )
+        })
+        .collect();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let tree = compile_write_tree::<$endianness, u32>(spec.clone()).unwrap();
+            let listed: Vec<(u32, Vec<u8>)> = tree
+                .iter()
+                .map(|(symbol, bits, len)| {
+                    assert_eq!(bits.len() as u32, len);
+                    assert_eq!(tree.code_len(symbol), Some(len));
+                    (*symbol, bits)
+                })
+                .collect();
+            assert_eq!(listed, spec);
+
+            for (symbol, bits) in spec.iter() {
+                match tree.code(symbol) {
+                    Some((len, code)) => {
+                        assert!(bits.len() <= 64);
+                        assert_eq!(len, bits.len() as u32);
+                        for (i, bit) in bits.iter().enumerate() {
+                            assert_eq!((code >> (len as usize - 1 - i)) & 1, u64::from(*bit));
+                        }
+                    }
+                    None => assert!(bits.len() > 64),
+                }
+            }
+            assert_eq!(tree.code(&70), None);
+
+            // a tree rebuilt from its own listing is the same tree
+            let rebuilt = compile_write_tree::<$endianness, u32>(
+                tree.iter()
+                    .map(|(symbol, bits, _)| (*symbol, bits))
+                    .collect(),
+            )
+            .unwrap();
+            assert!(rebuilt.iter().eq(tree.iter()));
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
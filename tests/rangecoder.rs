@@ -0,0 +1,87 @@
+// Copyright 2017 Brian Langenberger
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bitstream_io;
+use bitstream_io::rangecoder::{Context, RangeDecoder, RangeEncoder};
+use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter, LittleEndian};
+use std::io::Cursor;
+
+// a simple deterministic generator of skewed bits
+fn skewed_bits(count: usize, one_in: u32) -> Vec<bool> {
+    let mut state = 0x1234_5678u32;
+    (0..count)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) % one_in == one_in - 1
+        })
+        .collect()
+}
+
+macro_rules! define_rangecoder_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            for &one_in in [2, 5, 50, 1000].iter() {
+                let bits = skewed_bits(10000, one_in);
+                let mut output: Vec<u8> = Vec::new();
+                {
+                    let mut writer = BitWriter::endian(&mut output, $endianness);
+                    // start range coding at an unaligned position
+                    writer.write(3, 0b101).unwrap();
+                    let mut encoder = RangeEncoder::new(writer);
+                    let mut contexts = [Context::new(), Context::new()];
+                    let mut previous = false;
+                    for (i, bit) in bits.iter().enumerate() {
+                        encoder
+                            .encode(&mut contexts[previous as usize], *bit)
+                            .unwrap();
+                        previous = *bit;
+                        if i % 1000 == 0 {
+                            encoder.encode_direct(32, i as u32 * 7919).unwrap();
+                        }
+                    }
+                    let mut writer = encoder.finish().unwrap();
+                    writer.write(5, 0b11011).unwrap();
+                }
+                if one_in >= 50 {
+                    assert!(output.len() < bits.len() / 8 / 2);
+                }
+                {
+                    let mut reader = BitReader::endian(Cursor::new(&output), $endianness);
+                    assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+                    let mut decoder = RangeDecoder::new(reader).unwrap();
+                    let mut contexts = [Context::new(), Context::new()];
+                    let mut previous = false;
+                    for (i, bit) in bits.iter().enumerate() {
+                        let decoded = decoder.decode(&mut contexts[previous as usize]).unwrap();
+                        assert_eq!(decoded, *bit);
+                        previous = decoded;
+                        if i % 1000 == 0 {
+                            assert_eq!(decoder.decode_direct(32).unwrap(), i as u32 * 7919);
+                        }
+                    }
+                    let mut reader = decoder.into_reader();
+                    assert_eq!(reader.read::<u8>(5).unwrap(), 0b11011);
+                }
+            }
+        }
+    };
+}
+
+define_rangecoder_roundtrip!(test_rangecoder_roundtrip_be, BigEndian);
+define_rangecoder_roundtrip!(test_rangecoder_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_rangecoder_errors() {
+    let mut encoder = RangeEncoder::new(BitWriter::endian(Vec::new(), BigEndian));
+    assert!(encoder.encode_direct(33, 0).is_err());
+    assert!(encoder.encode_direct(4, 16).is_err());
+
+    let data = [0u8; 4];
+    assert!(RangeDecoder::new(BitReader::endian(Cursor::new(&data), BigEndian)).is_err());
+}
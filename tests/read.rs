@@ -645,3 +645,812 @@ fn test_clone() {
     }
     let _r = BitReader::endian(NotCloneRead(&actual_data[..]), BigEndian);
 }
+
+#[test]
+fn test_limited_reader() {
+    use bitstream_io::{BigEndian, BitLimitError, BitRead, BitReader, LittleEndian};
+    use std::io::ErrorKind;
+
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    // reads within the limit succeed, reads beyond it fail without consuming
+    let mut r = BitReader::endian(Cursor::new(&actual_data), BigEndian).take(20);
+    assert_eq!(r.read::<u32>(4).unwrap(), 0xB);
+    assert_eq!(r.remaining(), 16);
+    let err = r.read::<u32>(17).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+    let limit = BitLimitError::from_io(&err).unwrap();
+    assert_eq!((limit.requested(), limit.remaining()), (17, 16));
+    assert_eq!(r.read_signed::<i32>(4).unwrap(), 1);
+    let mut buf = [0; 2];
+    let err = r.read_bytes(&mut buf).unwrap_err();
+    assert_eq!(
+        BitLimitError::from_io(&err).map(BitLimitError::requested),
+        Some(16)
+    );
+    assert_eq!(r.read_unary0().unwrap(), 3);
+    assert!(r.skip(9).is_err());
+    assert!(r.skip(8).is_ok());
+    assert_eq!(r.remaining(), 0);
+    assert!(r.read_bit().is_err());
+    let mut r = r.into_reader();
+    assert_eq!(r.read::<u32>(12).unwrap(), 0xBC1);
+
+    // aligning consumes the rest of the current byte from the budget
+    let mut r = BitReader::endian(Cursor::new(&actual_data), LittleEndian).take(16);
+    assert_eq!(r.read::<u32>(4).unwrap(), 0x1);
+    r.byte_align();
+    assert!(r.byte_aligned());
+    assert_eq!(r.remaining(), 8);
+    assert_eq!(r.read::<u32>(8).unwrap(), 0xED);
+    assert!(r.read::<u32>(1).is_err());
+
+    // running out of input isn't mistaken for an exhausted budget
+    let mut r = BitReader::endian(Cursor::new(&actual_data), BigEndian).take(64);
+    let err = r.read::<u64>(64).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(BitLimitError::from_io(&err).is_none());
+
+    // an exhausted budget still leaves the underlying reader aligned
+    let mut r = BitReader::endian(Cursor::new(&actual_data), BigEndian).take(2);
+    assert_eq!(r.read::<u32>(2).unwrap(), 0x2);
+    r.byte_align();
+    let mut r = r.into_reader();
+    assert!(r.byte_aligned());
+    assert_eq!(r.read::<u32>(8).unwrap(), 0xED);
+}
+
+#[test]
+fn test_read_to_bitbuffer() {
+    use bitstream_io::buffer::BitBuffer;
+    use bitstream_io::{BigEndian, BitRead, BitReader, LittleEndian};
+
+    let data: Vec<u8> = (0..10000).map(|i| (i * 7 + 3) as u8).collect();
+
+    // a sub-stream starting at any bit offset reads the same values
+    // as the stream it was extracted from
+    for offset in 0..8 {
+        let mut r = BitReader::endian(Cursor::new(&data), BigEndian);
+        r.skip(offset).unwrap();
+        let mut expected = r.clone();
+        let mut buffer: BitBuffer<BigEndian> = r.read_to_bitbuffer(9000 * 8 + 5).unwrap();
+        for _ in 0..9000 {
+            assert_eq!(
+                buffer.read::<u8>(8).unwrap(),
+                expected.read::<u8>(8).unwrap()
+            );
+        }
+        assert_eq!(
+            buffer.read::<u8>(5).unwrap(),
+            expected.read::<u8>(5).unwrap()
+        );
+        assert!(buffer.read_bit().is_err());
+        assert_eq!(r.read::<u8>(3).unwrap(), expected.read::<u8>(3).unwrap());
+
+        let mut r = BitReader::endian(Cursor::new(&data), LittleEndian);
+        r.skip(offset).unwrap();
+        let mut expected = r.clone();
+        let mut buffer: BitBuffer<LittleEndian> = r.read_to_bitbuffer(77).unwrap();
+        assert_eq!(
+            buffer.read::<u64>(64).unwrap(),
+            expected.read::<u64>(64).unwrap()
+        );
+        assert_eq!(
+            buffer.read_signed::<i16>(13).unwrap(),
+            expected.read_signed::<i16>(13).unwrap()
+        );
+        assert!(buffer.skip(1).is_err());
+    }
+
+    // requesting more bits than the stream holds is an error
+    let mut r = BitReader::endian(Cursor::new(&data[0..2]), BigEndian);
+    assert!(r.read_to_bitbuffer::<BigEndian>(17).is_err());
+}
+
+#[test]
+fn test_reader_fork() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, LittleEndian};
+    use std::thread;
+
+    static FRAMES: [u8; 9] = [0x01, 0xAB, 0xCD, 0x02, 0x12, 0x34, 0x03, 0xEF, 0x01];
+
+    // each frame is a header byte and two bytes of payload
+    // decoded on its own thread
+    let mut reader = BitReader::endian(&FRAMES[..], LittleEndian);
+    let mut handles = Vec::new();
+    while let Ok(frame) = reader.split_bytes(3) {
+        handles.push(thread::spawn(move || {
+            let mut frame = frame;
+            let header = frame.read::<u8>(8).unwrap();
+            let payload = frame.read::<u16>(16).unwrap();
+            assert!(frame.read_bit().is_err());
+            (header, payload)
+        }));
+    }
+    let decoded: Vec<(u8, u16)> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(decoded, vec![(1, 0xCDAB), (2, 0x3412), (3, 0x01EF)]);
+
+    // seekable streams fork independently of the parent's position
+    let reader = BitReader::endian(Cursor::new(&FRAMES[..]), BigEndian);
+    let mut forks: Vec<_> = (0..3)
+        .rev()
+        .map(|frame| reader.fork(frame * 3 + 1..frame * 3 + 3).unwrap())
+        .collect();
+    assert_eq!(forks[0].read::<u16>(16).unwrap(), 0xEF01);
+    assert_eq!(forks[2].read::<u16>(16).unwrap(), 0xABCD);
+    assert_eq!(forks[1].read::<u16>(16).unwrap(), 0x1234);
+    assert!(forks[1].read_bit().is_err());
+
+    // a range past the end of the stream is truncated
+    let mut fork = reader.fork(8..20).unwrap();
+    assert_eq!(fork.read::<u8>(8).unwrap(), 0x01);
+    assert!(fork.read_bit().is_err());
+}
+
+#[test]
+fn test_read_bytes_borrowed() {
+    use bitstream_io::{
+        BigEndian, BitRead, BitReader, BitSliceReader, ByteRead, ByteReader, LittleEndian,
+    };
+
+    // length-prefixed payloads between unaligned fields
+    let data: Vec<u8> = (0..8u8)
+        .flat_map(|len| {
+            std::iter::once(len)
+                .chain(0..len)
+                .chain(std::iter::once(0xA5))
+        })
+        .collect();
+
+    let mut reader = BitReader::endian(data.as_slice(), BigEndian);
+    let mut slice = BitSliceReader::endian(data.as_slice(), LittleEndian);
+    let mut payloads = Vec::new();
+    let mut offset = 0;
+    for len in 0..8u8 {
+        assert_eq!(reader.read::<u8>(8).unwrap(), len);
+        assert_eq!(slice.read::<u8>(8).unwrap(), len);
+        let payload = reader.read_bytes_borrowed(len.into()).unwrap();
+        assert_eq!(payload, slice.read_bytes_borrowed(len.into()).unwrap());
+        // payloads point into the data rather than a copy of it
+        assert_eq!(payload.as_ptr(), data[offset + 1..].as_ptr());
+        offset += usize::from(len) + 2;
+        payloads.push(payload);
+
+        assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+        assert_eq!(slice.read::<u8>(3).unwrap(), 0b101);
+        assert_eq!(
+            reader.read_bytes_borrowed(0).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            slice.read_bytes_borrowed(0).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(reader.read::<u8>(5).unwrap(), 0b00101);
+        assert_eq!(slice.read::<u8>(5).unwrap(), 0b10100);
+    }
+    for (len, payload) in payloads.iter().enumerate() {
+        assert_eq!(payload.to_vec(), (0..len as u8).collect::<Vec<u8>>());
+    }
+
+    // reads past the end leave the reader unchanged
+    let mut reader = BitReader::endian(&data[0..4], BigEndian);
+    let mut slice = BitSliceReader::endian(&data[0..4], BigEndian);
+    assert_eq!(
+        reader.read_bytes_borrowed(5).unwrap_err().kind(),
+        std::io::ErrorKind::UnexpectedEof
+    );
+    assert_eq!(
+        slice.read_bytes_borrowed(5).unwrap_err().kind(),
+        std::io::ErrorKind::UnexpectedEof
+    );
+    assert_eq!(reader.read_bytes_borrowed(4).unwrap(), &data[0..4]);
+    assert_eq!(slice.read_bytes_borrowed(4).unwrap(), &data[0..4]);
+    assert_eq!(slice.remaining_bits(), 0);
+    assert!(reader.read_bit().is_err());
+
+    // byte readers borrow the same way, and count what they borrow
+    let mut reader = ByteReader::endian(data.as_slice(), LittleEndian);
+    let mut offset = 0;
+    for (len, payload) in payloads.iter().enumerate() {
+        assert_eq!(reader.read::<u8>().unwrap(), len as u8);
+        let borrowed = reader.read_bytes_borrowed(len).unwrap();
+        assert_eq!(borrowed, *payload);
+        assert_eq!(borrowed.as_ptr(), data[offset + 1..].as_ptr());
+        assert_eq!(reader.read::<u8>().unwrap(), 0xA5);
+        offset += len + 2;
+        assert_eq!(reader.position(), offset as u64);
+    }
+    assert!(reader.read_bytes_borrowed(1).is_err());
+    assert_eq!(reader.read_bytes_borrowed(0).unwrap(), []);
+}
+
+#[test]
+fn test_seek_to_pattern() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter, LittleEndian};
+
+    // sync codes placed at odd bit offsets
+    // on either side of the scanning buffer's boundary
+    fn check<E: bitstream_io::Endianness>(endian: E) {
+        let offsets = [3u64, 4093 * 8 + 5, 9001 * 8 + 1];
+        let mut data = Vec::new();
+        {
+            let mut writer = BitWriter::<_, E>::new(&mut data);
+            let mut position = 0;
+            for offset in offsets.iter() {
+                while position < *offset {
+                    writer.write_bit(false).unwrap();
+                    position += 1;
+                }
+                writer.write(24, 0xFFF5A5).unwrap();
+                position += 24;
+            }
+            writer.byte_align().unwrap();
+        }
+        let mut reader: BitReader<_, E> = BitReader::endian(Cursor::new(&data), endian);
+        let mut position = 0;
+        for offset in offsets.iter() {
+            let skipped = reader.seek_to_pattern(24, 0xFFF5A5).unwrap();
+            assert_eq!(position + skipped, *offset);
+            assert_eq!(reader.position_in_bits().unwrap(), *offset);
+            assert_eq!(reader.read::<u32>(24).unwrap(), 0xFFF5A5);
+            position = *offset + 24;
+        }
+        assert!(reader.seek_to_pattern(24, 0xFFF5A5).is_err());
+        assert!(reader.seek_to_pattern(65, 0).is_err());
+        assert!(reader.seek_to_pattern(4, 16).is_err());
+    }
+    check(BigEndian);
+    check(LittleEndian);
+}
+
+#[test]
+fn test_seek_to_pattern_unseekable() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter, LittleEndian};
+    use std::io::Read;
+
+    /*a stream which can't seek and returns a few bytes at a time*/
+    struct Trickle<'a> {
+        data: &'a [u8],
+        size: usize,
+    }
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.size).min(self.data.len());
+            buf[0..len].copy_from_slice(&self.data[0..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    fn check<E: bitstream_io::Endianness>(endian: E, size: usize) {
+        let offsets = [0u64, 41, 130, 172, 4100 * 8 + 7, 4106 * 8, 9001 * 8 + 3];
+        let mut data = Vec::new();
+        {
+            let mut writer = BitWriter::<_, E>::new(&mut data);
+            let mut position = 0;
+            for offset in offsets.iter() {
+                while position < *offset {
+                    writer.write_bit(false).unwrap();
+                    position += 1;
+                }
+                writer.write(40, 0xFF_FFF5_A5A5u64).unwrap();
+                position += 40;
+            }
+            writer.write(11, 0x7FF).unwrap();
+            writer.byte_align().unwrap();
+        }
+        let mut reader: BitReader<_, E> = BitReader::endian(Trickle { data: &data, size }, endian);
+        let mut position = 0;
+        for offset in offsets.iter() {
+            let skipped = reader.seek_to_pattern(40, 0xFF_FFF5_A5A5).unwrap();
+            assert_eq!(position + skipped, *offset);
+            assert_eq!(reader.byte_aligned(), offset % 8 == 0);
+            assert_eq!(reader.read::<u64>(40).unwrap(), 0xFF_FFF5_A5A5);
+            position = *offset + 40;
+        }
+        assert_eq!(reader.read::<u16>(11).unwrap(), 0x7FF);
+        assert!(reader.seek_to_pattern(40, 0xFF_FFF5_A5A5).is_err());
+        assert!(reader.read_bit().is_err());
+    }
+    for size in [1, 3, 8, 9, 5000].iter() {
+        check(BigEndian, *size);
+        check(LittleEndian, *size);
+    }
+}
+
+#[test]
+fn test_reader_bookmark() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, Bookmark};
+
+    // headers are either 4-bit type + 12-bit length
+    // or 4-bit type + 4-bit length, and only the long form
+    // is valid when its type is 0xF
+    fn parse(reader: &mut BitReader<Cursor<&[u8]>, BigEndian>) -> (u8, u16) {
+        let bookmark: Bookmark = reader.bookmark().unwrap();
+        let kind = reader.read::<u8>(4).unwrap();
+        if kind == 0xF {
+            (kind, reader.read::<u16>(12).unwrap())
+        } else {
+            reader.restore(&bookmark).unwrap();
+            reader.skip(4).unwrap();
+            (kind, reader.read::<u16>(4).unwrap())
+        }
+    }
+
+    let data: &[u8] = &[0xF1, 0x23, 0x45, 0xF6, 0x78];
+    let mut reader = BitReader::endian(Cursor::new(data), BigEndian);
+    assert_eq!(parse(&mut reader), (0xF, 0x123));
+    assert_eq!(parse(&mut reader), (0x4, 0x5));
+    assert_eq!(parse(&mut reader), (0xF, 0x678));
+
+    // bookmarks taken mid-byte restore the partial byte too
+    let mut reader = BitReader::endian(Cursor::new(data), BigEndian);
+    reader.skip(3).unwrap();
+    let bookmark = reader.bookmark().unwrap();
+    assert_eq!(bookmark, reader.bookmark().unwrap());
+    let first: Vec<bool> = (0..21).map(|_| reader.read_bit().unwrap()).collect();
+    reader.restore(&bookmark).unwrap();
+    assert_eq!(reader.position_in_bits().unwrap(), 3);
+    let second: Vec<bool> = (0..21).map(|_| reader.read_bit().unwrap()).collect();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_slice_reader() {
+    use bitstream_io::huffman::compile_read_tree;
+    use bitstream_io::{
+        BigEndian, BitRead, BitReader, BitSliceReader, Endianness, HuffmanRead, LittleEndian,
+    };
+
+    // a slice reader reads the same values as a stream reader
+    fn check<E: Endianness>(endian: E) {
+        let data: Vec<u8> = (0..200u32).map(|i| (i * 73 % 251) as u8).collect();
+        let tree = compile_read_tree::<E, u8>(vec![
+            (0, vec![0]),
+            (1, vec![1, 0]),
+            (2, vec![1, 1, 0]),
+            (3, vec![1, 1, 1]),
+        ])
+        .unwrap();
+
+        let mut stream = BitReader::<_, E>::new(Cursor::new(&data));
+        let mut slice = BitSliceReader::endian(&data, endian);
+        for i in 0..60u32 {
+            match i % 6 {
+                0 => assert_eq!(
+                    stream.read::<u32>(i % 33).unwrap(),
+                    slice.read(i % 33).unwrap()
+                ),
+                1 => assert_eq!(stream.read::<u64>(64).unwrap(), slice.read(64).unwrap()),
+                2 => assert_eq!(
+                    stream.read_signed::<i16>(i % 16 + 1).unwrap(),
+                    slice.read_signed(i % 16 + 1).unwrap()
+                ),
+                3 => assert_eq!(
+                    stream.read_huffman(&tree).unwrap(),
+                    slice.read_huffman(&tree).unwrap()
+                ),
+                4 => {
+                    let mut expected = [0; 3];
+                    let mut found = [0; 3];
+                    stream.read_bytes(&mut expected).unwrap();
+                    slice.read_bytes(&mut found).unwrap();
+                    assert_eq!(expected, found);
+                }
+                _ => {
+                    assert_eq!(stream.read_bit().unwrap(), slice.read_bit().unwrap());
+                    stream.byte_align();
+                    slice.byte_align();
+                    assert_eq!(stream.read::<u16>(16).unwrap(), slice.read(16).unwrap());
+                }
+            }
+            assert_eq!(stream.position_in_bits().unwrap(), slice.position_in_bits());
+        }
+
+        // failed reads leave the position unchanged
+        slice.skip(slice.remaining_bits() - 5).unwrap();
+        assert!(slice.read::<u8>(6).is_err());
+        assert!(slice.skip(6).is_err());
+        assert_eq!(slice.remaining_bits(), 5);
+        assert_eq!(slice.remaining_slice(), &data[data.len() - 1..]);
+        assert_eq!(slice.read::<u8>(5).unwrap(), {
+            let mut stream = BitReader::<_, E>::new(Cursor::new(&data));
+            stream.skip(data.len() as u64 * 8 - 5).unwrap();
+            stream.read::<u8>(5).unwrap()
+        });
+        assert!(slice.read_bit().is_err());
+        assert!(slice.read_huffman(&tree).is_err());
+    }
+    check(BigEndian);
+    check(LittleEndian);
+}
+
+#[test]
+fn test_read_expect() {
+    use bitstream_io::read::UnexpectedValueError;
+    use bitstream_io::{BigEndian, BitRead, BitReader, LittleEndian};
+    use std::io::ErrorKind;
+
+    let data = [0x0B, 0x77, 0xA5, 0x80];
+
+    // an AC-3 style sync word followed by marker bits
+    let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    reader.read_expect(16, 0x0B77u16).unwrap();
+    reader.read_expect(3, 0b101u8).unwrap();
+    let err = reader.read_expect(5, 0u8).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let err = UnexpectedValueError::from_io(&err).unwrap();
+    assert_eq!(err.bits(), 5);
+    assert_eq!(err.expected(), 0);
+    assert_eq!(err.found(), 0b00101);
+    assert_eq!(err.to_string(), "expected 0x0 in 5 bits but found 0x5");
+    // an expected value too large for its bits reads nothing
+    let err = reader.read_expect(1, 2u8).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(UnexpectedValueError::from_io(&err).is_none());
+    reader.read_expect(1, 1u8).unwrap();
+    reader.read_expect(7, 0u8).unwrap();
+    assert_eq!(
+        reader.read_expect(1, 0u8).unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+
+    let mut reader = BitReader::endian(Cursor::new(&data), LittleEndian);
+    reader.read_expect(16, 0x770Bu16).unwrap();
+    reader.read_expect(4, 0x5u8).unwrap();
+    let err = reader.read_expect(12, 0x80Bu64).unwrap_err();
+    let err = UnexpectedValueError::from_io(&err).unwrap();
+    assert_eq!((err.expected(), err.found()), (0x80B, 0x80A));
+    // values of every width are reported in full
+    let mut reader = BitReader::endian(Cursor::new([0xFF; 16]), BigEndian);
+    let err = reader.read_expect(128, u128::MAX - 1).unwrap_err();
+    let err = UnexpectedValueError::from_io(&err).unwrap();
+    assert_eq!((err.expected(), err.found()), (u128::MAX - 1, u128::MAX));
+}
+
+#[test]
+fn test_lookahead_reader() {
+    use bitstream_io::{
+        BigEndian, BitRead, BitReader, BitWrite, BitWriter, LittleEndian, LookaheadReader,
+    };
+    use std::io::ErrorKind;
+
+    let data: Vec<u8> = (0..40u8).map(|i| i.wrapping_mul(73) ^ 0x5A).collect();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut plain = BitReader::endian(Cursor::new(data.as_slice()), $endianness);
+            let mut reader = LookaheadReader::endian(
+                BitReader::endian(data.as_slice(), $endianness),
+                $endianness,
+            );
+
+            // starting partway through a byte
+            assert_eq!(reader.read::<u8>(3).unwrap(), plain.read::<u8>(3).unwrap());
+            assert_eq!(reader.fill_lookahead(0).unwrap(), 0);
+            assert!(!reader.byte_aligned());
+
+            for window in [1, 9, 17, 56, 5, 33].iter() {
+                assert!(reader.fill_lookahead(*window).unwrap() >= *window);
+                assert_eq!(reader.byte_aligned(), plain.byte_aligned());
+                let mut copy = plain.clone();
+                assert_eq!(
+                    reader.lookahead(*window),
+                    copy.read::<u64>(*window).unwrap()
+                );
+                // consuming part of the window matches reading it
+                reader.consume(window / 2).unwrap();
+                plain.skip(u64::from(window / 2)).unwrap();
+                assert_eq!(reader.lookahead(1), copy_first(&plain));
+                // and ordinary reads continue past what's buffered
+                assert_eq!(
+                    reader.read::<u32>(29).unwrap(),
+                    plain.read::<u32>(29).unwrap()
+                );
+                assert_eq!(
+                    reader.read_signed::<i8>(5).unwrap(),
+                    plain.read_signed::<i8>(5).unwrap()
+                );
+                assert_eq!(reader.read_bit().unwrap(), plain.read_bit().unwrap());
+            }
+
+            reader.byte_align();
+            plain.byte_align();
+            assert!(reader.byte_aligned());
+            reader.fill_lookahead(12).unwrap();
+            reader.skip(20).unwrap();
+            plain.skip(20).unwrap();
+            assert_eq!(
+                reader.read::<u16>(16).unwrap(),
+                plain.read::<u16>(16).unwrap()
+            );
+
+            // the last bits of the stream are padded with 0s
+            let remaining = (data.len() * 8) as u32 - plain.position_in_bits().unwrap() as u32;
+            assert!(remaining < 56);
+            assert_eq!(reader.fill_lookahead(56).unwrap(), remaining);
+            let mut copy = plain.clone();
+            let last = copy.read::<u64>(remaining).unwrap();
+            assert_eq!(reader.lookahead(remaining), last);
+            let mut padded = BitWriter::endian(Vec::new(), $endianness);
+            padded.write(remaining, last).unwrap();
+            padded.write(56 - remaining, 0u64).unwrap();
+            let mut padded = BitReader::endian(Cursor::new(padded.into_writer()), $endianness);
+            assert_eq!(reader.lookahead(56), padded.read::<u64>(56).unwrap());
+
+            assert_eq!(
+                reader.consume(remaining + 1).unwrap_err().kind(),
+                ErrorKind::UnexpectedEof
+            );
+            assert_eq!(reader.lookahead_len(), remaining);
+            reader.consume(remaining).unwrap();
+            assert!(reader.read_bit().is_err());
+            assert!(reader.fill_lookahead(57).is_err());
+        };
+    }
+
+    fn copy_first<R: BitRead + Clone>(reader: &R) -> u64 {
+        reader.clone().read::<u64>(1).unwrap()
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_reader_from_bytes_iter() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, LittleEndian};
+    use std::sync::mpsc;
+    use std::thread;
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let data: Vec<u8> = (0..=255).rev().collect();
+
+            // bytes arriving from another thread
+            let (sender, receiver) = mpsc::channel();
+            let producer = {
+                let data = data.clone();
+                thread::spawn(move || {
+                    for byte in data {
+                        sender.send(byte).unwrap();
+                    }
+                })
+            };
+            let mut reader = BitReader::<_, $endianness>::from_bytes_iter(receiver);
+            let mut plain = BitReader::endian(Cursor::new(&data), $endianness);
+            for bits in (1..=32).cycle().take(62) {
+                assert_eq!(
+                    reader.read::<u32>(bits).unwrap(),
+                    plain.read::<u32>(bits).unwrap()
+                );
+            }
+            assert_eq!(
+                reader.read_signed::<i16>(15).unwrap(),
+                plain.read_signed::<i16>(15).unwrap()
+            );
+            // 1008 bits have been read so far
+            let mut rest = vec![0; 256 - 126];
+            reader.read_bytes(&mut rest).unwrap();
+            let mut expected = vec![0; 256 - 126];
+            plain.read_bytes(&mut expected).unwrap();
+            assert_eq!(rest, expected);
+            producer.join().unwrap();
+            assert!(reader.read::<u8>(1).is_err());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_reader_opt_at_eof() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, LittleEndian};
+    use std::io::ErrorKind;
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let data = [0xA5, 0x3C];
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            let mut plain = BitReader::endian(Cursor::new(&data), $endianness);
+            for _ in 0..16 {
+                assert_eq!(
+                    reader.read_bit_opt().unwrap(),
+                    Some(plain.read_bit().unwrap())
+                );
+            }
+            assert_eq!(reader.read_bit_opt().unwrap(), None);
+            assert_eq!(reader.read_bit_opt().unwrap(), None);
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            let mut plain = BitReader::endian(Cursor::new(&data), $endianness);
+            for _ in 0..4 {
+                assert_eq!(
+                    reader.read_opt::<u8>(4).unwrap(),
+                    Some(plain.read::<u8>(4).unwrap())
+                );
+            }
+            assert_eq!(reader.read_opt::<u8>(4).unwrap(), None);
+            assert_eq!(reader.read_opt::<u8>(0).unwrap(), Some(0));
+
+            // ending partway through a value is an error
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            reader.skip(3).unwrap();
+            assert_eq!(
+                reader.read_opt::<u16>(16).unwrap_err().kind(),
+                ErrorKind::UnexpectedEof
+            );
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(
+                reader.read_opt::<u32>(24).unwrap_err().kind(),
+                ErrorKind::UnexpectedEof
+            );
+            assert_eq!(
+                reader.read_opt::<u8>(9).unwrap_err().kind(),
+                ErrorKind::InvalidInput
+            );
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_reader_remaining_bits() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, IterReader, LittleEndian};
+    use std::io::Read;
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let data: Vec<u8> = (0..100).collect();
+
+            let mut slice = BitReader::endian(&data[..], $endianness);
+            let mut cursor = BitReader::endian(Cursor::new(&data), $endianness);
+            let mut take = BitReader::endian(Cursor::new(&data).take(60), $endianness);
+            let mut iter = BitReader::<_, $endianness>::from_bytes_iter(data.clone());
+            let mut remaining = 800;
+            for bits in (1..=32).cycle().take(20) {
+                assert_eq!(slice.remaining_bits(), Some(remaining));
+                assert_eq!(cursor.remaining_bits(), Some(remaining));
+                assert_eq!(take.remaining_bits(), Some(remaining - 320));
+                assert_eq!(iter.remaining_bits(), Some(remaining));
+                slice.read::<u32>(bits).unwrap();
+                cursor.read::<u32>(bits).unwrap();
+                take.read::<u32>(bits).unwrap();
+                iter.read::<u32>(bits).unwrap();
+                remaining -= u64::from(bits);
+            }
+            slice.skip(remaining).unwrap();
+            assert_eq!(slice.remaining_bits(), Some(0));
+            cursor.skip(remaining).unwrap();
+            assert_eq!(cursor.remaining_bits(), Some(0));
+            assert_eq!(IterReader::new(Vec::new()).remaining_bytes(), Some(0));
+
+            // a cursor positioned past its end has nothing remaining
+            let mut past = Cursor::new(&data);
+            past.set_position(1000);
+            assert_eq!(
+                BitReader::endian(past, $endianness).remaining_bits(),
+                Some(0)
+            );
+        };
+    }
+
+    use bitstream_io::RemainingBytes;
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_reader_length_checked() {
+    use bitstream_io::{BigEndian, BitRead, BitReader, LengthLimitError, LittleEndian};
+    use std::io::ErrorKind;
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let data: Vec<u8> = (0..=255).collect();
+
+            // unaligned reads match read_bytes
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            let mut plain = BitReader::endian(Cursor::new(&data), $endianness);
+            reader.skip(3).unwrap();
+            plain.skip(3).unwrap();
+            let mut expected = vec![0; 100];
+            plain.read_bytes(&mut expected).unwrap();
+            assert_eq!(reader.read_bytes_checked(100, 100).unwrap(), expected);
+            assert_eq!(reader.read_bytes_checked(0, 0).unwrap(), []);
+
+            // excessive lengths read nothing
+            let err = reader.read_bytes_checked(u64::MAX, 1000).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+            let limit = LengthLimitError::from_io(&err).unwrap();
+            assert_eq!((limit.requested(), limit.max()), (u64::MAX, 1000));
+            assert_eq!(reader.read::<u8>(8).unwrap(), plain.read::<u8>(8).unwrap());
+
+            // lengths within the maximum may still run out of data
+            assert_eq!(
+                reader.read_bytes_checked(200, 1000).unwrap_err().kind(),
+                ErrorKind::UnexpectedEof
+            );
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            let mut plain = BitReader::endian(Cursor::new(&data), $endianness);
+            let values = reader
+                .read_vec(50, 50, |r| r.read_signed::<i16>(13))
+                .unwrap();
+            for value in values {
+                assert_eq!(value, plain.read_signed::<i16>(13).unwrap());
+            }
+            let err = reader.read_vec(51, 50, |r| r.read::<u8>(8)).unwrap_err();
+            assert!(LengthLimitError::from_io(&err).is_some());
+            assert!(LengthLimitError::from_io(&io_error()).is_none());
+            assert_eq!(reader.read::<u8>(8).unwrap(), plain.read::<u8>(8).unwrap());
+        };
+    }
+
+    fn io_error() -> std::io::Error {
+        std::io::Error::new(ErrorKind::InvalidData, "other")
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_reader_peek_unseekable() {
+    use bitstream_io::huffman::compile_read_tree;
+    use bitstream_io::{BigEndian, BitRead, BitReader, HuffmanRead, LittleEndian};
+
+    let data = [0xB1, 0xED, 0x3B, 0xC1];
+
+    // neither a byte slice nor a byte iterator can seek
+    let mut r = BitReader::endian(&data[..], BigEndian);
+    assert_eq!(r.peek::<u32>(32).unwrap(), 0xB1ED3BC1);
+    assert_eq!(r.read::<u8>(3).unwrap(), 0b101);
+    assert_eq!(r.peek::<u8>(5).unwrap(), 0b10001);
+    assert_eq!(r.peek::<u32>(29).unwrap(), 0x11ED3BC1);
+    assert!(r.peek::<u32>(30).is_err());
+    assert_eq!(r.read::<u16>(13).unwrap(), 0b10001_11101101);
+    assert!(r.reader().is_none());
+    assert!(r.split_bytes(1).is_err());
+    assert_eq!(r.read::<u16>(16).unwrap(), 0x3BC1);
+    assert!(r.reader().is_some());
+    assert!(r.peek::<u8>(1).is_err());
+
+    let mut r = BitReader::<_, LittleEndian>::from_bytes_iter(data.iter().copied());
+    r.skip(3).unwrap();
+    assert_eq!(r.peek::<u8>(5).unwrap(), 0b10110);
+    assert_eq!(r.peek::<u16>(9).unwrap(), 0b1101_10110);
+    assert_eq!(r.read::<u16>(9).unwrap(), 0b1101_10110);
+    assert_eq!(r.remaining_bits(), Some(20));
+    let mut bytes = [0; 2];
+    assert_eq!(r.peek::<u32>(20).unwrap(), 0xC13BE);
+    r.skip(4).unwrap();
+    r.read_bytes(&mut bytes).unwrap();
+    assert_eq!(bytes, [0x3B, 0xC1]);
+    assert!(r.read_bit().is_err());
+
+    let tree = compile_read_tree(vec![
+        (0, vec![0]),
+        (1, vec![1, 0]),
+        (2, vec![1, 1, 0]),
+        (3, vec![1, 1, 1]),
+    ])
+    .unwrap();
+    let mut r = BitReader::endian(&[0b1110_1111, 0b1111_1110][..], BigEndian);
+    assert_eq!(r.remaining_bits(), Some(16));
+    assert_eq!(r.peek_huffman(&tree).unwrap(), 3);
+    assert_eq!(r.read::<u8>(2).unwrap(), 0b11);
+    assert_eq!(r.peek::<u16>(14).unwrap(), 0b10_1111_1111_1110);
+    assert_eq!(r.remaining_bits(), Some(14));
+    assert_eq!(r.read_huffman(&tree).unwrap(), 1);
+    assert_eq!(r.read_huffman(&tree).unwrap(), 3);
+    assert_eq!(r.read_huffman(&tree).unwrap(), 3);
+    assert_eq!(r.peek_huffman(&tree).unwrap(), 3);
+    assert_eq!(r.read::<u8>(6).unwrap(), 0b11_1110);
+}
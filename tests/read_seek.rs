@@ -133,3 +133,36 @@ pub fn test_reader_seek_end() -> io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+pub fn test_reader_peek() -> io::Result<()> {
+    let actual_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    let mut r = BitReader::endian(Cursor::new(&actual_data), BigEndian);
+    assert_eq!(r.peek::<u32>(32)?, 0xB1ED3BC1);
+    r.skip(3)?;
+    assert_eq!(r.peek::<u8>(5)?, 0b10001);
+    assert_eq!(r.position_in_bits()?, 3);
+    assert_eq!(r.peek::<u32>(29)?, 0x11ED3BC1);
+    assert!(r.peek::<u32>(30).is_err());
+    assert_eq!(r.position_in_bits()?, 3);
+    assert_eq!(r.read::<u32>(29)?, 0x11ED3BC1);
+
+    let mut r = BitReader::endian(Cursor::new(&actual_data), LittleEndian);
+    r.skip(3)?;
+    assert_eq!(r.peek::<u8>(5)?, 0b10110);
+    assert_eq!(r.peek::<u16>(9)?, 0b1101_10110);
+    assert_eq!(r.read::<u16>(9)?, 0b1101_10110);
+    assert_eq!(r.position_in_bits()?, 12);
+
+    // seeking discards any bytes buffered by a peek
+    assert_eq!(r.peek::<u32>(20)?, 0xC13BE);
+    r.seek_bits(SeekFrom::Start(8))?;
+    assert_eq!(r.read::<u8>(8)?, 0xED);
+    assert_eq!(r.peek::<u16>(16)?, 0xC13B);
+    r.seek_bits(SeekFrom::Current(-8))?;
+    assert_eq!(r.position_in_bits()?, 8);
+    assert_eq!(r.read::<u8>(8)?, 0xED);
+
+    Ok(())
+}
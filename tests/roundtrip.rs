@@ -102,3 +102,3446 @@ macro_rules! define_unary_roundtrip {
 
 define_unary_roundtrip!(test_unary_roundtrip_be, BigEndian);
 define_unary_roundtrip!(test_unary_roundtrip_le, LittleEndian);
+
+macro_rules! define_word_roundtrip {
+    ($func_name:ident, $endianness:ident, $word:ty) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::word::{WordReader, WordWriter};
+
+            let mut output: Vec<u8> = Vec::new();
+            {
+                let mut writer =
+                    BitWriter::endian(WordWriter::<_, $word>::new(&mut output), $endianness);
+                for bits in 1..17 {
+                    writer.write(bits, (1u32 << bits) - 1).unwrap();
+                    writer.write_signed(bits + 1, -1i32).unwrap();
+                }
+                writer.byte_align().unwrap();
+                writer.into_writer().word_align().unwrap();
+            }
+            assert_eq!(output.len() % std::mem::size_of::<$word>(), 0);
+            {
+                let mut reader =
+                    BitReader::endian(WordReader::<_, $word>::new(output.as_slice()), $endianness);
+                for bits in 1..17 {
+                    assert_eq!(reader.read::<u32>(bits).unwrap(), (1u32 << bits) - 1);
+                    assert_eq!(reader.read_signed::<i32>(bits + 1).unwrap(), -1);
+                }
+            }
+        }
+    };
+}
+
+define_word_roundtrip!(test_word16_roundtrip_be, BigEndian, u16);
+define_word_roundtrip!(test_word16_roundtrip_le, LittleEndian, u16);
+define_word_roundtrip!(test_word32_roundtrip_be, BigEndian, u32);
+define_word_roundtrip!(test_word32_roundtrip_le, LittleEndian, u32);
+
+macro_rules! define_fixed_point_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::Rounding;
+
+            const MODES: [Rounding; 5] = [
+                Rounding::Nearest,
+                Rounding::NearestEven,
+                Rounding::Floor,
+                Rounding::Ceiling,
+                Rounding::TowardZero,
+            ];
+
+            // values on either side of and exactly between
+            // two representable 4.4 values, and their expected roundings
+            // in the order of the modes above
+            let cases: [(f64, [f64; 5]); 6] = [
+                (1.53125, [1.5625, 1.5, 1.5, 1.5625, 1.5]),
+                (1.59375, [1.625, 1.625, 1.5625, 1.625, 1.5625]),
+                (1.55, [1.5625, 1.5625, 1.5, 1.5625, 1.5]),
+                (-1.53125, [-1.5625, -1.5, -1.5625, -1.5, -1.5]),
+                (-1.59375, [-1.625, -1.625, -1.625, -1.5625, -1.5625]),
+                (-1.55, [-1.5625, -1.5625, -1.5625, -1.5, -1.5]),
+            ];
+
+            let mut output = Vec::new();
+            {
+                let mut writer = BitWriter::endian(&mut output, $endianness);
+                writer.write_bit(true).unwrap();
+                for (value, _) in cases.iter() {
+                    for mode in MODES.iter() {
+                        writer
+                            .write_signed_fixed_point(4, 4, *value, *mode)
+                            .unwrap();
+                        if *value > 0.0 {
+                            writer.write_fixed_point(4, 4, *value, *mode).unwrap();
+                        }
+                    }
+                }
+                writer
+                    .write_fixed_point(16, 16, 65535.0, Rounding::Nearest)
+                    .unwrap();
+                writer
+                    .write_signed_fixed_point(2, 62, -2.0, Rounding::Nearest)
+                    .unwrap();
+                assert!(writer
+                    .write_fixed_point(16, 16, 65535.9999999, Rounding::Nearest)
+                    .is_err());
+                assert!(writer
+                    .write_signed_fixed_point(4, 4, 7.99, Rounding::Nearest)
+                    .is_err());
+                assert!(writer
+                    .write_fixed_point(4, 4, f64::NAN, Rounding::Floor)
+                    .is_err());
+                assert!(writer
+                    .write_fixed_point(32, 33, 0.0, Rounding::Floor)
+                    .is_err());
+                assert!(writer
+                    .write_fixed_point(u32::MAX, 2, 0.0, Rounding::Floor)
+                    .is_err());
+                assert!(writer
+                    .write_signed_fixed_point(2, u32::MAX, 0.0, Rounding::Floor)
+                    .is_err());
+                writer.write(7, 0u8).unwrap();
+            }
+
+            let mut reader = BitReader::endian(Cursor::new(&output), $endianness);
+            assert!(reader.read_bit().unwrap());
+            for (value, expected) in cases.iter() {
+                for expected in expected.iter() {
+                    assert_eq!(reader.read_signed_fixed_point(4, 4).unwrap(), *expected);
+                    if *value > 0.0 {
+                        assert_eq!(reader.read_fixed_point(4, 4).unwrap(), *expected);
+                    }
+                }
+            }
+            assert_eq!(reader.read_fixed_point(16, 16).unwrap(), 65535.0);
+            assert_eq!(reader.read_signed_fixed_point(2, 62).unwrap(), -2.0);
+            assert!(reader.read_fixed_point(32, 33).is_err());
+            assert_eq!(
+                reader.read_fixed_point(u32::MAX, 2).unwrap_err().kind(),
+                std::io::ErrorKind::InvalidInput
+            );
+            assert_eq!(
+                reader
+                    .read_signed_fixed_point(2, u32::MAX)
+                    .unwrap_err()
+                    .kind(),
+                std::io::ErrorKind::InvalidInput
+            );
+        }
+    };
+}
+
+define_fixed_point_roundtrip!(test_fixed_point_roundtrip_be, BigEndian);
+define_fixed_point_roundtrip!(test_fixed_point_roundtrip_le, LittleEndian);
+
+macro_rules! define_string_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::{ByteReader, ByteWriter, Utf8Policy};
+            use std::io::ErrorKind;
+
+            let strings = ["", "foo", "\u{1F600} unicode", "longer string for bits"];
+
+            let mut output = Vec::new();
+            {
+                let mut writer = BitWriter::endian(&mut output, $endianness);
+                writer.write(3, 0b101u8).unwrap();
+                for s in strings.iter() {
+                    writer.write_cstr(s).unwrap();
+                    writer.write_prefixed_str(5, s).unwrap();
+                }
+                writer.write_bytes(b"bad\xFF\0").unwrap();
+                writer.write(5, 0u8).unwrap();
+            }
+
+            let mut reader = BitReader::endian(Cursor::new(&output), $endianness);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            for s in strings.iter() {
+                assert_eq!(reader.read_cstr(s.len(), Utf8Policy::Strict).unwrap(), *s);
+                assert_eq!(
+                    reader
+                        .read_prefixed_str(5, s.len(), Utf8Policy::Strict)
+                        .unwrap(),
+                    *s
+                );
+            }
+            let mut strict = reader.clone();
+            assert_eq!(
+                strict.read_cstr(16, Utf8Policy::Strict).unwrap_err().kind(),
+                ErrorKind::InvalidData
+            );
+            assert_eq!(
+                reader.read_cstr(16, Utf8Policy::Lossy).unwrap(),
+                "bad\u{FFFD}"
+            );
+
+            // strings longer than the maximum are errors
+            let mut reader = BitReader::endian(Cursor::new(&output), $endianness);
+            reader.skip(3 + 8 + 5).unwrap();
+            let mut cstr = reader.clone();
+            assert_eq!(
+                cstr.read_cstr(2, Utf8Policy::Strict).unwrap_err().kind(),
+                ErrorKind::InvalidData
+            );
+            reader.skip(32).unwrap();
+            assert_eq!(
+                reader
+                    .read_prefixed_str(5, 2, Utf8Policy::Strict)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidData
+            );
+
+            // strings which can't be represented are write errors
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            assert!(writer.write_cstr("a\0b").is_err());
+            assert!(writer.write_prefixed_str(4, "sixteen chars!!!").is_err());
+
+            let mut writer = ByteWriter::endian(Vec::new(), $endianness);
+            writer.write_cstr("foo").unwrap();
+            writer.write_prefixed_str(3, "bar").unwrap();
+            let output = writer.into_writer();
+            assert_eq!(output.len(), 4 + 3 + 3);
+            let mut reader = ByteReader::endian(Cursor::new(&output), $endianness);
+            assert_eq!(reader.read_cstr(3, Utf8Policy::Strict).unwrap(), "foo");
+            assert_eq!(
+                reader.read_prefixed_str(3, 3, Utf8Policy::Strict).unwrap(),
+                "bar"
+            );
+        }
+    };
+}
+
+define_string_roundtrip!(test_string_roundtrip_be, BigEndian);
+define_string_roundtrip!(test_string_roundtrip_le, LittleEndian);
+
+macro_rules! define_signed_repr_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::SignedRepr;
+
+            const REPRS: [SignedRepr; 3] = [
+                SignedRepr::TwosComplement,
+                SignedRepr::SignMagnitude,
+                SignedRepr::OnesComplement,
+            ];
+
+            for &repr in REPRS.iter() {
+                for bits in 2..=8 {
+                    let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                    let limit = 1i16 << (bits - 1);
+                    let low = if repr == SignedRepr::TwosComplement {
+                        -limit
+                    } else {
+                        1 - limit
+                    };
+                    for value in low..limit {
+                        writer.write_signed_with(repr, bits, value as i8).unwrap();
+                    }
+                    writer.byte_align().unwrap();
+                    let data = writer.into_writer();
+                    let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                    for value in low..limit {
+                        assert_eq!(
+                            reader.read_signed_with::<i8>(repr, bits).unwrap(),
+                            value as i8
+                        );
+                    }
+                }
+
+                // the 16-bit widths agree with the 8-bit ones
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                writer.write_signed_with(repr, 8, -100i16).unwrap();
+                writer.write_signed_with(repr, 16, -100i16).unwrap();
+                let data = writer.into_writer();
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                assert_eq!(reader.read_signed_with::<i8>(repr, 8).unwrap(), -100);
+                assert_eq!(reader.read_signed_with::<i16>(repr, 16).unwrap(), -100);
+            }
+
+            // only twos-complement can hold the most negative value
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            assert!(writer
+                .write_signed_with(SignedRepr::SignMagnitude, 8, -128i8)
+                .is_err());
+            assert!(writer
+                .write_signed_with(SignedRepr::OnesComplement, 4, -8i8)
+                .is_err());
+            assert!(writer
+                .write_signed_with(SignedRepr::OnesComplement, 4, 8i8)
+                .is_err());
+            writer
+                .write_signed_with(SignedRepr::TwosComplement, 8, -128i8)
+                .unwrap();
+
+            // negative zero reads back as zero
+            let data = [0b1000_0000, 0b1111_1111];
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(
+                reader
+                    .read_signed_with::<i8>(SignedRepr::SignMagnitude, 8)
+                    .unwrap()
+                    | reader
+                        .read_signed_with::<i8>(SignedRepr::OnesComplement, 8)
+                        .unwrap(),
+                0
+            );
+        }
+    };
+}
+
+define_signed_repr_roundtrip!(test_signed_repr_roundtrip_be, BigEndian);
+define_signed_repr_roundtrip!(test_signed_repr_roundtrip_le, LittleEndian);
+
+macro_rules! define_zigzag_gray_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for value in -64..64 {
+                writer.write_zigzag(7, value).unwrap();
+            }
+            for &value in [i64::MIN, i64::MAX, 0, -1].iter() {
+                writer.write_zigzag(64, value).unwrap();
+            }
+            assert!(writer.write_zigzag(7, 64).is_err());
+            assert!(writer.write_zigzag(7, -65).is_err());
+
+            for value in 0..256u16 {
+                writer.write_gray(8, value as u8).unwrap();
+                writer.write_gray(9, value).unwrap();
+            }
+            writer.write_gray(64, u64::MAX).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for value in -64..64 {
+                assert_eq!(reader.read_zigzag(7).unwrap(), value);
+            }
+            for &value in [i64::MIN, i64::MAX, 0, -1].iter() {
+                assert_eq!(reader.read_zigzag(64).unwrap(), value);
+            }
+            let mut previous: Option<u8> = None;
+            for value in 0..256u16 {
+                // adjacent gray codes differ in a single bit
+                let mut peek = reader.clone();
+                let code = peek.read::<u8>(8).unwrap();
+                if let Some(previous) = previous {
+                    assert_eq!((previous ^ code).count_ones(), 1);
+                }
+                previous = Some(code);
+
+                assert_eq!(reader.read_gray::<u8>(8).unwrap(), value as u8);
+                assert_eq!(reader.read_gray::<u16>(9).unwrap(), value);
+            }
+            assert_eq!(reader.read_gray::<u64>(64).unwrap(), u64::MAX);
+        }
+    };
+}
+
+define_zigzag_gray_roundtrip!(test_zigzag_gray_roundtrip_be, BigEndian);
+define_zigzag_gray_roundtrip!(test_zigzag_gray_roundtrip_le, LittleEndian);
+
+macro_rules! define_copy_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let data: Vec<u8> = (0..10000u32).map(|i| (i * 37 % 251) as u8).collect();
+            let total = data.len() as u64 * 8;
+
+            for &(skip, offset, bits) in [
+                (0, 0, total),
+                (0, 0, 8 * 5000 + 5),
+                (3, 0, 8 * 6000),
+                (0, 5, 8 * 6000 + 3),
+                (7, 1, 64 * 100 + 63),
+                (1, 0, 1),
+            ]
+            .iter()
+            {
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                reader.skip(skip).unwrap();
+                writer.write(offset, 0u8).unwrap();
+                reader.copy_to(&mut writer, bits).unwrap();
+                writer.byte_align().unwrap();
+                let output = writer.into_writer();
+
+                // the copied bits match the source bit for bit
+                let mut source = BitReader::endian(Cursor::new(&data), $endianness);
+                let mut copy = BitReader::endian(Cursor::new(&output), $endianness);
+                source.skip(skip).unwrap();
+                copy.skip(u64::from(offset)).unwrap();
+                for _ in 0..bits {
+                    assert_eq!(source.read_bit().unwrap(), copy.read_bit().unwrap());
+                }
+            }
+
+            // copying past the end of the source is an error
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            assert!(reader.copy_to(&mut writer, total + 1).is_err());
+        }
+    };
+}
+
+define_copy_roundtrip!(test_copy_roundtrip_be, BigEndian);
+define_copy_roundtrip!(test_copy_roundtrip_le, LittleEndian);
+
+macro_rules! define_stuffing_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::stuffing::{
+                BitStuffingReader, BitStuffingWriter, EmulationPreventionReader,
+                EmulationPreventionWriter,
+            };
+
+            let data: Vec<u8> = (0..1000u32)
+                .map(|i| match i % 7 {
+                    0 | 1 | 3 => 0,
+                    2 => (i % 4) as u8,
+                    4 => 0xFF,
+                    _ => (i * 37 % 251) as u8,
+                })
+                .collect();
+
+            // escaped payloads never contain a start code prefix
+            let mut writer =
+                BitWriter::endian(EmulationPreventionWriter::new(Vec::new()), $endianness);
+            writer.write(3, 0u8).unwrap();
+            writer.write_bytes(&data).unwrap();
+            writer.write(5, 0u8).unwrap();
+            let escaped = writer.into_writer().into_writer();
+            assert!(escaped.len() > data.len() + 1);
+            assert!(escaped
+                .windows(3)
+                .all(|w| w[0] != 0 || w[1] != 0 || w[2] > 2));
+
+            let mut reader = BitReader::endian(
+                EmulationPreventionReader::new(escaped.as_slice()),
+                $endianness,
+            );
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0);
+            let mut payload = vec![0; data.len()];
+            reader.read_bytes(&mut payload).unwrap();
+            assert_eq!(payload, data);
+            assert_eq!(reader.read::<u8>(5).unwrap(), 0);
+            assert!(reader.read_bit().is_err());
+
+            // stuffed bitstreams never contain six 1 bits in a row
+            let mut writer =
+                BitStuffingWriter::endian(BitWriter::endian(Vec::new(), $endianness), $endianness);
+            writer.write_bytes(&data).unwrap();
+            writer.write_signed(7, -1i8).unwrap();
+            writer.write(32, u32::MAX).unwrap();
+            writer.byte_align().unwrap();
+            let stuffed = writer.into_writer().into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&stuffed), $endianness);
+            let mut ones = 0;
+            while let Ok(bit) = reader.read_bit() {
+                ones = if bit { ones + 1 } else { 0 };
+                assert!(ones <= 5);
+            }
+
+            let mut reader = BitStuffingReader::endian(
+                BitReader::endian(Cursor::new(&stuffed), $endianness),
+                $endianness,
+            );
+            let mut payload = vec![0; data.len()];
+            reader.read_bytes(&mut payload).unwrap();
+            assert_eq!(payload, data);
+            assert_eq!(reader.read_signed::<i8>(7).unwrap(), -1);
+            assert_eq!(reader.read::<u32>(32).unwrap(), u32::MAX);
+
+            // an unstuffed run of six 1 bits is an error
+            let mut reader = BitStuffingReader::endian(
+                BitReader::endian(Cursor::new(&[0xFF]), $endianness),
+                $endianness,
+            );
+            assert!(reader.read::<u8>(6).is_err());
+        }
+    };
+}
+
+define_stuffing_roundtrip!(test_stuffing_roundtrip_be, BigEndian);
+define_stuffing_roundtrip!(test_stuffing_roundtrip_le, LittleEndian);
+
+macro_rules! define_parity_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::parity::{Parity, ParityReader, ParityWriter};
+
+            let data: Vec<u8> = (0..256u32).map(|i| (i * 37 % 251) as u8).collect();
+
+            for &parity in [Parity::Even, Parity::Odd].iter() {
+                // 8 data bits and a parity bit per frame
+                let mut writer = ParityWriter::endian(
+                    BitWriter::endian(Vec::new(), $endianness),
+                    $endianness,
+                    parity,
+                    8,
+                );
+                writer.write_bytes(&data).unwrap();
+                writer.write_signed(5, -3i8).unwrap();
+                writer.write(27, 0x5A5_A5A5u32).unwrap();
+                writer.write(32, u32::MAX).unwrap();
+                assert_eq!(writer.group_position(), 0);
+                let framed = writer.into_writer().into_writer();
+                assert_eq!(framed.len(), (data.len() + 8) * 9 / 8);
+
+                // every frame has the requested parity
+                let mut reader = BitReader::endian(Cursor::new(&framed), $endianness);
+                for _ in 0..(data.len() + 8) {
+                    let ones = (0..9).filter(|_| reader.read_bit().unwrap()).count();
+                    assert_eq!(ones % 2 == 0, parity == Parity::Even);
+                }
+
+                let mut reader = ParityReader::endian(
+                    BitReader::endian(Cursor::new(&framed), $endianness),
+                    $endianness,
+                    parity,
+                    8,
+                );
+                let mut payload = vec![0; data.len()];
+                reader.read_bytes(&mut payload).unwrap();
+                assert_eq!(payload, data);
+                assert_eq!(reader.read_signed::<i8>(5).unwrap(), -3);
+                assert_eq!(reader.read::<u32>(27).unwrap(), 0x5A5_A5A5);
+                assert_eq!(reader.read::<u32>(32).unwrap(), u32::MAX);
+                assert!(reader.read_bit().is_err());
+
+                // any single flipped bit is detected
+                for i in 0..framed.len() * 8 {
+                    let mut corrupt = framed.clone();
+                    corrupt[i / 8] ^= 1 << (i % 8);
+                    let mut reader = ParityReader::endian(
+                        BitReader::endian(Cursor::new(&corrupt), $endianness),
+                        $endianness,
+                        parity,
+                        8,
+                    );
+                    assert!(reader.skip((data.len() as u64 + 8) * 8).is_err());
+                }
+            }
+        }
+    };
+}
+
+define_parity_roundtrip!(test_parity_roundtrip_be, BigEndian);
+define_parity_roundtrip!(test_parity_roundtrip_le, LittleEndian);
+
+macro_rules! define_run_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::{BitCounter, BitRecorder};
+
+            let runs: Vec<u64> = vec![1, 7, 8, 9, 64, 65, 3, 4096 * 8 + 13, 2, 100_000, 1];
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            let mut recorder: BitRecorder<u64, $endianness> = BitRecorder::new();
+            let mut counter: BitCounter<u64, $endianness> = BitCounter::new();
+            writer.write_run(false, 0).unwrap();
+            for (i, run) in runs.iter().enumerate() {
+                writer.write_run(i % 2 == 0, *run).unwrap();
+                recorder.write_run(i % 2 == 0, *run).unwrap();
+                counter.write_run(i % 2 == 0, *run).unwrap();
+            }
+            assert_eq!(counter.written(), runs.iter().sum::<u64>());
+            assert_eq!(recorder.written(), counter.written());
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut played = BitWriter::endian(Vec::new(), $endianness);
+            recorder.playback(&mut played).unwrap();
+            played.byte_align().unwrap();
+            assert_eq!(played.into_writer(), data);
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read_run(false).unwrap(), 0);
+            for (i, run) in runs.iter().enumerate() {
+                assert_eq!(reader.read_run(i % 2 == 0).unwrap(), *run);
+            }
+            assert_eq!(
+                reader.read_run(false).unwrap(),
+                (8 - counter.written() % 8) % 8
+            );
+            assert_eq!(reader.read_run(true).unwrap(), 0);
+        }
+    };
+}
+
+define_run_roundtrip!(test_run_roundtrip_be, BigEndian);
+define_run_roundtrip!(test_run_roundtrip_le, LittleEndian);
+
+macro_rules! define_exp_golomb_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let values: Vec<u64> = (0..300)
+                .chain((1..64).map(|bits| (1 << bits) - 1))
+                .chain((1..64).map(|bits| 1 << bits))
+                .chain(Some(u64::MAX))
+                .collect();
+            let orders = [0, 1, 2, 5, 31, 63, 64];
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for k in orders.iter() {
+                for value in values.iter() {
+                    writer.write_exp_golomb(*k, *value).unwrap();
+                }
+            }
+            assert!(writer.write_exp_golomb(65, 0).is_err());
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for k in orders.iter() {
+                for value in values.iter() {
+                    assert_eq!(reader.read_exp_golomb(*k).unwrap(), *value);
+                }
+            }
+            assert!(reader.read_exp_golomb(65).is_err());
+
+            // codes for values beyond u64::MAX are rejected
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            writer.write_unary1(64).unwrap();
+            writer.write(64, u64::MAX).unwrap();
+            writer.write_unary1(65).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert!(reader.read_exp_golomb(0).is_err());
+            assert!(reader.read_exp_golomb(0).is_err());
+        }
+    };
+}
+
+define_exp_golomb_roundtrip!(test_exp_golomb_roundtrip_be, BigEndian);
+define_exp_golomb_roundtrip!(test_exp_golomb_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_dyn_endianness_roundtrip() {
+    use bitstream_io::{DynBitReader, DynBitWriter, DynEndianness};
+
+    // the same non-generic code handles either endianness
+    fn write_fields(writer: &mut DynBitWriter<&mut Vec<u8>>) {
+        writer.write(3, 0b101u8).unwrap();
+        writer.write_signed(11, -500i16).unwrap();
+        writer.write_unary0(9).unwrap();
+        writer.write_bytes(b"abc").unwrap();
+        writer.write(64, u64::MAX - 1).unwrap();
+        writer.byte_align().unwrap();
+    }
+
+    fn read_fields(reader: &mut DynBitReader<Cursor<&Vec<u8>>>) {
+        assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+        assert_eq!(reader.read_signed::<i16>(11).unwrap(), -500);
+        assert_eq!(reader.read_unary0().unwrap(), 9);
+        let mut bytes = [0; 3];
+        reader.read_bytes(&mut bytes).unwrap();
+        assert_eq!(&bytes, b"abc");
+        assert_eq!(reader.read::<u64>(64).unwrap(), u64::MAX - 1);
+        reader.byte_align();
+        assert!(reader.read_bit().is_err());
+    }
+
+    let mut big = Vec::new();
+    write_fields(&mut DynBitWriter::new(&mut big, DynEndianness::Big));
+    let mut little = Vec::new();
+    let mut writer = DynBitWriter::from(BitWriter::endian(&mut little, LittleEndian));
+    assert_eq!(writer.endianness(), DynEndianness::Little);
+    write_fields(&mut writer);
+
+    // the output matches that of a statically chosen endianness
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    writer.write(3, 0b101u8).unwrap();
+    writer.write_signed(11, -500i16).unwrap();
+    assert_eq!(writer.into_writer(), &big[0..1]);
+    assert_ne!(big, little);
+
+    let mut reader = DynBitReader::new(Cursor::new(&big), DynEndianness::from(BigEndian));
+    assert_eq!(reader.endianness(), DynEndianness::Big);
+    read_fields(&mut reader);
+    read_fields(&mut DynBitReader::from(BitReader::endian(
+        Cursor::new(&little),
+        LittleEndian,
+    )));
+}
+
+macro_rules! define_dyn_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::{BitReadCore, BitWriteCore};
+
+            fn write_all(writer: &mut dyn BitWriteCore) {
+                writer.write(7, 100u8).unwrap();
+                writer.write(16, 0xBEEFu16).unwrap();
+                writer.write(64, u64::MAX).unwrap();
+                writer.write(40, 0xAB_CDEF_0123u128).unwrap();
+                writer.write_signed(5, -16i8).unwrap();
+                writer.write_signed(33, -(1i64 << 32)).unwrap();
+                writer.write_signed(64, i64::MIN).unwrap();
+                writer.write_signed(50, -12345i128).unwrap();
+                writer.write_signed(12, 2047i16).unwrap();
+                writer.write_bit(true).unwrap();
+                assert!(writer.write(65, 0u128).is_err());
+                assert!(writer.write(9, 0u8).is_err());
+                assert!(writer.write(3, 8u32).is_err());
+                assert!(writer.write_signed(65, 0i128).is_err());
+                writer.byte_align().unwrap();
+            }
+
+            fn read_all(reader: &mut dyn BitReadCore) {
+                assert_eq!(reader.read::<u8>(7).unwrap(), 100);
+                assert_eq!(reader.read::<u16>(16).unwrap(), 0xBEEF);
+                assert_eq!(reader.read::<u64>(64).unwrap(), u64::MAX);
+                assert_eq!(reader.read::<u128>(40).unwrap(), 0xAB_CDEF_0123);
+                assert_eq!(reader.read_signed::<i8>(5).unwrap(), -16);
+                assert_eq!(reader.read_signed::<i64>(33).unwrap(), -(1 << 32));
+                assert_eq!(reader.read_signed::<i64>(64).unwrap(), i64::MIN);
+                assert_eq!(reader.read_signed::<i128>(50).unwrap(), -12345);
+                assert_eq!(reader.read_signed::<i16>(12).unwrap(), 2047);
+                assert!(reader.read_bit().unwrap());
+                assert!(reader.read::<u128>(65).is_err());
+                assert!(reader.read::<u8>(9).is_err());
+                reader.byte_align();
+                assert!(reader.read_bit().is_err());
+            }
+
+            // the dynamic writer's output matches the static writer's
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            write_all(&mut writer);
+            let data = writer.into_writer();
+
+            let mut expected = BitWriter::endian(Vec::new(), $endianness);
+            expected.write(7, 100u8).unwrap();
+            expected.write(16, 0xBEEFu16).unwrap();
+            expected.write(64, u64::MAX).unwrap();
+            expected.write(40, 0xAB_CDEF_0123u128).unwrap();
+            expected.write_signed(5, -16i8).unwrap();
+            expected.write_signed(33, -(1i64 << 32)).unwrap();
+            expected.write_signed(64, i64::MIN).unwrap();
+            expected.write_signed(50, -12345i128).unwrap();
+            expected.write_signed(12, 2047i16).unwrap();
+            expected.write_bit(true).unwrap();
+            expected.byte_align().unwrap();
+            assert_eq!(data, expected.into_writer());
+
+            let mut reader: Box<dyn BitReadCore> =
+                Box::new(BitReader::endian(Cursor::new(&data), $endianness));
+            read_all(reader.as_mut());
+        }
+    };
+}
+
+define_dyn_roundtrip!(test_dyn_roundtrip_be, BigEndian);
+define_dyn_roundtrip!(test_dyn_roundtrip_le, LittleEndian);
+
+#[test]
+fn test_mut_ref_roundtrip() {
+    use bitstream_io::huffman::{compile_read_tree, compile_write_tree};
+    use bitstream_io::{HuffmanRead, HuffmanWrite};
+
+    // encoding functions which take their writers by value
+    fn encode_header<W: BitWrite>(mut writer: W, kind: u8) {
+        writer.write(4, kind).unwrap();
+        writer.write_unary1(kind.into()).unwrap();
+    }
+
+    fn encode_symbols<W: HuffmanWrite<BigEndian>>(
+        mut writer: W,
+        tree: &bitstream_io::huffman::WriteHuffmanTree<BigEndian, char>,
+    ) {
+        for symbol in "abcd".chars() {
+            writer.write_huffman(tree, symbol).unwrap();
+        }
+    }
+
+    fn decode_header<R: BitRead>(mut reader: R) -> u8 {
+        let kind = reader.read(4).unwrap();
+        assert_eq!(reader.read_unary1().unwrap(), u32::from(kind));
+        kind
+    }
+
+    fn decode_symbols<R: HuffmanRead<BigEndian>>(
+        mut reader: R,
+        tree: &[bitstream_io::huffman::ReadHuffmanTree<BigEndian, char>],
+    ) -> String {
+        let mut symbols = ['\0'; 4];
+        reader.read_huffman_into(tree, &mut symbols).unwrap();
+        symbols.iter().collect()
+    }
+
+    let codes = vec![
+        ('a', vec![0]),
+        ('b', vec![1, 0]),
+        ('c', vec![1, 1, 0]),
+        ('d', vec![1, 1, 1]),
+    ];
+    let write_tree = compile_write_tree(codes.clone()).unwrap();
+    let read_tree = compile_read_tree(codes).unwrap();
+
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    encode_header(&mut writer, 3);
+    encode_symbols(&mut writer, &write_tree);
+    encode_header(&mut &mut writer, 5);
+    writer.byte_align().unwrap();
+    let data = writer.into_writer();
+
+    let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    assert_eq!(decode_header(&mut reader), 3);
+    assert_eq!(decode_symbols(&mut reader, &read_tree), "abcd");
+    assert_eq!(decode_header(&mut &mut reader), 5);
+    let remaining = &mut reader;
+    remaining.byte_align();
+    assert!(remaining.read_bit().is_err());
+}
+
+macro_rules! define_bits_iter_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::BitRecorder;
+
+            // a pseudorandom bitmap which doesn't fill whole words
+            let bitmap: Vec<bool> = (0..1001u32).map(|i| (i * 37 + i / 5) % 3 == 0).collect();
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            let mut recorder: BitRecorder<u32, $endianness> = BitRecorder::new();
+            writer.write(3, 0b101u8).unwrap();
+            writer.write_bits_from(bitmap.iter().copied()).unwrap();
+            writer.write_bits_from(std::iter::empty()).unwrap();
+            writer.write(5, 0b10011u8).unwrap();
+            recorder.write(3, 0b101u8).unwrap();
+            recorder.write_bits_from(bitmap.iter().copied()).unwrap();
+            recorder.write(5, 0b10011u8).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut played = BitWriter::endian(Vec::new(), $endianness);
+            recorder.playback(&mut played).unwrap();
+            played.byte_align().unwrap();
+            assert_eq!(played.into_writer(), data);
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            let bits = reader.read_bits_iter(bitmap.len() as u64);
+            assert_eq!(bits.remaining(), bitmap.len() as u64);
+            let read = bits.collect::<Result<Vec<bool>, _>>().unwrap();
+            assert_eq!(read, bitmap);
+            assert_eq!(reader.read::<u8>(5).unwrap(), 0b10011);
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            let total = data.len() as u64 * 8;
+            assert!(reader
+                .read_bits_iter(total + 1)
+                .collect::<Result<Vec<bool>, _>>()
+                .is_err());
+        }
+    };
+}
+
+define_bits_iter_roundtrip!(test_bits_iter_roundtrip_be, BigEndian);
+define_bits_iter_roundtrip!(test_bits_iter_roundtrip_le, LittleEndian);
+
+#[cfg(feature = "observer")]
+macro_rules! define_observer_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::huffman::{compile_read_tree, compile_write_tree};
+            use bitstream_io::observer::BitObserver;
+            use bitstream_io::{BitCounter, HuffmanRead, HuffmanWrite};
+            use std::sync::{Arc, Mutex};
+
+            #[derive(Default)]
+            struct Runs {
+                written: Vec<(u32, u64)>,
+                read: Vec<(u32, u64)>,
+            }
+
+            impl BitObserver for Runs {
+                fn on_write(&mut self, bits: u32, offset: u64) {
+                    self.written.push((bits, offset));
+                }
+
+                fn on_read(&mut self, bits: u32, offset: u64) {
+                    self.read.push((bits, offset));
+                }
+            }
+
+            // runs are contiguous and return their total
+            fn total(runs: &[(u32, u64)]) -> u64 {
+                runs.iter().fold(0, |offset, (bits, start)| {
+                    assert_eq!(*start, offset);
+                    offset + u64::from(*bits)
+                })
+            }
+
+            let symbols = ['b', 'c', 'd', 'e', 'a'];
+            let codes = vec![
+                ('a', vec![0]),
+                ('b', vec![1, 0]),
+                ('c', vec![1, 1, 0]),
+                ('d', vec![1, 1, 1, 0]),
+                ('e', vec![1, 1, 1, 1]),
+            ];
+            let write_tree = compile_write_tree::<$endianness, char>(codes.clone()).unwrap();
+            let read_tree = compile_read_tree::<$endianness, char>(codes).unwrap();
+
+            fn encode<W: BitWrite + HuffmanWrite<$endianness>>(
+                w: &mut W,
+                tree: &bitstream_io::huffman::WriteHuffmanTree<$endianness, char>,
+                symbols: &[char],
+            ) {
+                w.write_bit(true).unwrap();
+                w.write(13, 0x1234u16).unwrap();
+                w.write_signed(9, -200i16).unwrap();
+                w.write_unary0(20).unwrap();
+                w.write_unary1(3).unwrap();
+                w.write_huffman_all(tree, symbols).unwrap();
+                w.write_run(true, 5000).unwrap();
+                w.write_bcd(3, 407).unwrap();
+                w.byte_align().unwrap();
+                w.write_bytes(b"observed").unwrap();
+                w.write(128, u128::MAX).unwrap();
+                w.write(3, 0b010u8).unwrap();
+                w.write_unary0_capped(4, 4).unwrap();
+                w.write_unary1_capped(4, 2).unwrap();
+                w.write_huffman_all(tree, symbols).unwrap();
+                w.write_run(false, 3).unwrap();
+                w.write(6, 0b100001u8).unwrap();
+                w.byte_align().unwrap();
+            }
+
+            let runs = Arc::new(Mutex::new(Runs::default()));
+            let mut counter = BitCounter::<u64, $endianness>::new();
+            encode(&mut counter, &write_tree, &symbols);
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            writer.set_observer(runs.clone());
+            encode(&mut writer, &write_tree, &symbols);
+            let data = writer.into_writer();
+            assert_eq!(total(&runs.lock().unwrap().written), counter.written());
+            assert!(runs.lock().unwrap().read.is_empty());
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            reader.set_observer(runs.clone());
+            assert!(reader.read_bit().unwrap());
+            assert_eq!(reader.peek::<u16>(13).unwrap(), 0x1234);
+            assert_eq!(reader.read::<u16>(13).unwrap(), 0x1234);
+            assert_eq!(reader.read_signed::<i16>(9).unwrap(), -200);
+            assert_eq!(reader.read_unary0().unwrap(), 20);
+            assert_eq!(reader.read_unary1().unwrap(), 3);
+            for symbol in symbols.iter() {
+                assert_eq!(reader.read_huffman(&read_tree).unwrap(), *symbol);
+            }
+            assert_eq!(reader.read_run(true).unwrap(), 5000);
+            assert_eq!(reader.read_bcd(3).unwrap(), 407);
+            reader.byte_align();
+            let mut buf = [0; 8];
+            reader.read_bytes(&mut buf).unwrap();
+            assert_eq!(&buf, b"observed");
+            reader.skip(128).unwrap();
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b010);
+            assert_eq!(reader.read_unary0_capped(4).unwrap(), 4);
+            assert_eq!(reader.read_unary1_capped(4).unwrap(), 2);
+            let mut decoded = ['?'; 5];
+            reader.read_huffman_into(&read_tree, &mut decoded).unwrap();
+            assert_eq!(decoded, symbols);
+            assert_eq!(reader.read_run(false).unwrap(), 3);
+            assert_eq!(reader.read::<u8>(6).unwrap(), 0b100001);
+            reader.byte_align();
+            assert!(reader.read_bit().is_err());
+
+            let runs = runs.lock().unwrap();
+            assert_eq!(total(&runs.read), data.len() as u64 * 8);
+        }
+    };
+}
+
+#[cfg(feature = "observer")]
+define_observer_roundtrip!(test_observer_roundtrip_be, BigEndian);
+#[cfg(feature = "observer")]
+define_observer_roundtrip!(test_observer_roundtrip_le, LittleEndian);
+
+#[cfg(feature = "observer")]
+#[test]
+fn test_sectioned_counter() {
+    use bitstream_io::observer::SectionedBitCounter;
+    use std::sync::{Arc, Mutex};
+
+    let write_sections = Arc::new(Mutex::new(SectionedBitCounter::new()));
+    let read_sections = Arc::new(Mutex::new(SectionedBitCounter::new()));
+
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    writer.set_observer(write_sections.clone());
+    for frame in 0..10u32 {
+        write_sections.lock().unwrap().start_section("header");
+        writer.write(14, 0x3FFEu16).unwrap();
+        writer.write_unary0(frame).unwrap();
+        write_sections.lock().unwrap().start_section("residual");
+        writer
+            .write_run(frame % 2 == 0, u64::from(frame) * 100)
+            .unwrap();
+        write_sections.lock().unwrap().end_section();
+        write_sections.lock().unwrap().end_section();
+        writer.byte_align().unwrap();
+    }
+    let data = writer.into_writer();
+
+    let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    reader.set_observer(read_sections.clone());
+    for frame in 0..10u32 {
+        read_sections.lock().unwrap().start_section("header");
+        assert_eq!(reader.read::<u16>(14).unwrap(), 0x3FFE);
+        assert_eq!(reader.read_unary0().unwrap(), frame);
+        read_sections.lock().unwrap().start_section("residual");
+        reader.skip(u64::from(frame) * 100).unwrap();
+        assert_eq!(
+            read_sections.lock().unwrap().current_section(),
+            Some("residual")
+        );
+        read_sections.lock().unwrap().end_section();
+        read_sections.lock().unwrap().end_section();
+        assert_eq!(read_sections.lock().unwrap().current_section(), None);
+        reader.byte_align();
+    }
+
+    let written = write_sections.lock().unwrap();
+    let read = read_sections.lock().unwrap();
+    assert_eq!(written.section_bits("header"), 10 * 15 + 45);
+    assert_eq!(written.section_bits("residual"), 4500);
+    assert_eq!(written.total_bits(), data.len() as u64 * 8);
+    assert_eq!(written.sections(), read.sections());
+    assert_eq!(written.unsectioned_bits(), read.unsectioned_bits());
+}
+
+#[cfg(feature = "bitvec")]
+#[test]
+fn test_bitvec_roundtrip() {
+    use bitvec::prelude::*;
+
+    let bits: BitVec<u8, Msb0> = (0..203u32).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            writer.write(3, 0b101u8).unwrap();
+            writer.write_bitslice(&bits).unwrap();
+            writer.write_bitslice(&bits[5..17]).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            assert_eq!(reader.read_bitvec(bits.len() as u64).unwrap(), bits);
+            assert_eq!(reader.read_bitvec(12).unwrap(), bits[5..17]);
+            assert!(reader.read_bitvec(8).is_err());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_roundtrip() {
+    use bitstream_io::huffman::{
+        compile_compact_read_tree, compile_write_tree, CompactReadHuffmanTree, WriteHuffmanTree,
+    };
+    use bitstream_io::{BitRecorder, HuffmanRead, HuffmanWrite};
+
+    let spec = vec![
+        (0u16, vec![1, 1]),
+        (1u16, vec![0]),
+        (2u16, vec![1, 0, 1]),
+        (3u16, vec![1, 0, 0, 0]),
+        (4u16, vec![1, 0, 0, 1]),
+    ];
+    let symbols: Vec<u16> = (0..101).map(|i| (i * 7 + i / 5) % 5).collect();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let write_tree = compile_write_tree::<$endianness, u16>(spec.clone()).unwrap();
+            let write_tree: WriteHuffmanTree<$endianness, u16> =
+                serde_json::from_str(&serde_json::to_string(&write_tree).unwrap()).unwrap();
+            let read_tree = compile_compact_read_tree::<$endianness, u16>(spec.clone(), 2).unwrap();
+            let read_tree: CompactReadHuffmanTree<$endianness, u16> =
+                serde_json::from_str(&serde_json::to_string(&read_tree).unwrap()).unwrap();
+
+            let mut recorder: BitRecorder<u32, $endianness> = BitRecorder::new();
+            recorder.write(3, 0b101u8).unwrap();
+            for symbol in symbols.iter() {
+                recorder.write_huffman(&write_tree, *symbol).unwrap();
+            }
+            let written = recorder.written();
+            let recorder: BitRecorder<u32, $endianness> =
+                serde_json::from_str(&serde_json::to_string(&recorder).unwrap()).unwrap();
+            assert_eq!(recorder.written(), written);
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            recorder.playback(&mut writer).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            for symbol in symbols.iter() {
+                assert_eq!(reader.read_compact_huffman(&read_tree).unwrap(), *symbol);
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+
+    // byte count doesn't match bit count
+    assert!(
+        serde_json::from_str::<BitRecorder<u32, BigEndian>>(r#"{"bits":9,"bytes":[1]}"#).is_err()
+    );
+    // too many bits for counter
+    let mut recorder: BitRecorder<u32, BigEndian> = BitRecorder::new();
+    recorder.write_bytes(&[0; 8191]).unwrap();
+    recorder.write(7, 0u8).unwrap();
+    let json = serde_json::to_string(&recorder).unwrap();
+    assert!(serde_json::from_str::<BitRecorder<u16, BigEndian>>(&json).is_ok());
+    recorder.write_bit(false).unwrap();
+    let json = serde_json::to_string(&recorder).unwrap();
+    assert!(serde_json::from_str::<BitRecorder<u16, BigEndian>>(&json).is_err());
+    // incomplete write trees still compile, invalid ones don't
+    assert!(serde_json::from_str::<WriteHuffmanTree<BigEndian, u16>>("[[0,[0]]]").is_ok());
+    assert!(serde_json::from_str::<WriteHuffmanTree<BigEndian, u16>>("[[0,[2]]]").is_err());
+    // lookup tables must stay inside the tree and lead forward
+    let table = |offset: usize, bits: u32| {
+        format!(
+            r#"{{"root_bits":1,"entries":[{{"Leaf":0}},{{"Table":{{"offset":{},"bits":{}}}}},"Unknown","Unknown"]}}"#,
+            offset, bits
+        )
+    };
+    assert!(serde_json::from_str::<CompactReadHuffmanTree<BigEndian, u16>>(&table(2, 1)).is_ok());
+    for (offset, bits) in [(1, 1), (0, 1), (2, 0), (2, 2), (3, 1)].iter() {
+        assert!(
+            serde_json::from_str::<CompactReadHuffmanTree<BigEndian, u16>>(&table(*offset, *bits))
+                .is_err()
+        );
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "testing"))]
+#[test]
+fn test_op_log_serde_roundtrip() {
+    use bitstream_io::testing::{Op, OpLog};
+
+    let log: OpLog = vec![
+        Op::Bit(true),
+        Op::Unsigned { bits: 3, value: 5 },
+        Op::Signed { bits: 4, value: -2 },
+        Op::Unary0(3),
+        Op::ByteAlign,
+        Op::Bytes(vec![1, 2, 3]),
+        Op::Unary1(4),
+        Op::Skip(3),
+        Op::Unsigned {
+            bits: 128,
+            value: u128::MAX,
+        },
+        Op::Signed {
+            bits: 128,
+            value: i128::MIN,
+        },
+    ]
+    .into_iter()
+    .collect();
+
+    let json = serde_json::to_string(&log).unwrap();
+    let restored: OpLog = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, log);
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            restored.apply_to_writer(&mut writer).unwrap();
+            let data = writer.into_writer();
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(log.apply_to_reader(&mut reader).unwrap(), log);
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_counting_roundtrip() {
+    use bitstream_io::{BitCounter, CountingBitReader};
+
+    fn write_fields<W: BitWrite>(w: &mut W) {
+        w.write(3, 0b101u8).unwrap();
+        w.write_signed(13, -1234i16).unwrap();
+        w.write_unary0(17).unwrap();
+        w.write_unary1(3).unwrap();
+        w.write_float(1.5f32).unwrap();
+        w.write_bytes(b"abc").unwrap();
+        w.byte_align().unwrap();
+        w.write(7, 0x55u8).unwrap();
+    }
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut counter: BitCounter<u64, $endianness> = BitCounter::new();
+            write_fields(&mut counter);
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            write_fields(&mut writer);
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader =
+                CountingBitReader::new(BitReader::endian(Cursor::new(&data), $endianness));
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            assert_eq!(reader.read_signed::<i16>(13).unwrap(), -1234);
+            assert_eq!(reader.bits_read(), 16);
+            assert_eq!(reader.read_unary0().unwrap(), 17);
+            assert_eq!(reader.read_unary1().unwrap(), 3);
+            assert_eq!(reader.bits_read(), 16 + 18 + 4);
+            assert_eq!(reader.read_float::<f32>().unwrap(), 1.5);
+            let mut abc = [0; 3];
+            reader.read_bytes(&mut abc).unwrap();
+            assert_eq!(&abc, b"abc");
+            reader.byte_align();
+            assert_eq!(reader.bytes_read() * 8, reader.bits_read());
+            assert_eq!(reader.read::<u8>(7).unwrap(), 0x55);
+            assert_eq!(reader.bits_read(), counter.written());
+            assert_eq!(reader.bytes_read(), data.len() as u64);
+            // a failed read isn't counted
+            assert!(reader.read::<u8>(2).is_err());
+            assert_eq!(reader.bits_read(), counter.written());
+            reader.reset();
+            assert_eq!(reader.bits_read(), 0);
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_inverted_roundtrip() {
+    use bitstream_io::invert::{InvertedReader, InvertedWriter};
+
+    fn write_fields<W: BitWrite>(w: &mut W) {
+        w.write(3, 0b101u8).unwrap();
+        w.write(64, 0x0123_4567_89AB_CDEFu64).unwrap();
+        w.write(0, 0u8).unwrap();
+        w.write_signed(13, -1234i16).unwrap();
+        w.write_signed(8, i8::MIN).unwrap();
+        w.write_bit(false).unwrap();
+        w.write_unary0(5).unwrap();
+        w.write_unary1(3).unwrap();
+        w.write_run(true, 70).unwrap();
+        w.write_bytes(&[0x12; 300]).unwrap();
+        w.write(7, 0x7Fu8).unwrap();
+        w.write_bytes(b"abc").unwrap();
+        w.write(5, 0x15u8).unwrap();
+        w.write(3, 0b010u8).unwrap();
+    }
+
+    fn read_fields<R: BitRead>(r: &mut R) {
+        assert_eq!(r.read::<u8>(3).unwrap(), 0b101);
+        assert_eq!(r.read::<u64>(64).unwrap(), 0x0123_4567_89AB_CDEF);
+        assert_eq!(r.read::<u8>(0).unwrap(), 0);
+        assert_eq!(r.read_signed::<i16>(13).unwrap(), -1234);
+        assert_eq!(r.read_signed::<i8>(8).unwrap(), i8::MIN);
+        assert!(!r.read_bit().unwrap());
+        assert_eq!(r.read_unary0().unwrap(), 5);
+        assert_eq!(r.read_unary1().unwrap(), 3);
+        r.skip(70).unwrap();
+        let mut buf = [0; 300];
+        r.read_bytes(&mut buf).unwrap();
+        assert!(buf.iter().all(|b| *b == 0x12));
+        assert_eq!(r.read::<u8>(7).unwrap(), 0x7F);
+        let mut abc = [0; 3];
+        r.read_bytes(&mut abc).unwrap();
+        assert_eq!(&abc, b"abc");
+        assert_eq!(r.read::<u8>(5).unwrap(), 0x15);
+        assert_eq!(r.read::<u8>(3).unwrap(), 0b010);
+        assert!(r.read_bit().is_err());
+    }
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            write_fields(&mut writer);
+            let plain = writer.into_writer();
+
+            let mut writer = InvertedWriter::new(BitWriter::endian(Vec::new(), $endianness));
+            write_fields(&mut writer);
+            // invalid writes are still rejected
+            assert!(writer.write(3, 8u8).is_err());
+            assert!(writer.write(9, 0u8).is_err());
+            assert!(writer.write_signed(3, 4i8).is_err());
+            assert!(writer.write_signed(3, -5i8).is_err());
+            let inverted = writer.into_writer().into_writer();
+
+            // the fields fill whole bytes, so every bit is flipped
+            assert_eq!(inverted, plain.iter().map(|b| !b).collect::<Vec<u8>>());
+
+            read_fields(&mut InvertedReader::new(BitReader::endian(
+                Cursor::new(&inverted),
+                $endianness,
+            )));
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_scrambled_roundtrip() {
+    use bitstream_io::scramble::{DescramblingReader, Lfsr, ScramblingWriter};
+
+    // maximal-length registers repeat after 2 ^ stages - 1 bits
+    let mut lfsr = Lfsr::new(1 << 7 | 1 << 4, 0b101_0011);
+    assert_eq!(lfsr.len(), 7);
+    let sequence: Vec<bool> = (0..127).map(|_| lfsr.next_bit()).collect();
+    assert_eq!(lfsr.state(), 0b101_0011);
+    assert_eq!(sequence.iter().filter(|b| **b).count(), 64);
+    lfsr.next_bit();
+    lfsr.reset();
+    assert_eq!(lfsr.state(), 0b101_0011);
+
+    let (polynomial, seed) = (1 << 15 | 1 << 14, 0b000_0000_1010_1001);
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut writer = ScramblingWriter::endian(
+                BitWriter::endian(Vec::new(), $endianness),
+                $endianness,
+                polynomial,
+                seed,
+            );
+            writer.write(3, 0b101u8).unwrap();
+            writer.write(64, 0x0123_4567_89AB_CDEFu64).unwrap();
+            writer.write_signed(13, -1234i16).unwrap();
+            writer.write_unary0(5).unwrap();
+            writer.write_bytes(&[0; 100]).unwrap();
+            writer.byte_align().unwrap();
+            assert!(writer.write(3, 8u8).is_err());
+            assert!(writer.write(9, 0u8).is_err());
+            writer.reset();
+            writer.write_bytes(b"next frame").unwrap();
+            let data = writer.into_writer().into_writer();
+            // zeros are no longer zeros once scrambled
+            assert!(data[20..110].iter().any(|b| *b != 0));
+
+            let mut reader = DescramblingReader::endian(
+                BitReader::endian(Cursor::new(&data), $endianness),
+                $endianness,
+                polynomial,
+                seed,
+            );
+            assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+            assert_eq!(reader.read::<u64>(64).unwrap(), 0x0123_4567_89AB_CDEF);
+            assert_eq!(reader.read_signed::<i16>(13).unwrap(), -1234);
+            assert_eq!(reader.read_unary0().unwrap(), 5);
+            // skipped bits still advance the register
+            reader.skip(99 * 8).unwrap();
+            assert_eq!(reader.read::<u8>(8).unwrap(), 0);
+            // as do the bits discarded by alignment
+            reader.byte_align();
+            reader.reset();
+            let mut frame = [0; 10];
+            reader.read_bytes(&mut frame).unwrap();
+            assert_eq!(&frame, b"next frame");
+            assert!(reader.read_bit().is_err());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_interleaved_roundtrip() {
+    use bitstream_io::interleave::{DeinterleavingReader, Interleaver, InterleavingWriter};
+
+    let interleaver = Interleaver::rows_columns(5, 7);
+    assert_eq!(interleaver.block_size(), 35);
+    let mut sorted = interleaver.permutation().to_vec();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (0..35).collect::<Vec<usize>>());
+
+    // a burst of errors in the sent bits is spread across the block
+    let interleaver = Interleaver::rows_columns(4, 8);
+    let mut writer = InterleavingWriter::endian(
+        BitWriter::endian(Vec::new(), BigEndian),
+        BigEndian,
+        interleaver.clone(),
+    );
+    writer.write(32, 0u32).unwrap();
+    let mut data = writer.finish().unwrap().into_writer();
+    data[0] = 0xFF;
+    let mut reader = DeinterleavingReader::endian(
+        BitReader::endian(Cursor::new(&data), BigEndian),
+        BigEndian,
+        interleaver,
+    );
+    for _ in 0..4 {
+        assert_eq!(reader.read::<u8>(8).unwrap(), 0b1100_0000);
+    }
+
+    let interleavers = [
+        Interleaver::rows_columns(3, 11),
+        Interleaver::new((0..24).rev().collect()),
+        Interleaver::new(vec![0]),
+    ];
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            for interleaver in interleavers.iter() {
+                let mut writer = InterleavingWriter::endian(
+                    BitWriter::endian(Vec::new(), $endianness),
+                    $endianness,
+                    interleaver.clone(),
+                );
+                writer.write(3, 0b101u8).unwrap();
+                writer.write(64, 0x0123_4567_89AB_CDEFu64).unwrap();
+                writer.write_signed(13, -1234i16).unwrap();
+                writer.write_unary1(5).unwrap();
+                assert!(!writer.byte_aligned());
+                writer.byte_align().unwrap();
+                assert!(writer.byte_aligned());
+                writer.write_bytes(b"block").unwrap();
+                assert!(writer.write(3, 8u8).is_err());
+                assert!(writer.write(9, 0u8).is_err());
+                assert!(writer.pending() < interleaver.block_size());
+                let mut writer = writer.finish().unwrap();
+                writer.byte_align().unwrap();
+                let data = writer.into_writer();
+
+                let mut reader = DeinterleavingReader::endian(
+                    BitReader::endian(Cursor::new(&data), $endianness),
+                    $endianness,
+                    interleaver.clone(),
+                );
+                assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+                assert_eq!(reader.read::<u64>(64).unwrap(), 0x0123_4567_89AB_CDEF);
+                assert_eq!(reader.read_signed::<i16>(13).unwrap(), -1234);
+                reader.skip(1).unwrap();
+                assert_eq!(reader.read_unary1().unwrap(), 4);
+                assert!(!reader.byte_aligned());
+                reader.byte_align();
+                let mut block = [0; 5];
+                reader.read_bytes(&mut block).unwrap();
+                assert_eq!(&block, b"block");
+                assert!(reader.read::<u8>(9).is_err());
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_convolutional_roundtrip() {
+    use bitstream_io::convolutional::{ConvolutionalCode, ConvolutionalWriter, ViterbiReader};
+
+    let codes = [
+        ConvolutionalCode::new(7, &[0o171, 0o133]),
+        ConvolutionalCode::new(7, &[0o133, 0o171, 0o165]),
+        ConvolutionalCode::new(2, &[0b11, 0b10]),
+    ];
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            for code in codes.iter() {
+                // 3 frames of 96 data bits each
+                let mut writer = ConvolutionalWriter::endian(
+                    BitWriter::endian(Vec::new(), $endianness),
+                    $endianness,
+                    code.clone(),
+                );
+                assert_eq!(writer.code(), code);
+                for frame in 0..3u8 {
+                    writer.write(3, 0b101u8).unwrap();
+                    writer.write(64, 0x0123_4567_89AB_CDEFu64).unwrap();
+                    writer.write_signed(13, -1234i16).unwrap();
+                    writer.write_unary1(5).unwrap();
+                    assert!(!writer.byte_aligned());
+                    writer.byte_align().unwrap();
+                    writer.write(8, frame).unwrap();
+                    assert!(writer.write(3, 8u8).is_err());
+                    assert!(writer.write(9, 0u8).is_err());
+                    writer.terminate().unwrap();
+                }
+                let mut writer = writer.into_writer();
+                writer.byte_align().unwrap();
+                let mut data = writer.into_writer();
+                let frame_bits = (96 + code.constraint_length() - 1) * code.outputs();
+                assert_eq!(
+                    data.len() as u32,
+                    frame_bits * 3 / 8 + u32::from(frame_bits & 7 != 0)
+                );
+
+                // an isolated error in each of the last two frames
+                data[(frame_bits / 8 + 2) as usize] ^= 0b0010_0000;
+                data[(frame_bits / 4 + 5) as usize] ^= 0b0000_0001;
+
+                let mut reader = ViterbiReader::endian(
+                    BitReader::endian(Cursor::new(&data), $endianness),
+                    $endianness,
+                    code.clone(),
+                    96,
+                );
+                for frame in 0..3u8 {
+                    assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+                    assert_eq!(reader.read::<u64>(64).unwrap(), 0x0123_4567_89AB_CDEF);
+                    assert_eq!(reader.frame_errors(), if frame == 0 { 0 } else { 1 });
+                    assert_eq!(reader.read_signed::<i16>(13).unwrap(), -1234);
+                    reader.skip(1).unwrap();
+                    assert_eq!(reader.read_unary1().unwrap(), 4);
+                    assert!(!reader.byte_aligned());
+                    reader.byte_align();
+                    assert_eq!(reader.read::<u8>(8).unwrap(), frame);
+                    assert!(reader.read::<u8>(9).is_err());
+                }
+                assert!(reader.read_bit().is_err());
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_async_byte_roundtrip() {
+    use bitstream_io::async_io::{AsyncByteReader, AsyncByteWriter};
+    use bitstream_io::{ByteRead, ByteReader};
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            runtime.block_on(async {
+                // the same bytes as a synchronous ByteWriter
+                let (client, server) = tokio::io::duplex(64);
+                let mut writer = AsyncByteWriter::endian(client, $endianness);
+                writer.write(0x0123_4567_89AB_CDEFu64).await.unwrap();
+                writer.write(-1234i16).await.unwrap();
+                writer.write(0xFFu8).await.unwrap();
+                writer.write_bytes(b"payload").await.unwrap();
+                writer.write(0x1234_5678u32).await.unwrap();
+                writer.flush().await.unwrap();
+                assert_eq!(writer.position(), 22);
+                drop(writer);
+
+                let mut reader = AsyncByteReader::endian(server, $endianness);
+                let mut data = [0; 22];
+                reader.read_bytes(&mut data).await.unwrap();
+                assert!(reader.read::<u8>().await.is_err());
+
+                let mut reader = ByteReader::endian(&data[..], $endianness);
+                assert_eq!(reader.read::<u64>().unwrap(), 0x0123_4567_89AB_CDEF);
+
+                let mut reader = AsyncByteReader::endian(&data[..], $endianness);
+                assert_eq!(reader.read::<u64>().await.unwrap(), 0x0123_4567_89AB_CDEF);
+                assert_eq!(reader.read::<i16>().await.unwrap(), -1234);
+                assert_eq!(reader.read::<u8>().await.unwrap(), 0xFF);
+                let mut payload = [0; 7];
+                reader.read_bytes(&mut payload).await.unwrap();
+                assert_eq!(&payload, b"payload");
+                assert_eq!(reader.position(), 18);
+                assert!(reader.read::<u64>().await.is_err());
+            });
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_sign_placement_roundtrip() {
+    use bitstream_io::SignPlacement;
+
+    macro_rules! check {
+        ($endianness:ident, $native:expr) => {
+            for placement in [SignPlacement::First, SignPlacement::Last].iter() {
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                let mut native = BitWriter::endian(Vec::new(), $endianness);
+                for bits in 1..=8 {
+                    let limit = 1i16 << (bits - 1);
+                    for value in -limit..limit {
+                        writer
+                            .write_signed_placed(*placement, bits, value as i8)
+                            .unwrap();
+                        native.write_signed(bits, value as i8).unwrap();
+                    }
+                    assert!(writer.write_signed_placed(*placement, bits, limit).is_err());
+                    assert!(writer
+                        .write_signed_placed(*placement, bits, -limit - 1)
+                        .is_err());
+                }
+                writer.byte_align().unwrap();
+                writer
+                    .write_signed_placed(*placement, 32, i32::MIN)
+                    .unwrap();
+                writer
+                    .write_signed_placed(*placement, 32, i32::MAX)
+                    .unwrap();
+                assert!(writer.write_signed_placed(*placement, 0, 0i8).is_err());
+                assert!(writer.write_signed_placed(*placement, 9, 0i8).is_err());
+                native.byte_align().unwrap();
+                let data = writer.into_writer();
+                // the endianness' own placement matches write_signed
+                assert_eq!(
+                    data[..data.len() - 8] == native.into_writer()[..],
+                    *placement == $native
+                );
+
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                for bits in 1..=8 {
+                    let limit = 1i16 << (bits - 1);
+                    for value in -limit..limit {
+                        assert_eq!(
+                            reader.read_signed_placed::<i8>(*placement, bits).unwrap(),
+                            value as i8
+                        );
+                    }
+                }
+                reader.byte_align();
+                assert_eq!(
+                    reader.read_signed_placed::<i32>(*placement, 32).unwrap(),
+                    i32::MIN
+                );
+                assert_eq!(
+                    reader.read_signed_placed::<i32>(*placement, 32).unwrap(),
+                    i32::MAX
+                );
+                assert!(reader.read_signed_placed::<i8>(*placement, 0).is_err());
+                assert!(reader.read_signed_placed::<i8>(*placement, 9).is_err());
+            }
+        };
+    }
+
+    check!(BigEndian, SignPlacement::First);
+    check!(LittleEndian, SignPlacement::Last);
+}
+
+#[test]
+fn test_interleaved_samples_roundtrip() {
+    macro_rules! check {
+        ($endianness:ident) => {
+            for bits in [1, 8, 12, 20, 24, 32].iter() {
+                let (min, max) = if *bits == 32 {
+                    (i32::MIN, i32::MAX)
+                } else {
+                    (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+                };
+                let channels: Vec<Vec<i32>> = (0..3)
+                    .map(|c| {
+                        (0..50)
+                            .map(|i| match (i + c) % 5 {
+                                0 => min,
+                                1 => max,
+                                2 => 0,
+                                _ => {
+                                    (((i * 7919 + c * 104729) as i64 % (i64::from(max) + 1)) as i32)
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect();
+                let slices: Vec<&[i32]> = channels.iter().map(|c| c.as_slice()).collect();
+
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                writer.write(3, 0b101u8).unwrap();
+                writer.write_samples_interleaved(*bits, &slices).unwrap();
+
+                // the same as writing each sample in turn
+                let mut expected = BitWriter::endian(Vec::new(), $endianness);
+                expected.write(3, 0b101u8).unwrap();
+                for i in 0..50 {
+                    for channel in channels.iter() {
+                        expected.write_signed(*bits, channel[i]).unwrap();
+                    }
+                }
+
+                // nothing is written for invalid samples or channels
+                if *bits < 32 {
+                    let too_large = [0, max + 1];
+                    let too_small = [min - 1, 0];
+                    assert!(writer
+                        .write_samples_interleaved(*bits, &[&too_large, &too_small])
+                        .is_err());
+                    assert!(writer
+                        .write_samples_interleaved(*bits, &[&too_small, &too_large])
+                        .is_err());
+                }
+                assert!(writer
+                    .write_samples_interleaved(*bits, &[&[0, 0], &[0]])
+                    .is_err());
+                writer.byte_align().unwrap();
+                expected.byte_align().unwrap();
+                let data = writer.into_writer();
+                assert_eq!(data, expected.into_writer());
+
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                assert_eq!(reader.read::<u8>(3).unwrap(), 0b101);
+                let mut buffers = vec![vec![0; 50]; 3];
+                {
+                    let mut buffers: Vec<&mut [i32]> =
+                        buffers.iter_mut().map(|b| b.as_mut_slice()).collect();
+                    reader
+                        .read_samples_interleaved(*bits, &mut buffers)
+                        .unwrap();
+                }
+                assert_eq!(buffers, channels);
+                assert!(reader
+                    .read_samples_interleaved(*bits, &mut [&mut [0; 2], &mut [0; 1]])
+                    .is_err());
+            }
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            assert!(writer.write_samples_interleaved(0, &[&[0]]).is_err());
+            assert!(writer.write_samples_interleaved(33, &[&[0]]).is_err());
+            writer.write_samples_interleaved(16, &[]).unwrap();
+            assert!(writer.into_writer().is_empty());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_bit_planes_roundtrip() {
+    use bitstream_io::planes::BitPlanes;
+    use bitstream_io::BitCounter;
+    use std::io::ErrorKind;
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            for planes in [1, 3, 8].iter() {
+                let pixels: Vec<u8> = (0..7 * 5)
+                    .map(|i| ((i * 37 + 11) % (1 << planes)) as u8)
+                    .collect();
+                for layout in [
+                    BitPlanes::new(7, 5, *planes),
+                    BitPlanes::new(7, 5, *planes).row_interleaved(),
+                    BitPlanes::new(7, 5, *planes).row_alignment(16),
+                    BitPlanes::new(7, 5, *planes)
+                        .row_interleaved()
+                        .row_alignment(16),
+                ]
+                .iter()
+                {
+                    let mut counter: BitCounter<u64, $endianness> = BitCounter::new();
+                    layout.write_planes(&mut counter, &pixels).unwrap();
+                    assert_eq!(counter.written(), layout.total_bits());
+
+                    let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                    layout.write_planes(&mut writer, &pixels).unwrap();
+                    writer.byte_align().unwrap();
+                    let data = writer.into_writer();
+
+                    let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                    assert_eq!(layout.read_planes::<_, u8>(&mut reader).unwrap(), pixels);
+                    let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                    let wide: Vec<u32> = pixels.iter().map(|p| u32::from(*p)).collect();
+                    assert_eq!(layout.read_planes::<_, u32>(&mut reader).unwrap(), wide);
+                }
+            }
+
+            let layout = BitPlanes::new(2, 2, 2);
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            assert_eq!(
+                layout
+                    .write_planes(&mut writer, &[0u8, 1, 2])
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidInput
+            );
+            assert_eq!(
+                layout
+                    .write_planes(&mut writer, &[0u8, 1, 2, 4])
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidInput
+            );
+            assert_eq!(
+                BitPlanes::new(2, 2, 9)
+                    .write_planes(&mut writer, &[0u8; 4])
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidInput
+            );
+            assert!(writer.into_writer().is_empty());
+
+            let mut reader = BitReader::endian(Cursor::new([0xFF]), $endianness);
+            assert_eq!(
+                BitPlanes::new(2, 2, 9)
+                    .read_planes::<_, u8>(&mut reader)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidInput
+            );
+            let mut reader = BitReader::endian(Cursor::new([0xFF]), $endianness);
+            assert_eq!(
+                BitPlanes::new(3, 3, 1)
+                    .read_planes::<_, u8>(&mut reader)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::UnexpectedEof
+            );
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[cfg(feature = "codecs")]
+#[test]
+fn test_packbits_roundtrip() {
+    use bitstream_io::codecs::{PackBitsRead, PackBitsWrite};
+    use bitstream_io::{ByteRead, ByteReader, ByteWrite, ByteWriter};
+    use std::io::ErrorKind;
+
+    let mut inputs: Vec<Vec<u8>> = vec![
+        vec![],
+        vec![1],
+        vec![1, 1],
+        vec![1, 2, 2],
+        vec![0; 128],
+        vec![0; 129],
+        vec![0; 1000],
+        (0..=255).collect(),
+        (0..1000).map(|i| (i / 7) as u8).collect(),
+    ];
+    inputs.push(
+        (0..1000)
+            .map(|i: u32| if i % 300 < 150 { i as u8 } else { 0xAA })
+            .collect(),
+    );
+
+    for data in inputs.iter() {
+        let mut writer = ByteWriter::endian(Vec::new(), BigEndian);
+        writer.write_packbits(data).unwrap();
+        writer.write(0xFFu8).unwrap();
+        let packed = writer.into_writer();
+
+        // literal runs cost at most one header per 128 bytes
+        assert!(packed.len() <= data.len() + data.len() / 128 + 2);
+
+        let mut reader = ByteReader::endian(Cursor::new(&packed), BigEndian);
+        assert_eq!(&reader.read_packbits(data.len()).unwrap(), data);
+        assert_eq!(reader.read::<u8>().unwrap(), 0xFF);
+    }
+
+    // no-op headers are skipped
+    let mut reader = ByteReader::endian(Cursor::new([0x80, 0x80, 0x01, 1, 2]), BigEndian);
+    assert_eq!(reader.read_packbits(2).unwrap(), [1, 2]);
+
+    let mut reader = ByteReader::endian(Cursor::new([0x03, 1, 2]), BigEndian);
+    assert_eq!(
+        reader.read_packbits(4).unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+    let mut reader = ByteReader::endian(Cursor::new([0xFE, 1]), BigEndian);
+    assert_eq!(
+        reader.read_packbits(2).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn test_basen_roundtrip() {
+    use bitstream_io::basen::{DecodingReader, Encoding, EncodingWriter, FmtWriter};
+    use std::io::{ErrorKind, Read, Write};
+
+    // test vectors from RFC 4648
+    let vectors: [(Encoding, [&str; 7]); 4] = [
+        (
+            Encoding::Base64,
+            [
+                "", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy",
+            ],
+        ),
+        (
+            Encoding::Base64Url,
+            ["", "Zg", "Zm8", "Zm9v", "Zm9vYg", "Zm9vYmE", "Zm9vYmFy"],
+        ),
+        (
+            Encoding::Base32,
+            [
+                "",
+                "MY======",
+                "MZXQ====",
+                "MZXW6===",
+                "MZXW6YQ=",
+                "MZXW6YTB",
+                "MZXW6YTBOI======",
+            ],
+        ),
+        (
+            Encoding::Hex,
+            [
+                "",
+                "66",
+                "666f",
+                "666f6f",
+                "666f6f62",
+                "666f6f6261",
+                "666f6f626172",
+            ],
+        ),
+    ];
+    for (encoding, texts) in vectors.iter() {
+        for (len, text) in texts.iter().enumerate() {
+            let data = &b"foobar"[0..len];
+
+            let mut writer = EncodingWriter::new(Vec::new(), *encoding);
+            for byte in data.iter() {
+                writer.write_all(&[*byte]).unwrap();
+            }
+            assert_eq!(writer.finish().unwrap(), text.as_bytes());
+
+            let mut decoded = Vec::new();
+            DecodingReader::new(text.as_bytes(), *encoding)
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, data);
+        }
+    }
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            for encoding in [
+                Encoding::Base64,
+                Encoding::Base64Url,
+                Encoding::Base32,
+                Encoding::Hex,
+            ]
+            .iter()
+            {
+                let mut text = String::new();
+                let mut writer = BitWriter::endian(
+                    EncodingWriter::new(FmtWriter::new(&mut text), *encoding),
+                    $endianness,
+                );
+                for i in 0..100u32 {
+                    writer.write(i % 17 + 1, i % (1 << (i % 17))).unwrap();
+                }
+                writer.byte_align().unwrap();
+                writer.into_writer().finish().unwrap();
+
+                // the text survives being wrapped across lines
+                let wrapped: String = text
+                    .as_bytes()
+                    .chunks(10)
+                    .map(|line| format!("{}\r\n", std::str::from_utf8(line).unwrap()))
+                    .collect();
+                let mut reader = BitReader::endian(
+                    DecodingReader::new(wrapped.as_bytes(), *encoding),
+                    $endianness,
+                );
+                for i in 0..100u32 {
+                    assert_eq!(reader.read::<u32>(i % 17 + 1).unwrap(), i % (1 << (i % 17)));
+                }
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+
+    // bytes before an invalid character are returned first
+    let mut reader = DecodingReader::new(&b"Zm9v!Zm9v"[..], Encoding::Base64);
+    let mut buf = [0; 6];
+    assert_eq!(reader.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[0..3], b"foo");
+    assert_eq!(
+        reader.read(&mut buf).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(
+        reader.read(&mut buf).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+
+    // nothing is read past padding
+    let mut reader = DecodingReader::new(&b"Zg==Zm8="[..], Encoding::Base64);
+    let mut decoded = Vec::new();
+    reader.read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded, b"f");
+    assert_eq!(reader.into_reader(), b"=Zm8=");
+
+    // either case is accepted for hex and base32
+    let mut decoded = Vec::new();
+    DecodingReader::new(&b"666F6f"[..], Encoding::Hex)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    DecodingReader::new(&b"mzxw6==="[..], Encoding::Base32)
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, b"foofoo");
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi_roundtrip() {
+    use bitstream_io::ffi::*;
+    use std::ptr;
+
+    for endian in [BITSTREAM_BIG_ENDIAN, BITSTREAM_LITTLE_ENDIAN].iter() {
+        unsafe {
+            let writer = bitstream_writer_new(*endian);
+            assert!(!writer.is_null());
+            for i in 0..64u32 {
+                let bits = i + 1;
+                let value = u64::MAX >> (63 - i) >> 1;
+                assert_eq!(bitstream_writer_write(writer, bits, value), BITSTREAM_OK);
+                let signed = i64::MIN >> (63 - i);
+                assert_eq!(
+                    bitstream_writer_write_signed(writer, bits, signed),
+                    BITSTREAM_OK
+                );
+            }
+            assert_eq!(bitstream_writer_byte_aligned(writer), 1);
+            assert_eq!(bitstream_writer_write(writer, 3, 0b101), BITSTREAM_OK);
+            assert_eq!(bitstream_writer_byte_aligned(writer), 0);
+            assert_eq!(bitstream_writer_byte_align(writer), BITSTREAM_OK);
+            assert_eq!(bitstream_writer_byte_aligned(writer), 1);
+            assert_eq!(
+                bitstream_writer_write_bytes(writer, b"abc".as_ptr(), 3),
+                BITSTREAM_OK
+            );
+
+            // out of range values write nothing
+            assert_eq!(
+                bitstream_writer_write(writer, 65, 0),
+                BITSTREAM_ERR_INVALID_INPUT
+            );
+            assert_eq!(
+                bitstream_writer_write_signed(writer, 8, 128),
+                BITSTREAM_ERR_INVALID_INPUT
+            );
+            assert_eq!(
+                bitstream_writer_write_signed(writer, 8, -129),
+                BITSTREAM_ERR_INVALID_INPUT
+            );
+            assert_eq!(
+                bitstream_writer_write_signed(writer, 0, 0),
+                BITSTREAM_ERR_INVALID_INPUT
+            );
+
+            // bytes taken out partway keep any partial byte
+            assert_eq!(bitstream_writer_write(writer, 4, 0b1001), BITSTREAM_OK);
+            let mut len = 0;
+            let data = bitstream_writer_data(writer, &mut len);
+            let mut output = std::slice::from_raw_parts(data, len).to_vec();
+            assert_eq!(bitstream_writer_clear(writer), BITSTREAM_OK);
+            assert_eq!(bitstream_writer_write(writer, 4, 0b0110), BITSTREAM_OK);
+            let data = bitstream_writer_data(writer, &mut len);
+            assert_eq!(len, 1);
+            output.extend_from_slice(std::slice::from_raw_parts(data, len));
+            bitstream_writer_free(writer);
+
+            let reader = bitstream_reader_new(*endian, output.as_ptr(), output.len());
+            drop(output);
+            for i in 0..64u32 {
+                let bits = i + 1;
+                let mut value = 0;
+                assert_eq!(
+                    bitstream_reader_read(reader, bits, &mut value),
+                    BITSTREAM_OK
+                );
+                assert_eq!(value, u64::MAX >> (63 - i) >> 1);
+                let mut signed = 0;
+                assert_eq!(
+                    bitstream_reader_read_signed(reader, bits, &mut signed),
+                    BITSTREAM_OK
+                );
+                assert_eq!(signed, i64::MIN >> (63 - i));
+            }
+            assert_eq!(bitstream_reader_byte_aligned(reader), 1);
+            let mut value = 0;
+            assert_eq!(bitstream_reader_read(reader, 3, &mut value), BITSTREAM_OK);
+            assert_eq!(value, 0b101);
+            assert_eq!(bitstream_reader_byte_aligned(reader), 0);
+            assert_eq!(bitstream_reader_byte_align(reader), BITSTREAM_OK);
+            assert_eq!(bitstream_reader_byte_aligned(reader), 1);
+            let mut bytes = [0; 3];
+            assert_eq!(
+                bitstream_reader_read_bytes(reader, bytes.as_mut_ptr(), 3),
+                BITSTREAM_OK
+            );
+            assert_eq!(&bytes, b"abc");
+            let mut value = 0;
+            assert_eq!(bitstream_reader_skip(reader, 4), BITSTREAM_OK);
+            assert_eq!(bitstream_reader_read(reader, 4, &mut value), BITSTREAM_OK);
+            assert_eq!(value, 0b0110);
+            assert_eq!(
+                bitstream_reader_read(reader, 1, &mut value),
+                BITSTREAM_ERR_EOF
+            );
+            assert_eq!(
+                bitstream_reader_read_signed(reader, 65, &mut 0),
+                BITSTREAM_ERR_INVALID_INPUT
+            );
+            bitstream_reader_free(reader);
+        }
+    }
+
+    unsafe {
+        assert!(bitstream_writer_new(2).is_null());
+        assert!(bitstream_reader_new(2, ptr::null(), 0).is_null());
+        assert!(bitstream_reader_new(BITSTREAM_BIG_ENDIAN, ptr::null(), 1).is_null());
+        assert_eq!(
+            bitstream_writer_write(ptr::null_mut(), 1, 0),
+            BITSTREAM_ERR_NULL
+        );
+        assert_eq!(
+            bitstream_reader_read(ptr::null_mut(), 1, &mut 0),
+            BITSTREAM_ERR_NULL
+        );
+        assert!(bitstream_writer_data(ptr::null(), &mut 0).is_null());
+        bitstream_writer_free(ptr::null_mut());
+        bitstream_reader_free(ptr::null_mut());
+
+        let reader = bitstream_reader_new(BITSTREAM_LITTLE_ENDIAN, ptr::null(), 0);
+        assert_eq!(
+            bitstream_reader_read(reader, 1, ptr::null_mut()),
+            BITSTREAM_ERR_NULL
+        );
+        assert_eq!(
+            bitstream_reader_read_bytes(reader, ptr::null_mut(), 0),
+            BITSTREAM_OK
+        );
+        bitstream_reader_free(reader);
+    }
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn test_python_roundtrip() {
+    use pyo3::prelude::*;
+    use pyo3::types::{PyDict, PyModule};
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let module = PyModule::new_bound(py, "bitstream_io").unwrap();
+        bitstream_io::python::bitstream_io(&module).unwrap();
+        py.import_bound("sys")
+            .and_then(|sys| sys.getattr("modules"))
+            .and_then(|modules| modules.set_item("bitstream_io", module))
+            .unwrap();
+        let globals = PyDict::new_bound(py);
+        py.run_bound(
+            r#"
+from bitstream_io import BitReader, BitWriter
+
+for endianness in ["big", "little"]:
+    writer = BitWriter(endianness)
+    for i in range(64):
+        writer.write(i + 1, (1 << i) | 1)
+        writer.write_signed(i + 1, -(1 << i))
+    writer.write_bit(True)
+    assert not writer.byte_aligned()
+    writer.byte_align()
+    assert writer.byte_aligned()
+    writer.write_bytes(b"abc")
+    writer.write(4, 0b1001)
+    data = writer.getvalue()
+
+    for bits, value in [(65, 0), (3, 8), (3, -1)]:
+        try:
+            writer.write(bits, value)
+        except (ValueError, OverflowError):
+            pass
+        else:
+            raise AssertionError("out of range value written")
+    for bits, value in [(0, 0), (65, 0), (8, 128), (8, -129)]:
+        try:
+            writer.write_signed(bits, value)
+        except (ValueError, OverflowError):
+            pass
+        else:
+            raise AssertionError("out of range signed value written")
+    assert writer.getvalue() == data
+
+    reader = BitReader(data, endianness)
+    for i in range(64):
+        assert reader.read(i + 1) == (1 << i) | 1
+        assert reader.read_signed(i + 1) == -(1 << i)
+    assert reader.read_bit()
+    assert reader.position() == 64 * 65 + 1
+    reader.byte_align()
+    assert reader.byte_aligned()
+    assert reader.read_bytes(3) == b"abc"
+    try:
+        reader.read(1)
+    except EOFError:
+        pass
+    else:
+        raise AssertionError("read past end of data")
+
+assert BitWriter().getvalue() == b""
+assert BitReader(b"\x80").read_bit()
+assert not BitReader(b"\x80", "little").read_bit()
+try:
+    BitWriter("middle")
+except ValueError:
+    pass
+else:
+    raise AssertionError("unknown endianness accepted")
+"#,
+            Some(&globals),
+            None,
+        )
+        .unwrap();
+    });
+}
+
+#[test]
+fn test_bit_order_roundtrip() {
+    use bitstream_io::bitorder::{convert_bit_order, convert_bit_order_in_place};
+
+    let mut state = 1u32;
+    let data: Vec<u8> = (0..10_000)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+
+    // every length and offset exercises each block, word and byte path
+    for start in 0..20 {
+        for end in (start..start + 100).chain(Some(data.len())) {
+            let original = &data[start..end];
+            let converted = convert_bit_order(original);
+            let expected: Vec<u8> = original.iter().map(|b| b.reverse_bits()).collect();
+            assert_eq!(converted, expected);
+
+            let mut restored = converted.clone();
+            convert_bit_order_in_place(&mut restored);
+            assert_eq!(restored, original);
+        }
+    }
+
+    // the same bits in the opposite bit order
+    let converted = convert_bit_order(&data);
+    let mut msb_first = BitReader::endian(Cursor::new(&data), BigEndian);
+    let mut lsb_first = BitReader::endian(Cursor::new(&converted), LittleEndian);
+    for _ in 0..data.len() * 8 {
+        assert_eq!(msb_first.read_bit().unwrap(), lsb_first.read_bit().unwrap());
+    }
+}
+
+#[test]
+fn test_symbol_recorder_roundtrip() {
+    use bitstream_io::huffman::{compile_read_tree, compile_write_tree};
+    use bitstream_io::{HuffmanRead, SymbolRecorder};
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let text = "this is some sample text with mostly common letters";
+            let mut recorder: SymbolRecorder<$endianness, char> = SymbolRecorder::new();
+            recorder.write(5, 0b10110u8).unwrap();
+            assert!(!recorder.byte_aligned());
+            recorder.byte_align().unwrap();
+            assert!(recorder.byte_aligned());
+            recorder.write_bytes(b"hdr").unwrap();
+            for (i, c) in text.chars().enumerate() {
+                recorder.write_symbol(c);
+                assert!(!recorder.byte_aligned());
+                if i % 10 == 0 {
+                    recorder.write_signed(7, -(i as i8)).unwrap();
+                }
+            }
+            recorder.byte_align().unwrap();
+            assert!(recorder.byte_aligned());
+            recorder.write_bit(true).unwrap();
+            assert_eq!(recorder.symbols().count(), text.len());
+
+            // codes suited to the text
+            // and the same codes assigned in alphabetical order
+            let unary = |symbols: &[char]| -> Vec<(char, Vec<u8>)> {
+                symbols
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        let mut code = vec![1; i];
+                        if i + 1 < symbols.len() {
+                            code.push(0);
+                        }
+                        (*c, code)
+                    })
+                    .collect()
+            };
+            let mut symbols: Vec<char> = recorder.symbols().copied().collect();
+            symbols.sort_unstable();
+            symbols.dedup();
+            let alphabetical = unary(&symbols);
+            symbols.sort_by_key(|c| std::cmp::Reverse(text.matches(*c).count()));
+            let suited = unary(&symbols);
+
+            for codes in [suited, alphabetical] {
+                let write_tree = compile_write_tree::<$endianness, char>(codes.clone()).unwrap();
+                let read_tree = compile_read_tree::<$endianness, char>(codes).unwrap();
+
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                recorder.playback(&mut writer, &write_tree).unwrap();
+                writer.byte_align().unwrap();
+                let data = writer.into_writer();
+                let bits = recorder.written_with(&write_tree).unwrap();
+                assert_eq!((bits + 7) / 8, data.len() as u64);
+
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                assert_eq!(reader.read::<u8>(5).unwrap(), 0b10110);
+                reader.byte_align();
+                assert_eq!(reader.read_to_bytes::<3>().unwrap(), *b"hdr");
+                for (i, c) in text.chars().enumerate() {
+                    assert_eq!(reader.read_huffman(&read_tree).unwrap(), c);
+                    if i % 10 == 0 {
+                        assert_eq!(reader.read_signed::<i8>(7).unwrap(), -(i as i8));
+                    }
+                }
+                reader.byte_align();
+                assert!(reader.read_bit().unwrap());
+            }
+
+            // a tree missing recorded symbols writes nothing
+            let partial =
+                compile_write_tree::<$endianness, char>(vec![('t', vec![0]), ('h', vec![1])])
+                    .unwrap();
+            assert_eq!(recorder.written_with(&partial), None);
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            assert!(recorder.playback(&mut writer, &partial).is_err());
+            assert!(writer.into_writer().is_empty());
+
+            recorder.clear();
+            assert!(recorder.byte_aligned());
+            assert_eq!(recorder.symbols().count(), 0);
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_frequency_counter_roundtrip() {
+    use bitstream_io::huffman::{compile_read_tree, compile_write_tree, FrequencyCounter};
+    use bitstream_io::{HuffmanRead, SymbolRecorder};
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut state = 1u32;
+            let symbols: Vec<u16> = (0..5000)
+                .map(|_| {
+                    state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                    // skewed toward small symbols
+                    ((state >> 16) % 300).min((state >> 8) % 300) as u16
+                })
+                .collect();
+
+            let mut recorder: SymbolRecorder<$endianness, u16> = SymbolRecorder::new();
+            recorder.write(12, 0xABCu16).unwrap();
+            for symbol in &symbols {
+                recorder.write_symbol(*symbol);
+            }
+            recorder.write_bit(true).unwrap();
+
+            // first pass gathers statistics
+            let counter: FrequencyCounter<u16> = recorder.symbols().copied().collect();
+            assert_eq!(counter.total(), symbols.len() as u64);
+            assert_eq!(counter.len(), {
+                let mut distinct = symbols.clone();
+                distinct.sort_unstable();
+                distinct.dedup();
+                distinct.len()
+            });
+            let codes = counter.huffman_codes();
+            assert_eq!(codes.len(), counter.len());
+
+            // codes never get shorter as symbols get rarer
+            for pair in codes.windows(2) {
+                assert!(pair[0].1.len() <= pair[1].1.len());
+            }
+            let mut by_frequency: Vec<_> = codes
+                .iter()
+                .map(|(symbol, code)| (counter.frequency(symbol), code.len()))
+                .collect();
+            by_frequency.sort_unstable_by_key(|(count, len)| (std::cmp::Reverse(*count), *len));
+            for pair in by_frequency.windows(2) {
+                assert!(pair[0].1 <= pair[1].1);
+            }
+
+            // second pass writes with the codes built
+            let write_tree = compile_write_tree::<$endianness, u16>(codes.clone()).unwrap();
+            write_tree.verify_complete().unwrap();
+            let read_tree = compile_read_tree::<$endianness, u16>(codes).unwrap();
+            let bits = recorder.written_with(&write_tree).unwrap();
+            let fixed_bits = 12 + 9 * symbols.len() as u64 + 1;
+            assert!(bits < fixed_bits);
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            recorder.playback(&mut writer, &write_tree).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+            assert_eq!(data.len() as u64, (bits + 7) / 8);
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read::<u16>(12).unwrap(), 0xABC);
+            for symbol in &symbols {
+                assert_eq!(reader.read_huffman(&read_tree).unwrap(), *symbol);
+            }
+            assert!(reader.read_bit().unwrap());
+
+            // a lone symbol needs no bits at all
+            let counter: FrequencyCounter<u16> = std::iter::repeat(7).take(10).collect();
+            let codes = counter.huffman_codes();
+            assert_eq!(codes, [(7, vec![])]);
+            let read_tree = compile_read_tree::<$endianness, u16>(codes).unwrap();
+            let mut reader = BitReader::endian(Cursor::new(&[]), $endianness);
+            assert_eq!(reader.read_huffman(&read_tree).unwrap(), 7);
+
+            let mut counter = FrequencyCounter::<u16>::default();
+            assert!(counter.huffman_codes().is_empty());
+            counter.extend([1, 2, 2]);
+            assert_eq!(counter.huffman_codes(), [(1, vec![0]), (2, vec![1])]);
+            counter.clear();
+            assert!(counter.is_empty());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_nibble_roundtrip() {
+    use bitstream_io::{ByteRead, ByteReader, ByteWriter, NibbleWriter};
+
+    let nibbles: Vec<u8> = (0..37).map(|i| (i * 7 % 16) as u8).collect();
+
+    let mut writer = NibbleWriter::new(ByteWriter::endian(Vec::new(), BigEndian));
+    let (first, rest) = nibbles.split_at(1);
+    writer.write_nibble(first[0]).unwrap();
+    for pair in rest.chunks(2) {
+        match pair {
+            [high, low] => writer.write_nibbles(*high, *low).unwrap(),
+            [nibble] => writer.write_nibble(*nibble).unwrap(),
+            _ => unreachable!(),
+        }
+    }
+    assert!(writer.write_nibble(16).is_err());
+    assert!(writer.write_nibbles(0, 16).is_err());
+    writer.byte_align().unwrap();
+    let data = writer.into_writer().into_writer();
+    assert_eq!(data.len(), 19);
+
+    let mut reader = ByteReader::endian(Cursor::new(&data), LittleEndian);
+    let mut read = Vec::new();
+    for _ in 0..data.len() {
+        let (high, low) = reader.read_nibbles().unwrap();
+        read.push(high);
+        read.push(low);
+    }
+    assert_eq!(read[0..nibbles.len()], nibbles[..]);
+    assert_eq!(read[nibbles.len()], 0);
+
+    // nibbles are in the same order as 4-bit big-endian fields
+    let mut reader = BitReader::endian(Cursor::new(&data), BigEndian);
+    for nibble in nibbles.iter() {
+        assert_eq!(reader.read::<u8>(4).unwrap(), *nibble);
+    }
+}
+
+#[test]
+fn test_byte_stream_roundtrip() {
+    use bitstream_io::{ByteRead, ByteReader, ByteWrite, ByteWriter, FromByteStream, ToByteStream};
+    use std::io;
+
+    #[derive(Debug, PartialEq)]
+    struct WaveFormat {
+        format: u16,
+        channels: u16,
+        sample_rate: u32,
+        byte_rate: u32,
+        block_align: u16,
+        bits_per_sample: u16,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct FormatChunk {
+        id: [u8; 4],
+        format: WaveFormat,
+    }
+
+    impl ToByteStream for WaveFormat {
+        type Error = io::Error;
+
+        fn to_writer<W: ByteWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+            w.write(self.format)?;
+            w.write(self.channels)?;
+            w.write(self.sample_rate)?;
+            w.write(self.byte_rate)?;
+            w.write(self.block_align)?;
+            w.write(self.bits_per_sample)
+        }
+    }
+
+    impl FromByteStream for WaveFormat {
+        type Error = io::Error;
+
+        fn from_reader<R: ByteRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+            Ok(WaveFormat {
+                format: r.read()?,
+                channels: r.read()?,
+                sample_rate: r.read()?,
+                byte_rate: r.read()?,
+                block_align: r.read()?,
+                bits_per_sample: r.read()?,
+            })
+        }
+    }
+
+    // nested structures build on each other
+    impl ToByteStream for FormatChunk {
+        type Error = io::Error;
+
+        fn to_writer<W: ByteWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+            w.write_bytes(&self.id)?;
+            w.write(16u32)?;
+            w.build(&self.format)
+        }
+    }
+
+    impl FromByteStream for FormatChunk {
+        type Error = io::Error;
+
+        fn from_reader<R: ByteRead + ?Sized>(r: &mut R) -> io::Result<Self> {
+            let mut id = [0; 4];
+            r.read_bytes(&mut id)?;
+            if r.read::<u32>()? != 16 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid chunk size",
+                ));
+            }
+            Ok(FormatChunk {
+                id,
+                format: r.parse()?,
+            })
+        }
+    }
+
+    let chunk = FormatChunk {
+        id: *b"fmt ",
+        format: WaveFormat {
+            format: 1,
+            channels: 2,
+            sample_rate: 44100,
+            byte_rate: 176400,
+            block_align: 4,
+            bits_per_sample: 16,
+        },
+    };
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut writer = ByteWriter::endian(Vec::new(), $endianness);
+            writer.build(&chunk).unwrap();
+            let data = writer.into_writer();
+            assert_eq!(data.len(), 24);
+            assert_eq!(&data[0..4], b"fmt ");
+
+            let mut reader = ByteReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.parse::<FormatChunk>().unwrap(), chunk);
+
+            let mut reader = ByteReader::endian(Cursor::new(&data[..20]), $endianness);
+            assert!(reader.parse::<FormatChunk>().is_err());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_bit_stream_build() {
+    use bitstream_io::{BitWrite, ToBitStream};
+    use std::io;
+
+    struct Sample {
+        flag: bool,
+        delta: i16,
+        run: u32,
+    }
+
+    struct Block {
+        samples: Vec<Sample>,
+    }
+
+    impl ToBitStream for Sample {
+        type Error = io::Error;
+
+        fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+            w.write_bit(self.flag)?;
+            w.write_signed(11, self.delta)?;
+            w.write_unary0(self.run)
+        }
+    }
+
+    // nested structures build on each other
+    impl ToBitStream for Block {
+        type Error = io::Error;
+
+        fn to_writer<W: BitWrite + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+            w.write(5, self.samples.len() as u8)?;
+            for sample in self.samples.iter() {
+                w.build(sample)?;
+            }
+            Ok(())
+        }
+    }
+
+    let block = Block {
+        samples: (0..20)
+            .map(|i| Sample {
+                flag: i % 3 == 0,
+                delta: (i * 97 % 2000) - 1000,
+                run: (i * 7 % 5) as u32,
+            })
+            .collect(),
+    };
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            writer.build(&block).unwrap();
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            // lengths match what's actually written
+            assert_eq!(block.bytes_len().unwrap(), data.len() as u64);
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(reader.read::<u8>(5).unwrap(), 20);
+            for sample in block.samples.iter() {
+                assert_eq!(reader.read_bit().unwrap(), sample.flag);
+                assert_eq!(reader.read_signed::<i16>(11).unwrap(), sample.delta);
+                assert_eq!(reader.read_unary0().unwrap(), sample.run);
+            }
+            assert_eq!(
+                reader.position_in_bits().unwrap(),
+                block.bits_len().unwrap()
+            );
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+
+    // errors from building are passed along
+    let block = Block {
+        samples: vec![Sample {
+            flag: false,
+            delta: 1024,
+            run: 0,
+        }],
+    };
+    assert!(block.bits_len().is_err());
+    assert!(block.bytes_len().is_err());
+}
+
+macro_rules! define_debug_writer_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::DebugBitWriter;
+
+            fn encode<W: BitWrite>(w: &mut W) {
+                w.write_bit(true).unwrap();
+                w.write(13, 0x1234u16).unwrap();
+                w.write_signed(9, -200i16).unwrap();
+                w.write_unary0(4).unwrap();
+                w.write_unary1(2).unwrap();
+                w.write_bcd(3, 907).unwrap();
+                w.byte_align().unwrap();
+                w.write_bytes(b"debug").unwrap();
+                w.write(128, u128::MAX).unwrap();
+            }
+
+            let mut plain = BitWriter::endian(Vec::new(), $endianness);
+            encode(&mut plain);
+
+            let mut lines: Vec<String> = Vec::new();
+            let mut writer =
+                DebugBitWriter::with_callback(BitWriter::endian(Vec::new(), $endianness), |line| {
+                    lines.push(line.to_owned())
+                });
+            encode(&mut writer);
+            assert_eq!(writer.position(), 48 + 40 + 128);
+            assert_eq!(writer.into_writer().into_writer(), plain.into_writer());
+
+            // each line starts at the bit where the previous one left off
+            assert_eq!(
+                lines
+                    .iter()
+                    .map(|line| line.split(':').next().unwrap().parse::<u64>().unwrap())
+                    .collect::<Vec<_>>(),
+                [0, 1, 14, 23, 28, 31, 35, 39, 43, 48, 88]
+            );
+            assert_eq!(lines[2], "14: write_signed 9 bits 0b100111000 (-200)");
+            assert_eq!(lines[9], "48: write_bytes 5 bytes [64 65 62 75 67]");
+            assert_eq!(
+                lines[10],
+                format!("88: write 128 bits 0b{} ({})", "1".repeat(128), u128::MAX)
+            );
+        }
+    };
+}
+
+define_debug_writer_roundtrip!(test_debug_writer_roundtrip_be, BigEndian);
+define_debug_writer_roundtrip!(test_debug_writer_roundtrip_le, LittleEndian);
+
+macro_rules! define_byte_position_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::{ByteRead, ByteReader, ByteWrite, ByteWriter, Utf8Policy};
+            use std::io::SeekFrom;
+
+            // chunks are written with placeholder sizes
+            // which are filled in once each chunk is done
+            let mut writer = ByteWriter::endian(Cursor::new(Vec::new()), $endianness);
+            let mut offsets = Vec::new();
+            for chunk in 0..5u8 {
+                let start = writer.position();
+                writer.write_bytes(b"chnk").unwrap();
+                writer.write(0u32).unwrap();
+                writer.write_cstr("name").unwrap();
+                writer.write_prefixed_str(2, "\u{263A}").unwrap();
+                writer.write_float(f64::from(chunk) / 3.0).unwrap();
+                writer.write_bytes(&vec![chunk; chunk.into()]).unwrap();
+                let end = writer.position();
+                writer.seek(SeekFrom::Start(start + 4)).unwrap();
+                writer.write((end - start - 8) as u32).unwrap();
+                assert_eq!(writer.seek(SeekFrom::End(0)).unwrap(), end);
+                offsets.push((start, end));
+            }
+            let data = writer.into_writer().into_inner();
+            assert_eq!(data.len() as u64, offsets.last().unwrap().1);
+
+            let mut reader = ByteReader::endian(Cursor::new(&data), $endianness);
+            for (chunk, (start, end)) in offsets.iter().enumerate().rev() {
+                assert_eq!(reader.seek(SeekFrom::Start(*start)).unwrap(), *start);
+                let mut id = [0; 4];
+                reader.read_bytes(&mut id).unwrap();
+                assert_eq!(&id, b"chnk");
+                let size = reader.read::<u32>().unwrap();
+                assert_eq!(u64::from(size), end - start - 8);
+                assert_eq!(reader.read_cstr(16, Utf8Policy::Strict).unwrap(), "name");
+                assert_eq!(
+                    reader.read_prefixed_str(2, 16, Utf8Policy::Strict).unwrap(),
+                    "\u{263A}"
+                );
+                assert_eq!(reader.read_float::<f64>().unwrap(), chunk as f64 / 3.0);
+                let mut payload = vec![0; chunk];
+                reader.read_bytes(&mut payload).unwrap();
+                assert_eq!(reader.position(), *end);
+            }
+            let len = reader.seek(SeekFrom::End(0)).unwrap();
+            assert_eq!(reader.position(), len);
+            assert!(reader.read::<u8>().is_err());
+        }
+    };
+}
+
+define_byte_position_roundtrip!(test_byte_position_roundtrip_be, BigEndian);
+define_byte_position_roundtrip!(test_byte_position_roundtrip_le, LittleEndian);
+
+macro_rules! define_hashed_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::checksum::{Checksum, Crc32, HashedBitWriter};
+
+            let mut writer = HashedBitWriter::endian(
+                BitWriter::endian(Vec::new(), $endianness),
+                $endianness,
+                Crc32::new(),
+            );
+            let mut total_bits = 0;
+            for i in 0..200u32 {
+                let bits = i % 17;
+                match i % 4 {
+                    0 => writer
+                        .write(bits, i.wrapping_mul(2654435761) & ((1 << bits) - 1))
+                        .unwrap(),
+                    1 => writer.write_signed(bits + 3, (i as i32 % 7) - 3).unwrap(),
+                    2 => writer
+                        .write_bytes(&i.to_be_bytes()[0..(bits / 4) as usize])
+                        .unwrap(),
+                    _ => writer.write_bit(i % 3 == 0).unwrap(),
+                }
+                total_bits += match i % 4 {
+                    0 => bits,
+                    1 => bits + 3,
+                    2 => bits / 4 * 8,
+                    _ => 1,
+                };
+
+                // the checksum of everything written so far,
+                // padded to a whole byte, plus the partial byte's length
+                let mut aligned = writer.writer().clone();
+                aligned.byte_align().unwrap();
+                let mut expected = aligned.into_writer();
+                if total_bits % 8 != 0 {
+                    expected.push((total_bits % 8) as u8);
+                }
+                let mut crc = Crc32::new();
+                crc.update(&expected);
+                assert_eq!(writer.hash(), crc.checksum());
+            }
+
+            // bits are hashed from the last reset,
+            // whether or not the stream is aligned
+            assert!(!writer.byte_aligned());
+            writer.reset();
+            writer.write_bytes(&[0x12, 0x34]).unwrap();
+            let mut crc = Crc32::new();
+            crc.update(&[0x12, 0x34]);
+            assert_eq!(writer.hash(), crc.checksum());
+        }
+    };
+}
+
+define_hashed_roundtrip!(test_hashed_roundtrip_be, BigEndian);
+define_hashed_roundtrip!(test_hashed_roundtrip_le, LittleEndian);
+
+macro_rules! define_chunk_roundtrip {
+    ($func_name:ident, $endianness:ident, $layout:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::chunk::{ChunkLayout, ChunkWriter};
+            use bitstream_io::{ByteRead, ByteReader, ByteWrite, ByteWriter};
+
+            fn read_chunk<R: std::io::Read>(
+                reader: &mut ByteReader<R, $endianness>,
+            ) -> ([u8; 4], u32) {
+                let mut id = [0; 4];
+                match ChunkLayout::$layout {
+                    ChunkLayout::Riff => {
+                        reader.read_bytes(&mut id).unwrap();
+                        (id, reader.read::<u32>().unwrap())
+                    }
+                    ChunkLayout::Box => {
+                        let length = reader.read::<u32>().unwrap();
+                        reader.read_bytes(&mut id).unwrap();
+                        (id, length - 8)
+                    }
+                }
+            }
+
+            let pad = |length: u32| match ChunkLayout::$layout {
+                ChunkLayout::Riff => length % 2,
+                ChunkLayout::Box => 0,
+            };
+
+            // an outer chunk containing nested chunks of every length 0 to 9
+            let writer = ByteWriter::endian(Cursor::new(Vec::new()), $endianness);
+            let mut writer = ChunkWriter::new(writer, ChunkLayout::$layout);
+            writer.begin_chunk(b"list").unwrap();
+            for length in 0..10u8 {
+                writer
+                    .begin_chunk(&[b'c', b'0' + length, b'_', b'_'])
+                    .unwrap();
+                for byte in 0..length {
+                    writer.write(byte).unwrap();
+                }
+                writer.end_chunk().unwrap();
+            }
+            assert_eq!(writer.depth(), 1);
+            writer.begin_chunk(b"last").unwrap();
+            writer.begin_chunk(b"deep").unwrap();
+            writer.write(0x1234_5678u32).unwrap();
+            let writer = writer.finish().unwrap();
+            let end = writer.position();
+            let data = writer.into_writer().into_inner();
+            assert_eq!(data.len() as u64, end);
+
+            let mut reader = ByteReader::endian(data.as_slice(), $endianness);
+            let (id, mut remaining) = read_chunk(&mut reader);
+            assert_eq!(&id, b"list");
+            assert_eq!(u64::from(remaining) + 8, end);
+            for length in 0..10u8 {
+                let (id, chunk_length) = read_chunk(&mut reader);
+                assert_eq!(id, [b'c', b'0' + length, b'_', b'_']);
+                assert_eq!(chunk_length, u32::from(length));
+                for byte in 0..length {
+                    assert_eq!(reader.read::<u8>().unwrap(), byte);
+                }
+                if pad(chunk_length) == 1 {
+                    assert_eq!(reader.read::<u8>().unwrap(), 0);
+                }
+                remaining -= 8 + chunk_length + pad(chunk_length);
+            }
+            assert_eq!(read_chunk(&mut reader), (*b"last", 12));
+            assert_eq!(read_chunk(&mut reader), (*b"deep", 4));
+            assert_eq!(reader.read::<u32>().unwrap(), 0x1234_5678);
+            assert_eq!(remaining, 20);
+            assert_eq!(reader.position(), end);
+        }
+    };
+}
+
+define_chunk_roundtrip!(test_chunk_roundtrip_riff, LittleEndian, Riff);
+define_chunk_roundtrip!(test_chunk_roundtrip_box, BigEndian, Box);
+
+#[test]
+fn test_chunk_writer_errors() {
+    use bitstream_io::chunk::{ChunkLayout, ChunkWriter};
+    use bitstream_io::{ByteWrite, ByteWriter};
+
+    let writer = ByteWriter::endian(Cursor::new(Vec::new()), BigEndian);
+    let mut writer = ChunkWriter::new(writer, ChunkLayout::Box);
+    assert_eq!(
+        writer.end_chunk().unwrap_err().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+    writer.begin_chunk(b"free").unwrap();
+    writer.write(0u8).unwrap();
+    writer.end_chunk().unwrap();
+    assert_eq!(writer.depth(), 0);
+    assert!(writer.end_chunk().is_err());
+    assert_eq!(
+        writer.into_writer().into_writer().into_inner(),
+        b"\x00\x00\x00\x09free\x00"
+    );
+}
+
+#[test]
+fn test_chunk_writer_offset() {
+    use bitstream_io::chunk::{ChunkLayout, ChunkWriter};
+    use bitstream_io::{ByteWrite, ByteWriter};
+    use std::io::Write;
+
+    // chunks written after some bytes already in the stream
+    let mut cursor = Cursor::new(Vec::new());
+    cursor.write_all(b"header").unwrap();
+    let writer = ByteWriter::endian(cursor, LittleEndian);
+    let mut writer = ChunkWriter::new(writer, ChunkLayout::Riff);
+    writer.begin_chunk(b"LIST").unwrap();
+    writer.begin_chunk(b"odd_").unwrap();
+    writer.write_bytes(b"abc").unwrap();
+    writer.end_chunk().unwrap();
+    writer.write(1u16).unwrap();
+    let writer = writer.finish().unwrap();
+    assert_eq!(writer.position(), 22);
+    assert_eq!(
+        writer.into_writer().into_inner(),
+        b"headerLIST\x0E\x00\x00\x00odd_\x03\x00\x00\x00abc\x00\x01\x00"
+    );
+}
+
+macro_rules! define_unary_u64_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::{BitCounter, BufBitWriter, LimitedBitWriter};
+
+            let values: Vec<u32> = vec![0, 1, 31, 32, 33, 63, 64, 65, 100, 4096 * 8 + 3, 100_000];
+            let total: u64 = values.iter().map(|v| u64::from(*v) + 1).sum::<u64>() * 4;
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            let mut buffered = BufBitWriter::endian(Vec::new(), $endianness);
+            let mut counter: BitCounter<u64, $endianness> = BitCounter::new();
+            for value in values.iter() {
+                writer.write_unary0(*value).unwrap();
+                writer.write_unary1(*value).unwrap();
+                assert_eq!(
+                    writer.write_unary0_u64(u64::from(*value)).unwrap(),
+                    u64::from(*value) + 1
+                );
+                assert_eq!(
+                    writer.write_unary1_u64(u64::from(*value)).unwrap(),
+                    u64::from(*value) + 1
+                );
+                buffered.write_unary0_u64(u64::from(*value)).unwrap();
+                buffered.write_unary1_u64(u64::from(*value)).unwrap();
+                counter.write_unary0_u64(u64::from(*value)).unwrap();
+                counter.write_unary1_u64(u64::from(*value)).unwrap();
+            }
+            assert_eq!(counter.written() * 2, total);
+            writer.byte_align().unwrap();
+            buffered.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for value in values.iter() {
+                assert_eq!(reader.read_unary0().unwrap(), *value);
+                assert_eq!(reader.read_unary1().unwrap(), *value);
+                assert_eq!(reader.read_unary0().unwrap(), *value);
+                assert_eq!(reader.read_unary1().unwrap(), *value);
+            }
+
+            let buffered = buffered.into_writer().unwrap();
+            let mut reader = BitReader::endian(Cursor::new(&buffered), $endianness);
+            for value in values.iter() {
+                assert_eq!(reader.read_unary0().unwrap(), *value);
+                assert_eq!(reader.read_unary1().unwrap(), *value);
+            }
+
+            // the run fits the limit but its stop bit doesn't
+            let mut limited =
+                LimitedBitWriter::new(BitWriter::endian(Vec::new(), $endianness), 100);
+            assert!(limited.write_unary0_u64(100).is_err());
+            assert_eq!(limited.written(), 100);
+            let err = limited.write_unary1_u64(0).unwrap_err();
+            assert!(bitstream_io::BitLimitError::from_io(&err).is_some());
+        }
+    };
+}
+
+define_unary_u64_roundtrip!(test_unary_u64_roundtrip_be, BigEndian);
+define_unary_u64_roundtrip!(test_unary_u64_roundtrip_le, LittleEndian);
+
+macro_rules! define_unary_capped_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let maximums: Vec<u32> = vec![0, 1, 7, 8, 9, 31, 32, 33, 100];
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for max in maximums.iter() {
+                for value in 0..=*max {
+                    writer.write_unary0_capped(*max, value).unwrap();
+                    writer.write_unary1_capped(*max, value).unwrap();
+                }
+                assert!(writer.write_unary0_capped(*max, *max + 1).is_err());
+                assert!(writer.write_unary1_capped(*max, *max + 1).is_err());
+            }
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            // the bit-by-bit reading must agree with the optimized one
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            let mut bits = BitReader::endian(Cursor::new(&data), $endianness);
+            for max in maximums.iter() {
+                for value in 0..=*max {
+                    assert_eq!(reader.read_unary0_capped(*max).unwrap(), value);
+                    assert_eq!(reader.read_unary1_capped(*max).unwrap(), value);
+
+                    let mut unary = 0;
+                    while unary < *max && bits.read_bit().unwrap() {
+                        unary += 1;
+                    }
+                    assert_eq!(unary, value);
+                    let mut unary = 0;
+                    while unary < *max && !bits.read_bit().unwrap() {
+                        unary += 1;
+                    }
+                    assert_eq!(unary, value);
+                }
+            }
+        }
+    };
+}
+
+define_unary_capped_roundtrip!(test_unary_capped_roundtrip_be, BigEndian);
+define_unary_capped_roundtrip!(test_unary_capped_roundtrip_le, LittleEndian);
+
+macro_rules! define_wide_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            let hash: [u8; 20] = core::array::from_fn(|i| (i as u8).wrapping_mul(0x9D) ^ 0x5A);
+            let id: [u8; 32] = core::array::from_fn(|i| (i as u8).wrapping_mul(0x3B) ^ 0xC6);
+
+            for offset in 0..8 {
+                for extra in 0..8u64 {
+                    let mut tail = id.to_vec();
+                    if extra > 0 {
+                        tail.push((1 << extra) - 1);
+                    }
+                    let tail_bits = 256 + extra;
+
+                    let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                    writer.write(offset, 0u8).unwrap();
+                    writer.write_bytes(&hash).unwrap();
+                    writer.write_bits_from_bytes(tail_bits, &tail).unwrap();
+                    writer.write_bit(true).unwrap();
+                    writer.byte_align().unwrap();
+                    let data = writer.into_writer();
+
+                    let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                    assert_eq!(reader.read::<u8>(offset).unwrap(), 0);
+                    assert_eq!(reader.read_to_bytes::<20>().unwrap(), hash);
+                    assert_eq!(reader.read_bits_to_vec(tail_bits).unwrap(), tail);
+                    assert!(reader.read_bit().unwrap());
+                }
+            }
+        }
+    };
+}
+
+define_wide_roundtrip!(test_wide_roundtrip_be, BigEndian);
+define_wide_roundtrip!(test_wide_roundtrip_le, LittleEndian);
+
+macro_rules! define_u128_roundtrip {
+    ($func_name:ident, $endianness:ident, $msb_first:expr) => {
+        #[test]
+        fn $func_name() {
+            let value: u128 = 0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210;
+            let signed: i128 = -0x0123_4567_89AB_CDEF_0011_2233_4455_6677;
+
+            for offset in 0..8 {
+                for bits in [1, 63, 64, 65, 100, 127, 128] {
+                    let unsigned = if bits == 128 {
+                        value
+                    } else {
+                        value & ((1 << bits) - 1)
+                    };
+                    let negative = signed >> (128 - bits);
+
+                    let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                    writer.write(offset, 0u8).unwrap();
+                    writer.write(bits, unsigned).unwrap();
+                    writer.write_signed(bits, negative).unwrap();
+                    writer.write(128, u128::MAX).unwrap();
+                    writer.byte_align().unwrap();
+                    let data = writer.into_writer();
+
+                    // compare against writing every bit individually
+                    let mut expected = BitWriter::endian(Vec::new(), $endianness);
+                    expected.write(offset, 0u8).unwrap();
+                    for field in [unsigned, negative as u128] {
+                        for i in 0..bits {
+                            let shift = if $msb_first { bits - 1 - i } else { i };
+                            expected.write_bit((field >> shift) & 1 == 1).unwrap();
+                        }
+                    }
+                    for _ in 0..128 {
+                        expected.write_bit(true).unwrap();
+                    }
+                    expected.byte_align().unwrap();
+                    assert_eq!(data, expected.into_writer());
+
+                    let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                    assert_eq!(reader.read::<u8>(offset).unwrap(), 0);
+                    assert_eq!(reader.read::<u128>(bits).unwrap(), unsigned);
+                    assert_eq!(reader.read_signed::<i128>(bits).unwrap(), negative);
+                    assert_eq!(reader.read::<u128>(128).unwrap(), u128::MAX);
+                }
+            }
+        }
+    };
+}
+
+define_u128_roundtrip!(test_u128_roundtrip_be, BigEndian, true);
+define_u128_roundtrip!(test_u128_roundtrip_le, LittleEndian, false);
+
+macro_rules! define_bcd_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::BcdOrder;
+            use std::io::ErrorKind;
+
+            let orders = [
+                BcdOrder::MostSignificantFirst,
+                BcdOrder::LeastSignificantFirst,
+            ];
+            let mut fields = Vec::new();
+            for digits in 0..=19u32 {
+                let limit = 10u64.pow(digits);
+                for value in [0, 1, 7, limit / 3, limit - 1] {
+                    if value < limit {
+                        fields.push((digits, value));
+                    }
+                }
+            }
+
+            for order in orders {
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                for (digits, value) in fields.iter() {
+                    writer.write_bcd_with(order, *digits, *value).unwrap();
+                    let limit = 10u64.checked_pow(*digits).unwrap_or(u64::MAX);
+                    assert!(writer.write_bcd_with(order, *digits, limit).is_err());
+                }
+                assert!(writer.write_bcd_with(order, 20, 0).is_err());
+                writer.byte_align().unwrap();
+                let data = writer.into_writer();
+
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                for (digits, value) in fields.iter() {
+                    assert_eq!(reader.read_bcd_with(order, *digits).unwrap(), *value);
+                }
+                assert_eq!(
+                    reader.read_bcd_with(order, 20).unwrap_err().kind(),
+                    ErrorKind::InvalidInput
+                );
+            }
+
+            // an SMPTE-style timecode of 2-digit hours, minutes, seconds and frames
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for field in [23, 59, 58, 29] {
+                writer.write_bcd(2, field).unwrap();
+            }
+            let data = writer.into_writer();
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for field in [23, 59, 58, 29] {
+                assert_eq!(reader.read_bcd(2).unwrap(), field);
+            }
+
+            // nibbles above 9 are rejected
+            let data = [0x9A];
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(
+                reader.read_bcd(2).unwrap_err().kind(),
+                ErrorKind::InvalidData
+            );
+        }
+    };
+}
+
+define_bcd_roundtrip!(test_bcd_roundtrip_be, BigEndian);
+define_bcd_roundtrip!(test_bcd_roundtrip_le, LittleEndian);
+
+macro_rules! define_split_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::split::{Segment, SplitField};
+            use std::io::ErrorKind;
+
+            const PTS: SplitField = SplitField::new(&[
+                Segment::Marker {
+                    bits: 4,
+                    value: 0b0011,
+                },
+                Segment::Value(3),
+                Segment::Marker { bits: 1, value: 1 },
+                Segment::Value(15),
+                Segment::Marker { bits: 1, value: 1 },
+                Segment::Value(15),
+                Segment::Marker { bits: 1, value: 1 },
+            ]);
+            const WIDE: SplitField = SplitField::new(&[
+                Segment::Value(0),
+                Segment::Value(64),
+                Segment::Marker {
+                    bits: 64,
+                    value: u64::MAX,
+                },
+            ]);
+            const MARKERS: SplitField = SplitField::new(&[Segment::Marker {
+                bits: 7,
+                value: 0b1010101,
+            }]);
+
+            let pts_values = [0, 1, 0x1_0000_0000, 0x1_FFFF_FFFF, 0x0_8000_7FFF];
+            let wide_values = [0, 1, u64::MAX, 0x0123_4567_89AB_CDEF];
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for value in pts_values.iter() {
+                writer.write_split(&PTS, *value).unwrap();
+                writer.write_bit(false).unwrap();
+            }
+            assert!(writer.write_split(&PTS, 1 << 33).is_err());
+            for value in wide_values.iter() {
+                writer.write_split(&WIDE, *value).unwrap();
+            }
+            writer.write_split(&MARKERS, 0).unwrap();
+            assert!(writer.write_split(&MARKERS, 1).is_err());
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for value in pts_values.iter() {
+                assert_eq!(reader.read_split(&PTS).unwrap(), *value);
+                assert!(!reader.read_bit().unwrap());
+            }
+            for value in wide_values.iter() {
+                assert_eq!(reader.read_split(&WIDE).unwrap(), *value);
+            }
+            assert_eq!(reader.read_split(&MARKERS).unwrap(), 0);
+
+            // the same layout with different markers fails to read
+            const DTS: SplitField = SplitField::new(&[
+                Segment::Marker {
+                    bits: 4,
+                    value: 0b0001,
+                },
+                Segment::Value(3),
+                Segment::Marker { bits: 1, value: 1 },
+                Segment::Value(15),
+                Segment::Marker { bits: 1, value: 1 },
+                Segment::Value(15),
+                Segment::Marker { bits: 1, value: 1 },
+            ]);
+            assert_eq!(PTS.total_bits(), DTS.total_bits());
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            assert_eq!(
+                reader.read_split(&DTS).unwrap_err().kind(),
+                ErrorKind::InvalidData
+            );
+        }
+    };
+}
+
+define_split_roundtrip!(test_split_roundtrip_be, BigEndian);
+define_split_roundtrip!(test_split_roundtrip_le, LittleEndian);
+
+macro_rules! define_schema_roundtrip {
+    ($func_name:ident, $endianness:ident) => {
+        #[test]
+        fn $func_name() {
+            use bitstream_io::schema::{FieldKind, FieldValue, Schema};
+
+            let mut schema = Schema::new();
+            schema.push("sync", FieldKind::Unsigned(12));
+            schema.push("flag", FieldKind::Bit);
+            schema.push("delta", FieldKind::Signed(7));
+            schema.push("wide", FieldKind::Unsigned(64));
+            schema.push("name", FieldKind::Bytes(3));
+            schema.push("run0", FieldKind::Unary0);
+            schema.push("run1", FieldKind::Unary1);
+            schema.push("frames", FieldKind::Bcd(2));
+            schema.push("empty", FieldKind::Bytes(0));
+
+            let records = vec![
+                vec![
+                    FieldValue::Unsigned(0xFFF),
+                    FieldValue::Bit(true),
+                    FieldValue::Signed(-64),
+                    FieldValue::Unsigned(u64::MAX),
+                    FieldValue::Bytes(b"abc".to_vec()),
+                    FieldValue::Unsigned(0),
+                    FieldValue::Unsigned(70),
+                    FieldValue::Unsigned(29),
+                    FieldValue::Bytes(Vec::new()),
+                ],
+                vec![
+                    FieldValue::Unsigned(0),
+                    FieldValue::Bit(false),
+                    FieldValue::Signed(63),
+                    FieldValue::Unsigned(0x0123_4567_89AB_CDEF),
+                    FieldValue::Bytes(vec![0, 0xFF, 0x80]),
+                    FieldValue::Unsigned(9),
+                    FieldValue::Unsigned(0),
+                    FieldValue::Unsigned(0),
+                    FieldValue::Bytes(Vec::new()),
+                ],
+            ];
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            for record in records.iter() {
+                writer.write_record(&schema, record).unwrap();
+            }
+            // mismatched values are rejected
+            let mut wrong = records[0].clone();
+            wrong[4] = FieldValue::Bytes(b"abcd".to_vec());
+            assert!(writer.write_record(&schema, &wrong).is_err());
+            wrong[4] = FieldValue::Signed(0);
+            assert!(writer.write_record(&schema, &wrong).is_err());
+            assert!(writer.write_record(&schema, &records[0][1..]).is_err());
+            writer.byte_align().unwrap();
+            let data = writer.into_writer();
+
+            let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+            for record in records.iter() {
+                let read = reader.read_record(&schema).unwrap();
+                assert_eq!(read.values(), &record[..]);
+                assert_eq!(read.get("frames"), Some(&record[7]));
+                assert_eq!(read.get("missing"), None);
+                let names: Vec<&str> = read.iter().map(|(name, _)| name).collect();
+                assert_eq!(names.len(), schema.len());
+                assert_eq!(names[0], "sync");
+            }
+            // nothing from the mismatched records was written
+            assert!(reader.read::<u8>(8).is_err());
+        }
+    };
+}
+
+define_schema_roundtrip!(test_schema_roundtrip_be, BigEndian);
+define_schema_roundtrip!(test_schema_roundtrip_le, LittleEndian);
+
+macro_rules! define_reversed_roundtrip {
+    ($func_name:ident, $endianness:ident, $msb_first:expr) => {
+        #[test]
+        fn $func_name() {
+            let value: u128 = 0xF0E1_D2C3_B4A5_9687_7869_5A4B_3C2D_1E0F;
+
+            for bits in 0..=128u32 {
+                let field = if bits == 128 {
+                    value
+                } else {
+                    value & ((1 << bits) - 1)
+                };
+
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                writer.write_bit(true).unwrap();
+                writer.write_reversed(bits, field).unwrap();
+                if bits <= 64 {
+                    writer.write_reversed(bits, field as u64).unwrap();
+                }
+                if bits <= 8 {
+                    writer.write_reversed(bits, field as u8).unwrap();
+                }
+                writer.byte_align().unwrap();
+                let data = writer.into_writer();
+
+                // reversed fields are written in the opposite bit order
+                let mut expected = BitWriter::endian(Vec::new(), $endianness);
+                expected.write_bit(true).unwrap();
+                let copies = 1 + u32::from(bits <= 64) + u32::from(bits <= 8);
+                for _ in 0..copies {
+                    for i in 0..bits {
+                        let shift = if $msb_first { i } else { bits - 1 - i };
+                        expected.write_bit((field >> shift) & 1 == 1).unwrap();
+                    }
+                }
+                expected.byte_align().unwrap();
+                assert_eq!(data, expected.into_writer());
+
+                let mut reader = BitReader::endian(Cursor::new(&data), $endianness);
+                assert!(reader.read_bit().unwrap());
+                assert_eq!(reader.read_reversed::<u128>(bits).unwrap(), field);
+                if bits <= 64 {
+                    assert_eq!(reader.read_reversed::<u64>(bits).unwrap(), field as u64);
+                }
+                if bits <= 8 {
+                    assert_eq!(reader.read_reversed::<u8>(bits).unwrap(), field as u8);
+                }
+            }
+
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            assert!(writer.write_reversed(9, 0u8).is_err());
+            assert!(writer.write_reversed(4, 16u8).is_err());
+        }
+    };
+}
+
+define_reversed_roundtrip!(test_reversed_roundtrip_be, BigEndian, true);
+define_reversed_roundtrip!(test_reversed_roundtrip_le, LittleEndian, false);
@@ -1193,6 +1193,61 @@ fn test_counter_huffman_be() {
     assert_eq!(w.written(), 32);
 }
 
+#[test]
+fn test_counter_overflow() {
+    use bitstream_io::{BigEndian, BitCounter, BitWrite, CounterOverflowError};
+    use std::io::ErrorKind;
+
+    let mut w: BitCounter<u16, BigEndian> = BitCounter::new();
+    w.write_bytes(&[0; 8191]).unwrap();
+    w.write(7, 0u8).unwrap();
+    assert_eq!(w.written(), 65535);
+    assert_eq!(w.written_bytes(), 8192);
+    assert!(!w.byte_aligned());
+
+    /*writes that overflow leave the count unchanged*/
+    let err = w.write_bytes(b"\x00").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+    assert_eq!(CounterOverflowError::from_io(&err).unwrap().requested(), 8);
+    let err = w.write_unary0(u32::MAX).unwrap_err();
+    assert_eq!(
+        CounterOverflowError::from_io(&err).unwrap().requested(),
+        1 << 32
+    );
+    assert!(CounterOverflowError::from_io(&w.write(2, 0u8).unwrap_err()).is_some());
+    assert!(w.write_bit(false).is_err());
+    assert_eq!(w.written(), 65535);
+
+    /*invalid writes are still rejected as such*/
+    let err = w.write(9, 0u8).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(CounterOverflowError::from_io(&err).is_none());
+
+    /*unary values past u32::MAX bits are counted in full*/
+    let mut w: BitCounter<u64, BigEndian> = BitCounter::new();
+    w.write_unary1(u32::MAX).unwrap();
+    assert_eq!(w.written(), 1 << 32);
+    assert_eq!(w.written_bytes(), 1 << 29);
+
+    /*signed and floating-point counters remain available*/
+    let mut w: BitCounter<i64, BigEndian> = BitCounter::new();
+    w.write(3, 0b101u8).unwrap();
+    assert_eq!((w.written(), w.written_bytes()), (3, 1));
+    let mut w: BitCounter<i128, BigEndian> = BitCounter::new();
+    w.write_bytes(b"abc").unwrap();
+    assert_eq!((w.written(), w.written_bytes()), (24, 3));
+    assert!(w.byte_aligned());
+    let mut w: BitCounter<f64, BigEndian> = BitCounter::new();
+    w.write_bit(true).unwrap();
+    w.write_bytes(b"ab").unwrap();
+    assert_eq!((w.written(), w.written_bytes()), (17.0, 3.0));
+    assert!(!w.byte_aligned());
+    w.write_run(false, (1 << 53) - 17).unwrap();
+    assert!(w.byte_aligned());
+    assert!(CounterOverflowError::from_io(&w.write_bit(false).unwrap_err()).is_some());
+    assert_eq!(w.written(), (1u64 << 53) as f64);
+}
+
 #[test]
 fn test_counter_le() {
     use bitstream_io::{BitCounter, BitWrite, LittleEndian};
@@ -1697,3 +1752,818 @@ fn test_recorder_huffman_le() {
     w.playback(&mut w2).unwrap();
     assert_eq!(w2.into_writer().as_slice(), &final_data);
 }
+
+#[test]
+fn test_recorder_unaligned_playback() {
+    use bitstream_io::{BigEndian, BitRecorder, BitWrite, BitWriter, LittleEndian};
+
+    /*playback onto a partially-written writer matches writing directly*/
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut recorder: BitRecorder<u64, $endianness> = BitRecorder::with_byte_capacity(16);
+            let mut direct = BitWriter::endian(Vec::new(), $endianness);
+            direct.write(3, 0b101u8).unwrap();
+            for i in 0..37u32 {
+                recorder.write_bit(i % 3 == 0).unwrap();
+                direct.write_bit(i % 3 == 0).unwrap();
+            }
+            recorder.write_signed(11, -300i16).unwrap();
+            direct.write_signed(11, -300i16).unwrap();
+            recorder.write_bytes(b"abc").unwrap();
+            direct.write_bytes(b"abc").unwrap();
+            recorder.write_unary1(5).unwrap();
+            direct.write_unary1(5).unwrap();
+            assert_eq!(recorder.written(), 37 + 11 + 24 + 6);
+            assert_eq!(recorder.written_bytes(), 10);
+            direct.byte_align().unwrap();
+
+            let mut played = BitWriter::endian(Vec::new(), $endianness);
+            played.write(3, 0b101u8).unwrap();
+            recorder.playback(&mut played).unwrap();
+            played.byte_align().unwrap();
+            assert_eq!(played.into_writer(), direct.into_writer());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_writer_clone() {
+    use bitstream_io::huffman::compile_write_tree;
+    use bitstream_io::{BigEndian, BitCounter, BitRecorder, BitWrite, BitWriter, HuffmanWrite};
+
+    /*a cloned recorder can try an alternative encoding independently*/
+    let mut recorder: BitRecorder<u32, BigEndian> = BitRecorder::default();
+    recorder.write(4, 0b1010u8).unwrap();
+    let mut fork = recorder.clone();
+    recorder.write_unary0(12).unwrap();
+    fork.write(8, 12u8).unwrap();
+    assert_eq!(recorder.written(), 17);
+    assert_eq!(fork.written(), 12);
+    let smaller = if fork.written() < recorder.written() {
+        fork
+    } else {
+        recorder
+    };
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    smaller.playback(&mut writer).unwrap();
+    let writer2 = writer.clone();
+    writer.write(4, 0u8).unwrap();
+    assert_eq!(writer.into_writer(), [0xA0, 0xC0]);
+    assert!(format!("{:?}", writer2).starts_with("BitWriter"));
+
+    let tree = compile_write_tree(vec![(0, vec![1, 1]), (1, vec![1, 0]), (2, vec![0])]).unwrap();
+    let tree2 = tree.clone();
+    let mut counter: BitCounter<u32, BigEndian> = BitCounter::default();
+    counter.write_huffman(&tree2, 1).unwrap();
+    assert_eq!(counter.clone().written(), 2);
+    assert_eq!(format!("{:?}", counter), "BitCounter { bits: 2 }");
+}
+
+#[test]
+fn test_tee_writer() {
+    use bitstream_io::huffman::compile_write_tree;
+    use bitstream_io::{
+        BigEndian, BitCounter, BitRecorder, BitWrite, BitWriter, HuffmanWrite, LittleEndian,
+        TeeBitWriter,
+    };
+
+    let final_data: [u8; 4] = [0xB1, 0xED, 0x3B, 0xC1];
+
+    /*writing to a writer and a recorder together*/
+    let mut w = TeeBitWriter::new(
+        BitWriter::endian(Vec::with_capacity(4), BigEndian),
+        BitRecorder::<u32, BigEndian>::new(),
+    );
+    w.write(2, 2u32).unwrap();
+    w.write_signed(3, -2).unwrap();
+    assert!(!w.byte_aligned());
+    w.write_unary1(2).unwrap();
+    w.write_unary0(3).unwrap();
+    w.write(4, 0b1101u8).unwrap();
+    assert!(w.byte_aligned());
+    w.write_bytes(&final_data[2..]).unwrap();
+    let (writer, recorder) = w.into_writers();
+    assert_eq!(writer.into_writer().as_slice(), &final_data);
+    assert_eq!(recorder.written(), 32);
+    let mut w2 = BitWriter::endian(Vec::with_capacity(4), BigEndian);
+    recorder.playback(&mut w2).unwrap();
+    assert_eq!(w2.into_writer().as_slice(), &final_data);
+
+    /*Huffman codes are written to both writers*/
+    let tree = compile_write_tree(vec![(0, vec![1, 1]), (1, vec![1, 0]), (2, vec![0])]).unwrap();
+    let mut w = TeeBitWriter::new(
+        BitWriter::endian(Vec::new(), LittleEndian),
+        BitCounter::<u32, LittleEndian>::new(),
+    );
+    w.write_huffman(&tree, 1).unwrap();
+    w.write_huffman(&tree, 0).unwrap();
+    w.write_huffman(&tree, 2).unwrap();
+    w.byte_align().unwrap();
+    assert_eq!(w.second().written(), 8);
+    let (writer, _) = w.into_writers();
+    assert_eq!(writer.into_writer(), [0b0000_1101]);
+}
+
+#[test]
+fn test_limited_writer() {
+    use bitstream_io::huffman::compile_write_tree;
+    use bitstream_io::{
+        BigEndian, BitLimitError, BitWrite, BitWriter, HuffmanWrite, LimitedBitWriter,
+    };
+    use std::io::ErrorKind;
+
+    let tree = compile_write_tree(vec![(0, vec![1, 1]), (1, vec![1, 0]), (2, vec![0])]).unwrap();
+    let mut w = LimitedBitWriter::new(BitWriter::endian(Vec::new(), BigEndian), 24);
+    w.write_unary0(3).unwrap();
+    w.write_huffman(&tree, 1).unwrap();
+    w.write_signed(2, -1).unwrap();
+    assert_eq!(w.written(), 8);
+    let err = w.write_bytes(b"abc").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+    let limit = BitLimitError::from_io(&err).unwrap();
+    assert_eq!((limit.requested(), limit.remaining()), (24, 16));
+    assert_eq!(w.written(), 8);
+    w.write_bytes(b"a").unwrap();
+    assert!(BitLimitError::from_io(&w.write_unary1(8).unwrap_err()).is_some());
+    w.write_unary1(6).unwrap();
+    assert!(BitLimitError::from_io(&w.write_huffman(&tree, 0).unwrap_err()).is_some());
+    w.write_huffman(&tree, 2).unwrap();
+    assert_eq!(w.remaining(), 0);
+    assert!(w.byte_aligned());
+
+    /*errors from the underlying writer don't count against the limit*/
+    assert_eq!(w.write(0, 1u8).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(w.written(), 24);
+
+    /*a full sink isn't mistaken for an exceeded budget*/
+    let mut sink = [0; 1];
+    let mut full = LimitedBitWriter::new(BitWriter::endian(&mut sink[..], BigEndian), 16);
+    let err = full.write(16, 0xFFFFu16).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+    assert!(BitLimitError::from_io(&err).is_none());
+    assert_eq!(
+        w.into_writer().into_writer(),
+        [0b1110_1011, b'a', 0b0000_0010]
+    );
+}
+
+#[test]
+fn test_write_checked() {
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, LittleEndian};
+    use std::io::ErrorKind;
+
+    // successful checked writes match regular writes
+    let mut checked = BitWriter::endian(Vec::new(), LittleEndian);
+    let mut regular = BitWriter::endian(Vec::new(), LittleEndian);
+    for bits in 1..=64 {
+        let value = 0x0123_4567_89AB_CDEFu64 >> (64 - bits);
+        checked.write_checked(bits, value).unwrap();
+        regular.write(bits, value).unwrap();
+    }
+    checked.byte_align().unwrap();
+    regular.byte_align().unwrap();
+    assert_eq!(checked.into_writer(), regular.into_writer());
+
+    // invalid values commit nothing
+    let mut w = BitWriter::endian(Vec::new(), BigEndian);
+    let err = w.write_checked(9, 0u8).unwrap_err();
+    assert_eq!(err.committed(), 0);
+    assert_eq!(err.error().kind(), ErrorKind::InvalidInput);
+    let err = w.write_checked(4, 16u8).unwrap_err();
+    assert_eq!(err.committed(), 0);
+
+    // failing on the byte which completes a partial byte commits nothing
+    let mut data = [0; 1];
+    let mut w = BitWriter::endian(&mut data[..], LittleEndian);
+    w.write(8, 0xFFu8).unwrap();
+    w.write(3, 0b101u8).unwrap();
+    let err = w.write_checked(32, 0xFFFF_FFFFu32).unwrap_err();
+    assert_eq!(err.committed(), 0);
+    assert!(w.byte_aligned());
+
+    // failing after whole bytes commits the bits in those bytes
+    let mut data = [0; 3];
+    let mut w = BitWriter::endian(&mut data[..], LittleEndian);
+    w.write(3, 0b101u8).unwrap();
+    let err = w.write_checked(30, 0x3FFF_FFFFu32).unwrap_err();
+    assert_eq!(err.committed(), 5 + 16);
+    assert_eq!(std::io::Error::from(err).kind(), ErrorKind::WriteZero);
+    assert_eq!(data, [0b1111_1101, 0xFF, 0xFF]);
+}
+
+#[test]
+fn test_slice_writer() {
+    use bitstream_io::{
+        BigEndian, BitLimitError, BitSliceWriter, BitWrite, BitWriter, Endianness, LittleEndian,
+    };
+    use std::io::ErrorKind;
+
+    // a slice writer writes the same bytes as a stream writer
+    fn check<E: Endianness>(endian: E) {
+        let mut buf = [0; 128];
+        let mut slice = BitSliceWriter::endian(&mut buf, endian);
+        let mut stream = BitWriter::<_, E>::new(Vec::new());
+        for i in 0..20u32 {
+            slice.write(i % 33, (1u32 << (i % 32)) - 1).unwrap();
+            stream.write(i % 33, (1u32 << (i % 32)) - 1).unwrap();
+            slice.write_signed(7, -(i as i8)).unwrap();
+            stream.write_signed(7, -(i as i8)).unwrap();
+            slice.write_bit(i % 3 == 0).unwrap();
+            stream.write_bit(i % 3 == 0).unwrap();
+            slice.write_bytes(&[i as u8, 0xA5]).unwrap();
+            stream.write_bytes(&[i as u8, 0xA5]).unwrap();
+        }
+        let written = slice.written_bits();
+        slice.byte_align().unwrap();
+        stream.byte_align().unwrap();
+        let expected = stream.into_writer();
+        assert_eq!(
+            written,
+            20 * 16 + 20 * 7 + 20 + (0..20).map(|i| i % 33).sum::<u64>()
+        );
+        assert_eq!(slice.written(), expected.as_slice());
+
+        // failed writes leave the slice unchanged
+        let mut full = [0; 2];
+        let mut slice = BitSliceWriter::<E>::new(&mut full);
+        slice.write(13, 0x1FFFu16).unwrap();
+        assert!(BitLimitError::from_io(&slice.write(4, 0u8).unwrap_err()).is_some());
+        assert!(BitLimitError::from_io(&slice.write_signed(4, -1i8).unwrap_err()).is_some());
+        assert!(BitLimitError::from_io(&slice.write_bytes(&[0]).unwrap_err()).is_some());
+        assert!(slice.write(9, 0u8).unwrap_err().kind() == ErrorKind::InvalidInput);
+        slice.write(3, 0u8).unwrap();
+        assert!(slice.write_bit(true).is_err());
+        assert_eq!(slice.remaining_bits(), 0);
+        assert_eq!(slice.written_bits(), 16);
+    }
+    check(BigEndian);
+    check(LittleEndian);
+}
+
+#[test]
+fn test_bit_packer() {
+    use bitstream_io::pack::BitPacker;
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, LittleEndian};
+
+    const BE: [u8; 18] = BitPacker::big_endian()
+        .field(3, 0b101)
+        .field(64, 0x0123_4567_89AB_CDEF)
+        .signed_field(9, -200)
+        .field(0, 0)
+        .signed_field(64, i64::MIN)
+        .field(1, 1)
+        .into_bytes();
+    const LE: [u8; 18] = BitPacker::little_endian()
+        .field(3, 0b101)
+        .field(64, 0x0123_4567_89AB_CDEF)
+        .signed_field(9, -200)
+        .field(0, 0)
+        .signed_field(64, i64::MIN)
+        .field(1, 1)
+        .into_bytes();
+
+    // packed constants match what a writer writes
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    writer.write(3, 0b101u8).unwrap();
+    writer.write(64, 0x0123_4567_89AB_CDEFu64).unwrap();
+    writer.write_signed(9, -200i16).unwrap();
+    writer.write_signed(64, i64::MIN).unwrap();
+    writer.write_bit(true).unwrap();
+    writer.byte_align().unwrap();
+    assert_eq!(writer.into_writer(), BE);
+
+    let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    writer.write(3, 0b101u8).unwrap();
+    writer.write(64, 0x0123_4567_89AB_CDEFu64).unwrap();
+    writer.write_signed(9, -200i16).unwrap();
+    writer.write_signed(64, i64::MIN).unwrap();
+    writer.write_bit(true).unwrap();
+    writer.byte_align().unwrap();
+    assert_eq!(writer.into_writer(), LE);
+
+    let packer = BitPacker::<1>::big_endian();
+    assert!(packer.is_empty());
+    assert_eq!(packer.field(5, 0).len(), 5);
+}
+
+#[test]
+#[should_panic]
+fn test_bit_packer_overflow() {
+    use bitstream_io::pack::BitPacker;
+    BitPacker::<1>::big_endian().field(4, 0).field(5, 0);
+}
+
+#[test]
+fn test_writer_widths() {
+    use bitstream_io::pack::BitPacker;
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, Endianness, LittleEndian};
+
+    // every width at every alignment matches
+    // a packer which places one bit at a time
+    fn check<E: Endianness>(endian: E, packer: fn() -> BitPacker<40>) {
+        let mut writer = BitWriter::endian(Vec::new(), endian);
+        for offset in 0..8 {
+            for bits in 1..=64u32 {
+                let mask = u64::MAX >> (64 - bits);
+                let value = 0xA5C3_F00F_1234_5678u64 & mask;
+                let signed = -((0x1357_9BDF_2468_ACE0u64 & (mask >> 1)) as i64) - 1;
+
+                writer.write(offset, 0u8).unwrap();
+                writer.write(bits, value).unwrap();
+                writer.write_signed(bits, signed).unwrap();
+                if bits <= 32 {
+                    writer.write(bits, value as u32).unwrap();
+                    writer.write_signed(bits, signed as i32).unwrap();
+                } else {
+                    writer.write(bits, value).unwrap();
+                    writer.write_signed(bits, signed).unwrap();
+                }
+                writer.byte_align().unwrap();
+
+                let packed = packer()
+                    .field(offset, 0)
+                    .field(bits, value)
+                    .signed_field(bits, signed)
+                    .field(bits, value)
+                    .signed_field(bits, signed);
+                let len = (packed.len() >> 3) + usize::from(packed.len() & 7 != 0);
+                let written = writer.writer().unwrap();
+                assert_eq!(written.as_slice(), &packed.into_bytes()[0..len]);
+                written.clear();
+            }
+        }
+    }
+
+    check(BigEndian, BitPacker::big_endian);
+    check(LittleEndian, BitPacker::little_endian);
+}
+
+#[test]
+fn test_buf_writer() {
+    use bitstream_io::huffman::compile_write_tree;
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, BufBitWriter, HuffmanWrite, LittleEndian};
+    use std::io;
+
+    // a stream which records the size of every write
+    struct Chunks(Vec<u8>, Vec<usize>);
+
+    impl io::Write for Chunks {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.extend_from_slice(buf);
+            self.1.push(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            fn write_all<W: BitWrite + HuffmanWrite<$endianness>>(
+                w: &mut W,
+                tree: &bitstream_io::huffman::WriteHuffmanTree<$endianness, u8>,
+            ) {
+                for i in 0..1000u32 {
+                    w.write(i % 17, i & ((1 << (i % 17)) - 1)).unwrap();
+                    w.write_signed(9, (i % 200) as i16 - 100).unwrap();
+                    w.write_huffman(tree, (i % 3) as u8).unwrap();
+                    w.write_unary0(i % 40).unwrap();
+                }
+                w.write_run(true, 1000).unwrap();
+                w.write_bytes(&[0x12; 100]).unwrap();
+                w.byte_align().unwrap();
+                w.write_bytes(&[0x34; 100]).unwrap();
+            }
+
+            let tree =
+                compile_write_tree(vec![(0u8, vec![0]), (1, vec![1, 0]), (2, vec![1, 1])]).unwrap();
+            let mut writer = BitWriter::endian(Vec::new(), $endianness);
+            write_all(&mut writer, &tree);
+            let expected = writer.into_writer();
+
+            let mut writer = BufBitWriter::endian(Chunks(Vec::new(), Vec::new()), $endianness);
+            assert_eq!(writer.capacity(), 8192);
+            write_all(&mut writer, &tree);
+            assert_eq!(writer.buffer().len(), expected.len());
+            let chunks = writer.into_writer().unwrap();
+            assert_eq!(chunks.0, expected);
+            assert_eq!(chunks.1, [expected.len()]);
+
+            let mut writer: BufBitWriter<_, $endianness> =
+                BufBitWriter::with_capacity(64, Chunks(Vec::new(), Vec::new()));
+            write_all(&mut writer, &tree);
+            writer.flush().unwrap();
+            assert!(writer.buffer().is_empty());
+            let chunks = writer.into_writer().unwrap();
+            assert_eq!(chunks.0, expected);
+            // besides the final flush, only a bulk write bypasses the buffer
+            assert!(chunks.1.len() <= expected.len() / 64 + 2);
+        };
+    }
+
+    {
+        check!(BigEndian);
+    }
+    {
+        check!(LittleEndian);
+    }
+}
+
+#[test]
+fn test_bits_display() {
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, Bits};
+
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    writer.write(3, 0b101u8).unwrap();
+    writer.write(14, 0x2AAAu16).unwrap();
+    writer.write(1, 1u8).unwrap();
+    let bits = 18;
+    writer.byte_align().unwrap();
+    let data = writer.into_writer();
+
+    assert_eq!(Bits::new(&data, bits).to_string(), "1011_0101 0101_0101 01");
+    assert_eq!(Bits::new(&data, bits).len(), 18);
+    assert_eq!(
+        format!("{:?}", Bits::all(&data)),
+        "1011_0101 0101_0101 0100_0000"
+    );
+    assert_eq!(Bits::new(&data, 4).to_string(), "1011");
+    assert_eq!(Bits::new(&data, 5).to_string(), "1011_0");
+    assert_eq!(Bits::new(&data, 9).to_string(), "1011_0101 0");
+    assert!(Bits::new(&[], 8).is_empty());
+    assert_eq!(Bits::new(&[], 8).to_string(), "");
+}
+
+#[test]
+fn test_write_reserved() {
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, LittleEndian, ReservedPolicy};
+    use std::io::ErrorKind;
+
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    writer.write_reserved(0, ReservedPolicy::Ones).unwrap();
+    writer.write(1, 1u8).unwrap();
+    writer.write_reserved(70, ReservedPolicy::Ones).unwrap();
+    writer.write_reserved(9, ReservedPolicy::Zeros).unwrap();
+    assert_eq!(
+        writer.into_writer(),
+        [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0x00]
+    );
+
+    let mut writer = BitWriter::endian(Vec::new(), LittleEndian);
+    writer
+        .write_reserved_checked(3, ReservedPolicy::Ones, 0b111u8)
+        .unwrap();
+    writer
+        .write_reserved_checked(5, ReservedPolicy::Zeros, 0u32)
+        .unwrap();
+    writer
+        .write_reserved_checked(8, ReservedPolicy::Ones, -1i8)
+        .unwrap();
+    writer
+        .write_reserved_checked(128, ReservedPolicy::Ones, u128::MAX)
+        .unwrap();
+    for (bits, policy, value) in [
+        (3, ReservedPolicy::Ones, 0b011u8),
+        (3, ReservedPolicy::Ones, 0b1111),
+        (3, ReservedPolicy::Zeros, 0b1000),
+        (8, ReservedPolicy::Ones, 0x7F),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            writer
+                .write_reserved_checked(*bits, *policy, *value)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+    // fields too large for the type are rejected as with any write
+    assert!(writer
+        .write_reserved_checked(9, ReservedPolicy::Ones, u8::MAX)
+        .is_err());
+    let mut expected = vec![0b0000_0111, 0xFF];
+    expected.extend([0xFF; 16].iter());
+    assert_eq!(writer.into_writer(), expected);
+}
+
+#[test]
+fn test_writer_resume_pending() {
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, LittleEndian};
+    use std::io::ErrorKind;
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let fields = [
+                (3, 0b101u32),
+                (13, 0x1ABC),
+                (7, 0x55),
+                (9, 0x1FF),
+                (16, 0xBEEF),
+            ];
+            let mut whole = BitWriter::endian(Vec::new(), $endianness);
+            for (bits, value) in fields.iter() {
+                whole.write(*bits, *value).unwrap();
+            }
+            let whole = whole.into_writer();
+
+            // stopping after any field and resuming
+            // gives the same bytes as writing all at once
+            for split in 0..=fields.len() {
+                let mut writer = BitWriter::endian(Vec::new(), $endianness);
+                for (bits, value) in fields[..split].iter() {
+                    writer.write(*bits, *value).unwrap();
+                }
+                let (bits, value) = (writer.pending_bits(), writer.pending_value());
+                assert!(bits < 8);
+                let data = writer.into_writer();
+                let mut writer =
+                    BitWriter::<_, $endianness>::from_pending(data, bits, value).unwrap();
+                for (bits, value) in fields[split..].iter() {
+                    writer.write(*bits, *value).unwrap();
+                }
+                assert_eq!(writer.pending_bits(), 0);
+                assert_eq!(writer.into_writer(), whole);
+            }
+
+            for (bits, value) in [(8, 0), (7, 0x80), (0, 1)].iter() {
+                assert_eq!(
+                    BitWriter::<_, $endianness>::from_pending(Vec::new(), *bits, *value)
+                        .unwrap_err()
+                        .kind(),
+                    ErrorKind::InvalidInput
+                );
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_callback_writer() {
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, CallbackWriter, LittleEndian};
+    use std::io::{ErrorKind, Write};
+    use std::sync::mpsc;
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let mut expected = BitWriter::endian(Vec::new(), $endianness);
+            let (sender, receiver) = mpsc::channel();
+            let mut writer = BitWriter::endian(
+                CallbackWriter::with_capacity(16, move |chunk: &[u8]| {
+                    sender
+                        .send(chunk.to_vec())
+                        .map_err(|_| std::io::Error::new(ErrorKind::BrokenPipe, "receiver gone"))
+                }),
+                $endianness,
+            );
+            for i in 0..200u32 {
+                writer.write(i % 32 + 1, i % (1 << (i % 32))).unwrap();
+                expected.write(i % 32 + 1, i % (1 << (i % 32))).unwrap();
+            }
+            writer.byte_align().unwrap();
+            expected.byte_align().unwrap();
+            // dropping the callback drops its sender
+            drop(writer.into_writer().into_callback().unwrap());
+
+            let chunks: Vec<Vec<u8>> = receiver.iter().collect();
+            assert!(chunks[0..chunks.len() - 1].iter().all(|c| c.len() >= 16));
+            assert_eq!(chunks.concat(), expected.into_writer());
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+
+    // every write is delivered at once without a capacity
+    let mut chunks = 0;
+    let mut writer = CallbackWriter::with_capacity(0, |chunk: &[u8]| {
+        assert_eq!(chunk, [1, 2]);
+        chunks += 1;
+        Ok(())
+    });
+    writer.write_all(&[1, 2]).unwrap();
+    writer.write_all(&[1, 2]).unwrap();
+    assert!(writer.buffer().is_empty());
+    drop(writer);
+    assert_eq!(chunks, 2);
+
+    // a failed delivery leaves the buffer as it was
+    let mut writer = CallbackWriter::with_capacity(4, |_: &[u8]| {
+        Err(std::io::Error::new(std::io::ErrorKind::Other, "full"))
+    });
+    writer.write_all(&[1, 2, 3]).unwrap();
+    assert!(writer.write(&[4, 5]).is_err());
+    assert_eq!(writer.buffer(), [1, 2, 3]);
+    assert!(writer.flush().is_err());
+    assert_eq!(writer.buffer(), [1, 2, 3]);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_recording_writer() {
+    use bitstream_io::huffman::compile_write_tree;
+    use bitstream_io::testing::{Op, RecordingWriter};
+    use bitstream_io::{BigEndian, BitWrite, BitWriter, Endianness, HuffmanWrite, LittleEndian};
+
+    fn encode<E: Endianness, W: BitWrite + HuffmanWrite<E>>(
+        writer: &mut W,
+        tree: &bitstream_io::huffman::WriteHuffmanTree<E, u8>,
+        last: i64,
+    ) {
+        writer.write_bit(true).unwrap();
+        writer.write(7, 0x55u8).unwrap();
+        writer.write(40, 0xAB_CDEF_0123u64).unwrap();
+        writer.write_signed(9, -200i16).unwrap();
+        writer.write_signed(128, i128::MIN).unwrap();
+        writer.write_unary0(5).unwrap();
+        writer.write_unary1(3).unwrap();
+        writer.write_huffman(tree, 2).unwrap();
+        writer.write_run(false, 20).unwrap();
+        writer.byte_align().unwrap();
+        writer.write_bytes(b"abc").unwrap();
+        writer.write_signed(64, last).unwrap();
+        writer.write_zigzag(6, -3).unwrap();
+        writer.byte_align().unwrap();
+    }
+
+    macro_rules! check {
+        ($endianness:ident) => {
+            let tree = compile_write_tree::<$endianness, u8>(vec![
+                (0, vec![0]),
+                (1, vec![1, 0]),
+                (2, vec![1, 1]),
+            ])
+            .unwrap();
+
+            let mut first = RecordingWriter::new(BitWriter::endian(Vec::new(), $endianness));
+            encode(&mut first, &tree, -1);
+            let mut second = RecordingWriter::new(BitWriter::endian(Vec::new(), $endianness));
+            encode(&mut second, &tree, i64::MIN);
+
+            // replaying reproduces the output exactly
+            let mut replayed = BitWriter::endian(Vec::new(), $endianness);
+            first.log().apply_to_writer(&mut replayed).unwrap();
+            let (first, first_log) = first.into_parts();
+            let first_ops = first_log.ops();
+            let first = first.into_writer();
+            assert_eq!(replayed.into_writer(), first);
+
+            let mut direct = BitWriter::endian(Vec::new(), $endianness);
+            encode(&mut direct, &tree, -1);
+            assert_eq!(direct.into_writer(), first);
+
+            // the logs of two runs show where they diverge
+            let second_log = second.take_log();
+            let second_ops = second_log.ops();
+            assert!(second.log().ops().is_empty());
+            assert_eq!(first_ops.len(), second_ops.len());
+            let diverged = first_ops
+                .iter()
+                .zip(second_ops)
+                .position(|(a, b)| a != b)
+                .unwrap();
+            assert_eq!(
+                (&first_ops[diverged], &second_ops[diverged]),
+                (
+                    &Op::Signed {
+                        bits: 64,
+                        value: -1
+                    },
+                    &Op::Signed {
+                        bits: 64,
+                        value: i64::MIN.into()
+                    }
+                )
+            );
+            assert_eq!(
+                first_ops[4],
+                Op::Signed {
+                    bits: 128,
+                    value: i128::MIN
+                }
+            );
+            assert_eq!(first_ops[7], Op::Unsigned { bits: 2, value: 3 });
+
+            // rejected writes aren't recorded
+            let mut writer = RecordingWriter::new(BitWriter::endian(Vec::new(), $endianness));
+            assert!(writer.write(4, 16u8).is_err());
+            assert!(writer.write_signed(4, 8i8).is_err());
+            assert!(writer.log().ops().is_empty());
+
+            #[cfg(feature = "serde")]
+            {
+                let json = serde_json::to_string(&first_log).unwrap();
+                let log: bitstream_io::testing::OpLog = serde_json::from_str(&json).unwrap();
+                assert_eq!(log, first_log);
+            }
+        };
+    }
+
+    check!(BigEndian);
+    check!(LittleEndian);
+}
+
+#[test]
+fn test_word_writer_retry() {
+    use bitstream_io::word::WordWriter;
+    use std::io::{self, Write};
+
+    /*a stream which rejects every other write*/
+    struct Flaky {
+        data: Vec<u8>,
+        fail: bool,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.fail = !self.fail;
+            if self.fail {
+                Err(io::Error::new(io::ErrorKind::Other, "rejected"))
+            } else {
+                self.data.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut w = WordWriter::<_, u32>::new(Flaky {
+        data: Vec::new(),
+        fail: false,
+    });
+
+    /*a failed word is kept and its final bytes aren't accepted*/
+    assert_eq!(w.write(&[1, 2, 3]).unwrap(), 3);
+    assert!(w.write(&[4, 5]).is_err());
+    assert!(!w.word_aligned());
+    assert_eq!(w.write(&[4, 5]).unwrap(), 1);
+    assert!(w.word_aligned());
+
+    /*padding is kept when writing a partial word fails*/
+    assert_eq!(w.write(&[5, 6]).unwrap(), 2);
+    assert!(w.word_align().is_err());
+    assert!(!w.word_aligned());
+    w.word_align().unwrap();
+    assert!(w.word_aligned());
+
+    assert_eq!(w.into_writer().data, [4, 3, 2, 1, 0, 0, 6, 5]);
+}
+
+#[test]
+fn test_encoding_writer_retry() {
+    use bitstream_io::basen::{Encoding, EncodingWriter};
+    use std::io::{self, Write};
+
+    /*a stream which rejects every other write*/
+    struct Flaky {
+        data: Vec<u8>,
+        fail: bool,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.fail = !self.fail;
+            if self.fail {
+                Err(io::Error::new(io::ErrorKind::Other, "rejected"))
+            } else {
+                self.data.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut w = EncodingWriter::new(
+        Flaky {
+            data: Vec::new(),
+            fail: false,
+        },
+        Encoding::Base64,
+    );
+
+    /*a failed write leaves the partial character untouched*/
+    assert!(w.write(b"fo").is_err());
+    assert_eq!(w.written(), 0);
+    assert_eq!(w.write(b"fo").unwrap(), 2);
+    assert_eq!(w.written(), 2);
+    assert!(w.write(b"obar").is_err());
+    assert_eq!(w.written(), 2);
+    assert_eq!(w.write(b"obar").unwrap(), 4);
+    assert_eq!(w.written(), 8);
+
+    assert_eq!(w.into_writer().data, b"Zm9vYmFy");
+}